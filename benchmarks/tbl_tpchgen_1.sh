@@ -15,5 +15,5 @@ uname -a >> $LOGFILE
 SCALE_FACTORS="1000"
 for sf in $SCALE_FACTORS ; do
     echo "SF=$sf" >> $LOGFILE
-    /usr/bin/time -a -o $LOGFILE tpchgen-cli --num-threads=1 -s $sf --output-dir=out_tpchgen
+    /usr/bin/time -a -o $LOGFILE tpchgen-cli --num-threads=1 -s $sf --output-dir=out_tpchgen --force
 done
@@ -0,0 +1,331 @@
+//! Arrow types of the columns of the generated tables
+
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch, StringArray, StringDictionaryBuilder};
+use arrow::compute::cast;
+use arrow::datatypes::{
+    ArrowDictionaryKeyType, DataType, Field, Int8Type, Int16Type, Schema, SchemaRef, TimeUnit,
+};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use tpchgen::distribution::{Distribution, Distributions};
+
+/// Arrow type of the decimal columns, e.g. prices, balances and discounts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecimalType {
+    /// `Decimal128(15, 2)`, as in the TPC-H specification
+    #[default]
+    Decimal128,
+    /// `Float64`, for engines without decimal support or that are faster
+    /// with floating point values
+    Float64,
+}
+
+impl FromStr for DecimalType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "decimal128" => Ok(Self::Decimal128),
+            "float64" => Ok(Self::Float64),
+            _ => Err(format!(
+                "invalid decimal type '{s}', expected decimal128 or float64"
+            )),
+        }
+    }
+}
+
+impl Display for DecimalType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Decimal128 => write!(f, "decimal128"),
+            Self::Float64 => write!(f, "float64"),
+        }
+    }
+}
+
+/// Arrow type of the date columns, e.g. order and ship dates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateType {
+    /// `Date32`, days since the Unix epoch
+    #[default]
+    Date32,
+    /// `Utf8` strings in `YYYY-MM-DD` format, as in TBL files
+    Utf8,
+    /// `Timestamp(Microsecond)` of midnight of the date, without time zone
+    Timestamp,
+}
+
+impl FromStr for DateType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "date32" => Ok(Self::Date32),
+            "utf8" => Ok(Self::Utf8),
+            "timestamp" => Ok(Self::Timestamp),
+            _ => Err(format!(
+                "invalid date type '{s}', expected date32, utf8 or timestamp"
+            )),
+        }
+    }
+}
+
+impl Display for DateType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Date32 => write!(f, "date32"),
+            Self::Utf8 => write!(f, "utf8"),
+            Self::Timestamp => write!(f, "timestamp"),
+        }
+    }
+}
+
+/// Arrow type of the string columns, e.g. names and comments
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringType {
+    /// `Utf8View`, whose short strings are inlined in their views, as
+    /// preferred by arrow-rs and DataFusion
+    #[default]
+    Utf8View,
+    /// `Utf8`, for consumers without support for string views
+    Utf8,
+}
+
+impl FromStr for StringType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "utf8view" => Ok(Self::Utf8View),
+            "utf8" => Ok(Self::Utf8),
+            _ => Err(format!(
+                "invalid string type '{s}', expected utf8view or utf8"
+            )),
+        }
+    }
+}
+
+impl Display for StringType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Utf8View => write!(f, "utf8view"),
+            Self::Utf8 => write!(f, "utf8"),
+        }
+    }
+}
+
+/// Arrow types of the columns of all tables
+///
+/// By default the columns have the types of the schemas of the `*Arrow`
+/// generators. Each generator converts its batches to other types with
+/// `with_column_types`, keeping the values the same:
+///
+/// ```
+/// # use arrow::array::AsArray;
+/// # use arrow::datatypes::{DataType, Int8Type};
+/// # use tpchgen::generators::OrderGenerator;
+/// # use tpchgen_arrow::{
+/// #     ColumnTypes, DateType, DecimalType, OrderArrow, RecordBatchIterator, StringType,
+/// # };
+/// let types = ColumnTypes::default().with_decimal(DecimalType::Float64);
+/// let mut orders = OrderArrow::new(OrderGenerator::new(0.01, 1, 1)).with_column_types(&types);
+/// let field = orders.schema().field_with_name("o_totalprice").unwrap();
+/// assert_eq!(field.data_type(), &DataType::Float64);
+/// let batch = orders.next().unwrap();
+/// assert_eq!(batch.column(3).data_type(), &DataType::Float64);
+///
+/// // the dates as strings, e.g. `1996-01-02`
+/// let types = ColumnTypes::default().with_date(DateType::Utf8);
+/// let mut orders = OrderArrow::new(OrderGenerator::new(0.01, 1, 1)).with_column_types(&types);
+/// let batch = orders.next().unwrap();
+/// assert_eq!(batch.column(4).as_string::<i32>().value(0), "1996-01-02");
+///
+/// // the strings as `Utf8` rather than `Utf8View`
+/// let types = ColumnTypes::default().with_string(StringType::Utf8);
+/// let mut orders = OrderArrow::new(OrderGenerator::new(0.01, 1, 1)).with_column_types(&types);
+/// let batch = orders.next().unwrap();
+/// assert_eq!(batch.column(6).as_string::<i32>().value(0), "Clerk#000000951");
+///
+/// // the low cardinality columns as dictionaries, e.g. `5-LOW`
+/// let types = ColumnTypes::default().with_dictionary(true);
+/// let mut orders = OrderArrow::new(OrderGenerator::new(0.01, 1, 1)).with_column_types(&types);
+/// let batch = orders.next().unwrap();
+/// let priorities = batch.column(5).as_dictionary::<Int8Type>();
+/// assert_eq!(priorities.values().len(), 5);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColumnTypes {
+    pub decimal: DecimalType,
+    pub date: DateType,
+    pub string: StringType,
+    /// Whether the low cardinality string columns are dictionaries, see
+    /// [`Self::with_dictionary`]
+    pub dictionary: bool,
+}
+
+impl ColumnTypes {
+    /// Set the type of the decimal columns
+    pub fn with_decimal(mut self, decimal: DecimalType) -> Self {
+        self.decimal = decimal;
+        self
+    }
+
+    /// Set the type of the date columns
+    pub fn with_date(mut self, date: DateType) -> Self {
+        self.date = date;
+        self
+    }
+
+    /// Set the type of the string columns
+    pub fn with_string(mut self, string: StringType) -> Self {
+        self.string = string;
+        self
+    }
+
+    /// Set whether the string columns with few distinct values, e.g.
+    /// `l_shipmode`, `o_orderpriority` and `p_brand`, are dictionaries of
+    /// `Utf8` values with `Int8` keys, or `Int16` keys for the 150 values of
+    /// `p_type`
+    ///
+    /// The dictionary of a column holds all its values, so it is the same in
+    /// every batch, as Arrow IPC files require.
+    pub fn with_dictionary(mut self, dictionary: bool) -> Self {
+        self.dictionary = dictionary;
+        self
+    }
+
+    /// Returns `schema` with the columns of these types
+    pub fn schema(&self, schema: &SchemaRef) -> SchemaRef {
+        if self.is_default() {
+            return Arc::clone(schema);
+        }
+        let fields: Vec<_> = schema
+            .fields()
+            .iter()
+            .map(|field| Field::clone(field).with_data_type(self.data_type(field)))
+            .collect();
+        Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+    }
+
+    /// Converts `batch` to `schema`, the [`Self::schema`] of its schema
+    pub fn convert(&self, batch: RecordBatch, schema: &SchemaRef) -> RecordBatch {
+        if self.is_default() {
+            return batch;
+        }
+        let columns = batch
+            .columns()
+            .iter()
+            .zip(schema.fields())
+            .map(|(column, field)| match field.data_type() {
+                data_type if data_type == column.data_type() => Arc::clone(column),
+                DataType::Dictionary(key_type, _) => {
+                    let values = dictionary_values(field.name()).expect("dictionary column");
+                    match key_type.as_ref() {
+                        DataType::Int8 => dictionary_array::<Int8Type>(column, values),
+                        _ => dictionary_array::<Int16Type>(column, values),
+                    }
+                }
+                // safe to unwrap because decimals can always be cast to
+                // floats, dates to strings and timestamps, and string views
+                // to strings
+                _ => cast(column, field.data_type()).unwrap(),
+            })
+            .collect();
+        RecordBatch::try_new(Arc::clone(schema), columns).unwrap()
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the type of `field`
+    fn data_type(&self, field: &Field) -> DataType {
+        let data_type = field.data_type();
+        if let Some(values) = dictionary_values(field.name()).filter(|_| self.dictionary) {
+            let key_type = match values.len() <= i8::MAX as usize {
+                true => DataType::Int8,
+                false => DataType::Int16,
+            };
+            return DataType::Dictionary(Box::new(key_type), Box::new(DataType::Utf8));
+        }
+        match data_type {
+            DataType::Decimal128(_, _) => match self.decimal {
+                DecimalType::Decimal128 => data_type.clone(),
+                DecimalType::Float64 => DataType::Float64,
+            },
+            DataType::Date32 => match self.date {
+                DateType::Date32 => data_type.clone(),
+                DateType::Utf8 => DataType::Utf8,
+                DateType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+            },
+            DataType::Utf8View => match self.string {
+                StringType::Utf8View => data_type.clone(),
+                StringType::Utf8 => DataType::Utf8,
+            },
+            _ => data_type.clone(),
+        }
+    }
+}
+
+/// Returns all the values of the string column `name` if it has few distinct
+/// values, in the order of its dictionary
+fn dictionary_values(name: &str) -> Option<&'static StringArray> {
+    static DICTIONARIES: OnceLock<Vec<(&str, StringArray)>> = OnceLock::new();
+    let dictionaries = DICTIONARIES.get_or_init(|| {
+        let distributions = Distributions::static_default();
+        let distribution =
+            |distribution: &Distribution| StringArray::from_iter_values(distribution.get_values());
+        let numbers = || 1..=5;
+        vec![
+            ("l_returnflag", StringArray::from(vec!["R", "A", "N"])),
+            ("l_linestatus", StringArray::from(vec!["O", "F"])),
+            (
+                "l_shipinstruct",
+                distribution(distributions.ship_instructions()),
+            ),
+            ("l_shipmode", distribution(distributions.ship_modes())),
+            ("o_orderstatus", StringArray::from(vec!["O", "F", "P"])),
+            (
+                "o_orderpriority",
+                distribution(distributions.order_priority()),
+            ),
+            (
+                "p_mfgr",
+                StringArray::from_iter_values(numbers().map(|m| format!("Manufacturer#{m}"))),
+            ),
+            (
+                "p_brand",
+                StringArray::from_iter_values(
+                    numbers()
+                        .flat_map(|m| numbers().map(move |n| format!("Brand#{m}{n}")))
+                        .collect::<Vec<_>>(),
+                ),
+            ),
+            ("p_type", distribution(distributions.part_types())),
+            ("p_container", distribution(distributions.part_containers())),
+            (
+                "c_mktsegment",
+                distribution(distributions.market_segments()),
+            ),
+        ]
+    });
+    dictionaries
+        .iter()
+        .find(|(column, _)| *column == name)
+        .map(|(_, values)| values)
+}
+
+/// Returns the string array `column` as a dictionary array of `values`
+fn dictionary_array<K: ArrowDictionaryKeyType>(
+    column: &ArrayRef,
+    values: &StringArray,
+) -> ArrayRef {
+    // safe to unwrap because string views can always be cast to strings, and
+    // the values are distinct and fit the keys
+    let strings = cast(column, &DataType::Utf8).unwrap();
+    let mut builder =
+        StringDictionaryBuilder::<K>::new_with_dictionary(strings.len(), values).unwrap();
+    builder.extend(strings.as_string::<i32>());
+    Arc::new(builder.finish())
+}
@@ -1,9 +1,9 @@
 use crate::conversions::{decimal128_array_from_iter, string_view_array_from_display_iter};
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
+use crate::{BatchSizes, ColumnTypes, DEFAULT_BATCH_SIZE, RecordBatchIterator};
 use arrow::array::{Int64Array, RecordBatch};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use std::sync::{Arc, LazyLock};
-use tpchgen::generators::{CustomerGenerator, CustomerGeneratorIterator};
+use tpchgen::generators::{Customer, CustomerGenerator, CustomerGeneratorIterator};
 
 /// Generate [`Customer`]s in [`RecordBatch`] format
 ///
@@ -45,6 +45,8 @@ use tpchgen::generators::{CustomerGenerator, CustomerGeneratorIterator};
 pub struct CustomerArrow {
     inner: CustomerGeneratorIterator<'static>,
     batch_size: usize,
+    column_types: ColumnTypes,
+    schema: SchemaRef,
 }
 
 impl CustomerArrow {
@@ -52,6 +54,8 @@ impl CustomerArrow {
         Self {
             inner: generator.iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            column_types: ColumnTypes::default(),
+            schema: Arc::clone(&CUSTOMER_SCHEMA),
         }
     }
 
@@ -60,24 +64,24 @@ impl CustomerArrow {
         self.batch_size = batch_size;
         self
     }
-}
 
-impl RecordBatchIterator for CustomerArrow {
-    fn schema(&self) -> &SchemaRef {
-        &CUSTOMER_SCHEMA
+    /// Set the batch size to the `customer` size of `batch_sizes`
+    pub fn with_batch_sizes(self, batch_sizes: &BatchSizes) -> Self {
+        self.with_batch_size(batch_sizes.customer)
     }
-}
 
-impl Iterator for CustomerArrow {
-    type Item = RecordBatch;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
-        if rows.is_empty() {
-            return None;
-        }
+    /// Set the Arrow types of the columns, see [`ColumnTypes`]
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.column_types = *column_types;
+        self.schema = column_types.schema(&CUSTOMER_SCHEMA);
+        self
+    }
 
+    /// Convert `rows` to a [`RecordBatch`] with this table's schema
+    ///
+    /// This can be used for rows that were not generated by this iterator,
+    /// for example rows parsed with [`tpchgen::parse`].
+    pub fn batch_from_rows(rows: &[Customer<'_>]) -> RecordBatch {
         let c_custkey = Int64Array::from_iter_values(rows.iter().map(|r| r.c_custkey));
         let c_name = string_view_array_from_display_iter(rows.iter().map(|r| r.c_name));
         let c_address = string_view_array_from_display_iter(rows.iter().map(|r| &r.c_address));
@@ -87,8 +91,8 @@ impl Iterator for CustomerArrow {
         let c_mktsegment = string_view_array_from_display_iter(rows.iter().map(|r| r.c_mktsegment));
         let c_comment = string_view_array_from_display_iter(rows.iter().map(|r| r.c_comment));
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
+        RecordBatch::try_new(
+            Arc::clone(&CUSTOMER_SCHEMA),
             vec![
                 Arc::new(c_custkey),
                 Arc::new(c_name),
@@ -100,8 +104,27 @@ impl Iterator for CustomerArrow {
                 Arc::new(c_comment),
             ],
         )
-        .unwrap();
-        Some(batch)
+        .unwrap()
+    }
+}
+
+impl RecordBatchIterator for CustomerArrow {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl Iterator for CustomerArrow {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Get next rows to convert
+        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
+        if rows.is_empty() {
+            return None;
+        }
+        let batch = Self::batch_from_rows(&rows);
+        Some(self.column_types.convert(batch, &self.schema))
     }
 }
 
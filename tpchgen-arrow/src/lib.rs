@@ -34,6 +34,7 @@
 //!   "+------------+-----------+-----------+--------------+------------+-----------------+------------+-------+--------------+--------------+------------+--------------+---------------+-------------------+------------+-------------------------------------+"
 //! ]);
 //! ```
+mod column_types;
 pub mod conversions;
 mod customer;
 mod lineitem;
@@ -46,6 +47,7 @@ mod supplier;
 
 use arrow::array::RecordBatch;
 use arrow::datatypes::SchemaRef;
+pub use column_types::{ColumnTypes, DateType, DecimalType, StringType};
 pub use customer::CustomerArrow;
 pub use lineitem::LineItemArrow;
 pub use nation::NationArrow;
@@ -62,3 +64,85 @@ pub trait RecordBatchIterator: Iterator<Item = RecordBatch> + Send {
 
 /// The default number of rows in each Batch
 pub const DEFAULT_BATCH_SIZE: usize = 8 * 1000;
+
+/// Number of rows in each batch, per table
+///
+/// Use this to size the batches of each table differently rather than using
+/// one batch size for all of them, for example to match the row groups of
+/// Parquet files. Each `*Arrow` generator uses its table's size with
+/// `with_batch_sizes`:
+///
+/// ```
+/// # use tpchgen::generators::{LineItemGenerator, NationGenerator};
+/// # use tpchgen_arrow::{BatchSizes, LineItemArrow, NationArrow};
+/// let sizes = BatchSizes::default()
+///     .with_table("lineitem", 1000)
+///     .unwrap();
+/// let lineitem = LineItemArrow::new(LineItemGenerator::new(0.01, 1, 1)).with_batch_sizes(&sizes);
+/// assert_eq!(lineitem.map(|b| b.num_rows()).max(), Some(1000));
+/// // the 25 nations are in a single batch
+/// let nation = NationArrow::new(NationGenerator::new(1.0, 1, 1)).with_batch_sizes(&sizes);
+/// assert_eq!(nation.count(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchSizes {
+    pub nation: usize,
+    pub region: usize,
+    pub part: usize,
+    pub supplier: usize,
+    pub partsupp: usize,
+    pub customer: usize,
+    pub orders: usize,
+    pub lineitem: usize,
+}
+
+impl BatchSizes {
+    /// The same `batch_size` for every table
+    pub fn new(batch_size: usize) -> Self {
+        Self {
+            nation: batch_size,
+            region: batch_size,
+            part: batch_size,
+            supplier: batch_size,
+            partsupp: batch_size,
+            customer: batch_size,
+            orders: batch_size,
+            lineitem: batch_size,
+        }
+    }
+
+    /// Returns the batch size of the table named `table`, e.g. `lineitem`, or
+    /// `None` if there is no such table
+    pub fn get(&self, table: &str) -> Option<usize> {
+        let mut sizes = *self;
+        sizes.table_mut(table).copied()
+    }
+
+    /// Sets the batch size of the table named `table`, e.g. `lineitem`, or
+    /// returns `None` if there is no such table
+    pub fn with_table(mut self, table: &str, batch_size: usize) -> Option<Self> {
+        *self.table_mut(table)? = batch_size;
+        Some(self)
+    }
+
+    fn table_mut(&mut self, table: &str) -> Option<&mut usize> {
+        match table {
+            "nation" => Some(&mut self.nation),
+            "region" => Some(&mut self.region),
+            "part" => Some(&mut self.part),
+            "supplier" => Some(&mut self.supplier),
+            "partsupp" => Some(&mut self.partsupp),
+            "customer" => Some(&mut self.customer),
+            "orders" => Some(&mut self.orders),
+            "lineitem" => Some(&mut self.lineitem),
+            _ => None,
+        }
+    }
+}
+
+impl Default for BatchSizes {
+    /// [`DEFAULT_BATCH_SIZE`] for every table
+    fn default() -> Self {
+        Self::new(DEFAULT_BATCH_SIZE)
+    }
+}
@@ -1,11 +1,11 @@
 use crate::conversions::{decimal128_array_from_iter, to_arrow_date32};
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
+use crate::{BatchSizes, ColumnTypes, DEFAULT_BATCH_SIZE, RecordBatchIterator};
 use arrow::array::{
     Date32Array, Decimal128Array, Int32Array, Int64Array, RecordBatch, StringViewArray,
 };
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use std::sync::{Arc, LazyLock};
-use tpchgen::generators::{LineItemGenerator, LineItemGeneratorIterator};
+use tpchgen::generators::{LineItem, LineItemGenerator, LineItemGeneratorIterator};
 
 /// Generate  [`LineItem`]s in [`RecordBatch`] format
 ///
@@ -52,6 +52,8 @@ use tpchgen::generators::{LineItemGenerator, LineItemGeneratorIterator};
 pub struct LineItemArrow {
     inner: LineItemGeneratorIterator<'static>,
     batch_size: usize,
+    column_types: ColumnTypes,
+    schema: SchemaRef,
 }
 
 impl LineItemArrow {
@@ -59,6 +61,8 @@ impl LineItemArrow {
         Self {
             inner: generator.iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            column_types: ColumnTypes::default(),
+            schema: Arc::clone(&LINEITEM_SCHEMA),
         }
     }
 
@@ -67,25 +71,24 @@ impl LineItemArrow {
         self.batch_size = batch_size;
         self
     }
-}
 
-impl RecordBatchIterator for LineItemArrow {
-    fn schema(&self) -> &SchemaRef {
-        &LINEITEM_SCHEMA
+    /// Set the batch size to the `lineitem` size of `batch_sizes`
+    pub fn with_batch_sizes(self, batch_sizes: &BatchSizes) -> Self {
+        self.with_batch_size(batch_sizes.lineitem)
     }
-}
 
-impl Iterator for LineItemArrow {
-    type Item = RecordBatch;
-
-    /// Generate the next batch of data, if there is one
-    fn next(&mut self) -> Option<Self::Item> {
-        // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
-        if rows.is_empty() {
-            return None;
-        }
+    /// Set the Arrow types of the columns, see [`ColumnTypes`]
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.column_types = *column_types;
+        self.schema = column_types.schema(&LINEITEM_SCHEMA);
+        self
+    }
 
+    /// Convert `rows` to a [`RecordBatch`] with this table's schema
+    ///
+    /// This can be used for rows that were not generated by this iterator,
+    /// for example rows parsed with [`tpchgen::parse`].
+    pub fn batch_from_rows(rows: &[LineItem<'_>]) -> RecordBatch {
         // Convert column by column
         let l_orderkey = Int64Array::from_iter_values(rows.iter().map(|row| row.l_orderkey));
         let l_partkey = Int64Array::from_iter_values(rows.iter().map(|row| row.l_partkey));
@@ -103,9 +106,9 @@ impl Iterator for LineItemArrow {
         let l_discount = decimal128_array_from_iter(rows.iter().map(|row| row.l_discount));
         let l_tax = decimal128_array_from_iter(rows.iter().map(|row| row.l_tax));
         let l_returnflag =
-            StringViewArray::from_iter_values(rows.iter().map(|row| row.l_returnflag));
+            StringViewArray::from_iter_values(rows.iter().map(|row| row.l_returnflag.as_str()));
         let l_linestatus =
-            StringViewArray::from_iter_values(rows.iter().map(|row| row.l_linestatus));
+            StringViewArray::from_iter_values(rows.iter().map(|row| row.l_linestatus.as_str()));
         let l_shipdate = Date32Array::from_iter_values(
             rows.iter().map(|row| row.l_shipdate).map(to_arrow_date32),
         );
@@ -119,11 +122,12 @@ impl Iterator for LineItemArrow {
         );
         let l_shipinstruct =
             StringViewArray::from_iter_values(rows.iter().map(|row| row.l_shipinstruct));
-        let l_shipmode = StringViewArray::from_iter_values(rows.iter().map(|row| row.l_shipmode));
+        let l_shipmode =
+            StringViewArray::from_iter_values(rows.iter().map(|row| row.l_shipmode.as_str()));
         let l_comment = StringViewArray::from_iter_values(rows.iter().map(|row| row.l_comment));
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
+        RecordBatch::try_new(
+            Arc::clone(&LINEITEM_SCHEMA),
             vec![
                 Arc::new(l_orderkey),
                 Arc::new(l_partkey),
@@ -143,9 +147,28 @@ impl Iterator for LineItemArrow {
                 Arc::new(l_comment),
             ],
         )
-        .unwrap();
+        .unwrap()
+    }
+}
 
-        Some(batch)
+impl RecordBatchIterator for LineItemArrow {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl Iterator for LineItemArrow {
+    type Item = RecordBatch;
+
+    /// Generate the next batch of data, if there is one
+    fn next(&mut self) -> Option<Self::Item> {
+        // Get next rows to convert
+        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
+        if rows.is_empty() {
+            return None;
+        }
+        let batch = Self::batch_from_rows(&rows);
+        Some(self.column_types.convert(batch, &self.schema))
     }
 }
 
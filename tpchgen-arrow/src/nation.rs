@@ -1,8 +1,8 @@
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
+use crate::{BatchSizes, ColumnTypes, DEFAULT_BATCH_SIZE, RecordBatchIterator};
 use arrow::array::{Int64Array, RecordBatch, StringViewArray};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use std::sync::{Arc, LazyLock};
-use tpchgen::generators::{NationGenerator, NationGeneratorIterator};
+use tpchgen::generators::{Nation, NationGenerator, NationGeneratorIterator};
 
 /// Generate  [`Nation`]s in [`RecordBatch`] format
 ///
@@ -44,6 +44,8 @@ use tpchgen::generators::{NationGenerator, NationGeneratorIterator};
 pub struct NationArrow {
     inner: NationGeneratorIterator<'static>,
     batch_size: usize,
+    column_types: ColumnTypes,
+    schema: SchemaRef,
 }
 
 impl NationArrow {
@@ -51,6 +53,8 @@ impl NationArrow {
         Self {
             inner: generator.iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            column_types: ColumnTypes::default(),
+            schema: Arc::clone(&NATION_SCHEMA),
         }
     }
 
@@ -59,31 +63,31 @@ impl NationArrow {
         self.batch_size = batch_size;
         self
     }
-}
 
-impl RecordBatchIterator for NationArrow {
-    fn schema(&self) -> &SchemaRef {
-        &NATION_SCHEMA
+    /// Set the batch size to the `nation` size of `batch_sizes`
+    pub fn with_batch_sizes(self, batch_sizes: &BatchSizes) -> Self {
+        self.with_batch_size(batch_sizes.nation)
     }
-}
 
-impl Iterator for NationArrow {
-    type Item = RecordBatch;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
-        if rows.is_empty() {
-            return None;
-        }
+    /// Set the Arrow types of the columns, see [`ColumnTypes`]
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.column_types = *column_types;
+        self.schema = column_types.schema(&NATION_SCHEMA);
+        self
+    }
 
+    /// Convert `rows` to a [`RecordBatch`] with this table's schema
+    ///
+    /// This can be used for rows that were not generated by this iterator,
+    /// for example rows parsed with [`tpchgen::parse`].
+    pub fn batch_from_rows(rows: &[Nation<'_>]) -> RecordBatch {
         let n_nationkey = Int64Array::from_iter_values(rows.iter().map(|r| r.n_nationkey));
         let n_name = StringViewArray::from_iter_values(rows.iter().map(|r| r.n_name));
         let n_regionkey = Int64Array::from_iter_values(rows.iter().map(|r| r.n_regionkey));
         let n_comment = StringViewArray::from_iter_values(rows.iter().map(|r| r.n_comment));
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
+        RecordBatch::try_new(
+            Arc::clone(&NATION_SCHEMA),
             vec![
                 Arc::new(n_nationkey),
                 Arc::new(n_name),
@@ -91,8 +95,27 @@ impl Iterator for NationArrow {
                 Arc::new(n_comment),
             ],
         )
-        .unwrap();
-        Some(batch)
+        .unwrap()
+    }
+}
+
+impl RecordBatchIterator for NationArrow {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl Iterator for NationArrow {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Get next rows to convert
+        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
+        if rows.is_empty() {
+            return None;
+        }
+        let batch = Self::batch_from_rows(&rows);
+        Some(self.column_types.convert(batch, &self.schema))
     }
 }
 
@@ -1,11 +1,11 @@
 use crate::conversions::{
     decimal128_array_from_iter, string_view_array_from_display_iter, to_arrow_date32,
 };
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
+use crate::{BatchSizes, ColumnTypes, DEFAULT_BATCH_SIZE, RecordBatchIterator};
 use arrow::array::{Date32Array, Int32Array, Int64Array, RecordBatch, StringViewArray};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use std::sync::{Arc, LazyLock};
-use tpchgen::generators::{OrderGenerator, OrderGeneratorIterator};
+use tpchgen::generators::{Order, OrderGenerator, OrderGeneratorIterator};
 
 /// Generate [`Order`]s in [`RecordBatch`] format
 ///
@@ -47,6 +47,8 @@ use tpchgen::generators::{OrderGenerator, OrderGeneratorIterator};
 pub struct OrderArrow {
     inner: OrderGeneratorIterator<'static>,
     batch_size: usize,
+    column_types: ColumnTypes,
+    schema: SchemaRef,
 }
 
 impl OrderArrow {
@@ -54,6 +56,8 @@ impl OrderArrow {
         Self {
             inner: generator.iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            column_types: ColumnTypes::default(),
+            schema: Arc::clone(&ORDER_SCHEMA),
         }
     }
 
@@ -62,24 +66,24 @@ impl OrderArrow {
         self.batch_size = batch_size;
         self
     }
-}
 
-impl RecordBatchIterator for OrderArrow {
-    fn schema(&self) -> &SchemaRef {
-        &ORDER_SCHEMA
+    /// Set the batch size to the `orders` size of `batch_sizes`
+    pub fn with_batch_sizes(self, batch_sizes: &BatchSizes) -> Self {
+        self.with_batch_size(batch_sizes.orders)
     }
-}
 
-impl Iterator for OrderArrow {
-    type Item = RecordBatch;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
-        if rows.is_empty() {
-            return None;
-        }
+    /// Set the Arrow types of the columns, see [`ColumnTypes`]
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.column_types = *column_types;
+        self.schema = column_types.schema(&ORDER_SCHEMA);
+        self
+    }
 
+    /// Convert `rows` to a [`RecordBatch`] with this table's schema
+    ///
+    /// This can be used for rows that were not generated by this iterator,
+    /// for example rows parsed with [`tpchgen::parse`].
+    pub fn batch_from_rows(rows: &[Order<'_>]) -> RecordBatch {
         let o_orderkey = Int64Array::from_iter_values(rows.iter().map(|r| r.o_orderkey));
         let o_custkey = Int64Array::from_iter_values(rows.iter().map(|r| r.o_custkey));
         let o_orderstatus =
@@ -88,13 +92,13 @@ impl Iterator for OrderArrow {
         let o_orderdate =
             Date32Array::from_iter_values(rows.iter().map(|r| r.o_orderdate).map(to_arrow_date32));
         let o_orderpriority =
-            StringViewArray::from_iter_values(rows.iter().map(|r| r.o_orderpriority));
+            StringViewArray::from_iter_values(rows.iter().map(|r| r.o_orderpriority.as_str()));
         let o_clerk = string_view_array_from_display_iter(rows.iter().map(|r| r.o_clerk));
         let o_shippriority = Int32Array::from_iter_values(rows.iter().map(|r| r.o_shippriority));
         let o_comment = StringViewArray::from_iter_values(rows.iter().map(|r| r.o_comment));
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
+        RecordBatch::try_new(
+            Arc::clone(&ORDER_SCHEMA),
             vec![
                 Arc::new(o_orderkey),
                 Arc::new(o_custkey),
@@ -107,8 +111,27 @@ impl Iterator for OrderArrow {
                 Arc::new(o_comment),
             ],
         )
-        .unwrap();
-        Some(batch)
+        .unwrap()
+    }
+}
+
+impl RecordBatchIterator for OrderArrow {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl Iterator for OrderArrow {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Get next rows to convert
+        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
+        if rows.is_empty() {
+            return None;
+        }
+        let batch = Self::batch_from_rows(&rows);
+        Some(self.column_types.convert(batch, &self.schema))
     }
 }
 
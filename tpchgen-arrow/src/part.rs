@@ -1,9 +1,9 @@
 use crate::conversions::{decimal128_array_from_iter, string_view_array_from_display_iter};
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
+use crate::{BatchSizes, ColumnTypes, DEFAULT_BATCH_SIZE, RecordBatchIterator};
 use arrow::array::{Int32Array, Int64Array, RecordBatch, StringViewArray};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use std::sync::{Arc, LazyLock};
-use tpchgen::generators::{PartGenerator, PartGeneratorIterator};
+use tpchgen::generators::{Part, PartGenerator, PartGeneratorIterator};
 
 /// Generate [`Part`]s in [`RecordBatch`] format
 ///
@@ -45,6 +45,8 @@ use tpchgen::generators::{PartGenerator, PartGeneratorIterator};
 pub struct PartArrow {
     inner: PartGeneratorIterator<'static>,
     batch_size: usize,
+    column_types: ColumnTypes,
+    schema: SchemaRef,
 }
 
 impl PartArrow {
@@ -52,6 +54,8 @@ impl PartArrow {
         Self {
             inner: generator.iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            column_types: ColumnTypes::default(),
+            schema: Arc::clone(&PART_SCHEMA),
         }
     }
 
@@ -60,24 +64,24 @@ impl PartArrow {
         self.batch_size = batch_size;
         self
     }
-}
 
-impl RecordBatchIterator for PartArrow {
-    fn schema(&self) -> &SchemaRef {
-        &PART_SCHEMA
+    /// Set the batch size to the `part` size of `batch_sizes`
+    pub fn with_batch_sizes(self, batch_sizes: &BatchSizes) -> Self {
+        self.with_batch_size(batch_sizes.part)
     }
-}
 
-impl Iterator for PartArrow {
-    type Item = RecordBatch;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
-        if rows.is_empty() {
-            return None;
-        }
+    /// Set the Arrow types of the columns, see [`ColumnTypes`]
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.column_types = *column_types;
+        self.schema = column_types.schema(&PART_SCHEMA);
+        self
+    }
 
+    /// Convert `rows` to a [`RecordBatch`] with this table's schema
+    ///
+    /// This can be used for rows that were not generated by this iterator,
+    /// for example rows parsed with [`tpchgen::parse`].
+    pub fn batch_from_rows(rows: &[Part<'_>]) -> RecordBatch {
         let p_partkey = Int64Array::from_iter_values(rows.iter().map(|r| r.p_partkey));
         let p_name = string_view_array_from_display_iter(rows.iter().map(|r| &r.p_name));
         let p_mfgr = string_view_array_from_display_iter(rows.iter().map(|r| r.p_mfgr));
@@ -88,8 +92,8 @@ impl Iterator for PartArrow {
         let p_retailprice = decimal128_array_from_iter(rows.iter().map(|r| r.p_retailprice));
         let p_comment = StringViewArray::from_iter_values(rows.iter().map(|r| r.p_comment));
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
+        RecordBatch::try_new(
+            Arc::clone(&PART_SCHEMA),
             vec![
                 Arc::new(p_partkey),
                 Arc::new(p_name),
@@ -102,8 +106,27 @@ impl Iterator for PartArrow {
                 Arc::new(p_comment),
             ],
         )
-        .unwrap();
-        Some(batch)
+        .unwrap()
+    }
+}
+
+impl RecordBatchIterator for PartArrow {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl Iterator for PartArrow {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Get next rows to convert
+        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
+        if rows.is_empty() {
+            return None;
+        }
+        let batch = Self::batch_from_rows(&rows);
+        Some(self.column_types.convert(batch, &self.schema))
     }
 }
 
@@ -1,9 +1,9 @@
 use crate::conversions::{decimal128_array_from_iter, string_view_array_from_display_iter};
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
+use crate::{BatchSizes, ColumnTypes, DEFAULT_BATCH_SIZE, RecordBatchIterator};
 use arrow::array::{Int32Array, Int64Array, RecordBatch};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use std::sync::{Arc, LazyLock};
-use tpchgen::generators::{PartSuppGenerator, PartSuppGeneratorIterator};
+use tpchgen::generators::{PartSupp, PartSuppGenerator, PartSuppGeneratorIterator};
 
 /// Generate [`PartSupp`]s in [`RecordBatch`] format
 ///
@@ -45,6 +45,8 @@ use tpchgen::generators::{PartSuppGenerator, PartSuppGeneratorIterator};
 pub struct PartSuppArrow {
     inner: PartSuppGeneratorIterator<'static>,
     batch_size: usize,
+    column_types: ColumnTypes,
+    schema: SchemaRef,
 }
 
 impl PartSuppArrow {
@@ -52,6 +54,8 @@ impl PartSuppArrow {
         Self {
             inner: generator.iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            column_types: ColumnTypes::default(),
+            schema: Arc::clone(&PARTSUPP_SCHEMA),
         }
     }
 
@@ -60,32 +64,32 @@ impl PartSuppArrow {
         self.batch_size = batch_size;
         self
     }
-}
 
-impl RecordBatchIterator for PartSuppArrow {
-    fn schema(&self) -> &SchemaRef {
-        &PARTSUPP_SCHEMA
+    /// Set the batch size to the `partsupp` size of `batch_sizes`
+    pub fn with_batch_sizes(self, batch_sizes: &BatchSizes) -> Self {
+        self.with_batch_size(batch_sizes.partsupp)
     }
-}
 
-impl Iterator for PartSuppArrow {
-    type Item = RecordBatch;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
-        if rows.is_empty() {
-            return None;
-        }
+    /// Set the Arrow types of the columns, see [`ColumnTypes`]
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.column_types = *column_types;
+        self.schema = column_types.schema(&PARTSUPP_SCHEMA);
+        self
+    }
 
+    /// Convert `rows` to a [`RecordBatch`] with this table's schema
+    ///
+    /// This can be used for rows that were not generated by this iterator,
+    /// for example rows parsed with [`tpchgen::parse`].
+    pub fn batch_from_rows(rows: &[PartSupp<'_>]) -> RecordBatch {
         let ps_partkey = Int64Array::from_iter_values(rows.iter().map(|r| r.ps_partkey));
         let ps_suppkey = Int64Array::from_iter_values(rows.iter().map(|r| r.ps_suppkey));
         let ps_availqty = Int32Array::from_iter_values(rows.iter().map(|r| r.ps_availqty));
         let ps_supplycost = decimal128_array_from_iter(rows.iter().map(|r| r.ps_supplycost));
         let ps_comment = string_view_array_from_display_iter(rows.iter().map(|r| r.ps_comment));
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
+        RecordBatch::try_new(
+            Arc::clone(&PARTSUPP_SCHEMA),
             vec![
                 Arc::new(ps_partkey),
                 Arc::new(ps_suppkey),
@@ -94,8 +98,27 @@ impl Iterator for PartSuppArrow {
                 Arc::new(ps_comment),
             ],
         )
-        .unwrap();
-        Some(batch)
+        .unwrap()
+    }
+}
+
+impl RecordBatchIterator for PartSuppArrow {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl Iterator for PartSuppArrow {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Get next rows to convert
+        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
+        if rows.is_empty() {
+            return None;
+        }
+        let batch = Self::batch_from_rows(&rows);
+        Some(self.column_types.convert(batch, &self.schema))
     }
 }
 
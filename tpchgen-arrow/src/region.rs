@@ -1,8 +1,8 @@
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
+use crate::{BatchSizes, ColumnTypes, DEFAULT_BATCH_SIZE, RecordBatchIterator};
 use arrow::array::{Int64Array, RecordBatch, StringViewArray};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use std::sync::{Arc, LazyLock};
-use tpchgen::generators::{RegionGenerator, RegionGeneratorIterator};
+use tpchgen::generators::{Region, RegionGenerator, RegionGeneratorIterator};
 
 /// Generate  [`Region`]s in [`RecordBatch`] format
 ///
@@ -39,6 +39,8 @@ use tpchgen::generators::{RegionGenerator, RegionGeneratorIterator};
 pub struct RegionArrow {
     inner: RegionGeneratorIterator<'static>,
     batch_size: usize,
+    column_types: ColumnTypes,
+    schema: SchemaRef,
 }
 
 impl RegionArrow {
@@ -46,6 +48,8 @@ impl RegionArrow {
         Self {
             inner: generator.iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            column_types: ColumnTypes::default(),
+            schema: Arc::clone(&REGION_SCHEMA),
         }
     }
 
@@ -54,11 +58,39 @@ impl RegionArrow {
         self.batch_size = batch_size;
         self
     }
+
+    /// Set the batch size to the `region` size of `batch_sizes`
+    pub fn with_batch_sizes(self, batch_sizes: &BatchSizes) -> Self {
+        self.with_batch_size(batch_sizes.region)
+    }
+
+    /// Set the Arrow types of the columns, see [`ColumnTypes`]
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.column_types = *column_types;
+        self.schema = column_types.schema(&REGION_SCHEMA);
+        self
+    }
+
+    /// Convert `rows` to a [`RecordBatch`] with this table's schema
+    ///
+    /// This can be used for rows that were not generated by this iterator,
+    /// for example rows parsed with [`tpchgen::parse`].
+    pub fn batch_from_rows(rows: &[Region<'_>]) -> RecordBatch {
+        let r_regionkey = Int64Array::from_iter_values(rows.iter().map(|r| r.r_regionkey));
+        let r_name = StringViewArray::from_iter_values(rows.iter().map(|r| r.r_name));
+        let r_comment = StringViewArray::from_iter_values(rows.iter().map(|r| r.r_comment));
+
+        RecordBatch::try_new(
+            Arc::clone(&REGION_SCHEMA),
+            vec![Arc::new(r_regionkey), Arc::new(r_name), Arc::new(r_comment)],
+        )
+        .unwrap()
+    }
 }
 
 impl RecordBatchIterator for RegionArrow {
     fn schema(&self) -> &SchemaRef {
-        &REGION_SCHEMA
+        &self.schema
     }
 }
 
@@ -71,17 +103,8 @@ impl Iterator for RegionArrow {
         if rows.is_empty() {
             return None;
         }
-
-        let r_regionkey = Int64Array::from_iter_values(rows.iter().map(|r| r.r_regionkey));
-        let r_name = StringViewArray::from_iter_values(rows.iter().map(|r| r.r_name));
-        let r_comment = StringViewArray::from_iter_values(rows.iter().map(|r| r.r_comment));
-
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
-            vec![Arc::new(r_regionkey), Arc::new(r_name), Arc::new(r_comment)],
-        )
-        .unwrap();
-        Some(batch)
+        let batch = Self::batch_from_rows(&rows);
+        Some(self.column_types.convert(batch, &self.schema))
     }
 }
 
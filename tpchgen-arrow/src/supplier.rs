@@ -1,9 +1,9 @@
 use crate::conversions::{decimal128_array_from_iter, string_view_array_from_display_iter};
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
+use crate::{BatchSizes, ColumnTypes, DEFAULT_BATCH_SIZE, RecordBatchIterator};
 use arrow::array::{Int64Array, RecordBatch};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use std::sync::{Arc, LazyLock};
-use tpchgen::generators::{SupplierGenerator, SupplierGeneratorIterator};
+use tpchgen::generators::{Supplier, SupplierGenerator, SupplierGeneratorIterator};
 
 /// Generate [`Supplier`]s in [`RecordBatch`] format
 ///
@@ -32,6 +32,8 @@ use tpchgen::generators::{SupplierGenerator, SupplierGeneratorIterator};
 pub struct SupplierArrow {
     inner: SupplierGeneratorIterator<'static>,
     batch_size: usize,
+    column_types: ColumnTypes,
+    schema: SchemaRef,
 }
 
 impl SupplierArrow {
@@ -39,6 +41,8 @@ impl SupplierArrow {
         Self {
             inner: generator.iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            column_types: ColumnTypes::default(),
+            schema: Arc::clone(&SUPPLIER_SCHEMA),
         }
     }
 
@@ -47,24 +51,24 @@ impl SupplierArrow {
         self.batch_size = batch_size;
         self
     }
-}
 
-impl RecordBatchIterator for SupplierArrow {
-    fn schema(&self) -> &SchemaRef {
-        &SUPPLIER_SCHEMA
+    /// Set the batch size to the `supplier` size of `batch_sizes`
+    pub fn with_batch_sizes(self, batch_sizes: &BatchSizes) -> Self {
+        self.with_batch_size(batch_sizes.supplier)
     }
-}
 
-impl Iterator for SupplierArrow {
-    type Item = RecordBatch;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
-        if rows.is_empty() {
-            return None;
-        }
+    /// Set the Arrow types of the columns, see [`ColumnTypes`]
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.column_types = *column_types;
+        self.schema = column_types.schema(&SUPPLIER_SCHEMA);
+        self
+    }
 
+    /// Convert `rows` to a [`RecordBatch`] with this table's schema
+    ///
+    /// This can be used for rows that were not generated by this iterator,
+    /// for example rows parsed with [`tpchgen::parse`].
+    pub fn batch_from_rows(rows: &[Supplier]) -> RecordBatch {
         let s_suppkey = Int64Array::from_iter_values(rows.iter().map(|r| r.s_suppkey));
         let s_name = string_view_array_from_display_iter(rows.iter().map(|r| r.s_name));
         let s_address = string_view_array_from_display_iter(rows.iter().map(|r| &r.s_address));
@@ -73,8 +77,8 @@ impl Iterator for SupplierArrow {
         let s_acctbal = decimal128_array_from_iter(rows.iter().map(|r| r.s_acctbal));
         let s_comment = string_view_array_from_display_iter(rows.iter().map(|r| &r.s_comment));
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
+        RecordBatch::try_new(
+            Arc::clone(&SUPPLIER_SCHEMA),
             vec![
                 Arc::new(s_suppkey),
                 Arc::new(s_name),
@@ -85,8 +89,27 @@ impl Iterator for SupplierArrow {
                 Arc::new(s_comment),
             ],
         )
-        .unwrap();
-        Some(batch)
+        .unwrap()
+    }
+}
+
+impl RecordBatchIterator for SupplierArrow {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl Iterator for SupplierArrow {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Get next rows to convert
+        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
+        if rows.is_empty() {
+            return None;
+        }
+        let batch = Self::batch_from_rows(&rows);
+        Some(self.column_types.convert(batch, &self.schema))
     }
 }
 
@@ -0,0 +1,1351 @@
+//! Answer sets of the queries (`answers`)
+//!
+//! `tpchgen-cli answers` computes the results of the 22 TPC-H queries on the
+//! data of `--scale-factor` directly from the generators, and writes them to
+//! `q1.out` to `q22.out`, so that the results of an engine can be checked
+//! without the reference answers of the TPC. The parameters of the queries
+//! are those written by `tpchgen-cli queries` with the same `--seed` and
+//! `--stream`, by default the validation parameters of the specification,
+//! whose answers at scale factor 1 are the reference answers.
+//!
+//! The results are written with `|` between the values and a header of the
+//! column names:
+//!
+//! ```text
+//! l_returnflag|l_linestatus|sum_qty|sum_base_price|sum_disc_price|sum_charge|avg_qty|avg_price|avg_disc|count_order
+//! A|F|380456.00|532348211.65|505822441.4861|526165934.000839|25.58|35785.71|0.05|14876
+//! ...
+//! ```
+//!
+//! Sums are exact, with the decimal places of the products they sum, and
+//! averages and ratios are rounded to two decimal places.
+//!
+//! The tables are held in memory while the queries run, in about 400MB per
+//! unit of scale factor, so answers can only be computed up to scale factor 1.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{self, Write};
+use std::ops::Range;
+use tpchgen::dates::TPCHDate;
+use tpchgen::distribution::Distributions;
+use tpchgen::generators::{
+    CustomerGenerator, LineItemGenerator, LineStatus, NationGenerator, OrderGenerator,
+    OrderPriority, OrderStatus, PartGenerator, PartSuppGenerator, RegionGenerator, ReturnFlag,
+    ShipMode, SupplierGenerator,
+};
+use tpchgen::params::{query_params, QueryParams};
+use tpchgen::text::TextPool;
+
+/// The largest scale factor answers can be computed for
+pub const MAX_SCALE_FACTOR: f64 = 1.0;
+
+/// The result of a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer {
+    pub columns: &'static [&'static str],
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Answer {
+    /// Writes the answer with `|` between the values and a header of the
+    /// column names
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{}", self.columns.join("|"))?;
+        for row in &self.rows {
+            writeln!(out, "{}", row.join("|"))?;
+        }
+        Ok(())
+    }
+}
+
+struct Nation {
+    name: &'static str,
+    regionkey: usize,
+}
+
+struct Part {
+    name: String,
+    mfgr: String,
+    brand: String,
+    part_type: &'static str,
+    size: i32,
+    container: &'static str,
+}
+
+struct Supplier {
+    name: String,
+    address: String,
+    nationkey: usize,
+    phone: String,
+    acctbal: i64,
+    comment: String,
+}
+
+struct PartSupp {
+    suppkey: i64,
+    availqty: i32,
+    supplycost: i64,
+}
+
+struct Customer {
+    name: String,
+    address: String,
+    nationkey: usize,
+    phone: String,
+    acctbal: i64,
+    mktsegment: &'static str,
+    comment: &'static str,
+}
+
+struct Order {
+    orderkey: i64,
+    custkey: i64,
+    orderstatus: OrderStatus,
+    totalprice: i64,
+    orderdate: TPCHDate,
+    orderpriority: OrderPriority,
+    shippriority: i32,
+    comment: &'static str,
+    /// Indexes of the line items of the order
+    lines: Range<usize>,
+}
+
+struct LineItem {
+    /// Index of the order of the line item
+    order: usize,
+    partkey: i64,
+    suppkey: i64,
+    quantity: i64,
+    extendedprice: i64,
+    discount: i64,
+    tax: i64,
+    returnflag: ReturnFlag,
+    linestatus: LineStatus,
+    shipdate: TPCHDate,
+    commitdate: TPCHDate,
+    receiptdate: TPCHDate,
+    shipinstruct: &'static str,
+    shipmode: ShipMode,
+}
+
+/// The tables of a scale factor, with the columns the queries use
+///
+/// Keys are dense, so the row with key `k` is at index `k - 1` (`k` for
+/// nations and regions), except for orders and line items, which refer to
+/// their orders by index.
+pub struct Database {
+    scale_factor: f64,
+    nations: Vec<Nation>,
+    regions: Vec<&'static str>,
+    parts: Vec<Part>,
+    suppliers: Vec<Supplier>,
+    partsupps: Vec<PartSupp>,
+    customers: Vec<Customer>,
+    orders: Vec<Order>,
+    lineitems: Vec<LineItem>,
+}
+
+impl Database {
+    /// Generates the tables of `scale_factor`, in `num_threads` threads
+    pub fn generate(
+        scale_factor: f64,
+        num_threads: usize,
+        distributions: &'static Distributions,
+        text_pool: &'static TextPool,
+    ) -> Self {
+        let nations =
+            NationGenerator::new_with_distributions_and_text_pool(distributions, text_pool)
+                .iter()
+                .map(|n| Nation {
+                    name: n.n_name,
+                    regionkey: n.n_regionkey as usize,
+                })
+                .collect();
+        let regions =
+            RegionGenerator::new_with_distributions_and_text_pool(distributions, text_pool)
+                .iter()
+                .map(|r| r.r_name)
+                .collect();
+        let parts = PartGenerator::new_with_distributions_and_text_pool(
+            scale_factor,
+            1,
+            1,
+            distributions,
+            text_pool,
+        )
+        .iter()
+        .map(|p| Part {
+            name: p.p_name.to_string(),
+            mfgr: p.p_mfgr.to_string(),
+            brand: p.p_brand.to_string(),
+            part_type: p.p_type,
+            size: p.p_size,
+            container: p.p_container,
+        })
+        .collect();
+        let suppliers = SupplierGenerator::new_with_distributions_and_text_pool(
+            scale_factor,
+            1,
+            1,
+            distributions,
+            text_pool,
+        )
+        .iter()
+        .map(|s| Supplier {
+            name: s.s_name.to_string(),
+            address: s.s_address.to_string(),
+            nationkey: s.s_nationkey as usize,
+            phone: s.s_phone.to_string(),
+            acctbal: s.s_acctbal.into_inner(),
+            comment: s.s_comment,
+        })
+        .collect();
+        let partsupps = PartSuppGenerator::new_with_distributions_and_text_pool(
+            scale_factor,
+            1,
+            1,
+            distributions,
+            text_pool,
+        )
+        .iter()
+        .map(|ps| PartSupp {
+            suppkey: ps.ps_suppkey,
+            availqty: ps.ps_availqty,
+            supplycost: ps.ps_supplycost.into_inner(),
+        })
+        .collect();
+        let customers = CustomerGenerator::new_with_distributions_and_text_pool(
+            scale_factor,
+            1,
+            1,
+            distributions,
+            text_pool,
+        )
+        .iter()
+        .map(|c| Customer {
+            name: c.c_name.to_string(),
+            address: c.c_address.to_string(),
+            nationkey: c.c_nationkey as usize,
+            phone: c.c_phone.to_string(),
+            acctbal: c.c_acctbal.into_inner(),
+            mktsegment: c.c_mktsegment,
+            comment: c.c_comment,
+        })
+        .collect();
+
+        // orders and line items are generated in parts in parallel
+        let num_parts = num_threads.max(1) as i32;
+        let (orders, lineitems) = std::thread::scope(|scope| {
+            let handles: Vec<_> = (1..=num_parts)
+                .map(|part| {
+                    scope.spawn(move || {
+                        let orders: Vec<_> = OrderGenerator::new_with_distributions_and_text_pool(
+                            scale_factor,
+                            part,
+                            num_parts,
+                            distributions,
+                            text_pool,
+                        )
+                        .iter()
+                        .map(|o| Order {
+                            orderkey: o.o_orderkey,
+                            custkey: o.o_custkey,
+                            orderstatus: o.o_orderstatus,
+                            totalprice: o.o_totalprice.into_inner(),
+                            orderdate: o.o_orderdate,
+                            orderpriority: o.o_orderpriority,
+                            shippriority: o.o_shippriority,
+                            comment: o.o_comment,
+                            lines: 0..0,
+                        })
+                        .collect();
+                        let lineitems: Vec<_> =
+                            LineItemGenerator::new_with_distributions_and_text_pool(
+                                scale_factor,
+                                part,
+                                num_parts,
+                                distributions,
+                                text_pool,
+                            )
+                            .iter()
+                            .map(|l| {
+                                let line = LineItem {
+                                    order: 0,
+                                    partkey: l.l_partkey,
+                                    suppkey: l.l_suppkey,
+                                    quantity: l.l_quantity,
+                                    extendedprice: l.l_extendedprice.into_inner(),
+                                    discount: l.l_discount.into_inner(),
+                                    tax: l.l_tax.into_inner(),
+                                    returnflag: l.l_returnflag,
+                                    linestatus: l.l_linestatus,
+                                    shipdate: l.l_shipdate,
+                                    commitdate: l.l_commitdate,
+                                    receiptdate: l.l_receiptdate,
+                                    shipinstruct: l.l_shipinstruct,
+                                    shipmode: l.l_shipmode,
+                                };
+                                (l.l_orderkey, line)
+                            })
+                            .collect();
+                        (orders, lineitems)
+                    })
+                })
+                .collect();
+            let mut orders = Vec::new();
+            let mut lineitems = Vec::new();
+            for handle in handles {
+                let (part_orders, part_lineitems) = handle.join().expect("generator panicked");
+                orders.extend(part_orders);
+                lineitems.extend(part_lineitems);
+            }
+            (orders, lineitems)
+        });
+
+        // the line items of each order follow each other, in the order of
+        // the orders
+        let mut orders: Vec<Order> = orders;
+        let mut order = 0;
+        let mut start = 0;
+        let lineitems = lineitems
+            .into_iter()
+            .enumerate()
+            .map(|(index, (orderkey, mut line))| {
+                while orders[order].orderkey != orderkey {
+                    orders[order].lines = start..index;
+                    order += 1;
+                    start = index;
+                }
+                line.order = order;
+                line
+            })
+            .collect::<Vec<_>>();
+        orders[order].lines = start..lineitems.len();
+
+        Self {
+            scale_factor,
+            nations,
+            regions,
+            parts,
+            suppliers,
+            partsupps,
+            customers,
+            orders,
+            lineitems,
+        }
+    }
+
+    /// Returns the answer of the query of `params`
+    pub fn answer(&self, params: &QueryParams) -> Answer {
+        match params {
+            QueryParams::Q1 { delta } => self.q1(*delta),
+            QueryParams::Q2 {
+                size,
+                type_syllable,
+                region,
+            } => self.q2(*size, type_syllable, region),
+            QueryParams::Q3 { segment, date } => self.q3(segment, *date),
+            QueryParams::Q4 { date } => self.q4(*date),
+            QueryParams::Q5 { region, date } => self.q5(region, *date),
+            QueryParams::Q6 {
+                date,
+                discount,
+                quantity,
+            } => self.q6(*date, discount.into_inner(), *quantity as i64),
+            QueryParams::Q7 { nation1, nation2 } => self.q7(nation1, nation2),
+            QueryParams::Q8 {
+                nation,
+                region,
+                part_type,
+            } => self.q8(nation, region, part_type),
+            QueryParams::Q9 { color } => self.q9(color),
+            QueryParams::Q10 { date } => self.q10(*date),
+            QueryParams::Q11 { nation, fraction } => self.q11(nation, *fraction),
+            QueryParams::Q12 {
+                ship_mode1,
+                ship_mode2,
+                date,
+            } => self.q12(ship_mode1, ship_mode2, *date),
+            QueryParams::Q13 { word1, word2 } => self.q13(word1, word2),
+            QueryParams::Q14 { date } => self.q14(*date),
+            QueryParams::Q15 { date } => self.q15(*date),
+            QueryParams::Q16 {
+                brand,
+                part_type,
+                sizes,
+            } => self.q16(*brand, part_type, sizes),
+            QueryParams::Q17 { brand, container } => self.q17(*brand, container),
+            QueryParams::Q18 { quantity } => self.q18(*quantity as i64),
+            QueryParams::Q19 { brands, quantities } => self.q19(brands, quantities),
+            QueryParams::Q20 {
+                color,
+                date,
+                nation,
+            } => self.q20(color, *date, nation),
+            QueryParams::Q21 { nation } => self.q21(nation),
+            QueryParams::Q22 { country_codes } => self.q22(country_codes),
+        }
+    }
+
+    fn part(&self, partkey: i64) -> &Part {
+        &self.parts[partkey as usize - 1]
+    }
+
+    fn supplier(&self, suppkey: i64) -> &Supplier {
+        &self.suppliers[suppkey as usize - 1]
+    }
+
+    fn customer(&self, custkey: i64) -> &Customer {
+        &self.customers[custkey as usize - 1]
+    }
+
+    /// Returns the rows of `partsupp` of the part `partkey`
+    fn part_supps(&self, partkey: i64) -> &[PartSupp] {
+        let suppliers = self.partsupps.len() / self.parts.len();
+        let start = (partkey as usize - 1) * suppliers;
+        &self.partsupps[start..start + suppliers]
+    }
+
+    /// Returns the key of the nation `name`
+    fn nation_key(&self, name: &str) -> usize {
+        self.nations
+            .iter()
+            .position(|n| n.name == name)
+            .expect("known nation")
+    }
+
+    /// Returns the key of the region `name`
+    fn region_key(&self, name: &str) -> usize {
+        self.regions
+            .iter()
+            .position(|r| *r == name)
+            .expect("known region")
+    }
+
+    /// Pricing Summary Report Query (Q1)
+    fn q1(&self, delta: i32) -> Answer {
+        #[derive(Default)]
+        struct Group {
+            quantity: i128,
+            price: i128,
+            disc_price: i128,
+            charge: i128,
+            discount: i128,
+            count: i128,
+        }
+        let cutoff = date_add_days(date(1998, 12, 1), -delta);
+        let mut groups: BTreeMap<(&str, &str), Group> = BTreeMap::new();
+        for l in &self.lineitems {
+            if l.shipdate > cutoff {
+                continue;
+            }
+            let group = groups
+                .entry((l.returnflag.as_str(), l.linestatus.as_str()))
+                .or_default();
+            group.quantity += l.quantity as i128;
+            group.price += l.extendedprice as i128;
+            group.disc_price += volume(l);
+            group.charge += volume(l) * (100 + l.tax as i128);
+            group.discount += l.discount as i128;
+            group.count += 1;
+        }
+        let rows = groups
+            .into_iter()
+            .map(|((returnflag, linestatus), g)| {
+                vec![
+                    returnflag.to_string(),
+                    linestatus.to_string(),
+                    decimal(g.quantity * 100, 2),
+                    decimal(g.price, 2),
+                    decimal(g.disc_price, 4),
+                    decimal(g.charge, 6),
+                    ratio(g.quantity, g.count),
+                    ratio(g.price, g.count * 100),
+                    ratio(g.discount, g.count * 100),
+                    g.count.to_string(),
+                ]
+            })
+            .collect();
+        Answer {
+            columns: &[
+                "l_returnflag",
+                "l_linestatus",
+                "sum_qty",
+                "sum_base_price",
+                "sum_disc_price",
+                "sum_charge",
+                "avg_qty",
+                "avg_price",
+                "avg_disc",
+                "count_order",
+            ],
+            rows,
+        }
+    }
+
+    /// Minimum Cost Supplier Query (Q2)
+    fn q2(&self, size: i32, type_syllable: &str, region: &str) -> Answer {
+        let region = self.region_key(region);
+        let in_region =
+            |ps: &PartSupp| self.nations[self.supplier(ps.suppkey).nationkey].regionkey == region;
+        let mut rows = Vec::new();
+        for (index, part) in self.parts.iter().enumerate() {
+            if part.size != size || !part.part_type.ends_with(type_syllable) {
+                continue;
+            }
+            let partkey = index as i64 + 1;
+            let supps = self.part_supps(partkey);
+            let Some(min_cost) = supps
+                .iter()
+                .filter(|ps| in_region(ps))
+                .map(|ps| ps.supplycost)
+                .min()
+            else {
+                continue;
+            };
+            for ps in supps {
+                if ps.supplycost == min_cost && in_region(ps) {
+                    let supplier = self.supplier(ps.suppkey);
+                    rows.push((supplier, partkey, part));
+                }
+            }
+        }
+        rows.sort_by(|(s1, p1, _), (s2, p2, _)| {
+            let n1 = self.nations[s1.nationkey].name;
+            let n2 = self.nations[s2.nationkey].name;
+            (Reverse(s1.acctbal), n1, &s1.name, p1).cmp(&(Reverse(s2.acctbal), n2, &s2.name, p2))
+        });
+        let rows = rows
+            .into_iter()
+            .take(100)
+            .map(|(s, partkey, part)| {
+                vec![
+                    decimal(s.acctbal as i128, 2),
+                    s.name.clone(),
+                    self.nations[s.nationkey].name.to_string(),
+                    partkey.to_string(),
+                    part.mfgr.clone(),
+                    s.address.clone(),
+                    s.phone.clone(),
+                    s.comment.clone(),
+                ]
+            })
+            .collect();
+        Answer {
+            columns: &[
+                "s_acctbal",
+                "s_name",
+                "n_name",
+                "p_partkey",
+                "p_mfgr",
+                "s_address",
+                "s_phone",
+                "s_comment",
+            ],
+            rows,
+        }
+    }
+
+    /// Shipping Priority Query (Q3)
+    fn q3(&self, segment: &str, date: TPCHDate) -> Answer {
+        let mut rows = Vec::new();
+        for order in &self.orders {
+            if order.orderdate >= date || self.customer(order.custkey).mktsegment != segment {
+                continue;
+            }
+            let revenue: i128 = self.lineitems[order.lines.clone()]
+                .iter()
+                .filter(|l| l.shipdate > date)
+                .map(volume)
+                .sum();
+            // the volume of each line item is positive
+            if revenue > 0 {
+                rows.push((order, revenue));
+            }
+        }
+        rows.sort_by_key(|(order, revenue)| (Reverse(*revenue), order.orderdate));
+        let rows = rows
+            .into_iter()
+            .take(10)
+            .map(|(order, revenue)| {
+                vec![
+                    order.orderkey.to_string(),
+                    decimal(revenue, 4),
+                    order.orderdate.to_string(),
+                    order.shippriority.to_string(),
+                ]
+            })
+            .collect();
+        Answer {
+            columns: &["l_orderkey", "revenue", "o_orderdate", "o_shippriority"],
+            rows,
+        }
+    }
+
+    /// Order Priority Checking Query (Q4)
+    fn q4(&self, date: TPCHDate) -> Answer {
+        let end = date_add_months(date, 3);
+        let mut counts: BTreeMap<&str, i64> = BTreeMap::new();
+        for order in &self.orders {
+            if order.orderdate < date || order.orderdate >= end {
+                continue;
+            }
+            let late = self.lineitems[order.lines.clone()]
+                .iter()
+                .any(|l| l.commitdate < l.receiptdate);
+            if late {
+                *counts.entry(order.orderpriority.as_str()).or_default() += 1;
+            }
+        }
+        let rows = counts
+            .into_iter()
+            .map(|(priority, count)| vec![priority.to_string(), count.to_string()])
+            .collect();
+        Answer {
+            columns: &["o_orderpriority", "order_count"],
+            rows,
+        }
+    }
+
+    /// Local Supplier Volume Query (Q5)
+    fn q5(&self, region: &str, date: TPCHDate) -> Answer {
+        let region = self.region_key(region);
+        let end = date_add_months(date, 12);
+        let mut revenue: HashMap<usize, i128> = HashMap::new();
+        for order in &self.orders {
+            if order.orderdate < date || order.orderdate >= end {
+                continue;
+            }
+            let nation = self.customer(order.custkey).nationkey;
+            if self.nations[nation].regionkey != region {
+                continue;
+            }
+            for l in &self.lineitems[order.lines.clone()] {
+                if self.supplier(l.suppkey).nationkey == nation {
+                    *revenue.entry(nation).or_default() += volume(l);
+                }
+            }
+        }
+        let mut rows: Vec<_> = revenue.into_iter().collect();
+        rows.sort_by_key(|(_, revenue)| Reverse(*revenue));
+        let rows = rows
+            .into_iter()
+            .map(|(nation, revenue)| {
+                vec![self.nations[nation].name.to_string(), decimal(revenue, 4)]
+            })
+            .collect();
+        Answer {
+            columns: &["n_name", "revenue"],
+            rows,
+        }
+    }
+
+    /// Forecasting Revenue Change Query (Q6)
+    fn q6(&self, date: TPCHDate, discount: i64, quantity: i64) -> Answer {
+        let end = date_add_months(date, 12);
+        let revenue: i128 = self
+            .lineitems
+            .iter()
+            .filter(|l| {
+                l.shipdate >= date
+                    && l.shipdate < end
+                    && (discount - 1..=discount + 1).contains(&l.discount)
+                    && l.quantity < quantity
+            })
+            .map(|l| l.extendedprice as i128 * l.discount as i128)
+            .sum();
+        Answer {
+            columns: &["revenue"],
+            rows: vec![vec![decimal(revenue, 4)]],
+        }
+    }
+
+    /// Volume Shipping Query (Q7)
+    fn q7(&self, nation1: &str, nation2: &str) -> Answer {
+        let nation1 = self.nation_key(nation1);
+        let nation2 = self.nation_key(nation2);
+        let (start, end) = (date(1995, 1, 1), date(1996, 12, 31));
+        let mut revenue: BTreeMap<(&str, &str, i32), i128> = BTreeMap::new();
+        for l in &self.lineitems {
+            if l.shipdate < start || l.shipdate > end {
+                continue;
+            }
+            let supp_nation = self.supplier(l.suppkey).nationkey;
+            let order = &self.orders[l.order];
+            let cust_nation = self.customer(order.custkey).nationkey;
+            if (supp_nation, cust_nation) == (nation1, nation2)
+                || (supp_nation, cust_nation) == (nation2, nation1)
+            {
+                let key = (
+                    self.nations[supp_nation].name,
+                    self.nations[cust_nation].name,
+                    year(l.shipdate),
+                );
+                *revenue.entry(key).or_default() += volume(l);
+            }
+        }
+        let rows = revenue
+            .into_iter()
+            .map(|((supp_nation, cust_nation, year), revenue)| {
+                vec![
+                    supp_nation.to_string(),
+                    cust_nation.to_string(),
+                    year.to_string(),
+                    decimal(revenue, 4),
+                ]
+            })
+            .collect();
+        Answer {
+            columns: &["supp_nation", "cust_nation", "l_year", "revenue"],
+            rows,
+        }
+    }
+
+    /// National Market Share Query (Q8)
+    fn q8(&self, nation: &str, region: &str, part_type: &str) -> Answer {
+        let nation = self.nation_key(nation);
+        let region = self.region_key(region);
+        let (start, end) = (date(1995, 1, 1), date(1996, 12, 31));
+        let mut volumes: BTreeMap<i32, (i128, i128)> = BTreeMap::new();
+        for order in &self.orders {
+            if order.orderdate < start || order.orderdate > end {
+                continue;
+            }
+            let cust_nation = self.customer(order.custkey).nationkey;
+            if self.nations[cust_nation].regionkey != region {
+                continue;
+            }
+            for l in &self.lineitems[order.lines.clone()] {
+                if self.part(l.partkey).part_type != part_type {
+                    continue;
+                }
+                let (nation_volume, total) = volumes.entry(year(order.orderdate)).or_default();
+                if self.supplier(l.suppkey).nationkey == nation {
+                    *nation_volume += volume(l);
+                }
+                *total += volume(l);
+            }
+        }
+        let rows = volumes
+            .into_iter()
+            .map(|(year, (nation_volume, total))| {
+                vec![year.to_string(), ratio(nation_volume, total)]
+            })
+            .collect();
+        Answer {
+            columns: &["o_year", "mkt_share"],
+            rows,
+        }
+    }
+
+    /// Product Type Profit Measure Query (Q9)
+    fn q9(&self, color: &str) -> Answer {
+        let mut profit: BTreeMap<(&str, Reverse<i32>), i128> = BTreeMap::new();
+        for l in &self.lineitems {
+            if !self.part(l.partkey).name.contains(color) {
+                continue;
+            }
+            let ps = self
+                .part_supps(l.partkey)
+                .iter()
+                .find(|ps| ps.suppkey == l.suppkey)
+                .expect("partsupp of line item");
+            let nation = self.nations[self.supplier(l.suppkey).nationkey].name;
+            let year = year(self.orders[l.order].orderdate);
+            let amount = volume(l) - ps.supplycost as i128 * l.quantity as i128 * 100;
+            *profit.entry((nation, Reverse(year))).or_default() += amount;
+        }
+        let rows = profit
+            .into_iter()
+            .map(|((nation, Reverse(year)), profit)| {
+                vec![nation.to_string(), year.to_string(), decimal(profit, 4)]
+            })
+            .collect();
+        Answer {
+            columns: &["nation", "o_year", "sum_profit"],
+            rows,
+        }
+    }
+
+    /// Returned Item Reporting Query (Q10)
+    fn q10(&self, date: TPCHDate) -> Answer {
+        let end = date_add_months(date, 3);
+        let mut revenue: HashMap<i64, i128> = HashMap::new();
+        for order in &self.orders {
+            if order.orderdate < date || order.orderdate >= end {
+                continue;
+            }
+            for l in &self.lineitems[order.lines.clone()] {
+                if l.returnflag == ReturnFlag::Returned {
+                    *revenue.entry(order.custkey).or_default() += volume(l);
+                }
+            }
+        }
+        let mut rows: Vec<_> = revenue.into_iter().collect();
+        rows.sort_by_key(|(custkey, revenue)| (Reverse(*revenue), *custkey));
+        let rows = rows
+            .into_iter()
+            .take(20)
+            .map(|(custkey, revenue)| {
+                let c = self.customer(custkey);
+                vec![
+                    custkey.to_string(),
+                    c.name.clone(),
+                    decimal(revenue, 4),
+                    decimal(c.acctbal as i128, 2),
+                    self.nations[c.nationkey].name.to_string(),
+                    c.address.clone(),
+                    c.phone.clone(),
+                    c.comment.to_string(),
+                ]
+            })
+            .collect();
+        Answer {
+            columns: &[
+                "c_custkey",
+                "c_name",
+                "revenue",
+                "c_acctbal",
+                "n_name",
+                "c_address",
+                "c_phone",
+                "c_comment",
+            ],
+            rows,
+        }
+    }
+
+    /// Important Stock Identification Query (Q11)
+    fn q11(&self, nation: &str, fraction: f64) -> Answer {
+        let nation = self.nation_key(nation);
+        let suppliers = self.partsupps.len() / self.parts.len();
+        let mut values = Vec::new();
+        for (index, supps) in self.partsupps.chunks(suppliers).enumerate() {
+            let value: i128 = supps
+                .iter()
+                .filter(|ps| self.supplier(ps.suppkey).nationkey == nation)
+                .map(|ps| ps.supplycost as i128 * ps.availqty as i128)
+                .sum();
+            if value > 0 {
+                values.push((index as i64 + 1, value));
+            }
+        }
+        let total: i128 = values.iter().map(|(_, value)| value).sum();
+        let threshold = total as f64 * fraction;
+        values.retain(|(_, value)| *value as f64 > threshold);
+        values.sort_by_key(|(partkey, value)| (Reverse(*value), *partkey));
+        let rows = values
+            .into_iter()
+            .map(|(partkey, value)| vec![partkey.to_string(), decimal(value, 2)])
+            .collect();
+        Answer {
+            columns: &["ps_partkey", "value"],
+            rows,
+        }
+    }
+
+    /// Shipping Modes and Order Priority Query (Q12)
+    fn q12(&self, ship_mode1: &str, ship_mode2: &str, date: TPCHDate) -> Answer {
+        let end = date_add_months(date, 12);
+        let mut counts: BTreeMap<&str, (i64, i64)> = BTreeMap::new();
+        for l in &self.lineitems {
+            let mode = l.shipmode.as_str();
+            if (mode != ship_mode1 && mode != ship_mode2)
+                || l.commitdate >= l.receiptdate
+                || l.shipdate >= l.commitdate
+                || l.receiptdate < date
+                || l.receiptdate >= end
+            {
+                continue;
+            }
+            let (high, low) = counts.entry(mode).or_default();
+            match self.orders[l.order].orderpriority.as_str() {
+                "1-URGENT" | "2-HIGH" => *high += 1,
+                _ => *low += 1,
+            }
+        }
+        let rows = counts
+            .into_iter()
+            .map(|(mode, (high, low))| vec![mode.to_string(), high.to_string(), low.to_string()])
+            .collect();
+        Answer {
+            columns: &["l_shipmode", "high_line_count", "low_line_count"],
+            rows,
+        }
+    }
+
+    /// Customer Distribution Query (Q13)
+    fn q13(&self, word1: &str, word2: &str) -> Answer {
+        let mut counts = vec![0_i64; self.customers.len()];
+        for order in &self.orders {
+            let matches = order
+                .comment
+                .find(word1)
+                .is_some_and(|i| order.comment[i + word1.len()..].contains(word2));
+            if !matches {
+                counts[order.custkey as usize - 1] += 1;
+            }
+        }
+        let mut distribution: HashMap<i64, i64> = HashMap::new();
+        for count in counts {
+            *distribution.entry(count).or_default() += 1;
+        }
+        let mut rows: Vec<_> = distribution.into_iter().collect();
+        rows.sort_by_key(|(count, customers)| (Reverse(*customers), Reverse(*count)));
+        let rows = rows
+            .into_iter()
+            .map(|(count, customers)| vec![count.to_string(), customers.to_string()])
+            .collect();
+        Answer {
+            columns: &["c_count", "custdist"],
+            rows,
+        }
+    }
+
+    /// Promotion Effect Query (Q14)
+    fn q14(&self, date: TPCHDate) -> Answer {
+        let end = date_add_months(date, 1);
+        let (mut promo, mut total) = (0, 0);
+        for l in &self.lineitems {
+            if l.shipdate < date || l.shipdate >= end {
+                continue;
+            }
+            if self.part(l.partkey).part_type.starts_with("PROMO") {
+                promo += volume(l);
+            }
+            total += volume(l);
+        }
+        Answer {
+            columns: &["promo_revenue"],
+            rows: vec![vec![ratio(100 * promo, total)]],
+        }
+    }
+
+    /// Top Supplier Query (Q15)
+    fn q15(&self, date: TPCHDate) -> Answer {
+        let end = date_add_months(date, 3);
+        let mut revenue = vec![0_i128; self.suppliers.len()];
+        for l in &self.lineitems {
+            if l.shipdate >= date && l.shipdate < end {
+                revenue[l.suppkey as usize - 1] += volume(l);
+            }
+        }
+        let max = revenue.iter().copied().max().unwrap_or_default();
+        let rows = revenue
+            .iter()
+            .enumerate()
+            .filter(|(_, revenue)| **revenue == max)
+            .map(|(index, revenue)| {
+                let s = &self.suppliers[index];
+                vec![
+                    (index + 1).to_string(),
+                    s.name.clone(),
+                    s.address.clone(),
+                    s.phone.clone(),
+                    decimal(*revenue, 4),
+                ]
+            })
+            .collect();
+        Answer {
+            columns: &[
+                "s_suppkey",
+                "s_name",
+                "s_address",
+                "s_phone",
+                "total_revenue",
+            ],
+            rows,
+        }
+    }
+
+    /// Parts/Supplier Relationship Query (Q16)
+    fn q16(&self, brand: i32, part_type: &str, sizes: &[i32; 8]) -> Answer {
+        let brand = format!("Brand#{brand}");
+        let complaints: HashSet<i64> = (1..=self.suppliers.len() as i64)
+            .filter(|suppkey| {
+                let comment = &self.supplier(*suppkey).comment;
+                comment
+                    .find("Customer")
+                    .is_some_and(|i| comment[i + "Customer".len()..].contains("Complaints"))
+            })
+            .collect();
+        let mut groups: HashMap<(&str, &str, i32), BTreeSet<i64>> = HashMap::new();
+        for (index, part) in self.parts.iter().enumerate() {
+            if part.brand == brand
+                || part.part_type.starts_with(part_type)
+                || !sizes.contains(&part.size)
+            {
+                continue;
+            }
+            let suppliers = groups
+                .entry((&part.brand, part.part_type, part.size))
+                .or_default();
+            for ps in self.part_supps(index as i64 + 1) {
+                if !complaints.contains(&ps.suppkey) {
+                    suppliers.insert(ps.suppkey);
+                }
+            }
+        }
+        let mut rows: Vec<_> = groups
+            .into_iter()
+            .map(|(key, suppliers)| (key, suppliers.len()))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        rows.sort_by_key(|((brand, part_type, size), count)| {
+            (Reverse(*count), *brand, *part_type, *size)
+        });
+        let rows = rows
+            .into_iter()
+            .map(|((brand, part_type, size), count)| {
+                vec![
+                    brand.to_string(),
+                    part_type.to_string(),
+                    size.to_string(),
+                    count.to_string(),
+                ]
+            })
+            .collect();
+        Answer {
+            columns: &["p_brand", "p_type", "p_size", "supplier_cnt"],
+            rows,
+        }
+    }
+
+    /// Small-Quantity-Order Revenue Query (Q17)
+    fn q17(&self, brand: i32, container: &str) -> Answer {
+        let brand = format!("Brand#{brand}");
+        let selected = |partkey: i64| {
+            let part = self.part(partkey);
+            part.brand == brand && part.container == container
+        };
+        // sum and count of the quantities of each selected part
+        let mut quantities: HashMap<i64, (i128, i128)> = HashMap::new();
+        for l in &self.lineitems {
+            if selected(l.partkey) {
+                let (sum, count) = quantities.entry(l.partkey).or_default();
+                *sum += l.quantity as i128;
+                *count += 1;
+            }
+        }
+        let total: i128 = self
+            .lineitems
+            .iter()
+            .filter(|l| {
+                quantities.get(&l.partkey).is_some_and(|(sum, count)| {
+                    // l_quantity < 0.2 * avg(l_quantity)
+                    5 * l.quantity as i128 * count < *sum
+                })
+            })
+            .map(|l| l.extendedprice as i128)
+            .sum();
+        Answer {
+            columns: &["avg_yearly"],
+            rows: vec![vec![ratio(total, 700)]],
+        }
+    }
+
+    /// Large Volume Customer Query (Q18)
+    fn q18(&self, quantity: i64) -> Answer {
+        let mut rows: Vec<_> = self
+            .orders
+            .iter()
+            .filter_map(|order| {
+                let total: i64 = self.lineitems[order.lines.clone()]
+                    .iter()
+                    .map(|l| l.quantity)
+                    .sum();
+                (total > quantity).then_some((order, total))
+            })
+            .collect();
+        rows.sort_by_key(|(order, _)| (Reverse(order.totalprice), order.orderdate));
+        let rows = rows
+            .into_iter()
+            .take(100)
+            .map(|(order, total)| {
+                vec![
+                    self.customer(order.custkey).name.clone(),
+                    order.custkey.to_string(),
+                    order.orderkey.to_string(),
+                    order.orderdate.to_string(),
+                    decimal(order.totalprice as i128, 2),
+                    decimal(total as i128 * 100, 2),
+                ]
+            })
+            .collect();
+        Answer {
+            columns: &[
+                "c_name",
+                "c_custkey",
+                "o_orderkey",
+                "o_orderdate",
+                "o_totalprice",
+                "sum(l_quantity)",
+            ],
+            rows,
+        }
+    }
+
+    /// Discounted Revenue Query (Q19)
+    fn q19(&self, brands: &[i32; 3], quantities: &[i32; 3]) -> Answer {
+        const CONTAINERS: [[&str; 4]; 3] = [
+            ["SM CASE", "SM BOX", "SM PACK", "SM PKG"],
+            ["MED BAG", "MED BOX", "MED PKG", "MED PACK"],
+            ["LG CASE", "LG BOX", "LG PACK", "LG PKG"],
+        ];
+        const MAX_SIZES: [i32; 3] = [5, 10, 15];
+        let brands = brands.map(|brand| format!("Brand#{brand}"));
+        let revenue: i128 = self
+            .lineitems
+            .iter()
+            .filter(|l| {
+                // as in the query, although the ship mode is `REG AIR`
+                if !matches!(l.shipmode.as_str(), "AIR" | "AIR REG")
+                    || l.shipinstruct != "DELIVER IN PERSON"
+                {
+                    return false;
+                }
+                let part = self.part(l.partkey);
+                (0..3).any(|i| {
+                    let quantity = quantities[i] as i64;
+                    part.brand == brands[i]
+                        && CONTAINERS[i].contains(&part.container)
+                        && (quantity..=quantity + 10).contains(&l.quantity)
+                        && (1..=MAX_SIZES[i]).contains(&part.size)
+                })
+            })
+            .map(volume)
+            .sum();
+        Answer {
+            columns: &["revenue"],
+            rows: vec![vec![decimal(revenue, 4)]],
+        }
+    }
+
+    /// Potential Part Promotion Query (Q20)
+    fn q20(&self, color: &str, date: TPCHDate, nation: &str) -> Answer {
+        let nation = self.nation_key(nation);
+        let end = date_add_months(date, 12);
+        let selected = |partkey: i64| self.part(partkey).name.starts_with(color);
+        let mut shipped: HashMap<(i64, i64), i64> = HashMap::new();
+        for l in &self.lineitems {
+            if l.shipdate >= date && l.shipdate < end && selected(l.partkey) {
+                *shipped.entry((l.partkey, l.suppkey)).or_default() += l.quantity;
+            }
+        }
+        let mut suppliers = BTreeSet::new();
+        for partkey in 1..=self.parts.len() as i64 {
+            if !selected(partkey) {
+                continue;
+            }
+            for ps in self.part_supps(partkey) {
+                let excess = shipped
+                    .get(&(partkey, ps.suppkey))
+                    .is_some_and(|quantity| 2 * ps.availqty as i64 > *quantity);
+                if excess && self.supplier(ps.suppkey).nationkey == nation {
+                    let supplier = self.supplier(ps.suppkey);
+                    suppliers.insert((&supplier.name, &supplier.address));
+                }
+            }
+        }
+        let rows = suppliers
+            .into_iter()
+            .map(|(name, address)| vec![name.clone(), address.clone()])
+            .collect();
+        Answer {
+            columns: &["s_name", "s_address"],
+            rows,
+        }
+    }
+
+    /// Suppliers Who Kept Orders Waiting Query (Q21)
+    fn q21(&self, nation: &str) -> Answer {
+        let nation = self.nation_key(nation);
+        let mut waiting: HashMap<i64, i64> = HashMap::new();
+        for order in &self.orders {
+            if order.orderstatus != OrderStatus::Fulfilled {
+                continue;
+            }
+            let lines = &self.lineitems[order.lines.clone()];
+            for l1 in lines {
+                if l1.receiptdate <= l1.commitdate || self.supplier(l1.suppkey).nationkey != nation
+                {
+                    continue;
+                }
+                let other_supplier = lines.iter().any(|l2| l2.suppkey != l1.suppkey);
+                let other_late = lines
+                    .iter()
+                    .any(|l3| l3.suppkey != l1.suppkey && l3.receiptdate > l3.commitdate);
+                if other_supplier && !other_late {
+                    *waiting.entry(l1.suppkey).or_default() += 1;
+                }
+            }
+        }
+        let mut rows: Vec<_> = waiting
+            .into_iter()
+            .map(|(suppkey, count)| (&self.supplier(suppkey).name, count))
+            .collect();
+        rows.sort_by_key(|(name, count)| (Reverse(*count), *name));
+        let rows = rows
+            .into_iter()
+            .take(100)
+            .map(|(name, count)| vec![name.clone(), count.to_string()])
+            .collect();
+        Answer {
+            columns: &["s_name", "numwait"],
+            rows,
+        }
+    }
+
+    /// Global Sales Opportunity Query (Q22)
+    fn q22(&self, country_codes: &[i32; 7]) -> Answer {
+        let codes: Vec<String> = country_codes.iter().map(|c| c.to_string()).collect();
+        let selected = |c: &Customer| codes.iter().any(|code| c.phone.starts_with(code.as_str()));
+        let (sum, count) = self
+            .customers
+            .iter()
+            .filter(|c| c.acctbal > 0 && selected(c))
+            .fold((0_i128, 0_i128), |(sum, count), c| {
+                (sum + c.acctbal as i128, count + 1)
+            });
+        let mut has_orders = vec![false; self.customers.len()];
+        for order in &self.orders {
+            has_orders[order.custkey as usize - 1] = true;
+        }
+        let mut groups: BTreeMap<&str, (i64, i128)> = BTreeMap::new();
+        for (index, c) in self.customers.iter().enumerate() {
+            // c_acctbal > avg(c_acctbal)
+            if has_orders[index] || !selected(c) || c.acctbal as i128 * count <= sum {
+                continue;
+            }
+            let (customers, total) = groups.entry(&c.phone[..2]).or_default();
+            *customers += 1;
+            *total += c.acctbal as i128;
+        }
+        let rows = groups
+            .into_iter()
+            .map(|(code, (customers, total))| {
+                vec![code.to_string(), customers.to_string(), decimal(total, 2)]
+            })
+            .collect();
+        Answer {
+            columns: &["cntrycode", "numcust", "totacctbal"],
+            rows,
+        }
+    }
+}
+
+/// Returns the answers of the 22 queries on `database`, with the parameters
+/// chosen with `seed` (see [`query_params`]), in the order of the query
+/// numbers
+pub fn answers(database: &Database, seed: Option<i64>) -> Vec<Answer> {
+    (1..=22)
+        .map(|query| {
+            let params =
+                query_params(query, database.scale_factor, seed).expect("valid query number");
+            database.answer(&params)
+        })
+        .collect()
+}
+
+/// Returns `l_extendedprice * (1 - l_discount)` of `l`, with 4 decimal places
+fn volume(l: &LineItem) -> i128 {
+    l.extendedprice as i128 * (100 - l.discount as i128)
+}
+
+/// Formats `value` with `scale` decimal places, e.g. `12345` with scale 2 as
+/// `123.45`
+fn decimal(value: i128, scale: u32) -> String {
+    let unit = 10_i128.pow(scale);
+    let sign = if value < 0 { "-" } else { "" };
+    let value = value.abs();
+    format!(
+        "{sign}{}.{:0width$}",
+        value / unit,
+        value % unit,
+        width = scale as usize
+    )
+}
+
+/// Formats `numerator / denominator` rounded to two decimal places
+fn ratio(numerator: i128, denominator: i128) -> String {
+    if denominator == 0 {
+        return "NULL".to_string();
+    }
+    let scaled = numerator * 100;
+    let (quotient, remainder) = (scaled / denominator, scaled % denominator);
+    // round half away from zero
+    let rounded = if 2 * remainder.abs() >= denominator.abs() {
+        quotient + scaled.signum() * denominator.signum()
+    } else {
+        quotient
+    };
+    decimal(rounded, 2)
+}
+
+/// Returns the date of `year`, `month` and `day`
+fn date(year: i32, month: i32, day: i32) -> TPCHDate {
+    TPCHDate::from_ymd(year, month, day).expect("query dates are in the generated range")
+}
+
+/// Returns the year of `date`
+fn year(date: TPCHDate) -> i32 {
+    1900 + date.to_ymd().0
+}
+
+/// Returns `date` plus `days` days
+fn date_add_days(date: TPCHDate, days: i32) -> TPCHDate {
+    TPCHDate::from_epoch_days(date.to_epoch_days() + days)
+        .expect("query dates are in the generated range")
+}
+
+/// Returns `date` plus `months` months
+fn date_add_months(date: TPCHDate, months: i32) -> TPCHDate {
+    let (_, month, day) = date.to_ymd();
+    let months = month - 1 + months;
+    self::date(year(date) + months / 12, months % 12 + 1, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_decimals() {
+        assert_eq!(decimal(12345, 2), "123.45");
+        assert_eq!(decimal(-5, 4), "-0.0005");
+        assert_eq!(ratio(2, 3), "0.67");
+        assert_eq!(ratio(-1, 8), "-0.13");
+        assert_eq!(ratio(1, 0), "NULL");
+        assert_eq!(date_add_months(date(1997, 10, 1), 3), date(1998, 1, 1));
+    }
+
+    #[test]
+    fn answers_at_sf_0_01() {
+        let database = Database::generate(
+            0.01,
+            4,
+            Distributions::static_default(),
+            TextPool::get_or_init_default(),
+        );
+        let answers = answers(&database, None);
+        let mut q1 = Vec::new();
+        answers[0].write(&mut q1).unwrap();
+        let q1 = String::from_utf8(q1).unwrap();
+        let lines: Vec<_> = q1.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[1],
+            "A|F|380456.00|532348211.65|505822441.4861|526165934.000839|25.58|35785.71|0.05|14876"
+        );
+        // Q6 has a single revenue
+        assert_eq!(answers[5].rows.len(), 1);
+        // Q13 counts every customer once
+        let customers: i64 = answers[12]
+            .rows
+            .iter()
+            .map(|row| row[1].parse::<i64>().unwrap())
+            .sum();
+        assert_eq!(customers, 1500);
+    }
+}
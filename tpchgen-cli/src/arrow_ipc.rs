@@ -0,0 +1,242 @@
+//! Arrow IPC output format (`--format=arrow-ipc`)
+//!
+//! The batches produced by `tpchgen-arrow` are written without any encoding,
+//! so in-memory engines can load them without the cost of decoding Parquet.
+//! Files use the Arrow IPC file format (Feather version 2) by default, or
+//! with `--arrow-ipc-stream` the streaming format, which has no footer and
+//! can be read while it is written, e.g. from `--stdout`.
+//!
+//! The parts are generated in parallel and their batches written in part
+//! order.
+
+use crate::progress::{write_callbacks, ProgressReporter};
+use arrow::array::RecordBatch;
+use arrow::datatypes::Schema;
+use arrow::error::ArrowError;
+use arrow::ipc::writer::{FileWriter, StreamWriter};
+use futures::StreamExt;
+use log::debug;
+use std::io::{self, Write};
+use std::sync::Arc;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::generate::Sink;
+use tpchgen_cli::statistics::WriteStatistics;
+
+/// Converts a set of RecordBatchIterators into an Arrow IPC file written to
+/// `sink`, in the streaming format if `stream`
+///
+/// Uses num_threads to generate the batches of each iterator in parallel.
+/// The batches are written in the order of the input.
+pub async fn generate_arrow_ipc<S, I>(
+    sink: S,
+    iter_iter: I,
+    num_threads: usize,
+    stream: bool,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    S: Sink + 'static,
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+{
+    debug!("Generating Arrow IPC with {num_threads} threads (stream: {stream})");
+    let mut iter_iter = iter_iter.peekable();
+
+    // get schema from the first iterator
+    let Some(first_iter) = iter_iter.peek() else {
+        return Ok(()); // no data shrug
+    };
+    let schema = Arc::clone(first_iter.schema());
+
+    let mut statistics =
+        WriteStatistics::new("parts").with_callbacks(write_callbacks(progress.as_ref()));
+
+    // A blocking task that writes the batches of each part to the sink
+    let (tx, mut rx): (Sender<Vec<RecordBatch>>, Receiver<Vec<RecordBatch>>) =
+        tokio::sync::mpsc::channel(num_threads);
+    let writer_task = tokio::task::spawn_blocking(move || {
+        let mut writer = IpcWriter::try_new(SinkWriter::new(sink), &schema, stream)?;
+        while let Some(batches) = rx.blocking_recv() {
+            let start = writer.get_ref().num_bytes;
+            let mut rows = 0;
+            for batch in &batches {
+                writer.write(batch)?;
+                rows += batch.num_rows() as u64;
+            }
+            statistics.increment_chunks(1);
+            if let Some(progress) = progress.as_mut() {
+                progress.part_complete(rows, writer.get_ref().num_bytes - start);
+            }
+        }
+        let writer = writer.into_inner()?;
+        statistics.increment_bytes(writer.num_bytes as usize);
+        writer.sink.flush()
+    });
+
+    // generate the batches for each part in parallel
+    let mut batch_stream = futures::stream::iter(iter_iter)
+        .map(async |iter| {
+            // run on a separate thread
+            tokio::task::spawn(async move { iter.collect::<Vec<RecordBatch>>() })
+                .await
+                .expect("Inner task panicked")
+        })
+        .buffered(num_threads);
+
+    while let Some(batches) = batch_stream.next().await {
+        // send the batches to the writer task
+        if let Err(e) = tx.send(batches).await {
+            debug!("Error sending batches to writer: {e}");
+            break; // stop early
+        }
+    }
+    // signal the writer task that we are done
+    drop(tx);
+
+    // Wait for the writer task to finish
+    writer_task.await?
+}
+
+/// Writer of the Arrow IPC file or streaming format
+enum IpcWriter<W: Write> {
+    File(FileWriter<W>),
+    Stream(StreamWriter<W>),
+}
+
+impl<W: Write> IpcWriter<W> {
+    fn try_new(writer: W, schema: &Schema, stream: bool) -> io::Result<Self> {
+        let writer = if stream {
+            Self::Stream(StreamWriter::try_new(writer, schema).map_err(io::Error::other)?)
+        } else {
+            Self::File(FileWriter::try_new(writer, schema).map_err(io::Error::other)?)
+        };
+        Ok(writer)
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> io::Result<()> {
+        match self {
+            Self::File(writer) => writer.write(batch),
+            Self::Stream(writer) => writer.write(batch),
+        }
+        .map_err(io::Error::other)
+    }
+
+    fn get_ref(&self) -> &W {
+        match self {
+            Self::File(writer) => writer.get_ref(),
+            Self::Stream(writer) => writer.get_ref(),
+        }
+    }
+
+    /// Writes the footer (or end of stream marker) and returns the writer
+    fn into_inner(self) -> io::Result<W> {
+        let writer: Result<W, ArrowError> = match self {
+            Self::File(writer) => writer.into_inner(),
+            Self::Stream(writer) => writer.into_inner(),
+        };
+        writer.map_err(io::Error::other)
+    }
+}
+
+/// A [`Write`] that writes to a [`Sink`], counting the bytes written
+struct SinkWriter<S> {
+    sink: S,
+    num_bytes: u64,
+}
+
+impl<S: Sink> SinkWriter<S> {
+    fn new(sink: S) -> Self {
+        Self { sink, num_bytes: 0 }
+    }
+}
+
+impl<S: Sink> Write for SinkWriter<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sink.sink(buf)?;
+        self.num_bytes += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    /// The sink is flushed once all batches are written
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::compute::cast;
+    use arrow::datatypes::DataType;
+    use arrow::ipc::reader::{FileReader, StreamReader};
+    use std::sync::Mutex;
+    use tpchgen::generators::{CustomerGenerator, PartGenerator};
+    use tpchgen_arrow::{ColumnTypes, CustomerArrow, PartArrow};
+
+    /// Collects the buffers written to it
+    struct VecSink(Arc<Mutex<Vec<u8>>>);
+
+    impl Sink for VecSink {
+        fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error> {
+            self.0.lock().unwrap().extend_from_slice(buffer);
+            Ok(())
+        }
+
+        fn flush(self) -> Result<(), io::Error> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trips() {
+        let sources =
+            || (1..=3).map(|part| CustomerArrow::new(CustomerGenerator::new(0.01, part, 3)));
+        let expected: Vec<_> = sources().flatten().collect();
+        for stream in [false, true] {
+            let file = Arc::new(Mutex::new(Vec::new()));
+            generate_arrow_ipc(VecSink(Arc::clone(&file)), sources(), 2, stream, None)
+                .await
+                .unwrap();
+            let file = io::Cursor::new(file.lock().unwrap().clone());
+            let batches: Vec<_> = if stream {
+                StreamReader::try_new(file, None).unwrap().collect()
+            } else {
+                FileReader::try_new(file, None).unwrap().collect()
+            };
+            let batches: Vec<_> = batches.into_iter().map(Result::unwrap).collect();
+            assert_eq!(batches, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_dictionaries_to_files() {
+        // the parts have different brands and types, but the same dictionaries
+        let types = ColumnTypes::default().with_dictionary(true);
+        let sources = (1..=3).map(move |part| {
+            PartArrow::new(PartGenerator::new(0.01, part, 3)).with_column_types(&types)
+        });
+        let file = Arc::new(Mutex::new(Vec::new()));
+        generate_arrow_ipc(VecSink(Arc::clone(&file)), sources, 2, false, None)
+            .await
+            .unwrap();
+        let file = io::Cursor::new(file.lock().unwrap().clone());
+        let batches: Vec<_> = FileReader::try_new(file, None)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        let expected: Vec<_> = (1..=3)
+            .flat_map(|part| PartArrow::new(PartGenerator::new(0.01, part, 3)))
+            .collect();
+        assert_eq!(batches.len(), expected.len());
+        for (batch, expected) in batches.iter().zip(&expected) {
+            assert_eq!(
+                batch.column(4).data_type(),
+                &DataType::Dictionary(Box::new(DataType::Int16), Box::new(DataType::Utf8))
+            );
+            for (column, expected) in batch.columns().iter().zip(expected.columns()) {
+                let column = cast(column, expected.data_type()).unwrap();
+                assert_eq!(&column, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! Avro Object Container File output format (`--format=avro`)
+//!
+//! Each file starts with the Avro schema of the table, a record named after
+//! the table with one field per column, so Kafka and other Avro tooling can
+//! read it without a schema registry. Dates use the `date` and decimals the
+//! `decimal` logical types.
+//!
+//! The parts are encoded in parallel, each Arrow batch as one data block
+//! compressed with `--avro-codec`, and written in part order. The sync marker
+//! separating the blocks is derived from the schema rather than random, so
+//! the file is identical for any number of threads.
+//!
+//! See the [specification](https://avro.apache.org/docs/1.11.1/specification/#object-container-files)
+
+use crate::progress::{write_callbacks, ProgressReporter};
+use arrow::array::{Array, AsArray, LargeStringArray, RecordBatch, StringArray, StringViewArray};
+use arrow::datatypes::{
+    DataType, Date32Type, Decimal128Type, Int16Type, Int32Type, Int64Type, SchemaRef,
+};
+use clap::ValueEnum;
+use futures::StreamExt;
+use log::debug;
+use std::io::{self, Write};
+use tokio::sync::mpsc::{Receiver, Sender};
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::generate::Sink;
+use tpchgen_cli::statistics::WriteStatistics;
+
+/// Compression codec of the data blocks of Avro output
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum AvroCodec {
+    Null,
+    Deflate,
+    #[default]
+    Snappy,
+}
+
+impl AvroCodec {
+    /// Returns the value of the `avro.codec` metadata
+    fn name(&self) -> &'static str {
+        match self {
+            AvroCodec::Null => "null",
+            AvroCodec::Deflate => "deflate",
+            AvroCodec::Snappy => "snappy",
+        }
+    }
+
+    /// Compresses the serialized objects of a block
+    fn compress(&self, data: Vec<u8>) -> io::Result<Vec<u8>> {
+        match self {
+            AvroCodec::Null => Ok(data),
+            // raw deflate, without the zlib header
+            AvroCodec::Deflate => {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&data)?;
+                encoder.finish()
+            }
+            // followed by the big endian CRC32 of the uncompressed data
+            AvroCodec::Snappy => {
+                let mut compressed = snap::raw::Encoder::new()
+                    .compress_vec(&data)
+                    .map_err(io::Error::other)?;
+                compressed.extend_from_slice(&crc32fast::hash(&data).to_be_bytes());
+                Ok(compressed)
+            }
+        }
+    }
+}
+
+/// Converts a set of RecordBatchIterators into an Avro file written to
+/// `sink`, with records named `name`
+///
+/// Uses num_threads to generate and encode the parts in parallel. The
+/// blocks are written in the order of the input.
+pub async fn generate_avro<S, I>(
+    sink: S,
+    name: &str,
+    iter_iter: I,
+    num_threads: usize,
+    codec: AvroCodec,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    S: Sink + 'static,
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+{
+    debug!("Generating Avro with {num_threads} threads, using {codec:?} codec");
+    let mut iter_iter = iter_iter.peekable();
+
+    // get schema from the first iterator
+    let Some(first_iter) = iter_iter.peek() else {
+        return Ok(()); // no data shrug
+    };
+    let schema = avro_schema(name, first_iter.schema())?;
+    let sync = sync_marker(&schema);
+
+    let mut statistics =
+        WriteStatistics::new("blocks").with_callbacks(write_callbacks(progress.as_ref()));
+
+    // A blocking task that writes the blocks of each part to the sink
+    let (tx, mut rx): (Sender<EncodedPart>, Receiver<EncodedPart>) =
+        tokio::sync::mpsc::channel(num_threads);
+    let writer_task = tokio::task::spawn_blocking(move || {
+        let mut sink = sink;
+        let header = header(&schema, codec, &sync);
+        sink.sink(&header)?;
+        statistics.increment_bytes(header.len());
+        while let Some(part) = rx.blocking_recv() {
+            sink.sink(&part.bytes)?;
+            statistics.increment_bytes(part.bytes.len());
+            statistics.increment_chunks(part.num_blocks);
+            if let Some(progress) = progress.as_mut() {
+                progress.part_complete(part.num_rows, part.bytes.len() as u64);
+            }
+        }
+        sink.flush()
+    });
+
+    // encode the parts in parallel
+    let mut part_stream = futures::stream::iter(iter_iter)
+        .map(async |iter| {
+            // run on a separate thread
+            tokio::task::spawn(async move { encode_part(iter, codec, sync) })
+                .await
+                .expect("Inner task panicked")
+        })
+        .buffered(num_threads);
+
+    let mut result = Ok(());
+    while let Some(part) = part_stream.next().await {
+        let part = match part {
+            Ok(part) => part,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+        // send the part to the writer task
+        if let Err(e) = tx.send(part).await {
+            debug!("Error sending blocks to writer: {e}");
+            break; // stop early
+        }
+    }
+    // signal the writer task that we are done
+    drop(tx);
+
+    // Wait for the writer task to finish
+    writer_task.await??;
+    result
+}
+
+/// The data blocks of one part
+#[derive(Debug)]
+struct EncodedPart {
+    bytes: Vec<u8>,
+    num_blocks: usize,
+    num_rows: u64,
+}
+
+/// Encodes each batch of one part as a data block
+fn encode_part<I: RecordBatchIterator>(
+    iter: I,
+    codec: AvroCodec,
+    sync: [u8; 16],
+) -> io::Result<EncodedPart> {
+    let mut part = EncodedPart {
+        bytes: Vec::new(),
+        num_blocks: 0,
+        num_rows: 0,
+    };
+    for batch in iter {
+        if batch.num_rows() == 0 {
+            continue;
+        }
+        let data = codec.compress(encode_batch(&batch)?)?;
+        write_long(&mut part.bytes, batch.num_rows() as i64);
+        write_long(&mut part.bytes, data.len() as i64);
+        part.bytes.extend_from_slice(&data);
+        part.bytes.extend_from_slice(&sync);
+        part.num_blocks += 1;
+        part.num_rows += batch.num_rows() as u64;
+    }
+    Ok(part)
+}
+
+/// Returns the schema of the records as JSON
+fn avro_schema(name: &str, schema: &SchemaRef) -> io::Result<String> {
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    for field in schema.fields() {
+        let field_type = match field.data_type() {
+            DataType::Int16 | DataType::Int32 => r#""int""#.to_string(),
+            DataType::Int64 => r#""long""#.to_string(),
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => r#""string""#.to_string(),
+            DataType::Date32 => r#"{"type":"int","logicalType":"date"}"#.to_string(),
+            DataType::Decimal128(precision, scale) => format!(
+                r#"{{"type":"bytes","logicalType":"decimal","precision":{precision},"scale":{scale}}}"#
+            ),
+            data_type => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "Avro output does not support column {} of type {data_type}",
+                        field.name()
+                    ),
+                ))
+            }
+        };
+        fields.push(format!(
+            r#"{{"name":"{}","type":{field_type}}}"#,
+            field.name()
+        ));
+    }
+    Ok(format!(
+        r#"{{"type":"record","name":"{name}","namespace":"tpch","fields":[{}]}}"#,
+        fields.join(",")
+    ))
+}
+
+/// Returns the sync marker of files with `schema`: the first 16 bytes of its
+/// SHA-1
+fn sync_marker(schema: &str) -> [u8; 16] {
+    let digest = sha1_smol::Sha1::from(schema).digest().bytes();
+    digest[..16].try_into().unwrap()
+}
+
+/// Returns the file header: the magic, the metadata and the sync marker
+fn header(schema: &str, codec: AvroCodec, sync: &[u8; 16]) -> Vec<u8> {
+    let mut header = b"Obj\x01".to_vec();
+    // a map with a single block of two entries
+    write_long(&mut header, 2);
+    write_bytes(&mut header, b"avro.schema");
+    write_bytes(&mut header, schema.as_bytes());
+    write_bytes(&mut header, b"avro.codec");
+    write_bytes(&mut header, codec.name().as_bytes());
+    write_long(&mut header, 0);
+    header.extend_from_slice(sync);
+    header
+}
+
+/// The values of a column of a batch
+enum Column<'a> {
+    Int16(&'a [i16]),
+    /// `int` and `date` columns
+    Int32(&'a [i32]),
+    Int64(&'a [i64]),
+    Utf8View(&'a StringViewArray),
+    Utf8(&'a StringArray),
+    LargeUtf8(&'a LargeStringArray),
+    Decimal(&'a [i128]),
+}
+
+/// Serializes the rows of `batch`
+fn encode_batch(batch: &RecordBatch) -> io::Result<Vec<u8>> {
+    if batch.columns().iter().any(|array| array.null_count() > 0) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Avro output does not support null values",
+        ));
+    }
+    let columns: Vec<_> = batch
+        .columns()
+        .iter()
+        .map(|array| match array.data_type() {
+            DataType::Int16 => Column::Int16(array.as_primitive::<Int16Type>().values()),
+            DataType::Int32 => Column::Int32(array.as_primitive::<Int32Type>().values()),
+            DataType::Date32 => Column::Int32(array.as_primitive::<Date32Type>().values()),
+            DataType::Int64 => Column::Int64(array.as_primitive::<Int64Type>().values()),
+            DataType::Utf8View => Column::Utf8View(array.as_string_view()),
+            DataType::Utf8 => Column::Utf8(array.as_string()),
+            DataType::LargeUtf8 => Column::LargeUtf8(array.as_string()),
+            DataType::Decimal128(_, _) => {
+                Column::Decimal(array.as_primitive::<Decimal128Type>().values())
+            }
+            data_type => unreachable!("column type {data_type} checked by avro_schema"),
+        })
+        .collect();
+    let mut out = Vec::with_capacity(batch.get_array_memory_size());
+    for row in 0..batch.num_rows() {
+        for column in &columns {
+            match column {
+                Column::Int16(values) => write_long(&mut out, values[row] as i64),
+                Column::Int32(values) => write_long(&mut out, values[row] as i64),
+                Column::Int64(values) => write_long(&mut out, values[row]),
+                Column::Utf8View(array) => write_bytes(&mut out, array.value(row).as_bytes()),
+                Column::Utf8(array) => write_bytes(&mut out, array.value(row).as_bytes()),
+                Column::LargeUtf8(array) => write_bytes(&mut out, array.value(row).as_bytes()),
+                Column::Decimal(values) => write_decimal(&mut out, values[row]),
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Appends the unscaled `value` of a decimal as the shortest big endian two's
+/// complement bytes
+fn write_decimal(out: &mut Vec<u8>, value: i128) {
+    let bytes = value.to_be_bytes();
+    // skip the leading bytes that only repeat the sign bit of the next byte
+    let mut start = 0;
+    while start < bytes.len() - 1 {
+        let (byte, next) = (bytes[start], bytes[start + 1]);
+        if (byte == 0x00 && next < 0x80) || (byte == 0xff && next >= 0x80) {
+            start += 1;
+        } else {
+            break;
+        }
+    }
+    write_bytes(out, &bytes[start..]);
+}
+
+/// Appends `value` as a zigzag encoded varint
+fn write_long(out: &mut Vec<u8>, value: i64) {
+    let mut value = ((value << 1) ^ (value >> 63)) as u64;
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Appends the length of `value` followed by its bytes
+fn write_bytes(out: &mut Vec<u8>, value: &[u8]) {
+    write_long(out, value.len() as i64);
+    out.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tpchgen::generators::NationGenerator;
+    use tpchgen_arrow::NationArrow;
+
+    #[test]
+    fn encodes_values() {
+        let mut out = Vec::new();
+        // examples of the specification
+        for value in [0, -1, 1, -2, 2, -64, 64] {
+            write_long(&mut out, value);
+        }
+        assert_eq!(out, [0x00, 0x01, 0x02, 0x03, 0x04, 0x7f, 0x80, 0x01]);
+
+        let decimal = |value| {
+            let mut out = Vec::new();
+            write_decimal(&mut out, value);
+            out
+        };
+        assert_eq!(decimal(0), [0x02, 0x00]);
+        assert_eq!(decimal(127), [0x02, 0x7f]);
+        assert_eq!(decimal(128), [0x04, 0x00, 0x80]);
+        assert_eq!(decimal(-1), [0x02, 0xff]);
+        assert_eq!(decimal(-129), [0x04, 0xff, 0x7f]);
+    }
+
+    #[test]
+    fn writes_schema_and_blocks() {
+        let batches = NationArrow::new(NationGenerator::default()).with_batch_size(10);
+        let schema = avro_schema("nation", batches.schema()).unwrap();
+        assert_eq!(
+            schema,
+            r#"{"type":"record","name":"nation","namespace":"tpch","fields":[{"name":"n_nationkey","type":"long"},{"name":"n_name","type":"string"},{"name":"n_regionkey","type":"long"},{"name":"n_comment","type":"string"}]}"#
+        );
+        let sync = sync_marker(&schema);
+        let header = header(&schema, AvroCodec::Null, &sync);
+        assert!(header.starts_with(b"Obj\x01"));
+        assert!(header.ends_with(&sync));
+
+        let part = encode_part(batches, AvroCodec::Null, sync).unwrap();
+        assert_eq!((part.num_blocks, part.num_rows), (3, 25));
+        // the first block has 10 rows, starting with nation 0, ALGERIA
+        assert_eq!(part.bytes[0], 20);
+        let mut first_row = Vec::new();
+        write_long(&mut first_row, 0);
+        write_bytes(&mut first_row, b"ALGERIA");
+        let data_start = 1 + if part.bytes[1] < 0x80 { 1 } else { 2 };
+        assert!(part.bytes[data_start..].starts_with(&first_row));
+        assert!(part.bytes.ends_with(&sync));
+    }
+}
@@ -0,0 +1,335 @@
+//! BSON output format (`--format=bson`)
+//!
+//! Each file is a sequence of BSON documents, one per row, in the format of
+//! the `.bson` files written by `mongodump`, so a directory of tables can be
+//! loaded with `mongorestore`, which names each collection after its file:
+//!
+//! ```shell
+//! mongorestore --db tpch --dir tpch-bson
+//! ```
+//!
+//! Integers are stored as `int32` / `int64`, decimals as `decimal128`, dates
+//! as UTC `datetime`s at midnight, strings as `string`s and NULLs as
+//! `null`s. The documents do not have an `_id` field, so MongoDB adds one
+//! when they are inserted.
+//!
+//! The `documents` subcommand writes nested order documents in this format
+//! with `--format=bson`, see [`crate::documents`].
+//!
+//! See the [specification](https://bsonspec.org/spec.html)
+
+use arrow::array::{Array, AsArray, LargeStringArray, RecordBatch, StringArray, StringViewArray};
+use arrow::datatypes::{DataType, Date32Type, Decimal128Type, Int32Type, Int64Type, SchemaRef};
+use std::io;
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::generate::Source;
+
+/// Milliseconds per day, to convert dates to `datetime`s
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+/// Exponent bias of `decimal128`
+const DECIMAL128_EXPONENT_BIAS: i64 = 6176;
+
+/// Appends the start of a document to `out`, returning its position to pass
+/// to [`end_document`]
+pub fn start_document(out: &mut Vec<u8>) -> usize {
+    let start = out.len();
+    // the size, filled in by end_document
+    out.extend_from_slice(&[0; 4]);
+    start
+}
+
+/// Ends the document started at `start`
+pub fn end_document(out: &mut Vec<u8>, start: usize) {
+    out.push(0);
+    let size = (out.len() - start) as i32;
+    out[start..start + 4].copy_from_slice(&size.to_le_bytes());
+}
+
+/// Appends the element type and name of an element
+fn write_name(out: &mut Vec<u8>, element_type: u8, name: &str) {
+    out.push(element_type);
+    out.extend_from_slice(name.as_bytes());
+    out.push(0);
+}
+
+/// Appends an `int32` element
+pub fn write_i32(out: &mut Vec<u8>, name: &str, value: i32) {
+    write_name(out, 0x10, name);
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Appends an `int64` element
+pub fn write_i64(out: &mut Vec<u8>, name: &str, value: i64) {
+    write_name(out, 0x12, name);
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Appends a `string` element
+pub fn write_string(out: &mut Vec<u8>, name: &str, value: &str) {
+    write_name(out, 0x02, name);
+    out.extend_from_slice(&(value.len() as i32 + 1).to_le_bytes());
+    out.extend_from_slice(value.as_bytes());
+    out.push(0);
+}
+
+/// Appends a `null` element
+pub fn write_null(out: &mut Vec<u8>, name: &str) {
+    write_name(out, 0x0A, name);
+}
+
+/// Appends a UTC `datetime` element for midnight of the date `days` after
+/// the Unix epoch
+pub fn write_date(out: &mut Vec<u8>, name: &str, days: i32) {
+    write_name(out, 0x09, name);
+    out.extend_from_slice(&(days as i64 * MILLIS_PER_DAY).to_le_bytes());
+}
+
+/// Appends a `decimal128` element for the decimal with the unscaled `value`
+/// and `scale`
+///
+/// `decimal128` uses the binary integer decimal (BID) encoding: the sign,
+/// the biased exponent (`-scale`) and the coefficient, which always fits in
+/// the 113 low bits for the decimals of TPC-H.
+pub fn write_decimal(out: &mut Vec<u8>, name: &str, value: i128, scale: i8) {
+    write_name(out, 0x13, name);
+    let coefficient = value.unsigned_abs();
+    let exponent = (DECIMAL128_EXPONENT_BIAS - scale as i64) as u128;
+    let sign = (value < 0) as u128;
+    let bits = sign << 127 | exponent << 113 | coefficient;
+    out.extend_from_slice(&bits.to_le_bytes());
+}
+
+/// Appends the start of an `array` element, returning its position to pass
+/// to [`end_document`]
+///
+/// The elements of the array are named by their index: `"0"`, `"1"`, etc.
+pub fn start_array(out: &mut Vec<u8>, name: &str) -> usize {
+    write_name(out, 0x04, name);
+    start_document(out)
+}
+
+/// Appends the start of an embedded document element to an array, returning
+/// its position to pass to [`end_document`]
+pub fn start_array_document(out: &mut Vec<u8>, index: usize) -> usize {
+    write_name(out, 0x03, &index.to_string());
+    start_document(out)
+}
+
+/// Returns the number of complete documents in `buffer`
+pub fn count_documents(buffer: &[u8]) -> usize {
+    let mut count = 0;
+    let mut offset = 0;
+    while let Some(size) = buffer.get(offset..offset + 4) {
+        let size = i32::from_le_bytes(size.try_into().unwrap()) as usize;
+        if size == 0 || offset + size > buffer.len() {
+            break;
+        }
+        offset += size;
+        count += 1;
+    }
+    count
+}
+
+/// Returns an error if a column of `schema` has a type without a BSON
+/// equivalent
+pub fn check_schema(schema: &SchemaRef) -> io::Result<()> {
+    for field in schema.fields() {
+        match field.data_type() {
+            DataType::Int32
+            | DataType::Int64
+            | DataType::Date32
+            | DataType::Decimal128(_, _)
+            | DataType::Utf8View
+            | DataType::Utf8
+            | DataType::LargeUtf8 => {}
+            data_type => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "BSON output does not support column {} of type {data_type}",
+                        field.name()
+                    ),
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The values of a column of a batch
+enum Column<'a> {
+    Int32(&'a [i32]),
+    Int64(&'a [i64]),
+    Date(&'a [i32]),
+    Decimal(&'a [i128], i8),
+    Utf8View(&'a StringViewArray),
+    Utf8(&'a StringArray),
+    LargeUtf8(&'a LargeStringArray),
+}
+
+/// A [`Source`] that writes the batches of a [`RecordBatchIterator`] as one
+/// BSON document per row
+///
+/// The schema of the batches must be checked with [`check_schema`].
+pub struct BsonSource<I> {
+    batches: I,
+}
+
+impl<I: RecordBatchIterator> BsonSource<I> {
+    pub fn new(batches: I) -> Self {
+        Self { batches }
+    }
+}
+
+impl<I: RecordBatchIterator> Source for BsonSource<I> {
+    fn header(&self, buffer: Vec<u8>) -> Vec<u8> {
+        // BSON files do not have a header
+        buffer
+    }
+
+    fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+        for batch in self.batches {
+            encode_batch(&mut buffer, &batch);
+        }
+        buffer
+    }
+}
+
+/// Appends a document for each row of `batch` to `out`
+fn encode_batch(out: &mut Vec<u8>, batch: &RecordBatch) {
+    let schema = batch.schema();
+    let columns: Vec<_> = batch
+        .columns()
+        .iter()
+        .map(|array| {
+            let column = match array.data_type() {
+                DataType::Int32 => Column::Int32(array.as_primitive::<Int32Type>().values()),
+                DataType::Int64 => Column::Int64(array.as_primitive::<Int64Type>().values()),
+                DataType::Date32 => Column::Date(array.as_primitive::<Date32Type>().values()),
+                DataType::Decimal128(_, scale) => {
+                    Column::Decimal(array.as_primitive::<Decimal128Type>().values(), *scale)
+                }
+                DataType::Utf8View => Column::Utf8View(array.as_string_view()),
+                DataType::Utf8 => Column::Utf8(array.as_string()),
+                DataType::LargeUtf8 => Column::LargeUtf8(array.as_string()),
+                data_type => unreachable!("column type {data_type} checked by check_schema"),
+            };
+            (column, array.nulls())
+        })
+        .collect();
+    for row in 0..batch.num_rows() {
+        let start = start_document(out);
+        for (field, (column, nulls)) in schema.fields().iter().zip(&columns) {
+            let name = field.name();
+            if nulls.is_some_and(|nulls| nulls.is_null(row)) {
+                write_null(out, name);
+                continue;
+            }
+            match column {
+                Column::Int32(values) => write_i32(out, name, values[row]),
+                Column::Int64(values) => write_i64(out, name, values[row]),
+                Column::Date(values) => write_date(out, name, values[row]),
+                Column::Decimal(values, scale) => write_decimal(out, name, values[row], *scale),
+                Column::Utf8View(array) => write_string(out, name, array.value(row)),
+                Column::Utf8(array) => write_string(out, name, array.value(row)),
+                Column::LargeUtf8(array) => write_string(out, name, array.value(row)),
+            }
+        }
+        end_document(out, start);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int64Array;
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+    use tpchgen::generators::RegionGenerator;
+    use tpchgen_arrow::RegionArrow;
+
+    #[test]
+    fn encodes_elements() {
+        // examples of the specification and the BSON corpus
+        let mut out = Vec::new();
+        let start = start_document(&mut out);
+        write_string(&mut out, "hello", "world");
+        end_document(&mut out, start);
+        assert_eq!(
+            out,
+            b"\x16\x00\x00\x00\x02hello\x00\x06\x00\x00\x00world\x00\x00"
+        );
+
+        let mut out = Vec::new();
+        write_decimal(&mut out, "d", 10, 1);
+        assert_eq!(
+            out,
+            b"\x13d\x00\x0a\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x3e\x30"
+        );
+        let mut out = Vec::new();
+        write_decimal(&mut out, "d", -1, 0);
+        assert_eq!(
+            &out[3..],
+            b"\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x40\xb0"
+        );
+
+        let mut out = Vec::new();
+        write_date(&mut out, "d", 1);
+        assert_eq!(&out[3..], 86_400_000i64.to_le_bytes());
+    }
+
+    #[test]
+    fn writes_document_per_row() {
+        let output =
+            BsonSource::new(RegionArrow::new(RegionGenerator::default())).create(Vec::new());
+        assert_eq!(count_documents(&output), 5);
+        assert_eq!(count_documents(&output[..output.len() - 1]), 4);
+        // the first region: {r_regionkey: 0, r_name: "AFRICA", ...}
+        let mut expected = vec![0; 4];
+        write_i64(&mut expected, "r_regionkey", 0);
+        write_string(&mut expected, "r_name", "AFRICA");
+        assert_eq!(output[4..expected.len()], expected[4..]);
+    }
+
+    #[test]
+    fn writes_nulls() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("key", DataType::Int64, false),
+            Field::new("name", DataType::Utf8View, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(StringViewArray::from(vec![Some("a"), None])),
+            ],
+        )
+        .unwrap();
+        let mut output = Vec::new();
+        encode_batch(&mut output, &batch);
+        let mut expected = Vec::new();
+        for (key, name) in [(1, Some("a")), (2, None)] {
+            let start = start_document(&mut expected);
+            write_i64(&mut expected, "key", key);
+            match name {
+                Some(name) => write_string(&mut expected, "name", name),
+                None => write_null(&mut expected, "name"),
+            }
+            end_document(&mut expected, start);
+        }
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn rejects_unsupported_columns() {
+        assert!(check_schema(RegionArrow::new(RegionGenerator::default()).schema()).is_ok());
+        let schema = Arc::new(Schema::new(vec![Field::new("f", DataType::Float64, false)]));
+        let error = check_schema(&schema).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(
+            error.to_string(),
+            "BSON output does not support column f of type Float64"
+        );
+    }
+}
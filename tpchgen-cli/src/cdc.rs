@@ -0,0 +1,504 @@
+//! Change data capture events (`tpchgen-cli cdc`)
+//!
+//! Writes the changes to ORDERS and LINEITEM as JSON lines of change events
+//! in the format of Debezium, for testing CDC pipelines, e.g. with Kafka
+//! Connect or Flink, end to end:
+//!
+//! ```text
+//! {"before":null,"after":{"o_orderkey":9,"o_custkey":...},"source":{"version":"1.1.1","connector":"tpchgen","name":"tpchgen","ts_ms":768960000000,"db":"tpch","table":"orders"},"op":"c","ts_ms":768960000000,"transaction":{"id":"rf1.1","total_order":1,"data_collection_order":1}}
+//! ```
+//!
+//! `op` is `c` for inserts, `u` for updates and `d` for deletes, with the
+//! rows before and after the change in `before` and `after`. Decimals are
+//! written as JSON numbers and dates as `YYYY-MM-DD` strings, as in
+//! [`crate::documents`]. `transaction` has the id of the transaction of the
+//! event, the position of the event in the transaction and its position
+//! among the events of the same table. `ts_ms` is the date of the change at
+//! midnight UTC: the order date for inserts and deletes, and the ship or
+//! receipt date for updates.
+//!
+//! The events of the refresh sets come first. Refresh set `n` writes two
+//! transactions:
+//!
+//! * `rf1.<n>` inserts the orders of refresh function RF1, each followed by
+//!   its line items, see [`OrderGenerator::with_update_set`]
+//! * `rf2.<n>` deletes the orders of refresh function RF2, each after its
+//!   line items, see [`OrderGenerator::with_delete_set`]
+//!
+//! `--live-orders N` then writes `N` new orders of a [`LiveOrderGenerator`],
+//! with increasing order dates after those of the base orders. Each order is
+//! inserted in its own transaction `order.<key>` as it is placed, as
+//! generated: open, with none of its line items shipped or returned. As the
+//! order dates pass the ship and receipt dates of the line items, they are
+//! updated in transactions `update.<key>.<date>`: the line items that ship
+//! change `l_linestatus` to `F`, with the change of `o_orderstatus` if any,
+//! and the line items that are received change `l_returnflag` to `R` or `A`,
+//! every other one by line number as half of the received line items of the
+//! specification are returned. The updates due after the last order are
+//! written at the end, after which every order is fulfilled.
+//!
+//! [`OrderGenerator::with_update_set`]: tpchgen::generators::OrderGenerator::with_update_set
+//! [`OrderGenerator::with_delete_set`]: tpchgen::generators::OrderGenerator::with_delete_set
+
+use crate::documents::{write_lineitem_fields, write_order, Json};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::io::Write;
+use tpchgen::dates::TPCHDate;
+use tpchgen::generators::{
+    LineItem, LineItemGenerator, LineStatus, LiveOrderGenerator, Order, OrderGenerator,
+    OrderStatus, ReturnFlag,
+};
+use tpchgen_cli::generate::Source;
+
+/// Arguments of the `cdc` subcommand
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct CdcArgs {
+    /// Number of refresh sets whose inserts and deletes are written
+    /// (default: 1)
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub refresh_sets: i32,
+
+    /// Number of new orders written after the refresh sets, with the
+    /// updates of their line items (default: 0)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub live_orders: usize,
+}
+
+/// Milliseconds per day, for `ts_ms`
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// The change of an event
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Create,
+    Update,
+    Delete,
+}
+
+impl Op {
+    /// Returns the Debezium code of the change
+    fn code(self) -> &'static str {
+        match self {
+            Op::Create => "c",
+            Op::Update => "u",
+            Op::Delete => "d",
+        }
+    }
+}
+
+/// The image of a row before or after a change
+#[derive(Clone, Copy)]
+enum Row<'r> {
+    Order(&'r Order<'r>),
+    LineItem(&'r LineItem<'r>),
+}
+
+impl Row<'_> {
+    /// Returns the name of the table of the row
+    fn table(&self) -> &'static str {
+        match self {
+            Row::Order(_) => "orders",
+            Row::LineItem(_) => "lineitem",
+        }
+    }
+
+    /// Appends the row as a JSON object
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            Row::Order(order) => {
+                write_order(out, order);
+                out.push(b'}');
+            }
+            Row::LineItem(item) => {
+                write!(out, r#"{{"l_orderkey":{},"#, item.l_orderkey)
+                    .expect("writing to memory is infallible");
+                write_lineitem_fields(out, item);
+                out.push(b'}');
+            }
+        }
+    }
+}
+
+/// Writes the events of one transaction
+struct Transaction<'a> {
+    out: &'a mut Vec<u8>,
+    id: String,
+    events: usize,
+    orders: usize,
+    line_items: usize,
+}
+
+impl<'a> Transaction<'a> {
+    fn new(out: &'a mut Vec<u8>, id: String) -> Self {
+        Self {
+            out,
+            id,
+            events: 0,
+            orders: 0,
+            line_items: 0,
+        }
+    }
+
+    /// Appends an event that changes the row `before` to `after`, on `date`
+    fn event(&mut self, op: Op, before: Option<Row>, after: Option<Row>, date: TPCHDate) {
+        let table = before.or(after).expect("a change has a row").table();
+        self.events += 1;
+        let table_events = match table {
+            "orders" => &mut self.orders,
+            _ => &mut self.line_items,
+        };
+        *table_events += 1;
+        let table_events = *table_events;
+        let out = &mut *self.out;
+        out.extend_from_slice(br#"{"before":"#);
+        write_image(out, before);
+        out.extend_from_slice(br#","after":"#);
+        write_image(out, after);
+        let ts_ms = date.to_unix_epoch() as i64 * MILLIS_PER_DAY;
+        writeln!(
+            out,
+            r#","source":{{"version":{},"connector":"tpchgen","name":"tpchgen","ts_ms":{ts_ms},"db":"tpch","table":"{table}"}},"op":"{}","ts_ms":{ts_ms},"transaction":{{"id":{},"total_order":{},"data_collection_order":{table_events}}}}}"#,
+            Json(env!("CARGO_PKG_VERSION")),
+            op.code(),
+            Json(&self.id),
+            self.events,
+        )
+        .expect("writing to memory is infallible");
+    }
+}
+
+/// Appends `row`, or `null` if there is none
+fn write_image(out: &mut Vec<u8>, row: Option<Row>) {
+    match row {
+        Some(row) => row.write(out),
+        None => out.extend_from_slice(b"null"),
+    }
+}
+
+/// A [`Source`] that writes the events of the inserts and deletes of one
+/// refresh set
+pub struct RefreshSetCdcSource {
+    orders: OrderGenerator<'static>,
+    lineitems: LineItemGenerator<'static>,
+    update_set: i32,
+}
+
+impl RefreshSetCdcSource {
+    /// Create a source for the (1 based) refresh set `update_set` of the
+    /// base orders and line items of the generators
+    pub fn new(
+        orders: OrderGenerator<'static>,
+        lineitems: LineItemGenerator<'static>,
+        update_set: i32,
+    ) -> Self {
+        Self {
+            orders,
+            lineitems,
+            update_set,
+        }
+    }
+}
+
+impl Source for RefreshSetCdcSource {
+    fn header(&self, buffer: Vec<u8>) -> Vec<u8> {
+        // JSON lines do not have a header
+        buffer
+    }
+
+    fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+        let set = self.update_set;
+        let mut rf1 = Transaction::new(&mut buffer, format!("rf1.{set}"));
+        let orders = self.orders.clone().with_update_set(set);
+        let mut lineitems = self
+            .lineitems
+            .clone()
+            .with_update_set(set)
+            .iter()
+            .peekable();
+        for order in orders.iter() {
+            let date = order.o_orderdate;
+            rf1.event(Op::Create, None, Some(Row::Order(&order)), date);
+            while let Some(item) = lineitems.next_if(|l| l.l_orderkey == order.o_orderkey) {
+                rf1.event(Op::Create, None, Some(Row::LineItem(&item)), date);
+            }
+        }
+
+        let mut rf2 = Transaction::new(&mut buffer, format!("rf2.{set}"));
+        let orders = self.orders.with_delete_set(set);
+        let mut lineitems = self.lineitems.with_delete_set(set).iter().peekable();
+        for order in orders.iter() {
+            let date = order.o_orderdate;
+            while let Some(item) = lineitems.next_if(|l| l.l_orderkey == order.o_orderkey) {
+                rf2.event(Op::Delete, Some(Row::LineItem(&item)), None, date);
+            }
+            rf2.event(Op::Delete, Some(Row::Order(&order)), None, date);
+        }
+        buffer
+    }
+}
+
+/// A [`Source`] of change events, of a refresh set or of new orders
+pub enum CdcSource {
+    RefreshSet(Box<RefreshSetCdcSource>),
+    Live(LiveCdcSource),
+}
+
+impl Source for CdcSource {
+    fn header(&self, buffer: Vec<u8>) -> Vec<u8> {
+        // JSON lines do not have a header
+        buffer
+    }
+
+    fn create(self, buffer: Vec<u8>) -> Vec<u8> {
+        match self {
+            CdcSource::RefreshSet(source) => (*source).create(buffer),
+            CdcSource::Live(source) => source.create(buffer),
+        }
+    }
+}
+
+/// Returns the return flag of `item` once it is received: every other line
+/// item, by line number, is returned (`R`) and the others accepted (`A`)
+fn return_flag(item: &LineItem) -> ReturnFlag {
+    if (item.l_orderkey + item.l_linenumber as i64) % 2 == 0 {
+        ReturnFlag::Returned
+    } else {
+        ReturnFlag::Accepted
+    }
+}
+
+/// A change of a line item of a new order, when the order dates pass its
+/// ship or receipt date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Change {
+    Ship,
+    Return,
+}
+
+/// A new order whose line items have changes that are not yet written
+struct OpenOrder<'a> {
+    /// The rows as of the last change
+    order: Order<'a>,
+    line_items: Vec<LineItem<'a>>,
+    pending: usize,
+}
+
+/// A [`Source`] that writes the events of the new orders of a
+/// [`LiveOrderGenerator`] and the updates of their line items
+pub struct LiveCdcSource {
+    generator: LiveOrderGenerator<'static>,
+    count: usize,
+}
+
+impl LiveCdcSource {
+    /// Create a source for the first `count` orders of `generator`
+    pub fn new(generator: LiveOrderGenerator<'static>, count: usize) -> Self {
+        Self { generator, count }
+    }
+}
+
+/// The pending changes of the new orders, by date, order key and line item
+type Changes = BinaryHeap<Reverse<(TPCHDate, i64, usize, Change)>>;
+
+impl Source for LiveCdcSource {
+    fn header(&self, buffer: Vec<u8>) -> Vec<u8> {
+        // JSON lines do not have a header
+        buffer
+    }
+
+    fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+        let mut changes = Changes::new();
+        let mut open = HashMap::new();
+        for live in self.generator.iter().take(self.count) {
+            let date = live.order.o_orderdate;
+            write_changes(&mut buffer, &mut changes, &mut open, Some(date));
+
+            // the order as placed, which is after the current date of the
+            // specification, so none of its line items has shipped
+            let key = live.order.o_orderkey;
+            let mut transaction = Transaction::new(&mut buffer, format!("order.{key}"));
+            transaction.event(Op::Create, None, Some(Row::Order(&live.order)), date);
+            for item in &live.line_items {
+                transaction.event(Op::Create, None, Some(Row::LineItem(item)), date);
+            }
+
+            // each line item ships and is received later
+            for (index, item) in live.line_items.iter().enumerate() {
+                changes.push(Reverse((item.l_shipdate, key, index, Change::Ship)));
+                changes.push(Reverse((item.l_receiptdate, key, index, Change::Return)));
+            }
+            let order = OpenOrder {
+                order: live.order,
+                pending: 2 * live.line_items.len(),
+                line_items: live.line_items,
+            };
+            open.insert(key, order);
+        }
+        write_changes(&mut buffer, &mut changes, &mut open, None);
+        buffer
+    }
+}
+
+/// Appends the updates of the changes before `until`, or all changes if
+/// `until` is `None`, one transaction per order and date
+fn write_changes(
+    out: &mut Vec<u8>,
+    changes: &mut Changes,
+    open: &mut HashMap<i64, OpenOrder>,
+    until: Option<TPCHDate>,
+) {
+    while let Some(&Reverse((date, key, _, _))) = changes.peek() {
+        if until.is_some_and(|until| date >= until) {
+            return;
+        }
+        let order = open.get_mut(&key).expect("changes are of open orders");
+        let mut transaction = Transaction::new(out, format!("update.{key}.{date}"));
+        while let Some(&Reverse((_, _, index, change))) = changes
+            .peek()
+            .filter(|Reverse((d, k, _, _))| *d == date && *k == key)
+        {
+            changes.pop();
+            let before = order.line_items[index].clone();
+            let item = &mut order.line_items[index];
+            match change {
+                Change::Ship => item.l_linestatus = LineStatus::Fulfilled,
+                Change::Return => item.l_returnflag = return_flag(item),
+            }
+            transaction.event(
+                Op::Update,
+                Some(Row::LineItem(&before)),
+                Some(Row::LineItem(item)),
+                date,
+            );
+            order.pending -= 1;
+            if change == Change::Ship {
+                let shipped = order
+                    .line_items
+                    .iter()
+                    .filter(|item| item.l_linestatus == LineStatus::Fulfilled)
+                    .count();
+                let status = if shipped == order.line_items.len() {
+                    OrderStatus::Fulfilled
+                } else {
+                    OrderStatus::Pending
+                };
+                if status != order.order.o_orderstatus {
+                    let before = order.order.clone();
+                    order.order.o_orderstatus = status;
+                    transaction.event(
+                        Op::Update,
+                        Some(Row::Order(&before)),
+                        Some(Row::Order(&order.order)),
+                        date,
+                    );
+                }
+            }
+        }
+        if order.pending == 0 {
+            open.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tpchgen::generators::DeleteKeyGenerator;
+
+    /// Returns the values of `field` of the events in `output`
+    fn values<'a>(output: &'a str, field: &str) -> Vec<&'a str> {
+        let pattern = format!(r#""{field}":"#);
+        output
+            .lines()
+            .map(|line| {
+                let start = line.rfind(&pattern).unwrap() + pattern.len();
+                let end = line[start..].find([',', '}']).unwrap();
+                &line[start..start + end]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn writes_refresh_set() {
+        let source = RefreshSetCdcSource::new(
+            OrderGenerator::new(0.01, 1, 1),
+            LineItemGenerator::new(0.01, 1, 1),
+            2,
+        );
+        let output = String::from_utf8(source.create(Vec::new())).unwrap();
+        let first = output.lines().next().unwrap();
+        assert!(first.starts_with(r#"{"before":null,"after":{"o_orderkey":"#));
+        assert!(first.contains(r#""table":"orders"},"op":"c","#));
+        let ops = values(&output, "op");
+        let inserts = ops.iter().filter(|&&op| op == r#""c""#).count();
+        let deletes = ops.iter().filter(|&&op| op == r#""d""#).count();
+        assert_eq!(inserts + deletes, ops.len());
+        let inserted = LineItemGenerator::new(0.01, 1, 1).with_update_set(2);
+        assert_eq!(inserts, 15 + inserted.iter().count());
+        let deleted = LineItemGenerator::new(0.01, 1, 1).with_delete_set(2);
+        assert_eq!(deletes, 15 + deleted.iter().count());
+        // the events are numbered within each transaction
+        let ids = values(&output, "id");
+        let positions = values(&output, "total_order");
+        assert_eq!(ids[inserts - 1], r#""rf1.2""#);
+        assert_eq!(positions[inserts - 1], inserts.to_string());
+        assert_eq!(ids[inserts], r#""rf2.2""#);
+        assert_eq!(positions[inserts], "1");
+        // the deleted orders are the base orders
+        let last = output.lines().last().unwrap();
+        let key = DeleteKeyGenerator::new(0.01, 1, 1)
+            .with_update_set(2)
+            .iter()
+            .last();
+        let before = format!(r#"{{"before":{{"o_orderkey":{},"#, key.unwrap());
+        assert!(last.starts_with(&before), "{last}");
+        assert!(last.contains(r#""after":null"#));
+    }
+
+    #[test]
+    fn updates_live_orders() {
+        let generator = LiveOrderGenerator::new(0.01).with_orders_per_day(1);
+        let source = LiveCdcSource::new(generator.clone(), 500);
+        let output = String::from_utf8(source.create(Vec::new())).unwrap();
+        let live: Vec<_> = generator.iter().take(500).collect();
+        let line_items: usize = live.iter().map(|l| l.line_items.len()).sum();
+        let ops = values(&output, "op");
+        let inserts = ops.iter().filter(|&&op| op == r#""c""#).count();
+        assert_eq!(inserts, live.len() + line_items);
+
+        // the dates of the events never decrease
+        let dates = values(&output, "ts_ms");
+        let dates: Vec<i64> = dates.iter().map(|d| d.parse().unwrap()).collect();
+        assert!(dates.windows(2).all(|w| w[0] <= w[1]));
+
+        // after all updates, the orders are fulfilled
+        let mut rows = HashMap::new();
+        for line in output.lines() {
+            let start = line.find(r#""after":"#).unwrap() + 8;
+            let end = line.find(r#","source":"#).unwrap();
+            let after = &line[start..end];
+            let key_end = after[15..].find(',').unwrap() + 15;
+            let key = after[..key_end].to_string();
+            rows.insert(key, after.to_string());
+        }
+        for l in &live {
+            let order = Order {
+                o_orderstatus: OrderStatus::Fulfilled,
+                ..l.order.clone()
+            };
+            let mut expected = Vec::new();
+            Row::Order(&order).write(&mut expected);
+            let expected = String::from_utf8(expected).unwrap();
+            let key = format!(r#"{{"o_orderkey":{}"#, l.order.o_orderkey);
+            assert_eq!(rows[&key], expected);
+        }
+        // each line item ships and is received, and each order with more
+        // than one line item is pending before it is fulfilled
+        let updates = ops.iter().filter(|&&op| op == r#""u""#).count();
+        let pending = live.iter().filter(|l| l.line_items.len() > 1).count();
+        assert_eq!(updates, 2 * line_items + live.len() + pending);
+        assert!(output.contains(r#""l_returnflag":"R""#));
+        assert!(output.contains(r#""l_returnflag":"A""#));
+    }
+}
@@ -0,0 +1,150 @@
+//! Checksum sidecar files (`--checksums sha256`)
+//!
+//! With `--checksums sha256` each output file, e.g. `lineitem.parquet`, gets
+//! a `lineitem.parquet.sha256` file next to it in the format of `sha256sum`,
+//! so the file can be verified after it is copied with:
+//!
+//! ```shell
+//! sha256sum -c lineitem.parquet.sha256
+//! ```
+//!
+//! The hash is computed from the bytes as they are written, so the output is
+//! not read again. The same hash is listed in the manifest (`--manifest`),
+//! see [`crate::manifest`].
+
+use crate::manifest::Manifest;
+use clap::ValueEnum;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Checksum algorithm of the sidecar files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Checksum {
+    /// SHA-256, in `<file>.sha256`
+    Sha256,
+}
+
+impl Checksum {
+    /// Returns the path of the sidecar file of `path`, e.g.
+    /// `lineitem.tbl.sha256`
+    pub fn sidecar_path(&self, path: &Path) -> PathBuf {
+        let mut sidecar = path.as_os_str().to_owned();
+        match self {
+            Checksum::Sha256 => sidecar.push(".sha256"),
+        }
+        PathBuf::from(sidecar)
+    }
+}
+
+/// Returns the hex encoded SHA-256 of `bytes`
+pub fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Writes the sidecar file of `path`, with the hex encoded `digest` of its
+/// contents
+pub fn write_sidecar(checksum: Checksum, path: &Path, digest: &str) -> io::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    // the format of sha256sum: the digest, two spaces and the file name
+    fs::write(checksum.sidecar_path(path), format!("{digest}  {name}\n"))
+}
+
+/// An output file that computes the checksum of its contents as they are
+/// written, and writes the sidecar file and records the file in the
+/// manifest when it is finished
+pub struct ChecksumFile {
+    file: File,
+    path: PathBuf,
+    checksum: Option<Checksum>,
+    /// The manifest, and the index of the file in it
+    manifest: Option<(Manifest, usize)>,
+    hasher: Option<Sha256>,
+    bytes: u64,
+}
+
+impl ChecksumFile {
+    /// Creates the file `path`, with a sidecar file if `checksum` is set and
+    /// recorded in `manifest`, if any
+    pub fn create(
+        path: PathBuf,
+        checksum: Option<Checksum>,
+        manifest: Option<&Manifest>,
+    ) -> io::Result<Self> {
+        let file = File::create(&path)?;
+        let manifest = manifest.map(|m| (m.clone(), m.file_started(&path)));
+        let hasher = (checksum.is_some() || manifest.is_some()).then(Sha256::new);
+        Ok(Self {
+            file,
+            path,
+            checksum,
+            manifest,
+            hasher,
+            bytes: 0,
+        })
+    }
+
+    /// Flushes the file, writes its sidecar file and records its size and
+    /// checksum in the manifest, returning the file
+    ///
+    /// If the file is dropped without being finished, e.g. after an error,
+    /// no sidecar file is written.
+    pub fn finish(mut self) -> io::Result<File> {
+        self.file.flush()?;
+        if let Some(hasher) = self.hasher.take() {
+            let digest = format!("{:x}", hasher.finalize());
+            if let Some(checksum) = self.checksum {
+                write_sidecar(checksum, &self.path, &digest)?;
+            }
+            if let Some((manifest, index)) = &self.manifest {
+                manifest.file_finished(*index, self.bytes, digest)?;
+            }
+        }
+        Ok(self.file)
+    }
+}
+
+impl Write for ChecksumFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&buf[..written]);
+        }
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_sidecar() {
+        let dir = std::env::temp_dir().join(format!("tpchgen-checksum-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("region.tbl");
+        let manifest = Manifest::new(dir.clone(), 1.0, "tbl");
+        let mut file =
+            ChecksumFile::create(path.clone(), Some(Checksum::Sha256), Some(&manifest)).unwrap();
+        file.write_all(b"hello ").unwrap();
+        file.write_all(b"world\n").unwrap();
+        manifest.part_complete(1, 1);
+        file.finish().unwrap();
+        // echo "hello world" | sha256sum
+        let expected =
+            "a948904f2f0f479b8f8197694b30184b0d2ed1c1cd2a1ec0fb85d299a192a447  region.tbl\n";
+        let sidecar = fs::read_to_string(dir.join("region.tbl.sha256")).unwrap();
+        assert_eq!(sidecar, expected);
+        let digest = sha256(b"hello world\n");
+        assert_eq!(sidecar, format!("{digest}  region.tbl\n"));
+        let files = manifest.files();
+        assert_eq!((files[0].bytes, &files[0].sha256), (12, &digest));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,629 @@
+//! Column statistics of the generated data (`tpchgen-cli stats`)
+//!
+//! The data is deterministic, so the statistics an optimizer would gather
+//! with `ANALYZE` can be computed once while the tables are generated,
+//! without writing or scanning them. For each column:
+//!
+//! * the number of NULLs, the minimum and the maximum, which are exact
+//! * the number of distinct values (NDV), which is exact for single column
+//!   primary keys and otherwise estimated with a [HyperLogLog] sketch (about
+//!   1% error)
+//! * an equi-depth histogram of `--histogram-buckets` buckets, computed from
+//!   a sample of about 30000 rows
+//! * the average and maximum length of strings
+//!
+//! The rows are sampled by a hash of their primary key, so the statistics do
+//! not depend on the number of parts or threads.
+//!
+//! The statistics are written as JSON (`--stats-format json`), or as the
+//! statements setting them in an engine:
+//!
+//! * `postgres`: `ALTER TABLE ... ALTER COLUMN ... SET (n_distinct = ...)`,
+//!   which the next `ANALYZE` uses instead of its own estimate
+//! * `hive`: `ALTER TABLE ... UPDATE STATISTICS`, for the Hive metastore
+//!   used by Hive, Trino and Spark
+//!
+//! [HyperLogLog]: https://en.wikipedia.org/wiki/HyperLogLog
+
+use crate::surrogate_key::primary_key_columns;
+use crate::Table;
+use arrow::array::{Array, AsArray, RecordBatch};
+use arrow::datatypes::{DataType, Date32Type, Decimal128Type, Int32Type, Int64Type, SchemaRef};
+use arrow::temporal_conversions::date32_to_datetime;
+use clap::ValueEnum;
+use serde_json::json;
+use std::fmt::Write;
+use std::io;
+
+/// Approximate number of rows sampled for the histograms of each table
+const SAMPLE_ROWS: u64 = 30_000;
+
+/// log2 of the number of HyperLogLog registers (16 KiB per column)
+const HLL_PRECISION: u32 = 14;
+
+/// Arguments of the `stats` subcommand
+#[derive(Debug, clap::Args)]
+pub struct StatsArgs {
+    /// Format of the statistics
+    #[arg(long, default_value = "json")]
+    pub stats_format: StatsFormat,
+
+    /// Number of buckets of the histogram of each column, 0 for none
+    #[arg(long, default_value_t = 10)]
+    pub histogram_buckets: usize,
+}
+
+/// Format of the column statistics
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum StatsFormat {
+    /// JSON with all statistics
+    Json,
+    /// PostgreSQL `ALTER TABLE ... SET (n_distinct = ...)` statements
+    Postgres,
+    /// Hive metastore `ALTER TABLE ... UPDATE STATISTICS` statements
+    Hive,
+}
+
+impl StatsArgs {
+    /// Returns the name of the file the statistics are written to
+    pub fn file_name(&self) -> &'static str {
+        match self.stats_format {
+            StatsFormat::Json => "column_stats.json",
+            StatsFormat::Postgres | StatsFormat::Hive => "column_stats.sql",
+        }
+    }
+}
+
+/// A (non NULL) value of a column
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Value {
+    Int(i64),
+    /// A decimal with its scale
+    Decimal(i128, i8),
+    /// Days since the Unix epoch
+    Date(i32),
+    String(String),
+}
+
+impl Value {
+    /// Returns the value as a JSON number or string
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Int(value) => json!(value),
+            _ => json!(self.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{value}"),
+            Value::Decimal(value, scale) => {
+                let divisor = 10i128.pow(*scale as u32);
+                let sign = if *value < 0 { "-" } else { "" };
+                let (units, fraction) = (value.abs() / divisor, value.abs() % divisor);
+                write!(
+                    f,
+                    "{sign}{units}.{fraction:0width$}",
+                    width = *scale as usize
+                )
+            }
+            Value::Date(days) => match date32_to_datetime(*days) {
+                Some(date) => write!(f, "{}", date.date()),
+                None => write!(f, "{days}"),
+            },
+            Value::String(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Returns the value of row `row` of `array`, or `None` if it is NULL
+fn value(array: &dyn Array, row: usize) -> Option<Value> {
+    if array.is_null(row) {
+        return None;
+    }
+    Some(match array.data_type() {
+        DataType::Int32 => Value::Int(array.as_primitive::<Int32Type>().value(row) as i64),
+        DataType::Int64 => Value::Int(array.as_primitive::<Int64Type>().value(row)),
+        DataType::Decimal128(_, scale) => {
+            Value::Decimal(array.as_primitive::<Decimal128Type>().value(row), *scale)
+        }
+        DataType::Date32 => Value::Date(array.as_primitive::<Date32Type>().value(row)),
+        DataType::Utf8View => Value::String(array.as_string_view().value(row).to_string()),
+        DataType::Utf8 => Value::String(array.as_string::<i32>().value(row).to_string()),
+        other => unreachable!("column type {other} checked by TableCollector::new"),
+    })
+}
+
+/// Mixes the bits of `x` (the finalizer of SplitMix64)
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Returns a hash of `value` that is the same on every platform and run
+fn hash(value: &Value) -> u64 {
+    match value {
+        Value::Int(value) => mix(*value as u64),
+        Value::Decimal(value, _) => mix(*value as u64 ^ mix((*value >> 64) as u64)),
+        Value::Date(value) => mix(*value as u64),
+        // FNV-1a
+        Value::String(value) => mix(value.bytes().fold(0xcbf29ce484222325, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x100000001b3)
+        })),
+    }
+}
+
+/// Statistics of one column, collected from the batches of one or more parts
+#[derive(Debug, Clone)]
+struct ColumnCollector {
+    null_count: u64,
+    min: Option<Value>,
+    max: Option<Value>,
+    /// HyperLogLog registers
+    registers: Vec<u8>,
+    total_length: u64,
+    max_length: u64,
+    sample: Vec<Value>,
+}
+
+impl ColumnCollector {
+    fn new() -> Self {
+        Self {
+            null_count: 0,
+            min: None,
+            max: None,
+            registers: vec![0; 1 << HLL_PRECISION],
+            total_length: 0,
+            max_length: 0,
+            sample: vec![],
+        }
+    }
+
+    fn add(&mut self, value: Option<Value>, sampled: bool) {
+        let Some(value) = value else {
+            self.null_count += 1;
+            return;
+        };
+        let h = hash(&value);
+        let register = (h >> (64 - HLL_PRECISION)) as usize;
+        let rank = ((h << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() as u8 + 1;
+        self.registers[register] = self.registers[register].max(rank);
+        if let Value::String(s) = &value {
+            self.total_length += s.len() as u64;
+            self.max_length = self.max_length.max(s.len() as u64);
+        }
+        if self.min.as_ref().is_none_or(|min| value < *min) {
+            self.min = Some(value.clone());
+        }
+        if self.max.as_ref().is_none_or(|max| value > *max) {
+            self.max = Some(value.clone());
+        }
+        if sampled {
+            self.sample.push(value);
+        }
+    }
+
+    fn merge(&mut self, other: ColumnCollector) {
+        self.null_count += other.null_count;
+        for (a, b) in self.registers.iter_mut().zip(other.registers) {
+            *a = (*a).max(b);
+        }
+        self.total_length += other.total_length;
+        self.max_length = self.max_length.max(other.max_length);
+        self.min = match (self.min.take(), other.min) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max = match (self.max.take(), other.max) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.sample.extend(other.sample);
+    }
+
+    /// Returns the HyperLogLog estimate of the number of distinct values
+    fn estimated_ndv(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|r| 2f64.powi(-(*r as i32))).sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        // linear counting is more accurate for small cardinalities
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+/// Collects the statistics of the columns of a table from its batches
+#[derive(Debug, Clone)]
+pub struct TableCollector {
+    table: Table,
+    schema: SchemaRef,
+    row_count: u64,
+    /// Every `sample_stride`th row (by the hash of its key) is sampled
+    sample_stride: u64,
+    columns: Vec<ColumnCollector>,
+}
+
+impl TableCollector {
+    /// Creates a collector for the batches of `table` with `schema`, of
+    /// which about `estimated_rows` are expected
+    ///
+    /// Returns an error if a column has a type statistics are not collected
+    /// for.
+    pub fn new(table: Table, schema: SchemaRef, estimated_rows: u64) -> io::Result<Self> {
+        for field in schema.fields() {
+            match field.data_type() {
+                DataType::Int32
+                | DataType::Int64
+                | DataType::Decimal128(_, _)
+                | DataType::Date32
+                | DataType::Utf8View
+                | DataType::Utf8 => {}
+                data_type => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        format!(
+                            "Statistics are not supported for column {} of type {data_type}",
+                            field.name()
+                        ),
+                    ))
+                }
+            }
+        }
+        let columns = schema.fields().iter().map(|_| ColumnCollector::new());
+        Ok(Self {
+            table,
+            sample_stride: (estimated_rows / SAMPLE_ROWS).max(1),
+            row_count: 0,
+            columns: columns.collect(),
+            schema,
+        })
+    }
+
+    /// Adds the rows of `batch`
+    pub fn add(&mut self, batch: &RecordBatch) {
+        let keys = primary_key_columns(self.table, batch);
+        for row in 0..batch.num_rows() {
+            let key = keys.iter().fold(0, |h, k| mix(h ^ k.value(row) as u64));
+            let sampled = key % self.sample_stride == 0;
+            for (column, array) in self.columns.iter_mut().zip(batch.columns()) {
+                column.add(value(array, row), sampled);
+            }
+        }
+        self.row_count += batch.num_rows() as u64;
+    }
+
+    /// Adds the rows added to `other`
+    pub fn merge(&mut self, other: TableCollector) {
+        self.row_count += other.row_count;
+        for (a, b) in self.columns.iter_mut().zip(other.columns) {
+            a.merge(b);
+        }
+    }
+
+    /// Returns the statistics of the rows added, with histograms of
+    /// `histogram_buckets` buckets
+    pub fn finish(self, histogram_buckets: usize) -> TableStatistics {
+        let primary_key = self.table.primary_key();
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .zip(self.columns)
+            .map(|(field, mut column)| {
+                let non_null = self.row_count - column.null_count;
+                let ndv = if primary_key == [field.name().as_str()] {
+                    non_null
+                } else {
+                    column.estimated_ndv().min(non_null)
+                };
+                column.sample.sort_unstable();
+                let histogram = match column.sample.len() {
+                    0 => vec![],
+                    _ if histogram_buckets == 0 => vec![],
+                    len => (0..=histogram_buckets)
+                        .map(|i| column.sample[(len - 1) * i / histogram_buckets].clone())
+                        .collect(),
+                };
+                let is_string = field.data_type() == &DataType::Utf8View
+                    || field.data_type() == &DataType::Utf8;
+                ColumnStatistics {
+                    name: field.name().clone(),
+                    data_type: field.data_type().clone(),
+                    null_count: column.null_count,
+                    ndv,
+                    min: column.min,
+                    max: column.max,
+                    lengths: is_string.then(|| {
+                        let average = match non_null {
+                            0 => 0.0,
+                            n => column.total_length as f64 / n as f64,
+                        };
+                        (average, column.max_length)
+                    }),
+                    histogram,
+                }
+            })
+            .collect();
+        TableStatistics {
+            table: self.table,
+            row_count: self.row_count,
+            columns,
+        }
+    }
+}
+
+/// The statistics of a table
+#[derive(Debug, Clone)]
+pub struct TableStatistics {
+    pub table: Table,
+    pub row_count: u64,
+    pub columns: Vec<ColumnStatistics>,
+}
+
+/// The statistics of a column
+#[derive(Debug, Clone)]
+pub struct ColumnStatistics {
+    pub name: String,
+    pub data_type: DataType,
+    pub null_count: u64,
+    /// Number of distinct non NULL values
+    pub ndv: u64,
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+    /// Average and maximum length in bytes of strings
+    pub lengths: Option<(f64, u64)>,
+    /// Bounds of the equi-depth histogram buckets, from the minimum to the
+    /// maximum of the sample
+    pub histogram: Vec<Value>,
+}
+
+impl ColumnStatistics {
+    /// Returns the PostgreSQL `n_distinct` of the column: the number of
+    /// distinct values, or minus their fraction of the rows if it grows
+    /// with the table, as `ANALYZE` does
+    fn n_distinct(&self, row_count: u64) -> String {
+        if row_count > 0 && self.ndv as f64 > 0.1 * row_count as f64 {
+            let fraction = self.ndv as f64 / row_count as f64;
+            format!("-{}", (fraction * 1e4).round() / 1e4)
+        } else {
+            self.ndv.to_string()
+        }
+    }
+}
+
+/// Returns the statistics of `tables` at `scale_factor` in `format`
+pub fn format_statistics(
+    format: StatsFormat,
+    scale_factor: f64,
+    tables: &[TableStatistics],
+) -> String {
+    match format {
+        StatsFormat::Json => {
+            let tables: Vec<_> = tables
+                .iter()
+                .map(|table| {
+                    let columns: Vec<_> = table
+                        .columns
+                        .iter()
+                        .map(|c| {
+                            let mut column = json!({
+                                "name": c.name,
+                                "type": c.data_type.to_string(),
+                                "null_count": c.null_count,
+                                "ndv": c.ndv,
+                                "min": c.min.as_ref().map(Value::to_json),
+                                "max": c.max.as_ref().map(Value::to_json),
+                            });
+                            if let Some((average, max)) = c.lengths {
+                                column["avg_length"] = json!((average * 100.0).round() / 100.0);
+                                column["max_length"] = json!(max);
+                            }
+                            if !c.histogram.is_empty() {
+                                let bounds = c.histogram.iter().map(Value::to_json);
+                                column["histogram"] = json!(bounds.collect::<Vec<_>>());
+                            }
+                            column
+                        })
+                        .collect();
+                    json!({
+                        "table": table.table.name(),
+                        "row_count": table.row_count,
+                        "columns": columns,
+                    })
+                })
+                .collect();
+            let json = json!({ "scale_factor": scale_factor, "tables": tables });
+            serde_json::to_string_pretty(&json).unwrap() + "\n"
+        }
+        StatsFormat::Postgres => {
+            let mut sql = format!(
+                "-- Column statistics of TPC-H SF {scale_factor}, generated by tpchgen-cli\n\
+                 -- n_distinct replaces the estimate of the next ANALYZE\n"
+            );
+            for table in tables {
+                sql.push('\n');
+                for column in &table.columns {
+                    writeln!(
+                        sql,
+                        "ALTER TABLE {} ALTER COLUMN {} SET (n_distinct = {});",
+                        table.table,
+                        column.name,
+                        column.n_distinct(table.row_count)
+                    )
+                    .unwrap();
+                }
+            }
+            sql
+        }
+        StatsFormat::Hive => {
+            let mut sql = format!(
+                "-- Column statistics of TPC-H SF {scale_factor}, generated by tpchgen-cli\n"
+            );
+            for table in tables {
+                let name = table.table;
+                sql.push('\n');
+                writeln!(
+                    sql,
+                    "ALTER TABLE {name} UPDATE STATISTICS SET ('numRows'='{}');",
+                    table.row_count
+                )
+                .unwrap();
+                for column in &table.columns {
+                    let mut properties = vec![
+                        format!("'numDVs'='{}'", column.ndv),
+                        format!("'numNulls'='{}'", column.null_count),
+                    ];
+                    match column.lengths {
+                        Some((average, max)) => {
+                            properties.push(format!("'avgColLen'='{average:.2}'"));
+                            properties.push(format!("'maxColLen'='{max}'"));
+                        }
+                        None => {
+                            if let (Some(min), Some(max)) = (&column.min, &column.max) {
+                                properties.push(format!("'lowValue'='{min}'"));
+                                properties.push(format!("'highValue'='{max}'"));
+                            }
+                        }
+                    }
+                    writeln!(
+                        sql,
+                        "ALTER TABLE {name} UPDATE STATISTICS FOR COLUMN {} SET ({});",
+                        column.name,
+                        properties.join(", ")
+                    )
+                    .unwrap();
+                }
+            }
+            sql
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+    use tpchgen::generators::{NationGenerator, OrderGenerator};
+    use tpchgen_arrow::{NationArrow, OrderArrow, RecordBatchIterator};
+
+    fn nation_statistics() -> TableStatistics {
+        let nations = NationArrow::new(NationGenerator::default()).with_batch_size(10);
+        let mut collector =
+            TableCollector::new(Table::Nation, Arc::clone(nations.schema()), 25).unwrap();
+        for batch in nations {
+            collector.add(&batch);
+        }
+        collector.finish(4)
+    }
+
+    #[test]
+    fn nation_statistics_are_exact() {
+        let stats = nation_statistics();
+        assert_eq!(stats.row_count, 25);
+        let key = &stats.columns[0];
+        assert_eq!(key.name, "n_nationkey");
+        assert_eq!((key.ndv, key.null_count), (25, 0));
+        assert_eq!(key.min, Some(Value::Int(0)));
+        assert_eq!(key.max, Some(Value::Int(24)));
+        // every row is sampled
+        let bounds = [0, 6, 12, 18, 24].map(Value::Int);
+        assert_eq!(key.histogram, bounds);
+
+        let name = &stats.columns[1];
+        assert_eq!(name.ndv, 25);
+        assert_eq!(name.min, Some(Value::String("ALGERIA".to_string())));
+        assert_eq!(name.max, Some(Value::String("VIETNAM".to_string())));
+        assert_eq!(name.lengths.unwrap().1, 14); // "UNITED KINGDOM"
+        assert_eq!(stats.columns[2].ndv, 5); // n_regionkey
+        assert!(key.lengths.is_none());
+    }
+
+    #[test]
+    fn merged_parts_match_one_part() {
+        let collect = |parts: i32| {
+            let mut total: Option<TableCollector> = None;
+            for part in 1..=parts {
+                let orders = OrderArrow::new(OrderGenerator::new(0.01, part, parts));
+                let schema = Arc::clone(orders.schema());
+                let mut collector = TableCollector::new(Table::Orders, schema, 15_000).unwrap();
+                orders.for_each(|batch| collector.add(&batch));
+                match &mut total {
+                    Some(total) => total.merge(collector),
+                    None => total = Some(collector),
+                }
+            }
+            total.unwrap().finish(10)
+        };
+        let (one, three) = (collect(1), collect(3));
+        assert_eq!(one.row_count, 15_000);
+        assert_eq!(three.row_count, 15_000);
+        for (a, b) in one.columns.iter().zip(&three.columns) {
+            assert_eq!(a.ndv, b.ndv, "{}", a.name);
+            assert_eq!((&a.min, &a.max), (&b.min, &b.max), "{}", a.name);
+            assert_eq!(a.histogram, b.histogram, "{}", a.name);
+        }
+
+        // the NDV of o_custkey is estimated: two thirds of the 1500
+        // customers have orders
+        let custkey = &one.columns[1];
+        assert!((950..=1050).contains(&custkey.ndv), "{}", custkey.ndv);
+        let total_price = &one.columns[3];
+        assert!(matches!(total_price.min, Some(Value::Decimal(_, 2))));
+        assert_eq!(
+            one.columns[4].min.as_ref().unwrap().to_string(),
+            "1992-01-01"
+        );
+    }
+
+    #[test]
+    fn formats_statistics() {
+        let stats = [nation_statistics()];
+        let json = format_statistics(StatsFormat::Json, 1.0, &stats);
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["tables"][0]["table"], "nation");
+        assert_eq!(json["tables"][0]["row_count"], 25);
+        assert_eq!(json["tables"][0]["columns"][1]["max"], "VIETNAM");
+
+        let sql = format_statistics(StatsFormat::Postgres, 1.0, &stats);
+        assert!(sql.contains("ALTER TABLE nation ALTER COLUMN n_nationkey SET (n_distinct = -1);"));
+        assert!(
+            sql.contains("ALTER TABLE nation ALTER COLUMN n_regionkey SET (n_distinct = -0.2);")
+        );
+
+        let sql = format_statistics(StatsFormat::Hive, 1.0, &stats);
+        assert!(sql.contains("ALTER TABLE nation UPDATE STATISTICS SET ('numRows'='25');"));
+        assert!(sql.contains(
+            "ALTER TABLE nation UPDATE STATISTICS FOR COLUMN n_nationkey SET ('numDVs'='25', 'numNulls'='0', 'lowValue'='0', 'highValue'='24');"
+        ));
+    }
+
+    #[test]
+    fn formats_values() {
+        assert_eq!(Value::Decimal(-105, 2).to_string(), "-1.05");
+        assert_eq!(Value::Decimal(123456, 2).to_string(), "1234.56");
+        assert_eq!(Value::Date(0).to_string(), "1970-01-01");
+    }
+
+    #[test]
+    fn rejects_unsupported_columns() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("n_nationkey", DataType::Int64, false),
+            Field::new("n_weight", DataType::Float64, false),
+        ]));
+        let error = TableCollector::new(Table::Nation, schema, 25).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(
+            error.to_string(),
+            "Statistics are not supported for column n_weight of type Float64"
+        );
+    }
+}
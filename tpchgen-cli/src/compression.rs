@@ -0,0 +1,149 @@
+//! Compressed TBL and CSV output (`--compression`)
+//!
+//! With `--compression gzip` or `--compression zstd` the text output is
+//! written to `lineitem.tbl.gz`, `lineitem.csv.zst`, etc. The file is
+//! compressed as one stream while it is written, so memory use does not
+//! depend on its size, but the compression runs on the single thread that
+//! writes the file: zstd is several times faster than gzip.
+
+use clap::ValueEnum;
+use flate2::write::GzEncoder;
+use std::io::{self, Write};
+use tpchgen_cli::generate::Sink;
+
+/// Compression of text output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TextCompression {
+    /// Not compressed
+    #[default]
+    None,
+    /// gzip, at the default level (6)
+    Gzip,
+    /// zstd, at the default level (3)
+    Zstd,
+}
+
+impl TextCompression {
+    /// Returns `filename` with the extension of the compression appended,
+    /// e.g. `lineitem.tbl.gz`
+    pub fn file_name(&self, filename: String) -> String {
+        match self {
+            TextCompression::None => filename,
+            TextCompression::Gzip => format!("{filename}.gz"),
+            TextCompression::Zstd => format!("{filename}.zst"),
+        }
+    }
+
+    /// Returns a [`Sink`] that compresses the buffers written to it into
+    /// `inner`
+    pub fn sink<S: Sink>(&self, inner: S) -> io::Result<CompressedSink<S>> {
+        let writer = SinkWriter(inner);
+        Ok(match self {
+            TextCompression::None => CompressedSink::None(writer.0),
+            TextCompression::Gzip => {
+                CompressedSink::Gzip(GzEncoder::new(writer, flate2::Compression::default()))
+            }
+            TextCompression::Zstd => {
+                CompressedSink::Zstd(zstd::Encoder::new(writer, zstd::DEFAULT_COMPRESSION_LEVEL)?)
+            }
+        })
+    }
+}
+
+/// A [`Sink`] that compresses its input, see [`TextCompression::sink`]
+pub enum CompressedSink<S: Sink> {
+    None(S),
+    Gzip(GzEncoder<SinkWriter<S>>),
+    Zstd(zstd::Encoder<'static, SinkWriter<S>>),
+}
+
+impl<S: Sink> Sink for CompressedSink<S> {
+    fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error> {
+        match self {
+            CompressedSink::None(sink) => sink.sink(buffer),
+            CompressedSink::Gzip(encoder) => encoder.write_all(buffer),
+            CompressedSink::Zstd(encoder) => encoder.write_all(buffer),
+        }
+    }
+
+    /// Writes the end of the compressed stream and flushes the inner sink
+    fn flush(self) -> Result<(), io::Error> {
+        let writer = match self {
+            CompressedSink::None(sink) => return sink.flush(),
+            CompressedSink::Gzip(encoder) => encoder.finish()?,
+            CompressedSink::Zstd(encoder) => encoder.finish()?,
+        };
+        writer.0.flush()
+    }
+}
+
+/// A [`Write`] that writes the compressed bytes to a [`Sink`]
+pub struct SinkWriter<S>(S);
+
+impl<S: Sink> Write for SinkWriter<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.sink(buf)?;
+        Ok(buf.len())
+    }
+
+    /// The sink is flushed once the stream is finished
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+
+    /// Collects the buffers written to it
+    struct VecSink(Arc<Mutex<Vec<u8>>>);
+
+    impl Sink for VecSink {
+        fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error> {
+            self.0.lock().unwrap().extend_from_slice(buffer);
+            Ok(())
+        }
+
+        fn flush(self) -> Result<(), io::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let text = "1|Customer#000000001|IVhzIApeRb ot,c,E|15|\n".repeat(1000);
+        for compression in [
+            TextCompression::None,
+            TextCompression::Gzip,
+            TextCompression::Zstd,
+        ] {
+            let output = Arc::new(Mutex::new(Vec::new()));
+            let mut sink = compression.sink(VecSink(Arc::clone(&output))).unwrap();
+            for line in text.split_inclusive('\n') {
+                sink.sink(line.as_bytes()).unwrap();
+            }
+            sink.flush().unwrap();
+            let output = output.lock().unwrap().clone();
+            let decompressed = match compression {
+                TextCompression::None => output,
+                TextCompression::Gzip => {
+                    let mut decompressed = Vec::new();
+                    GzDecoder::new(output.as_slice())
+                        .read_to_end(&mut decompressed)
+                        .unwrap();
+                    decompressed
+                }
+                TextCompression::Zstd => zstd::decode_all(output.as_slice()).unwrap(),
+            };
+            assert_eq!(decompressed, text.as_bytes(), "{compression:?}");
+        }
+        assert_eq!(
+            TextCompression::Zstd.file_name("lineitem.csv".to_string()),
+            "lineitem.csv.zst"
+        );
+    }
+}
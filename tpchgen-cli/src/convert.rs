@@ -0,0 +1,592 @@
+//! Converting existing TBL and CSV files to other formats (`convert`)
+//!
+//! For example, `tpchgen-cli convert lineitem.tbl --to parquet` re-encodes
+//! `lineitem.tbl` (e.g. written by dbgen) as `lineitem.parquet`, using the
+//! same schemas and writers as generated data.
+//!
+//! Input files are read in chunks of about the size of a generated part,
+//! and each chunk is parsed with [`tpchgen::parse`] and written on its own
+//! thread, like a generated part. The table is determined from the file
+//! name (e.g. `lineitem.tbl` or `lineitem.tbl.3`) unless `--table` is given.
+
+use crate::csv::uuid_quote;
+use crate::surrogate_key::SurrogateKey;
+use crate::{OutputFormat, Table, TableValueParser};
+use arrow::array::RecordBatch;
+use arrow::datatypes::SchemaRef;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tpchgen::csv::{
+    CsvDialect, CustomerCsv, LineItemCsv, NationCsv, OrderCsv, PartCsv, PartSuppCsv, RegionCsv,
+    SupplierCsv,
+};
+use tpchgen::parse::{
+    parse_customer, parse_lineitem, parse_nation, parse_order, parse_part, parse_partsupp,
+    parse_region, parse_supplier, Format, ParseError,
+};
+use tpchgen_arrow::{
+    ColumnTypes, CustomerArrow, LineItemArrow, NationArrow, OrderArrow, PartArrow, PartSuppArrow,
+    RecordBatchIterator, RegionArrow, SupplierArrow, DEFAULT_BATCH_SIZE,
+};
+use tpchgen_cli::generate::Source;
+
+/// Size of the chunks the input is split into, about the size of the parts
+/// tables are generated in
+pub const CHUNK_SIZE: usize = 15 * 1024 * 1024;
+
+/// Arguments of the `convert` subcommand
+#[derive(Debug, clap::Args)]
+pub struct ConvertArgs {
+    /// TBL or CSV files to convert, e.g. `lineitem.tbl` or `orders.csv`
+    #[arg(required = true, value_name = "INPUT")]
+    pub inputs: Vec<PathBuf>,
+
+    /// Output format: tbl, csv, parquet or vortex
+    #[arg(long)]
+    pub to: OutputFormat,
+
+    /// Table of the input files (default: from the file names)
+    #[arg(long, value_parser = TableValueParser)]
+    pub table: Option<Table>,
+}
+
+/// Returns the table of the input file at `path`, from the first part of
+/// its file name
+pub fn input_table(path: &Path) -> io::Result<Table> {
+    let name = file_name(path)?;
+    let base = name.split('.').next().unwrap_or(name);
+    Table::from_str(base).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Can not determine the table of {}, use --table",
+                path.display()
+            ),
+        )
+    })
+}
+
+/// Returns the format of the input file at `path` from its extension
+pub fn input_format(path: &Path) -> io::Result<Format> {
+    let name = file_name(path)?;
+    let mut extensions = name.split('.').skip(1);
+    if extensions.clone().any(|e| e == "tbl") {
+        Ok(Format::Tbl)
+    } else if extensions.any(|e| e == "csv") {
+        Ok(Format::Csv)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Can not determine the format of {}, expected a .tbl or .csv file",
+                path.display()
+            ),
+        ))
+    }
+}
+
+/// Returns the name of the converted file for the input file at `path`,
+/// e.g. `lineitem.parquet` for `lineitem.tbl` and `lineitem.3.parquet` for
+/// `lineitem.tbl.3`
+pub fn output_filename(path: &Path, to: OutputFormat) -> io::Result<String> {
+    let name = file_name(path)?;
+    let mut parts: Vec<&str> = name
+        .split('.')
+        .enumerate()
+        .filter(|&(i, part)| i == 0 || (part != "tbl" && part != "csv"))
+        .map(|(_, part)| part)
+        .collect();
+    parts.push(to.extension());
+    Ok(parts.join("."))
+}
+
+fn file_name(path: &Path) -> io::Result<&str> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid input file {}", path.display()),
+            )
+        })
+}
+
+/// The first error found while converting a file
+///
+/// [`Source`]s and [`RecordBatchIterator`]s can not return errors, so they
+/// record the error here and stop producing output.
+#[derive(Debug, Clone, Default)]
+pub struct FirstError(Arc<Mutex<Option<io::Error>>>);
+
+impl FirstError {
+    /// Records `error`, unless an error was already recorded
+    pub fn set(&self, error: io::Error) {
+        self.0.lock().unwrap().get_or_insert(error);
+    }
+
+    fn is_set(&self) -> bool {
+        self.0.lock().unwrap().is_some()
+    }
+
+    /// Returns the error, if any
+    pub fn take(&self) -> io::Result<()> {
+        match self.0.lock().unwrap().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Calls the macro `$convert` with the parse function, CSV formatter and
+/// Arrow converter for `$table`
+macro_rules! for_table {
+    ($table:expr, $convert:ident) => {
+        match $table {
+            Table::Nation => $convert!(parse_nation, NationCsv, NationArrow),
+            Table::Region => $convert!(parse_region, RegionCsv, RegionArrow),
+            Table::Part => $convert!(parse_part, PartCsv, PartArrow),
+            Table::Supplier => $convert!(parse_supplier, SupplierCsv, SupplierArrow),
+            Table::Partsupp => $convert!(parse_partsupp, PartSuppCsv, PartSuppArrow),
+            Table::Customer => $convert!(parse_customer, CustomerCsv, CustomerArrow),
+            Table::Orders => $convert!(parse_order, OrderCsv, OrderArrow),
+            Table::Lineitem => $convert!(parse_lineitem, LineItemCsv, LineItemArrow),
+        }
+    };
+}
+
+/// A chunk of complete lines of an input file
+pub struct Chunk {
+    /// The name of the input file, for error messages
+    name: Arc<str>,
+    /// Line number (1 based) of the first line in `text`
+    first_line: usize,
+    text: String,
+}
+
+impl Chunk {
+    /// Creates a chunk of `text` from `name`, starting at line `first_line`
+    pub fn new(name: &str, first_line: usize, text: String) -> Self {
+        Self {
+            name: name.into(),
+            first_line,
+            text,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the non empty lines in `text`, a part of this chunk starting
+    /// at line `first_line`, with their line numbers
+    fn lines(text: &str, first_line: usize) -> impl Iterator<Item = (usize, &str)> + '_ {
+        text.lines()
+            .enumerate()
+            .map(move |(i, line)| (first_line + i, line))
+            .filter(|(_, line)| !line.is_empty())
+    }
+
+    fn parse_error(&self, line: usize, error: ParseError) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}:{line}: {error}", self.name),
+        )
+    }
+}
+
+/// Reads an input file in [`Chunk`]s
+pub struct Chunks<R> {
+    reader: R,
+    name: Arc<str>,
+    /// Line number of the next line
+    line: usize,
+    /// The CSV header to skip if it is the first line
+    header: Option<&'static str>,
+    error: FirstError,
+}
+
+impl<R: BufRead> Chunks<R> {
+    /// Read chunks of `table` in `format` from `reader`, named `name`
+    ///
+    /// The first line of CSV input is skipped if it is the CSV header.
+    pub fn new(reader: R, name: &str, table: Table, format: Format, error: FirstError) -> Self {
+        macro_rules! csv_header {
+            ($parse:ident, $csv:ident, $arrow:ident) => {
+                $csv::header()
+            };
+        }
+        let header = (format == Format::Csv).then(|| for_table!(table, csv_header));
+        Self {
+            reader,
+            name: name.into(),
+            line: 1,
+            header,
+            error,
+        }
+    }
+
+    fn read_chunk(&mut self) -> io::Result<Option<Chunk>> {
+        let mut buffer = Vec::with_capacity(CHUNK_SIZE + 1024);
+        if let Some(header) = self.header.take() {
+            self.reader.read_until(b'\n', &mut buffer)?;
+            if buffer.trim_ascii_end() == header.as_bytes() {
+                buffer.clear();
+                self.line += 1;
+            }
+        }
+        while buffer.len() < CHUNK_SIZE {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                break;
+            }
+            let len = available.len().min(CHUNK_SIZE - buffer.len());
+            buffer.extend_from_slice(&available[..len]);
+            self.reader.consume(len);
+        }
+        // complete the last line
+        if !buffer.ends_with(b"\n") {
+            self.reader.read_until(b'\n', &mut buffer)?;
+        }
+        if buffer.is_empty() {
+            return Ok(None);
+        }
+        let text = String::from_utf8(buffer).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not valid UTF-8", self.name),
+            )
+        })?;
+        let chunk = Chunk {
+            name: Arc::clone(&self.name),
+            first_line: self.line,
+            text,
+        };
+        self.line += chunk.text.lines().count();
+        Ok(Some(chunk))
+    }
+}
+
+impl<R: BufRead> Iterator for Chunks<R> {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Chunk> {
+        // stop early if a chunk could not be converted
+        if self.error.is_set() {
+            return None;
+        }
+        self.read_chunk().unwrap_or_else(|e| {
+            self.error.set(e);
+            None
+        })
+    }
+}
+
+/// A [`Source`] that converts a [`Chunk`] to TBL or CSV
+pub struct TextSource {
+    table: Table,
+    from: Format,
+    to: Format,
+    chunk: Chunk,
+    error: FirstError,
+    uuid_column: bool,
+    dialect: CsvDialect,
+}
+
+impl TextSource {
+    pub fn new(table: Table, from: Format, to: Format, chunk: Chunk, error: FirstError) -> Self {
+        Self {
+            table,
+            from,
+            to,
+            chunk,
+            error,
+            uuid_column: false,
+            dialect: CsvDialect::default(),
+        }
+    }
+
+    /// Append the UUID column, see [`crate::surrogate_key`]
+    pub fn with_uuid_column(mut self, uuid_column: bool) -> Self {
+        self.uuid_column = uuid_column;
+        self
+    }
+
+    /// Set the delimiter, quoting and escaping of CSV output
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+}
+
+impl Source for TextSource {
+    fn header(&self, mut buffer: Vec<u8>) -> Vec<u8> {
+        macro_rules! write_header {
+            ($parse:ident, $csv:ident, $arrow:ident) => {
+                match (self.to, self.uuid_column) {
+                    (Format::Csv, false) => {
+                        writeln!(&mut buffer, "{}", self.dialect.header($csv::header()))
+                    }
+                    (Format::Csv, true) => {
+                        let header = format!("{},{}", $csv::header(), self.table.uuid_column());
+                        writeln!(&mut buffer, "{}", self.dialect.header(&header))
+                    }
+                    (Format::Tbl, _) => Ok(()),
+                }
+                .expect("writing to memory is infallible")
+            };
+        }
+        for_table!(self.table, write_header);
+        buffer
+    }
+
+    fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+        let chunk = &self.chunk;
+        let (from, to, error) = (self.from, self.to, &self.error);
+        let uuid_column = self.uuid_column;
+        let dialect = self.dialect;
+        let (delimiter, quote) = (dialect.delimiter(), uuid_quote(&dialect));
+        macro_rules! write_rows {
+            ($parse:ident, $csv:ident, $arrow:ident) => {
+                for (line_number, line) in Chunk::lines(&chunk.text, chunk.first_line) {
+                    let row = match $parse(line, from) {
+                        Ok(row) => row,
+                        Err(e) => {
+                            error.set(chunk.parse_error(line_number, e));
+                            break;
+                        }
+                    };
+                    let uuid = uuid_column.then(|| row.uuid());
+                    match (to, uuid) {
+                        (Format::Tbl, None) => writeln!(&mut buffer, "{row}"),
+                        (Format::Tbl, Some(uuid)) => writeln!(&mut buffer, "{row}{uuid}|"),
+                        (Format::Csv, None) => {
+                            writeln!(&mut buffer, "{}", $csv::new(row).with_dialect(dialect))
+                        }
+                        (Format::Csv, Some(uuid)) => {
+                            let csv = $csv::new(row).with_dialect(dialect);
+                            writeln!(&mut buffer, "{csv}{delimiter}{quote}{uuid}{quote}")
+                        }
+                    }
+                    .expect("writing to memory is infallible");
+                }
+            };
+        }
+        for_table!(self.table, write_rows);
+        buffer
+    }
+}
+
+/// A [`RecordBatchIterator`] that converts a [`Chunk`] to Arrow
+pub struct BatchSource {
+    table: Table,
+    from: Format,
+    schema: SchemaRef,
+    chunk: Chunk,
+    /// Byte offset of the first line of the next batch in the chunk
+    offset: usize,
+    /// Line number of the first line of the next batch
+    line: usize,
+    error: FirstError,
+    /// Number of lines converted to each batch
+    batch_size: usize,
+    column_types: ColumnTypes,
+}
+
+impl BatchSource {
+    pub fn new(
+        table: Table,
+        from: Format,
+        schema: SchemaRef,
+        chunk: Chunk,
+        error: FirstError,
+    ) -> Self {
+        let line = chunk.first_line;
+        Self {
+            table,
+            from,
+            schema,
+            chunk,
+            offset: 0,
+            line,
+            error,
+            batch_size: DEFAULT_BATCH_SIZE,
+            column_types: ColumnTypes::default(),
+        }
+    }
+
+    /// Set the number of lines converted to each batch
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set the Arrow types of the columns, see [`ColumnTypes`]
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.column_types = *column_types;
+        self.schema = column_types.schema(&self.schema);
+        self
+    }
+}
+
+impl RecordBatchIterator for BatchSource {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl Iterator for BatchSource {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<RecordBatch> {
+        let text = &self.chunk.text[self.offset..];
+        if text.is_empty() {
+            return None;
+        }
+        // the next batch_size lines
+        let len = text
+            .match_indices('\n')
+            .nth(self.batch_size - 1)
+            .map_or(text.len(), |(i, _)| i + 1);
+        let text = &text[..len];
+        let first_line = self.line;
+        self.offset += len;
+        self.line += text.lines().count();
+
+        macro_rules! convert_rows {
+            ($parse:ident, $csv:ident, $arrow:ident) => {{
+                let mut rows = Vec::with_capacity(self.batch_size);
+                for (line_number, line) in Chunk::lines(text, first_line) {
+                    match $parse(line, self.from) {
+                        Ok(row) => rows.push(row),
+                        Err(e) => {
+                            self.error.set(self.chunk.parse_error(line_number, e));
+                            return None;
+                        }
+                    }
+                }
+                let batch = $arrow::batch_from_rows(&rows);
+                self.column_types.convert(batch, &self.schema)
+            }};
+        }
+        Some(for_table!(self.table, convert_rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::compute::concat_batches;
+    use tpchgen::csv::LineItemCsv;
+    use tpchgen::generators::LineItemGenerator;
+    use tpchgen_arrow::{DateType, DecimalType};
+
+    #[test]
+    fn file_names() {
+        let path = Path::new("data/lineitem.tbl.3");
+        assert_eq!(input_table(path).unwrap(), Table::Lineitem);
+        assert_eq!(input_format(path).unwrap(), Format::Tbl);
+        assert_eq!(
+            output_filename(path, OutputFormat::Parquet).unwrap(),
+            "lineitem.3.parquet"
+        );
+        let path = Path::new("orders.csv");
+        assert_eq!(input_table(path).unwrap(), Table::Orders);
+        assert_eq!(input_format(path).unwrap(), Format::Csv);
+        assert_eq!(
+            output_filename(path, OutputFormat::Tbl).unwrap(),
+            "orders.tbl"
+        );
+
+        assert!(input_table(Path::new("items.tbl")).is_err());
+        assert!(input_format(Path::new("lineitem.parquet")).is_err());
+    }
+
+    /// Converts `input` in `format` to a single batch
+    fn convert_to_batch(input: &str, format: Format, error: &FirstError) -> RecordBatch {
+        let schema = Arc::clone(LineItemArrow::new(LineItemGenerator::new(0.01, 1, 1)).schema());
+        let chunks = Chunks::new(
+            input.as_bytes(),
+            "lineitem.tbl",
+            Table::Lineitem,
+            format,
+            error.clone(),
+        );
+        let batches: Vec<_> = chunks
+            .flat_map(|chunk| {
+                BatchSource::new(
+                    Table::Lineitem,
+                    format,
+                    Arc::clone(&schema),
+                    chunk,
+                    error.clone(),
+                )
+            })
+            .collect();
+        concat_batches(&schema, &batches).unwrap()
+    }
+
+    #[test]
+    fn converted_batches_match_generated() {
+        let generator = LineItemGenerator::new(0.01, 1, 1);
+        let schema = Arc::clone(LineItemArrow::new(generator.clone()).schema());
+        let batches: Vec<_> = LineItemArrow::new(generator.clone()).collect();
+        let expected = concat_batches(&schema, &batches).unwrap();
+
+        let tbl: String = generator.iter().map(|row| format!("{row}\n")).collect();
+        let mut csv = format!("{}\n", LineItemCsv::header());
+        for row in generator.iter() {
+            csv.push_str(&format!("{}\n", LineItemCsv::new(row)));
+        }
+        for (input, format) in [(tbl, Format::Tbl), (csv, Format::Csv)] {
+            let error = FirstError::default();
+            let actual = convert_to_batch(&input, format, &error);
+            error.take().unwrap();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn converted_batches_have_column_types() {
+        let generator = LineItemGenerator::new(0.001, 1, 1);
+        let column_types = ColumnTypes::default()
+            .with_decimal(DecimalType::Float64)
+            .with_date(DateType::Utf8);
+        let expected = LineItemArrow::new(generator.clone()).with_column_types(&column_types);
+        let schema = Arc::clone(expected.schema());
+        let expected = concat_batches(&schema, &expected.collect::<Vec<_>>()).unwrap();
+
+        let tbl: String = generator.iter().map(|row| format!("{row}\n")).collect();
+        let error = FirstError::default();
+        let chunks = Chunks::new(
+            tbl.as_bytes(),
+            "lineitem.tbl",
+            Table::Lineitem,
+            Format::Tbl,
+            error.clone(),
+        );
+        let batches: Vec<_> = chunks
+            .flat_map(|chunk| {
+                let schema = Table::Lineitem.schema();
+                BatchSource::new(Table::Lineitem, Format::Tbl, schema, chunk, error.clone())
+                    .with_column_types(&column_types)
+            })
+            .collect();
+        error.take().unwrap();
+        assert_eq!(concat_batches(&schema, &batches).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_errors_have_line_numbers() {
+        let input = "1|155190|7706|1|17|21168.23|0.04|0.02|N|O|1996-03-13|1996-02-12|1996-03-22|DELIVER IN PERSON|TRUCK|egular courts above the|\n\
+                     1|67310|7311|2|36|45983.16|0.09|0.06|N|O|1996-04-12|1996-02-28|1996-04-20|TAKE BACK RETURN|MAIL|\n";
+        let error = FirstError::default();
+        convert_to_batch(input, Format::Tbl, &error);
+        assert_eq!(
+            error.take().unwrap_err().to_string(),
+            "lineitem.tbl:2: wrong number of columns for lineitem: expected 16"
+        );
+    }
+}
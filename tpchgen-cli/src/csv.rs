@@ -1,39 +1,161 @@
-//! Implementations of [`Source`] for generating data in TBL format
-use super::generate::Source;
+//! Implementations of [`Source`] for generating data in CSV format
+use crate::surrogate_key::SurrogateKey;
+use clap::ValueEnum;
 use std::io::Write;
 use tpchgen::csv::{
-    CustomerCsv, LineItemCsv, NationCsv, OrderCsv, PartCsv, PartSuppCsv, RegionCsv, SupplierCsv,
+    CsvDialect, CsvEscape, CsvQuoting, CustomerCsv, LineItemCsv, NationCsv, OrderCsv, PartCsv,
+    PartSuppCsv, RegionCsv, SupplierCsv,
 };
 use tpchgen::generators::{
     CustomerGenerator, LineItemGenerator, NationGenerator, OrderGenerator, PartGenerator,
     PartSuppGenerator, RegionGenerator, SupplierGenerator,
 };
+use tpchgen::nulls::NullInjection;
+use tpchgen_cli::generate::Source;
+
+/// Which fields of CSV output are quoted (`--csv-quoting`), see [`CsvQuoting`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Quoting {
+    /// Quote the comments and addresses, and other fields only if needed
+    #[default]
+    Text,
+    /// Quote only fields that contain the delimiter, a quote or a line break
+    Necessary,
+    /// Quote all fields
+    All,
+}
+
+impl From<Quoting> for CsvQuoting {
+    fn from(quoting: Quoting) -> Self {
+        match quoting {
+            Quoting::Text => CsvQuoting::Text,
+            Quoting::Necessary => CsvQuoting::Necessary,
+            Quoting::All => CsvQuoting::All,
+        }
+    }
+}
+
+/// How quotes in quoted fields of CSV output are escaped (`--csv-escape`),
+/// see [`CsvEscape`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Escape {
+    /// Double the quote (""), as in RFC 4180
+    #[default]
+    Double,
+    /// Precede quotes and backslashes with a backslash
+    Backslash,
+}
+
+impl From<Escape> for CsvEscape {
+    fn from(escape: Escape) -> Self {
+        match escape {
+            Escape::Double => CsvEscape::Double,
+            Escape::Backslash => CsvEscape::Backslash,
+        }
+    }
+}
+
+/// Parses a CSV delimiter: a single character, or `\t` or `tab` for a tab
+pub fn parse_delimiter(value: &str) -> Result<char, String> {
+    let delimiter = match value {
+        "\\t" | "tab" => '\t',
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(format!("expected a single character, got '{value}'")),
+            }
+        }
+    };
+    if matches!(delimiter, '"' | '\\' | '\n' | '\r') {
+        return Err(format!(
+            "{delimiter:?} can not be used as delimiter, it is a quote, escape or line break"
+        ));
+    }
+    Ok(delimiter)
+}
+
+/// Returns the quote to write around the values of the UUID column in
+/// `dialect`, if any
+pub fn uuid_quote(dialect: &CsvDialect) -> &'static str {
+    // UUIDs only contain hex digits and '-'
+    let delimiter = dialect.delimiter();
+    if dialect.quoting() == CsvQuoting::All || delimiter == '-' || delimiter.is_ascii_hexdigit() {
+        "\""
+    } else {
+        ""
+    }
+}
 
 /// Define a Source that writes the table in CSV format
 macro_rules! define_csv_source {
     ($SOURCE_NAME:ident, $GENERATOR_TYPE:ty, $FORMATTER:ty) => {
         pub struct $SOURCE_NAME {
             inner: $GENERATOR_TYPE,
+            uuid_column: bool,
+            dialect: CsvDialect,
+            nulls: Option<&'static NullInjection>,
         }
 
         impl $SOURCE_NAME {
             pub fn new(inner: $GENERATOR_TYPE) -> Self {
-                Self { inner }
+                Self {
+                    inner,
+                    uuid_column: false,
+                    dialect: CsvDialect::default(),
+                    nulls: None,
+                }
+            }
+
+            /// Append the UUID column, see [`crate::surrogate_key`]
+            pub fn with_uuid_column(mut self, uuid_column: bool) -> Self {
+                self.uuid_column = uuid_column;
+                self
+            }
+
+            /// Set the delimiter, quoting and escaping of the output
+            pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+                self.dialect = dialect;
+                self
+            }
+
+            /// Write the NULLs selected by `nulls`, see [`crate::nulls`]
+            pub fn with_nulls(mut self, nulls: Option<&'static NullInjection>) -> Self {
+                self.nulls = nulls;
+                self
             }
         }
 
         impl Source for $SOURCE_NAME {
             fn header(&self, buffer: Vec<u8>) -> Vec<u8> {
                 let mut buffer = buffer;
-                writeln!(&mut buffer, "{}", <$FORMATTER>::header())
+                let header = if self.uuid_column {
+                    let table = <<$GENERATOR_TYPE as IntoIterator>::Item>::TABLE;
+                    format!("{},{}", <$FORMATTER>::header(), table.uuid_column())
+                } else {
+                    <$FORMATTER>::header().to_string()
+                };
+                writeln!(&mut buffer, "{}", self.dialect.header(&header))
                     .expect("writing to memory is infallible");
                 buffer
             }
 
             fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+                let delimiter = self.dialect.delimiter();
+                let quote = uuid_quote(&self.dialect);
                 for item in self.inner.iter() {
-                    let formatter = <$FORMATTER>::new(item);
-                    writeln!(&mut buffer, "{formatter}").expect("writing to memory is infallible");
+                    let uuid = self.uuid_column.then(|| item.uuid());
+                    let mut formatter = <$FORMATTER>::new(item).with_dialect(self.dialect);
+                    if let Some(nulls) = self.nulls {
+                        formatter = formatter.with_nulls(nulls);
+                    }
+                    match uuid {
+                        Some(uuid) => {
+                            writeln!(&mut buffer, "{formatter}{delimiter}{quote}{uuid}{quote}")
+                        }
+                        None => writeln!(&mut buffer, "{formatter}"),
+                    }
+                    .expect("writing to memory is infallible");
                 }
                 buffer
             }
@@ -50,3 +172,35 @@ define_csv_source!(PartSuppCsvSource, PartSuppGenerator<'static>, PartSuppCsv);
 define_csv_source!(CustomerCsvSource, CustomerGenerator<'static>, CustomerCsv);
 define_csv_source!(OrderCsvSource, OrderGenerator<'static>, OrderCsv);
 define_csv_source!(LineItemCsvSource, LineItemGenerator<'static>, LineItemCsv);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_delimiters() {
+        assert_eq!(parse_delimiter(";"), Ok(';'));
+        assert_eq!(parse_delimiter("tab"), Ok('\t'));
+        assert_eq!(parse_delimiter("\\t"), Ok('\t'));
+        assert_eq!(parse_delimiter("§"), Ok('§'));
+        assert!(parse_delimiter("").is_err());
+        assert!(parse_delimiter(";;").is_err());
+        assert!(parse_delimiter("\"").is_err());
+    }
+
+    #[test]
+    fn writes_dialect() {
+        let dialect = CsvDialect::default()
+            .with_delimiter('a')
+            .with_quoting(CsvQuoting::Necessary);
+        let source = RegionCsvSource::new(RegionGenerator::default())
+            .with_uuid_column(true)
+            .with_dialect(dialect);
+        let header = String::from_utf8(source.header(Vec::new())).unwrap();
+        assert_eq!(header, "r_regionkeya\"r_name\"ar_commentar_uuid\n");
+        let output = String::from_utf8(source.create(Vec::new())).unwrap();
+        // UUIDs are quoted as they may contain the delimiter
+        assert!(output.starts_with("0aAFRICAa\"lar deposits."));
+        assert!(output.lines().all(|line| line.ends_with('"')));
+    }
+}
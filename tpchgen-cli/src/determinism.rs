@@ -0,0 +1,106 @@
+//! Determinism verification (`--verify-determinism`)
+//!
+//! Each table is generated twice in TBL format without writing any output:
+//! once with `--num-threads` threads and the usual number of parts, and once
+//! with `--verify-num-threads` threads and `--verify-parts` parts. The SHA-1
+//! hashes of the two outputs must be equal, since the rows of a table do not
+//! depend on how it is split or on the order the parts are generated in.
+//!
+//! The hashes are of the TBL text, so they are also comparable between
+//! runs, machines and versions of `tpchgen-cli`.
+
+use sha1_smol::Sha1;
+use std::fmt::Display;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tpchgen_cli::generate::{generate_in_chunks, Sink, Source};
+
+/// The hash of the output of a table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentHash {
+    /// Hex encoded SHA-1 of the output
+    pub digest: String,
+    /// Size of the output in bytes
+    pub num_bytes: u64,
+    /// Number of lines of the output, the rows of TBL output
+    pub num_lines: u64,
+}
+
+impl Display for ContentHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sha1 {} ({} bytes)", self.digest, self.num_bytes)
+    }
+}
+
+/// A [`Sink`] that hashes the buffers written to it, storing the
+/// [`ContentHash`] in a shared slot when flushed
+struct HashSink {
+    hasher: Sha1,
+    num_bytes: u64,
+    num_lines: u64,
+    result: Arc<Mutex<Option<ContentHash>>>,
+}
+
+impl Sink for HashSink {
+    fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error> {
+        self.hasher.update(buffer);
+        self.num_bytes += buffer.len() as u64;
+        self.num_lines += count_lines(buffer);
+        Ok(())
+    }
+
+    fn flush(self) -> Result<(), io::Error> {
+        *self.result.lock().unwrap() = Some(ContentHash {
+            digest: self.hasher.digest().to_string(),
+            num_bytes: self.num_bytes,
+            num_lines: self.num_lines,
+        });
+        Ok(())
+    }
+}
+
+/// Generates `sources` with `num_threads` threads and returns the hash of
+/// the output
+pub async fn hash<I>(sources: I, num_threads: usize) -> io::Result<ContentHash>
+where
+    I: Iterator<Item: Source> + 'static,
+{
+    let result = Arc::new(Mutex::new(None));
+    let sink = HashSink {
+        hasher: Sha1::new(),
+        num_bytes: 0,
+        num_lines: 0,
+        result: Arc::clone(&result),
+    };
+    generate_in_chunks(sink, sources, num_threads).await?;
+    let hash = result.lock().unwrap().take();
+    Ok(hash.expect("sink flushed"))
+}
+
+/// Returns the number of line breaks in `buffer`
+pub fn count_lines(buffer: &[u8]) -> u64 {
+    buffer.iter().filter(|&&b| b == b'\n').count() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tbl::OrderTblSource;
+    use tpchgen::generators::OrderGenerator;
+
+    #[tokio::test]
+    async fn hash_is_independent_of_parts_and_threads() {
+        let sources = |num_parts| {
+            (1..=num_parts)
+                .map(move |part| OrderTblSource::new(OrderGenerator::new(0.01, part, num_parts)))
+        };
+        let expected = hash(sources(1), 1).await.unwrap();
+        assert_eq!(expected.digest.len(), 40);
+        assert_eq!(expected.num_lines, 15000);
+        assert_eq!(hash(sources(7), 4).await.unwrap(), expected);
+
+        // a different table has a different hash
+        let other = (1..=2).map(|part| OrderTblSource::new(OrderGenerator::new(0.02, part, 2)));
+        assert_ne!(hash(other, 2).await.unwrap(), expected);
+    }
+}
@@ -0,0 +1,229 @@
+//! Dirty data injection (`--dirty-percent`)
+//!
+//! With `--dirty-percent 1`, about 1% of the rows of each table are written
+//! with a duplicate key, an out of range decimal, a malformed date or
+//! truncated, see [`tpchgen::dirty`]. The corrupted rows of each TBL file,
+//! e.g. `lineitem.tbl`, are listed in a sidecar file next to it, e.g.
+//! `lineitem.tbl.dirty`, with one line per row in TBL format: the primary
+//! key columns of the row, the corruption and the corrupted column, if any:
+//!
+//! ```text
+//! 7|3|malformed-date|l_commitdate|
+//! 9|1|duplicate-key||
+//! ```
+//!
+//! The same rows are corrupted for every number of parts.
+
+use crate::Table;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tpchgen::dirty::DirtyData;
+
+/// The corrupted rows of a table, written to its sidecar file by the sources
+/// of its parts while they are generated in parallel
+pub struct DirtyLog {
+    dirty: &'static DirtyData,
+    table: Table,
+    sidecar: Mutex<Sidecar>,
+}
+
+/// The sidecar file of a [`DirtyLog`]
+///
+/// Like the parts of the TBL file, the lines of each part are written in
+/// order as soon as the parts before it are written, so only the lines of
+/// the parts that finish before an earlier one are held in memory.
+#[derive(Default)]
+struct Sidecar {
+    file: Option<io::BufWriter<fs::File>>,
+    /// The parts whose lines are not written yet, in order
+    pending: VecDeque<i32>,
+    /// The lines of the parts that finished before an earlier part
+    finished: BTreeMap<i32, Vec<u8>>,
+    /// The first error writing to the file, returned by [`DirtyLog::finish`]
+    error: Option<io::Error>,
+}
+
+impl Sidecar {
+    /// Writes the lines of the finished parts that are next in order
+    fn write_ready(&mut self) {
+        while let Some(lines) = self
+            .pending
+            .front()
+            .and_then(|part| self.finished.remove(part))
+        {
+            self.pending.pop_front();
+            if let (Some(file), None) = (&mut self.file, &self.error) {
+                self.error = file.write_all(&lines).err();
+            }
+        }
+    }
+}
+
+impl DirtyLog {
+    /// Creates an empty log of the rows of `table` corrupted by `dirty`
+    pub fn new(dirty: &'static DirtyData, table: Table) -> Arc<Self> {
+        Arc::new(Self {
+            dirty,
+            table,
+            sidecar: Mutex::new(Sidecar::default()),
+        })
+    }
+
+    /// Creates the sidecar file `path`, to which the rows of `parts` are
+    /// written in that order as the parts finish, replacing any rows of an
+    /// earlier attempt to generate the table
+    pub fn create(&self, path: &Path, parts: &[i32]) -> io::Result<()> {
+        *self.sidecar.lock().unwrap() = Sidecar {
+            file: Some(io::BufWriter::new(fs::File::create(path)?)),
+            pending: parts.iter().copied().collect(),
+            ..Sidecar::default()
+        };
+        Ok(())
+    }
+
+    /// Returns the writer of the rows of `part`
+    pub fn part(self: &Arc<Self>, part: i32) -> DirtyPart {
+        DirtyPart {
+            log: Arc::clone(self),
+            part,
+            sidecar: Vec::new(),
+        }
+    }
+
+    /// Flushes the sidecar file once all parts are finished, returning any
+    /// error writing the rows of the parts
+    pub fn finish(&self) -> io::Result<()> {
+        let mut sidecar = self.sidecar.lock().unwrap();
+        if let Some(err) = sidecar.error.take() {
+            return Err(err);
+        }
+        if let Some(part) = sidecar.pending.front() {
+            return Err(io::Error::other(format!(
+                "the corrupted rows of part {part} of {} were not written",
+                self.table
+            )));
+        }
+        match sidecar.file.take() {
+            Some(mut file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes the rows of one part of a table, corrupting those selected by its
+/// [`DirtyLog`]
+pub struct DirtyPart {
+    log: Arc<DirtyLog>,
+    part: i32,
+    /// The sidecar lines of the corrupted rows written so far
+    sidecar: Vec<u8>,
+}
+
+impl DirtyPart {
+    /// Appends `line`, a row in TBL format without a newline, to `buffer`,
+    /// corrupted if it is selected, and records it in the sidecar
+    ///
+    /// `columns` are the names of the columns of the row, and `key` its
+    /// primary key.
+    pub fn write_row(&mut self, buffer: &mut Vec<u8>, line: &str, columns: &[String], key: &[i64]) {
+        let table = self.log.table.name();
+        let Some(row) = self
+            .log
+            .dirty
+            .write_tbl_row(buffer, table, columns, key, line)
+        else {
+            return;
+        };
+        for k in key {
+            write!(&mut self.sidecar, "{k}|").expect("writing to memory is infallible");
+        }
+        writeln!(
+            &mut self.sidecar,
+            "{}|{}|",
+            row.corruption,
+            row.column.unwrap_or_default()
+        )
+        .expect("writing to memory is infallible");
+    }
+
+    /// Writes the recorded rows to the sidecar file of the log once all rows
+    /// of the part are written, or once those of the parts before it are
+    pub fn finish(self) {
+        let mut sidecar = self.log.sidecar.lock().unwrap();
+        sidecar.finished.insert(self.part, self.sidecar);
+        sidecar.write_ready();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nulls::column_names;
+    use crate::surrogate_key::SurrogateKey;
+    use tpchgen::generators::LineItemGenerator;
+
+    #[test]
+    fn sidecar_lists_corrupted_rows_in_part_order() {
+        let dirty = Box::leak(Box::new(DirtyData::new(10.0)));
+        let log = DirtyLog::new(dirty, Table::Lineitem);
+        let dir = std::env::temp_dir().join(format!("tpchgen-dirty-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lineitem.tbl.dirty");
+        log.create(&path, &[1, 2, 3]).unwrap();
+        let mut outputs = vec![];
+        // finish the parts out of order, as parallel generation may: only
+        // the rows of the parts waiting for part 1 are kept in memory
+        for (part, waiting) in [(3, 1), (2, 2), (1, 0)] {
+            let mut writer = log.part(part);
+            let mut buffer = Vec::new();
+            for item in LineItemGenerator::new(0.01, part, 3).iter() {
+                let columns = column_names(&item);
+                writer.write_row(&mut buffer, &item.to_string(), &columns, &item.key());
+            }
+            writer.finish();
+            assert_eq!(log.sidecar.lock().unwrap().finished.len(), waiting);
+            outputs.push(buffer);
+        }
+        log.finish().unwrap();
+        let sidecar = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let rows: Vec<_> = sidecar.lines().collect();
+        // about 10% of the 60175 line items
+        assert!((5500..6500).contains(&rows.len()), "{}", rows.len());
+        assert!(rows.windows(2).all(|w| {
+            let key = |row: &str| -> (i64, i64) {
+                let mut fields = row.split('|').map(|f| f.parse().unwrap());
+                (fields.next().unwrap(), fields.next().unwrap())
+            };
+            key(w[0]) < key(w[1])
+        }));
+        let duplicates = rows
+            .iter()
+            .filter(|r| r.contains("|duplicate-key|"))
+            .count();
+        let lines: usize = outputs
+            .iter()
+            .map(|output| output.iter().filter(|&&b| b == b'\n').count())
+            .sum();
+        assert_eq!(lines, 60175 + duplicates);
+    }
+
+    #[test]
+    fn finish_fails_for_unfinished_parts() {
+        let dirty = Box::leak(Box::new(DirtyData::new(10.0)));
+        let log = DirtyLog::new(dirty, Table::Nation);
+        let dir = std::env::temp_dir().join(format!("tpchgen-dirty-parts-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        log.create(&dir.join("nation.tbl.dirty"), &[1, 2]).unwrap();
+        log.part(2).finish();
+        let err = log.finish().unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            err.to_string(),
+            "the corrupted rows of part 1 of nation were not written"
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Free disk space preflight check (disable with `--no-space-check`)
+//!
+//! Before generating, the estimated size of the output is compared with the
+//! space available in the output directory so that a run fails immediately
+//! instead of when the disk fills up part way through.
+
+use log::debug;
+use std::io;
+use std::path::Path;
+
+/// Returns an error if the `estimated` size in bytes of the output is larger
+/// than the `available` bytes in `dir`.
+pub fn check_space(dir: &Path, estimated: u64, available: u64) -> io::Result<()> {
+    let gb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    debug!(
+        "Estimated output size {:.02} GB, {:.02} GB available",
+        gb(estimated),
+        gb(available)
+    );
+    if estimated > available {
+        return Err(io::Error::new(
+            io::ErrorKind::StorageFull,
+            format!(
+                "Estimated output size of {:.02} GB is larger than the {:.02} GB available in {}. Use --no-space-check to generate anyway",
+                gb(estimated),
+                gb(available),
+                dir.display()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the number of bytes available to unprivileged users on the
+/// filesystem containing `path`, or `None` if this is not known on the
+/// current platform.
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> io::Result<Option<u64>> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is a valid nul terminated string and `stat` is a valid
+    // statvfs struct for the duration of the call
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    #[allow(clippy::unnecessary_cast)] // the field types differ between platforms
+    Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+}
+
+/// Returns the number of bytes available on the filesystem containing
+/// `path`, or `None` if this is not known on the current platform.
+#[cfg(not(unix))]
+pub fn available_space(_path: &Path) -> io::Result<Option<u64>> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cli, Table};
+    use clap::Parser;
+    use tpchgen::generators::{
+        CustomerGenerator, OrderGenerator, PartGenerator, SupplierGenerator,
+    };
+
+    fn estimated_output_size(args: &[&str], table: Table) -> u64 {
+        let argv = std::iter::once("tpchgen-cli").chain(args.iter().copied());
+        Cli::try_parse_from(argv)
+            .unwrap()
+            .estimated_output_size(table)
+    }
+
+    /// Returns the size in bytes of `rows` in TBL format
+    fn tbl_size<T: std::fmt::Display>(rows: impl IntoIterator<Item = T>) -> i64 {
+        rows.into_iter()
+            .map(|row| format!("{row}\n").len() as i64)
+            .sum()
+    }
+
+    #[test]
+    fn estimates_tbl_size() {
+        let scale_factor = 0.01;
+        let actual = [
+            (
+                Table::Part,
+                tbl_size(PartGenerator::new(scale_factor, 1, 1)),
+            ),
+            (
+                Table::Supplier,
+                tbl_size(SupplierGenerator::new(scale_factor, 1, 1)),
+            ),
+            (
+                Table::Customer,
+                tbl_size(CustomerGenerator::new(scale_factor, 1, 1)),
+            ),
+            (
+                Table::Orders,
+                tbl_size(OrderGenerator::new(scale_factor, 1, 1)),
+            ),
+        ];
+        for (table, actual) in actual {
+            let (avg_row_size_bytes, row_count) = table.estimated_tbl_size(scale_factor);
+            let estimated = avg_row_size_bytes * row_count;
+            let error = (estimated - actual).abs() as f64 / actual as f64;
+            assert!(
+                error < 0.05,
+                "{table:?}: estimated {estimated} bytes, generated {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn estimates_output_size() {
+        let (avg_row_size_bytes, row_count) = Table::Lineitem.estimated_tbl_size(1.0);
+        let tbl = (avg_row_size_bytes * row_count) as u64;
+        assert_eq!(estimated_output_size(&[], Table::Lineitem), tbl);
+        assert_eq!(
+            estimated_output_size(
+                &["--format", "parquet", "-c", "uncompressed"],
+                Table::Lineitem
+            ),
+            tbl / 2
+        );
+        // compressed TBL is about a third of the size
+        let gzip = estimated_output_size(&["--compression", "gzip"], Table::Lineitem);
+        assert_eq!(gzip, (tbl as f64 * 0.35) as u64);
+        // one of 4 parts
+        let part = estimated_output_size(&["--parts", "4", "--part", "2"], Table::Lineitem);
+        assert_eq!(part, tbl / 4);
+    }
+
+    #[test]
+    fn checks_space() {
+        let dir = Path::new("/data/tpch");
+        check_space(dir, 0, 0).unwrap();
+        check_space(dir, 1 << 30, 1 << 30).unwrap();
+
+        let err = check_space(dir, 3 << 30, 1 << 30).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+        assert_eq!(
+            err.to_string(),
+            "Estimated output size of 3.00 GB is larger than the 1.00 GB available in \
+             /data/tpch. Use --no-space-check to generate anyway"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_available_space() {
+        let available = available_space(&std::env::temp_dir()).unwrap();
+        assert!(available.is_some());
+        assert!(available_space(Path::new("/does/not/exist")).is_err());
+    }
+}
@@ -0,0 +1,418 @@
+//! Nested order documents (`tpchgen-cli documents`)
+//!
+//! Generates ORDERS and LINEITEM together and writes one JSON document per
+//! order, with the line items of the order embedded, as JSON lines that can
+//! be imported into document stores such as MongoDB (`mongoimport`),
+//! Couchbase (`cbimport json --format lines`) or Elasticsearch:
+//!
+//! ```text
+//! {"o_orderkey":1,"o_custkey":370,"o_orderstatus":"O","o_totalprice":172799.49,"o_orderdate":"1996-01-02",...,"lineitems":[{"l_partkey":156,"l_suppkey":4,"l_linenumber":1,...},...]}
+//! ```
+//!
+//! The line items omit `l_orderkey`, which is the key of the document.
+//! Decimals are written as JSON numbers and dates as `YYYY-MM-DD` strings.
+//!
+//! With `--format=bson` the documents are written as BSON instead, with the
+//! types of [`crate::bson`], for `mongorestore`.
+//!
+//! With `--format=parquet` the orders are written as Parquet instead, with
+//! the columns of ORDERS and a `lineitems: LIST<STRUCT<...>>` column of the
+//! columns of LINEITEM other than `l_orderkey`, for testing readers of nested
+//! types. The columns have the types of the tables, see
+//! [`OrderDocumentArrow`].
+//!
+//! Part `n` of ORDERS contains the same orders as part `n` of LINEITEM, so
+//! each part is generated by merging the two generators without joining.
+//! With `--filter`, orders are written with the line items that match the
+//! filters on LINEITEM, which may be none.
+
+use crate::{bson, Table};
+use arrow::array::{ListArray, RecordBatch, StructArray};
+use arrow::buffer::OffsetBuffer;
+use arrow::datatypes::{DataType, Field, Fields, Schema, SchemaRef};
+use std::fmt::Display;
+use std::io::Write;
+use std::iter::Peekable;
+use std::sync::Arc;
+use tpchgen::generators::{
+    LineItem, LineItemGenerator, LineItemGeneratorIterator, Order, OrderGenerator,
+    OrderGeneratorIterator,
+};
+use tpchgen_arrow::{
+    ColumnTypes, LineItemArrow, OrderArrow, RecordBatchIterator, DEFAULT_BATCH_SIZE,
+};
+use tpchgen_cli::generate::Source;
+
+/// A [`Source`] that writes the orders of a part with their line items as
+/// JSON lines
+pub struct OrderDocumentSource {
+    orders: OrderGenerator<'static>,
+    lineitems: LineItemGenerator<'static>,
+    bson: bool,
+}
+
+impl OrderDocumentSource {
+    /// Create a source for the orders and line items of the same part
+    pub fn new(orders: OrderGenerator<'static>, lineitems: LineItemGenerator<'static>) -> Self {
+        Self {
+            orders,
+            lineitems,
+            bson: false,
+        }
+    }
+
+    /// Write BSON documents instead of JSON lines
+    pub fn with_bson(mut self, bson: bool) -> Self {
+        self.bson = bson;
+        self
+    }
+}
+
+impl Source for OrderDocumentSource {
+    fn header(&self, buffer: Vec<u8>) -> Vec<u8> {
+        // JSON lines do not have a header
+        buffer
+    }
+
+    fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+        let mut lineitems = self.lineitems.iter().peekable();
+        for order in self.orders.iter() {
+            // skip the line items of orders removed by a filter
+            while lineitems
+                .next_if(|item| item.l_orderkey < order.o_orderkey)
+                .is_some()
+            {}
+            let lineitems = std::iter::from_fn(|| {
+                lineitems.next_if(|item| item.l_orderkey == order.o_orderkey)
+            });
+            if self.bson {
+                write_bson_document(&mut buffer, &order, lineitems);
+                continue;
+            }
+            write_order(&mut buffer, &order);
+            buffer.extend_from_slice(br#","lineitems":["#);
+            let mut first = true;
+            for item in lineitems {
+                if !first {
+                    buffer.push(b',');
+                }
+                first = false;
+                write_lineitem(&mut buffer, &item);
+            }
+            buffer.extend_from_slice(b"]}\n");
+        }
+        buffer
+    }
+}
+
+/// Generates the orders of a part with their line items in [`RecordBatch`]es
+///
+/// Each batch has the columns of [`OrderArrow`] followed by a `lineitems`
+/// list of structs with the columns of [`LineItemArrow`] other than
+/// `l_orderkey`:
+///
+/// ```text
+/// o_orderkey: Int64, ..., o_comment: Utf8View,
+/// lineitems: List<Struct<l_partkey: Int64, ..., l_comment: Utf8View>>
+/// ```
+pub struct OrderDocumentArrow {
+    orders: OrderGeneratorIterator<'static>,
+    lineitems: Peekable<LineItemGeneratorIterator<'static>>,
+    batch_size: usize,
+    column_types: ColumnTypes,
+    order_schema: SchemaRef,
+    lineitem_schema: SchemaRef,
+    schema: SchemaRef,
+}
+
+impl OrderDocumentArrow {
+    /// Create a generator of the orders and line items of the same part
+    pub fn new(orders: OrderGenerator<'static>, lineitems: LineItemGenerator<'static>) -> Self {
+        let order_schema = Table::Orders.schema();
+        let lineitem_schema = Table::Lineitem.schema();
+        Self {
+            orders: orders.iter(),
+            lineitems: lineitems.iter().peekable(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            column_types: ColumnTypes::default(),
+            schema: document_schema(&order_schema, &lineitem_schema),
+            order_schema,
+            lineitem_schema,
+        }
+    }
+
+    /// Set the number of orders in each batch
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set the Arrow types of the columns, including those of the line
+    /// items, see [`ColumnTypes`]
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.column_types = *column_types;
+        self.order_schema = column_types.schema(&Table::Orders.schema());
+        self.lineitem_schema = column_types.schema(&Table::Lineitem.schema());
+        self.schema = document_schema(&self.order_schema, &self.lineitem_schema);
+        self
+    }
+}
+
+impl RecordBatchIterator for OrderDocumentArrow {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl Iterator for OrderDocumentArrow {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let orders: Vec<_> = self.orders.by_ref().take(self.batch_size).collect();
+        if orders.is_empty() {
+            return None;
+        }
+        let mut items = Vec::new();
+        let mut lengths = Vec::with_capacity(orders.len());
+        for order in &orders {
+            // skip the line items of orders removed by a filter
+            while self
+                .lineitems
+                .next_if(|item| item.l_orderkey < order.o_orderkey)
+                .is_some()
+            {}
+            let start = items.len();
+            while let Some(item) = self
+                .lineitems
+                .next_if(|item| item.l_orderkey == order.o_orderkey)
+            {
+                items.push(item);
+            }
+            lengths.push(items.len() - start);
+        }
+        let orders = OrderArrow::batch_from_rows(&orders);
+        let orders = self.column_types.convert(orders, &self.order_schema);
+        let items = LineItemArrow::batch_from_rows(&items);
+        let items = self.column_types.convert(items, &self.lineitem_schema);
+        // the columns other than l_orderkey, which is the key of the order
+        let items = items
+            .project(&(1..items.num_columns()).collect::<Vec<_>>())
+            .unwrap();
+        let DataType::List(item_field) = self.schema.field(orders.num_columns()).data_type() else {
+            unreachable!("lineitems is a list");
+        };
+        let lineitems = ListArray::new(
+            Arc::clone(item_field),
+            OffsetBuffer::from_lengths(lengths),
+            Arc::new(StructArray::from(items)),
+            None,
+        );
+        let mut columns = orders.columns().to_vec();
+        columns.push(Arc::new(lineitems));
+        Some(RecordBatch::try_new(Arc::clone(&self.schema), columns).unwrap())
+    }
+}
+
+/// Returns the schema of the orders with a `lineitems` list of structs of
+/// the line item columns other than `l_orderkey`
+fn document_schema(order_schema: &SchemaRef, lineitem_schema: &SchemaRef) -> SchemaRef {
+    let item_fields: Fields = lineitem_schema.fields().iter().skip(1).cloned().collect();
+    let item_field = Field::new_list_field(DataType::Struct(item_fields), false);
+    let lineitems = Field::new("lineitems", DataType::List(Arc::new(item_field)), false);
+    let mut fields: Vec<_> = order_schema.fields().iter().cloned().collect();
+    fields.push(Arc::new(lineitems));
+    Arc::new(Schema::new(fields))
+}
+
+/// Appends the fields of `order`, without the closing brace
+pub fn write_order(out: &mut Vec<u8>, order: &Order) {
+    write!(
+        out,
+        r#"{{"o_orderkey":{},"o_custkey":{},"o_orderstatus":{},"o_totalprice":{},"o_orderdate":{},"o_orderpriority":{},"o_clerk":{},"o_shippriority":{},"o_comment":{}"#,
+        order.o_orderkey,
+        order.o_custkey,
+        Json(&order.o_orderstatus),
+        order.o_totalprice,
+        Json(&order.o_orderdate),
+        Json(&order.o_orderpriority),
+        Json(&order.o_clerk),
+        order.o_shippriority,
+        Json(order.o_comment),
+    )
+    .expect("writing to memory is infallible");
+}
+
+/// Appends `item` as a JSON object, without `l_orderkey`
+fn write_lineitem(out: &mut Vec<u8>, item: &LineItem) {
+    out.push(b'{');
+    write_lineitem_fields(out, item);
+    out.push(b'}');
+}
+
+/// Appends the fields of `item` other than `l_orderkey`, without braces
+pub fn write_lineitem_fields(out: &mut Vec<u8>, item: &LineItem) {
+    write!(
+        out,
+        r#""l_partkey":{},"l_suppkey":{},"l_linenumber":{},"l_quantity":{},"l_extendedprice":{},"l_discount":{},"l_tax":{},"l_returnflag":{},"l_linestatus":{},"l_shipdate":{},"l_commitdate":{},"l_receiptdate":{},"l_shipinstruct":{},"l_shipmode":{},"l_comment":{}"#,
+        item.l_partkey,
+        item.l_suppkey,
+        item.l_linenumber,
+        item.l_quantity,
+        item.l_extendedprice,
+        item.l_discount,
+        item.l_tax,
+        Json(&item.l_returnflag),
+        Json(&item.l_linestatus),
+        Json(&item.l_shipdate),
+        Json(&item.l_commitdate),
+        Json(&item.l_receiptdate),
+        Json(item.l_shipinstruct),
+        Json(&item.l_shipmode),
+        Json(item.l_comment),
+    )
+    .expect("writing to memory is infallible");
+}
+
+/// Appends `order` and its `lineitems` as a BSON document
+fn write_bson_document<'a>(
+    out: &mut Vec<u8>,
+    order: &Order,
+    lineitems: impl Iterator<Item = LineItem<'a>>,
+) {
+    let document = bson::start_document(out);
+    bson::write_i64(out, "o_orderkey", order.o_orderkey);
+    bson::write_i64(out, "o_custkey", order.o_custkey);
+    bson::write_string(out, "o_orderstatus", &order.o_orderstatus.to_string());
+    bson::write_decimal(out, "o_totalprice", order.o_totalprice.0 as i128, 2);
+    bson::write_date(out, "o_orderdate", order.o_orderdate.to_unix_epoch());
+    bson::write_string(out, "o_orderpriority", &order.o_orderpriority.to_string());
+    bson::write_string(out, "o_clerk", &order.o_clerk.to_string());
+    bson::write_i32(out, "o_shippriority", order.o_shippriority);
+    bson::write_string(out, "o_comment", order.o_comment);
+    let array = bson::start_array(out, "lineitems");
+    for (index, item) in lineitems.enumerate() {
+        let element = bson::start_array_document(out, index);
+        bson::write_i64(out, "l_partkey", item.l_partkey);
+        bson::write_i64(out, "l_suppkey", item.l_suppkey);
+        bson::write_i32(out, "l_linenumber", item.l_linenumber);
+        // a decimal, as in the LINEITEM table
+        bson::write_decimal(out, "l_quantity", item.l_quantity as i128 * 100, 2);
+        bson::write_decimal(out, "l_extendedprice", item.l_extendedprice.0 as i128, 2);
+        bson::write_decimal(out, "l_discount", item.l_discount.0 as i128, 2);
+        bson::write_decimal(out, "l_tax", item.l_tax.0 as i128, 2);
+        bson::write_string(out, "l_returnflag", &item.l_returnflag.to_string());
+        bson::write_string(out, "l_linestatus", &item.l_linestatus.to_string());
+        bson::write_date(out, "l_shipdate", item.l_shipdate.to_unix_epoch());
+        bson::write_date(out, "l_commitdate", item.l_commitdate.to_unix_epoch());
+        bson::write_date(out, "l_receiptdate", item.l_receiptdate.to_unix_epoch());
+        bson::write_string(out, "l_shipinstruct", item.l_shipinstruct);
+        bson::write_string(out, "l_shipmode", &item.l_shipmode.to_string());
+        bson::write_string(out, "l_comment", item.l_comment);
+        bson::end_document(out, element);
+    }
+    bson::end_document(out, array);
+    bson::end_document(out, document);
+}
+
+/// Formats a value as a quoted and escaped JSON string
+pub struct Json<T>(pub T);
+
+impl<T: Display> Display for Json<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = self.0.to_string();
+        f.write_str("\"")?;
+        for c in value.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, AsArray};
+    use arrow::datatypes::Int64Type;
+
+    #[test]
+    fn embeds_lineitems() {
+        let source = OrderDocumentSource::new(
+            OrderGenerator::new(0.01, 1, 10),
+            LineItemGenerator::new(0.01, 1, 10),
+        );
+        let output = String::from_utf8(source.create(Vec::new())).unwrap();
+        let documents: Vec<_> = output.lines().collect();
+        assert_eq!(documents.len(), 1500);
+        assert!(documents[0].starts_with(
+            r#"{"o_orderkey":1,"o_custkey":370,"o_orderstatus":"O","o_totalprice":172799.49,"o_orderdate":"1996-01-02","#
+        ));
+        // order 1 has 6 line items
+        assert!(documents[0].contains(
+            r#","lineitems":[{"l_partkey":1552,"l_suppkey":93,"l_linenumber":1,"l_quantity":17,"#
+        ));
+        assert_eq!(documents[0].matches("l_linenumber").count(), 6);
+        let lineitems: usize = documents
+            .iter()
+            .map(|d| d.matches("l_linenumber").count())
+            .sum();
+        assert_eq!(
+            lineitems,
+            LineItemGenerator::new(0.01, 1, 10).iter().count()
+        );
+    }
+
+    #[test]
+    fn writes_bson() {
+        let new_source = || {
+            OrderDocumentSource::new(
+                OrderGenerator::new(0.01, 2, 10),
+                LineItemGenerator::new(0.01, 2, 10),
+            )
+        };
+        let json = new_source().create(Vec::new());
+        let bson = new_source().with_bson(true).create(Vec::new());
+        assert_eq!(
+            bson::count_documents(&bson),
+            json.iter().filter(|&&b| b == b'\n').count()
+        );
+    }
+
+    #[test]
+    fn nests_lineitems() {
+        let batches: Vec<_> = OrderDocumentArrow::new(
+            OrderGenerator::new(0.01, 1, 10),
+            LineItemGenerator::new(0.01, 1, 10),
+        )
+        .with_batch_size(1000)
+        .collect();
+        assert_eq!(batches.len(), 2);
+        let batch = &batches[0];
+        assert_eq!(batch.num_columns(), 10);
+        let lineitems = batch.column_by_name("lineitems").unwrap().as_list::<i32>();
+        // order 1 has 6 line items
+        assert_eq!(lineitems.value_length(0), 6);
+        let items = lineitems.value(0);
+        let items = items.as_struct();
+        assert_eq!(items.num_columns(), 15);
+        assert_eq!(items.column_names()[0], "l_partkey");
+        assert_eq!(
+            items.column(0).as_primitive::<Int64Type>().values()[..2],
+            [1552, 674]
+        );
+        let total: usize = batches
+            .iter()
+            .map(|batch| batch.column(9).as_list::<i32>().values().len())
+            .sum();
+        assert_eq!(total, LineItemGenerator::new(0.01, 1, 10).iter().count());
+    }
+
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(Json("a\"b\\c\nd").to_string(), r#""a\"b\\c\u000ad""#);
+    }
+}
@@ -0,0 +1,146 @@
+//! Loading into a DuckDB database file with the appender API (`--duckdb`)
+//!
+//! Requires the `duckdb` feature, which compiles DuckDB into tpchgen-cli.
+//! Instead of writing files, the Arrow batches for each table are appended
+//! directly to the database, without an intermediate Parquet or CSV file:
+//!
+//! * The database file is created if it does not exist
+//! * The table is created (`CREATE TABLE IF NOT EXISTS`) if needed, with the
+//!   statement of `--format=sql`, see [`crate::sql`]
+//! * Parts are generated and appended concurrently, each with its own
+//!   [`Appender`] on its own connection to the database
+//!
+//! Rows within a part are appended in order, but parts may complete in any
+//! order.
+
+use crate::progress::{write_callbacks, ProgressReporter};
+use crate::sql::{create_table_sql, SqlDialect};
+use duckdb::{Appender, Connection};
+use futures::StreamExt;
+use log::debug;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::statistics::WriteStatistics;
+
+/// Options for loading tables into DuckDB
+#[derive(Debug, Clone, Copy)]
+pub struct LoadOptions {
+    /// Number of parts appended concurrently (and connections used) per table
+    pub concurrency: usize,
+}
+
+/// Loads the batches from a set of RecordBatchIterators into `table` in the
+/// DuckDB database file at `path`
+pub async fn load_table<I>(
+    path: &Path,
+    table: &'static str,
+    iter_iter: I,
+    options: LoadOptions,
+    progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+{
+    debug!(
+        "Loading {table} into {} with {} appenders",
+        path.display(),
+        options.concurrency
+    );
+    let mut iter_iter = iter_iter.peekable();
+
+    // get schema from the first iterator
+    let Some(first_iter) = iter_iter.peek() else {
+        return Ok(()); // no data shrug
+    };
+    let schema = Arc::clone(first_iter.schema());
+
+    // DuckDB's types are those of PostgreSQL for the columns of TPC-H
+    let connection = Connection::open(path).map_err(io::Error::other)?;
+    connection
+        .execute_batch(&create_table_sql(SqlDialect::Postgres, table, &schema)?)
+        .map_err(io::Error::other)?;
+
+    let mut statistics =
+        WriteStatistics::new("parts").with_callbacks(write_callbacks(progress.as_ref()));
+    let progress = progress.map(|p| Arc::new(Mutex::new(p)));
+
+    let mut loads = futures::stream::iter(iter_iter)
+        .map(|iter| {
+            let connection = connection.try_clone().map_err(io::Error::other);
+            let progress = progress.clone();
+            async move {
+                let connection = connection?;
+                // generate and append each part on a separate thread, as
+                // appending blocks
+                tokio::task::spawn_blocking(move || {
+                    let mut appender = connection.appender(table).map_err(io::Error::other)?;
+                    let (rows, bytes) = append_part(&mut appender, iter)?;
+                    if let Some(progress) = progress {
+                        progress.lock().unwrap().part_complete(rows, bytes);
+                    }
+                    Ok(bytes) as Result<u64, io::Error>
+                })
+                .await
+                .expect("Inner task panicked")
+            }
+        })
+        .buffer_unordered(options.concurrency);
+
+    while let Some(bytes) = loads.next().await {
+        statistics.increment_bytes(bytes? as usize);
+        statistics.increment_chunks(1);
+    }
+    Ok(())
+}
+
+/// Appends the batches of a single part and flushes them, returning the
+/// number of rows and the size of the batches in memory
+fn append_part(
+    appender: &mut Appender<'_>,
+    batches: impl Iterator<Item = arrow::array::RecordBatch>,
+) -> Result<(u64, u64), io::Error> {
+    let mut rows = 0;
+    let mut bytes = 0;
+    for batch in batches {
+        rows += batch.num_rows() as u64;
+        bytes += batch.get_array_memory_size() as u64;
+        appender
+            .append_record_batch(batch)
+            .map_err(io::Error::other)?;
+    }
+    appender.flush().map_err(io::Error::other)?;
+    Ok((rows, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tpchgen::generators::SupplierGenerator;
+    use tpchgen_arrow::SupplierArrow;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn appends_parts() {
+        let dir = std::env::temp_dir().join(format!("tpchgen-duckdb-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tpch.duckdb");
+        // 100 suppliers in 3 parts
+        let parts = (1..=3).map(|part| SupplierArrow::new(SupplierGenerator::new(0.01, part, 3)));
+        let options = LoadOptions { concurrency: 2 };
+        load_table(&path, "supplier", parts, options, None)
+            .await
+            .unwrap();
+
+        let connection = Connection::open(&path).unwrap();
+        let (rows, keys): (i64, i64) = connection
+            .query_row(
+                "SELECT count(*), sum(s_suppkey)::BIGINT FROM supplier",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((rows, keys), (100, (1..=100).sum()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,104 @@
+//! Character encodings of TBL and CSV output (`--encoding`)
+//!
+//! By default generated data is ASCII, so every encoding writes the same
+//! bytes, except that `utf8-bom` starts the file with a byte order mark.
+//! Files converted with `tpchgen-cli convert` or generated with
+//! `--extended-text` may contain other characters: these are transcoded to
+//! `latin1`, and text that can not be represented in the encoding is an
+//! error rather than a file that a loader later rejects.
+
+use clap::ValueEnum;
+use std::borrow::Cow;
+use std::io;
+
+/// Byte order mark written at the start of `utf8-bom` files
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Character encoding of text output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TextEncoding {
+    /// UTF-8 without a byte order mark
+    #[default]
+    Utf8,
+    /// UTF-8 starting with a byte order mark, for loaders that require one
+    Utf8Bom,
+    /// ISO-8859-1
+    Latin1,
+    /// 7-bit ASCII
+    Ascii,
+}
+
+impl TextEncoding {
+    /// Returns the bytes written at the start of a file
+    pub fn prefix(&self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf8Bom => UTF8_BOM,
+            TextEncoding::Utf8 | TextEncoding::Latin1 | TextEncoding::Ascii => &[],
+        }
+    }
+
+    /// Encodes UTF-8 `text`, borrowing it if the bytes do not change
+    pub fn encode<'a>(&self, text: &'a [u8]) -> io::Result<Cow<'a, [u8]>> {
+        if text.is_ascii() {
+            return Ok(Cow::Borrowed(text));
+        }
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => Ok(Cow::Borrowed(text)),
+            TextEncoding::Ascii => {
+                let text = String::from_utf8_lossy(text);
+                let c = text.chars().find(|c| !c.is_ascii()).unwrap();
+                Err(unencodable(c, "ascii"))
+            }
+            TextEncoding::Latin1 => {
+                let text = std::str::from_utf8(text)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                text.chars()
+                    .map(|c| u8::try_from(c).map_err(|_| unencodable(c, "latin1")))
+                    .collect::<io::Result<Vec<u8>>>()
+                    .map(Cow::Owned)
+            }
+        }
+    }
+}
+
+fn unencodable(c: char, encoding: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "character '{c}' (U+{:04X}) can not be encoded as {encoding}",
+            c as u32
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_text() {
+        let ascii = b"1|Customer#000000001|".as_slice();
+        for encoding in TextEncoding::value_variants() {
+            assert_eq!(encoding.encode(ascii).unwrap(), ascii);
+        }
+        assert_eq!(TextEncoding::Utf8Bom.prefix(), UTF8_BOM);
+        assert!(TextEncoding::Utf8.prefix().is_empty());
+
+        let text = "1|Müller|".as_bytes();
+        assert_eq!(TextEncoding::Utf8.encode(text).unwrap(), text);
+        assert_eq!(
+            TextEncoding::Latin1.encode(text).unwrap(),
+            b"1|M\xFCller|".as_slice()
+        );
+        let err = TextEncoding::Ascii.encode(text).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "character 'ü' (U+00FC) can not be encoded as ascii"
+        );
+        let err = TextEncoding::Latin1.encode("1|€|".as_bytes()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "character '€' (U+20AC) can not be encoded as latin1"
+        );
+    }
+}
@@ -0,0 +1,476 @@
+//! Row filters for `--filter`
+//!
+//! A filter is a simple comparison between a key, integer or date column and
+//! a constant, for example `l_shipdate>=1995-01-01` or `o_custkey<1000`. Rows
+//! that do not match every filter on their table are skipped during
+//! generation (see `with_filter` on the generators in [`tpchgen::generators`]).
+//!
+//! Filters only remove rows: the values of the remaining rows are the same as
+//! in the unfiltered output. Filters on the keys that determine the rows
+//! generated, `p_partkey`, `ps_partkey`, `s_suppkey`, `c_custkey`,
+//! `o_orderkey` and `l_orderkey`, are also pushed down into the generators,
+//! see [`key_range`], so only the rows in their range are generated. Filters
+//! on the dates of ORDERS and LINEITEM, which follow the order date, are
+//! pushed down as a range of order dates, see [`order_date_range`], so the
+//! other columns of the orders outside it are not generated. The other
+//! filters, on the foreign keys and other random values, are applied to every
+//! generated row.
+
+use crate::Table;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use tpchgen::dates::TPCHDate;
+use tpchgen::generators::{
+    Customer, LineItem, LineItemGenerator, Nation, Order, Part, PartSupp, Region, Supplier,
+};
+
+/// Columns that can be used in a filter, with the table they belong to and
+/// whether they are dates
+const COLUMNS: &[(&str, Table, bool)] = &[
+    ("n_nationkey", Table::Nation, false),
+    ("n_regionkey", Table::Nation, false),
+    ("r_regionkey", Table::Region, false),
+    ("p_partkey", Table::Part, false),
+    ("p_size", Table::Part, false),
+    ("s_suppkey", Table::Supplier, false),
+    ("s_nationkey", Table::Supplier, false),
+    ("ps_partkey", Table::Partsupp, false),
+    ("ps_suppkey", Table::Partsupp, false),
+    ("ps_availqty", Table::Partsupp, false),
+    ("c_custkey", Table::Customer, false),
+    ("c_nationkey", Table::Customer, false),
+    ("o_orderkey", Table::Orders, false),
+    ("o_custkey", Table::Orders, false),
+    ("o_orderdate", Table::Orders, true),
+    ("l_orderkey", Table::Lineitem, false),
+    ("l_partkey", Table::Lineitem, false),
+    ("l_suppkey", Table::Lineitem, false),
+    ("l_linenumber", Table::Lineitem, false),
+    ("l_quantity", Table::Lineitem, false),
+    ("l_shipdate", Table::Lineitem, true),
+    ("l_commitdate", Table::Lineitem, true),
+    ("l_receiptdate", Table::Lineitem, true),
+];
+
+/// Comparison operator of a [`Predicate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl Op {
+    /// Operators in the order they are matched, so that `<=` is found before `<`
+    const ALL: [(&'static str, Op); 6] = [
+        ("!=", Op::NotEq),
+        ("<=", Op::LtEq),
+        (">=", Op::GtEq),
+        ("=", Op::Eq),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+    ];
+}
+
+/// A comparison between a column and a constant, e.g. `l_shipdate>=1995-01-01`
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    column: &'static str,
+    table: Table,
+    op: Op,
+    /// The constant; dates are stored as days since the Unix epoch
+    value: i64,
+}
+
+impl Predicate {
+    /// Returns the table this predicate filters
+    pub fn table(&self) -> Table {
+        self.table
+    }
+
+    /// Returns the smallest and largest values that satisfy this predicate,
+    /// or `None` for `!=`
+    fn bounds(&self) -> Option<(i64, i64)> {
+        Some(match self.op {
+            Op::Eq => (self.value, self.value),
+            Op::NotEq => return None,
+            Op::Lt => (i64::MIN, self.value.saturating_sub(1)),
+            Op::LtEq => (i64::MIN, self.value),
+            Op::Gt => (self.value.saturating_add(1), i64::MAX),
+            Op::GtEq => (self.value, i64::MAX),
+        })
+    }
+
+    /// Returns true if `row` satisfies this predicate
+    pub fn matches(&self, row: &impl FilterRow) -> bool {
+        let value = row
+            .column(self.column)
+            .unwrap_or_else(|| panic!("{} is not a column of {}", self.column, self.table));
+        match self.op {
+            Op::Eq => value == self.value,
+            Op::NotEq => value != self.value,
+            Op::Lt => value < self.value,
+            Op::LtEq => value <= self.value,
+            Op::Gt => value > self.value,
+            Op::GtEq => value >= self.value,
+        }
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((start, op_str, op)) = Op::ALL
+            .iter()
+            .filter_map(|&(op_str, op)| s.find(op_str).map(|start| (start, op_str, op)))
+            .min_by_key(|&(start, _, _)| start)
+        else {
+            return Err(format!(
+                "invalid filter '{s}', expected <column><op><value> where <op> is one of =, !=, <, <=, >, >="
+            ));
+        };
+        let name = s[..start].trim();
+        let value = s[start + op_str.len()..].trim();
+
+        let Some(&(column, table, is_date)) = COLUMNS.iter().find(|(c, _, _)| *c == name) else {
+            let columns: Vec<_> = COLUMNS.iter().map(|(c, _, _)| *c).collect();
+            return Err(format!(
+                "can not filter on column '{name}', supported columns are: {}",
+                columns.join(", ")
+            ));
+        };
+        let value = if is_date {
+            parse_date(value).ok_or_else(|| {
+                format!("invalid date '{value}' for {column}, expected YYYY-MM-DD")
+            })?
+        } else {
+            value
+                .parse()
+                .map_err(|_| format!("invalid integer '{value}' for {column}"))?
+        };
+        Ok(Self {
+            column,
+            table,
+            op,
+            value,
+        })
+    }
+}
+
+/// Returns the columns of `table` that can be filtered on, and whether they
+/// are dates
+pub fn columns(table: Table) -> impl Iterator<Item = (&'static str, bool)> {
+    COLUMNS
+        .iter()
+        .filter(move |(_, t, _)| *t == table)
+        .map(|&(column, _, is_date)| (column, is_date))
+}
+
+/// Returns true if `row` satisfies all `predicates`
+pub fn matches_all(predicates: &[Predicate], row: &impl FilterRow) -> bool {
+    predicates.iter().all(|p| p.matches(row))
+}
+
+/// Columns whose filters are pushed down into the generators, see
+/// [`key_range`]
+const KEY_COLUMNS: &[&str] = &[
+    "p_partkey",
+    "ps_partkey",
+    "s_suppkey",
+    "c_custkey",
+    "o_orderkey",
+    "l_orderkey",
+];
+
+/// Returns the range of keys that can match `predicates`, the filters on one
+/// table, or `None` if they do not filter on the key of the table in
+/// [`KEY_COLUMNS`]
+///
+/// The rows outside the range need not be generated, see
+/// `PartGenerator::with_part_keys` and `OrderGenerator::with_order_keys`.
+/// The range may be empty, and `!=` does not narrow it.
+pub fn key_range(predicates: &[Predicate]) -> Option<RangeInclusive<i64>> {
+    intersect(
+        predicates
+            .iter()
+            .filter(|p| KEY_COLUMNS.contains(&p.column))
+            .filter_map(Predicate::bounds),
+    )
+}
+
+/// Date columns whose filters are pushed down into the generators, with the
+/// smallest and largest number of days they are after the order date, see
+/// [`order_date_range`]
+const ORDER_DATE_COLUMNS: &[(&str, i64, i64)] = &[
+    ("o_orderdate", 0, 0),
+    (
+        "l_shipdate",
+        LineItemGenerator::SHIP_DATE_MIN as i64,
+        LineItemGenerator::SHIP_DATE_MAX as i64,
+    ),
+    (
+        "l_commitdate",
+        LineItemGenerator::COMMIT_DATE_MIN as i64,
+        LineItemGenerator::COMMIT_DATE_MAX as i64,
+    ),
+    (
+        "l_receiptdate",
+        (LineItemGenerator::SHIP_DATE_MIN + LineItemGenerator::RECEIPT_DATE_MIN) as i64,
+        (LineItemGenerator::SHIP_DATE_MAX + LineItemGenerator::RECEIPT_DATE_MAX) as i64,
+    ),
+];
+
+/// Returns the range of order dates, as days since the Unix epoch, of the
+/// rows that can match `predicates`, the filters on ORDERS or LINEITEM, or
+/// `None` if they do not filter on a date in [`ORDER_DATE_COLUMNS`]
+///
+/// The rows of the orders outside the range need not be generated, see
+/// `OrderGenerator::with_order_dates`. The ship, commit and receipt dates of
+/// the line items are a bounded number of days after the order date, so
+/// their filters narrow the range of order dates by those days. The range
+/// may be empty, and `!=` does not narrow it.
+pub fn order_date_range(predicates: &[Predicate]) -> Option<RangeInclusive<i32>> {
+    let range = intersect(predicates.iter().filter_map(|p| {
+        let &(_, min_days, max_days) =
+            ORDER_DATE_COLUMNS.iter().find(|(c, _, _)| *c == p.column)?;
+        let (start, end) = p.bounds()?;
+        Some((start.saturating_sub(max_days), end.saturating_sub(min_days)))
+    }))?;
+    let clamp = |days: i64| days.clamp(i32::MIN.into(), i32::MAX.into()) as i32;
+    Some(clamp(*range.start())..=clamp(*range.end()))
+}
+
+/// Returns the intersection of the ranges from the smallest to the largest
+/// value of `bounds`, or `None` if there are none
+fn intersect(bounds: impl Iterator<Item = (i64, i64)>) -> Option<RangeInclusive<i64>> {
+    bounds
+        .reduce(|(s1, e1), (s2, e2)| (s1.max(s2), e1.min(e2)))
+        .map(|(start, end)| start..=end)
+}
+
+/// Parses a `YYYY-MM-DD` date into days since the Unix epoch
+fn parse_date(s: &str) -> Option<i64> {
+    let mut parts = s.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    TPCHDate::ymd_to_epoch_days(year, month, day).map(i64::from)
+}
+
+/// A generated row whose key, integer and date columns can be filtered on
+pub trait FilterRow {
+    /// Returns the value of `column`, with dates as days since the Unix
+    /// epoch, or `None` if the row has no such column
+    fn column(&self, column: &str) -> Option<i64>;
+}
+
+impl FilterRow for Nation<'_> {
+    fn column(&self, column: &str) -> Option<i64> {
+        match column {
+            "n_nationkey" => Some(self.n_nationkey),
+            "n_regionkey" => Some(self.n_regionkey),
+            _ => None,
+        }
+    }
+}
+
+impl FilterRow for Region<'_> {
+    fn column(&self, column: &str) -> Option<i64> {
+        match column {
+            "r_regionkey" => Some(self.r_regionkey),
+            _ => None,
+        }
+    }
+}
+
+impl FilterRow for Part<'_> {
+    fn column(&self, column: &str) -> Option<i64> {
+        match column {
+            "p_partkey" => Some(self.p_partkey),
+            "p_size" => Some(self.p_size as i64),
+            _ => None,
+        }
+    }
+}
+
+impl FilterRow for Supplier {
+    fn column(&self, column: &str) -> Option<i64> {
+        match column {
+            "s_suppkey" => Some(self.s_suppkey),
+            "s_nationkey" => Some(self.s_nationkey),
+            _ => None,
+        }
+    }
+}
+
+impl FilterRow for PartSupp<'_> {
+    fn column(&self, column: &str) -> Option<i64> {
+        match column {
+            "ps_partkey" => Some(self.ps_partkey),
+            "ps_suppkey" => Some(self.ps_suppkey),
+            "ps_availqty" => Some(self.ps_availqty as i64),
+            _ => None,
+        }
+    }
+}
+
+impl FilterRow for Customer<'_> {
+    fn column(&self, column: &str) -> Option<i64> {
+        match column {
+            "c_custkey" => Some(self.c_custkey),
+            "c_nationkey" => Some(self.c_nationkey),
+            _ => None,
+        }
+    }
+}
+
+impl FilterRow for Order<'_> {
+    fn column(&self, column: &str) -> Option<i64> {
+        match column {
+            "o_orderkey" => Some(self.o_orderkey),
+            "o_custkey" => Some(self.o_custkey),
+            "o_orderdate" => Some(self.o_orderdate.to_epoch_days() as i64),
+            _ => None,
+        }
+    }
+}
+
+impl FilterRow for LineItem<'_> {
+    fn column(&self, column: &str) -> Option<i64> {
+        match column {
+            "l_orderkey" => Some(self.l_orderkey),
+            "l_partkey" => Some(self.l_partkey),
+            "l_suppkey" => Some(self.l_suppkey),
+            "l_linenumber" => Some(self.l_linenumber as i64),
+            "l_quantity" => Some(self.l_quantity),
+            "l_shipdate" => Some(self.l_shipdate.to_epoch_days() as i64),
+            "l_commitdate" => Some(self.l_commitdate.to_epoch_days() as i64),
+            "l_receiptdate" => Some(self.l_receiptdate.to_epoch_days() as i64),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tpchgen::generators::{NationGenerator, OrderGenerator};
+
+    fn predicate(s: &str) -> Predicate {
+        s.parse().unwrap()
+    }
+
+    fn predicates(filters: &[&str]) -> Vec<Predicate> {
+        filters.iter().map(|s| predicate(s)).collect()
+    }
+
+    #[test]
+    fn compares_values() {
+        // n_nationkey 0 to 24
+        let nations: Vec<_> = NationGenerator::default().iter().collect();
+        let keys = |s: &str| -> Vec<i64> {
+            let p = predicate(s);
+            nations
+                .iter()
+                .filter(|n| p.matches(*n))
+                .map(|n| n.n_nationkey)
+                .collect()
+        };
+        assert_eq!(keys("n_nationkey=3"), [3]);
+        assert_eq!(keys("n_nationkey!=3").len(), 24);
+        assert_eq!(keys("n_nationkey<3"), [0, 1, 2]);
+        assert_eq!(keys("n_nationkey<=2"), [0, 1, 2]);
+        assert_eq!(keys("n_nationkey>22"), [23, 24]);
+        assert_eq!(keys("n_nationkey >= 23"), [23, 24]);
+    }
+
+    #[test]
+    fn compares_dates() {
+        let p = predicate("o_orderdate>=1995-01-01");
+        let q = predicate("o_orderdate<1995-01-01");
+        let start = TPCHDate::from_ymd(1995, 1, 1).unwrap();
+        for order in OrderGenerator::new(0.001, 1, 1).iter().take(100) {
+            let after = order.o_orderdate >= start;
+            assert_eq!(p.matches(&order), after, "{}", order.o_orderdate);
+            assert_eq!(q.matches(&order), !after, "{}", order.o_orderdate);
+        }
+        assert_eq!(predicate("o_orderdate=1970-01-01").value, 0);
+        assert_eq!(predicate("l_shipdate<2000-02-29").value, 11016);
+    }
+
+    #[test]
+    fn rejects_invalid_filters() {
+        for (filter, error) in [
+            ("o_orderdate", "invalid filter 'o_orderdate'"),
+            ("o_comment=x", "can not filter on column 'o_comment'"),
+            ("o_custkey=x", "invalid integer 'x' for o_custkey"),
+            ("o_orderdate>=1995", "invalid date '1995' for o_orderdate"),
+            ("o_orderdate>=1995-13-01", "invalid date '1995-13-01'"),
+            ("o_orderdate>=2023-02-31", "invalid date '2023-02-31'"),
+            ("o_orderdate>=2023-02-29", "invalid date '2023-02-29'"),
+            ("l_shipdate<1995-04-31", "invalid date '1995-04-31'"),
+        ] {
+            let err = filter.parse::<Predicate>().unwrap_err();
+            assert!(err.starts_with(error), "{filter}: {err}");
+        }
+        predicate("o_orderdate>=2024-02-29");
+    }
+
+    #[test]
+    fn intersects_key_ranges() {
+        let range = |filters: &[&str]| key_range(&predicates(filters));
+        assert_eq!(range(&["o_custkey<10"]), None);
+        assert_eq!(range(&["o_orderkey=5"]), Some(5..=5));
+        assert_eq!(range(&["o_orderkey!=5"]), None);
+        assert_eq!(range(&["o_orderkey<10"]), Some(i64::MIN..=9));
+        assert_eq!(range(&["o_orderkey>10"]), Some(11..=i64::MAX));
+        assert_eq!(
+            range(&[
+                "o_orderkey>=10",
+                "o_custkey<5",
+                "o_orderkey<=20",
+                "o_orderkey>12"
+            ]),
+            Some(13..=20)
+        );
+        assert_eq!(
+            range(&["o_orderkey>=10", "o_orderkey!=15", "o_orderkey<20"]),
+            Some(10..=19)
+        );
+        assert!(range(&["o_orderkey>20", "o_orderkey<10"])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn intersects_order_date_ranges() {
+        let range = |filters: &[&str]| order_date_range(&predicates(filters));
+        let day = |date| parse_date(date).unwrap() as i32;
+        assert_eq!(range(&["o_orderkey<10", "o_orderdate!=1995-01-01"]), None);
+        assert_eq!(
+            range(&["o_orderdate>=1995-01-01", "o_orderdate<1995-02-01"]),
+            Some(day("1995-01-01")..=day("1995-01-31"))
+        );
+        // ship dates are 1 to 121 days after the order date
+        assert_eq!(
+            range(&["l_shipdate=1995-06-01"]),
+            Some(day("1995-01-31")..=day("1995-05-31"))
+        );
+        // commit dates 30 to 90, receipt dates 2 to 151
+        assert_eq!(
+            range(&["l_commitdate>1995-06-01", "l_receiptdate<=1995-06-10"]),
+            Some(day("1995-03-04")..=day("1995-06-08"))
+        );
+        assert_eq!(
+            range(&["o_orderdate<1995-01-01"]),
+            Some(i32::MIN..=day("1994-12-31"))
+        );
+        assert!(range(&["o_orderdate>1995-01-01", "o_orderdate<1994-01-01"])
+            .unwrap()
+            .is_empty());
+    }
+}
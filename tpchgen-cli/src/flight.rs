@@ -0,0 +1,126 @@
+//! Pushing tables to an Arrow Flight endpoint (`--flight-push`)
+//!
+//! Requires the `flight` feature. Instead of writing files, each generated
+//! part is sent to the endpoint with its own `DoPut` call, so data can be
+//! generated on one machine and landed directly in a Flight capable system.
+//!
+//! The [`FlightDescriptor`] of each `DoPut` is a path of the table name and
+//! the (1 based) part number out of the total number of parts, for example
+//! `["lineitem", "3", "8"]`. Parts are pushed concurrently and may complete in
+//! any order. A part whose `DoPut` fails is sent again in full with the same
+//! descriptor, so endpoints can replace any partially received data.
+
+use crate::progress::{write_callbacks, ProgressReporter};
+use crate::retry::RetryPolicy;
+use arrow::array::RecordBatch;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::{FlightClient, FlightDescriptor};
+use futures::{StreamExt, TryStreamExt};
+use log::debug;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tonic::transport::{Channel, Endpoint};
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::statistics::WriteStatistics;
+
+/// Options for pushing tables to an Arrow Flight endpoint
+#[derive(Debug, Clone, Copy)]
+pub struct PushOptions {
+    /// Number of parts pushed concurrently per table
+    pub concurrency: usize,
+    /// How failed `DoPut` calls are retried
+    pub retry: RetryPolicy,
+}
+
+/// Pushes `parts` of `table` (out of `num_parts`) to the Flight endpoint at
+/// `url`
+pub async fn push_table<I>(
+    url: &str,
+    table: &'static str,
+    num_parts: i32,
+    parts: Vec<i32>,
+    iter_iter: I,
+    options: PushOptions,
+    progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+{
+    debug!(
+        "Pushing {table} to {url} with {} concurrent DoPut calls",
+        options.concurrency
+    );
+    let channel = Endpoint::from_shared(url.to_string())
+        .map_err(io::Error::other)?
+        .connect()
+        .await
+        .map_err(io::Error::other)?;
+
+    let mut statistics =
+        WriteStatistics::new("parts").with_callbacks(write_callbacks(progress.as_ref()));
+    let progress = progress.map(|p| Arc::new(Mutex::new(p)));
+
+    let mut pushes = futures::stream::iter(parts.into_iter().zip(iter_iter))
+        .map(|(part, iter)| {
+            let channel = channel.clone();
+            let progress = progress.clone();
+            async move {
+                tokio::task::spawn(async move {
+                    let batches: Vec<RecordBatch> = iter.collect();
+                    let rows = batches.iter().map(|b| b.num_rows() as u64).sum();
+                    let bytes = batches
+                        .iter()
+                        .map(|b| b.get_array_memory_size() as u64)
+                        .sum();
+                    let what = format!("DoPut of {table} part {part}");
+                    options
+                        .retry
+                        .retry(&what, || {
+                            push_part(channel.clone(), table, part, num_parts, batches.clone())
+                        })
+                        .await?;
+                    if let Some(progress) = progress {
+                        progress.lock().unwrap().part_complete(rows, bytes);
+                    }
+                    Ok(bytes) as Result<u64, io::Error>
+                })
+                .await
+                .expect("Inner task panicked")
+            }
+        })
+        .buffer_unordered(options.concurrency);
+
+    while let Some(bytes) = pushes.next().await {
+        statistics.increment_bytes(bytes? as usize);
+        statistics.increment_chunks(1);
+    }
+    Ok(())
+}
+
+/// Sends the batches of a single part with one `DoPut` call
+async fn push_part(
+    channel: Channel,
+    table: &str,
+    part: i32,
+    num_parts: i32,
+    batches: Vec<RecordBatch>,
+) -> Result<(), io::Error> {
+    let descriptor = FlightDescriptor::new_path(vec![
+        table.to_string(),
+        part.to_string(),
+        num_parts.to_string(),
+    ]);
+    let flight_data = FlightDataEncoderBuilder::new()
+        .with_flight_descriptor(Some(descriptor))
+        .build(futures::stream::iter(batches.into_iter().map(Ok)));
+
+    let mut client = FlightClient::new(channel);
+    client
+        .do_put(flight_data)
+        .await
+        .map_err(io::Error::other)?
+        .try_collect::<Vec<_>>()
+        .await
+        .map_err(io::Error::other)?;
+    Ok(())
+}
@@ -0,0 +1,166 @@
+//! Mounting the tables as a read only file system (`tpchgen-cli mount`)
+//!
+//! Requires the `fuse` feature. The mounted directory contains one file per
+//! table and format, e.g. `lineitem.tbl` and `orders.parquet`, whose contents
+//! are generated when they are read (see [`VirtualFile`]), so any program that
+//! reads files can read the tables without them taking up any storage.
+//!
+//! The size of a file is computed by generating it once the first time the
+//! file is looked up, for example by `ls -l`, so this can take a while for
+//! the large tables. Listing the directory without sizes is immediate.
+
+use crate::virtual_file::VirtualFile;
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry,
+    Request, FUSE_ROOT_ID,
+};
+use log::{debug, info};
+use std::ffi::OsStr;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long the kernel may cache attributes; the files never change
+const TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Block size reported for the files
+const BLOCK_SIZE: u32 = 512;
+
+/// Mounts `files` at `mountpoint`, returning once the file system is
+/// unmounted, e.g. with `fusermount -u <mountpoint>`
+pub fn mount(mountpoint: &Path, files: Vec<(String, VirtualFile)>) -> io::Result<()> {
+    let options = [
+        MountOption::RO,
+        MountOption::FSName("tpchgen".to_string()),
+        MountOption::Subtype("tpchgen".to_string()),
+    ];
+    fuser::mount2(TpchFs::new(files), mountpoint, &options)
+}
+
+/// A single directory of [`VirtualFile`]s
+struct TpchFs {
+    /// The files, with inode numbers starting after the root directory
+    files: Vec<(String, VirtualFile)>,
+    mounted: SystemTime,
+    uid: u32,
+    gid: u32,
+}
+
+impl TpchFs {
+    fn new(files: Vec<(String, VirtualFile)>) -> Self {
+        // SAFETY: getuid and getgid can not fail
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        Self {
+            files,
+            mounted: SystemTime::now(),
+            uid,
+            gid,
+        }
+    }
+
+    /// Returns the file with inode `ino`, if any
+    fn file(&self, ino: u64) -> Option<&(String, VirtualFile)> {
+        let index = ino.checked_sub(FUSE_ROOT_ID + 1)?;
+        self.files.get(usize::try_from(index).ok()?)
+    }
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, perm, size) = if ino == FUSE_ROOT_ID {
+            (FileType::Directory, 0o555, 0)
+        } else {
+            let (name, file) = self.file(ino)?;
+            if !file.is_size_known() {
+                info!("Generating {name} to determine its size");
+                let start = Instant::now();
+                let size = file.size();
+                debug!("{name} is {size} bytes (took {:?})", start.elapsed());
+            }
+            (FileType::RegularFile, 0o444, file.size())
+        };
+        Some(FileAttr {
+            ino,
+            size,
+            blocks: size.div_ceil(BLOCK_SIZE as u64),
+            atime: self.mounted,
+            mtime: self.mounted,
+            ctime: self.mounted,
+            crtime: self.mounted,
+            kind,
+            perm,
+            nlink: if kind == FileType::Directory { 2 } else { 1 },
+            uid: self.uid,
+            gid: self.gid,
+            rdev: 0,
+            blksize: BLOCK_SIZE,
+            flags: 0,
+        })
+    }
+}
+
+impl Filesystem for TpchFs {
+    fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        let index = self.files.iter().position(|(n, _)| OsStr::new(n) == name);
+        match index {
+            Some(index) if parent == FUSE_ROOT_ID => {
+                let ino = FUSE_ROOT_ID + 1 + index as u64;
+                reply.entry(&TTL, &self.attr(ino).unwrap(), 0)
+            }
+            _ => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request<'_>, ino: u64, _fh: Option<u64>, reply: ReplyAttr) {
+        match self.attr(ino) {
+            Some(attr) => reply.attr(&TTL, &attr),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        match (self.file(ino), u64::try_from(offset)) {
+            (Some((_, file)), Ok(offset)) => reply.data(&file.read(offset, size as usize)),
+            (None, _) => reply.error(libc::ENOENT),
+            (_, Err(_)) => reply.error(libc::EINVAL),
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        if ino != FUSE_ROOT_ID {
+            reply.error(libc::ENOTDIR);
+            return;
+        }
+        let entries = [
+            (FUSE_ROOT_ID, FileType::Directory, "."),
+            (FUSE_ROOT_ID, FileType::Directory, ".."),
+        ]
+        .into_iter()
+        .chain(self.files.iter().enumerate().map(|(index, (name, _))| {
+            let ino = FUSE_ROOT_ID + 1 + index as u64;
+            (ino, FileType::RegularFile, name.as_str())
+        }));
+        // offset is that of the last entry already returned
+        for (index, (ino, kind, name)) in entries.enumerate().skip(offset as usize) {
+            if reply.add(ino, index as i64 + 1, kind, name) {
+                break; // buffer full
+            }
+        }
+        reply.ok();
+    }
+}
@@ -2,6 +2,81 @@
 //!
 //! These traits and function are used to generate data in parallel and write it to a sink
 //! in streaming fashion (chunks). This is useful for generating large datasets that don't fit in memory.
+//!
+//! [`generate_in_chunks`] is the scheduling piece of `tpchgen-cli` and does
+//! not depend on TPC-H: any data that can be split into independent chunks can
+//! be generated with it.
+//!
+//! # Guarantees
+//!
+//! * **Ordering**: buffers are written to the [`Sink`] in the order of the
+//!   [`Source`]es, regardless of which finishes first, so the output is
+//!   deterministic.
+//! * **Header**: [`Source::header`] of the first source is written before
+//!   any data, exactly once.
+//! * **Parallelism**: at most `num_threads` sources are generated at a time,
+//!   each on its own tokio task, which also bounds memory use to roughly
+//!   `num_threads` buffers.
+//! * **Blocking IO**: the [`Sink`] is called from a single blocking thread,
+//!   so it can do synchronous IO (or block on async IO with
+//!   [`Handle::block_on`](tokio::runtime::Handle::block_on)).
+//! * **Errors**: the first error returned by [`Sink::sink`] stops generation
+//!   and is returned. [`Sink::flush`] is only called if all writes succeed.
+//!
+//! # Example
+//! ```
+//! use std::io::{self, Write};
+//! use std::sync::{Arc, Mutex};
+//! use tpchgen_cli::generate::{generate_in_chunks, Sink, Source};
+//!
+//! /// Generates the numbers `start..end`, one per line
+//! struct Numbers {
+//!     start: usize,
+//!     end: usize,
+//! }
+//!
+//! impl Source for Numbers {
+//!     fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+//!         for i in self.start..self.end {
+//!             writeln!(buffer, "{i}").unwrap();
+//!         }
+//!         buffer
+//!     }
+//!
+//!     fn header(&self, mut buffer: Vec<u8>) -> Vec<u8> {
+//!         buffer.extend_from_slice(b"n\n");
+//!         buffer
+//!     }
+//! }
+//!
+//! /// Collects the output in memory
+//! struct VecSink(Arc<Mutex<Vec<u8>>>);
+//!
+//! impl Sink for VecSink {
+//!     fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error> {
+//!         self.0.lock().unwrap().extend_from_slice(buffer);
+//!         Ok(())
+//!     }
+//!
+//!     fn flush(self) -> Result<(), io::Error> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), io::Error> {
+//! let output = Arc::new(Mutex::new(Vec::new()));
+//! // generate 0..100 in 10 chunks, using 4 threads
+//! let sources = (0..10).map(|i| Numbers { start: i * 10, end: (i + 1) * 10 });
+//! generate_in_chunks(VecSink(Arc::clone(&output)), sources, 4).await?;
+//!
+//! let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+//! let lines: Vec<_> = output.lines().collect();
+//! assert_eq!(lines[0], "n");
+//! assert_eq!(lines[1..], (0..100).map(|i| i.to_string()).collect::<Vec<_>>());
+//! # Ok(())
+//! # }
+//! ```
 
 use futures::StreamExt;
 use log::debug;
@@ -14,8 +89,14 @@ use tokio::task::JoinSet;
 ///
 /// For example, this is implemented for the different generators in the tpchgen
 /// crate
+///
+/// Each source generates one independent chunk of the output. Sources are
+/// moved to other threads, so must be [`Send`].
 pub trait Source: Send {
     /// generates the data for this generator into the buffer, returning the buffer.
+    ///
+    /// The buffer is empty, but may have capacity from a previous use, and
+    /// the returned buffer may be reused for another source once written.
     fn create(self, buffer: Vec<u8>) -> Vec<u8>;
 
     /// Create the first line for the output, into the buffer
@@ -30,9 +111,13 @@ pub trait Source: Send {
 /// For example, this is implemented for a file writer.
 pub trait Sink: Send {
     /// Write all data from the buffer to the sink
+    ///
+    /// Called once for the header and then once for each [`Source`], in order.
     fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error>;
 
     /// Complete and flush any remaining data from the sink
+    ///
+    /// Called once after all buffers have been written successfully.
     fn flush(self) -> Result<(), io::Error>;
 }
 
@@ -43,7 +128,10 @@ pub trait Sink: Send {
 ///
 /// This function will run the [`Source`]es in parallel up to num_threads.
 /// Data is written to the [`Sink`] in the order of the [`Source`]es in
-/// the input iterator.
+/// the input iterator. See the [module documentation](self) for the full
+/// set of guarantees.
+///
+/// Must be called within a tokio runtime.
 ///
 /// G: Generator
 /// I: Iterator<Item = G>
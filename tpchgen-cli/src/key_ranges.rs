@@ -0,0 +1,236 @@
+//! Minimum and maximum keys of each output file (`--key-ranges`)
+//!
+//! With `--key-ranges`, `key_ranges.json` is written to the output directory
+//! next to `manifest.json`, listing the minimum and maximum of the key,
+//! integer and date columns of each output file (the columns `--filter`
+//! supports), so the file pruning of query engines can be tested against the
+//! exact bounds of the files:
+//!
+//! ```text
+//! {
+//!   "scale_factor": 1,
+//!   "format": "parquet",
+//!   "files": [
+//!     {"path": "lineitem/lineitem_0001.parquet", "table": "lineitem", "rows": 1500522, "min": {"l_orderkey": 1, ..., "l_shipdate": "1992-01-02", ...}, "max": {...}},
+//!     ...
+//!   ]
+//! }
+//! ```
+//!
+//! The values are recorded for each part as its rows are generated, and the
+//! range of a file is that of the parts the [`Manifest`] lists in it. Dates
+//! replaced by NULLs with `--null-percent` are still included, so the ranges
+//! are bounds of the values in the file rather than exact.
+
+use crate::filter::{self, FilterRow};
+use crate::manifest::{quote, Manifest};
+use crate::Table;
+use arrow::temporal_conversions::date32_to_datetime;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The minimum and maximum of the columns of one part of a table, updated
+/// as its rows are generated
+#[derive(Debug)]
+pub struct PartRanges {
+    /// The columns, and whether they are dates
+    columns: Vec<(&'static str, bool)>,
+    min: Vec<AtomicI64>,
+    max: Vec<AtomicI64>,
+}
+
+impl PartRanges {
+    fn new(table: Table) -> Self {
+        let columns: Vec<_> = filter::columns(table).collect();
+        Self {
+            min: columns.iter().map(|_| AtomicI64::new(i64::MAX)).collect(),
+            max: columns.iter().map(|_| AtomicI64::new(i64::MIN)).collect(),
+            columns,
+        }
+    }
+
+    /// Includes the values of `row` in the ranges
+    pub fn observe(&self, row: &impl FilterRow) {
+        for (i, (column, _)) in self.columns.iter().enumerate() {
+            let Some(value) = row.column(column) else {
+                continue;
+            };
+            // the ranges rarely change, so only update them if they do
+            if value < self.min[i].load(Ordering::Relaxed) {
+                self.min[i].fetch_min(value, Ordering::Relaxed);
+            }
+            if value > self.max[i].load(Ordering::Relaxed) {
+                self.max[i].fetch_max(value, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Returns the minimum and maximum of each column
+    fn ranges(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        let min = self.min.iter().map(|v| v.load(Ordering::Relaxed));
+        let max = self.max.iter().map(|v| v.load(Ordering::Relaxed));
+        min.zip(max)
+    }
+}
+
+/// Records the ranges of the parts of the tables generated, shared by their
+/// generators
+#[derive(Debug, Clone, Default)]
+pub struct KeyRanges(Arc<Mutex<Parts>>);
+
+/// The ranges of each part of each table
+type Parts = BTreeMap<(Table, i32), Arc<PartRanges>>;
+
+impl KeyRanges {
+    /// Returns the ranges of `part` of `table`, to update as it is generated
+    ///
+    /// A part generated again, e.g. when an upload is retried, updates the
+    /// same ranges.
+    pub fn part(&self, table: Table, part: i32) -> Arc<PartRanges> {
+        let mut parts = self.0.lock().unwrap();
+        let ranges = parts
+            .entry((table, part))
+            .or_insert_with(|| Arc::new(PartRanges::new(table)));
+        Arc::clone(ranges)
+    }
+
+    /// Returns the `key_ranges.json` of the complete files in `manifest`
+    ///
+    /// Files of tables without recorded ranges, e.g. of earlier runs, are
+    /// not listed.
+    pub fn to_json(&self, manifest: &Manifest, scale_factor: f64, format: &str) -> String {
+        let parts = self.0.lock().unwrap();
+        let mut lines = Vec::new();
+        for file in manifest.files() {
+            let (Ok(table), Some((first, last))) = (file.table.parse::<Table>(), file.parts) else {
+                continue;
+            };
+            let Some(first_part) = parts.get(&(table, first)) else {
+                continue;
+            };
+            let mut ranges: Vec<_> = first_part.ranges().collect();
+            for part in first + 1..=last {
+                let Some(part) = parts.get(&(table, part)) else {
+                    continue;
+                };
+                for ((min, max), (part_min, part_max)) in ranges.iter_mut().zip(part.ranges()) {
+                    *min = (*min).min(part_min);
+                    *max = (*max).max(part_max);
+                }
+            }
+            let format_values = |values: &mut dyn Iterator<Item = i64>| {
+                let values: Vec<_> = first_part
+                    .columns
+                    .iter()
+                    .zip(values)
+                    .map(|(&(column, is_date), value)| {
+                        format!("{}: {}", quote(column), format_value(value, is_date))
+                    })
+                    .collect();
+                format!("{{{}}}", values.join(", "))
+            };
+            // files without rows, e.g. of parts removed by --filter
+            let (min, max) = if file.rows == 0 {
+                ("null".to_string(), "null".to_string())
+            } else {
+                (
+                    format_values(&mut ranges.iter().map(|r| r.0)),
+                    format_values(&mut ranges.iter().map(|r| r.1)),
+                )
+            };
+            lines.push(format!(
+                r#"    {{"path": {}, "table": {}, "rows": {}, "min": {min}, "max": {max}}}"#,
+                quote(&manifest.relative_path(&file.path)),
+                quote(&file.table),
+                file.rows,
+            ));
+        }
+        let mut json = String::new();
+        writeln!(json, "{{").unwrap();
+        writeln!(json, r#"  "scale_factor": {scale_factor},"#).unwrap();
+        writeln!(json, r#"  "format": {},"#, quote(format)).unwrap();
+        writeln!(json, r#"  "files": ["#).unwrap();
+        if !lines.is_empty() {
+            writeln!(json, "{}", lines.join(",\n")).unwrap();
+        }
+        writeln!(json, "  ]").unwrap();
+        writeln!(json, "}}").unwrap();
+        json
+    }
+
+    /// Writes `key_ranges.json` next to the `manifest.json` of `manifest`
+    pub fn save(&self, manifest: &Manifest, scale_factor: f64, format: &str) -> io::Result<()> {
+        let path = manifest.path().with_file_name("key_ranges.json");
+        fs::write(path, self.to_json(manifest, scale_factor, format))
+    }
+}
+
+/// Formats `value` as JSON, with dates (days since the Unix epoch) as
+/// `YYYY-MM-DD` strings
+fn format_value(value: i64, is_date: bool) -> String {
+    match date32_to_datetime(value as i32).filter(|_| is_date) {
+        Some(date) => quote(&date.date().to_string()),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tpchgen::generators::OrderGenerator;
+
+    #[test]
+    fn records_ranges_of_files() {
+        let dir = std::env::temp_dir().join(format!("tpchgen-key-ranges-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let manifest = Manifest::new(dir.clone(), 0.01, "tbl");
+        let key_ranges = KeyRanges::default();
+        manifest.table_start("orders", 3);
+        // parts 1 and 2 in the first file, 3 in the second
+        for (part, file) in [
+            (1, Some("orders.tbl.1")),
+            (2, None),
+            (3, Some("orders.tbl.2")),
+        ] {
+            if let Some(file) = file {
+                let index = manifest.file_started(&dir.join(file));
+                manifest
+                    .file_finished(index, 100, "aa".to_string())
+                    .unwrap();
+            }
+            let ranges = key_ranges.part(Table::Orders, part);
+            let mut rows = 0;
+            for order in OrderGenerator::new(0.01, part, 3).iter() {
+                ranges.observe(&order);
+                rows += 1;
+            }
+            manifest.part_complete(part, rows);
+        }
+
+        let json = key_ranges.to_json(&manifest, 0.01, "tbl");
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let files = json["files"].as_array().unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0]["path"], "orders.tbl.1");
+        assert_eq!(files[0]["rows"], 10000);
+        // the order keys are sparse: 8 of every 32 are used
+        assert_eq!(files[0]["min"]["o_orderkey"], 1);
+        assert_eq!(files[0]["max"]["o_orderkey"], 40000);
+        assert_eq!(files[1]["min"]["o_orderkey"], 40001);
+        assert_eq!(files[1]["max"]["o_orderkey"], 60000);
+        assert_eq!(files[0]["min"]["o_orderdate"], "1992-01-01");
+        assert_eq!(files[1]["max"]["o_orderdate"], "1998-08-02");
+        assert_eq!(files[1]["max"]["o_custkey"], 1499);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_value(8035, true), r#""1992-01-01""#);
+        assert_eq!(format_value(8035, false), "8035");
+    }
+}
@@ -0,0 +1,304 @@
+//! Output directory layouts for query engines (`--layout`)
+//!
+//! By default (`flat`) all tables are written directly to the output
+//! directory. The other layouts write each table to its own directory inside
+//! a dataset directory named after the scale factor, and write a
+//! `create_tables.sql` script that registers the tables with the engine:
+//!
+//! ```text
+//! <output_dir>/tpch_sf10/create_tables.sql
+//! <output_dir>/tpch_sf10/lineitem/lineitem.parquet
+//! <output_dir>/tpch_sf10/orders/orders.parquet
+//! ...
+//! ```
+//!
+//! The script uses absolute paths so it can be run from any directory, e.g.
+//! `duckdb < tpch_sf10/create_tables.sql`.
+
+use crate::OutputFormat;
+use arrow::datatypes::{DataType, SchemaRef};
+use clap::ValueEnum;
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+
+/// Name of the extra column used to read the trailing `|` of TBL lines
+const TBL_PLACEHOLDER_COLUMN: &str = "__placeholder";
+
+/// How output files are arranged in the output directory
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum OutputLayout {
+    /// All files directly in the output directory
+    Flat,
+    /// DuckDB: a schema with one view per table
+    Duckdb,
+    /// Spark SQL: a database with one external table per table
+    Spark,
+    /// DataFusion: one `CREATE EXTERNAL TABLE` per table
+    Datafusion,
+}
+
+impl OutputLayout {
+    /// Returns true if the engine for this layout can read `format`
+    pub fn supports(&self, format: OutputFormat) -> bool {
+        match self {
+            OutputLayout::Flat => true,
+            // SQL statements, COPY and BSON files are loaded with a client,
+            // not read in place
+            _ if matches!(
+                format,
+                OutputFormat::Sql | OutputFormat::PgCopy | OutputFormat::Bson
+            ) =>
+            {
+                false
+            }
+            // via the vortex and avro extensions, DuckDB does not read ORC
+            // or Arrow IPC
+            OutputLayout::Duckdb => !matches!(format, OutputFormat::Orc | OutputFormat::ArrowIpc),
+            OutputLayout::Spark => !matches!(format, OutputFormat::Vortex | OutputFormat::ArrowIpc),
+            OutputLayout::Datafusion => !matches!(format, OutputFormat::Vortex | OutputFormat::Orc),
+        }
+    }
+
+    /// Returns the name of the dataset directory (and schema / database) for
+    /// the scale factor, e.g. `tpch_sf10` or `tpch_sf0_1`, or `None` for the
+    /// flat layout.
+    pub fn dataset_name(&self, scale_factor: f64) -> Option<String> {
+        match self {
+            OutputLayout::Flat => None,
+            _ => Some(format!("tpch_sf{scale_factor}").replace('.', "_")),
+        }
+    }
+
+    /// Returns the SQL script registering `tables` (name and schema) in
+    /// `format`, where each table's files are in `<dataset_path>/<table>/`.
+    ///
+    /// Returns an error if a column has a type without a SQL type.
+    pub fn create_tables_sql(
+        &self,
+        dataset_name: &str,
+        dataset_path: &Path,
+        format: OutputFormat,
+        tables: &[(&str, SchemaRef)],
+    ) -> io::Result<String> {
+        let mut sql = format!("-- TPC-H tables for {dataset_name}, generated by tpchgen-cli\n");
+        match self {
+            OutputLayout::Flat => return Ok(sql),
+            OutputLayout::Duckdb => {
+                writeln!(sql, "CREATE SCHEMA IF NOT EXISTS {dataset_name};").unwrap()
+            }
+            OutputLayout::Spark => {
+                writeln!(sql, "CREATE DATABASE IF NOT EXISTS {dataset_name};").unwrap()
+            }
+            OutputLayout::Datafusion => {}
+        }
+        for (table, schema) in tables {
+            let location = dataset_path.join(table);
+            let location = location.display();
+            sql.push('\n');
+            match self {
+                OutputLayout::Flat => unreachable!(),
+                OutputLayout::Duckdb => {
+                    let source = match format {
+                        OutputFormat::Parquet => format!("read_parquet('{location}/*.parquet')"),
+                        OutputFormat::Vortex => format!("read_vortex('{location}/*.vortex')"),
+                        OutputFormat::Avro => format!("read_avro('{location}/*.avro')"),
+                        OutputFormat::Orc
+                        | OutputFormat::ArrowIpc
+                        | OutputFormat::Sql
+                        | OutputFormat::PgCopy
+                        | OutputFormat::Bson => {
+                            unreachable!("see OutputLayout::supports")
+                        }
+                        OutputFormat::Csv => format!(
+                            "read_csv('{location}/*.csv', header = true, columns = {})",
+                            duckdb_columns(schema, false)?
+                        ),
+                        OutputFormat::Tbl => format!(
+                            "read_csv('{location}/*.tbl', delim = '|', header = false, columns = {})",
+                            duckdb_columns(schema, true)?
+                        ),
+                    };
+                    let exclude = if format == OutputFormat::Tbl {
+                        format!(" EXCLUDE ({TBL_PLACEHOLDER_COLUMN})")
+                    } else {
+                        String::new()
+                    };
+                    writeln!(
+                        sql,
+                        "CREATE OR REPLACE VIEW {dataset_name}.{table} AS SELECT *{exclude} FROM {source};"
+                    )
+                    .unwrap();
+                }
+                OutputLayout::Spark => {
+                    let columns =
+                        column_definitions(schema, format == OutputFormat::Tbl, |t| match t {
+                            DataType::Utf8 | DataType::Utf8View | DataType::LargeUtf8 => {
+                                Ok("STRING".to_string())
+                            }
+                            DataType::Dictionary(_, _) => Ok("STRING".to_string()),
+                            // without a time zone, as the written timestamps
+                            DataType::Timestamp(_, None) => Ok("TIMESTAMP_NTZ".to_string()),
+                            t => sql_type(t),
+                        })?;
+                    let using = match format {
+                        OutputFormat::Parquet => "parquet",
+                        OutputFormat::Orc => "orc",
+                        OutputFormat::Avro => "avro",
+                        OutputFormat::Csv => "csv OPTIONS (header 'true')",
+                        OutputFormat::Tbl => "csv OPTIONS (sep '|', header 'false')",
+                        OutputFormat::Vortex
+                        | OutputFormat::ArrowIpc
+                        | OutputFormat::Sql
+                        | OutputFormat::PgCopy
+                        | OutputFormat::Bson => {
+                            unreachable!("see OutputLayout::supports")
+                        }
+                    };
+                    writeln!(
+                        sql,
+                        "CREATE TABLE IF NOT EXISTS {dataset_name}.{table} ({columns})\nUSING {using}\nLOCATION '{location}';"
+                    )
+                    .unwrap();
+                }
+                OutputLayout::Datafusion => {
+                    let columns =
+                        column_definitions(schema, format == OutputFormat::Tbl, sql_type)?;
+                    let stored_as = match format {
+                        OutputFormat::Parquet => "PARQUET",
+                        OutputFormat::Csv => "CSV",
+                        OutputFormat::Tbl => "CSV",
+                        OutputFormat::Avro => "AVRO",
+                        OutputFormat::ArrowIpc => "ARROW",
+                        OutputFormat::Vortex
+                        | OutputFormat::Orc
+                        | OutputFormat::Sql
+                        | OutputFormat::PgCopy
+                        | OutputFormat::Bson => {
+                            unreachable!("see OutputLayout::supports")
+                        }
+                    };
+                    let options = match format {
+                        OutputFormat::Parquet
+                        | OutputFormat::Vortex
+                        | OutputFormat::Orc
+                        | OutputFormat::Avro
+                        | OutputFormat::ArrowIpc
+                        | OutputFormat::Sql
+                        | OutputFormat::PgCopy
+                        | OutputFormat::Bson => "",
+                        OutputFormat::Csv => "\nOPTIONS ('format.has_header' 'true')",
+                        OutputFormat::Tbl => {
+                            "\nOPTIONS ('format.delimiter' '|', 'format.has_header' 'false')"
+                        }
+                    };
+                    writeln!(
+                        sql,
+                        "CREATE EXTERNAL TABLE IF NOT EXISTS {table} ({columns})\nSTORED AS {stored_as}\nLOCATION '{location}/'{options};"
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        Ok(sql)
+    }
+}
+
+/// Returns `name TYPE, ...` for the fields of `schema`, using `to_sql` to
+/// convert the types.
+///
+/// If `placeholder` is true, adds an extra nullable column for the trailing
+/// `|` of TBL lines.
+fn column_definitions(
+    schema: &SchemaRef,
+    placeholder: bool,
+    to_sql: impl Fn(&DataType) -> io::Result<String>,
+) -> io::Result<String> {
+    let mut columns = schema
+        .fields()
+        .iter()
+        .map(|f| Ok(format!("{} {}", f.name(), to_sql(f.data_type())?)))
+        .collect::<io::Result<Vec<_>>>()?;
+    if placeholder {
+        columns.push(format!(
+            "{TBL_PLACEHOLDER_COLUMN} {}",
+            to_sql(&DataType::Utf8)?
+        ));
+    }
+    Ok(columns.join(", "))
+}
+
+/// Returns the DuckDB `columns` struct literal for `schema`
+fn duckdb_columns(schema: &SchemaRef, placeholder: bool) -> io::Result<String> {
+    let mut columns = schema
+        .fields()
+        .iter()
+        .map(|f| Ok(format!("'{}': '{}'", f.name(), sql_type(f.data_type())?)))
+        .collect::<io::Result<Vec<_>>>()?;
+    if placeholder {
+        columns.push(format!("'{TBL_PLACEHOLDER_COLUMN}': 'VARCHAR'"));
+    }
+    Ok(format!("{{{}}}", columns.join(", ")))
+}
+
+/// Returns the ANSI SQL type for an Arrow type produced by `tpchgen-arrow`,
+/// with the column types of `--decimal-as`, `--date-as`, `--string-as` and
+/// `--dictionary-arrays`
+fn sql_type(data_type: &DataType) -> io::Result<String> {
+    let sql_type = match data_type {
+        DataType::Int32 => "INTEGER".to_string(),
+        DataType::Int64 => "BIGINT".to_string(),
+        DataType::Decimal128(precision, scale) => format!("DECIMAL({precision},{scale})"),
+        DataType::Float64 => "DOUBLE".to_string(),
+        DataType::Date32 => "DATE".to_string(),
+        DataType::Timestamp(_, None) => "TIMESTAMP".to_string(),
+        DataType::Utf8 | DataType::Utf8View | DataType::LargeUtf8 => "VARCHAR".to_string(),
+        DataType::Dictionary(_, value_type) => return sql_type(value_type),
+        other => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("--layout can not define a column of type {other}"),
+            ))
+        }
+    };
+    Ok(sql_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{Field, Schema, TimeUnit};
+    use std::sync::Arc;
+
+    #[test]
+    fn create_tables_with_column_types() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("o_totalprice", DataType::Float64, false),
+            Field::new("o_orderdate", DataType::Utf8, false),
+            Field::new(
+                "l_shipdate",
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                false,
+            ),
+            Field::new(
+                "o_orderpriority",
+                DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8)),
+                false,
+            ),
+        ]));
+        let tables = [("orders", schema)];
+        let path = Path::new("/data/tpch_sf1");
+        let sql = OutputLayout::Spark
+            .create_tables_sql("tpch_sf1", path, OutputFormat::Parquet, &tables)
+            .unwrap();
+        assert!(sql.contains(
+            "(o_totalprice DOUBLE, o_orderdate STRING, l_shipdate TIMESTAMP_NTZ, o_orderpriority STRING)"
+        ));
+        let sql = OutputLayout::Datafusion
+            .create_tables_sql("tpch_sf1", path, OutputFormat::Parquet, &tables)
+            .unwrap();
+        assert!(sql.contains(
+            "(o_totalprice DOUBLE, o_orderdate VARCHAR, l_shipdate TIMESTAMP, o_orderpriority VARCHAR)"
+        ));
+    }
+}
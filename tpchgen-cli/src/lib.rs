@@ -0,0 +1,25 @@
+//! Library components of the `tpchgen-cli` TPC-H data generator
+//!
+//! The `tpchgen-cli` binary is built from these components, which can also be
+//! used by applications that embed TPC-H data generation:
+//!
+//! * [`generate`]: [`Source`](generate::Source) and [`Sink`](generate::Sink)
+//!   traits and [`generate_in_chunks`](generate::generate_in_chunks) to
+//!   generate data in parallel and write it in order
+//! * [`statistics`]: [`WriteStatistics`](statistics::WriteStatistics) with
+//!   callbacks for progress displays and metrics, [`Metrics`](statistics::Metrics)
+//!   to collect them per table as JSON, and a [`Sink`](generate::Sink) that
+//!   records the buffers written
+//! * [`live`]: a [`Stream`](futures::Stream) of new orders and line items at a
+//!   fixed rate of rows per second, for streaming ingestion benchmarks
+//! * `object_store_sink`: a [`Sink`](generate::Sink) that uploads to any
+//!   backend supported by the [`object_store`] crate (requires the
+//!   `object_store` feature)
+//!
+//! [`object_store`]: https://docs.rs/object_store
+
+pub mod generate;
+pub mod live;
+#[cfg(feature = "object_store")]
+pub mod object_store_sink;
+pub mod statistics;
@@ -0,0 +1,101 @@
+//! A [`Stream`] of new orders at a fixed rate, for streaming ingestion
+//! benchmarks
+//!
+//! [`live_orders`] paces the orders of a [`LiveOrderGenerator`], which
+//! continue the ORDERS and LINEITEM tables beyond the scale factor with
+//! increasing order dates after those of the base orders, so that the
+//! order and line item rows are emitted at `rows_per_second` on average.
+//! Each order is emitted with its line items, so a consumer can write the
+//! two tables to a message queue or a streaming table as a producer of new
+//! orders would.
+//!
+//! # Example
+//! ```
+//! use futures::StreamExt;
+//! use tpchgen::generators::LiveOrderGenerator;
+//! use tpchgen_cli::live::live_orders;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let orders = live_orders(LiveOrderGenerator::new(1.0), 20_000.0);
+//! let orders: Vec<_> = orders.take(10).collect().await;
+//! assert_eq!(orders[0].order.o_orderkey, 9);
+//! assert!(orders
+//!     .windows(2)
+//!     .all(|w| w[0].order.o_orderdate <= w[1].order.o_orderdate));
+//! # }
+//! ```
+
+use futures::Stream;
+use std::time::Duration;
+use tokio::time::Instant;
+use tpchgen::generators::{LiveOrder, LiveOrderGenerator};
+
+/// Returns a stream of the orders of `generator`, with their line items,
+/// that emits `rows_per_second` order and line item rows on average
+///
+/// The first order is emitted immediately, and each later order once the
+/// rows before it are due. The due times are counted from the start of the
+/// stream, so the orders that are already due when a slow consumer polls
+/// the stream are emitted without waiting until it catches up.
+///
+/// # Panics
+/// If `rows_per_second` is not positive
+pub fn live_orders(
+    generator: LiveOrderGenerator<'static>,
+    rows_per_second: f64,
+) -> impl Stream<Item = LiveOrder<'static>> {
+    assert!(
+        rows_per_second > 0.0,
+        "invalid rate of {rows_per_second} rows per second"
+    );
+    let start = Instant::now();
+    let state = (generator.iter(), 0usize);
+    futures::stream::unfold(state, move |(mut orders, rows)| async move {
+        let order = orders.next()?;
+        let due = start + Duration::from_secs_f64(rows as f64 / rows_per_second);
+        tokio::time::sleep_until(due).await;
+        let rows = rows + order.row_count();
+        Some((order, (orders, rows)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use tpchgen::dates::TPCHDate;
+
+    #[tokio::test(start_paused = true)]
+    async fn paces_rows() {
+        let start = Instant::now();
+        let orders = live_orders(LiveOrderGenerator::new(0.01), 100.0);
+        let emitted: Vec<_> = orders
+            .map(|order| (order.row_count(), start.elapsed()))
+            .take(20)
+            .collect()
+            .await;
+        let mut rows = 0;
+        for (row_count, elapsed) in emitted {
+            // the timer has a resolution of a millisecond
+            let due = Duration::from_secs_f64(rows as f64 / 100.0);
+            assert!(elapsed >= due && elapsed <= due + Duration::from_millis(1));
+            rows += row_count;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dates_follow_base_orders() {
+        // more orders than the 15000 base orders of the scale factor
+        let orders = live_orders(LiveOrderGenerator::new(0.01), 1e6);
+        let dates: Vec<_> = orders
+            .map(|order| order.order.o_orderdate)
+            .take(30_000)
+            .collect()
+            .await;
+        let last_base_date = TPCHDate::from_ymd(1998, 8, 2).unwrap();
+        assert!(dates[0] > last_base_date);
+        assert!(dates.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(dates.last().unwrap().to_string(), "2010-04-27");
+    }
+}
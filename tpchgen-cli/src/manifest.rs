@@ -0,0 +1,454 @@
+//! Manifest of the generated files (`--manifest`)
+//!
+//! With `--manifest`, `manifest.json` is written to the output directory
+//! after each table is generated, listing each output file with its table,
+//! the range of parts it contains, its number of rows and bytes and its
+//! SHA-256 checksum, so orchestration tools can register the output without
+//! listing or reading the directory:
+//!
+//! ```text
+//! {
+//!   "scale_factor": 1,
+//!   "format": "tbl",
+//!   "files": [
+//!     {"path": "nation.tbl", "table": "nation", "first_part": 1, "last_part": 1, "num_parts": 1, "rows": 25, "bytes": 2224, "sha256": "..."},
+//!     ...
+//!   ]
+//! }
+//! ```
+//!
+//! Paths are relative to the output directory. The rows of each part are
+//! recorded by the [`ProgressReporter`](crate::progress::ProgressReporter) of
+//! the table, and belong to the file that was created last, so the files of
+//! a table must be written one after the other.
+//!
+//! The manifest is also saved while the files of a table are written, at
+//! most once per second, so after an interruption `--resume` can skip the
+//! files that are listed in it and still have the same size and the exact
+//! number of rows of their parts, and only generate the missing tables and
+//! parts.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// An output file listed in the manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestFile {
+    pub path: PathBuf,
+    pub table: String,
+    /// The first and last (1 based) part in the file, if any
+    pub parts: Option<(i32, i32)>,
+    /// The number of parts the table is divided into
+    pub num_parts: i32,
+    pub rows: u64,
+    pub bytes: u64,
+    /// Hex encoded SHA-256 of the contents, empty until the file is complete
+    pub sha256: String,
+}
+
+/// Minimum time between saving the manifest while files are written
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Records the files written while generating, shared by the writers of the
+/// files and the progress reporters of the tables
+#[derive(Debug, Clone)]
+pub struct Manifest(Arc<Mutex<ManifestState>>);
+
+#[derive(Debug)]
+struct ManifestState {
+    /// The output directory
+    dir: PathBuf,
+    scale_factor: f64,
+    format: String,
+    /// The table being written, and its number of parts
+    table: &'static str,
+    num_parts: i32,
+    /// The complete files of an earlier run, see [`Manifest::load`]
+    previous: Vec<ManifestFile>,
+    files: Vec<ManifestFile>,
+    /// Index in `files` of the file the next parts are written to
+    current: Option<usize>,
+    last_save: Instant,
+}
+
+impl Manifest {
+    /// Creates an empty manifest of the files in `format`, e.g. `parquet`,
+    /// written to `dir`
+    pub fn new(dir: PathBuf, scale_factor: f64, format: &str) -> Self {
+        Self(Arc::new(Mutex::new(ManifestState {
+            dir,
+            scale_factor,
+            format: format.to_string(),
+            table: "",
+            num_parts: 0,
+            previous: Vec::new(),
+            files: Vec::new(),
+            current: None,
+            last_save: Instant::now(),
+        })))
+    }
+
+    /// Returns the path of `manifest.json`
+    pub fn path(&self) -> PathBuf {
+        self.0.lock().unwrap().dir.join("manifest.json")
+    }
+
+    /// Reads the files of an earlier run from `manifest.json`, if it exists,
+    /// for `--resume`
+    ///
+    /// Only the files that still have the size in the manifest are kept, and
+    /// the manifest must be of the same scale factor and format.
+    pub fn load(&self) -> io::Result<()> {
+        let path = self.path();
+        let json = match fs::read_to_string(&path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let invalid = |message: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid manifest {}: {message}", path.display()),
+            )
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(&json).map_err(|e| invalid(e.to_string()))?;
+        let mut state = self.0.lock().unwrap();
+        let scale_factor = value["scale_factor"].as_f64();
+        let format = value["format"].as_str();
+        if scale_factor != Some(state.scale_factor) || format != Some(state.format.as_str()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} was written for scale factor {} and format {}, not {} and {}: remove it to generate all files",
+                    path.display(),
+                    scale_factor.unwrap_or_default(),
+                    format.unwrap_or_default(),
+                    state.scale_factor,
+                    state.format,
+                ),
+            ));
+        }
+        let files = value["files"]
+            .as_array()
+            .ok_or_else(|| invalid("missing files".to_string()))?;
+        for file in files {
+            let field = |name: &str| {
+                file[name]
+                    .as_u64()
+                    .ok_or_else(|| invalid(format!("missing {name}")))
+            };
+            let (Some(path), Some(table), Some(sha256)) = (
+                file["path"].as_str(),
+                file["table"].as_str(),
+                file["sha256"].as_str(),
+            ) else {
+                return Err(invalid("missing path, table or sha256".to_string()));
+            };
+            let parts = match (file["first_part"].as_i64(), file["last_part"].as_i64()) {
+                (Some(first), Some(last)) => Some((first as i32, last as i32)),
+                _ => None,
+            };
+            let file = ManifestFile {
+                path: state.dir.join(path),
+                table: table.to_string(),
+                parts,
+                num_parts: field("num_parts")? as i32,
+                rows: field("rows")?,
+                bytes: field("bytes")?,
+                sha256: sha256.to_string(),
+            };
+            // files that were removed or changed are generated again
+            match fs::metadata(&file.path) {
+                Ok(metadata) if metadata.len() == file.bytes => state.previous.push(file),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if the files of an earlier run at `path`, or in the
+    /// directory `path`, contain `parts` of the table with `num_parts` parts,
+    /// and, if given, `expected_rows` rows
+    pub fn is_complete(
+        &self,
+        path: &Path,
+        num_parts: i32,
+        parts: &[i32],
+        expected_rows: Option<u64>,
+    ) -> bool {
+        let state = self.0.lock().unwrap();
+        let files: Vec<_> = state
+            .previous
+            .iter()
+            .filter(|file| file.path.starts_with(path) && file.num_parts == num_parts)
+            .collect();
+        let complete = parts.iter().all(|&part| {
+            files.iter().any(|file| {
+                file.parts
+                    .is_some_and(|(first, last)| (first..=last).contains(&part))
+            })
+        });
+        complete
+            && expected_rows.is_none_or(|rows| files.iter().map(|f| f.rows).sum::<u64>() == rows)
+    }
+
+    /// Removes the files of an earlier run at `path`, or in the directory
+    /// `path`, which are generated again
+    pub fn remove(&self, path: &Path) {
+        let mut state = self.0.lock().unwrap();
+        state.previous.retain(|file| !file.path.starts_with(path));
+    }
+
+    /// Record that the parts of `table` are written next
+    pub fn table_start(&self, table: &'static str, num_parts: i32) {
+        let mut state = self.0.lock().unwrap();
+        state.table = table;
+        state.num_parts = num_parts;
+        state.current = None;
+    }
+
+    /// Record that the file `path` was created, and that the next parts are
+    /// written to it, returning the index of the file for
+    /// [`Self::file_finished`]
+    pub fn file_started(&self, path: &Path) -> usize {
+        let mut state = self.0.lock().unwrap();
+        let file = ManifestFile {
+            path: path.to_path_buf(),
+            table: state.table.to_string(),
+            parts: None,
+            num_parts: state.num_parts,
+            rows: 0,
+            bytes: 0,
+            sha256: String::new(),
+        };
+        state.files.push(file);
+        let index = state.files.len() - 1;
+        state.current = Some(index);
+        index
+    }
+
+    /// Record that `part`, of `rows` rows, was written to the file created
+    /// last
+    pub fn part_complete(&self, part: i32, rows: u64) {
+        let mut state = self.0.lock().unwrap();
+        let Some(index) = state.current else {
+            return;
+        };
+        let file = &mut state.files[index];
+        file.parts = Some(match file.parts {
+            Some((first, last)) => (first.min(part), last.max(part)),
+            None => (part, part),
+        });
+        file.rows += rows;
+    }
+
+    /// Record the size and checksum of the file with `index`, once it is
+    /// complete
+    ///
+    /// Saves the manifest if it was not saved in the last second, so that
+    /// `--resume` can skip the file if generation is interrupted.
+    pub fn file_finished(&self, index: usize, bytes: u64, sha256: String) -> io::Result<()> {
+        let mut state = self.0.lock().unwrap();
+        let file = &mut state.files[index];
+        file.bytes = bytes;
+        file.sha256 = sha256;
+        if state.last_save.elapsed() >= SAVE_INTERVAL {
+            state.save()?;
+        }
+        Ok(())
+    }
+
+    /// Record a complete file of the current table, with the rows of `part`
+    ///
+    /// Unlike [`Self::file_started`], the rows of parts completed later are
+    /// not added to the file.
+    pub fn add_file(&self, path: &Path, part: i32, rows: u64, bytes: u64, sha256: String) {
+        let mut state = self.0.lock().unwrap();
+        let file = ManifestFile {
+            path: path.to_path_buf(),
+            table: state.table.to_string(),
+            parts: Some((part, part)),
+            num_parts: state.num_parts,
+            rows,
+            bytes,
+            sha256,
+        };
+        state.files.push(file);
+        state.current = None;
+    }
+
+    /// Returns the files of an earlier run that are kept, and the complete
+    /// files written since
+    pub fn files(&self) -> Vec<ManifestFile> {
+        self.0.lock().unwrap().files()
+    }
+
+    /// Writes `manifest.json` to the output directory, with the complete
+    /// files
+    pub fn save(&self) -> io::Result<()> {
+        self.0.lock().unwrap().save()
+    }
+
+    /// Returns `path` relative to the output directory, with `/` separators
+    /// on all platforms
+    pub fn relative_path(&self, path: &Path) -> String {
+        self.0.lock().unwrap().relative_path(path)
+    }
+}
+
+impl ManifestState {
+    /// See [`Manifest::files`]
+    fn files(&self) -> Vec<ManifestFile> {
+        // files that are not finished, or have no parts yet
+        let complete = |file: &&ManifestFile| !file.sha256.is_empty() && file.parts.is_some();
+        let files: Vec<_> = self.files.iter().filter(complete).collect();
+        let previous = self
+            .previous
+            .iter()
+            .filter(|p| !self.files.iter().any(|file| file.path == p.path));
+        previous.chain(files).cloned().collect()
+    }
+
+    /// See [`Manifest::relative_path`]
+    fn relative_path(&self, path: &Path) -> String {
+        let path = path.strip_prefix(&self.dir).unwrap_or(path);
+        let path: Vec<_> = path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        path.join("/")
+    }
+
+    /// See [`Manifest::save`]
+    fn save(&mut self) -> io::Result<()> {
+        let mut json = String::new();
+        writeln!(json, "{{").unwrap();
+        writeln!(json, r#"  "scale_factor": {},"#, self.scale_factor).unwrap();
+        writeln!(json, r#"  "format": {},"#, quote(&self.format)).unwrap();
+        writeln!(json, r#"  "files": ["#).unwrap();
+        let files = self.files();
+        for (index, file) in files.iter().enumerate() {
+            let path = self.relative_path(&file.path);
+            let (first, last) = match file.parts {
+                Some((first, last)) => (first.to_string(), last.to_string()),
+                None => ("null".to_string(), "null".to_string()),
+            };
+            let separator = if index + 1 < files.len() { "," } else { "" };
+            writeln!(
+                json,
+                r#"    {{"path": {}, "table": {}, "first_part": {first}, "last_part": {last}, "num_parts": {}, "rows": {}, "bytes": {}, "sha256": {}}}{separator}"#,
+                quote(&path),
+                quote(&file.table),
+                file.num_parts,
+                file.rows,
+                file.bytes,
+                quote(&file.sha256),
+            )
+            .unwrap();
+        }
+        writeln!(json, "  ]").unwrap();
+        writeln!(json, "}}").unwrap();
+        // replace the manifest at once, so it is complete if interrupted
+        let path = self.dir.join("manifest.json");
+        let partial = self.dir.join("manifest.json.partial");
+        fs::write(&partial, json)?;
+        fs::rename(partial, path)?;
+        self.last_save = Instant::now();
+        Ok(())
+    }
+}
+
+/// Formats `value` as a JSON string
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_parts_of_files() {
+        let dir = Path::new("/data/tpch");
+        let manifest = Manifest::new(dir.to_path_buf(), 1.0, "parquet");
+        manifest.table_start("orders", 4);
+        // e.g. two Parquet files with --max-file-size
+        let first = manifest.file_started(&dir.join("orders/orders_0001.parquet"));
+        manifest.part_complete(1, 10);
+        manifest.part_complete(2, 20);
+        let second = manifest.file_started(&dir.join("orders/orders_0002.parquet"));
+        manifest.part_complete(3, 30);
+        manifest.part_complete(4, 40);
+        // not listed until complete
+        assert_eq!(manifest.files().len(), 0);
+        let mut state = manifest.0.lock().unwrap();
+        state.files[first].sha256 = "aa".to_string();
+        state.files[second].sha256 = "bb".to_string();
+        state.files[second].bytes = 200;
+        drop(state);
+        manifest.table_start("nation", 1);
+        manifest.add_file(&dir.join("nation.tbl"), 1, 25, 2224, "cc".to_string());
+        manifest.part_complete(1, 25);
+
+        let files = manifest.files();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].table, "orders");
+        assert_eq!((files[0].parts, files[0].rows), (Some((1, 2)), 30));
+        assert_eq!((files[1].parts, files[1].rows), (Some((3, 4)), 70));
+        assert_eq!(files[1].bytes, 200);
+        assert_eq!((files[2].table.as_str(), files[2].rows), ("nation", 25));
+        assert_eq!(files[2].num_parts, 1);
+    }
+
+    #[test]
+    fn resumes_complete_files() {
+        let dir = std::env::temp_dir().join(format!("tpchgen-manifest-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let manifest = Manifest::new(dir.clone(), 0.01, "tbl");
+        manifest.table_start("orders", 3);
+        for part in 1..=3 {
+            let path = dir.join(format!("orders.tbl.{part}"));
+            fs::write(&path, "1|2|\n").unwrap();
+            let index = manifest.file_started(&path);
+            manifest.part_complete(part, 1);
+            manifest.file_finished(index, 5, "aa".to_string()).unwrap();
+        }
+        manifest.save().unwrap();
+        // part 3 was changed after the manifest was saved
+        fs::write(dir.join("orders.tbl.3"), "1|").unwrap();
+
+        let resumed = Manifest::new(dir.clone(), 0.01, "tbl");
+        resumed.load().unwrap();
+        assert!(resumed.is_complete(&dir.join("orders.tbl.1"), 3, &[1], None));
+        assert!(resumed.is_complete(&dir.join("orders.tbl.1"), 3, &[1], Some(1)));
+        // with fewer rows than the part has
+        assert!(!resumed.is_complete(&dir.join("orders.tbl.2"), 3, &[2], Some(2)));
+        assert!(!resumed.is_complete(&dir.join("orders.tbl.1"), 4, &[1], None));
+        assert!(!resumed.is_complete(&dir.join("orders.tbl.3"), 3, &[3], None));
+        assert_eq!(resumed.files().len(), 2);
+        // of another scale factor
+        assert!(Manifest::new(dir.clone(), 1.0, "tbl").load().is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn quotes_strings() {
+        assert_eq!(quote("a\"b\\c\nd"), r#""a\"b\\c\u000ad""#);
+    }
+}
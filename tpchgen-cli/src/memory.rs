@@ -0,0 +1,191 @@
+//! Memory usage reporting (`--report-memory`)
+//!
+//! A background thread samples the resident set size (RSS) of the process
+//! every `--memory-sample-ms` milliseconds. Generation is divided into
+//! stages, e.g. creating the distributions and text pool or writing the
+//! Parquet row groups of a table, and the RSS at the start of each stage and
+//! the peak RSS sampled during it are logged when the stage ends (and
+//! reported as `memory` events with `--progress-json`). This shows how much
+//! memory the in-flight chunks, Parquet encoders, etc. need for the chosen
+//! number of threads and batch sizes.
+//!
+//! The RSS is read from `/proc/self/status`, so it is only available on
+//! Linux. It is the memory of the whole process: stages of datasets built at
+//! the same time with `build --jobs` overlap.
+
+use crate::progress;
+use log::{debug, info};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Resident set size of the process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rss {
+    /// Current resident set size in bytes
+    pub current: u64,
+    /// Peak resident set size since the process started in bytes
+    pub peak: u64,
+}
+
+/// Returns the resident set size of the process, or `None` if this is not
+/// known on the current platform.
+#[cfg(target_os = "linux")]
+pub fn rss() -> Option<Rss> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_status(&status)
+}
+
+/// Returns the resident set size of the process, or `None` if this is not
+/// known on the current platform.
+#[cfg(not(target_os = "linux"))]
+pub fn rss() -> Option<Rss> {
+    None
+}
+
+/// Parses the `VmRSS` and `VmHWM` lines of `/proc/<pid>/status`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_status(status: &str) -> Option<Rss> {
+    let field = |name: &str| {
+        let line = status.lines().find(|line| line.starts_with(name))?;
+        let kb = line[name.len()..].trim().strip_suffix("kB")?;
+        kb.trim().parse::<u64>().ok().map(|kb| kb * 1024)
+    };
+    Some(Rss {
+        current: field("VmRSS:")?,
+        peak: field("VmHWM:")?,
+    })
+}
+
+/// Formats a number of bytes as MiB
+fn mib(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// The stage being sampled
+#[derive(Debug)]
+struct Stage {
+    name: String,
+    /// RSS when the stage started
+    start: u64,
+    /// Largest RSS sampled during the stage
+    peak: u64,
+}
+
+/// Samples the resident set size in a background thread, see the module
+/// documentation
+#[derive(Debug)]
+pub struct MemoryMonitor {
+    stage: Arc<Mutex<Option<Stage>>>,
+    progress_json: bool,
+    /// Stops the sampling thread when dropped
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MemoryMonitor {
+    /// Starts sampling every `interval`, or returns `None` if the RSS is not
+    /// known on the current platform
+    pub fn start(interval: Duration, progress_json: bool) -> Option<Self> {
+        rss()?;
+        let stage: Arc<Mutex<Option<Stage>>> = Arc::new(Mutex::new(None));
+        let (stop, stopped) = mpsc::channel();
+        let thread = std::thread::spawn({
+            let stage = Arc::clone(&stage);
+            move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let Some(rss) = rss() else { continue };
+                    match stage.lock().unwrap().as_mut() {
+                        Some(stage) => {
+                            stage.peak = stage.peak.max(rss.current);
+                            debug!("RSS {} during {}", mib(rss.current), stage.name);
+                        }
+                        None => debug!("RSS {}", mib(rss.current)),
+                    }
+                }
+            }
+        });
+        Some(Self {
+            stage,
+            progress_json,
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+
+    /// Attributes the samples to the stage `name` until the returned guard
+    /// is dropped
+    pub fn stage(&self, name: impl Into<String>) -> MemoryStage<'_> {
+        let start = rss().map(|rss| rss.current).unwrap_or_default();
+        *self.stage.lock().unwrap() = Some(Stage {
+            name: name.into(),
+            start,
+            peak: start,
+        });
+        MemoryStage { monitor: self }
+    }
+
+    /// Ends the current stage, reporting its memory usage
+    fn end_stage(&self) {
+        let Some(mut stage) = self.stage.lock().unwrap().take() else {
+            return;
+        };
+        if let Some(rss) = rss() {
+            stage.peak = stage.peak.max(rss.current);
+        }
+        info!(
+            "Memory: {} peak RSS {} ({} at start)",
+            stage.name,
+            mib(stage.peak),
+            mib(stage.start)
+        );
+        if self.progress_json {
+            progress::memory(&stage.name, stage.start, stage.peak);
+        }
+    }
+}
+
+impl Drop for MemoryMonitor {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        if let Some(rss) = rss() {
+            info!("Memory: peak RSS of the process {}", mib(rss.peak));
+        }
+    }
+}
+
+/// A stage of a [`MemoryMonitor`], which ends when dropped
+#[derive(Debug)]
+pub struct MemoryStage<'a> {
+    monitor: &'a MemoryMonitor,
+}
+
+impl Drop for MemoryStage<'_> {
+    fn drop(&mut self) {
+        self.monitor.end_stage();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_status() {
+        let status =
+            "Name:\ttpchgen-cli\nVmPeak:\t  200000 kB\nVmHWM:\t   51200 kB\nVmRSS:\t   10240 kB\n";
+        assert_eq!(
+            parse_status(status),
+            Some(Rss {
+                current: 10 * 1024 * 1024,
+                peak: 50 * 1024 * 1024,
+            })
+        );
+        assert_eq!(parse_status("Name:\ttpchgen-cli\n"), None);
+        assert_eq!(mib(3 * 1024 * 1024 / 2), "1.5 MiB");
+    }
+}
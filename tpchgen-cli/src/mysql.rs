@@ -0,0 +1,187 @@
+//! Bulk loading into MySQL and MariaDB (`--mysql-url`)
+//!
+//! Requires the `mysql` feature. Instead of writing files, the Arrow batches
+//! for each table are inserted with batched multi-row `INSERT` statements:
+//!
+//! * The table is created (`CREATE TABLE IF NOT EXISTS`) if needed
+//! * The statements are those of `--format=sql --sql-dialect mysql`, see
+//!   [`crate::sql`]
+//! * Parts are generated and loaded concurrently, each over its own connection
+//! * Failed `INSERT`s are retried with an exponential backoff if they failed
+//!   to connect or hit a deadlock or lock wait timeout, after which the
+//!   server rolled back the statement. Other server errors, e.g. a duplicate
+//!   key, fail the same way again, and an `INSERT` whose connection fails
+//!   after it was sent may have been committed, so neither is retried
+//!
+//! Any database that speaks the MySQL protocol and accepts these statements
+//! can be loaded, e.g. TiDB. Batches of `--mysql-batch-rows` rows keep each
+//! statement, and so each transaction, small.
+//!
+//! Rows within a part are inserted in order, but parts may complete in any
+//! order.
+
+use crate::progress::{write_callbacks, ProgressReporter};
+use crate::retry::{no_retry, RetryPolicy};
+use crate::sql::{create_table_sql, insert_sql, SqlDialect};
+use futures::StreamExt;
+use log::debug;
+use sqlx::mysql::{MySqlDatabaseError, MySqlPool, MySqlPoolOptions};
+use sqlx::Executor;
+use std::io;
+use std::sync::Arc;
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::statistics::WriteStatistics;
+
+/// Options for loading tables into MySQL
+#[derive(Debug, Clone, Copy)]
+pub struct LoadOptions {
+    /// Maximum number of rows in each `INSERT` statement
+    pub batch_rows: usize,
+    /// Number of parts loaded concurrently (and connections used) per table
+    pub concurrency: usize,
+    /// How failed statements are retried
+    pub retry: RetryPolicy,
+}
+
+/// Loads the batches from a set of RecordBatchIterators into `table` in the
+/// MySQL database at `url`
+pub async fn load_table<I>(
+    url: &str,
+    table: &'static str,
+    iter_iter: I,
+    options: LoadOptions,
+    progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+{
+    debug!(
+        "Loading {table} into MySQL with {} connections",
+        options.concurrency
+    );
+    let mut iter_iter = iter_iter.peekable();
+
+    // get schema from the first iterator
+    let Some(first_iter) = iter_iter.peek() else {
+        return Ok(()); // no data shrug
+    };
+    let schema = Arc::clone(first_iter.schema());
+
+    let pool = MySqlPoolOptions::new()
+        .max_connections(options.concurrency as u32)
+        .connect(url)
+        .await
+        .map_err(io::Error::other)?;
+    let sql = create_table_sql(SqlDialect::Mysql, table, &schema)?;
+    execute(&pool, &sql, options.retry).await?;
+
+    let mut statistics =
+        WriteStatistics::new("parts").with_callbacks(write_callbacks(progress.as_ref()));
+    let progress = progress.map(|p| Arc::new(std::sync::Mutex::new(p)));
+
+    let mut loads = futures::stream::iter(iter_iter)
+        .map(|iter| {
+            let pool = pool.clone();
+            let progress = progress.clone();
+            async move {
+                // generate and insert each part on a separate task
+                tokio::task::spawn(async move {
+                    let mut rows = 0;
+                    let mut bytes = 0;
+                    for batch in iter {
+                        let mut offset = 0;
+                        while offset < batch.num_rows() {
+                            let len = options.batch_rows.min(batch.num_rows() - offset);
+                            let batch = batch.slice(offset, len);
+                            let sql = insert_sql(SqlDialect::Mysql, table, &batch);
+                            insert(&pool, &sql, options.retry).await?;
+                            rows += len as u64;
+                            bytes += sql.len() as u64;
+                            offset += len;
+                        }
+                    }
+                    if let Some(progress) = progress {
+                        progress.lock().unwrap().part_complete(rows, bytes);
+                    }
+                    Ok(bytes) as Result<u64, io::Error>
+                })
+                .await
+                .expect("Inner task panicked")
+            }
+        })
+        .buffer_unordered(options.concurrency);
+
+    while let Some(bytes) = loads.next().await {
+        statistics.increment_bytes(bytes? as usize);
+        statistics.increment_chunks(1);
+    }
+    pool.close().await;
+    Ok(())
+}
+
+/// Executes the idempotent statement `sql`, e.g. `CREATE TABLE IF NOT
+/// EXISTS`, retrying according to `retry` on failure
+async fn execute(pool: &MySqlPool, sql: &str, retry: RetryPolicy) -> Result<(), io::Error> {
+    retry
+        .retry("MySQL statement", || async {
+            sqlx::raw_sql(sql)
+                .execute(pool)
+                .await
+                .map(|_| ())
+                .map_err(io::Error::other)
+        })
+        .await
+}
+
+/// Executes the `INSERT` statement `sql`, retrying according to `retry` only
+/// if it can not have inserted any rows
+async fn insert(pool: &MySqlPool, sql: &str, retry: RetryPolicy) -> Result<(), io::Error> {
+    retry.retry("MySQL INSERT", || try_insert(pool, sql)).await
+}
+
+/// Executes the `INSERT` statement `sql` once, returning an error marked with
+/// [`no_retry`] if it may have been committed
+async fn try_insert(pool: &MySqlPool, sql: &str) -> Result<(), io::Error> {
+    let mut connection = pool.acquire().await.map_err(io::Error::other)?;
+    connection
+        .execute(sqlx::raw_sql(sql))
+        .await
+        .map(|_| ())
+        .map_err(|e| match &e {
+            // rolled back by the server, and may succeed when retried
+            sqlx::Error::Database(error)
+                if error
+                    .try_downcast_ref::<MySqlDatabaseError>()
+                    .is_some_and(|error| is_transient(error.number())) =>
+            {
+                io::Error::other(e)
+            }
+            // e.g. a syntax error or duplicate key, which fails again, or a
+            // lost connection, after which the statement may have been
+            // committed
+            _ => no_retry(e),
+        })
+}
+
+/// Returns true if the MySQL error `number` is a transient failure of a
+/// statement that the server rolled back: a lock wait timeout (1205) or a
+/// deadlock (1213)
+fn is_transient(number: u16) -> bool {
+    matches!(number, 1205 | 1213)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_transient_errors() {
+        // ER_LOCK_WAIT_TIMEOUT, ER_LOCK_DEADLOCK
+        assert!(is_transient(1205));
+        assert!(is_transient(1213));
+        // ER_DUP_ENTRY, ER_PARSE_ERROR, ER_NO_SUCH_TABLE, ER_DATA_TOO_LONG
+        for number in [1062, 1064, 1146, 1406] {
+            assert!(!is_transient(number), "{number}");
+        }
+    }
+}
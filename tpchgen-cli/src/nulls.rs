@@ -0,0 +1,209 @@
+//! NULL injection (`--null-percent`)
+//!
+//! With `--null-percent 5`, about 5% of the values of each comment and date
+//! column are NULL, see [`tpchgen::nulls`]. TBL and CSV output write them as
+//! `--null-token` (by default an empty field), and Parquet and Arrow IPC
+//! output as real nulls, in columns marked nullable in the schema.
+//!
+//! The same values are NULL in every format and number of parts.
+
+use crate::surrogate_key::{primary_key_columns, SurrogateKey};
+use crate::Table;
+use arrow::array::{make_array, RecordBatch};
+use arrow::buffer::NullBuffer;
+use arrow::datatypes::{Field, Schema, SchemaRef};
+use std::sync::Arc;
+use tpchgen::nulls::{is_nullable, NullInjection};
+use tpchgen_arrow::RecordBatchIterator;
+
+/// Parses a percentage of NULLs from the command line, e.g. `2.5`
+pub fn parse_percent(value: &str) -> Result<f64, String> {
+    let percent = value
+        .trim()
+        .trim_end_matches('%')
+        .parse::<f64>()
+        .map_err(|_| format!("invalid percentage '{value}'"))?;
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(format!(
+            "invalid percentage '{value}': must be between 0 and 100"
+        ));
+    }
+    Ok(percent)
+}
+
+/// Returns the names of the columns of the table of `row`
+pub fn column_names<R: SurrogateKey>(_row: &R) -> Vec<String> {
+    let schema = R::TABLE.schema();
+    schema.fields().iter().map(|f| f.name().clone()).collect()
+}
+
+/// Appends `line`, a row in TBL format, to `buffer` with the values that are
+/// NULL in the row with primary key `key` replaced by the token of `nulls`
+///
+/// `columns` are the names of the columns of the row.
+pub fn write_tbl_line(
+    buffer: &mut Vec<u8>,
+    line: &str,
+    columns: &[String],
+    nulls: &NullInjection,
+    key: &[i64],
+) {
+    // generated values never contain '|'
+    for (value, column) in line.split_terminator('|').zip(columns) {
+        let value = if nulls.is_null(column, key) {
+            nulls.token()
+        } else {
+            value
+        };
+        buffer.extend_from_slice(value.as_bytes());
+        buffer.push(b'|');
+    }
+}
+
+/// Returns `schema` with the columns that NULLs are injected into marked
+/// nullable
+pub fn nullable_schema(schema: &SchemaRef) -> SchemaRef {
+    let fields: Vec<_> = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let nullable = is_nullable(field.name());
+            Field::clone(field).with_nullable(nullable)
+        })
+        .collect();
+    Arc::new(Schema::new(fields))
+}
+
+/// A [`RecordBatchIterator`] that replaces the values selected by a
+/// [`NullInjection`] in the batches of `inner` with nulls, if enabled
+pub struct NullInjectionArrow<I> {
+    inner: I,
+    table: Table,
+    nulls: Option<&'static NullInjection>,
+    schema: SchemaRef,
+}
+
+impl<I: RecordBatchIterator> NullInjectionArrow<I> {
+    /// Injects `nulls` into the batches of `table` of `inner`, or passes
+    /// them through unchanged if `nulls` is `None`
+    pub fn new(inner: I, table: Table, nulls: Option<&'static NullInjection>) -> Self {
+        let schema = match nulls {
+            Some(_) => nullable_schema(inner.schema()),
+            None => Arc::clone(inner.schema()),
+        };
+        Self {
+            inner,
+            table,
+            nulls,
+            schema,
+        }
+    }
+}
+
+impl<I: RecordBatchIterator> RecordBatchIterator for NullInjectionArrow<I> {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl<I: RecordBatchIterator> Iterator for NullInjectionArrow<I> {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.inner.next()?;
+        let Some(nulls) = self.nulls else {
+            return Some(batch);
+        };
+        let keys = primary_key_columns(self.table, &batch);
+        let mut key = vec![0; keys.len()];
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .map(|(field, array)| {
+                if !field.is_nullable() {
+                    return Arc::clone(array);
+                }
+                let valid: NullBuffer = (0..batch.num_rows())
+                    .map(|row| {
+                        for (k, column) in key.iter_mut().zip(&keys) {
+                            *k = column.value(row);
+                        }
+                        !nulls.is_null(field.name(), &key)
+                    })
+                    .collect();
+                let data = array.to_data().into_builder().nulls(Some(valid));
+                make_array(data.build().expect("valid null buffer"))
+            })
+            .collect();
+        Some(RecordBatch::try_new(Arc::clone(&self.schema), columns).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, AsArray};
+    use tpchgen::csv::OrderCsv;
+    use tpchgen::generators::OrderGenerator;
+    use tpchgen_arrow::OrderArrow;
+
+    #[test]
+    fn parses_percentages() {
+        assert_eq!(parse_percent("2.5"), Ok(2.5));
+        assert_eq!(parse_percent("10%"), Ok(10.0));
+        assert!(parse_percent("101").is_err());
+        assert!(parse_percent("-1").is_err());
+        assert!(parse_percent("x").is_err());
+    }
+
+    #[test]
+    fn arrow_matches_text() {
+        let nulls = Box::leak(Box::new(NullInjection::new(20.0).with_token("NULL")));
+        let generator = OrderGenerator::new(0.01, 1, 1);
+        let batches = NullInjectionArrow::new(
+            OrderArrow::new(generator.clone()),
+            Table::Orders,
+            Some(nulls),
+        );
+        let schema = Arc::clone(batches.schema());
+        assert!(schema.field_with_name("o_comment").unwrap().is_nullable());
+        assert!(!schema.field_with_name("o_orderkey").unwrap().is_nullable());
+
+        let index = schema.index_of("o_comment").unwrap();
+        let mut arrow_nulls = Vec::new();
+        for batch in batches {
+            let comments = batch.column(index).as_string_view();
+            arrow_nulls.extend((0..comments.len()).map(|row| comments.is_null(row)));
+        }
+        let text_nulls: Vec<_> = generator
+            .iter()
+            .map(|order| {
+                let csv = OrderCsv::new(order).with_nulls(nulls).to_string();
+                csv.ends_with(",NULL")
+            })
+            .collect();
+        assert_eq!(arrow_nulls, text_nulls);
+        // about 20% of the 15000 orders
+        let count = arrow_nulls.iter().filter(|&&null| null).count();
+        assert!((2700..3300).contains(&count), "{count}");
+    }
+
+    #[test]
+    fn replaces_tbl_values() {
+        let columns: Vec<_> = ["r_regionkey", "r_name", "r_comment"]
+            .map(String::from)
+            .to_vec();
+        let nulls = NullInjection::new(100.0).with_token("\\N");
+        let mut buffer = Vec::new();
+        write_tbl_line(
+            &mut buffer,
+            "0|AFRICA|lar deposits|",
+            &columns,
+            &nulls,
+            &[0],
+        );
+        assert_eq!(buffer, b"0|AFRICA|\\N|");
+    }
+}
@@ -0,0 +1,116 @@
+//! [`ObjectStoreSink`]: a [`Sink`] that writes to an [`ObjectStore`]
+//!
+//! Buffers are uploaded with a multipart upload: data is split into parts of a
+//! configurable size, and several parts are uploaded concurrently while later
+//! buffers are still being generated.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use object_store::memory::InMemory;
+//! # use object_store::path::Path;
+//! # use tpchgen_cli::object_store_sink::ObjectStoreSink;
+//! let store = Arc::new(InMemory::new());
+//! let sink = ObjectStoreSink::new(store, Path::from("tpch/lineitem.tbl"))
+//!     .with_part_size(16 * 1024 * 1024)
+//!     .with_max_concurrency(4);
+//! // pass `sink` to generate_in_chunks
+//! ```
+
+use crate::generate::Sink;
+use object_store::path::Path;
+use object_store::{ObjectStore, PutPayload, WriteMultipart};
+use std::io;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+/// Default size of each part of the multipart upload (10MB)
+pub const DEFAULT_PART_SIZE: usize = 10 * 1024 * 1024;
+
+/// Default number of parts uploaded concurrently
+pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// A [`Sink`] that uploads the data written to it to a single object in an
+/// [`ObjectStore`] using a multipart upload.
+///
+/// [`Sink`] methods are synchronous, so they must be called from a thread
+/// that can block within a tokio runtime, such as the writer task of
+/// [`generate_in_chunks`](crate::generate::generate_in_chunks).
+pub struct ObjectStoreSink {
+    store: Arc<dyn ObjectStore>,
+    path: Path,
+    part_size: usize,
+    max_concurrency: usize,
+    /// The upload in progress, started on the first write
+    upload: Option<WriteMultipart>,
+    /// Total bytes written
+    bytes_written: usize,
+}
+
+impl ObjectStoreSink {
+    /// Create a new sink writing to `path` in `store`
+    pub fn new(store: Arc<dyn ObjectStore>, path: Path) -> Self {
+        Self {
+            store,
+            path,
+            part_size: DEFAULT_PART_SIZE,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            upload: None,
+            bytes_written: 0,
+        }
+    }
+
+    /// Set the size of each part of the multipart upload
+    ///
+    /// Note that most object stores require parts (other than the last) to
+    /// be at least 5MB.
+    pub fn with_part_size(mut self, part_size: usize) -> Self {
+        self.part_size = part_size;
+        self
+    }
+
+    /// Set the maximum number of parts uploaded concurrently
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Return the total number of bytes written to this sink
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+}
+
+impl Sink for ObjectStoreSink {
+    fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error> {
+        let handle = Handle::current();
+        let upload = match &mut self.upload {
+            Some(upload) => upload,
+            None => {
+                let upload = handle
+                    .block_on(self.store.put_multipart(&self.path))
+                    .map_err(io::Error::other)?;
+                self.upload
+                    .insert(WriteMultipart::new_with_chunk_size(upload, self.part_size))
+            }
+        };
+        // limit the number of parts in flight
+        handle
+            .block_on(upload.wait_for_capacity(self.max_concurrency))
+            .map_err(io::Error::other)?;
+        upload.write(buffer);
+        self.bytes_written += buffer.len();
+        Ok(())
+    }
+
+    fn flush(self) -> Result<(), io::Error> {
+        let handle = Handle::current();
+        match self.upload {
+            Some(upload) => handle.block_on(upload.finish()).map(|_| ()),
+            // nothing was written, so create an empty object
+            None => handle
+                .block_on(self.store.put(&self.path, PutPayload::new()))
+                .map(|_| ()),
+        }
+        .map_err(io::Error::other)
+    }
+}
@@ -0,0 +1,174 @@
+//! Writing to HDFS, WebDAV and Alibaba Cloud OSS through OpenDAL
+//! (`--output-dir hdfs://...`)
+//!
+//! The OpenDAL [`Operator`] is wrapped in an [`OpendalStore`], so these
+//! stores are written by the same sinks as S3, GCS and Azure. The URL gives
+//! the location, and `--storage-option` the remaining configuration of the
+//! OpenDAL service, such as credentials:
+//!
+//! | URL                              | OpenDAL service | from the URL             |
+//! |----------------------------------|-----------------|--------------------------|
+//! | `hdfs://namenode:8020/tpch`      | `hdfs-native`   | `name_node`, `root`      |
+//! | `webdav://host/tpch` (`webdavs`) | `webdav`        | `endpoint` (http/https), `root` |
+//! | `oss://bucket/tpch`              | `oss`           | `bucket`, `root`         |
+//!
+//! Failed requests are retried with OpenDAL's [`RetryLayer`], configured by
+//! `--max-retries` and `--retry-backoff-ms`.
+
+use crate::retry::{RetryPolicy, MAX_BACKOFF};
+use object_store::path::Path;
+use object_store::ObjectStore;
+use object_store_opendal::OpendalStore;
+use opendal::layers::RetryLayer;
+use opendal::{Operator, Scheme};
+use std::io;
+use std::sync::Arc;
+use url::Url;
+
+/// Returns the store at `url` and the path of `filename` in it, or `None`
+/// if `url` is not written through OpenDAL. `options` are OpenDAL configuration
+/// keys, which take precedence over those derived from the URL.
+pub fn object_store_path(
+    url: &Url,
+    filename: &str,
+    options: impl IntoIterator<Item = (String, String)>,
+    retry: RetryPolicy,
+) -> io::Result<Option<(Arc<dyn ObjectStore>, Path)>> {
+    let Some((scheme, mut config)) = service_config(url)? else {
+        return Ok(None);
+    };
+    config.extend(options);
+    let operator = Operator::via_iter(scheme, config)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+        .layer(retry_layer(retry));
+    // the prefix of the URL is the root of the operator
+    let path = filename
+        .split('/')
+        .fold(Path::default(), |path, part| path.child(part));
+    Ok(Some((Arc::new(OpendalStore::new(operator)), path)))
+}
+
+/// An OpenDAL scheme and its configuration
+type ServiceConfig = (Scheme, Vec<(String, String)>);
+
+/// Returns the OpenDAL service of `url` and its configuration from the URL
+fn service_config(url: &Url) -> io::Result<Option<ServiceConfig>> {
+    let scheme = match url.scheme() {
+        "hdfs" => Scheme::HdfsNative,
+        "webdav" | "webdavs" => Scheme::Webdav,
+        "oss" => Scheme::Oss,
+        _ => return Ok(None),
+    };
+    let host = url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{url} has no host, e.g. {}://host/prefix", url.scheme()),
+            )
+        })?;
+    let authority = match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    };
+    let location = match url.scheme() {
+        "hdfs" => ("name_node", format!("hdfs://{authority}")),
+        "webdav" => ("endpoint", format!("http://{authority}")),
+        "webdavs" => ("endpoint", format!("https://{authority}")),
+        _ => ("bucket", authority),
+    };
+    let root = match url.path() {
+        "" => "/",
+        path => path,
+    };
+    Ok(Some((
+        scheme,
+        vec![
+            (location.0.to_string(), location.1),
+            ("root".to_string(), root.to_string()),
+        ],
+    )))
+}
+
+/// Returns the OpenDAL equivalent of `retry`
+fn retry_layer(retry: RetryPolicy) -> RetryLayer {
+    RetryLayer::new()
+        .with_max_times(retry.max_retries)
+        .with_min_delay(retry.initial_backoff)
+        .with_max_delay(MAX_BACKOFF)
+        .with_factor(2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn config(url: &str) -> Option<ServiceConfig> {
+        service_config(&Url::parse(url).unwrap()).unwrap()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn configures_services_from_urls() {
+        assert_eq!(
+            config("hdfs://namenode:8020/user/tpch"),
+            Some((
+                Scheme::HdfsNative,
+                pairs(&[
+                    ("name_node", "hdfs://namenode:8020"),
+                    ("root", "/user/tpch")
+                ])
+            ))
+        );
+        assert_eq!(
+            config("webdav://dav.example.com/remote.php/dav"),
+            Some((
+                Scheme::Webdav,
+                pairs(&[
+                    ("endpoint", "http://dav.example.com"),
+                    ("root", "/remote.php/dav")
+                ])
+            ))
+        );
+        assert_eq!(
+            config("webdavs://dav.example.com:8443"),
+            Some((
+                Scheme::Webdav,
+                pairs(&[("endpoint", "https://dav.example.com:8443"), ("root", "/")])
+            ))
+        );
+        assert_eq!(
+            config("oss://bucket/tpch/sf1"),
+            Some((
+                Scheme::Oss,
+                pairs(&[("bucket", "bucket"), ("root", "/tpch/sf1")])
+            ))
+        );
+        assert_eq!(config("s3://bucket/tpch"), None);
+        assert!(service_config(&Url::parse("hdfs:///tpch").unwrap()).is_err());
+    }
+
+    #[tokio::test]
+    async fn writes_through_object_store() {
+        let retry = RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+        };
+        let operator = Operator::via_iter(Scheme::Memory, pairs(&[("root", "/tpch")]))
+            .unwrap()
+            .layer(retry_layer(retry));
+        let store = OpendalStore::new(operator.clone());
+        let path = Path::from("nation/nation.tbl");
+        store.put(&path, "0|ALGERIA|0|\n".into()).await.unwrap();
+        let data = operator.read("nation/nation.tbl").await.unwrap();
+        assert_eq!(data.to_vec(), b"0|ALGERIA|0|\n");
+    }
+}
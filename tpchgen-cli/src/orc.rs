@@ -0,0 +1,738 @@
+//! ORC output format (`--format=orc`)
+//!
+//! Hive, Trino and Spark read [ORC] natively, so the tables can be
+//! benchmarked on ORC without converting the Parquet output. The writer
+//! implements the parts of the format the TPC-H tables need:
+//!
+//! * Each part is encoded in parallel as one or more stripes of at most about
+//!   `--orc-stripe-size` bytes before compression, and the stripes are
+//!   written in part order, so the file is identical for any number of
+//!   threads
+//! * Integer, date, decimal and string columns use the `DIRECT` encodings
+//!   (integer run length encoding version 1), without row indexes
+//! * Streams are compressed in chunks of 256KiB with `--orc-compression`
+//! * The file footer has the row counts of the columns (there are no nulls)
+//!
+//! [ORC]: https://orc.apache.org/specification/ORCv1/
+
+use crate::progress::{write_callbacks, ProgressReporter};
+use arrow::array::{Array, AsArray, RecordBatch};
+use arrow::datatypes::{
+    DataType, Date32Type, Decimal128Type, Int16Type, Int32Type, Int64Type, SchemaRef,
+};
+use clap::ValueEnum;
+use futures::StreamExt;
+use log::debug;
+use std::io::{self, Write};
+use tokio::sync::mpsc::{Receiver, Sender};
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::generate::Sink;
+use tpchgen_cli::statistics::WriteStatistics;
+
+/// Compression of the streams of ORC output
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum OrcCompression {
+    None,
+    /// Deflate, the default of Hive
+    Zlib,
+    #[default]
+    Snappy,
+    Zstd,
+}
+
+impl OrcCompression {
+    /// Returns the `CompressionKind` of the postscript
+    fn kind(&self) -> u64 {
+        match self {
+            OrcCompression::None => 0,
+            OrcCompression::Zlib => 1,
+            OrcCompression::Snappy => 2,
+            OrcCompression::Zstd => 5,
+        }
+    }
+
+    /// Compresses one chunk of a stream
+    fn compress(&self, chunk: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            OrcCompression::None => Ok(chunk.to_vec()),
+            // ORC uses raw deflate, without the zlib header
+            OrcCompression::Zlib => {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(chunk)?;
+                encoder.finish()
+            }
+            OrcCompression::Snappy => snap::raw::Encoder::new()
+                .compress_vec(chunk)
+                .map_err(io::Error::other),
+            OrcCompression::Zstd => zstd::bulk::compress(chunk, zstd::DEFAULT_COMPRESSION_LEVEL),
+        }
+    }
+}
+
+/// Options of ORC output
+#[derive(Debug, Clone, Copy)]
+pub struct OrcOptions {
+    compression: OrcCompression,
+    /// Size in bytes of the uncompressed streams at which a stripe is closed
+    stripe_size: usize,
+    /// Size in bytes of the uncompressed chunks of compressed streams
+    block_size: usize,
+}
+
+impl OrcOptions {
+    /// Default size of stripes, as in Hive
+    pub const DEFAULT_STRIPE_SIZE: usize = 64 * 1024 * 1024;
+
+    pub fn new(compression: OrcCompression) -> Self {
+        Self {
+            compression,
+            stripe_size: Self::DEFAULT_STRIPE_SIZE,
+            block_size: 256 * 1024,
+        }
+    }
+
+    /// Close stripes once their uncompressed streams are about `stripe_size`
+    /// bytes
+    pub fn with_stripe_size(mut self, stripe_size: usize) -> Self {
+        self.stripe_size = stripe_size;
+        self
+    }
+
+    /// Returns `stream` split into chunks, each compressed and preceded by a
+    /// 3 byte header with its length and whether it is stored uncompressed
+    fn compress_stream(&self, stream: &[u8]) -> io::Result<Vec<u8>> {
+        if self.compression == OrcCompression::None {
+            return Ok(stream.to_vec());
+        }
+        let mut out = Vec::with_capacity(stream.len() / 2);
+        for chunk in stream.chunks(self.block_size) {
+            let compressed = self.compression.compress(chunk)?;
+            let (header, body) = if compressed.len() < chunk.len() {
+                (compressed.len() << 1, compressed.as_slice())
+            } else {
+                ((chunk.len() << 1) | 1, chunk)
+            };
+            out.extend_from_slice(&(header as u32).to_le_bytes()[..3]);
+            out.extend_from_slice(body);
+        }
+        Ok(out)
+    }
+}
+
+/// Converts a set of RecordBatchIterators into an ORC file written to `sink`
+///
+/// Uses num_threads to generate and encode the parts in parallel. The
+/// stripes are written in the order of the input.
+pub async fn generate_orc<S, I>(
+    sink: S,
+    iter_iter: I,
+    num_threads: usize,
+    options: OrcOptions,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    S: Sink + 'static,
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+{
+    debug!(
+        "Generating ORC with {num_threads} threads, using {:?} compression",
+        options.compression
+    );
+    let mut iter_iter = iter_iter.peekable();
+
+    // get schema from the first iterator
+    let Some(first_iter) = iter_iter.peek() else {
+        return Ok(()); // no data shrug
+    };
+    let types = orc_types(first_iter.schema())?;
+
+    let mut statistics =
+        WriteStatistics::new("stripes").with_callbacks(write_callbacks(progress.as_ref()));
+
+    // A blocking task that writes the stripes of each part to the sink
+    let (tx, mut rx): (Sender<Vec<Stripe>>, Receiver<Vec<Stripe>>) =
+        tokio::sync::mpsc::channel(num_threads);
+    let writer_task = tokio::task::spawn_blocking(move || {
+        let mut writer = OrcFileWriter::try_new(sink)?;
+        while let Some(stripes) = rx.blocking_recv() {
+            let rows = stripes.iter().map(|s| s.num_rows).sum();
+            let bytes = stripes.iter().map(|s| s.bytes.len() as u64).sum();
+            for stripe in stripes {
+                writer.write_stripe(stripe)?;
+                statistics.increment_chunks(1);
+            }
+            if let Some(progress) = progress.as_mut() {
+                progress.part_complete(rows, bytes);
+            }
+        }
+        let size = writer.finish(&types, options)?;
+        statistics.increment_bytes(size as usize);
+        Ok(()) as Result<(), io::Error>
+    });
+
+    // encode the parts in parallel
+    let mut stripe_stream = futures::stream::iter(iter_iter)
+        .map(async |iter| {
+            // run on a separate thread
+            tokio::task::spawn(async move { encode_part(iter, options) })
+                .await
+                .expect("Inner task panicked")
+        })
+        .buffered(num_threads);
+
+    let mut result = Ok(());
+    while let Some(stripes) = stripe_stream.next().await {
+        let stripes = match stripes {
+            Ok(stripes) => stripes,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+        // send the stripes to the writer task
+        if let Err(e) = tx.send(stripes).await {
+            debug!("Error sending stripes to writer: {e}");
+            break; // stop early
+        }
+    }
+    // signal the writer task that we are done
+    drop(tx);
+
+    // Wait for the writer task to finish
+    writer_task.await??;
+    result
+}
+
+/// An encoded stripe: the data streams followed by the stripe footer
+#[derive(Debug)]
+struct Stripe {
+    bytes: Vec<u8>,
+    data_length: u64,
+    footer_length: u64,
+    num_rows: u64,
+}
+
+/// Encodes the batches of one part as stripes
+fn encode_part<I: RecordBatchIterator>(iter: I, options: OrcOptions) -> io::Result<Vec<Stripe>> {
+    let mut writer = StripeWriter::try_new(iter.schema())?;
+    let mut stripes = Vec::new();
+    for batch in iter {
+        writer.append(&batch)?;
+        if writer.estimated_size() >= options.stripe_size {
+            stripes.extend(writer.finish(options)?);
+        }
+    }
+    stripes.extend(writer.finish(options)?);
+    Ok(stripes)
+}
+
+/// Returns the ORC types of `schema`: the root struct followed by one type
+/// per column
+fn orc_types(schema: &SchemaRef) -> io::Result<Vec<Proto>> {
+    let columns: Vec<u64> = (1..=schema.fields().len() as u64).collect();
+    let mut root = Proto::default().uint(1, 12).packed(2, &columns);
+    for field in schema.fields() {
+        root = root.bytes(3, field.name().as_bytes());
+    }
+    let mut types = vec![root];
+    for field in schema.fields() {
+        let column = match field.data_type() {
+            DataType::Int16 => Proto::default().uint(1, 2),
+            DataType::Int32 => Proto::default().uint(1, 3),
+            DataType::Int64 => Proto::default().uint(1, 4),
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => {
+                Proto::default().uint(1, 7)
+            }
+            DataType::Decimal128(precision, scale) => Proto::default()
+                .uint(1, 14)
+                .uint(5, *precision as u64)
+                .uint(6, *scale as u64),
+            DataType::Date32 => Proto::default().uint(1, 15),
+            data_type => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "ORC output does not support column {} of type {data_type}",
+                        field.name()
+                    ),
+                ))
+            }
+        };
+        types.push(column);
+    }
+    Ok(types)
+}
+
+/// The values of a column in the current stripe
+#[derive(Debug)]
+enum ColumnValues {
+    /// `SHORT`, `INT`, `LONG` and `DATE` (days since the epoch) columns
+    Integer(Vec<i64>),
+    /// `STRING` columns: the concatenated values and the length of each
+    String { data: Vec<u8>, lengths: Vec<i64> },
+    /// `DECIMAL` columns: the unscaled values
+    Decimal { values: Vec<i128>, scale: i8 },
+}
+
+/// Buffers the columns of a stripe
+#[derive(Debug)]
+struct StripeWriter {
+    columns: Vec<ColumnValues>,
+    num_rows: u64,
+}
+
+impl StripeWriter {
+    fn try_new(schema: &SchemaRef) -> io::Result<Self> {
+        // validates the types
+        orc_types(schema)?;
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| match field.data_type() {
+                DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => ColumnValues::String {
+                    data: Vec::new(),
+                    lengths: Vec::new(),
+                },
+                DataType::Decimal128(_, scale) => ColumnValues::Decimal {
+                    values: Vec::new(),
+                    scale: *scale,
+                },
+                _ => ColumnValues::Integer(Vec::new()),
+            })
+            .collect();
+        Ok(Self {
+            columns,
+            num_rows: 0,
+        })
+    }
+
+    fn append(&mut self, batch: &RecordBatch) -> io::Result<()> {
+        for (values, array) in self.columns.iter_mut().zip(batch.columns()) {
+            if array.null_count() > 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "ORC output does not support null values",
+                ));
+            }
+            match (values, array.data_type()) {
+                (ColumnValues::Integer(values), DataType::Int16) => {
+                    let array = array.as_primitive::<Int16Type>();
+                    values.extend(array.values().iter().map(|&v| v as i64));
+                }
+                (ColumnValues::Integer(values), DataType::Int32) => {
+                    let array = array.as_primitive::<Int32Type>();
+                    values.extend(array.values().iter().map(|&v| v as i64));
+                }
+                (ColumnValues::Integer(values), DataType::Int64) => {
+                    values.extend(array.as_primitive::<Int64Type>().values());
+                }
+                (ColumnValues::Integer(values), DataType::Date32) => {
+                    let array = array.as_primitive::<Date32Type>();
+                    values.extend(array.values().iter().map(|&v| v as i64));
+                }
+                (ColumnValues::String { data, lengths }, data_type) => {
+                    let push = |value: &str| {
+                        data.extend_from_slice(value.as_bytes());
+                        lengths.push(value.len() as i64);
+                    };
+                    match data_type {
+                        DataType::Utf8View => {
+                            array.as_string_view().iter().flatten().for_each(push)
+                        }
+                        DataType::Utf8 => array.as_string::<i32>().iter().flatten().for_each(push),
+                        _ => array.as_string::<i64>().iter().flatten().for_each(push),
+                    }
+                }
+                (ColumnValues::Decimal { values, .. }, _) => {
+                    values.extend(array.as_primitive::<Decimal128Type>().values());
+                }
+                (_, data_type) => unreachable!("column type {data_type} changed"),
+            }
+        }
+        self.num_rows += batch.num_rows() as u64;
+        Ok(())
+    }
+
+    /// Returns the approximate size of the buffered values in bytes
+    fn estimated_size(&self) -> usize {
+        self.columns
+            .iter()
+            .map(|column| match column {
+                ColumnValues::Integer(values) => values.len() * 8,
+                ColumnValues::String { data, lengths } => data.len() + lengths.len() * 2,
+                ColumnValues::Decimal { values, .. } => values.len() * 16,
+            })
+            .sum()
+    }
+
+    /// Encodes the buffered rows as a stripe, if there are any
+    fn finish(&mut self, options: OrcOptions) -> io::Result<Option<Stripe>> {
+        if self.num_rows == 0 {
+            return Ok(None);
+        }
+        // (kind, column, uncompressed stream)
+        let mut streams = Vec::new();
+        for (i, values) in self.columns.iter_mut().enumerate() {
+            let column = i as u64 + 1;
+            match values {
+                ColumnValues::Integer(values) => {
+                    let mut data = Vec::new();
+                    write_rle_v1(values, true, &mut data);
+                    values.clear();
+                    streams.push((STREAM_DATA, column, data));
+                }
+                ColumnValues::String { data, lengths } => {
+                    let mut length = Vec::new();
+                    write_rle_v1(lengths, false, &mut length);
+                    lengths.clear();
+                    streams.push((STREAM_DATA, column, std::mem::take(data)));
+                    streams.push((STREAM_LENGTH, column, length));
+                }
+                ColumnValues::Decimal { values, scale } => {
+                    let mut data = Vec::new();
+                    for &value in values.iter() {
+                        write_varint(&mut data, ((value << 1) ^ (value >> 127)) as u128);
+                    }
+                    let mut scales = Vec::new();
+                    write_rle_v1(&vec![*scale as i64; values.len()], true, &mut scales);
+                    values.clear();
+                    streams.push((STREAM_DATA, column, data));
+                    streams.push((STREAM_SECONDARY, column, scales));
+                }
+            }
+        }
+
+        let mut bytes = Vec::new();
+        let mut footer = Proto::default();
+        for (kind, column, stream) in streams {
+            let stream = options.compress_stream(&stream)?;
+            let info = Proto::default()
+                .uint(1, kind)
+                .uint(2, column)
+                .uint(3, stream.len() as u64);
+            footer = footer.message(1, info);
+            bytes.extend_from_slice(&stream);
+        }
+        let data_length = bytes.len() as u64;
+        // all columns, including the root struct, use the DIRECT encoding
+        for _ in 0..=self.columns.len() {
+            footer = footer.message(2, Proto::default().uint(1, 0));
+        }
+        let footer = options.compress_stream(&footer.0)?;
+        bytes.extend_from_slice(&footer);
+
+        let num_rows = std::mem::take(&mut self.num_rows);
+        Ok(Some(Stripe {
+            bytes,
+            data_length,
+            footer_length: footer.len() as u64,
+            num_rows,
+        }))
+    }
+}
+
+/// `Stream.Kind` of the stripe footer
+const STREAM_DATA: u64 = 1;
+const STREAM_LENGTH: u64 = 2;
+const STREAM_SECONDARY: u64 = 5;
+
+/// The `WriterVersion` of the postscript, `ORC_135`
+const WRITER_VERSION: u64 = 6;
+
+/// Writes the stripes and the file tail to a [`Sink`]
+struct OrcFileWriter<S> {
+    sink: S,
+    /// Number of bytes written so far
+    offset: u64,
+    /// `StripeInformation` of each stripe written
+    stripes: Vec<Proto>,
+    num_rows: u64,
+}
+
+impl<S: Sink> OrcFileWriter<S> {
+    fn try_new(mut sink: S) -> io::Result<Self> {
+        sink.sink(b"ORC")?;
+        Ok(Self {
+            sink,
+            offset: 3,
+            stripes: Vec::new(),
+            num_rows: 0,
+        })
+    }
+
+    fn write_stripe(&mut self, stripe: Stripe) -> io::Result<()> {
+        self.sink.sink(&stripe.bytes)?;
+        let info = Proto::default()
+            .uint(1, self.offset)
+            .uint(2, 0)
+            .uint(3, stripe.data_length)
+            .uint(4, stripe.footer_length)
+            .uint(5, stripe.num_rows);
+        self.stripes.push(info);
+        self.offset += stripe.bytes.len() as u64;
+        self.num_rows += stripe.num_rows;
+        Ok(())
+    }
+
+    /// Writes the footer and postscript, returning the size of the file
+    fn finish(mut self, types: &[Proto], options: OrcOptions) -> io::Result<u64> {
+        let mut footer = Proto::default().uint(1, 3).uint(2, self.offset);
+        for stripe in std::mem::take(&mut self.stripes) {
+            footer = footer.message(3, stripe);
+        }
+        for column_type in types {
+            footer = footer.bytes(4, &column_type.0);
+        }
+        footer = footer.uint(6, self.num_rows);
+        // the number of values and no nulls for every column
+        for _ in types {
+            let statistics = Proto::default().uint(1, self.num_rows).uint(10, 0);
+            footer = footer.message(7, statistics);
+        }
+        // no row indexes
+        footer = footer.uint(8, 0);
+        let footer = options.compress_stream(&footer.0)?;
+
+        let postscript = Proto::default()
+            .uint(1, footer.len() as u64)
+            .uint(2, options.compression.kind())
+            .uint(3, options.block_size as u64)
+            .packed(4, &[0, 12])
+            .uint(5, 0)
+            .uint(6, WRITER_VERSION)
+            .bytes(8000, b"ORC");
+        self.sink.sink(&footer)?;
+        self.sink.sink(&postscript.0)?;
+        self.sink.sink(&[postscript.0.len() as u8])?;
+        self.sink.flush()?;
+        Ok(self.offset + footer.len() as u64 + postscript.0.len() as u64 + 1)
+    }
+}
+
+/// A protocol buffers message, for the ORC metadata
+#[derive(Debug, Default)]
+struct Proto(Vec<u8>);
+
+impl Proto {
+    /// Appends a varint field
+    fn uint(mut self, field: u64, value: u64) -> Self {
+        write_varint(&mut self.0, (field << 3) as u128);
+        write_varint(&mut self.0, value as u128);
+        self
+    }
+
+    /// Appends a length delimited field
+    fn bytes(mut self, field: u64, value: &[u8]) -> Self {
+        write_varint(&mut self.0, ((field << 3) | 2) as u128);
+        write_varint(&mut self.0, value.len() as u128);
+        self.0.extend_from_slice(value);
+        self
+    }
+
+    /// Appends an embedded message field
+    fn message(self, field: u64, message: Proto) -> Self {
+        self.bytes(field, &message.0)
+    }
+
+    /// Appends a packed repeated varint field
+    fn packed(self, field: u64, values: &[u64]) -> Self {
+        let mut packed = Vec::new();
+        for &value in values {
+            write_varint(&mut packed, value as u128);
+        }
+        self.bytes(field, &packed)
+    }
+}
+
+/// Appends `value` as a base 128 varint, least significant group first
+fn write_varint(out: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Appends `values` with the integer run length encoding version 1, zigzag
+/// encoding them if `signed`
+///
+/// Runs of 3 to 130 values with a constant delta between -128 and 127 are
+/// written as a run, other values as literals in groups of up to 128.
+fn write_rle_v1(values: &[i64], signed: bool, out: &mut Vec<u8>) {
+    let write_value = |out: &mut Vec<u8>, value: i64| {
+        let value = if signed {
+            ((value << 1) ^ (value >> 63)) as u64
+        } else {
+            value as u64
+        };
+        write_varint(out, value as u128);
+    };
+    let write_literals = |out: &mut Vec<u8>, literals: &[i64]| {
+        for group in literals.chunks(128) {
+            out.push((-(group.len() as i32)) as u8);
+            for &value in group {
+                write_value(out, value);
+            }
+        }
+    };
+
+    let mut literals_start = 0;
+    let mut i = 0;
+    while i < values.len() {
+        match run_length(&values[i..]) {
+            Some((length, delta)) => {
+                write_literals(out, &values[literals_start..i]);
+                out.push((length - 3) as u8);
+                out.push(delta as u8);
+                write_value(out, values[i]);
+                i += length;
+                literals_start = i;
+            }
+            None => i += 1,
+        }
+    }
+    write_literals(out, &values[literals_start..]);
+}
+
+/// Returns the length and delta of the run at the start of `values`, if it
+/// has at least 3 values
+fn run_length(values: &[i64]) -> Option<(usize, i8)> {
+    if values.len() < 3 {
+        return None;
+    }
+    let delta = values[1].checked_sub(values[0])?;
+    let delta = i8::try_from(delta).ok()?;
+    let mut length = 2;
+    while length < values.len().min(130)
+        && values[length].checked_sub(values[length - 1]) == Some(delta as i64)
+    {
+        length += 1;
+    }
+    (length >= 3).then_some((length, delta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tpchgen::generators::NationGenerator;
+    use tpchgen_arrow::NationArrow;
+
+    #[test]
+    fn rle_v1_matches_specification() {
+        // examples of the ORC specification
+        let mut out = Vec::new();
+        write_rle_v1(&[7; 100], false, &mut out);
+        assert_eq!(out, [0x61, 0x00, 0x07]);
+
+        let mut out = Vec::new();
+        write_rle_v1(&(1..=100).rev().collect::<Vec<_>>(), false, &mut out);
+        assert_eq!(out, [0x61, 0xff, 0x64]);
+
+        let mut out = Vec::new();
+        write_rle_v1(&[2, 3, 6, 7, 11], false, &mut out);
+        assert_eq!(out, [0xfb, 0x02, 0x03, 0x06, 0x07, 0x0b]);
+
+        // literals followed by a run, zigzag encoded
+        let mut out = Vec::new();
+        write_rle_v1(&[-1, 5, 1, 2, 3], true, &mut out);
+        assert_eq!(out, [0xfe, 0x01, 0x0a, 0x00, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn compressed_chunks_have_headers() {
+        let options = OrcOptions::new(OrcCompression::Zlib);
+        let stream = vec![b'a'; 1000];
+        let compressed = options.compress_stream(&stream).unwrap();
+        let header = u32::from_le_bytes([compressed[0], compressed[1], compressed[2], 0]);
+        assert_eq!(header & 1, 0);
+        assert_eq!((header >> 1) as usize, compressed.len() - 3);
+
+        // incompressible chunks are stored as is
+        let compressed = options.compress_stream(b"abc").unwrap();
+        assert_eq!(compressed, [0x07, 0x00, 0x00, b'a', b'b', b'c']);
+    }
+
+    /// Collects the buffers written to it
+    struct VecSink(Arc<Mutex<Vec<u8>>>);
+
+    impl Sink for VecSink {
+        fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error> {
+            self.0.lock().unwrap().extend_from_slice(buffer);
+            Ok(())
+        }
+
+        fn flush(self) -> Result<(), io::Error> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_file_tail() {
+        let file = Arc::new(Mutex::new(Vec::new()));
+        let sources = (0..2).map(|_| NationArrow::new(NationGenerator::default()));
+        let options = OrcOptions::new(OrcCompression::None);
+        generate_orc(VecSink(Arc::clone(&file)), sources, 2, options, None)
+            .await
+            .unwrap();
+        let file = file.lock().unwrap();
+        assert_eq!(&file[..3], b"ORC");
+
+        // the file ends with the postscript and its length
+        let postscript_length = *file.last().unwrap() as usize;
+        let postscript = &file[file.len() - 1 - postscript_length..file.len() - 1];
+        let postscript = fields(postscript);
+        assert_eq!(postscript[1], (2, Field::Varint(0)));
+        assert_eq!(postscript.last(), Some(&(8000, Field::Bytes(b"ORC"))));
+        let (1, Field::Varint(footer_length)) = postscript[0] else {
+            panic!("no footer length");
+        };
+        let footer_start = file.len() - 1 - postscript_length - footer_length as usize;
+        let footer = fields(&file[footer_start..file.len() - 1 - postscript_length]);
+        let count = |field| footer.iter().filter(|(f, _)| *f == field).count();
+        // one stripe per part, the root struct and 4 columns
+        assert_eq!(count(3), 2);
+        assert_eq!(count(4), 5);
+        assert!(footer.contains(&(6, Field::Varint(50))));
+        assert!(footer.contains(&(2, Field::Varint(footer_start as u64))));
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Field<'a> {
+        Varint(u64),
+        Bytes(&'a [u8]),
+    }
+
+    /// Decodes the fields of a protocol buffers message
+    fn fields(mut message: &[u8]) -> Vec<(u64, Field<'_>)> {
+        fn varint(message: &mut &[u8]) -> u64 {
+            let mut value = 0;
+            for shift in (0..).step_by(7) {
+                let byte = message[0];
+                *message = &message[1..];
+                value |= ((byte & 0x7f) as u64) << shift;
+                if byte < 0x80 {
+                    break;
+                }
+            }
+            value
+        }
+        let mut fields = Vec::new();
+        while !message.is_empty() {
+            let tag = varint(&mut message);
+            let field = match tag & 7 {
+                0 => Field::Varint(varint(&mut message)),
+                2 => {
+                    let length = varint(&mut message) as usize;
+                    let (bytes, rest) = message.split_at(length);
+                    message = rest;
+                    Field::Bytes(bytes)
+                }
+                wire_type => panic!("unexpected wire type {wire_type}"),
+            };
+            fields.push((tag >> 3, field));
+        }
+        fields
+    }
+}
@@ -1,20 +1,303 @@
 //! Parquet output format
 
-use crate::statistics::WriteStatistics;
+use crate::progress::{write_callbacks, ProgressReporter};
+use arrow::array::RecordBatch;
 use arrow::datatypes::SchemaRef;
-use futures::StreamExt;
+#[cfg(feature = "object_store")]
+use bytes::Bytes;
+use clap::ValueEnum;
+use futures::{Stream, StreamExt};
 use log::debug;
-use parquet::arrow::arrow_writer::{compute_leaves, get_column_writers, ArrowColumnChunk};
+use parquet::arrow::arrow_writer::{
+    compute_leaves, get_column_writers, ArrowColumnChunk, ArrowColumnWriter, ArrowLeafColumn,
+};
+#[cfg(feature = "object_store")]
+use parquet::arrow::async_writer::AsyncFileWriter;
 use parquet::arrow::ArrowSchemaConverter;
-use parquet::basic::Compression;
-use parquet::file::properties::WriterProperties;
+use parquet::basic::{Compression, Encoding, Type as PhysicalType};
+#[cfg(feature = "object_store")]
+use parquet::file::metadata::RowGroupMetaDataPtr;
+use parquet::file::properties::{WriterProperties, WriterVersion};
 use parquet::file::writer::SerializedFileWriter;
-use parquet::schema::types::SchemaDescPtr;
+use parquet::schema::types::{SchemaDescPtr, SchemaDescriptor};
 use std::io;
 use std::io::Write;
+use std::str::FromStr;
 use std::sync::Arc;
+#[cfg(feature = "object_store")]
+use std::sync::Mutex;
 use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::statistics::WriteStatistics;
+
+/// The order in which row groups are written to Parquet output
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum RowGroupOrder {
+    /// Row groups are written in part order, so the output is identical
+    /// regardless of the number of threads
+    #[default]
+    Strict,
+    /// Row groups are written as soon as they are encoded, which avoids
+    /// waiting on slow parts but makes the order depend on scheduling
+    Any,
+}
+
+/// Version of the Parquet format written
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum ParquetWriterVersion {
+    /// Parquet 1.0, which all readers support
+    #[default]
+    #[value(name = "1.0")]
+    V1,
+    /// Parquet 2.0, with data page V2 headers and the newer encodings by
+    /// default
+    #[value(name = "2.0")]
+    V2,
+}
+
+/// Whether a Parquet column is dictionary encoded
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ParquetDictionary {
+    On,
+    Off,
+}
+
+/// Encoding of the values of Parquet data pages that are not dictionary
+/// encoded
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ParquetEncoding {
+    Plain,
+    DeltaBinaryPacked,
+    DeltaLengthByteArray,
+    DeltaByteArray,
+    ByteStreamSplit,
+}
+
+impl ParquetEncoding {
+    fn encoding(self) -> Encoding {
+        match self {
+            ParquetEncoding::Plain => Encoding::PLAIN,
+            ParquetEncoding::DeltaBinaryPacked => Encoding::DELTA_BINARY_PACKED,
+            ParquetEncoding::DeltaLengthByteArray => Encoding::DELTA_LENGTH_BYTE_ARRAY,
+            ParquetEncoding::DeltaByteArray => Encoding::DELTA_BYTE_ARRAY,
+            ParquetEncoding::ByteStreamSplit => Encoding::BYTE_STREAM_SPLIT,
+        }
+    }
+
+    /// Returns true if columns of `physical_type` can be written with this
+    /// encoding
+    fn supports(self, physical_type: PhysicalType) -> bool {
+        use PhysicalType::*;
+        match self {
+            ParquetEncoding::Plain => true,
+            ParquetEncoding::DeltaBinaryPacked => matches!(physical_type, INT32 | INT64),
+            ParquetEncoding::DeltaLengthByteArray => physical_type == BYTE_ARRAY,
+            ParquetEncoding::DeltaByteArray => {
+                matches!(physical_type, BYTE_ARRAY | FIXED_LEN_BYTE_ARRAY)
+            }
+            ParquetEncoding::ByteStreamSplit => matches!(
+                physical_type,
+                INT32 | INT64 | FLOAT | DOUBLE | FIXED_LEN_BYTE_ARRAY
+            ),
+        }
+    }
+}
+
+/// A setting of all Parquet columns, `VALUE`, or of one column,
+/// `COLUMN=VALUE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSetting<T> {
+    pub column: Option<String>,
+    pub value: T,
+}
+
+impl<T: ValueEnum> FromStr for ColumnSetting<T> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (column, value) = match s.split_once('=') {
+            Some((column, value)) => (Some(column.to_string()), value),
+            None => (None, s),
+        };
+        let value = T::from_str(value, true).map_err(|_| {
+            let values: Vec<_> = T::value_variants()
+                .iter()
+                .filter_map(|v| v.to_possible_value())
+                .map(|v| v.get_name().to_string())
+                .collect();
+            format!(
+                "invalid value '{value}', expected one of {}",
+                values.join(", ")
+            )
+        })?;
+        Ok(Self { column, value })
+    }
+}
+
+/// Returns the value of the last of `settings` of `column`, or else of the
+/// last of all columns that `applies` to it
+fn column_setting<T: Copy>(
+    settings: &[ColumnSetting<T>],
+    column: &str,
+    applies: impl Fn(T) -> bool,
+) -> Option<T> {
+    let settings = settings.iter().rev();
+    let mut column_settings = settings
+        .clone()
+        .filter(|s| s.column.as_deref() == Some(column));
+    match column_settings.next() {
+        Some(setting) => Some(setting.value),
+        None => settings
+            .filter(|s| s.column.is_none() && applies(s.value))
+            .map(|s| s.value)
+            .next(),
+    }
+}
+
+/// Options of the Parquet writer, besides the row groups
+///
+/// The settings of a column override those of all columns, and later
+/// settings override earlier ones.
+#[derive(Debug, Clone)]
+pub struct ParquetOptions {
+    compression: Compression,
+    /// Maximum size of data pages in bytes, or the default of the writer
+    data_page_size: Option<usize>,
+    writer_version: ParquetWriterVersion,
+    dictionary: Vec<ColumnSetting<ParquetDictionary>>,
+    encodings: Vec<ColumnSetting<ParquetEncoding>>,
+    /// Number of threads encoding the columns of each row group
+    column_threads: usize,
+}
+
+impl ParquetOptions {
+    /// Creates the options of files compressed with `compression`, and the
+    /// defaults of the writer otherwise
+    pub fn new(compression: Compression) -> Self {
+        Self {
+            compression,
+            data_page_size: None,
+            writer_version: ParquetWriterVersion::default(),
+            dictionary: vec![],
+            encodings: vec![],
+            column_threads: 1,
+        }
+    }
+
+    /// Set the maximum size of data pages in bytes
+    pub fn with_data_page_size(mut self, data_page_size: Option<usize>) -> Self {
+        self.data_page_size = data_page_size;
+        self
+    }
+
+    /// Set the version of the Parquet format written
+    pub fn with_writer_version(mut self, writer_version: ParquetWriterVersion) -> Self {
+        self.writer_version = writer_version;
+        self
+    }
+
+    /// Enable or disable dictionary encoding of all or some columns
+    pub fn with_dictionary(mut self, dictionary: Vec<ColumnSetting<ParquetDictionary>>) -> Self {
+        self.dictionary = dictionary;
+        self
+    }
+
+    /// Set the encoding of all or some columns, which is the fallback
+    /// encoding of columns that are dictionary encoded
+    ///
+    /// An encoding of all columns only applies to the columns whose type it
+    /// supports, e.g. `delta-binary-packed` to integer columns.
+    pub fn with_encodings(mut self, encodings: Vec<ColumnSetting<ParquetEncoding>>) -> Self {
+        self.encodings = encodings;
+        self
+    }
+
+    /// Set the number of threads encoding the leaf columns of each row group
+    ///
+    /// With 1 (the default) the columns of a row group are encoded one after
+    /// the other on the task generating its batches. The output is the same
+    /// for any number of threads.
+    pub fn with_column_threads(mut self, column_threads: usize) -> Self {
+        self.column_threads = column_threads.max(1);
+        self
+    }
+
+    /// Returns the number of threads encoding the columns of each row group
+    pub fn column_threads(&self) -> usize {
+        self.column_threads
+    }
+
+    /// Returns the compression codec
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Returns the names of the columns with settings
+    pub fn columns(&self) -> impl Iterator<Item = &str> {
+        let dictionary = self.dictionary.iter().filter_map(|s| s.column.as_deref());
+        let encodings = self.encodings.iter().filter_map(|s| s.column.as_deref());
+        dictionary.chain(encodings)
+    }
+
+    /// Returns the properties of the writer of files with `parquet_schema`
+    ///
+    /// Fails if an encoding is set for a column whose type it does not
+    /// support.
+    pub fn writer_properties(
+        &self,
+        parquet_schema: &SchemaDescriptor,
+    ) -> io::Result<WriterProperties> {
+        let writer_version = match self.writer_version {
+            ParquetWriterVersion::V1 => WriterVersion::PARQUET_1_0,
+            ParquetWriterVersion::V2 => WriterVersion::PARQUET_2_0,
+        };
+        let mut builder = WriterProperties::builder()
+            .set_compression(self.compression)
+            .set_writer_version(writer_version);
+        if let Some(data_page_size) = self.data_page_size {
+            builder = builder.set_data_page_size_limit(data_page_size);
+        }
+        for column in parquet_schema.columns() {
+            let name = column.path().string();
+            let physical_type = column.physical_type();
+            if let Some(dictionary) = column_setting(&self.dictionary, &name, |_| true) {
+                let enabled = dictionary == ParquetDictionary::On;
+                builder = builder.set_column_dictionary_enabled(column.path().clone(), enabled);
+            }
+            let Some(encoding) =
+                column_setting(&self.encodings, &name, |e| e.supports(physical_type))
+            else {
+                continue;
+            };
+            if !encoding.supports(physical_type) {
+                let encoding = encoding.to_possible_value().expect("encoding name");
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "--parquet-encoding {name}={} does not support the {physical_type} values of {name}",
+                        encoding.get_name()
+                    ),
+                ));
+            }
+            builder = builder.set_column_encoding(column.path().clone(), encoding.encoding());
+        }
+        Ok(builder.build())
+    }
+
+    /// Returns the Parquet schema of `schema` and the properties of the
+    /// writer of its files
+    pub(crate) fn parquet_schema(
+        &self,
+        schema: &SchemaRef,
+    ) -> io::Result<(SchemaDescPtr, Arc<WriterProperties>)> {
+        let parquet_schema = ArrowSchemaConverter::new()
+            .convert(schema)
+            .map_err(io::Error::other)?;
+        let writer_properties = self.writer_properties(&parquet_schema)?;
+        Ok((Arc::new(parquet_schema), Arc::new(writer_properties)))
+    }
+}
 
 pub trait IntoSize {
     /// Convert the object into a size
@@ -23,75 +306,60 @@ pub trait IntoSize {
 
 /// Converts a set of RecordBatchIterators into a Parquet file
 ///
-/// Uses num_threads to generate the data in parallel
+/// Uses num_threads to generate the data in parallel, with at most
+/// `max_row_groups` row groups in memory at once, if specified
 ///
 /// Note the input is an iterator of [`RecordBatchIterator`]; The batches
-/// produced by each iterator is encoded as its own row group.
+/// produced by each iterator is encoded as its own row group. With
+/// [`RowGroupOrder::Strict`] the row groups are written in the order of the
+/// input, so the file is byte for byte identical for any `num_threads`.
 pub async fn generate_parquet<W: Write + Send + IntoSize + 'static, I>(
     writer: W,
     iter_iter: I,
     num_threads: usize,
-    parquet_compression: Compression,
+    max_row_groups: Option<usize>,
+    row_group_order: RowGroupOrder,
+    options: &ParquetOptions,
+    mut progress: Option<ProgressReporter>,
 ) -> Result<(), io::Error>
 where
     I: Iterator<Item: RecordBatchIterator> + 'static,
 {
     debug!(
-        "Generating Parquet with {num_threads} threads, using {parquet_compression} compression"
+        "Generating Parquet with {num_threads} threads, using {} compression",
+        options.compression()
     );
-    // Based on example in https://docs.rs/parquet/latest/parquet/arrow/arrow_writer/struct.ArrowColumnWriter.html
-    let mut iter_iter = iter_iter.peekable();
-
-    // get schema from the first iterator
-    let Some(first_iter) = iter_iter.peek() else {
+    let Some(RowGroupStream {
+        mut row_group_stream,
+        parquet_schema,
+        writer_properties,
+    }) = RowGroupStream::try_new(
+        iter_iter,
+        num_threads,
+        max_row_groups,
+        row_group_order,
+        options,
+    )?
+    else {
         return Ok(()); // no data shrug
     };
-    let schema = Arc::clone(first_iter.schema());
-
-    // Compute the parquet schema
-    let writer_properties = WriterProperties::builder()
-        .set_compression(parquet_compression)
-        .build();
-    let writer_properties = Arc::new(writer_properties);
-    let parquet_schema = Arc::new(
-        ArrowSchemaConverter::new()
-            .with_coerce_types(writer_properties.coerce_types())
-            .convert(&schema)
-            .unwrap(),
-    );
 
-    // create a stream that computes the data for each row group
-    let mut row_group_stream = futures::stream::iter(iter_iter)
-        .map(async |iter| {
-            let parquet_schema = Arc::clone(&parquet_schema);
-            let writer_properties = Arc::clone(&writer_properties);
-            let schema = Arc::clone(&schema);
-            // run on a separate thread
-            tokio::task::spawn(async move {
-                encode_row_group(parquet_schema, writer_properties, schema, iter)
-            })
-            .await
-            .expect("Inner task panicked")
-        })
-        .buffered(num_threads); // generate row groups in parallel
-
-    let mut statistics = WriteStatistics::new("row groups");
+    let mut statistics =
+        WriteStatistics::new("row groups").with_callbacks(write_callbacks(progress.as_ref()));
 
     // A blocking task that writes the row groups to the file
     // done in a blocking task to avoid having a thread waiting on IO
     // Now, read each completed row group and write it to the file
     let root_schema = parquet_schema.root_schema_ptr();
     let writer_properties_captured = Arc::clone(&writer_properties);
-    let (tx, mut rx): (
-        Sender<Vec<ArrowColumnChunk>>,
-        Receiver<Vec<ArrowColumnChunk>>,
-    ) = tokio::sync::mpsc::channel(num_threads);
+    let (tx, mut rx): (Sender<IndexedRowGroup>, Receiver<IndexedRowGroup>) =
+        tokio::sync::mpsc::channel(num_threads);
     let writer_task = tokio::task::spawn_blocking(move || {
         // Create parquet writer
         let mut writer =
             SerializedFileWriter::new(writer, root_schema, writer_properties_captured).unwrap();
 
-        while let Some(chunks) = rx.blocking_recv() {
+        while let Some((_, chunks, _permit)) = rx.blocking_recv() {
             // Start row group
             let mut row_group_writer = writer.next_row_group().unwrap();
 
@@ -99,8 +367,19 @@ where
             for chunk in chunks {
                 chunk.append_to_row_group(&mut row_group_writer).unwrap();
             }
-            row_group_writer.close().unwrap();
+            let row_group_metadata = row_group_writer.close().unwrap();
+            statistics.row_group_closed(
+                row_group_metadata.num_rows() as u64,
+                row_group_metadata.compressed_size() as u64,
+                row_group_metadata.total_byte_size() as u64,
+            );
             statistics.increment_chunks(1);
+            if let Some(progress) = progress.as_mut() {
+                progress.part_complete(
+                    row_group_metadata.num_rows() as u64,
+                    row_group_metadata.compressed_size() as u64,
+                );
+            }
         }
         let size = writer.into_inner()?.into_size()?;
         statistics.increment_bytes(size);
@@ -108,9 +387,9 @@ where
     });
 
     // now, drive the input stream and send results to the writer task
-    while let Some(chunks) = row_group_stream.next().await {
+    while let Some(row_group) = row_group_stream.next().await {
         // send the chunks to the writer task
-        if let Err(e) = tx.send(chunks).await {
+        if let Err(e) = tx.send(row_group).await {
             debug!("Error sending chunks to writer: {e}");
             break; // stop early
         }
@@ -124,16 +403,645 @@ where
     Ok(())
 }
 
+/// Converts a set of RecordBatchIterators into a Parquet file written to an
+/// [`AsyncFileWriter`], such as a [`ParquetObjectWriter`] for remote storage
+///
+/// Like [`generate_parquet`], except that the bytes of each row group are
+/// written (e.g. uploaded) as soon as it is encoded, while later row groups
+/// are still being encoded.
+///
+/// [`ParquetObjectWriter`]: parquet::arrow::async_writer::ParquetObjectWriter
+#[cfg(feature = "object_store")]
+pub async fn generate_parquet_async<W, I>(
+    writer: W,
+    iter_iter: I,
+    num_threads: usize,
+    max_row_groups: Option<usize>,
+    row_group_order: RowGroupOrder,
+    options: &ParquetOptions,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    W: AsyncFileWriter,
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+{
+    debug!(
+        "Generating Parquet asynchronously with {num_threads} threads, using {} compression",
+        options.compression()
+    );
+    let Some(RowGroupStream {
+        mut row_group_stream,
+        parquet_schema,
+        writer_properties,
+    }) = RowGroupStream::try_new(
+        iter_iter,
+        num_threads,
+        max_row_groups,
+        row_group_order,
+        options,
+    )?
+    else {
+        return Ok(()); // no data shrug
+    };
+
+    let mut statistics =
+        WriteStatistics::new("row groups").with_callbacks(write_callbacks(progress.as_ref()));
+
+    let mut file = AsyncParquetFile::try_new(writer, &parquet_schema, writer_properties)?;
+    while let Some((_, chunks, _permit)) = row_group_stream.next().await {
+        let row_group_metadata = file.write_row_group(chunks).await?;
+        statistics.row_group_closed(
+            row_group_metadata.num_rows() as u64,
+            row_group_metadata.compressed_size() as u64,
+            row_group_metadata.total_byte_size() as u64,
+        );
+        statistics.increment_chunks(1);
+        if let Some(progress) = progress.as_mut() {
+            progress.part_complete(
+                row_group_metadata.num_rows() as u64,
+                row_group_metadata.compressed_size() as u64,
+            );
+        }
+    }
+    statistics.increment_bytes(file.close().await?);
+    Ok(())
+}
+
+/// Converts a set of RecordBatchIterators into Parquet files written to
+/// [`AsyncFileWriter`]s, each containing exactly one row group
+///
+/// Like [`generate_parquet_files`], except that `new_writer` creates an
+/// [`AsyncFileWriter`], e.g. a [`ParquetObjectWriter`] that uploads the file
+/// while it is written.
+///
+/// [`ParquetObjectWriter`]: parquet::arrow::async_writer::ParquetObjectWriter
+#[cfg(feature = "object_store")]
+pub async fn generate_parquet_files_async<W, I, F>(
+    mut new_writer: F,
+    iter_iter: I,
+    num_threads: usize,
+    max_row_groups: Option<usize>,
+    row_group_order: RowGroupOrder,
+    options: &ParquetOptions,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    W: AsyncFileWriter,
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+    F: FnMut(usize) -> Result<W, io::Error>,
+{
+    debug!(
+        "Generating one Parquet file per row group asynchronously with {num_threads} threads, using {} compression",
+        options.compression()
+    );
+    let Some(RowGroupStream {
+        mut row_group_stream,
+        parquet_schema,
+        writer_properties,
+    }) = RowGroupStream::try_new(
+        iter_iter,
+        num_threads,
+        max_row_groups,
+        row_group_order,
+        options,
+    )?
+    else {
+        return Ok(()); // no data shrug
+    };
+
+    let mut statistics =
+        WriteStatistics::new("files").with_callbacks(write_callbacks(progress.as_ref()));
+
+    while let Some((index, chunks, _permit)) = row_group_stream.next().await {
+        let mut file = AsyncParquetFile::try_new(
+            new_writer(index)?,
+            &parquet_schema,
+            Arc::clone(&writer_properties),
+        )?;
+        let row_group_metadata = file.write_row_group(chunks).await?;
+        statistics.row_group_closed(
+            row_group_metadata.num_rows() as u64,
+            row_group_metadata.compressed_size() as u64,
+            row_group_metadata.total_byte_size() as u64,
+        );
+        let size = file.close().await?;
+        statistics.increment_bytes(size);
+        statistics.increment_chunks(1);
+        if let Some(progress) = progress.as_mut() {
+            progress.part_complete(row_group_metadata.num_rows() as u64, size as u64);
+        }
+    }
+    Ok(())
+}
+
+/// Converts a set of RecordBatchIterators into Parquet files of at most
+/// about `max_file_size` bytes each, written to [`AsyncFileWriter`]s
+///
+/// Like [`generate_parquet_rolling`], except that `new_writer` creates an
+/// [`AsyncFileWriter`], e.g. a [`ParquetObjectWriter`] that uploads the file
+/// while it is written.
+///
+/// [`ParquetObjectWriter`]: parquet::arrow::async_writer::ParquetObjectWriter
+#[cfg(feature = "object_store")]
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_parquet_rolling_async<W, I, F>(
+    mut new_writer: F,
+    max_file_size: u64,
+    iter_iter: I,
+    num_threads: usize,
+    max_row_groups: Option<usize>,
+    row_group_order: RowGroupOrder,
+    options: &ParquetOptions,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    W: AsyncFileWriter,
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+    F: FnMut(usize) -> Result<W, io::Error>,
+{
+    debug!(
+        "Generating Parquet files of at most {max_file_size} bytes asynchronously with {num_threads} threads, using {} compression",
+        options.compression()
+    );
+    let Some(RowGroupStream {
+        mut row_group_stream,
+        parquet_schema,
+        writer_properties,
+    }) = RowGroupStream::try_new(
+        iter_iter,
+        num_threads,
+        max_row_groups,
+        row_group_order,
+        options,
+    )?
+    else {
+        return Ok(()); // no data shrug
+    };
+
+    let mut statistics =
+        WriteStatistics::new("files").with_callbacks(write_callbacks(progress.as_ref()));
+
+    let mut file: Option<AsyncParquetFile<W>> = None;
+    let mut num_files = 0;
+    let mut last_row_group_size = 0;
+    while let Some((_, chunks, _permit)) = row_group_stream.next().await {
+        if let Some(full) =
+            file.take_if(|f| f.bytes_written() as u64 + last_row_group_size > max_file_size)
+        {
+            statistics.increment_bytes(full.close().await?);
+            statistics.increment_chunks(1);
+        }
+        let file = match file.as_mut() {
+            Some(file) => file,
+            None => {
+                let writer = new_writer(num_files)?;
+                num_files += 1;
+                file.insert(AsyncParquetFile::try_new(
+                    writer,
+                    &parquet_schema,
+                    Arc::clone(&writer_properties),
+                )?)
+            }
+        };
+        let row_group_metadata = file.write_row_group(chunks).await?;
+        last_row_group_size = row_group_metadata.compressed_size() as u64;
+        statistics.row_group_closed(
+            row_group_metadata.num_rows() as u64,
+            row_group_metadata.compressed_size() as u64,
+            row_group_metadata.total_byte_size() as u64,
+        );
+        if let Some(progress) = progress.as_mut() {
+            progress.part_complete(
+                row_group_metadata.num_rows() as u64,
+                row_group_metadata.compressed_size() as u64,
+            );
+        }
+    }
+    if let Some(file) = file {
+        statistics.increment_bytes(file.close().await?);
+        statistics.increment_chunks(1);
+    }
+    Ok(())
+}
+
+/// A Parquet file written to an [`AsyncFileWriter`]
+///
+/// The file is encoded into an in memory buffer, whose contents are written
+/// out after each row group, so at most one row group of the file is in
+/// memory.
+#[cfg(feature = "object_store")]
+struct AsyncParquetFile<W> {
+    file_writer: SerializedFileWriter<SharedBuffer>,
+    buffer: SharedBuffer,
+    writer: W,
+}
+
+#[cfg(feature = "object_store")]
+impl<W: AsyncFileWriter> AsyncParquetFile<W> {
+    fn try_new(
+        writer: W,
+        parquet_schema: &SchemaDescPtr,
+        writer_properties: Arc<WriterProperties>,
+    ) -> io::Result<Self> {
+        let buffer = SharedBuffer::default();
+        let file_writer = SerializedFileWriter::new(
+            buffer.clone(),
+            parquet_schema.root_schema_ptr(),
+            writer_properties,
+        )
+        .map_err(io::Error::other)?;
+        Ok(Self {
+            file_writer,
+            buffer,
+            writer,
+        })
+    }
+
+    /// Number of bytes of the file written so far
+    fn bytes_written(&self) -> usize {
+        self.file_writer.bytes_written()
+    }
+
+    /// Appends a row group of `chunks` and writes out its bytes
+    async fn write_row_group(
+        &mut self,
+        chunks: Vec<ArrowColumnChunk>,
+    ) -> io::Result<RowGroupMetaDataPtr> {
+        let mut row_group_writer = self
+            .file_writer
+            .next_row_group()
+            .map_err(io::Error::other)?;
+        for chunk in chunks {
+            chunk
+                .append_to_row_group(&mut row_group_writer)
+                .map_err(io::Error::other)?;
+        }
+        let row_group_metadata = row_group_writer.close().map_err(io::Error::other)?;
+        let bytes = self.buffer.take();
+        self.writer.write(bytes).await.map_err(io::Error::other)?;
+        Ok(row_group_metadata)
+    }
+
+    /// Writes the footer and completes the file, returning its size
+    async fn close(mut self) -> io::Result<usize> {
+        self.file_writer.finish().map_err(io::Error::other)?;
+        let size = self.file_writer.bytes_written();
+        let bytes = self.buffer.take();
+        self.writer.write(bytes).await.map_err(io::Error::other)?;
+        self.writer.complete().await.map_err(io::Error::other)?;
+        Ok(size)
+    }
+}
+
+/// An in memory buffer that can be written to by a [`SerializedFileWriter`]
+/// while its contents are taken by another owner
+#[cfg(feature = "object_store")]
+#[derive(Debug, Clone, Default)]
+struct SharedBuffer {
+    inner: Arc<Mutex<Vec<u8>>>,
+}
+
+#[cfg(feature = "object_store")]
+impl SharedBuffer {
+    /// Remove and return the current contents of the buffer
+    fn take(&self) -> Bytes {
+        Bytes::from(std::mem::take(&mut *self.inner.lock().unwrap()))
+    }
+}
+
+#[cfg(feature = "object_store")]
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Converts a set of RecordBatchIterators into Parquet files, each containing
+/// exactly one row group.
+///
+/// `new_writer` is called with the (0 based) index of the input iterator of
+/// each row group to create the writer for its file, so the contents of each
+/// file do not depend on `row_group_order`.
+///
+/// Uses num_threads to generate the data in parallel, with at most
+/// `max_row_groups` row groups in memory at once, if specified
+pub async fn generate_parquet_files<W, I, F>(
+    mut new_writer: F,
+    iter_iter: I,
+    num_threads: usize,
+    max_row_groups: Option<usize>,
+    row_group_order: RowGroupOrder,
+    options: &ParquetOptions,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    W: Write + Send + IntoSize + 'static,
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+    F: FnMut(usize) -> Result<W, io::Error> + Send + 'static,
+{
+    debug!(
+        "Generating one Parquet file per row group with {num_threads} threads, using {} compression",
+        options.compression()
+    );
+    let Some(RowGroupStream {
+        mut row_group_stream,
+        parquet_schema,
+        writer_properties,
+    }) = RowGroupStream::try_new(
+        iter_iter,
+        num_threads,
+        max_row_groups,
+        row_group_order,
+        options,
+    )?
+    else {
+        return Ok(()); // no data shrug
+    };
+
+    let mut statistics =
+        WriteStatistics::new("files").with_callbacks(write_callbacks(progress.as_ref()));
+
+    // A blocking task that writes each row group to a new file
+    let root_schema = parquet_schema.root_schema_ptr();
+    let (tx, mut rx): (Sender<IndexedRowGroup>, Receiver<IndexedRowGroup>) =
+        tokio::sync::mpsc::channel(num_threads);
+    let writer_task = tokio::task::spawn_blocking(move || {
+        while let Some((index, chunks, _permit)) = rx.blocking_recv() {
+            let mut writer = SerializedFileWriter::new(
+                new_writer(index)?,
+                Arc::clone(&root_schema),
+                Arc::clone(&writer_properties),
+            )
+            .unwrap();
+            let mut row_group_writer = writer.next_row_group().unwrap();
+            for chunk in chunks {
+                chunk.append_to_row_group(&mut row_group_writer).unwrap();
+            }
+            let row_group_metadata = row_group_writer.close().unwrap();
+            statistics.row_group_closed(
+                row_group_metadata.num_rows() as u64,
+                row_group_metadata.compressed_size() as u64,
+                row_group_metadata.total_byte_size() as u64,
+            );
+            let size = writer.into_inner()?.into_size()?;
+            statistics.increment_bytes(size);
+            statistics.increment_chunks(1);
+            if let Some(progress) = progress.as_mut() {
+                progress.part_complete(row_group_metadata.num_rows() as u64, size as u64);
+            }
+        }
+        Ok(()) as Result<(), io::Error>
+    });
+
+    // now, drive the input stream and send results to the writer task
+    while let Some(row_group) = row_group_stream.next().await {
+        if let Err(e) = tx.send(row_group).await {
+            debug!("Error sending chunks to writer: {e}");
+            break; // stop early
+        }
+    }
+    // signal the writer task that we are done
+    drop(tx);
+
+    // Wait for the writer task to finish
+    writer_task.await??;
+
+    Ok(())
+}
+
+/// Converts a set of RecordBatchIterators into Parquet files of at most
+/// about `max_file_size` bytes each.
+///
+/// The row groups are written to a file until the next row group would make
+/// it larger than `max_file_size`, judging by the size of the previous row
+/// group, and then to a new file created by `new_writer` with its (0 based)
+/// index. Each file has at least one row group, so a row group larger than
+/// `max_file_size` is written to its own file.
+///
+/// Uses num_threads to generate the data in parallel, with at most
+/// `max_row_groups` row groups in memory at once, if specified
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_parquet_rolling<W, I, F>(
+    mut new_writer: F,
+    max_file_size: u64,
+    iter_iter: I,
+    num_threads: usize,
+    max_row_groups: Option<usize>,
+    row_group_order: RowGroupOrder,
+    options: &ParquetOptions,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    W: Write + Send + IntoSize + 'static,
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+    F: FnMut(usize) -> Result<W, io::Error> + Send + 'static,
+{
+    debug!(
+        "Generating Parquet files of at most {max_file_size} bytes with {num_threads} threads, using {} compression",
+        options.compression()
+    );
+    let Some(RowGroupStream {
+        mut row_group_stream,
+        parquet_schema,
+        writer_properties,
+    }) = RowGroupStream::try_new(
+        iter_iter,
+        num_threads,
+        max_row_groups,
+        row_group_order,
+        options,
+    )?
+    else {
+        return Ok(()); // no data shrug
+    };
+
+    let mut statistics =
+        WriteStatistics::new("files").with_callbacks(write_callbacks(progress.as_ref()));
+
+    // A blocking task that writes the row groups, rolling over to a new file
+    // when the current one is full
+    let root_schema = parquet_schema.root_schema_ptr();
+    let (tx, mut rx): (Sender<IndexedRowGroup>, Receiver<IndexedRowGroup>) =
+        tokio::sync::mpsc::channel(num_threads);
+    let writer_task = tokio::task::spawn_blocking(move || {
+        let mut writer: Option<SerializedFileWriter<W>> = None;
+        let mut num_files = 0;
+        let mut last_row_group_size = 0;
+        while let Some((_, chunks, _permit)) = rx.blocking_recv() {
+            if let Some(full) =
+                writer.take_if(|w| w.bytes_written() as u64 + last_row_group_size > max_file_size)
+            {
+                let size = full.into_inner()?.into_size()?;
+                statistics.increment_bytes(size);
+                statistics.increment_chunks(1);
+            }
+            let writer = match writer.as_mut() {
+                Some(writer) => writer,
+                None => {
+                    let file = new_writer(num_files)?;
+                    num_files += 1;
+                    writer.insert(
+                        SerializedFileWriter::new(
+                            file,
+                            Arc::clone(&root_schema),
+                            Arc::clone(&writer_properties),
+                        )
+                        .unwrap(),
+                    )
+                }
+            };
+            let mut row_group_writer = writer.next_row_group().unwrap();
+            for chunk in chunks {
+                chunk.append_to_row_group(&mut row_group_writer).unwrap();
+            }
+            let row_group_metadata = row_group_writer.close().unwrap();
+            last_row_group_size = row_group_metadata.compressed_size() as u64;
+            statistics.row_group_closed(
+                row_group_metadata.num_rows() as u64,
+                row_group_metadata.compressed_size() as u64,
+                row_group_metadata.total_byte_size() as u64,
+            );
+            if let Some(progress) = progress.as_mut() {
+                progress.part_complete(
+                    row_group_metadata.num_rows() as u64,
+                    row_group_metadata.compressed_size() as u64,
+                );
+            }
+        }
+        if let Some(writer) = writer {
+            let size = writer.into_inner()?.into_size()?;
+            statistics.increment_bytes(size);
+            statistics.increment_chunks(1);
+        }
+        Ok(()) as Result<(), io::Error>
+    });
+
+    // now, drive the input stream and send results to the writer task
+    while let Some(row_group) = row_group_stream.next().await {
+        if let Err(e) = tx.send(row_group).await {
+            debug!("Error sending chunks to writer: {e}");
+            break; // stop early
+        }
+    }
+    // signal the writer task that we are done
+    drop(tx);
+
+    // Wait for the writer task to finish
+    writer_task.await??;
+
+    Ok(())
+}
+
+/// An encoded row group, the index of the input it was encoded from, and
+/// its permit to be in memory, which is released once it is written
+type IndexedRowGroup = (usize, Vec<ArrowColumnChunk>, OwnedSemaphorePermit);
+
+/// A stream of encoded row groups, one for each input [`RecordBatchIterator`],
+/// in the order of the input unless [`RowGroupOrder::Any`] is used.
+///
+/// With `max_row_groups`, at most that many row groups are encoded, waiting
+/// to be written or being written at once: the next row group is only
+/// started once one of them is written, which bounds the memory used.
+struct RowGroupStream<S> {
+    row_group_stream: S,
+    parquet_schema: SchemaDescPtr,
+    writer_properties: Arc<WriterProperties>,
+}
+
+impl RowGroupStream<()> {
+    /// Returns `None` if there are no input iterators, and an error if
+    /// `options` can not be applied to their columns
+    fn try_new<I>(
+        iter_iter: I,
+        num_threads: usize,
+        max_row_groups: Option<usize>,
+        row_group_order: RowGroupOrder,
+        options: &ParquetOptions,
+    ) -> io::Result<Option<RowGroupStream<impl Stream<Item = IndexedRowGroup>>>>
+    where
+        I: Iterator<Item: RecordBatchIterator> + 'static,
+    {
+        // Based on example in https://docs.rs/parquet/latest/parquet/arrow/arrow_writer/struct.ArrowColumnWriter.html
+        let mut iter_iter = iter_iter.peekable();
+
+        // get schema from the first iterator
+        let Some(first_iter) = iter_iter.peek() else {
+            return Ok(None);
+        };
+        let schema = Arc::clone(first_iter.schema());
+
+        // Compute the parquet schema
+        let (parquet_schema, writer_properties) = options.parquet_schema(&schema)?;
+
+        // create a stream that computes the data for each row group
+        let captured_parquet_schema = Arc::clone(&parquet_schema);
+        let captured_writer_properties = Arc::clone(&writer_properties);
+        let column_threads = options.column_threads();
+        // the permits are acquired in input order, so that the row groups
+        // that are written next never wait for the ones after them
+        let permits = max_row_groups.unwrap_or(Semaphore::MAX_PERMITS);
+        let permits = Arc::new(Semaphore::new(permits.clamp(1, Semaphore::MAX_PERMITS)));
+        let row_group_stream = futures::stream::iter(iter_iter.enumerate())
+            .then(move |(index, iter)| {
+                let permits = Arc::clone(&permits);
+                Box::pin(async move {
+                    let permit = permits.acquire_owned().await.expect("semaphore closed");
+                    (index, iter, permit)
+                })
+            })
+            .map(move |(index, iter, permit)| {
+                let parquet_schema = Arc::clone(&captured_parquet_schema);
+                let writer_properties = Arc::clone(&captured_writer_properties);
+                let schema = Arc::clone(&schema);
+                // run on a separate thread
+                async move {
+                    let chunks = tokio::task::spawn(async move {
+                        encode_row_group(
+                            parquet_schema,
+                            writer_properties,
+                            schema,
+                            column_threads,
+                            iter,
+                        )
+                    })
+                    .await
+                    .expect("Inner task panicked");
+                    (index, chunks, permit)
+                }
+            });
+        // generate row groups in parallel
+        let row_group_stream = match row_group_order {
+            RowGroupOrder::Strict => row_group_stream.buffered(num_threads).left_stream(),
+            RowGroupOrder::Any => row_group_stream
+                .buffer_unordered(num_threads)
+                .right_stream(),
+        };
+
+        Ok(Some(RowGroupStream {
+            row_group_stream,
+            parquet_schema,
+            writer_properties,
+        }))
+    }
+}
+
 /// Creates the data for a particular row group
 ///
-/// Note at the moment it does not use multiple tasks/threads but it could
-/// potentially encode multiple columns with different threads .
+/// With `column_threads` greater than 1, the leaf columns are encoded by that
+/// many threads while the batches are generated, see
+/// [`encode_columns_in_parallel`].
 ///
 /// Returns an array of [`ArrowColumnChunk`]
-fn encode_row_group<I>(
+pub(crate) fn encode_row_group<I>(
     parquet_schema: SchemaDescPtr,
     writer_properties: Arc<WriterProperties>,
     schema: SchemaRef,
+    column_threads: usize,
     iter: I,
 ) -> Vec<ArrowColumnChunk>
 where
@@ -141,17 +1049,16 @@ where
 {
     // Create writers for each of the leaf columns
     let mut col_writers = get_column_writers(&parquet_schema, &writer_properties, &schema).unwrap();
+    if column_threads > 1 && col_writers.len() > 1 {
+        return encode_columns_in_parallel(col_writers, &schema, column_threads, iter);
+    }
 
-    // generate the data and send it to the tasks (via the sender channels)
     for batch in iter {
-        let columns = batch.columns().iter();
-        let col_writers = col_writers.iter_mut();
-        let fields = schema.fields().iter();
-
-        for ((col_writer, field), arr) in col_writers.zip(fields).zip(columns) {
-            for leaves in compute_leaves(field.as_ref(), arr).unwrap() {
-                col_writer.write(&leaves).unwrap();
-            }
+        // there is a writer per leaf column, e.g. several for a nested column
+        let mut col_writers = col_writers.iter_mut();
+        for leaves in batch_leaves(&schema, &batch) {
+            let col_writer = col_writers.next().expect("writer of leaf column");
+            col_writer.write(&leaves).unwrap();
         }
     }
     // finish the writers and create the column chunks
@@ -160,3 +1067,417 @@ where
         .map(|col_writer| col_writer.close().unwrap())
         .collect()
 }
+
+/// Encodes the leaf columns of `col_writers` on `num_threads` threads
+///
+/// Thread `i` encodes the leaf columns `i`, `i + num_threads`, etc. of each
+/// batch, which are sent to it as soon as the batch is generated, so the
+/// columns of wide tables or expensive codecs such as zstd are encoded in
+/// parallel with each other and with the generation of the next batch.
+fn encode_columns_in_parallel<I>(
+    col_writers: Vec<ArrowColumnWriter>,
+    schema: &SchemaRef,
+    num_threads: usize,
+    iter: I,
+) -> Vec<ArrowColumnChunk>
+where
+    I: RecordBatchIterator,
+{
+    let num_columns = col_writers.len();
+    let num_threads = num_threads.min(num_columns);
+    let mut thread_writers: Vec<Vec<_>> = (0..num_threads).map(|_| Vec::new()).collect();
+    for (index, col_writer) in col_writers.into_iter().enumerate() {
+        thread_writers[index % num_threads].push(col_writer);
+    }
+    std::thread::scope(|scope| {
+        let (senders, threads): (Vec<_>, Vec<_>) = thread_writers
+            .into_iter()
+            .map(|mut col_writers| {
+                // bound the leaves of batches waiting to be encoded
+                let (tx, rx) = std::sync::mpsc::sync_channel::<Vec<ArrowLeafColumn>>(2);
+                let thread = scope.spawn(move || {
+                    for leaves in rx {
+                        for (col_writer, leaves) in col_writers.iter_mut().zip(leaves) {
+                            col_writer.write(&leaves).unwrap();
+                        }
+                    }
+                    col_writers
+                        .into_iter()
+                        .map(|col_writer| col_writer.close().unwrap())
+                        .collect::<Vec<_>>()
+                });
+                (tx, thread)
+            })
+            .unzip();
+
+        for batch in iter {
+            let mut thread_leaves: Vec<Vec<_>> = (0..num_threads).map(|_| Vec::new()).collect();
+            for (index, leaves) in batch_leaves(schema, &batch).enumerate() {
+                thread_leaves[index % num_threads].push(leaves);
+            }
+            for (tx, leaves) in senders.iter().zip(thread_leaves) {
+                // only fails if the thread panicked, which is reported below
+                if tx.send(leaves).is_err() {
+                    break;
+                }
+            }
+        }
+        drop(senders);
+
+        // the column chunks in the order of the leaf columns
+        let mut thread_chunks: Vec<_> = threads
+            .into_iter()
+            .map(|thread| thread.join().expect("column encoding panicked").into_iter())
+            .collect();
+        (0..num_columns)
+            .map(|index| {
+                thread_chunks[index % num_threads]
+                    .next()
+                    .expect("chunk of leaf column")
+            })
+            .collect()
+    })
+}
+
+/// Returns the leaf columns of `batch`, e.g. several for a nested column
+fn batch_leaves<'a>(
+    schema: &'a SchemaRef,
+    batch: &'a RecordBatch,
+) -> impl Iterator<Item = ArrowLeafColumn> + 'a {
+    schema
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .flat_map(|(field, column)| compute_leaves(field.as_ref(), column).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::metadata::ParquetMetaDataReader;
+    use std::sync::Mutex;
+    use tpchgen::generators::LineItemGenerator;
+    use tpchgen_arrow::LineItemArrow;
+
+    /// An in memory file whose contents can be read after it is written
+    #[derive(Debug, Clone, Default)]
+    struct TestFile(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for TestFile {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl IntoSize for TestFile {
+        fn into_size(self) -> Result<usize, io::Error> {
+            Ok(self.0.lock().unwrap().len())
+        }
+    }
+
+    /// Writes lineitem at SF 0.01 in 16 row groups and returns the file
+    async fn write_lineitem(
+        num_threads: usize,
+        max_row_groups: Option<usize>,
+        row_group_order: RowGroupOrder,
+    ) -> Vec<u8> {
+        let num_parts = 16;
+        let sources = (1..=num_parts)
+            .map(move |part| LineItemArrow::new(LineItemGenerator::new(0.01, part, num_parts)));
+        let file = TestFile::default();
+        generate_parquet(
+            file.clone(),
+            sources,
+            num_threads,
+            max_row_groups,
+            row_group_order,
+            &ParquetOptions::new(Compression::SNAPPY),
+            None,
+        )
+        .await
+        .unwrap();
+        let bytes = file.0.lock().unwrap().clone();
+        bytes
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn rolls_over_to_new_files() {
+        let single_file = write_lineitem(4, None, RowGroupOrder::Strict).await;
+        let max_file_size = single_file.len() as u64 / 4;
+        let num_parts = 16;
+        let sources = (1..=num_parts)
+            .map(move |part| LineItemArrow::new(LineItemGenerator::new(0.01, part, num_parts)));
+        let files: Arc<Mutex<Vec<TestFile>>> = Arc::default();
+        let captured_files = Arc::clone(&files);
+        let new_writer = move |index: usize| {
+            let mut files = captured_files.lock().unwrap();
+            assert_eq!(index, files.len());
+            files.push(TestFile::default());
+            Ok(files[index].clone())
+        };
+        generate_parquet_rolling(
+            new_writer,
+            max_file_size,
+            sources,
+            4,
+            None,
+            RowGroupOrder::Strict,
+            &ParquetOptions::new(Compression::SNAPPY),
+            None,
+        )
+        .await
+        .unwrap();
+        let files = files.lock().unwrap();
+        // the 16 row groups are of about the same size
+        assert!((4..=5).contains(&files.len()), "{} files", files.len());
+        let mut num_rows = 0;
+        for file in files.iter() {
+            let bytes = file.0.lock().unwrap();
+            assert!(bytes.len() as u64 <= max_file_size * 11 / 10);
+            // the metadata is followed by its length and the magic `PAR1`
+            let footer = bytes[bytes.len() - 8..].try_into().unwrap();
+            let tail = ParquetMetaDataReader::decode_footer_tail(footer).unwrap();
+            let metadata_start = bytes.len() - 8 - tail.metadata_length();
+            let metadata =
+                ParquetMetaDataReader::decode_metadata(&bytes[metadata_start..bytes.len() - 8])
+                    .unwrap();
+            num_rows += metadata.file_metadata().num_rows();
+        }
+        assert_eq!(num_rows, 60175);
+    }
+
+    #[cfg(feature = "object_store")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn writes_files_to_object_store() {
+        use object_store::memory::InMemory;
+        use object_store::path::Path;
+        use object_store::ObjectStore;
+        use parquet::arrow::async_writer::ParquetObjectWriter;
+
+        let store = Arc::new(InMemory::new());
+        let new_sources =
+            || (1..=16).map(move |part| LineItemArrow::new(LineItemGenerator::new(0.01, part, 16)));
+        let new_writer = |name: String| {
+            let store: Arc<dyn ObjectStore> = Arc::clone(&store) as _;
+            Ok(ParquetObjectWriter::new(store, Path::from(name)))
+        };
+        generate_parquet_files_async(
+            |index| new_writer(format!("files/lineitem_{index:04}.parquet")),
+            new_sources(),
+            4,
+            None,
+            RowGroupOrder::Any,
+            &ParquetOptions::new(Compression::SNAPPY),
+            None,
+        )
+        .await
+        .unwrap();
+        let single_file = write_lineitem(4, None, RowGroupOrder::Strict).await;
+        let max_file_size = single_file.len() as u64 / 4;
+        generate_parquet_rolling_async(
+            |index| new_writer(format!("rolling/lineitem_{index:04}.parquet")),
+            max_file_size,
+            new_sources(),
+            4,
+            None,
+            RowGroupOrder::Strict,
+            &ParquetOptions::new(Compression::SNAPPY),
+            None,
+        )
+        .await
+        .unwrap();
+
+        for (prefix, num_files) in [("files", 16..=16), ("rolling", 4..=5)] {
+            let objects = store.list_with_delimiter(Some(&Path::from(prefix))).await;
+            let objects = objects.unwrap().objects;
+            assert!(num_files.contains(&objects.len()), "{prefix}");
+            let mut num_rows = 0;
+            for object in objects {
+                let bytes = store.get(&object.location).await.unwrap().bytes().await;
+                let bytes = bytes.unwrap();
+                let footer = bytes[bytes.len() - 8..].try_into().unwrap();
+                let tail = ParquetMetaDataReader::decode_footer_tail(footer).unwrap();
+                let metadata_start = bytes.len() - 8 - tail.metadata_length();
+                let metadata =
+                    ParquetMetaDataReader::decode_metadata(&bytes[metadata_start..bytes.len() - 8])
+                        .unwrap();
+                num_rows += metadata.file_metadata().num_rows();
+            }
+            assert_eq!(num_rows, 60175, "{prefix}");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn row_group_order_independent_of_num_threads() {
+        let expected = write_lineitem(1, None, RowGroupOrder::Strict).await;
+        for num_threads in [2, 4, 16] {
+            let actual = write_lineitem(num_threads, None, RowGroupOrder::Strict).await;
+            assert!(
+                actual == expected,
+                "output with {num_threads} threads differs from output with 1 thread"
+            );
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn max_row_groups_does_not_change_output() {
+        let expected = write_lineitem(4, None, RowGroupOrder::Strict).await;
+        for max_row_groups in [1, 2, 8] {
+            let actual = write_lineitem(4, Some(max_row_groups), RowGroupOrder::Strict).await;
+            assert!(
+                actual == expected,
+                "output with at most {max_row_groups} row groups in memory differs"
+            );
+        }
+        // with one row group at a time, they are written in order anyway
+        let any = write_lineitem(4, Some(1), RowGroupOrder::Any).await;
+        assert!(any == expected);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn writes_nested_columns() {
+        use crate::documents::OrderDocumentArrow;
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+        use tpchgen::generators::OrderGenerator;
+
+        let num_parts = 4;
+        let new_sources = move || {
+            (1..=num_parts).map(move |part| {
+                OrderDocumentArrow::new(
+                    OrderGenerator::new(0.01, part, num_parts),
+                    LineItemGenerator::new(0.01, part, num_parts),
+                )
+            })
+        };
+        let file = TestFile::default();
+        generate_parquet(
+            file.clone(),
+            new_sources(),
+            4,
+            None,
+            RowGroupOrder::Strict,
+            &ParquetOptions::new(Compression::SNAPPY),
+            None,
+        )
+        .await
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("tpchgen-nested-{}", std::process::id()));
+        std::fs::write(&path, file.0.lock().unwrap().as_slice()).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let actual: Vec<_> = reader.map(|batch| batch.unwrap()).collect();
+        std::fs::remove_file(path).unwrap();
+        let expected: Vec<_> = new_sources().flatten().collect();
+        // the strings are read back as Utf8 rather than Utf8View
+        let format = |batches: &[RecordBatch]| {
+            arrow::util::pretty::pretty_format_batches(batches)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(format(&actual), format(&expected));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn column_threads_do_not_change_output() {
+        let write = |column_threads| async move {
+            let options =
+                ParquetOptions::new(Compression::SNAPPY).with_column_threads(column_threads);
+            let sources =
+                (1..=4).map(move |part| LineItemArrow::new(LineItemGenerator::new(0.01, part, 4)));
+            let file = TestFile::default();
+            generate_parquet(
+                file.clone(),
+                sources,
+                4,
+                None,
+                RowGroupOrder::Strict,
+                &options,
+                None,
+            )
+            .await
+            .unwrap();
+            let bytes = file.0.lock().unwrap().clone();
+            bytes
+        };
+        let expected = write(1).await;
+        for column_threads in [2, 5, 32] {
+            let actual = write(column_threads).await;
+            assert!(
+                actual == expected,
+                "output with {column_threads} column threads differs"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn applies_column_options() {
+        let options = ParquetOptions::new(Compression::SNAPPY)
+            .with_writer_version(ParquetWriterVersion::V2)
+            .with_dictionary(vec![
+                "off".parse().unwrap(),
+                "l_comment=on".parse().unwrap(),
+            ])
+            .with_encodings(vec!["delta-binary-packed".parse().unwrap()]);
+        let sources = [LineItemArrow::new(LineItemGenerator::new(0.001, 1, 1))].into_iter();
+        let file = TestFile::default();
+        generate_parquet(
+            file.clone(),
+            sources,
+            1,
+            None,
+            RowGroupOrder::Strict,
+            &options,
+            None,
+        )
+        .await
+        .unwrap();
+        let bytes = file.0.lock().unwrap();
+        let footer = bytes[bytes.len() - 8..].try_into().unwrap();
+        let tail = ParquetMetaDataReader::decode_footer_tail(footer).unwrap();
+        let metadata_start = bytes.len() - 8 - tail.metadata_length();
+        let metadata =
+            ParquetMetaDataReader::decode_metadata(&bytes[metadata_start..bytes.len() - 8])
+                .unwrap();
+        assert_eq!(metadata.file_metadata().version(), 2);
+        let encodings = |name: &str| {
+            let row_group = metadata.row_group(0);
+            let column = row_group
+                .columns()
+                .iter()
+                .find(|c| c.column_path().string() == name)
+                .unwrap();
+            column.encodings().clone()
+        };
+        // the encoding of all columns only applies to the integer columns
+        assert!(encodings("l_orderkey").contains(&Encoding::DELTA_BINARY_PACKED));
+        assert!(!encodings("l_shipmode").contains(&Encoding::DELTA_BINARY_PACKED));
+        assert!(!encodings("l_shipmode").contains(&Encoding::RLE_DICTIONARY));
+        assert!(encodings("l_comment").contains(&Encoding::RLE_DICTIONARY));
+    }
+
+    #[test]
+    fn rejects_unsupported_column_encoding() {
+        let schema = LineItemArrow::new(LineItemGenerator::new(0.001, 1, 1))
+            .schema()
+            .clone();
+        let options = ParquetOptions::new(Compression::SNAPPY)
+            .with_encodings(vec!["l_comment=delta-binary-packed".parse().unwrap()]);
+        let err = options.parquet_schema(&schema).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--parquet-encoding l_comment=delta-binary-packed does not support the BYTE_ARRAY values of l_comment"
+        );
+        let setting: Result<ColumnSetting<ParquetDictionary>, _> = "l_comment=maybe".parse();
+        assert_eq!(
+            setting.unwrap_err(),
+            "invalid value 'maybe', expected one of on, off"
+        );
+    }
+}
@@ -0,0 +1,357 @@
+//! Hive-style partitioned Parquet output (`--partition-by`)
+//!
+//! `--partition-by o_orderdate` writes `orders` to one directory per year of
+//! `o_orderdate`, and `--partition-by o_orderdate=month` to one directory
+//! per year and month:
+//!
+//! ```text
+//! <output_dir>/orders/o_orderdate_year=1992/o_orderdate_month=01/part-0001.parquet
+//! <output_dir>/orders/o_orderdate_year=1992/o_orderdate_month=01/part-0002.parquet
+//! ...
+//! ```
+//!
+//! Engines that discover Hive partitions, e.g. DuckDB with
+//! `hive_partitioning = true`, Spark or DataFusion, can then skip the files
+//! of partitions that do not match a filter on the date. The files still
+//! contain all the columns of the table, including the date column.
+//!
+//! Each part is generated and split by partition in parallel, and its rows
+//! in each partition are written to a file named after the part, so a table
+//! has up to one file per part and partition.
+
+use crate::checksum::{self, Checksum};
+use crate::manifest::Manifest;
+use crate::parquet::ParquetOptions;
+use crate::progress::{write_callbacks, ProgressReporter};
+use crate::Table;
+use arrow::array::{ArrayRef, AsArray, RecordBatch, UInt32Array};
+use arrow::compute::kernels::temporal::{date_part, DatePart};
+use arrow::compute::take_record_batch;
+use arrow::datatypes::{DataType, Int32Type};
+use futures::StreamExt;
+use log::debug;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::statistics::WriteStatistics;
+
+/// The date granularity of partitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+    Year,
+    Month,
+}
+
+/// A date column to partition a table by, e.g. `o_orderdate=month`
+#[derive(Debug, Clone)]
+pub struct PartitionColumn {
+    column: String,
+    table: Table,
+    /// Index of the column in the batches of the table
+    index: usize,
+    granularity: Granularity,
+}
+
+impl PartitionColumn {
+    /// Returns the table of the column
+    pub fn table(&self) -> Table {
+        self.table
+    }
+
+    /// Returns the directory of the partition of `year` and `month`
+    fn directory(&self, year: i32, month: i32) -> String {
+        let column = &self.column;
+        match self.granularity {
+            Granularity::Year => format!("{column}_year={year}"),
+            Granularity::Month => format!("{column}_year={year}/{column}_month={month:02}"),
+        }
+    }
+}
+
+impl Display for PartitionColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.granularity {
+            Granularity::Year => write!(f, "{}", self.column),
+            Granularity::Month => write!(f, "{}=month", self.column),
+        }
+    }
+}
+
+impl FromStr for PartitionColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (column, granularity) = match s.split_once('=') {
+            None => (s, Granularity::Year),
+            Some((column, "year")) => (column, Granularity::Year),
+            Some((column, "month")) => (column, Granularity::Month),
+            Some((_, other)) => {
+                return Err(format!(
+                    "unknown partition granularity '{other}', expected year or month"
+                ))
+            }
+        };
+        for &table in Table::ALL.iter() {
+            let schema = table.schema();
+            let Ok(index) = schema.index_of(column) else {
+                continue;
+            };
+            if schema.field(index).data_type() != &DataType::Date32 {
+                return Err(format!("column '{column}' is not a date column"));
+            }
+            return Ok(Self {
+                column: column.to_string(),
+                table,
+                index,
+                granularity,
+            });
+        }
+        Err(format!("unknown column '{column}'"))
+    }
+}
+
+/// Generates the parts of a table in parallel and writes the rows of each
+/// part in each partition of `column` to its own Parquet file in `table_dir`
+///
+/// `parts` are the part numbers of the input iterators, of `num_parts`
+/// parts, and name the files. With `checksum`, each file gets a checksum
+/// file, see [`checksum`], and the files are recorded in `manifest`.
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_partitioned_parquet<I>(
+    table_dir: PathBuf,
+    column: PartitionColumn,
+    iter_iter: I,
+    num_parts: i32,
+    parts: &[i32],
+    num_threads: usize,
+    options: &ParquetOptions,
+    checksum: Option<Checksum>,
+    manifest: Option<Manifest>,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+{
+    debug!(
+        "Generating Parquet partitioned by {column} with {num_threads} threads, using {} compression",
+        options.compression()
+    );
+    let mut iter_iter = iter_iter.peekable();
+    let Some(first_iter) = iter_iter.peek() else {
+        return Ok(()); // no data shrug
+    };
+    let (_, writer_properties) = options.parquet_schema(first_iter.schema())?;
+    let writer_properties = Arc::unwrap_or_clone(writer_properties);
+
+    let mut statistics =
+        WriteStatistics::new("files").with_callbacks(write_callbacks(progress.as_ref()));
+
+    // A blocking task that writes the files of each part
+    let (tx, mut rx): (Sender<Vec<PartitionFile>>, Receiver<Vec<PartitionFile>>) =
+        tokio::sync::mpsc::channel(num_threads);
+    let writer_task = tokio::task::spawn_blocking(move || {
+        while let Some(files) = rx.blocking_recv() {
+            let mut rows = 0;
+            let mut bytes = 0;
+            for file in files {
+                let path = table_dir.join(&file.path);
+                fs::create_dir_all(path.parent().expect("partition directory"))?;
+                fs::write(&path, &file.bytes)?;
+                if checksum.is_some() || manifest.is_some() {
+                    let digest = checksum::sha256(&file.bytes);
+                    if let Some(checksum) = checksum {
+                        checksum::write_sidecar(checksum, &path, &digest)?;
+                    }
+                    if let Some(manifest) = &manifest {
+                        let bytes = file.bytes.len() as u64;
+                        manifest.add_file(&path, file.part, file.num_rows, bytes, digest);
+                    }
+                }
+                rows += file.num_rows;
+                bytes += file.bytes.len() as u64;
+                statistics.increment_bytes(file.bytes.len());
+                statistics.increment_chunks(1);
+            }
+            if let Some(progress) = progress.as_mut() {
+                progress.part_complete(rows, bytes);
+            }
+        }
+        Ok(()) as Result<(), io::Error>
+    });
+
+    // split and encode the parts in parallel
+    let width = num_parts.to_string().len().max(4);
+    let parts = parts.to_vec();
+    let mut part_stream = futures::stream::iter(iter_iter.zip(parts))
+        .map(|(iter, part)| {
+            let column = column.clone();
+            let writer_properties = writer_properties.clone();
+            async move {
+                // run on a separate thread
+                tokio::task::spawn(async move {
+                    let file_name = format!("part-{part:0width$}.parquet");
+                    encode_part(&column, iter, part, &file_name, writer_properties)
+                })
+                .await
+                .expect("Inner task panicked")
+            }
+        })
+        .buffered(num_threads);
+
+    let mut result = Ok(());
+    while let Some(files) = part_stream.next().await {
+        let files = match files {
+            Ok(files) => files,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+        // send the files to the writer task
+        if let Err(e) = tx.send(files).await {
+            debug!("Error sending files to writer: {e}");
+            break; // stop early
+        }
+    }
+    // signal the writer task that we are done
+    drop(tx);
+
+    // Wait for the writer task to finish
+    writer_task.await??;
+    result
+}
+
+/// An encoded Parquet file of the rows of one part in one partition
+#[derive(Debug)]
+struct PartitionFile {
+    /// Path relative to the table directory
+    path: PathBuf,
+    /// The (1 based) part of the rows
+    part: i32,
+    bytes: Vec<u8>,
+    num_rows: u64,
+}
+
+/// Splits the rows of one part by partition and encodes the rows of each
+/// partition as a Parquet file named `file_name`
+fn encode_part<I: RecordBatchIterator>(
+    column: &PartitionColumn,
+    iter: I,
+    part: i32,
+    file_name: &str,
+    writer_properties: WriterProperties,
+) -> io::Result<Vec<PartitionFile>> {
+    let schema = iter.schema().clone();
+    let mut files = Vec::new();
+    for ((year, month), batches) in split_part(column, iter)? {
+        let mut writer =
+            ArrowWriter::try_new(Vec::new(), schema.clone(), Some(writer_properties.clone()))?;
+        let mut num_rows = 0;
+        for batch in &batches {
+            writer.write(batch)?;
+            num_rows += batch.num_rows() as u64;
+        }
+        files.push(PartitionFile {
+            path: PathBuf::from(column.directory(year, month)).join(file_name),
+            part,
+            bytes: writer.into_inner()?,
+            num_rows,
+        });
+    }
+    Ok(files)
+}
+
+/// Returns the rows of the batches of one part by partition (year and month,
+/// or 0 when partitioning by year), in generation order
+fn split_part<I: RecordBatchIterator>(
+    column: &PartitionColumn,
+    iter: I,
+) -> io::Result<BTreeMap<(i32, i32), Vec<RecordBatch>>> {
+    let mut partitions: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for batch in iter {
+        let dates = batch.column(column.index);
+        let years = date_part(dates, DatePart::Year).map_err(io::Error::other)?;
+        let months: Option<ArrayRef> = match column.granularity {
+            Granularity::Year => None,
+            Granularity::Month => {
+                Some(date_part(dates, DatePart::Month).map_err(io::Error::other)?)
+            }
+        };
+        let years = years.as_primitive::<Int32Type>();
+        let months = months.as_ref().map(|m| m.as_primitive::<Int32Type>());
+
+        let mut rows: BTreeMap<_, Vec<u32>> = BTreeMap::new();
+        for row in 0..batch.num_rows() {
+            let month = months.map_or(0, |months| months.value(row));
+            rows.entry((years.value(row), month))
+                .or_default()
+                .push(row as u32);
+        }
+        for (key, indices) in rows {
+            let indices = UInt32Array::from(indices);
+            let rows = take_record_batch(&batch, &indices).map_err(io::Error::other)?;
+            partitions.entry(key).or_default().push(rows);
+        }
+    }
+    Ok(partitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tpchgen::generators::OrderGenerator;
+    use tpchgen_arrow::OrderArrow;
+
+    #[test]
+    fn parses_columns() {
+        let column: PartitionColumn = "o_orderdate".parse().unwrap();
+        assert_eq!(column.table(), Table::Orders);
+        assert_eq!(column.directory(1995, 3), "o_orderdate_year=1995");
+        let column: PartitionColumn = "l_shipdate=month".parse().unwrap();
+        assert_eq!(column.table(), Table::Lineitem);
+        assert_eq!(
+            column.directory(1995, 3),
+            "l_shipdate_year=1995/l_shipdate_month=03"
+        );
+        assert_eq!(column.to_string(), "l_shipdate=month");
+        assert!("o_custkey".parse::<PartitionColumn>().is_err());
+        assert!("o_orderdate=day".parse::<PartitionColumn>().is_err());
+    }
+
+    #[test]
+    fn splits_by_month() {
+        let column: PartitionColumn = "o_orderdate=month".parse().unwrap();
+        let orders = || OrderArrow::new(OrderGenerator::new(0.01, 1, 1)).with_batch_size(1000);
+        let partitions = split_part(&column, orders()).unwrap();
+        // orders are placed from 1992-01-01 to 1998-08-02
+        assert_eq!(partitions.len(), 6 * 12 + 8);
+        assert_eq!(partitions.keys().next(), Some(&(1992, 1)));
+        assert_eq!(partitions.keys().last(), Some(&(1998, 8)));
+        let num_rows: usize = partitions.values().flatten().map(|b| b.num_rows()).sum();
+        assert_eq!(num_rows, 15000);
+        // each partition only has rows of its month
+        for ((year, month), batches) in &partitions {
+            for batch in batches {
+                let dates = batch.column(column.index);
+                let years = date_part(dates, DatePart::Year).unwrap();
+                let months = date_part(dates, DatePart::Month).unwrap();
+                assert!(years
+                    .as_primitive::<Int32Type>()
+                    .iter()
+                    .all(|y| y == Some(*year)));
+                assert!(months
+                    .as_primitive::<Int32Type>()
+                    .iter()
+                    .all(|m| m == Some(*month)));
+            }
+        }
+    }
+}
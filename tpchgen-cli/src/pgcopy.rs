@@ -0,0 +1,291 @@
+//! PostgreSQL binary COPY output format (`--format=pg-copy`)
+//!
+//! Each file is in the format of `COPY ... TO ... WITH (FORMAT binary)` and
+//! can be loaded without parsing any text:
+//!
+//! ```sql
+//! COPY lineitem FROM '/data/lineitem.pgcopy' WITH (FORMAT binary);
+//! ```
+//!
+//! The binary representation depends on the column type, so the table must
+//! have the columns of the `CREATE TABLE` statement of
+//! `--format=sql --sql-dialect postgres`: `INT`, `BIGINT`, `DECIMAL(p,s)`,
+//! `DATE` and `VARCHAR`.
+//!
+//! The parts are encoded in parallel and written in part order. The same
+//! encoding is used to load tables with `--postgres-url`.
+//!
+//! See the [documentation](https://www.postgresql.org/docs/current/sql-copy.html#id-1.9.3.55.9.4)
+
+use crate::progress::{write_callbacks, ProgressReporter};
+use arrow::array::{Array, AsArray, LargeStringArray, RecordBatch, StringArray, StringViewArray};
+use arrow::datatypes::{DataType, Date32Type, Decimal128Type, Int32Type, Int64Type};
+use futures::StreamExt;
+use log::debug;
+use std::io;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::generate::Sink;
+use tpchgen_cli::statistics::WriteStatistics;
+
+/// Signature, flags and (empty) header extension
+pub const HEADER: &[u8] = b"PGCOPY\n\xff\r\n\0\0\0\0\0\0\0\0\0";
+
+/// A field count of -1
+pub const TRAILER: &[u8] = &[0xff, 0xff];
+
+/// Days from the Unix epoch to the PostgreSQL epoch, 2000-01-01
+const POSTGRES_EPOCH_DAYS: i32 = 10957;
+
+/// Converts a set of RecordBatchIterators into a binary COPY file written to
+/// `sink`
+///
+/// Uses num_threads to generate and encode the parts in parallel. The
+/// parts are written in the order of the input.
+pub async fn generate_pgcopy<S, I>(
+    sink: S,
+    iter_iter: I,
+    num_threads: usize,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    S: Sink + 'static,
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+{
+    debug!("Generating PostgreSQL binary COPY with {num_threads} threads");
+
+    let mut statistics =
+        WriteStatistics::new("parts").with_callbacks(write_callbacks(progress.as_ref()));
+
+    // A blocking task that writes the tuples of each part to the sink
+    let (tx, mut rx): (Sender<EncodedPart>, Receiver<EncodedPart>) =
+        tokio::sync::mpsc::channel(num_threads);
+    let writer_task = tokio::task::spawn_blocking(move || {
+        let mut sink = sink;
+        sink.sink(HEADER)?;
+        statistics.increment_bytes(HEADER.len());
+        while let Some(part) = rx.blocking_recv() {
+            sink.sink(&part.bytes)?;
+            statistics.increment_bytes(part.bytes.len());
+            statistics.increment_chunks(1);
+            if let Some(progress) = progress.as_mut() {
+                progress.part_complete(part.num_rows, part.bytes.len() as u64);
+            }
+        }
+        sink.sink(TRAILER)?;
+        statistics.increment_bytes(TRAILER.len());
+        sink.flush()
+    });
+
+    // encode the parts in parallel
+    let mut part_stream = futures::stream::iter(iter_iter)
+        .map(async |iter| {
+            // run on a separate thread
+            tokio::task::spawn(async move { encode_part(iter) })
+                .await
+                .expect("Inner task panicked")
+        })
+        .buffered(num_threads);
+
+    let mut result = Ok(());
+    while let Some(part) = part_stream.next().await {
+        let part = match part {
+            Ok(part) => part,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+        // send the part to the writer task
+        if let Err(e) = tx.send(part).await {
+            debug!("Error sending tuples to writer: {e}");
+            break; // stop early
+        }
+    }
+    // signal the writer task that we are done
+    drop(tx);
+
+    // Wait for the writer task to finish
+    writer_task.await??;
+    result
+}
+
+/// The tuples of one part
+#[derive(Debug)]
+pub struct EncodedPart {
+    pub bytes: Vec<u8>,
+    pub num_rows: u64,
+}
+
+/// Encodes the rows of each batch of one part as tuples, without the
+/// [`HEADER`] and [`TRAILER`] of the file
+pub fn encode_part<I: RecordBatchIterator>(iter: I) -> io::Result<EncodedPart> {
+    let mut part = EncodedPart {
+        bytes: Vec::new(),
+        num_rows: 0,
+    };
+    for batch in iter {
+        encode_batch(&mut part.bytes, &batch)?;
+        part.num_rows += batch.num_rows() as u64;
+    }
+    Ok(part)
+}
+
+/// The values of a column of a batch
+enum Column<'a> {
+    Int32(&'a [i32]),
+    Int64(&'a [i64]),
+    Date(&'a [i32]),
+    Decimal(&'a [i128], u32),
+    Utf8View(&'a StringViewArray),
+    Utf8(&'a StringArray),
+    LargeUtf8(&'a LargeStringArray),
+}
+
+/// Appends a tuple for each row of `batch` to `out`
+fn encode_batch(out: &mut Vec<u8>, batch: &RecordBatch) -> io::Result<()> {
+    let mut columns = Vec::with_capacity(batch.num_columns());
+    for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+        if array.null_count() > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "PostgreSQL COPY output does not support null values",
+            ));
+        }
+        let column = match array.data_type() {
+            DataType::Int32 => Column::Int32(array.as_primitive::<Int32Type>().values()),
+            DataType::Int64 => Column::Int64(array.as_primitive::<Int64Type>().values()),
+            DataType::Date32 => Column::Date(array.as_primitive::<Date32Type>().values()),
+            DataType::Decimal128(_, scale) if *scale >= 0 => Column::Decimal(
+                array.as_primitive::<Decimal128Type>().values(),
+                *scale as u32,
+            ),
+            DataType::Utf8View => Column::Utf8View(array.as_string_view()),
+            DataType::Utf8 => Column::Utf8(array.as_string()),
+            DataType::LargeUtf8 => Column::LargeUtf8(array.as_string()),
+            data_type => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "PostgreSQL COPY output does not support column {} of type {data_type}",
+                        field.name()
+                    ),
+                ))
+            }
+        };
+        columns.push(column);
+    }
+    let num_fields = (columns.len() as i16).to_be_bytes();
+    for row in 0..batch.num_rows() {
+        out.extend_from_slice(&num_fields);
+        for column in &columns {
+            match column {
+                Column::Int32(values) => write_field(out, &values[row].to_be_bytes()),
+                Column::Int64(values) => write_field(out, &values[row].to_be_bytes()),
+                Column::Date(values) => {
+                    write_field(out, &(values[row] - POSTGRES_EPOCH_DAYS).to_be_bytes())
+                }
+                Column::Decimal(values, scale) => write_numeric(out, values[row], *scale),
+                Column::Utf8View(array) => write_field(out, array.value(row).as_bytes()),
+                Column::Utf8(array) => write_field(out, array.value(row).as_bytes()),
+                Column::LargeUtf8(array) => write_field(out, array.value(row).as_bytes()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Appends the length of `value` followed by its bytes
+fn write_field(out: &mut Vec<u8>, value: &[u8]) {
+    out.extend_from_slice(&(value.len() as i32).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+/// Appends the decimal with the unscaled `value` and `scale` as a `numeric`
+///
+/// A `numeric` is stored as base 10000 digits, most significant first, and
+/// the weight (power of 10000) of the first digit, without leading or
+/// trailing zero digits.
+fn write_numeric(out: &mut Vec<u8>, value: i128, scale: u32) {
+    let abs = value.unsigned_abs();
+    let mut integer = abs / 10u128.pow(scale);
+    // the fractional digits, padded to a multiple of 4 digits
+    let frac_digits = scale.div_ceil(4) * 4;
+    let mut fraction = abs % 10u128.pow(scale) * 10u128.pow(frac_digits - scale);
+
+    let mut digits = Vec::new();
+    while integer > 0 {
+        digits.push((integer % 10000) as i16);
+        integer /= 10000;
+    }
+    let mut weight = digits.len() as i16 - 1;
+    digits.reverse();
+    let frac_start = digits.len();
+    for _ in 0..frac_digits / 4 {
+        digits.insert(frac_start, (fraction % 10000) as i16);
+        fraction /= 10000;
+    }
+
+    let leading_zeros = digits.iter().take_while(|d| **d == 0).count();
+    digits.drain(..leading_zeros);
+    weight -= leading_zeros as i16;
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    if digits.is_empty() {
+        weight = 0;
+    }
+    let sign: i16 = if value < 0 { 0x4000 } else { 0 };
+
+    out.extend_from_slice(&(8 + 2 * digits.len() as i32).to_be_bytes());
+    for word in [digits.len() as i16, weight, sign, scale as i16] {
+        out.extend_from_slice(&word.to_be_bytes());
+    }
+    for digit in digits {
+        out.extend_from_slice(&digit.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tpchgen::generators::RegionGenerator;
+    use tpchgen_arrow::RegionArrow;
+
+    /// Returns the `numeric` words (without the length) of `value`
+    fn numeric(value: i128, scale: u32) -> Vec<i16> {
+        let mut out = Vec::new();
+        write_numeric(&mut out, value, scale);
+        assert_eq!(
+            i32::from_be_bytes(out[..4].try_into().unwrap()) as usize,
+            out.len() - 4
+        );
+        out[4..]
+            .chunks(2)
+            .map(|word| i16::from_be_bytes([word[0], word[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn encodes_numeric() {
+        // ndigits, weight, sign, dscale, digits
+        assert_eq!(numeric(0, 2), [0, 0, 0, 2]);
+        assert_eq!(numeric(90100, 2), [1, 0, 0, 2, 901]);
+        assert_eq!(numeric(-5, 2), [1, -1, 0x4000, 2, 500]);
+        assert_eq!(numeric(1234567, 2), [3, 1, 0, 2, 1, 2345, 6700]);
+        assert_eq!(numeric(10_000_000_000, 2), [1, 2, 0, 2, 1]);
+        assert_eq!(numeric(-100_000_001, 4), [3, 1, 0x4000, 4, 1, 0, 1]);
+    }
+
+    #[test]
+    fn encodes_tuples() {
+        let part = encode_part(RegionArrow::new(RegionGenerator::default())).unwrap();
+        assert_eq!(part.num_rows, 5);
+        // the first region: 0, AFRICA, comment
+        let mut expected = 3i16.to_be_bytes().to_vec();
+        write_field(&mut expected, &0i64.to_be_bytes());
+        write_field(&mut expected, b"AFRICA");
+        assert!(part.bytes.starts_with(&expected));
+        assert_eq!(HEADER.len(), 19);
+    }
+}
@@ -0,0 +1,222 @@
+//! Plans of what would be generated (`--dry-run`)
+//!
+//! With `--dry-run`, nothing is generated or written. Instead, the files each
+//! table would be written to are printed, with its number of rows, the
+//! estimated size of its output and the number of parts it is generated in
+//! parallel, e.g. to plan the capacity of a large run. `--dry-run-json` also
+//! writes the plan as JSON:
+//!
+//! ```text
+//! {
+//!   "scale_factor": 1,
+//!   "format": "parquet",
+//!   "destination": "/data/sf1",
+//!   "num_threads": 8,
+//!   "tables": [
+//!     {"table": "nation", "rows": 25, "estimated_bytes": 1100, "num_parts": 1, "parts": [1], "files": ["/data/sf1/nation.parquet"]},
+//!     ...
+//!   ]
+//! }
+//! ```
+//!
+//! The number of rows of `lineitem` is counted exactly from the number of
+//! line items of each order of the dataset of `--seed-offset`, on
+//! `--num-threads` threads. The rows removed by `--filter` are not taken into
+//! account.
+
+use crate::manifest::quote;
+use crate::Table;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+/// What generating one table would do
+#[derive(Debug, Clone, PartialEq)]
+pub struct TablePlan {
+    pub table: Table,
+    pub rows: i64,
+    pub estimated_bytes: u64,
+    /// The number of parts the table is divided into
+    pub num_parts: i32,
+    /// The (1 based) parts that are generated
+    pub parts: Vec<i32>,
+    /// The output files, or directories for tables written to a number of
+    /// files that is only known once they are written. Empty if the output
+    /// is not written to files.
+    pub files: Vec<String>,
+}
+
+/// What generating the tables would do, see the [module
+/// documentation](self)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub scale_factor: f64,
+    pub format: String,
+    /// Where the output is written, e.g. the output directory or stdout
+    pub destination: String,
+    pub num_threads: usize,
+    pub tables: Vec<TablePlan>,
+}
+
+impl Plan {
+    /// Writes the plan as a table for humans to `out`
+    pub fn write_text(&self, out: &mut impl Write) -> io::Result<()> {
+        let threads = match self.num_threads {
+            1 => "1 thread".to_string(),
+            n => format!("{n} threads"),
+        };
+        writeln!(
+            out,
+            "Plan for scale factor {} as {} to {}, with {threads} (nothing is generated, --dry-run)",
+            self.scale_factor, self.format, self.destination
+        )?;
+        writeln!(
+            out,
+            "{:<10} {:>15} {:>12} {:>7}  files",
+            "table", "rows", "size", "parts"
+        )?;
+        for table in &self.tables {
+            let files = match table.files.as_slice() {
+                [] => "-".to_string(),
+                [file] => file.clone(),
+                [first, rest @ ..] => format!("{first} and {} more", rest.len()),
+            };
+            writeln!(
+                out,
+                "{:<10} {:>15} {:>12} {:>7}  {files}",
+                table.table.name(),
+                table.rows,
+                format_bytes(table.estimated_bytes),
+                table.parts.len(),
+            )?;
+        }
+        let rows: i64 = self.tables.iter().map(|t| t.rows).sum();
+        let bytes: u64 = self.tables.iter().map(|t| t.estimated_bytes).sum();
+        let parts: usize = self.tables.iter().map(|t| t.parts.len()).sum();
+        let files: usize = self.tables.iter().map(|t| t.files.len()).sum();
+        writeln!(
+            out,
+            "{:<10} {rows:>15} {:>12} {parts:>7}  {files} files",
+            "total",
+            format_bytes(bytes)
+        )
+    }
+
+    /// Returns the plan as JSON
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        writeln!(json, "{{").unwrap();
+        writeln!(json, r#"  "scale_factor": {},"#, self.scale_factor).unwrap();
+        writeln!(json, r#"  "format": {},"#, quote(&self.format)).unwrap();
+        writeln!(json, r#"  "destination": {},"#, quote(&self.destination)).unwrap();
+        writeln!(json, r#"  "num_threads": {},"#, self.num_threads).unwrap();
+        writeln!(json, r#"  "tables": ["#).unwrap();
+        for (index, table) in self.tables.iter().enumerate() {
+            let parts: Vec<_> = table.parts.iter().map(|p| p.to_string()).collect();
+            let files: Vec<_> = table.files.iter().map(|f| quote(f)).collect();
+            let separator = if index + 1 < self.tables.len() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(
+                json,
+                r#"    {{"table": {}, "rows": {}, "estimated_bytes": {}, "num_parts": {}, "parts": [{}], "files": [{}]}}{separator}"#,
+                quote(table.table.name()),
+                table.rows,
+                table.estimated_bytes,
+                table.num_parts,
+                parts.join(", "),
+                files.join(", "),
+            )
+            .unwrap();
+        }
+        writeln!(json, "  ]").unwrap();
+        writeln!(json, "}}").unwrap();
+        json
+    }
+}
+
+/// Formats a number of bytes for humans, e.g. `3.80 GB`
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.2} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan() -> Plan {
+        Plan {
+            scale_factor: 10.0,
+            format: "tbl".to_string(),
+            destination: "/data".to_string(),
+            num_threads: 4,
+            tables: vec![
+                TablePlan {
+                    table: Table::Nation,
+                    rows: 25,
+                    estimated_bytes: 2200,
+                    num_parts: 1,
+                    parts: vec![1],
+                    files: vec!["/data/nation.tbl".to_string()],
+                },
+                TablePlan {
+                    table: Table::Lineitem,
+                    rows: 60_000_000,
+                    estimated_bytes: 7_680_000_000,
+                    num_parts: 3,
+                    parts: vec![1, 2, 3],
+                    files: vec![
+                        "/data/lineitem.tbl.1".to_string(),
+                        "/data/lineitem.tbl.2".to_string(),
+                        "/data/lineitem.tbl.3".to_string(),
+                    ],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn writes_text() {
+        let mut text = Vec::new();
+        plan().write_text(&mut text).unwrap();
+        let lines: Vec<_> = std::str::from_utf8(&text).unwrap().lines().collect();
+        assert_eq!(
+            lines[1..],
+            [
+                "table                 rows         size   parts  files",
+                "nation                  25      2.15 KB       1  /data/nation.tbl",
+                "lineitem          60000000      7.15 GB       3  /data/lineitem.tbl.1 and 2 more",
+                "total             60000025      7.15 GB       4  4 files",
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_json() {
+        let json: serde_json::Value = serde_json::from_str(&plan().to_json()).unwrap();
+        assert_eq!(json["scale_factor"], 10.0);
+        let lineitem = &json["tables"][1];
+        assert_eq!(lineitem["table"], "lineitem");
+        assert_eq!(lineitem["rows"], 60_000_000);
+        assert_eq!(lineitem["parts"], serde_json::json!([1, 2, 3]));
+        assert_eq!(lineitem["files"][2], "/data/lineitem.tbl.3");
+    }
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(format_bytes(100), "100 B");
+        assert_eq!(format_bytes(1536), "1.50 KB");
+        assert_eq!(format_bytes(4 * 1024 * 1024 * 1024), "4.00 GB");
+    }
+}
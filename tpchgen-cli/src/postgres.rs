@@ -0,0 +1,150 @@
+//! Bulk loading into PostgreSQL with binary `COPY` (`--postgres-url`)
+//!
+//! Requires the `postgres` feature. Instead of writing files, the Arrow
+//! batches for each table are streamed into the database with
+//! `COPY ... FROM STDIN WITH (FORMAT binary)`:
+//!
+//! * The table is created (`CREATE TABLE IF NOT EXISTS`) if needed, with the
+//!   statement of `--format=sql --sql-dialect postgres`, see [`crate::sql`]
+//! * The rows are those of `--format=pg-copy`, see [`crate::pgcopy`], so
+//!   PostgreSQL does not parse any text
+//! * Parts are generated and loaded concurrently, each with its own `COPY`
+//!   over its own connection
+//! * A failed `COPY` is retried with an exponential backoff. `COPY` loads
+//!   all rows of a part or none, so a `COPY` that failed before it was
+//!   finished or was rejected by the server is retried. If the connection
+//!   fails while finishing it, the rows may have been committed, so it is
+//!   not retried and a part is never loaded twice
+//!
+//! Rows within a part are loaded in order, but parts may complete in any
+//! order.
+
+use crate::pgcopy::{encode_part, HEADER, TRAILER};
+use crate::progress::{write_callbacks, ProgressReporter};
+use crate::retry::{no_retry, RetryPolicy};
+use crate::sql::{create_table_sql, SqlDialect};
+use futures::StreamExt;
+use log::debug;
+use sqlx::postgres::{PgPool, PgPoolCopyExt, PgPoolOptions};
+use std::io;
+use std::sync::Arc;
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::statistics::WriteStatistics;
+
+/// Size of the `CopyData` messages the tuples of a part are sent in
+const COPY_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Options for loading tables into PostgreSQL
+#[derive(Debug, Clone, Copy)]
+pub struct LoadOptions {
+    /// Number of parts loaded concurrently (and connections used) per table
+    pub concurrency: usize,
+    /// How failed statements and `COPY`s are retried
+    pub retry: RetryPolicy,
+}
+
+/// Loads the batches from a set of RecordBatchIterators into `table` in the
+/// PostgreSQL database at `url`
+pub async fn load_table<I>(
+    url: &str,
+    table: &'static str,
+    iter_iter: I,
+    options: LoadOptions,
+    progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    I: Iterator<Item: RecordBatchIterator> + 'static,
+{
+    debug!(
+        "Loading {table} into PostgreSQL with {} connections",
+        options.concurrency
+    );
+    let mut iter_iter = iter_iter.peekable();
+
+    // get schema from the first iterator
+    let Some(first_iter) = iter_iter.peek() else {
+        return Ok(()); // no data shrug
+    };
+    let schema = Arc::clone(first_iter.schema());
+
+    let pool = PgPoolOptions::new()
+        .max_connections(options.concurrency as u32)
+        .connect(url)
+        .await
+        .map_err(io::Error::other)?;
+    let sql = create_table_sql(SqlDialect::Postgres, table, &schema)?;
+    let what = format!("CREATE TABLE {table}");
+    options
+        .retry
+        .retry(&what, || async {
+            sqlx::raw_sql(&sql)
+                .execute(&pool)
+                .await
+                .map(|_| ())
+                .map_err(io::Error::other)
+        })
+        .await?;
+
+    let mut statistics =
+        WriteStatistics::new("parts").with_callbacks(write_callbacks(progress.as_ref()));
+    let progress = progress.map(|p| Arc::new(std::sync::Mutex::new(p)));
+
+    let mut loads = futures::stream::iter(iter_iter)
+        .map(|iter| {
+            let pool = pool.clone();
+            let progress = progress.clone();
+            async move {
+                // generate, encode and copy each part on a separate task
+                tokio::task::spawn(async move {
+                    let part = encode_part(iter)?;
+                    let what = format!("COPY of {} rows into {table}", part.num_rows);
+                    options
+                        .retry
+                        .retry(&what, || copy(&pool, table, &part.bytes))
+                        .await?;
+                    let bytes = (HEADER.len() + part.bytes.len() + TRAILER.len()) as u64;
+                    if let Some(progress) = progress {
+                        progress.lock().unwrap().part_complete(part.num_rows, bytes);
+                    }
+                    Ok(bytes) as Result<u64, io::Error>
+                })
+                .await
+                .expect("Inner task panicked")
+            }
+        })
+        .buffer_unordered(options.concurrency);
+
+    while let Some(bytes) = loads.next().await {
+        statistics.increment_bytes(bytes? as usize);
+        statistics.increment_chunks(1);
+    }
+    pool.close().await;
+    Ok(())
+}
+
+/// Copies `tuples`, encoded by [`encode_part`], into `table` with a single
+/// binary `COPY`, returning an error marked with [`no_retry`] if it may have
+/// been committed
+async fn copy(pool: &PgPool, table: &str, tuples: &[u8]) -> Result<(), io::Error> {
+    let statement = format!("COPY {table} FROM STDIN WITH (FORMAT binary)");
+    let mut copy = pool
+        .copy_in_raw(&statement)
+        .await
+        .map_err(io::Error::other)?;
+    let chunks = std::iter::once(HEADER)
+        .chain(tuples.chunks(COPY_CHUNK_SIZE))
+        .chain(std::iter::once(TRAILER));
+    for chunk in chunks {
+        if let Err(e) = copy.send(chunk).await {
+            // roll back the rows sent so far
+            let _ = copy.abort(e.to_string()).await;
+            return Err(io::Error::other(e));
+        }
+    }
+    copy.finish().await.map(|_| ()).map_err(|e| match e {
+        // rejected by the server, which rolled back the COPY
+        sqlx::Error::Database(_) => io::Error::other(e),
+        // e.g. a lost connection after the end of the data was sent
+        e => no_retry(e),
+    })
+}
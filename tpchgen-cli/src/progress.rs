@@ -0,0 +1,224 @@
+//! Machine readable progress events (`--progress-json`)
+//!
+//! Events are written to stderr as JSON lines, one object per line, so that CI
+//! systems and orchestration tools can track generation without parsing the
+//! human readable log output. For example:
+//!
+//! ```text
+//! {"event":"table_start","table":"lineitem","parts":4}
+//! {"event":"part_complete","table":"lineitem","part":1,"parts":4,"rows":1500522,"bytes":188006154,"total_rows":1500522,"total_bytes":188006154,"elapsed_secs":0.420,"rows_per_sec":3572671,"bytes_per_sec":447633700,"eta_secs":1.260}
+//! ...
+//! {"event":"table_complete","table":"lineitem","parts":4,"total_rows":6001215,"total_bytes":759863287,"elapsed_secs":1.523,"rows_per_sec":3940391,"bytes_per_sec":498925336}
+//! ```
+//!
+//! The `eta_secs` of a `part_complete` event is the estimated time until the
+//! remaining parts are written, at the average time per part so far.
+//!
+//! With `--report-memory`, a `memory` event is also written at the end of each
+//! stage, see [`crate::memory`].
+//!
+//! With `--manifest`, the reporter also records the rows of each part in the
+//! [`Manifest`], with or without the events.
+//!
+//! With `--metrics-file`, the reporter carries the [`WriteCallbacks`] of the
+//! [`Metrics`](tpchgen_cli::statistics::Metrics) of the table to the
+//! [`WriteStatistics`](tpchgen_cli::statistics::WriteStatistics) of its
+//! writers, see [`write_callbacks`].
+
+use crate::manifest::Manifest;
+use std::io::Write;
+use std::time::Instant;
+use tpchgen_cli::statistics::WriteCallbacks;
+
+/// Reports progress of writing a single table as JSON lines on stderr.
+///
+/// Emits a `table_start` event when created, a `part_complete` event for each
+/// part (chunk or row group) written and a `table_complete` event on drop.
+#[derive(Debug)]
+pub struct ProgressReporter {
+    /// Are events written to stderr?
+    events: bool,
+    manifest: Option<Manifest>,
+    callbacks: WriteCallbacks,
+    table: &'static str,
+    /// Total number of parts the table is divided into
+    num_parts: i32,
+    /// The (1 based) parts being written, in the order they are written
+    parts: Vec<i32>,
+    start: Instant,
+    parts_complete: usize,
+    total_rows: u64,
+    total_bytes: u64,
+}
+
+impl ProgressReporter {
+    /// Create a new reporter for writing `parts` of `table`, which writes
+    /// events if `events` is true
+    pub fn new(table: &'static str, num_parts: i32, parts: Vec<i32>, events: bool) -> Self {
+        if events {
+            emit(&format!(
+                r#"{{"event":"table_start","table":"{table}","parts":{num_parts}}}"#
+            ));
+        }
+        Self {
+            events,
+            manifest: None,
+            callbacks: WriteCallbacks::default(),
+            table,
+            num_parts,
+            parts,
+            start: Instant::now(),
+            parts_complete: 0,
+            total_rows: 0,
+            total_bytes: 0,
+        }
+    }
+
+    /// Record the rows of each part in `manifest`
+    pub fn with_manifest(mut self, manifest: Option<Manifest>) -> Self {
+        if let Some(manifest) = &manifest {
+            manifest.table_start(self.table, self.num_parts);
+        }
+        self.manifest = manifest;
+        self
+    }
+
+    /// Pass `callbacks` to the statistics of the writers of the table
+    pub fn with_write_callbacks(mut self, callbacks: WriteCallbacks) -> Self {
+        self.callbacks = callbacks;
+        self
+    }
+
+    /// Record that the next part, containing `rows` rows and `bytes` bytes,
+    /// has been written
+    pub fn part_complete(&mut self, rows: u64, bytes: u64) {
+        let part = self
+            .parts
+            .get(self.parts_complete)
+            .copied()
+            .unwrap_or(self.parts_complete as i32 + 1);
+        self.parts_complete += 1;
+        self.total_rows += rows;
+        self.total_bytes += bytes;
+        if let Some(manifest) = &self.manifest {
+            manifest.part_complete(part, rows);
+        }
+        if !self.events {
+            return;
+        }
+        let elapsed = self.start.elapsed().as_secs_f64();
+        emit(&format!(
+            r#"{{"event":"part_complete","table":"{}","part":{part},"parts":{},"rows":{rows},"bytes":{bytes},{},"eta_secs":{:.3}}}"#,
+            self.table,
+            self.num_parts,
+            self.totals(elapsed),
+            self.eta_secs(elapsed)
+        ));
+    }
+
+    /// Returns the estimated number of seconds until the remaining parts are
+    /// written, `elapsed` seconds after the start, at the average time per
+    /// part written so far
+    fn eta_secs(&self, elapsed: f64) -> f64 {
+        if self.parts_complete == 0 {
+            return 0.0;
+        }
+        let remaining = self.parts.len().saturating_sub(self.parts_complete);
+        elapsed / self.parts_complete as f64 * remaining as f64
+    }
+
+    /// Formats the running totals and rates, `elapsed` seconds after the
+    /// start, as JSON fields
+    fn totals(&self, elapsed: f64) -> String {
+        let rate = |value: u64| {
+            if elapsed > 0.0 {
+                (value as f64 / elapsed) as u64
+            } else {
+                0
+            }
+        };
+        format!(
+            r#""total_rows":{},"total_bytes":{},"elapsed_secs":{elapsed:.3},"rows_per_sec":{},"bytes_per_sec":{}"#,
+            self.total_rows,
+            self.total_bytes,
+            rate(self.total_rows),
+            rate(self.total_bytes)
+        )
+    }
+}
+
+impl Drop for ProgressReporter {
+    fn drop(&mut self) {
+        if !self.events {
+            return;
+        }
+        emit(&format!(
+            r#"{{"event":"table_complete","table":"{}","parts":{},{}}}"#,
+            self.table,
+            self.num_parts,
+            self.totals(self.start.elapsed().as_secs_f64())
+        ));
+    }
+}
+
+/// Returns the [`WriteCallbacks`] of `progress`, if any, for the
+/// statistics of a writer of its table
+pub fn write_callbacks(progress: Option<&ProgressReporter>) -> WriteCallbacks {
+    progress.map_or_else(WriteCallbacks::default, |p| p.callbacks.clone())
+}
+
+/// Writes a single event line to stderr, ignoring errors (progress reporting
+/// should never cause generation to fail)
+fn emit(line: &str) {
+    let mut stderr = std::io::stderr().lock();
+    let _ = writeln!(stderr, "{line}");
+}
+
+/// Emits a `memory` event with the resident set size in bytes at the start
+/// of a stage and the peak sampled during it
+pub fn memory(stage: &str, start_bytes: u64, peak_bytes: u64) {
+    emit(&format!(
+        r#"{{"event":"memory","stage":"{stage}","start_rss_bytes":{start_bytes},"peak_rss_bytes":{peak_bytes}}}"#
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_rates() {
+        let mut progress = ProgressReporter::new("lineitem", 4, vec![1, 2, 3, 4], false);
+        assert_eq!(
+            progress.totals(0.0),
+            r#""total_rows":0,"total_bytes":0,"elapsed_secs":0.000,"rows_per_sec":0,"bytes_per_sec":0"#
+        );
+        progress.part_complete(1000, 128_000);
+        progress.part_complete(500, 64_000);
+        assert_eq!(
+            progress.totals(1.5),
+            r#""total_rows":1500,"total_bytes":192000,"elapsed_secs":1.500,"rows_per_sec":1000,"bytes_per_sec":128000"#
+        );
+        assert_eq!(
+            progress.totals(0.0),
+            r#""total_rows":1500,"total_bytes":192000,"elapsed_secs":0.000,"rows_per_sec":0,"bytes_per_sec":0"#
+        );
+    }
+
+    #[test]
+    fn estimates_remaining_time() {
+        // parts 3 to 6 of 8, as with --part
+        let mut progress = ProgressReporter::new("orders", 8, vec![3, 4, 5, 6], false);
+        assert_eq!(progress.eta_secs(5.0), 0.0);
+        progress.part_complete(100, 1000);
+        assert_eq!(progress.eta_secs(2.0), 6.0);
+        progress.part_complete(100, 1000);
+        assert_eq!(progress.eta_secs(3.0), 3.0);
+        progress.part_complete(100, 1000);
+        progress.part_complete(100, 1000);
+        assert_eq!(progress.eta_secs(8.0), 0.0);
+        // more parts than planned, e.g. the row groups of a part
+        progress.part_complete(100, 1000);
+        assert_eq!(progress.eta_secs(9.0), 0.0);
+    }
+}
@@ -0,0 +1,131 @@
+//! Query generation (`queries`)
+//!
+//! `tpchgen-cli queries` writes the 22 TPC-H queries with their substitution
+//! parameters filled in, like qgen. Without `--seed` the parameters are the
+//! validation parameters of the specification, as written by `qgen -d`, so
+//! the answers can be compared with the answer set of the specification.
+//! With `--seed`, they are chosen at random from the ranges of the
+//! specification, the same for the same seed. The stream does not change
+//! the parameters: give each stream its own seed.
+//!
+//! Without `--stream`, the queries are written in order to `queries.sql`.
+//! With `--stream N`, they are written in the order of query stream `N` of
+//! Appendix A of the specification to `stream<N>.sql`: stream 0 is the
+//! power test and streams 1 and up are the streams of the throughput test.
+//!
+//! ```text
+//! -- Q14 of stream 0
+//! select
+//!     100.00 * sum(case
+//! ...
+//! ```
+
+use std::io::{self, Write};
+use tpchgen::queries::{query_text, stream_order};
+
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct QueriesArgs {
+    /// Seed of the random substitution parameters (default: the validation
+    /// parameters of the specification)
+    #[arg(long, value_parser = parse_seed)]
+    pub seed: Option<i64>,
+
+    /// Query stream, whose order of the queries is used (default: the
+    /// queries in order, as stream 0)
+    #[arg(long)]
+    pub stream: Option<i32>,
+}
+
+impl QueriesArgs {
+    /// Returns the name of the file the queries are written to
+    pub fn file_name(&self) -> String {
+        match self.stream {
+            Some(stream) => format!("stream{stream}.sql"),
+            None => "queries.sql".to_string(),
+        }
+    }
+}
+
+/// Parses a `--seed`, rejecting the multiples of 2147483647 with which
+/// the random number generator only returns 0
+pub fn parse_seed(s: &str) -> Result<i64, String> {
+    let seed: i64 = s.parse().map_err(|e| format!("invalid seed '{s}': {e}"))?;
+    if seed % 2147483647 == 0 {
+        return Err(format!(
+            "invalid seed '{s}': must not be a multiple of 2147483647"
+        ));
+    }
+    Ok(seed)
+}
+
+/// Writes the queries selected by `args` at `scale_factor` to `out`
+pub fn write_queries(
+    out: &mut impl Write,
+    scale_factor: f64,
+    args: &QueriesArgs,
+) -> io::Result<()> {
+    let stream = args.stream.unwrap_or(0);
+    let order = match args.stream {
+        Some(stream) => stream_order(stream),
+        None => std::array::from_fn(|i| i as i32 + 1),
+    };
+    if let Some(seed) = args.seed {
+        writeln!(out, "-- using {seed} as a seed to the RNG")?;
+    }
+    for num in order {
+        let text = query_text(num, scale_factor, stream, args.seed).expect("valid query number");
+        writeln!(out, "-- Q{num} of stream {stream}")?;
+        writeln!(out, "{}", text.trim_start_matches('\n'))?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queries(seed: Option<i64>, stream: Option<i32>) -> String {
+        let mut out = Vec::new();
+        write_queries(&mut out, 1.0, &QueriesArgs { seed, stream }).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn query_order(text: &str) -> Vec<String> {
+        text.lines()
+            .filter_map(|line| line.strip_prefix("-- Q"))
+            .map(|line| line.split(' ').next().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn writes_queries_in_stream_order() {
+        let validation = queries(None, None);
+        let order = query_order(&validation);
+        assert_eq!(order.len(), 22);
+        assert_eq!(order[..3], ["1", "2", "3"]);
+        assert!(validation.contains("interval '90' day (3)"));
+
+        let stream = queries(None, Some(1));
+        assert_eq!(query_order(&stream)[..3], ["21", "3", "18"]);
+        assert!(stream.contains("create view revenue1"));
+    }
+
+    #[test]
+    fn seeds_parameters() {
+        let first = queries(Some(7), Some(2));
+        assert!(first.starts_with("-- using 7 as a seed to the RNG\n"));
+        assert_eq!(first, queries(Some(7), Some(2)));
+        assert_ne!(first, queries(Some(7), Some(3)));
+        assert_ne!(first, queries(None, Some(2)));
+    }
+
+    #[test]
+    fn parses_seeds() {
+        assert_eq!(parse_seed("1234"), Ok(1234));
+        assert_eq!(parse_seed("-5"), Ok(-5));
+        assert!(parse_seed("0").is_err());
+        assert!(parse_seed("2147483647").is_err());
+        assert!(parse_seed("x").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! Refresh sets (`--update-sets`)
+//!
+//! The TPC-H refresh functions change the data between the queries of the
+//! throughput test: RF1 inserts new orders with their line items and RF2
+//! deletes old orders with their line items. `--update-sets N` writes the
+//! data of the first `N` pairs of refresh functions, with the names and
+//! layout of `dbgen -U N`:
+//!
+//! ```text
+//! orders.tbl.u1      orders inserted by RF1 of the first refresh set
+//! lineitem.tbl.u1    their line items
+//! delete.1           keys of the orders deleted by RF2, as `1|`
+//! orders.tbl.u2
+//! ...
+//! ```
+//!
+//! Each refresh set inserts and deletes 0.1% of the orders. The inserted
+//! orders are generated by [`OrderGenerator::with_update_set`] and
+//! [`LineItemGenerator::with_update_set`], with the keys that the base
+//! orders leave unused. Refresh set `n` deletes the `n`th 0.1% of the base
+//! orders, see [`DeleteKeyGenerator`].
+//!
+//! `--delete-sets N` writes only the `delete.<n>` files, which are the same
+//! as those written by `--update-sets`.
+//!
+//! [`OrderGenerator::with_update_set`]: tpchgen::generators::OrderGenerator::with_update_set
+//! [`LineItemGenerator::with_update_set`]: tpchgen::generators::LineItemGenerator::with_update_set
+
+use std::io::Write;
+use tpchgen::generators::DeleteKeyGenerator;
+use tpchgen_cli::generate::Source;
+
+/// A [`Source`] that writes the keys of the orders deleted by a part of a
+/// refresh set, one `key|` per line
+pub struct DeleteKeySource {
+    inner: DeleteKeyGenerator,
+}
+
+impl DeleteKeySource {
+    pub fn new(inner: DeleteKeyGenerator) -> Self {
+        Self { inner }
+    }
+}
+
+impl Source for DeleteKeySource {
+    fn header(&self, buffer: Vec<u8>) -> Vec<u8> {
+        // delete files do not have a header
+        buffer
+    }
+
+    fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+        for key in self.inner.iter() {
+            writeln!(&mut buffer, "{key}|").expect("writing to memory is infallible");
+        }
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(update_set: i32, part: i32, num_parts: i32) -> String {
+        let generator = DeleteKeyGenerator::new(0.1, part, num_parts).with_update_set(update_set);
+        let source = DeleteKeySource::new(generator);
+        String::from_utf8(source.create(Vec::new())).unwrap()
+    }
+
+    #[test]
+    fn writes_deleted_keys() {
+        let first = keys(1, 1, 1);
+        let lines: Vec<_> = first.lines().collect();
+        assert_eq!(lines.len(), 150);
+        assert_eq!(
+            lines[..9],
+            ["1|", "2|", "3|", "4|", "5|", "6|", "7|", "32|", "33|"]
+        );
+        // the next set deletes the next orders
+        let second = keys(2, 1, 1);
+        assert_eq!(second.lines().next(), Some("583|"));
+        // the parts of a set are its keys
+        let parts: String = (1..=4).map(|part| keys(2, part, 4)).collect();
+        assert_eq!(parts, second);
+    }
+}
@@ -0,0 +1,168 @@
+//! Retrying remote operations with exponential backoff (`--max-retries`)
+//!
+//! Used by the sinks that write over the network (object stores, Arrow Flight
+//! and database loaders) so a transient failure does not abort a long run.
+//! The generated data is deterministic, so a failed part or file can be sent
+//! again, but only if the failed attempt left nothing behind:
+//!
+//! * Object store uploads replace the whole file, and Flight endpoints
+//!   replace the data of a part sent again with the same descriptor
+//! * Database loads only retry an `INSERT` or `COPY` that failed before it
+//!   was sent or that the database rejected, which rolls it back. If the
+//!   connection fails after it was sent, the rows may have been committed,
+//!   so the error is returned with [`no_retry`] instead of loading them
+//!   twice
+
+use log::warn;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+
+/// Upper bound for the delay between two attempts
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How often, and how long to wait before, retrying a failed operation
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Number of times a failed operation is retried before giving up
+    pub max_retries: usize,
+    /// Delay before the first retry; doubled for every following retry
+    pub initial_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub const NONE: Self = Self {
+        max_retries: 0,
+        initial_backoff: Duration::ZERO,
+    };
+
+    /// Returns the delay before retry number `attempt` (1 based)
+    pub fn backoff(&self, attempt: usize) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        self.initial_backoff.saturating_mul(factor).min(MAX_BACKOFF)
+    }
+
+    /// Runs `operation` until it succeeds or has been retried `max_retries`
+    /// times, returning the last error, or the first one marked with
+    /// [`no_retry`]. `what` describes the operation in log messages.
+    pub async fn retry<T, F, Fut>(&self, what: &str, mut operation: F) -> io::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = io::Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.max_retries && !is_no_retry(&e) => {
+                    attempt += 1;
+                    let backoff = self.backoff(attempt);
+                    warn!(
+                        "{what} failed (retry {attempt} of {} in {backoff:?}): {e}",
+                        self.max_retries
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Returns `error` marked as not to be retried by [`RetryPolicy::retry`], for
+/// failures after which the operation may have taken effect, e.g. a lost
+/// connection after an `INSERT` was sent
+#[cfg_attr(
+    not(any(feature = "mysql", feature = "postgres", feature = "clickhouse")),
+    allow(dead_code)
+)]
+pub fn no_retry(error: impl Into<Box<dyn Error + Send + Sync>>) -> io::Error {
+    io::Error::other(NoRetry(error.into()))
+}
+
+/// Returns true if `error` was returned by [`no_retry`]
+fn is_no_retry(error: &io::Error) -> bool {
+    error.get_ref().is_some_and(|e| e.is::<NoRetry>())
+}
+
+/// An error that is not retried, see [`no_retry`]
+#[derive(Debug)]
+struct NoRetry(Box<dyn Error + Send + Sync>);
+
+impl Display for NoRetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (not retried, it may have been applied)", self.0)
+    }
+}
+
+impl Error for NoRetry {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_retries: 100,
+            initial_backoff: Duration::from_millis(100),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(100), MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn retries_until_success_or_limit() {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(10),
+        };
+        let mut calls = 0;
+        let result = policy
+            .retry("test", || {
+                calls += 1;
+                let calls = calls;
+                async move {
+                    if calls < 3 {
+                        Err(io::Error::other("transient"))
+                    } else {
+                        Ok(calls)
+                    }
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: io::Result<()> = policy
+            .retry("test", || {
+                calls += 1;
+                async { Err(io::Error::other("permanent")) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        let mut calls = 0;
+        let result: io::Result<()> = policy
+            .retry("test", || {
+                calls += 1;
+                async { Err(no_retry("connection lost")) }
+            })
+            .await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "connection lost (not retried, it may have been applied)"
+        );
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,178 @@
+//! Parsing of human-friendly scale factors (e.g. `sf100`, `0.1`, `100gb`)
+//! and sizes (e.g. `512MB`)
+
+use crate::Table;
+use tpchgen::generators::MAX_SCALE_FACTOR;
+
+/// Scale factors allowed for published TPC-H results
+pub const STANDARD_SCALE_FACTORS: [f64; 10] = [
+    1.0, 10.0, 30.0, 100.0, 300.0, 1000.0, 3000.0, 10000.0, 30000.0, 100000.0,
+];
+
+/// Returns true if `scale_factor` is one of the [`STANDARD_SCALE_FACTORS`]
+pub fn is_standard(scale_factor: f64) -> bool {
+    STANDARD_SCALE_FACTORS.contains(&scale_factor)
+}
+
+/// Parses a scale factor from the command line.
+///
+/// Accepts:
+/// * plain numbers: `1`, `0.1`, `100`
+/// * an `sf` prefix: `sf100`, `SF0.01`
+/// * a target size in bytes with a unit suffix: `100gb`, `500MB`, `1TiB`.
+///   Decimal (`kb`, `mb`, `gb`, `tb`) and binary (`kib`, `mib`, `gib`,
+///   `tib`) units are supported. The size is converted to a scale factor
+///   using the estimated size of the TBL output at SF=1.
+///
+/// Scale factors above [`MAX_SCALE_FACTOR`] are rejected.
+pub fn parse_scale_factor(value: &str) -> Result<f64, String> {
+    let value = value.trim();
+    let lower = value.to_ascii_lowercase();
+
+    let scale_factor = if let Some(number) = lower.strip_prefix("sf") {
+        parse_number(number, value)?
+    } else if let Some((number, multiplier)) = split_size_suffix(&lower) {
+        let bytes = parse_number(number, value)? * multiplier;
+        bytes / estimated_tbl_bytes_per_scale_factor()
+    } else {
+        parse_number(&lower, value)?
+    };
+
+    if !scale_factor.is_finite() || scale_factor <= 0.0 {
+        return Err(format!(
+            "invalid scale factor '{value}': must be a positive number"
+        ));
+    }
+    if !is_supported(scale_factor) {
+        return Err(format!(
+            "invalid scale factor '{value}': must be at most {MAX_SCALE_FACTOR}, \
+             the largest supported scale factor"
+        ));
+    }
+    Ok(scale_factor)
+}
+
+/// Returns true if `scale_factor` is at most [`MAX_SCALE_FACTOR`], beyond
+/// which the generated data would be wrong
+pub fn is_supported(scale_factor: f64) -> bool {
+    scale_factor <= MAX_SCALE_FACTOR
+}
+
+/// Parses a size in bytes from the command line, e.g. `512MB` or `1GiB`,
+/// with the units of [`parse_scale_factor`], or a plain number of bytes.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let lower = value.to_ascii_lowercase();
+    let (number, multiplier) = split_size_suffix(&lower).unwrap_or((&lower, 1.0));
+    let bytes = number.trim().parse::<f64>().map_err(|_| {
+        format!("invalid size '{value}': expected a number of bytes or a size (e.g. '512mb')")
+    })? * multiplier;
+    if !bytes.is_finite() || bytes < 1.0 {
+        return Err(format!("invalid size '{value}': must be positive"));
+    }
+    Ok(bytes as u64)
+}
+
+/// Estimated size in bytes of all tables in TBL format at SF=1
+pub fn estimated_tbl_bytes_per_scale_factor() -> f64 {
+    Table::ALL
+        .iter()
+        .map(|table| {
+            let (avg_row_size_bytes, row_count) = table.estimated_tbl_size(1.0);
+            (avg_row_size_bytes * row_count) as f64
+        })
+        .sum()
+}
+
+fn parse_number(number: &str, original: &str) -> Result<f64, String> {
+    number.trim().parse::<f64>().map_err(|_| {
+        format!(
+            "invalid scale factor '{original}': expected a number (e.g. '10'), \
+             'sf<number>' (e.g. 'sf100') or a size (e.g. '100gb')"
+        )
+    })
+}
+
+/// Splits a size like `100gb` into (`100`, bytes per unit)
+fn split_size_suffix(value: &str) -> Option<(&str, f64)> {
+    const UNITS: [(&str, f64); 8] = [
+        ("kib", 1024.0),
+        ("mib", 1024.0 * 1024.0),
+        ("gib", 1024.0 * 1024.0 * 1024.0),
+        ("tib", 1024.0 * 1024.0 * 1024.0 * 1024.0),
+        ("kb", 1e3),
+        ("mb", 1e6),
+        ("gb", 1e9),
+        ("tb", 1e12),
+    ];
+    UNITS
+        .iter()
+        .find_map(|(suffix, multiplier)| Some((value.strip_suffix(suffix)?, *multiplier)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_scale_factors() {
+        assert_eq!(parse_scale_factor("1"), Ok(1.0));
+        assert_eq!(parse_scale_factor(" 0.1 "), Ok(0.1));
+        assert_eq!(parse_scale_factor("sf100"), Ok(100.0));
+        assert_eq!(parse_scale_factor("SF0.01"), Ok(0.01));
+        assert_eq!(parse_scale_factor("1000000"), Ok(MAX_SCALE_FACTOR));
+
+        let per_scale_factor = estimated_tbl_bytes_per_scale_factor();
+        assert_eq!(parse_scale_factor("100gb"), Ok(100e9 / per_scale_factor));
+        assert_eq!(parse_scale_factor("500MB"), Ok(500e6 / per_scale_factor));
+        assert_eq!(
+            parse_scale_factor("1TiB"),
+            Ok(1024.0 * 1024.0 * 1024.0 * 1024.0 / per_scale_factor)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_scale_factors() {
+        for (value, error) in [
+            ("", "expected a number"),
+            ("sf", "expected a number"),
+            ("ten", "expected a number"),
+            ("10pb", "expected a number"),
+            ("0", "must be a positive number"),
+            ("-1", "must be a positive number"),
+            ("sf-1", "must be a positive number"),
+            ("NaN", "must be a positive number"),
+            ("inf", "must be a positive number"),
+            ("1000001", "must be at most 1000000"),
+            ("sf2000000", "must be at most 1000000"),
+        ] {
+            let err = parse_scale_factor(value).unwrap_err();
+            assert!(
+                err.starts_with(&format!("invalid scale factor '{value}'")) && err.contains(error),
+                "{value}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn standard_scale_factors() {
+        for scale_factor in ["1", "sf10", "30", "100", "sf1000", "100000"] {
+            assert!(is_standard(parse_scale_factor(scale_factor).unwrap()));
+        }
+        for scale_factor in ["0.1", "sf0.01", "2", "50", "1000000", "1gb"] {
+            assert!(!is_standard(parse_scale_factor(scale_factor).unwrap()));
+        }
+    }
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("512MB"), Ok(512_000_000));
+        assert_eq!(parse_size("1 GiB"), Ok(1 << 30));
+        assert_eq!(parse_size("1.5kib"), Ok(1536));
+        assert_eq!(parse_size("2tb"), Ok(2_000_000_000_000));
+        for value in ["", "mb", "big", "0", "0.5", "-1kb"] {
+            assert!(parse_size(value).is_err(), "{value}");
+        }
+    }
+}
@@ -0,0 +1,474 @@
+//! Table definitions for SQL engines (`tpchgen-cli schema`)
+//!
+//! Prints a `CREATE TABLE` statement per table, with the column types of the
+//! generated data in the dialect of the engine, so the tables can be created
+//! before loading the output:
+//!
+//! ```shell
+//! tpchgen-cli schema --dialect duckdb | duckdb tpch.db
+//! ```
+//!
+//! Decimals keep their precision and scale, e.g. `DECIMAL(15,2)`, and dates
+//! are `DATE`s, unless `--decimal-as` or `--date-as` change their types in the
+//! generated files, e.g. to `DOUBLE` or `TIMESTAMP`. Columns are `NOT NULL`
+//! unless NULLs are injected into them with `--null-percent`, and
+//! `--uuid-column` adds its column, as in the generated files.
+//!
+//! With `--primary-keys`, `--foreign-keys` and `--indexes` the statements
+//! also define the primary and foreign keys of clause 1.4.2 of the
+//! specification, and indexes on the foreign keys and the most filtered date
+//! columns (`o_orderdate` and `l_shipdate`), which clause 1.5.7 allows:
+//!
+//! * PostgreSQL and MySQL: foreign keys are added with `ALTER TABLE` after
+//!   all tables are created
+//! * DuckDB: foreign keys are declared in `CREATE TABLE`, so the tables are
+//!   created after the tables they reference
+//! * BigQuery: keys are `NOT ENFORCED`, and there are no indexes
+//! * Trino and Spark SQL support neither keys nor indexes
+//!
+//! Foreign keys are only defined if the referenced table is also selected.
+
+use crate::Table;
+use arrow::datatypes::{DataType, SchemaRef, TimeUnit};
+use clap::ValueEnum;
+use std::fmt::Write;
+use std::io;
+
+/// Arguments of the `schema` subcommand
+#[derive(Debug, clap::Args)]
+pub struct SchemaArgs {
+    /// SQL dialect of the statements
+    #[arg(long, default_value = "postgres")]
+    pub dialect: SchemaDialect,
+
+    /// Define the primary keys of the tables
+    #[arg(long)]
+    pub primary_keys: bool,
+
+    /// Define the foreign keys between the tables
+    #[arg(long, requires = "primary_keys")]
+    pub foreign_keys: bool,
+
+    /// Create indexes on the foreign keys, `o_orderdate` and `l_shipdate`
+    #[arg(long)]
+    pub indexes: bool,
+}
+
+/// SQL dialect of the `CREATE TABLE` statements
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum SchemaDialect {
+    /// PostgreSQL
+    Postgres,
+    /// MySQL and MariaDB
+    Mysql,
+    /// DuckDB
+    Duckdb,
+    /// Trino and Presto
+    Trino,
+    /// Spark SQL
+    Spark,
+    /// Google BigQuery
+    Bigquery,
+}
+
+impl SchemaDialect {
+    /// Returns the name of the dialect on the command line, e.g. `duckdb`
+    fn name(&self) -> &'static str {
+        match self {
+            SchemaDialect::Postgres => "postgres",
+            SchemaDialect::Mysql => "mysql",
+            SchemaDialect::Duckdb => "duckdb",
+            SchemaDialect::Trino => "trino",
+            SchemaDialect::Spark => "spark",
+            SchemaDialect::Bigquery => "bigquery",
+        }
+    }
+
+    /// Returns the column type for an Arrow type produced by `tpchgen-arrow`,
+    /// or an error if the dialect has no type for it
+    fn column_type(&self, data_type: &DataType) -> io::Result<String> {
+        let column_type = match (self, data_type) {
+            (SchemaDialect::Bigquery, DataType::Int32 | DataType::Int64) => "INT64".to_string(),
+            (SchemaDialect::Spark | SchemaDialect::Mysql, DataType::Int32) => "INT".to_string(),
+            (_, DataType::Int32) => "INTEGER".to_string(),
+            (_, DataType::Int64) => "BIGINT".to_string(),
+            (SchemaDialect::Bigquery, DataType::Decimal128(precision, scale)) => {
+                format!("NUMERIC({precision}, {scale})")
+            }
+            (_, DataType::Decimal128(precision, scale)) => {
+                format!("DECIMAL({precision},{scale})")
+            }
+            (SchemaDialect::Postgres, DataType::Float64) => "DOUBLE PRECISION".to_string(),
+            (SchemaDialect::Bigquery, DataType::Float64) => "FLOAT64".to_string(),
+            (_, DataType::Float64) => "DOUBLE".to_string(),
+            (_, DataType::Date32) => "DATE".to_string(),
+            // without a time zone, in microseconds
+            (dialect, DataType::Timestamp(TimeUnit::Microsecond, None)) => match dialect {
+                SchemaDialect::Mysql => "DATETIME(6)".to_string(),
+                SchemaDialect::Bigquery => "DATETIME".to_string(),
+                SchemaDialect::Spark => "TIMESTAMP_NTZ".to_string(),
+                SchemaDialect::Trino => "TIMESTAMP(6)".to_string(),
+                SchemaDialect::Postgres | SchemaDialect::Duckdb => "TIMESTAMP".to_string(),
+            },
+            (dialect, DataType::Utf8 | DataType::Utf8View | DataType::LargeUtf8) => {
+                match dialect {
+                    // MySQL requires a length for VARCHAR
+                    SchemaDialect::Mysql => "VARCHAR(255)".to_string(),
+                    SchemaDialect::Spark | SchemaDialect::Bigquery => "STRING".to_string(),
+                    SchemaDialect::Postgres | SchemaDialect::Duckdb | SchemaDialect::Trino => {
+                        "VARCHAR".to_string()
+                    }
+                }
+            }
+            (dialect, DataType::Dictionary(_, value_type)) => {
+                return dialect.column_type(value_type)
+            }
+            (dialect, other) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("--dialect {} has no type for {other}", dialect.name()),
+                ))
+            }
+        };
+        Ok(column_type)
+    }
+
+    /// Returns true if the dialect supports primary and foreign keys
+    fn supports_keys(&self) -> bool {
+        !matches!(self, SchemaDialect::Trino | SchemaDialect::Spark)
+    }
+
+    /// Returns true if the dialect supports `CREATE INDEX`
+    fn supports_indexes(&self) -> bool {
+        matches!(
+            self,
+            SchemaDialect::Postgres | SchemaDialect::Mysql | SchemaDialect::Duckdb
+        )
+    }
+
+    /// Returns the suffix of key constraints, which BigQuery does not enforce
+    fn enforcement(&self) -> &'static str {
+        match self {
+            SchemaDialect::Bigquery => " NOT ENFORCED",
+            _ => "",
+        }
+    }
+}
+
+/// A foreign key: the columns of the table referencing the columns of another
+/// table
+type ForeignKey = (&'static [&'static str], Table, &'static [&'static str]);
+
+impl Table {
+    /// Returns the foreign keys of the table (clause 1.4.2.3)
+    fn foreign_keys(&self) -> &'static [ForeignKey] {
+        match self {
+            Table::Nation => &[(&["n_regionkey"], Table::Region, &["r_regionkey"])],
+            Table::Region | Table::Part => &[],
+            Table::Supplier => &[(&["s_nationkey"], Table::Nation, &["n_nationkey"])],
+            Table::Partsupp => &[
+                (&["ps_partkey"], Table::Part, &["p_partkey"]),
+                (&["ps_suppkey"], Table::Supplier, &["s_suppkey"]),
+            ],
+            Table::Customer => &[(&["c_nationkey"], Table::Nation, &["n_nationkey"])],
+            Table::Orders => &[(&["o_custkey"], Table::Customer, &["c_custkey"])],
+            Table::Lineitem => &[
+                (&["l_orderkey"], Table::Orders, &["o_orderkey"]),
+                (&["l_partkey"], Table::Part, &["p_partkey"]),
+                (&["l_suppkey"], Table::Supplier, &["s_suppkey"]),
+                (
+                    &["l_partkey", "l_suppkey"],
+                    Table::Partsupp,
+                    &["ps_partkey", "ps_suppkey"],
+                ),
+            ],
+        }
+    }
+
+    /// Returns the indexed columns of the table, other than its primary key:
+    /// the foreign keys and the date columns most queries filter on
+    fn indexes(&self) -> Vec<&'static [&'static str]> {
+        let mut indexes: Vec<_> = self.foreign_keys().iter().map(|(c, _, _)| *c).collect();
+        match self {
+            Table::Orders => indexes.push(&["o_orderdate"]),
+            Table::Lineitem => indexes.push(&["l_shipdate"]),
+            _ => {}
+        }
+        // e.g. l_partkey is covered by the index on (l_partkey, l_suppkey)
+        indexes
+            .iter()
+            .filter(|a| {
+                !indexes
+                    .iter()
+                    .any(|b| b.len() > a.len() && b.starts_with(a))
+            })
+            .copied()
+            .collect()
+    }
+}
+
+/// Returns the statements creating `tables` (table and schema) for `args`
+pub fn create_tables_ddl(args: &SchemaArgs, tables: &[(Table, SchemaRef)]) -> io::Result<String> {
+    let dialect = args.dialect;
+    let unsupported = if (args.primary_keys || args.foreign_keys) && !dialect.supports_keys() {
+        Some("--primary-keys and --foreign-keys")
+    } else if args.indexes && !dialect.supports_indexes() {
+        Some("--indexes")
+    } else {
+        None
+    };
+    if let Some(unsupported) = unsupported {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{unsupported} can not be used with --dialect {}",
+                dialect.name()
+            ),
+        ));
+    }
+
+    let selected = |table: &Table| tables.iter().any(|(t, _)| t == table);
+    // the foreign keys whose referenced table is selected
+    let foreign_keys = |table: Table| {
+        table
+            .foreign_keys()
+            .iter()
+            .filter(move |(_, referenced, _)| args.foreign_keys && selected(referenced))
+    };
+    let inline_foreign_keys = dialect == SchemaDialect::Duckdb;
+
+    // tables must be created after the tables their inline foreign keys
+    // reference
+    let mut ordered: Vec<&(Table, SchemaRef)> = Vec::with_capacity(tables.len());
+    if inline_foreign_keys {
+        while ordered.len() < tables.len() {
+            let next = tables
+                .iter()
+                .find(|(table, _)| {
+                    !ordered.iter().any(|(t, _)| t == table)
+                        && foreign_keys(*table)
+                            .all(|(_, referenced, _)| ordered.iter().any(|(t, _)| t == referenced))
+                })
+                .expect("foreign keys are acyclic");
+            ordered.push(next);
+        }
+    } else {
+        ordered.extend(tables);
+    }
+
+    let mut sql = String::new();
+    for (i, (table, schema)) in ordered.into_iter().enumerate() {
+        if i > 0 {
+            sql.push('\n');
+        }
+        let mut definitions = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let not_null = if field.is_nullable() { "" } else { " NOT NULL" };
+                let column_type = dialect.column_type(field.data_type())?;
+                Ok(format!("{} {column_type}{not_null}", field.name()))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        if args.primary_keys {
+            definitions.push(format!(
+                "PRIMARY KEY ({}){}",
+                table.primary_key().join(", "),
+                dialect.enforcement()
+            ));
+        }
+        if inline_foreign_keys {
+            for (columns, referenced, referenced_columns) in foreign_keys(*table) {
+                definitions.push(foreign_key_sql(
+                    dialect,
+                    columns,
+                    *referenced,
+                    referenced_columns,
+                ));
+            }
+        }
+        writeln!(sql, "CREATE TABLE IF NOT EXISTS {table} (").unwrap();
+        writeln!(sql, "    {}", definitions.join(",\n    ")).unwrap();
+        sql.push_str(");\n");
+    }
+
+    let mut alter_tables = String::new();
+    if !inline_foreign_keys {
+        for (table, _) in tables {
+            for (columns, referenced, referenced_columns) in foreign_keys(*table) {
+                let foreign_key =
+                    foreign_key_sql(dialect, columns, *referenced, referenced_columns);
+                writeln!(alter_tables, "ALTER TABLE {table} ADD {foreign_key};").unwrap();
+            }
+        }
+    }
+    if !alter_tables.is_empty() {
+        sql.push('\n');
+        sql.push_str(&alter_tables);
+    }
+
+    let mut indexes = String::new();
+    for (table, _) in tables.iter().filter(|_| args.indexes) {
+        for columns in table.indexes() {
+            // the primary key is indexed already
+            if args.primary_keys && table.primary_key().starts_with(columns) {
+                continue;
+            }
+            writeln!(
+                indexes,
+                "CREATE INDEX {}_idx ON {table} ({});",
+                columns.join("_"),
+                columns.join(", ")
+            )
+            .unwrap();
+        }
+    }
+    if !indexes.is_empty() {
+        sql.push('\n');
+        sql.push_str(&indexes);
+    }
+    Ok(sql)
+}
+
+/// Returns the `FOREIGN KEY` constraint of `columns` referencing `referenced`
+fn foreign_key_sql(
+    dialect: SchemaDialect,
+    columns: &[&str],
+    referenced: Table,
+    referenced_columns: &[&str],
+) -> String {
+    format!(
+        "FOREIGN KEY ({}) REFERENCES {referenced} ({}){}",
+        columns.join(", "),
+        referenced_columns.join(", "),
+        dialect.enforcement()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+
+    fn args(dialect: SchemaDialect) -> SchemaArgs {
+        SchemaArgs {
+            dialect,
+            primary_keys: false,
+            foreign_keys: false,
+            indexes: false,
+        }
+    }
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("o_orderkey", DataType::Int64, false),
+            Field::new("o_shippriority", DataType::Int32, false),
+            Field::new("o_totalprice", DataType::Decimal128(15, 2), false),
+            Field::new("o_orderdate", DataType::Date32, false),
+            Field::new("o_comment", DataType::Utf8View, true),
+        ]))
+    }
+
+    #[test]
+    fn create_tables_postgres() {
+        let args = args(SchemaDialect::Postgres);
+        let sql = create_tables_ddl(&args, &[(Table::Orders, schema())]).unwrap();
+        assert_eq!(
+            sql,
+            "CREATE TABLE IF NOT EXISTS orders (\n\
+            \x20   o_orderkey BIGINT NOT NULL,\n\
+            \x20   o_shippriority INTEGER NOT NULL,\n\
+            \x20   o_totalprice DECIMAL(15,2) NOT NULL,\n\
+            \x20   o_orderdate DATE NOT NULL,\n\
+            \x20   o_comment VARCHAR\n\
+            );\n"
+        );
+    }
+
+    #[test]
+    fn create_tables_with_column_types() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("o_totalprice", DataType::Float64, false),
+            Field::new(
+                "o_orderdate",
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                false,
+            ),
+            Field::new(
+                "o_orderpriority",
+                DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8)),
+                false,
+            ),
+        ]));
+        let tables = [(Table::Orders, schema)];
+        let sql = create_tables_ddl(&args(SchemaDialect::Postgres), &tables).unwrap();
+        assert!(sql.contains("o_totalprice DOUBLE PRECISION NOT NULL,"));
+        assert!(sql.contains("o_orderdate TIMESTAMP NOT NULL,"));
+        assert!(sql.contains("o_orderpriority VARCHAR NOT NULL\n"));
+        let sql = create_tables_ddl(&args(SchemaDialect::Mysql), &tables).unwrap();
+        assert!(sql.contains("o_orderdate DATETIME(6) NOT NULL,"));
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "o_orderdate",
+            DataType::Time32(TimeUnit::Second),
+            false,
+        )]));
+        let err = create_tables_ddl(&args(SchemaDialect::Trino), &[(Table::Orders, schema)]);
+        assert_eq!(err.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn create_tables_bigquery() {
+        let tables = [(Table::Orders, schema()), (Table::Region, schema())];
+        let sql = create_tables_ddl(&args(SchemaDialect::Bigquery), &tables).unwrap();
+        assert!(sql.contains("o_shippriority INT64 NOT NULL,"));
+        assert!(sql.contains("o_totalprice NUMERIC(15, 2) NOT NULL,"));
+        assert!(sql.contains("o_comment STRING\n);\n\nCREATE TABLE IF NOT EXISTS region ("));
+    }
+
+    #[test]
+    fn create_tables_with_keys() {
+        let tables: Vec<_> = Table::ALL.iter().map(|t| (*t, t.schema())).collect();
+        let args = SchemaArgs {
+            primary_keys: true,
+            foreign_keys: true,
+            indexes: true,
+            ..args(SchemaDialect::Postgres)
+        };
+        let sql = create_tables_ddl(&args, &tables).unwrap();
+        assert!(sql.contains("    PRIMARY KEY (l_orderkey, l_linenumber)\n);\n"));
+        assert!(sql.contains(
+            "ALTER TABLE lineitem ADD FOREIGN KEY (l_partkey, l_suppkey) REFERENCES partsupp (ps_partkey, ps_suppkey);\n"
+        ));
+        assert_eq!(sql.matches("FOREIGN KEY").count(), 10);
+        assert!(sql.contains("CREATE INDEX l_shipdate_idx ON lineitem (l_shipdate);\n"));
+        // covered by the primary key and the index on both keys
+        assert!(!sql.contains("l_orderkey_idx"));
+        assert!(!sql.contains("l_partkey_idx"));
+
+        // DuckDB creates region before nation, which references it
+        let args = SchemaArgs {
+            dialect: SchemaDialect::Duckdb,
+            ..args
+        };
+        let sql = create_tables_ddl(&args, &tables).unwrap();
+        assert!(!sql.contains("ALTER TABLE"));
+        assert!(sql.starts_with("CREATE TABLE IF NOT EXISTS region ("));
+        assert!(sql.contains("    FOREIGN KEY (n_regionkey) REFERENCES region (r_regionkey)\n);"));
+
+        // only the foreign keys between the selected tables
+        let sql = create_tables_ddl(&args, &tables[6..]).unwrap();
+        assert_eq!(sql.matches("FOREIGN KEY").count(), 1);
+
+        let args = SchemaArgs {
+            dialect: SchemaDialect::Trino,
+            ..args
+        };
+        let err = create_tables_ddl(&args, &tables).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--primary-keys and --foreign-keys can not be used with --dialect trino"
+        );
+    }
+}
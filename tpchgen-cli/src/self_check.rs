@@ -0,0 +1,251 @@
+//! Verification of written output (`--self-check`)
+//!
+//! After a table is written, a deterministic subset of its parts is read back
+//! from the output file and compared with freshly generated Arrow
+//! [`RecordBatch`]es. This catches serialization bugs during the run rather
+//! than when the data is loaded downstream.
+//!
+//! * TBL and CSV files are parsed with the arrow CSV reader, using the
+//!   delimiter and escaping of `--csv-delimiter` and `--csv-escape`
+//! * Parquet files are read with the parquet reader, one row group per part
+//!   (or one file per part with `--parquet-file-per-row-group`)
+
+use crate::OutputFormat;
+use arrow::array::RecordBatch;
+use arrow::compute::concat_batches;
+use arrow::datatypes::SchemaRef;
+use log::info;
+use parquet::arrow::arrow_reader::{ArrowReaderOptions, ParquetRecordBatchReaderBuilder};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tpchgen::csv::{CsvDialect, CsvEscape};
+use tpchgen_arrow::RecordBatchIterator;
+
+/// Returns the indexes of the parts to check: the first, middle and last
+/// parts written.
+pub fn parts_to_check(num_written: usize) -> Vec<usize> {
+    let mut indexes = vec![0, num_written / 2, num_written.saturating_sub(1)];
+    indexes.retain(|&i| i < num_written);
+    indexes.dedup();
+    indexes
+}
+
+/// Checks the parts written to `path`.
+///
+/// * `parts`: the (1 based) parts written to the file, in order
+/// * `chunk_sizes`: for TBL and CSV, the size in bytes of each buffer written
+///   to the file, starting with the header
+/// * `part_paths`: the file each part was written to, if each part was
+///   written to its own file rather than to `path`
+/// * `dialect`: the dialect of CSV files, which must have an ASCII delimiter
+/// * `expected`: creates the expected batches for a part
+#[allow(clippy::too_many_arguments)]
+pub fn check<F, R>(
+    path: &Path,
+    format: OutputFormat,
+    parts: &[i32],
+    chunk_sizes: &[usize],
+    part_paths: &[PathBuf],
+    dialect: &CsvDialect,
+    expected: F,
+) -> io::Result<()>
+where
+    F: Fn(i32) -> R,
+    R: RecordBatchIterator,
+{
+    for index in parts_to_check(parts.len()) {
+        let part = parts[index];
+        let expected = expected(part);
+        let schema = Arc::clone(expected.schema());
+        let expected = concat(&schema, expected.collect())?;
+
+        let actual = match format {
+            OutputFormat::Tbl | OutputFormat::Csv if !part_paths.is_empty() => {
+                // each file starts with the header
+                let data = read_range(
+                    &part_paths[index],
+                    chunk_sizes[0] as u64,
+                    chunk_sizes[index + 1],
+                )?;
+                parse_text(format, &data, dialect, &schema)?
+            }
+            OutputFormat::Tbl | OutputFormat::Csv => {
+                // the first chunk is the header
+                let offset: usize = chunk_sizes[..=index].iter().sum();
+                let len = chunk_sizes[index + 1];
+                let data = read_range(path, offset as u64, len)?;
+                parse_text(format, &data, dialect, &schema)?
+            }
+            OutputFormat::Parquet if !part_paths.is_empty() => {
+                read_row_group(&part_paths[index], 0, &schema)?
+            }
+            OutputFormat::Parquet => read_row_group(path, index, &schema)?,
+            OutputFormat::Vortex
+            | OutputFormat::Orc
+            | OutputFormat::Avro
+            | OutputFormat::ArrowIpc
+            | OutputFormat::Sql
+            | OutputFormat::PgCopy
+            | OutputFormat::Bson => {
+                unreachable!("self check is skipped for {format:?}")
+            }
+        };
+
+        if actual != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Self check failed: part {part} of {} does not match the generated data",
+                    path.display()
+                ),
+            ));
+        }
+        info!(
+            "Self check passed for part {part} of {} ({} rows)",
+            path.display(),
+            expected.num_rows()
+        );
+    }
+    Ok(())
+}
+
+fn read_range(path: &Path, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut data = vec![0; len];
+    file.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// Parses TBL or CSV data (without a header) into a single batch
+fn parse_text(
+    format: OutputFormat,
+    data: &[u8],
+    dialect: &CsvDialect,
+    schema: &SchemaRef,
+) -> io::Result<RecordBatch> {
+    let builder = arrow::csv::ReaderBuilder::new(Arc::clone(schema)).with_header(false);
+    let batches = match format {
+        OutputFormat::Tbl => {
+            // TBL lines end with '|' which the arrow csv parser treats as a
+            // delimiter for a new column, so remove it from each line
+            let mut lines = Vec::with_capacity(data.len());
+            for line in data.split_inclusive(|&b| b == b'\n') {
+                let line = line.strip_suffix(b"\n").unwrap_or(line);
+                lines.extend_from_slice(line.strip_suffix(b"|").unwrap_or(line));
+                lines.push(b'\n');
+            }
+            let reader = builder
+                .with_delimiter(b'|')
+                .build(lines.as_slice())
+                .map_err(io::Error::other)?;
+            reader.collect::<Result<Vec<_>, _>>()
+        }
+        OutputFormat::Csv => {
+            let delimiter = u8::try_from(dialect.delimiter()).expect("ASCII delimiter");
+            let builder = builder.with_delimiter(delimiter);
+            let builder = match dialect.escape() {
+                CsvEscape::Double => builder,
+                CsvEscape::Backslash => builder.with_escape(b'\\'),
+            };
+            builder
+                .build(data)
+                .map_err(io::Error::other)?
+                .collect::<Result<Vec<_>, _>>()
+        }
+        OutputFormat::Parquet
+        | OutputFormat::Vortex
+        | OutputFormat::Orc
+        | OutputFormat::Avro
+        | OutputFormat::ArrowIpc
+        | OutputFormat::Sql
+        | OutputFormat::PgCopy
+        | OutputFormat::Bson => {
+            unreachable!("{format:?} is not parsed by the self check")
+        }
+    }
+    .map_err(io::Error::other)?;
+    concat(schema, batches)
+}
+
+/// Reads the row group at `index` into a single batch
+fn read_row_group(path: &Path, index: usize, schema: &SchemaRef) -> io::Result<RecordBatch> {
+    // The files do not embed the Arrow schema, so supply it to read string
+    // columns as Utf8View
+    let options = ArrowReaderOptions::new().with_schema(Arc::clone(schema));
+    let reader = ParquetRecordBatchReaderBuilder::try_new_with_options(File::open(path)?, options)?
+        .with_row_groups(vec![index])
+        .build()?;
+    let batches = reader
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io::Error::other)?;
+    concat(schema, batches)
+}
+
+fn concat(schema: &SchemaRef, batches: Vec<RecordBatch>) -> io::Result<RecordBatch> {
+    concat_batches(schema, &batches).map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tpchgen::generators::SupplierGenerator;
+    use tpchgen_arrow::SupplierArrow;
+
+    /// Returns the TBL text of each of the 3 parts of supplier at SF 0.01
+    fn supplier_parts() -> Vec<String> {
+        (1..=3)
+            .map(|part| {
+                SupplierGenerator::new(0.01, part, 3)
+                    .iter()
+                    .map(|supplier| format!("{supplier}\n"))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn check_supplier(path: &Path, chunk_sizes: &[usize]) -> io::Result<()> {
+        check(
+            path,
+            OutputFormat::Tbl,
+            &[1, 2, 3],
+            chunk_sizes,
+            &[],
+            &CsvDialect::default(),
+            |part| SupplierArrow::new(SupplierGenerator::new(0.01, part, 3)),
+        )
+    }
+
+    #[test]
+    fn selects_parts() {
+        assert_eq!(parts_to_check(0), Vec::<usize>::new());
+        assert_eq!(parts_to_check(1), vec![0]);
+        assert_eq!(parts_to_check(2), vec![0, 1]);
+        assert_eq!(parts_to_check(10), vec![0, 5, 9]);
+    }
+
+    #[test]
+    fn detects_corrupted_rows() {
+        let dir = std::env::temp_dir().join(format!("tpchgen-self-check-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("supplier.tbl");
+        let mut parts = supplier_parts();
+        // TBL files have no header
+        let chunk_sizes: Vec<_> = std::iter::once(0)
+            .chain(parts.iter().map(String::len))
+            .collect();
+        fs::write(&path, parts.concat()).unwrap();
+        check_supplier(&path, &chunk_sizes).unwrap();
+
+        // change the name of the first supplier of the middle part
+        parts[1] = parts[1].replacen("Supplier#", "Supplier*", 1);
+        fs::write(&path, parts.concat()).unwrap();
+        let err = check_supplier(&path, &chunk_sizes).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("part 2 of"), "{err}");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
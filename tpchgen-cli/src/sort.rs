@@ -0,0 +1,494 @@
+//! Globally sorted output (`--sort-by`)
+//!
+//! `--sort-by l_shipdate` writes `lineitem` ordered by `l_shipdate` across
+//! all of its output files, e.g. for benchmarks of clustering and pruning.
+//!
+//! The table is generated in TBL format into a [`SortSink`], which sorts the
+//! rows in runs of at most `--sort-memory` bytes and spills each run to a
+//! temporary file (in `TMPDIR`). The runs are then merged and the rows are
+//! split into as many [`Chunk`]s as the table has parts, which are written
+//! like converted files (see [`crate::convert`]). The values are the same as
+//! in unsorted output, and rows with equal sort keys stay in the order they
+//! were generated in, so the output is deterministic.
+
+use crate::convert::{Chunk, FirstError};
+use crate::Table;
+use arrow::datatypes::DataType;
+use log::debug;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tpchgen_cli::generate::Sink;
+
+/// A column to sort a table by, e.g. `l_shipdate`
+#[derive(Debug, Clone)]
+pub struct SortColumn {
+    column: String,
+    table: Table,
+    key: SortKey,
+}
+
+impl SortColumn {
+    /// Returns the table of the column
+    pub fn table(&self) -> Table {
+        self.table
+    }
+
+    /// Returns the key to compare TBL lines of the table with
+    pub fn key(&self) -> SortKey {
+        self.key
+    }
+}
+
+impl Display for SortColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.column)
+    }
+}
+
+impl FromStr for SortColumn {
+    type Err = String;
+
+    fn from_str(column: &str) -> Result<Self, Self::Err> {
+        for &table in Table::ALL.iter() {
+            let schema = table.schema();
+            let Ok(index) = schema.index_of(column) else {
+                continue;
+            };
+            let kind = match schema.field(index).data_type() {
+                DataType::Int32 | DataType::Int64 => KeyKind::Integer,
+                DataType::Decimal128(_, _) => KeyKind::Decimal,
+                // dates are formatted as YYYY-MM-DD, so sort as text
+                _ => KeyKind::Text,
+            };
+            return Ok(Self {
+                column: column.to_string(),
+                table,
+                key: SortKey { index, kind },
+            });
+        }
+        Err(format!("unknown column '{column}'"))
+    }
+}
+
+/// How the values of a [`SortKey`] are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyKind {
+    Integer,
+    Decimal,
+    Text,
+}
+
+/// Compares TBL lines by the value of one column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    /// Index of the column in the line
+    index: usize,
+    kind: KeyKind,
+}
+
+impl SortKey {
+    /// Compares the values of the column in TBL lines `a` and `b`
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        let (a, b) = (self.value(a), self.value(b));
+        match self.kind {
+            KeyKind::Integer => Self::integer(a).cmp(&Self::integer(b)),
+            KeyKind::Decimal => Self::decimal(a).total_cmp(&Self::decimal(b)),
+            KeyKind::Text => a.cmp(b),
+        }
+    }
+
+    fn value<'a>(&self, line: &'a str) -> &'a str {
+        line.split('|').nth(self.index).unwrap_or_default()
+    }
+
+    fn integer(value: &str) -> i64 {
+        value.parse().unwrap_or_default()
+    }
+
+    fn decimal(value: &str) -> f64 {
+        value.parse().unwrap_or_default()
+    }
+}
+
+/// A directory of temporary files, removed when dropped
+#[derive(Debug)]
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(table: Table) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "tpchgen-sort-{}-{}",
+            std::process::id(),
+            table.name()
+        ));
+        fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.0) {
+            debug!("Could not remove {}: {e}", self.0.display());
+        }
+    }
+}
+
+/// The rows sorted by a [`SortSink`], available once it is flushed
+#[derive(Debug, Default, Clone)]
+pub struct Sorted(Arc<Mutex<Option<SortedRows>>>);
+
+impl Sorted {
+    /// Returns the sorted rows
+    ///
+    /// Panics if the [`SortSink`] was not flushed
+    pub fn take(&self) -> SortedRows {
+        self.0
+            .lock()
+            .unwrap()
+            .take()
+            .expect("sort sink not flushed")
+    }
+}
+
+/// A [`Sink`] that sorts the TBL lines written to it using at most about
+/// `memory_limit` bytes of memory
+pub struct SortSink {
+    key: SortKey,
+    memory_limit: usize,
+    /// Lines of the current run
+    lines: Vec<String>,
+    /// Bytes used by `lines`
+    memory: usize,
+    dir: Option<TempDir>,
+    table: Table,
+    runs: Vec<PathBuf>,
+    rows: usize,
+    sorted: Sorted,
+}
+
+impl SortSink {
+    /// Sorts lines of `table` by `key` into `sorted`
+    pub fn new(table: Table, key: SortKey, memory_limit: usize, sorted: Sorted) -> Self {
+        Self {
+            key,
+            memory_limit,
+            lines: Vec::new(),
+            memory: 0,
+            dir: None,
+            table,
+            runs: Vec::new(),
+            rows: 0,
+            sorted,
+        }
+    }
+
+    /// Sorts the current run (stable, so equal keys keep their order)
+    fn sort_run(&mut self) {
+        let key = self.key;
+        self.lines.sort_by(|a, b| key.compare(a, b));
+    }
+
+    /// Sorts the current run and writes it to a temporary file
+    fn spill(&mut self) -> io::Result<()> {
+        self.sort_run();
+        let dir = match &self.dir {
+            Some(dir) => dir,
+            None => self.dir.insert(TempDir::new(self.table)?),
+        };
+        let path = dir.0.join(format!("run-{}.tbl", self.runs.len()));
+        debug!(
+            "Spilling {} rows of {} to {}",
+            self.lines.len(),
+            self.table,
+            path.display()
+        );
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for line in self.lines.drain(..) {
+            writer.write_all(line.as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        self.runs.push(path);
+        self.memory = 0;
+        Ok(())
+    }
+}
+
+impl Sink for SortSink {
+    fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error> {
+        let text = std::str::from_utf8(buffer)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            self.memory += line.len() + size_of::<String>();
+            self.lines.push(line.to_string());
+            self.rows += 1;
+        }
+        if self.memory > self.memory_limit {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn flush(mut self) -> Result<(), io::Error> {
+        let rows = if self.runs.is_empty() {
+            self.sort_run();
+            Rows::Memory(std::mem::take(&mut self.lines).into_iter())
+        } else {
+            if !self.lines.is_empty() {
+                self.spill()?;
+            }
+            Rows::Merge(Merge::new(self.key, &self.runs)?)
+        };
+        *self.sorted.0.lock().unwrap() = Some(SortedRows {
+            rows,
+            count: self.rows,
+            dir: self.dir.take(),
+        });
+        Ok(())
+    }
+}
+
+/// The sorted TBL lines of a table
+#[derive(Debug)]
+pub struct SortedRows {
+    rows: Rows,
+    /// Number of rows
+    count: usize,
+    /// Directory of the runs, removed once the rows are read
+    dir: Option<TempDir>,
+}
+
+impl SortedRows {
+    /// Splits the rows into `num_parts` chunks of about the same number of
+    /// rows, named `name` in error messages
+    ///
+    /// Errors reading the runs are recorded in `error`.
+    pub fn into_chunks(self, num_parts: i32, name: &str, error: FirstError) -> SortedChunks {
+        let rows_per_chunk = self.count.div_ceil(num_parts.max(1) as usize);
+        SortedChunks {
+            rows: self,
+            name: name.into(),
+            rows_per_chunk,
+            remaining: num_parts,
+            line: 1,
+            error,
+        }
+    }
+}
+
+/// Iterator over the [`Chunk`]s of [`SortedRows`]
+pub struct SortedChunks {
+    rows: SortedRows,
+    name: Arc<str>,
+    rows_per_chunk: usize,
+    /// Number of chunks still to return
+    remaining: i32,
+    /// Line number of the first line of the next chunk
+    line: usize,
+    error: FirstError,
+}
+
+impl Iterator for SortedChunks {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Chunk> {
+        if self.remaining == 0 {
+            // remove the runs as soon as they are read
+            self.rows.dir.take();
+            return None;
+        }
+        self.remaining -= 1;
+        let mut text = String::new();
+        for line in self.rows.rows.by_ref().take(self.rows_per_chunk) {
+            match line {
+                Ok(line) => {
+                    text.push_str(&line);
+                    text.push('\n');
+                }
+                Err(e) => {
+                    self.error.set(e);
+                    self.remaining = 0;
+                    return None;
+                }
+            }
+        }
+        let chunk = Chunk::new(&self.name, self.line, text);
+        self.line += self.rows_per_chunk;
+        Some(chunk)
+    }
+}
+
+/// Sorted lines, either sorted in memory or merged from sorted runs
+#[derive(Debug)]
+enum Rows {
+    Memory(std::vec::IntoIter<String>),
+    Merge(Merge),
+}
+
+impl Iterator for Rows {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Rows::Memory(lines) => lines.next().map(Ok),
+            Rows::Merge(merge) => merge.next(),
+        }
+    }
+}
+
+/// The next line of a run in a [`Merge`]
+#[derive(Debug)]
+struct Head {
+    key: SortKey,
+    line: String,
+    run: usize,
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // runs were sorted in the order they were generated, so equal keys
+        // are taken from earlier runs first
+        self.key
+            .compare(&self.line, &other.line)
+            .then(self.run.cmp(&other.run))
+    }
+}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head {}
+
+/// Merges sorted runs, keeping the next line of each run in a heap
+#[derive(Debug)]
+struct Merge {
+    key: SortKey,
+    runs: Vec<Lines<BufReader<File>>>,
+    heap: BinaryHeap<Reverse<Head>>,
+}
+
+impl Merge {
+    fn new(key: SortKey, paths: &[PathBuf]) -> io::Result<Self> {
+        let mut merge = Self {
+            key,
+            runs: Vec::with_capacity(paths.len()),
+            heap: BinaryHeap::with_capacity(paths.len()),
+        };
+        for path in paths {
+            merge.runs.push(BufReader::new(File::open(path)?).lines());
+            merge.push_next(merge.runs.len() - 1)?;
+        }
+        Ok(merge)
+    }
+
+    /// Pushes the next line of `run`, if any, on the heap
+    fn push_next(&mut self, run: usize) -> io::Result<()> {
+        if let Some(line) = self.runs[run].next() {
+            let key = self.key;
+            self.heap.push(Reverse(Head {
+                key,
+                line: line?,
+                run,
+            }));
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Merge {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse(head) = self.heap.pop()?;
+        if let Err(e) = self.push_next(head.run) {
+            return Some(Err(e));
+        }
+        Some(Ok(head.line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tpchgen::generators::LineItemGenerator;
+
+    /// Sorts `lines` with a [`SortSink`], writing them in buffers of
+    /// `lines_per_buffer` lines, into `num_parts` chunks
+    fn sort(
+        column: &SortColumn,
+        lines: &[String],
+        lines_per_buffer: usize,
+        memory_limit: usize,
+        num_parts: i32,
+    ) -> Vec<String> {
+        let sorted = Sorted::default();
+        let mut sink = SortSink::new(column.table(), column.key(), memory_limit, sorted.clone());
+        for buffer in lines.chunks(lines_per_buffer) {
+            let buffer: String = buffer.iter().map(|line| format!("{line}\n")).collect();
+            sink.sink(buffer.as_bytes()).unwrap();
+        }
+        sink.flush().unwrap();
+        let error = FirstError::default();
+        let chunks: Vec<_> = sorted
+            .take()
+            .into_chunks(num_parts, "lineitem.tbl", error.clone())
+            .collect();
+        error.take().unwrap();
+        assert_eq!(chunks.len(), num_parts as usize);
+        chunks
+            .iter()
+            .flat_map(|chunk| chunk.text().lines().map(str::to_string))
+            .collect()
+    }
+
+    #[test]
+    fn sorts_with_and_without_spilling() {
+        let lines: Vec<_> = LineItemGenerator::new(0.001, 1, 1)
+            .iter()
+            .map(|row| row.to_string())
+            .collect();
+        for column in ["l_shipdate", "l_extendedprice", "l_suppkey", "l_comment"] {
+            let column: SortColumn = column.parse().unwrap();
+            assert_eq!(column.table(), Table::Lineitem);
+            let mut expected = lines.clone();
+            expected.sort_by(|a, b| column.key().compare(a, b));
+            // in memory
+            assert_eq!(sort(&column, &lines, 1000, usize::MAX, 1), expected);
+            // about 10 runs of 600 rows
+            assert_eq!(sort(&column, &lines, 100, 100_000, 7), expected);
+        }
+    }
+
+    #[test]
+    fn sort_columns() {
+        let column: SortColumn = "o_totalprice".parse().unwrap();
+        assert_eq!(column.table(), Table::Orders);
+        let key = column.key();
+        assert_eq!(key.kind, KeyKind::Decimal);
+        let order = |price| format!("1|2|O|{price}|1996-01-02|5-LOW|Clerk#000000951|0|x|");
+        assert_eq!(key.compare(&order("9.50"), &order("10.00")), Ordering::Less);
+
+        let column: SortColumn = "l_shipdate".parse().unwrap();
+        assert_eq!(column.key().kind, KeyKind::Text);
+        assert_eq!(
+            "l_nope".parse::<SortColumn>().unwrap_err(),
+            "unknown column 'l_nope'"
+        );
+    }
+}
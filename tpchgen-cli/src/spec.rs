@@ -0,0 +1,197 @@
+//! Dataset specifications (`build`)
+//!
+//! `tpchgen-cli build datasets.toml` generates several datasets in one
+//! invocation, described by a TOML file with one table per dataset. The keys
+//! of a dataset are the long names of the command line options, and the
+//! optional `[defaults]` table holds options shared by all datasets:
+//!
+//! ```toml
+//! [defaults]
+//! format = "parquet"
+//! parquet-compression = "ZSTD(1)"
+//!
+//! [datasets.sf1]
+//! scale-factor = 1
+//!
+//! [datasets.sf10-small]
+//! scale-factor = 10
+//! exclude-tables = ["lineitem", "orders"]
+//! format = "csv"
+//! output-dir = "s3://bucket/tpch/sf10-small"
+//! ```
+//!
+//! Each dataset is written to the directory named after it unless it sets
+//! `output-dir`. Values are passed to the option as if given on the command
+//! line: `true` for flags, arrays for options that take several values.
+//! The datasets share the process, and thus the distributions and text pool,
+//! and `--jobs` datasets are generated at the same time.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Arguments of the `build` subcommand
+#[derive(Debug, clap::Args)]
+pub struct BuildArgs {
+    /// TOML file describing the datasets, see the module documentation
+    pub spec: PathBuf,
+
+    /// Number of datasets generated at the same time
+    #[arg(long, default_value_t = 1)]
+    pub jobs: usize,
+}
+
+/// A dataset of a specification
+#[derive(Debug, PartialEq)]
+pub struct Dataset {
+    pub name: String,
+    /// Command line arguments for the dataset, without the program name
+    pub args: Vec<String>,
+}
+
+/// Reads the datasets of the specification at `path`
+pub fn read_spec(path: &Path) -> io::Result<Vec<Dataset>> {
+    let text = std::fs::read_to_string(path)?;
+    parse_spec(&text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid dataset specification {}: {e}", path.display()),
+        )
+    })
+}
+
+/// Parses the datasets of a specification
+pub fn parse_spec(text: &str) -> Result<Vec<Dataset>, String> {
+    let mut spec: Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+    let defaults = match spec.remove("defaults") {
+        Some(Value::Table(defaults)) => defaults,
+        Some(_) => return Err("`defaults` must be a table".to_string()),
+        None => Table::new(),
+    };
+    let datasets = match spec.remove("datasets") {
+        Some(Value::Table(datasets)) if !datasets.is_empty() => datasets,
+        Some(Value::Table(_)) | None => return Err("no [datasets.<name>] tables".to_string()),
+        Some(_) => return Err("`datasets` must be a table".to_string()),
+    };
+    if let Some(key) = spec.keys().next() {
+        return Err(format!(
+            "unknown key `{key}`, expected `defaults` or `datasets`"
+        ));
+    }
+
+    let mut result = Vec::new();
+    for (name, options) in datasets {
+        let Value::Table(options) = options else {
+            return Err(format!("dataset `{name}` must be a table"));
+        };
+        let mut args = Vec::new();
+        if !defaults.contains_key("output-dir") && !options.contains_key("output-dir") {
+            args.push(format!("--output-dir={name}"));
+        }
+        // options of the dataset replace the defaults
+        let defaults = defaults
+            .iter()
+            .filter(|(key, _)| !options.contains_key(*key));
+        for (key, value) in defaults.chain(&options) {
+            option_args(key, value, &mut args).map_err(|e| format!("dataset `{name}`: {e}"))?;
+        }
+        result.push(Dataset { name, args });
+    }
+    Ok(result)
+}
+
+/// Appends the command line arguments for option `key` with `value`
+fn option_args(key: &str, value: &Value, args: &mut Vec<String>) -> Result<(), String> {
+    match value {
+        Value::Boolean(true) => args.push(format!("--{key}")),
+        Value::Boolean(false) => {}
+        Value::String(s) => args.push(format!("--{key}={s}")),
+        Value::Integer(i) => args.push(format!("--{key}={i}")),
+        Value::Float(f) => args.push(format!("--{key}={f}")),
+        Value::Array(values) => {
+            for value in values {
+                match value {
+                    Value::Array(_) | Value::Table(_) | Value::Boolean(_) => {
+                        return Err(format!("`{key}` must be an array of values"))
+                    }
+                    value => option_args(key, value, args)?,
+                }
+            }
+        }
+        Value::Table(_) | Value::Datetime(_) => {
+            return Err(format!(
+                "`{key}` must be a string, number, boolean or array"
+            ))
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_datasets() {
+        let spec = r#"
+            [defaults]
+            format = "parquet"
+            num-threads = 4
+
+            [datasets.sf1]
+            scale-factor = 1
+            uuid-column = true
+
+            [datasets.small]
+            scale-factor = 0.5
+            format = "csv"
+            exclude-tables = ["lineitem", "orders"]
+            output-dir = "out/small"
+            edge-cases = false
+        "#;
+        let datasets = parse_spec(spec).unwrap();
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            datasets,
+            vec![
+                Dataset {
+                    name: "sf1".to_string(),
+                    args: args(&[
+                        "--output-dir=sf1",
+                        "--format=parquet",
+                        "--num-threads=4",
+                        "--scale-factor=1",
+                        "--uuid-column",
+                    ]),
+                },
+                Dataset {
+                    name: "small".to_string(),
+                    args: args(&[
+                        "--num-threads=4",
+                        "--scale-factor=0.5",
+                        "--format=csv",
+                        "--exclude-tables=lineitem",
+                        "--exclude-tables=orders",
+                        "--output-dir=out/small",
+                    ]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_specs() {
+        let error = |spec| parse_spec(spec).unwrap_err();
+        assert_eq!(error(""), "no [datasets.<name>] tables");
+        assert_eq!(
+            error("jobs = 2\n[datasets.a]"),
+            "unknown key `jobs`, expected `defaults` or `datasets`"
+        );
+        assert_eq!(error("datasets = 1"), "`datasets` must be a table");
+        assert_eq!(
+            error("[datasets.a]\ntables = [[1]]"),
+            "dataset `a`: `tables` must be an array of values"
+        );
+        assert!(error("[datasets").starts_with("TOML parse error"));
+    }
+}
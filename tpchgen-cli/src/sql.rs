@@ -0,0 +1,286 @@
+//! SQL `INSERT` statement output format (`--format=sql`)
+//!
+//! Each file starts with a `CREATE TABLE IF NOT EXISTS` statement for the
+//! table followed by multi-row `INSERT INTO ... VALUES` statements of at most
+//! `--sql-rows-per-statement` rows, so small datasets can be piped into
+//! `psql`, `sqlite3` or `mysql` without `COPY` or `LOAD DATA` support:
+//!
+//! ```shell
+//! tpchgen-cli -s 0.1 --format=sql --sql-dialect sqlite --stdout | sqlite3 tpch.db
+//! ```
+//!
+//! Identifiers and string literals are quoted for `--sql-dialect`. Decimals
+//! are written as numeric literals and dates as `'YYYY-MM-DD'` strings, which
+//! all dialects convert to the column type.
+//!
+//! The statements are also used to load tables with `--mysql-url`.
+
+use arrow::array::{Array, AsArray, RecordBatch};
+use arrow::datatypes::{DataType, Int32Type, Int64Type, SchemaRef};
+use arrow::util::display::array_value_to_string;
+use clap::ValueEnum;
+use std::fmt::Write;
+use std::io;
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::generate::Source;
+
+/// SQL dialect of the generated statements
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum SqlDialect {
+    /// PostgreSQL: `"identifiers"`, standard conforming strings
+    #[default]
+    Postgres,
+    /// MySQL and MariaDB: `` `identifiers` ``, backslash escapes in strings
+    Mysql,
+    /// SQLite: `"identifiers"`, standard strings
+    Sqlite,
+}
+
+impl SqlDialect {
+    /// Appends `name` to `sql` as a quoted identifier
+    fn write_identifier(&self, sql: &mut String, name: &str) {
+        let quote = match self {
+            SqlDialect::Mysql => '`',
+            SqlDialect::Postgres | SqlDialect::Sqlite => '"',
+        };
+        sql.push(quote);
+        for c in name.chars() {
+            if c == quote {
+                sql.push(quote);
+            }
+            sql.push(c);
+        }
+        sql.push(quote);
+    }
+
+    /// Appends `value` to `sql` as a quoted and escaped string literal
+    fn write_string(&self, sql: &mut String, value: &str) {
+        sql.push('\'');
+        for c in value.chars() {
+            match c {
+                '\'' => sql.push_str("''"),
+                // MySQL treats backslashes in strings as escapes by default
+                '\\' if *self == SqlDialect::Mysql => sql.push_str("\\\\"),
+                c => sql.push(c),
+            }
+        }
+        sql.push('\'');
+    }
+
+    /// Returns the column type for an Arrow type produced by `tpchgen-arrow`,
+    /// or an error for other types
+    fn column_type(&self, data_type: &DataType) -> io::Result<String> {
+        Ok(match data_type {
+            DataType::Int32 => "INT".to_string(),
+            DataType::Int64 => "BIGINT".to_string(),
+            DataType::Decimal128(precision, scale) => format!("DECIMAL({precision},{scale})"),
+            DataType::Date32 => "DATE".to_string(),
+            // MySQL requires a length for VARCHAR
+            DataType::Utf8 | DataType::Utf8View | DataType::LargeUtf8 => match self {
+                SqlDialect::Mysql => "VARCHAR(255)".to_string(),
+                SqlDialect::Postgres | SqlDialect::Sqlite => "VARCHAR".to_string(),
+            },
+            other => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("SQL output does not support columns of type {other}"),
+                ))
+            }
+        })
+    }
+}
+
+/// Returns the `CREATE TABLE IF NOT EXISTS` statement for `table`, or an
+/// error if a column has a type without an SQL equivalent
+pub fn create_table_sql(
+    dialect: SqlDialect,
+    table: &str,
+    schema: &SchemaRef,
+) -> io::Result<String> {
+    let mut sql = "CREATE TABLE IF NOT EXISTS ".to_string();
+    dialect.write_identifier(&mut sql, table);
+    sql.push_str(" (");
+    for (i, field) in schema.fields().iter().enumerate() {
+        if i > 0 {
+            sql.push_str(", ");
+        }
+        dialect.write_identifier(&mut sql, field.name());
+        write!(sql, " {} NOT NULL", dialect.column_type(field.data_type())?).unwrap();
+    }
+    sql.push(')');
+    Ok(sql)
+}
+
+/// Returns a multi-row `INSERT` statement for all rows of `batch`, with one
+/// row per line
+pub fn insert_sql(dialect: SqlDialect, table: &str, batch: &RecordBatch) -> String {
+    let mut sql = "INSERT INTO ".to_string();
+    dialect.write_identifier(&mut sql, table);
+    sql.push_str(" VALUES ");
+    for row in 0..batch.num_rows() {
+        if row > 0 {
+            sql.push_str(",\n");
+        }
+        sql.push('(');
+        for (i, column) in batch.columns().iter().enumerate() {
+            if i > 0 {
+                sql.push(',');
+            }
+            write_literal(dialect, &mut sql, column.as_ref(), row);
+        }
+        sql.push(')');
+    }
+    sql
+}
+
+/// Appends the value at `row` of `array` to `sql` as a literal
+fn write_literal(dialect: SqlDialect, sql: &mut String, array: &dyn Array, row: usize) {
+    match array.data_type() {
+        DataType::Int32 => write!(sql, "{}", array.as_primitive::<Int32Type>().value(row)).unwrap(),
+        DataType::Int64 => write!(sql, "{}", array.as_primitive::<Int64Type>().value(row)).unwrap(),
+        DataType::Utf8View => dialect.write_string(sql, array.as_string_view().value(row)),
+        DataType::Utf8 => dialect.write_string(sql, array.as_string::<i32>().value(row)),
+        // decimals are formatted by arrow, e.g. 901.00
+        DataType::Decimal128(_, _) => {
+            let value = array_value_to_string(array, row).expect("formatting value");
+            sql.push_str(&value);
+        }
+        // dates are formatted by arrow, e.g. 1996-01-02
+        _ => {
+            let value = array_value_to_string(array, row).expect("formatting value");
+            dialect.write_string(sql, &value)
+        }
+    }
+}
+
+/// A [`Source`] that writes the batches of a [`RecordBatchIterator`] as
+/// `INSERT` statements
+///
+/// The schema of the batches must be checked with [`create_table_sql`].
+pub struct SqlSource<I> {
+    table: &'static str,
+    batches: I,
+    dialect: SqlDialect,
+    rows_per_statement: usize,
+}
+
+impl<I: RecordBatchIterator> SqlSource<I> {
+    pub fn new(table: &'static str, batches: I) -> Self {
+        Self {
+            table,
+            batches,
+            dialect: SqlDialect::default(),
+            rows_per_statement: 1000,
+        }
+    }
+
+    /// Set the dialect of the statements
+    pub fn with_dialect(mut self, dialect: SqlDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Set the maximum number of rows in each `INSERT` statement
+    pub fn with_rows_per_statement(mut self, rows_per_statement: usize) -> Self {
+        self.rows_per_statement = rows_per_statement;
+        self
+    }
+}
+
+impl<I: RecordBatchIterator> Source for SqlSource<I> {
+    fn header(&self, mut buffer: Vec<u8>) -> Vec<u8> {
+        let sql = create_table_sql(self.dialect, self.table, self.batches.schema())
+            .expect("schema checked with create_table_sql");
+        buffer.extend_from_slice(sql.as_bytes());
+        buffer.extend_from_slice(b";\n");
+        buffer
+    }
+
+    fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+        for batch in self.batches {
+            let mut offset = 0;
+            while offset < batch.num_rows() {
+                let len = self.rows_per_statement.min(batch.num_rows() - offset);
+                let sql = insert_sql(self.dialect, self.table, &batch.slice(offset, len));
+                buffer.extend_from_slice(sql.as_bytes());
+                buffer.extend_from_slice(b";\n");
+                offset += len;
+            }
+        }
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Date32Array, Decimal128Array, Int64Array, StringViewArray};
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+    use tpchgen::generators::NationGenerator;
+    use tpchgen_arrow::NationArrow;
+
+    #[test]
+    fn quotes_for_dialect() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("k", DataType::Int64, false),
+            Field::new("s", DataType::Utf8View, false),
+            Field::new("d", DataType::Decimal128(15, 2), false),
+            Field::new("t", DataType::Date32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(StringViewArray::from(vec!["it's", r"a\b"])),
+                Arc::new(
+                    Decimal128Array::from(vec![90100, -5])
+                        .with_precision_and_scale(15, 2)
+                        .unwrap(),
+                ),
+                Arc::new(Date32Array::from(vec![9497, 0])),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            insert_sql(SqlDialect::Postgres, "t", &batch),
+            "INSERT INTO \"t\" VALUES (1,'it''s',901.00,'1996-01-02'),\n(2,'a\\b',-0.05,'1970-01-01')"
+        );
+        assert_eq!(
+            insert_sql(SqlDialect::Mysql, "t", &batch),
+            "INSERT INTO `t` VALUES (1,'it''s',901.00,'1996-01-02'),\n(2,'a\\\\b',-0.05,'1970-01-01')"
+        );
+        assert_eq!(
+            create_table_sql(SqlDialect::Sqlite, "t", &schema).unwrap(),
+            r#"CREATE TABLE IF NOT EXISTS "t" ("k" BIGINT NOT NULL, "s" VARCHAR NOT NULL, "d" DECIMAL(15,2) NOT NULL, "t" DATE NOT NULL)"#
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_columns() {
+        let schema = Arc::new(Schema::new(vec![Field::new("f", DataType::Float64, false)]));
+        let error = create_table_sql(SqlDialect::Postgres, "t", &schema).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(
+            error.to_string(),
+            "SQL output does not support columns of type Float64"
+        );
+    }
+
+    #[test]
+    fn splits_statements() {
+        let source = SqlSource::new("nation", NationArrow::new(NationGenerator::default()))
+            .with_dialect(SqlDialect::Sqlite)
+            .with_rows_per_statement(10);
+        let header = String::from_utf8(source.header(Vec::new())).unwrap();
+        assert!(header.starts_with(r#"CREATE TABLE IF NOT EXISTS "nation" ("n_nationkey" BIGINT"#));
+        let sql = String::from_utf8(source.create(Vec::new())).unwrap();
+        // one line per row of the 25 nations
+        assert_eq!(sql.lines().count(), 25);
+        let statements: Vec<_> = sql.split_terminator(";\n").collect();
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].starts_with(r#"INSERT INTO "nation" VALUES (0,'ALGERIA',0,"#));
+        assert!(statements.iter().all(|s| s.ends_with(')')));
+        assert_eq!(statements[2].matches("),\n(").count(), 4);
+    }
+}
@@ -1,12 +1,143 @@
 //! Statistics reporter for TPCH data generation.
+//!
+//! [`WriteStatistics`] counts the chunks (e.g. buffers, row groups or files)
+//! and bytes written to one output and logs a summary when it is dropped.
+//! Embedders can register [`WriteCallbacks`] to drive their own progress
+//! displays and metrics from the same events, and wrap any [`Sink`] in a
+//! [`StatisticsSink`] to record the buffers written by
+//! [`generate_in_chunks`](crate::generate::generate_in_chunks).
+//!
+//! [`Metrics`] collects the totals of each table from the callbacks, and the
+//! number and compression ratio of the Parquet row groups, as JSON for
+//! tracking the performance of generation, e.g. in CI.
+//!
+//! # Example
+//! ```
+//! use std::io;
+//! use std::sync::{Arc, Mutex};
+//! use tpchgen_cli::generate::Sink;
+//! use tpchgen_cli::statistics::{StatisticsSink, WriteCallbacks, WriteStatistics};
+//!
+//! /// Discards the output
+//! struct NullSink;
+//!
+//! impl Sink for NullSink {
+//!     fn sink(&mut self, _buffer: &[u8]) -> Result<(), io::Error> {
+//!         Ok(())
+//!     }
+//!
+//!     fn flush(self) -> Result<(), io::Error> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let chunks = Arc::new(Mutex::new(Vec::new()));
+//! let total = Arc::new(Mutex::new(None));
+//! let callbacks = WriteCallbacks::default()
+//!     .on_chunk_written({
+//!         let chunks = Arc::clone(&chunks);
+//!         move |progress| chunks.lock().unwrap().push(progress.num_bytes)
+//!     })
+//!     .on_table_finished({
+//!         let total = Arc::clone(&total);
+//!         move |progress| *total.lock().unwrap() = Some(progress.num_bytes)
+//!     });
+//! let statistics = WriteStatistics::new("buffers").with_callbacks(callbacks);
+//! let mut sink = StatisticsSink::new(NullSink, statistics);
+//! sink.sink(b"1|a|\n").unwrap();
+//! sink.sink(b"2|b|\n3|c|\n").unwrap();
+//! sink.flush().unwrap();
+//!
+//! assert_eq!(*chunks.lock().unwrap(), vec![5, 15]);
+//! assert_eq!(*total.lock().unwrap(), Some(15));
+//! ```
 
+use crate::generate::Sink;
 use log::{debug, info};
-use std::time::Instant;
+use serde_json::json;
+use std::fmt::Debug;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Totals of a [`WriteStatistics`] when a callback is called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteProgress {
+    /// Number of chunks written so far
+    pub num_chunks: usize,
+    /// Number of bytes written so far
+    pub num_bytes: usize,
+    /// Time since the [`WriteStatistics`] was created
+    pub elapsed: Duration,
+}
+
+/// A Parquet row group that was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowGroupClosed {
+    pub num_rows: u64,
+    /// Compressed size of the row group in bytes
+    pub compressed_bytes: u64,
+    /// Uncompressed size of the row group in bytes
+    pub uncompressed_bytes: u64,
+}
+
+type Callback<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// Callbacks called by [`WriteStatistics`]
+///
+/// Callbacks are called on the thread doing the writing, so they should
+/// return quickly. Clones share the same callbacks.
+#[derive(Clone, Default)]
+pub struct WriteCallbacks {
+    chunk_written: Vec<Callback<WriteProgress>>,
+    row_group_closed: Vec<Callback<RowGroupClosed>>,
+    table_finished: Vec<Callback<WriteProgress>>,
+}
+
+impl WriteCallbacks {
+    /// Call `callback` after each chunk is written
+    pub fn on_chunk_written(
+        mut self,
+        callback: impl Fn(&WriteProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.chunk_written.push(Arc::new(callback));
+        self
+    }
+
+    /// Call `callback` after each Parquet row group is closed
+    pub fn on_row_group_closed(
+        mut self,
+        callback: impl Fn(&RowGroupClosed) + Send + Sync + 'static,
+    ) -> Self {
+        self.row_group_closed.push(Arc::new(callback));
+        self
+    }
+
+    /// Call `callback` with the totals once the output is finished
+    pub fn on_table_finished(
+        mut self,
+        callback: impl Fn(&WriteProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.table_finished.push(Arc::new(callback));
+        self
+    }
+}
+
+impl Debug for WriteCallbacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WriteCallbacks")
+            .field("chunk_written", &self.chunk_written.len())
+            .field("row_group_closed", &self.row_group_closed.len())
+            .field("table_finished", &self.table_finished.len())
+            .finish()
+    }
+}
 
 /// Statistics for writing data to a file
 ///
-/// Reports the statistics on drop
-#[derive(Clone, Debug)]
+/// Reports the statistics, and calls the `on_table_finished` callbacks, on
+/// drop
+#[derive(Debug)]
 pub struct WriteStatistics {
     /// Time at which the writer was created
     start: Instant,
@@ -15,6 +146,7 @@ pub struct WriteStatistics {
     chunk_label: String,
     /// total bytes written
     num_bytes: usize,
+    callbacks: WriteCallbacks,
 }
 
 impl WriteStatistics {
@@ -25,18 +157,64 @@ impl WriteStatistics {
             num_chunks: 0,
             chunk_label: chunk_label.into(),
             num_bytes: 0,
+            callbacks: WriteCallbacks::default(),
         }
     }
 
+    /// Call `callbacks` when chunks are written and on drop
+    pub fn with_callbacks(mut self, callbacks: WriteCallbacks) -> Self {
+        self.callbacks = callbacks;
+        self
+    }
+
     /// Increment chunk count
+    ///
+    /// Calls the `on_chunk_written` callbacks once for each chunk.
     pub fn increment_chunks(&mut self, num_chunks: usize) {
-        self.num_chunks += num_chunks;
+        for _ in 0..num_chunks {
+            self.num_chunks += 1;
+            if !self.callbacks.chunk_written.is_empty() {
+                let progress = self.progress();
+                for callback in &self.callbacks.chunk_written {
+                    callback(&progress);
+                }
+            }
+        }
     }
 
     /// Increment byte count
     pub fn increment_bytes(&mut self, num_bytes: usize) {
         self.num_bytes += num_bytes;
     }
+
+    /// Record a closed Parquet row group, calling the `on_row_group_closed`
+    /// callbacks
+    ///
+    /// This does not change the counts, which are incremented separately.
+    pub fn row_group_closed(
+        &mut self,
+        num_rows: u64,
+        compressed_bytes: u64,
+        uncompressed_bytes: u64,
+    ) {
+        let row_group = RowGroupClosed {
+            num_rows,
+            compressed_bytes,
+            uncompressed_bytes,
+        };
+        for callback in &self.callbacks.row_group_closed {
+            callback(&row_group);
+        }
+    }
+
+    /// Returns the current totals
+    pub fn progress(&self) -> WriteProgress {
+        WriteProgress {
+            num_chunks: self.num_chunks,
+            num_bytes: self.num_bytes,
+            elapsed: self.start.elapsed(),
+        }
+    }
 }
 
 impl Drop for WriteStatistics {
@@ -52,5 +230,301 @@ impl Drop for WriteStatistics {
             "Wrote {} bytes in {} {}  {mb_per_chunk:.02} MB/{}",
             self.num_bytes, self.num_chunks, self.chunk_label, self.chunk_label
         );
+        let progress = self.progress();
+        for callback in &self.callbacks.table_finished {
+            callback(&progress);
+        }
+    }
+}
+
+/// Totals of the outputs of one table, see [`Metrics`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableMetrics {
+    pub table: String,
+    /// Time from the creation of the first callbacks of the table until its
+    /// last output was finished
+    pub elapsed: Duration,
+    /// Number of outputs (each with its own [`WriteStatistics`]) finished
+    pub num_outputs: usize,
+    pub num_chunks: usize,
+    pub num_bytes: usize,
+    /// Number of Parquet row groups, and their rows and sizes
+    pub num_row_groups: usize,
+    pub row_group_rows: u64,
+    pub row_group_compressed_bytes: u64,
+    pub row_group_uncompressed_bytes: u64,
+}
+
+impl TableMetrics {
+    /// Returns the ratio of the uncompressed to the compressed size of the
+    /// Parquet row groups, if any
+    pub fn compression_ratio(&self) -> Option<f64> {
+        (self.row_group_compressed_bytes > 0).then(|| {
+            self.row_group_uncompressed_bytes as f64 / self.row_group_compressed_bytes as f64
+        })
+    }
+}
+
+/// Collects the [`TableMetrics`] of each table from the [`WriteCallbacks`]
+/// returned by [`Metrics::callbacks`]
+///
+/// # Example
+/// ```
+/// # use tpchgen_cli::statistics::{Metrics, WriteStatistics};
+/// let metrics = Metrics::default();
+/// let mut statistics = WriteStatistics::new("row groups").with_callbacks(metrics.callbacks("nation"));
+/// statistics.row_group_closed(25, 1000, 3000);
+/// statistics.increment_chunks(1);
+/// statistics.increment_bytes(1200);
+/// drop(statistics);
+///
+/// let nation = &metrics.tables()[0];
+/// assert_eq!((nation.num_chunks, nation.num_bytes, nation.num_row_groups), (1, 1200, 1));
+/// assert_eq!(nation.compression_ratio(), Some(3.0));
+/// assert_eq!(metrics.to_json()[0]["table"], "nation");
+/// ```
+///
+/// Clones share the same metrics.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    /// The metrics of each table, and the time its first callbacks were
+    /// created
+    tables: Arc<Mutex<Vec<(Instant, TableMetrics)>>>,
+}
+
+impl Metrics {
+    /// Returns callbacks that record the outputs written with them as the
+    /// outputs of `table`
+    ///
+    /// The table starts when its callbacks are first created, later
+    /// callbacks (e.g. when retrying) add to the same totals.
+    pub fn callbacks(&self, table: impl Into<String>) -> WriteCallbacks {
+        let table = table.into();
+        let index = {
+            let mut tables = self.tables.lock().unwrap();
+            match tables.iter().position(|(_, t)| t.table == table) {
+                Some(index) => index,
+                None => {
+                    tables.push((
+                        Instant::now(),
+                        TableMetrics {
+                            table,
+                            elapsed: Duration::ZERO,
+                            num_outputs: 0,
+                            num_chunks: 0,
+                            num_bytes: 0,
+                            num_row_groups: 0,
+                            row_group_rows: 0,
+                            row_group_compressed_bytes: 0,
+                            row_group_uncompressed_bytes: 0,
+                        },
+                    ));
+                    tables.len() - 1
+                }
+            }
+        };
+        let row_group_tables = Arc::clone(&self.tables);
+        let finished_tables = Arc::clone(&self.tables);
+        WriteCallbacks::default()
+            .on_row_group_closed(move |row_group| {
+                let (_, table) = &mut row_group_tables.lock().unwrap()[index];
+                table.num_row_groups += 1;
+                table.row_group_rows += row_group.num_rows;
+                table.row_group_compressed_bytes += row_group.compressed_bytes;
+                table.row_group_uncompressed_bytes += row_group.uncompressed_bytes;
+            })
+            .on_table_finished(move |progress| {
+                let (start, table) = &mut finished_tables.lock().unwrap()[index];
+                table.elapsed = start.elapsed();
+                table.num_outputs += 1;
+                table.num_chunks += progress.num_chunks;
+                table.num_bytes += progress.num_bytes;
+            })
+    }
+
+    /// Returns the metrics of each table, in the order their callbacks were
+    /// created
+    pub fn tables(&self) -> Vec<TableMetrics> {
+        let tables = self.tables.lock().unwrap();
+        tables.iter().map(|(_, table)| table.clone()).collect()
+    }
+
+    /// Returns the metrics of each table as a JSON array
+    ///
+    /// The Parquet fields are `null` for tables without row groups.
+    pub fn to_json(&self) -> serde_json::Value {
+        let tables = self.tables();
+        let tables = tables.iter().map(|table| {
+            let elapsed = table.elapsed.as_secs_f64();
+            let parquet = |value: u64| (table.num_row_groups > 0).then_some(value);
+            json!({
+                "table": table.table,
+                "elapsed_secs": elapsed,
+                "outputs": table.num_outputs,
+                "chunks": table.num_chunks,
+                "bytes": table.num_bytes,
+                "bytes_per_sec": (elapsed > 0.0).then(|| (table.num_bytes as f64 / elapsed) as u64),
+                "row_groups": parquet(table.num_row_groups as u64),
+                "rows": parquet(table.row_group_rows),
+                "compressed_bytes": parquet(table.row_group_compressed_bytes),
+                "uncompressed_bytes": parquet(table.row_group_uncompressed_bytes),
+                "compression_ratio": table.compression_ratio(),
+            })
+        });
+        serde_json::Value::Array(tables.collect())
+    }
+}
+
+/// A [`Sink`] that records each buffer written to `inner` as a chunk in a
+/// [`WriteStatistics`]
+///
+/// The statistics are reported when the sink is flushed (or dropped).
+#[derive(Debug)]
+pub struct StatisticsSink<S> {
+    inner: S,
+    statistics: WriteStatistics,
+}
+
+impl<S: Sink> StatisticsSink<S> {
+    pub fn new(inner: S, statistics: WriteStatistics) -> Self {
+        Self { inner, statistics }
+    }
+}
+
+impl<S: Sink> Sink for StatisticsSink<S> {
+    fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error> {
+        self.inner.sink(buffer)?;
+        self.statistics.increment_bytes(buffer.len());
+        self.statistics.increment_chunks(1);
+        Ok(())
+    }
+
+    fn flush(self) -> Result<(), io::Error> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{generate_in_chunks, Source};
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+    use tpchgen::generators::SupplierGenerator;
+    use tpchgen_arrow::{RecordBatchIterator, SupplierArrow};
+
+    const SCALE_FACTOR: f64 = 0.01;
+    const PARTS: i32 = 3;
+
+    /// Generates one part of supplier in TBL format
+    struct SupplierSource(i32);
+
+    impl Source for SupplierSource {
+        fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+            for supplier in SupplierGenerator::new(SCALE_FACTOR, self.0, PARTS).iter() {
+                buffer.extend_from_slice(format!("{supplier}\n").as_bytes());
+            }
+            buffer
+        }
+
+        fn header(&self, buffer: Vec<u8>) -> Vec<u8> {
+            buffer
+        }
+    }
+
+    /// Collects the output
+    struct VecSink(Arc<Mutex<Vec<u8>>>);
+
+    impl Sink for VecSink {
+        fn sink(&mut self, buffer: &[u8]) -> Result<(), io::Error> {
+            self.0.lock().unwrap().extend_from_slice(buffer);
+            Ok(())
+        }
+
+        fn flush(self) -> Result<(), io::Error> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn counts_generated_buffers() {
+        let metrics = Metrics::default();
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+        let callbacks = metrics.callbacks("supplier").on_chunk_written({
+            let chunks = Arc::clone(&chunks);
+            move |progress| chunks.lock().unwrap().push(progress.num_bytes)
+        });
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let sink = StatisticsSink::new(
+            VecSink(Arc::clone(&output)),
+            WriteStatistics::new("buffers").with_callbacks(callbacks),
+        );
+        let sources = (1..=PARTS).map(SupplierSource);
+        generate_in_chunks(sink, sources, 2).await.unwrap();
+
+        // the (empty) header and then each part
+        let mut expected = vec![0];
+        for part in 1..=PARTS {
+            let bytes = SupplierSource(part).create(Vec::new()).len();
+            expected.push(expected.last().unwrap() + bytes);
+        }
+        assert_eq!(*chunks.lock().unwrap(), expected);
+
+        let output = output.lock().unwrap();
+        let supplier = &metrics.tables()[0];
+        assert_eq!(supplier.table, "supplier");
+        assert_eq!(supplier.num_outputs, 1);
+        assert_eq!(supplier.num_chunks, PARTS as usize + 1);
+        assert_eq!(supplier.num_bytes, output.len());
+        assert_eq!(supplier.num_row_groups, 0);
+        assert_eq!(supplier.compression_ratio(), None);
+        let json = &metrics.to_json()[0];
+        assert_eq!(json["bytes"], output.len());
+        assert!(json["rows"].is_null());
+    }
+
+    #[test]
+    fn counts_parquet_row_groups() {
+        let metrics = Metrics::default();
+        let mut statistics =
+            WriteStatistics::new("row groups").with_callbacks(metrics.callbacks("supplier"));
+        let schema =
+            Arc::clone(SupplierArrow::new(SupplierGenerator::new(SCALE_FACTOR, 1, 1)).schema());
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut writer = ArrowWriter::try_new(Vec::new(), schema, Some(properties)).unwrap();
+        let mut num_rows = 0;
+        for part in 1..=PARTS {
+            for batch in SupplierArrow::new(SupplierGenerator::new(SCALE_FACTOR, part, PARTS)) {
+                num_rows += batch.num_rows();
+                writer.write(&batch).unwrap();
+            }
+            // each part is a row group
+            writer.flush().unwrap();
+            let row_group = writer.flushed_row_groups().last().unwrap();
+            statistics.row_group_closed(
+                row_group.num_rows() as u64,
+                row_group.compressed_size() as u64,
+                row_group.total_byte_size() as u64,
+            );
+            statistics.increment_chunks(1);
+        }
+        let file = writer.into_inner().unwrap();
+        statistics.increment_bytes(file.len());
+        drop(statistics);
+
+        let supplier = &metrics.tables()[0];
+        assert_eq!(num_rows, 100);
+        assert_eq!(supplier.num_row_groups, PARTS as usize);
+        assert_eq!(supplier.row_group_rows, num_rows as u64);
+        assert_eq!(
+            (supplier.num_chunks, supplier.num_bytes),
+            (PARTS as usize, file.len())
+        );
+        let ratio = supplier.compression_ratio().unwrap();
+        assert!(ratio > 1.0, "compression ratio {ratio}");
+        assert_eq!(metrics.to_json()[0]["rows"], 100);
     }
 }
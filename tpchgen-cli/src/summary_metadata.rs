@@ -0,0 +1,197 @@
+//! Summary metadata files of Parquet datasets (`--parquet-summary-metadata`)
+//!
+//! When a table is written to many Parquet files, with
+//! `--parquet-file-per-row-group`, `--max-file-size` or `--partition-by`,
+//! `--parquet-summary-metadata` also writes the summary files of Spark and
+//! Dask to its directory:
+//!
+//! ```text
+//! <output_dir>/lineitem/_common_metadata      the schema of the files
+//! <output_dir>/lineitem/_metadata             the schema and the row groups of all files
+//! <output_dir>/lineitem/lineitem_0001.parquet
+//! ...
+//! ```
+//!
+//! Both are Parquet files without data. The column chunks of the row groups
+//! in `_metadata` refer to the file they are in by its path relative to the
+//! directory, so readers can plan a scan of all files, e.g. with
+//! `pyarrow.dataset.parquet_dataset` or `dask.dataframe.read_parquet`,
+//! without reading the footer of each file.
+//!
+//! The summary files are created from the footers of all Parquet files in
+//! the directory once the table is written, so files of earlier runs, e.g.
+//! with `--resume`, are included.
+
+use log::debug;
+use parquet::errors::ParquetError;
+use parquet::file::metadata::{
+    FileMetaData, ParquetMetaData, ParquetMetaDataReader, ParquetMetaDataWriter, RowGroupMetaData,
+};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Writes `_metadata` and `_common_metadata` of the Parquet files in `dir`
+/// and its subdirectories
+///
+/// Does nothing if there are no Parquet files in `dir`, and fails if their
+/// schemas differ.
+pub fn write_summary_metadata(dir: &Path) -> io::Result<()> {
+    let mut files = Vec::new();
+    parquet_files(dir, &mut files)?;
+    files.sort();
+    let Some(first) = files.first() else {
+        return Ok(());
+    };
+    debug!(
+        "Writing summary metadata of {} files in {}",
+        files.len(),
+        dir.display()
+    );
+    let first = read_metadata(first)?;
+    let file_metadata = first.file_metadata();
+    let schema = file_metadata.schema_descr_ptr();
+    let mut row_groups = Vec::new();
+    let mut num_rows = 0;
+    for path in &files {
+        let metadata = read_metadata(path)?;
+        if metadata.file_metadata().schema_descr_ptr() != schema {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} does not have the schema of the other Parquet files in {}",
+                    path.display(),
+                    dir.display()
+                ),
+            ));
+        }
+        let file_path = relative_path(dir, path);
+        for row_group in metadata.row_groups() {
+            num_rows += row_group.num_rows();
+            row_groups.push(with_file_path(row_group, &file_path)?);
+        }
+    }
+    let summary = |num_rows, row_groups| {
+        let file_metadata = FileMetaData::new(
+            file_metadata.version(),
+            num_rows,
+            file_metadata.created_by().map(str::to_string),
+            file_metadata.key_value_metadata().cloned(),
+            schema.clone(),
+            file_metadata.column_orders().cloned(),
+        );
+        ParquetMetaData::new(file_metadata, row_groups)
+    };
+    write_metadata(&dir.join("_common_metadata"), &summary(0, vec![]))?;
+    write_metadata(&dir.join("_metadata"), &summary(num_rows, row_groups))
+}
+
+/// Appends the Parquet files in `dir` and its subdirectories to `files`
+fn parquet_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            parquet_files(&path, files)?;
+        } else if path.extension().is_some_and(|e| e == "parquet") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the path of `path` relative to `dir`, with `/` separators
+fn relative_path(dir: &Path, path: &Path) -> String {
+    let path = path.strip_prefix(dir).expect("file in directory");
+    let components: Vec<_> = path.iter().map(|c| c.to_string_lossy()).collect();
+    components.join("/")
+}
+
+/// Returns `row_group` with its column chunks in the file at `file_path`
+fn with_file_path(row_group: &RowGroupMetaData, file_path: &str) -> io::Result<RowGroupMetaData> {
+    let columns = row_group
+        .columns()
+        .iter()
+        .map(|column| {
+            column
+                .clone()
+                .into_builder()
+                .set_file_path(file_path.to_string())
+                .build()
+        })
+        .collect::<Result<_, ParquetError>>()
+        .map_err(io::Error::other)?;
+    row_group
+        .clone()
+        .into_builder()
+        .set_column_metadata(columns)
+        .build()
+        .map_err(io::Error::other)
+}
+
+/// Reads the footer of the Parquet file at `path`
+fn read_metadata(path: &Path) -> io::Result<ParquetMetaData> {
+    ParquetMetaDataReader::new()
+        .parse_and_finish(&File::open(path)?)
+        .map_err(io::Error::other)
+}
+
+/// Writes `metadata` as a Parquet file without data
+fn write_metadata(path: &Path, metadata: &ParquetMetaData) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(b"PAR1")?;
+    ParquetMetaDataWriter::new(&mut writer, metadata)
+        .finish()
+        .map_err(io::Error::other)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use parquet::arrow::ArrowWriter;
+    use tpchgen::generators::OrderGenerator;
+    use tpchgen_arrow::{OrderArrow, RecordBatchIterator};
+
+    #[test]
+    fn summarizes_files() {
+        let dir = std::env::temp_dir().join(format!("tpchgen-summary-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for part in 1..=3 {
+            let batches = OrderArrow::new(OrderGenerator::new(0.01, part, 3));
+            let file = File::create(dir.join(format!("orders_{part:04}.parquet"))).unwrap();
+            let mut writer = ArrowWriter::try_new(file, batches.schema().clone(), None).unwrap();
+            for batch in batches {
+                writer.write(&batch).unwrap();
+            }
+            writer.close().unwrap();
+        }
+        write_summary_metadata(&dir).unwrap();
+
+        let metadata = read_metadata(&dir.join("_metadata")).unwrap();
+        assert_eq!(metadata.file_metadata().num_rows(), 15000);
+        let file_paths: Vec<_> = metadata
+            .row_groups()
+            .iter()
+            .map(|row_group| row_group.column(0).file_path().unwrap())
+            .collect();
+        assert_eq!(
+            file_paths,
+            [
+                "orders_0001.parquet",
+                "orders_0002.parquet",
+                "orders_0003.parquet"
+            ]
+        );
+        let common_metadata = read_metadata(&dir.join("_common_metadata")).unwrap();
+        assert_eq!(common_metadata.num_row_groups(), 0);
+        // the Arrow schema is kept, so readers of the summary files see the
+        // same types as readers of the data files
+        let reader =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(dir.join("_metadata")).unwrap())
+                .unwrap();
+        let expected = OrderArrow::new(OrderGenerator::new(0.01, 1, 3));
+        assert_eq!(reader.schema(), expected.schema());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,258 @@
+//! Deterministic UUID surrogate key column (`--uuid-column`)
+//!
+//! Some systems require a globally unique string identifier for every row.
+//! With `--uuid-column` each table gets an extra last column, e.g. `l_uuid`,
+//! holding a name based (version 5) UUID of the table name and the primary
+//! key of the row, e.g. `lineitem/1/3` for `l_orderkey = 1` and
+//! `l_linenumber = 3`, in the [`NAMESPACE`] UUID.
+//!
+//! The UUIDs only depend on the row, so they are the same for every output
+//! format, number of parts and scale factor, and can be recomputed from the
+//! primary key by any UUID library.
+
+use crate::Table;
+use arrow::array::{Array, Int64Array, RecordBatch, StringViewBuilder};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use std::sync::Arc;
+use tpchgen::generators::{Customer, LineItem, Nation, Order, Part, PartSupp, Region, Supplier};
+use tpchgen_arrow::{ColumnTypes, RecordBatchIterator};
+use uuid::Uuid;
+
+/// Namespace of the UUIDs: the version 5 UUID of the project URL
+/// `https://github.com/clflushopt/tpchgen-rs` in the URL namespace
+pub const NAMESPACE: Uuid = Uuid::from_u128(0x227f352f_755b_5258_98c2_dddc73682851);
+
+impl Table {
+    /// Returns the columns of the primary key of the table
+    pub fn primary_key(&self) -> &'static [&'static str] {
+        match self {
+            Table::Nation => &["n_nationkey"],
+            Table::Region => &["r_regionkey"],
+            Table::Part => &["p_partkey"],
+            Table::Supplier => &["s_suppkey"],
+            Table::Partsupp => &["ps_partkey", "ps_suppkey"],
+            Table::Customer => &["c_custkey"],
+            Table::Orders => &["o_orderkey"],
+            Table::Lineitem => &["l_orderkey", "l_linenumber"],
+        }
+    }
+
+    /// Returns the name of the UUID column, e.g. `l_uuid`
+    pub fn uuid_column(&self) -> String {
+        let (prefix, _) = self.primary_key()[0].split_once('_').unwrap();
+        format!("{prefix}_uuid")
+    }
+
+    /// Returns the schema of the table with the UUID column appended
+    pub fn schema_with_uuid(&self, schema: &Schema) -> SchemaRef {
+        let mut fields = schema.fields().to_vec();
+        fields.push(Arc::new(Field::new(
+            self.uuid_column(),
+            DataType::Utf8View,
+            false,
+        )));
+        Arc::new(Schema::new(fields))
+    }
+}
+
+/// Returns the UUID of the row of `table` with primary key `key`
+pub fn uuid(table: Table, key: &[i64]) -> Uuid {
+    let mut name = table.name().to_string();
+    for k in key {
+        name.push('/');
+        name.push_str(&k.to_string());
+    }
+    Uuid::new_v5(&NAMESPACE, name.as_bytes())
+}
+
+/// A row of a table, whose UUID is computed from its primary key
+pub trait SurrogateKey {
+    /// The table of the row
+    const TABLE: Table;
+
+    /// Returns the primary key of the row
+    fn key(&self) -> Vec<i64>;
+
+    /// Returns the UUID of the row
+    fn uuid(&self) -> Uuid;
+}
+
+macro_rules! impl_surrogate_key {
+    ($ROW:ty, $TABLE:expr, $($KEY:ident),+) => {
+        impl SurrogateKey for $ROW {
+            const TABLE: Table = $TABLE;
+
+            fn key(&self) -> Vec<i64> {
+                vec![$(self.$KEY as i64),+]
+            }
+
+            fn uuid(&self) -> Uuid {
+                uuid(Self::TABLE, &[$(self.$KEY as i64),+])
+            }
+        }
+    };
+}
+
+impl_surrogate_key!(Nation<'_>, Table::Nation, n_nationkey);
+impl_surrogate_key!(Region<'_>, Table::Region, r_regionkey);
+impl_surrogate_key!(Part<'_>, Table::Part, p_partkey);
+impl_surrogate_key!(Supplier, Table::Supplier, s_suppkey);
+impl_surrogate_key!(PartSupp<'_>, Table::Partsupp, ps_partkey, ps_suppkey);
+impl_surrogate_key!(Customer<'_>, Table::Customer, c_custkey);
+impl_surrogate_key!(Order<'_>, Table::Orders, o_orderkey);
+impl_surrogate_key!(LineItem<'_>, Table::Lineitem, l_orderkey, l_linenumber);
+
+/// Returns the columns of the primary key of `table` in `batch`, as integers
+pub fn primary_key_columns(table: Table, batch: &RecordBatch) -> Vec<Int64Array> {
+    table
+        .primary_key()
+        .iter()
+        .map(|name| {
+            let column = batch.column_by_name(name).expect("primary key column");
+            let column = cast(column, &DataType::Int64).expect("integer primary key");
+            column
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap()
+                .clone()
+        })
+        .collect()
+}
+
+/// A [`RecordBatchIterator`] that appends the UUID column to the batches of
+/// `inner`, if enabled
+pub struct SurrogateKeyArrow<I> {
+    inner: I,
+    /// The table, if the column is appended
+    table: Option<Table>,
+    schema: SchemaRef,
+}
+
+impl<I: RecordBatchIterator> SurrogateKeyArrow<I> {
+    /// Appends the UUID column of `table` to the batches of `inner` if
+    /// `enabled`, otherwise passes them through unchanged
+    pub fn new(inner: I, table: Table, enabled: bool) -> Self {
+        let schema = if enabled {
+            table.schema_with_uuid(inner.schema())
+        } else {
+            Arc::clone(inner.schema())
+        };
+        Self {
+            inner,
+            table: enabled.then_some(table),
+            schema,
+        }
+    }
+
+    /// Set the types of the columns, for the UUID column to have the type of
+    /// the other string columns
+    pub fn with_column_types(mut self, column_types: &ColumnTypes) -> Self {
+        self.schema = column_types.schema(&self.schema);
+        self
+    }
+}
+
+impl<I: RecordBatchIterator> RecordBatchIterator for SurrogateKeyArrow<I> {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+}
+
+impl<I: RecordBatchIterator> Iterator for SurrogateKeyArrow<I> {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.inner.next()?;
+        let Some(table) = self.table else {
+            return Some(batch);
+        };
+        let keys = primary_key_columns(table, &batch);
+        let mut uuids = StringViewBuilder::with_capacity(batch.num_rows());
+        let mut key = vec![0; keys.len()];
+        let mut buffer = Uuid::encode_buffer();
+        for row in 0..batch.num_rows() {
+            for (k, column) in key.iter_mut().zip(&keys) {
+                *k = column.value(row);
+            }
+            uuids.append_value(uuid(table, &key).hyphenated().encode_lower(&mut buffer));
+        }
+        let mut columns = batch.columns().to_vec();
+        let uuid_type = self.schema.fields().last().unwrap().data_type();
+        // safe to unwrap because string views can always be cast to strings
+        columns.push(cast(&uuids.finish(), uuid_type).unwrap());
+        Some(RecordBatch::try_new(Arc::clone(&self.schema), columns).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{AsArray, StringViewArray};
+    use tpchgen::generators::{LineItemGenerator, PartSuppGenerator};
+    use tpchgen_arrow::{LineItemArrow, PartSuppArrow, StringType};
+
+    #[test]
+    fn uuids_are_stable() {
+        // the namespace is the UUID of the project URL
+        let url = "https://github.com/clflushopt/tpchgen-rs";
+        assert_eq!(
+            NAMESPACE,
+            Uuid::new_v5(&Uuid::NAMESPACE_URL, url.as_bytes())
+        );
+        assert_eq!(
+            uuid(Table::Nation, &[0]).to_string(),
+            "7aa2c848-b03f-5e47-9dc7-515314265122"
+        );
+        assert_eq!(
+            uuid(Table::Lineitem, &[1, 1]).to_string(),
+            "01c545fb-1cdb-5bd7-b1d9-23252a9f084c"
+        );
+        assert_eq!(Table::Partsupp.uuid_column(), "ps_uuid");
+        assert_eq!(Table::Orders.uuid_column(), "o_uuid");
+    }
+
+    #[test]
+    fn arrow_matches_rows() {
+        let generator = LineItemGenerator::new(0.001, 2, 3);
+        let rows: Vec<_> = generator.clone().iter().collect();
+        let batches = SurrogateKeyArrow::new(LineItemArrow::new(generator), Table::Lineitem, true);
+        assert_eq!(batches.schema().fields().last().unwrap().name(), "l_uuid");
+        let mut uuids = Vec::new();
+        for batch in batches {
+            let column = batch.columns().last().unwrap();
+            let column = column.as_any().downcast_ref::<StringViewArray>().unwrap();
+            uuids.extend(column.iter().map(|uuid| uuid.unwrap().to_string()));
+        }
+        let expected: Vec<_> = rows.iter().map(|row| row.uuid().to_string()).collect();
+        assert_eq!(uuids, expected);
+
+        // disabled: the batches are unchanged
+        let generator = PartSuppGenerator::new(0.001, 1, 1);
+        let expected: Vec<_> = PartSuppArrow::new(generator.clone()).collect();
+        let batches = SurrogateKeyArrow::new(PartSuppArrow::new(generator), Table::Partsupp, false);
+        assert_eq!(batches.collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn uuid_column_has_column_types() {
+        let types = ColumnTypes::default().with_string(StringType::Utf8);
+        let generator = LineItemGenerator::new(0.001, 1, 1);
+        let batches = SurrogateKeyArrow::new(
+            LineItemArrow::new(generator.clone()).with_column_types(&types),
+            Table::Lineitem,
+            true,
+        )
+        .with_column_types(&types);
+        let field = batches.schema().field_with_name("l_uuid").unwrap().clone();
+        assert_eq!(field.data_type(), &DataType::Utf8);
+        let mut rows = generator.iter();
+        for batch in batches {
+            let uuids = batch.columns().last().unwrap().as_string::<i32>();
+            for uuid in uuids {
+                assert_eq!(uuid.unwrap(), rows.next().unwrap().uuid().to_string());
+            }
+        }
+        assert!(rows.next().is_none());
+    }
+}
@@ -1,22 +1,53 @@
 //! Implementations of [`Source`] for generating data in TBL format
 
-use super::generate::Source;
+use crate::dirty::DirtyPart;
+use crate::nulls::{column_names, write_tbl_line};
+use crate::surrogate_key::SurrogateKey;
+use std::fmt::Write as _;
 use std::io::Write;
 use tpchgen::generators::{
     CustomerGenerator, LineItemGenerator, NationGenerator, OrderGenerator, PartGenerator,
     PartSuppGenerator, RegionGenerator, SupplierGenerator,
 };
+use tpchgen::nulls::NullInjection;
+use tpchgen_cli::generate::Source;
 
 /// Define a Source that writes the table in TBL format
 macro_rules! define_tbl_source {
     ($SOURCE_NAME:ident, $GENERATOR_TYPE:ty) => {
         pub struct $SOURCE_NAME {
             inner: $GENERATOR_TYPE,
+            uuid_column: bool,
+            nulls: Option<&'static NullInjection>,
+            dirty: Option<DirtyPart>,
         }
 
         impl $SOURCE_NAME {
             pub fn new(inner: $GENERATOR_TYPE) -> Self {
-                Self { inner }
+                Self {
+                    inner,
+                    uuid_column: false,
+                    nulls: None,
+                    dirty: None,
+                }
+            }
+
+            /// Append the UUID column, see [`crate::surrogate_key`]
+            pub fn with_uuid_column(mut self, uuid_column: bool) -> Self {
+                self.uuid_column = uuid_column;
+                self
+            }
+
+            /// Write the NULLs selected by `nulls`, see [`crate::nulls`]
+            pub fn with_nulls(mut self, nulls: Option<&'static NullInjection>) -> Self {
+                self.nulls = nulls;
+                self
+            }
+
+            /// Corrupt the rows selected by `dirty`, see [`crate::dirty`]
+            pub fn with_dirty(mut self, dirty: Option<DirtyPart>) -> Self {
+                self.dirty = dirty;
+                self
             }
         }
 
@@ -26,11 +57,74 @@ macro_rules! define_tbl_source {
                 buffer
             }
 
-            fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
+            fn create(mut self, mut buffer: Vec<u8>) -> Vec<u8> {
+                if let Some(dirty) = self.dirty.take() {
+                    return self.create_dirty(dirty, buffer);
+                }
+                if let Some(nulls) = self.nulls {
+                    return self.create_with_nulls(nulls, buffer);
+                }
                 for item in self.inner.iter() {
                     // The default Display impl writes TBL format
-                    writeln!(&mut buffer, "{item}").expect("writing to memory is infallible");
+                    if self.uuid_column {
+                        writeln!(&mut buffer, "{item}{}|", item.uuid())
+                    } else {
+                        writeln!(&mut buffer, "{item}")
+                    }
+                    .expect("writing to memory is infallible");
+                }
+                buffer
+            }
+        }
+
+        impl $SOURCE_NAME {
+            /// Writes the rows with the values selected by `nulls` replaced
+            /// by its token
+            fn create_with_nulls(&self, nulls: &NullInjection, mut buffer: Vec<u8>) -> Vec<u8> {
+                let mut line = String::new();
+                let mut columns = Vec::new();
+                for item in self.inner.iter() {
+                    if columns.is_empty() {
+                        columns = column_names(&item);
+                    }
+                    line.clear();
+                    write!(&mut line, "{item}").expect("writing to a String is infallible");
+                    write_tbl_line(&mut buffer, &line, &columns, nulls, &item.key());
+                    if self.uuid_column {
+                        write!(&mut buffer, "{}|", item.uuid())
+                            .expect("writing to memory is infallible");
+                    }
+                    buffer.push(b'\n');
+                }
+                buffer
+            }
+
+            /// Writes the rows, with any NULLs, corrupting those selected by
+            /// `dirty`
+            fn create_dirty(&self, mut dirty: DirtyPart, mut buffer: Vec<u8>) -> Vec<u8> {
+                let mut line = String::new();
+                let mut row = Vec::new();
+                let mut columns = Vec::new();
+                for item in self.inner.iter() {
+                    if columns.is_empty() {
+                        columns = column_names(&item);
+                    }
+                    let key = item.key();
+                    line.clear();
+                    write!(&mut line, "{item}").expect("writing to a String is infallible");
+                    row.clear();
+                    match self.nulls {
+                        Some(nulls) => write_tbl_line(&mut row, &line, &columns, nulls, &key),
+                        None => row.extend_from_slice(line.as_bytes()),
+                    }
+                    if self.uuid_column {
+                        write!(&mut row, "{}|", item.uuid())
+                            .expect("writing to memory is infallible");
+                    }
+                    let row = std::str::from_utf8(&row).expect("TBL rows are UTF-8");
+                    dirty.write_row(&mut buffer, row, &columns, &key);
                 }
+                dirty.finish();
                 buffer
             }
         }
@@ -0,0 +1,229 @@
+//! Verification of TBL output (`verify`)
+//!
+//! `tpchgen-cli verify --scale-factor 1 --dir ./out` reads the TBL files of
+//! each table in `./out`, either `<table>.tbl` or the parts
+//! `<table>.tbl.1`, `<table>.tbl.2`, ... of `--part-files` or `dbgen -C`,
+//! and compares the number of rows and the SHA-1 of their contents, in the
+//! order of the parts, with the values expected at the scale factor:
+//!
+//! ```text
+//! nation     ok       25 rows  nation.tbl
+//! orders     corrupt  sha1 bbfc7aa1... (171952161 bytes), expected sha1 00d790a0... (171952161 bytes)  orders.tbl
+//! lineitem   stale    6001215 rows, expected 59986052  lineitem.tbl.1 lineitem.tbl.2
+//! ```
+//!
+//! A table is `corrupt` if it has the expected number of rows but different
+//! contents, and `stale` if it has a different number of rows, e.g. because
+//! it was generated at another scale factor or its generation did not
+//! finish.
+//!
+//! The expected values of the standard scale factors 0.01, 0.1, 1 and 10 are
+//! embedded, see [`golden`]. For other scale factors, or with `--zipf-theta`,
+//! `--rows` or `--seed-offset`, they are computed by generating the tables
+//! without writing them, which takes about as long as generating them.
+
+use crate::determinism::{count_lines, ContentHash};
+use crate::Table;
+use sha1_smol::Sha1;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct VerifyArgs {
+    /// Directory of the TBL files to verify
+    #[arg(long, value_name = "DIR")]
+    pub dir: PathBuf,
+}
+
+/// Rows, bytes and SHA-1 of the TBL output of each table at the standard
+/// scale factors, one table per line as `<scale factor> <table> <rows>
+/// <bytes> <sha1>`
+///
+/// The TBL output of the generators is the same as that of dbgen.
+const GOLDEN: &str = "\
+0.01 nation 25 2224 f361dffd3d927f5aa64e71cff91458fb5ea1315f
+0.01 region 5 389 ac61de9604337e791f1bdbcef8f0cdcc21b01514
+0.01 part 2000 237134 84c898b969abff12f85a02dd40be11a4d917d5b5
+0.01 supplier 100 13795 bef0ccf5d60b9b51d7701fd058922e520596d7f2
+0.01 partsupp 8000 1161705 3c62cc69413cd8b94d5dd0e1f3f4cf874137431c
+0.01 customer 1500 240990 c0345a5cef0407605ac2edcc7442a8dfe3a4c9f8
+0.01 orders 15000 1659137 ac72418f6b461f506ad717f131912fe2e58a8f77
+0.01 lineitem 60175 7264250 bd54ec6886fb1404e851d5bf27f25146e206698d
+0.1 nation 25 2224 f361dffd3d927f5aa64e71cff91458fb5ea1315f
+0.1 region 5 389 ac61de9604337e791f1bdbcef8f0cdcc21b01514
+0.1 part 20000 2391090 80f8c7d218c714cca6e1828e112d96bdb7ab867a
+0.1 supplier 1000 139625 154f6eee52179aa77feead51ce5bd6cdabc97c7f
+0.1 partsupp 80000 11728193 c3614611338589fd5d217a7885826cfb17691f14
+0.1 customer 15000 2426114 97e2cc316cc88be0f42d80d570f76b22f988fa04
+0.1 orders 150000 16893122 5373cec56b34fe4128f1e8399c4732935ee7df28
+0.1 lineitem 600572 74246996 5b97b8e7e0f2e74963c3e2e93973f26bce3cec2f
+1 nation 25 2224 f361dffd3d927f5aa64e71cff91458fb5ea1315f
+1 region 5 389 ac61de9604337e791f1bdbcef8f0cdcc21b01514
+1 part 200000 24135125 06615f7433806c06162af49c7bc27166c64a31d6
+1 supplier 10000 1409184 baad047476a2720d99b707b6f7a7c9e50c170d5a
+1 partsupp 800000 118984616 db0fcb935904765a9085505b5feb5260752f8bf3
+1 customer 150000 24346144 bee45e9c240e87d63786324696b1babad18a5e0b
+1 orders 1500000 171952161 00d790a08a116feec992cea14272a4f1e5c55925
+1 lineitem 6001215 759863287 4802b21c9975d965aa11893214a879df0d8d9e01
+10 nation 25 2224 f361dffd3d927f5aa64e71cff91458fb5ea1315f
+10 region 5 389 ac61de9604337e791f1bdbcef8f0cdcc21b01514
+10 part 2000000 243336157 efb2a169b6ce80d8ed3989147e8d70e7f2a38d6c
+10 supplier 100000 14176368 42a76ba965916326e52adca1725ed9ee18b8e61b
+10 partsupp 8000000 1204850769 eae140257dc91ba3b4a929c32ebe3d08f3605618
+10 customer 1500000 244847642 b717482bde38c8312cf232e7ca73aab62f5e1eca
+10 orders 15000000 1749195031 dddffc12e235da9cd8d17584dc1eab237654cb0f
+10 lineitem 59986052 7775727688 c3f5d0218b6623125887d7021922d1e91da11613
+";
+
+/// Returns the expected TBL output of `table` at `scale_factor`, if it is a
+/// standard scale factor with embedded values
+pub fn golden(scale_factor: f64, table: Table) -> Option<ContentHash> {
+    GOLDEN.lines().find_map(|line| {
+        let fields: Vec<_> = line.split(' ').collect();
+        let [sf, name, rows, bytes, sha1] = fields[..] else {
+            panic!("invalid golden line '{line}'");
+        };
+        let sf: f64 = sf.parse().expect("valid scale factor");
+        if (sf - scale_factor).abs() > 1e-9 || name != table.name() {
+            return None;
+        }
+        Some(ContentHash {
+            digest: sha1.to_string(),
+            num_bytes: bytes.parse().expect("valid bytes"),
+            num_lines: rows.parse().expect("valid rows"),
+        })
+    })
+}
+
+/// Returns the TBL files of `table` in `dir`: `<table>.tbl`, or its parts
+/// `<table>.tbl.<n>` in the order of the parts, or nothing if there are
+/// none
+pub fn table_files(dir: &Path, table: Table) -> io::Result<Vec<PathBuf>> {
+    let file = dir.join(format!("{}.tbl", table.name()));
+    if file.is_file() {
+        return Ok(vec![file]);
+    }
+    let prefix = format!("{}.tbl.", table.name());
+    let mut parts = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(part) = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(|part| part.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        parts.push((part, entry.path()));
+    }
+    parts.sort();
+    Ok(parts.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Returns the hash of the contents of `files`, one after the other
+pub fn hash_files(files: &[PathBuf]) -> io::Result<ContentHash> {
+    let mut hasher = Sha1::new();
+    let mut num_bytes = 0;
+    let mut num_lines = 0;
+    let mut buffer = vec![0; 1024 * 1024];
+    for path in files {
+        let mut file = File::open(path)?;
+        loop {
+            let n = file.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+            num_bytes += n as u64;
+            num_lines += count_lines(&buffer[..n]);
+        }
+    }
+    Ok(ContentHash {
+        digest: hasher.digest().to_string(),
+        num_bytes,
+        num_lines,
+    })
+}
+
+/// The result of verifying a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// There are no files of the table
+    Missing,
+    /// The table has the expected rows but different contents
+    Corrupt,
+    /// The table has a different number of rows
+    Stale,
+}
+
+impl Status {
+    /// Compares the `actual` contents of a table with the `expected` ones
+    pub fn new(actual: &ContentHash, expected: &ContentHash) -> Self {
+        if actual == expected {
+            Status::Ok
+        } else if actual.num_lines == expected.num_lines {
+            Status::Corrupt
+        } else {
+            Status::Stale
+        }
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            Status::Ok => "ok",
+            Status::Missing => "missing",
+            Status::Corrupt => "corrupt",
+            Status::Stale => "stale",
+        };
+        f.pad(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tpchgen::generators::RegionGenerator;
+
+    #[test]
+    fn verifies_files() {
+        let dir = std::env::temp_dir().join(format!("tpchgen-verify-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let expected = golden(0.01, Table::Region).unwrap();
+        assert_eq!(golden(1.0, Table::Lineitem).unwrap().num_lines, 6001215);
+        assert_eq!(golden(0.5, Table::Region), None);
+        assert!(table_files(&dir, Table::Region).unwrap().is_empty());
+
+        // the region table split into two part files
+        let lines: Vec<_> = RegionGenerator::new(0.01, 1, 1)
+            .iter()
+            .map(|r| format!("{r}\n"))
+            .collect();
+        fs::write(dir.join("region.tbl.2"), lines[2..].concat()).unwrap();
+        fs::write(dir.join("region.tbl.1"), lines[..2].concat()).unwrap();
+        let files = table_files(&dir, Table::Region).unwrap();
+        assert_eq!(files, [dir.join("region.tbl.1"), dir.join("region.tbl.2")]);
+        let actual = hash_files(&files).unwrap();
+        assert_eq!(Status::new(&actual, &expected), Status::Ok);
+
+        fs::write(
+            dir.join("region.tbl"),
+            lines.concat().replace("AFRICA", "AFRIKA"),
+        )
+        .unwrap();
+        let files = table_files(&dir, Table::Region).unwrap();
+        assert_eq!(files, [dir.join("region.tbl")]);
+        let actual = hash_files(&files).unwrap();
+        assert_eq!(Status::new(&actual, &expected), Status::Corrupt);
+
+        fs::write(dir.join("region.tbl"), lines[..4].concat()).unwrap();
+        let actual = hash_files(&files).unwrap();
+        assert_eq!(Status::new(&actual, &expected), Status::Stale);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,438 @@
+//! Files that are generated when they are read (`mount`)
+//!
+//! A [`VirtualFile`] is a table in one output format whose contents are never
+//! stored: each read generates the parts of the file that overlap the
+//! requested range. Generation is deterministic, so the same part always
+//! produces the same bytes.
+//!
+//! The size of the file, and where each part starts, is only known once the
+//! file has been generated. This layout is computed the first time it is
+//! needed by generating (and discarding) the whole file once, using
+//! `num_threads` threads.
+//!
+//! Parquet files have one row group per part. Encoded pages do not depend on
+//! where they are written in the file, so a row group can be encoded on its
+//! own and served from the offset it has in the complete file. The page
+//! indexes and footer, which do contain offsets, are kept in memory.
+
+use crate::parquet::{encode_row_group, ParquetOptions};
+use ::parquet::arrow::arrow_writer::ArrowColumnChunk;
+use ::parquet::file::properties::WriterProperties;
+use ::parquet::file::writer::SerializedFileWriter;
+use ::parquet::schema::types::SchemaDescPtr;
+use arrow::datatypes::SchemaRef;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::generate::Source;
+
+/// Number of generated parts kept in memory per file, so that the many small
+/// reads of a sequential scan do not generate the same part again
+const CACHED_PARTS: usize = 4;
+
+/// Magic bytes at the start and end of every Parquet file
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+
+/// A file whose contents are generated on demand, see the module docs
+pub struct VirtualFile {
+    num_parts: i32,
+    num_threads: usize,
+    encoding: Encoding,
+    layout: OnceLock<Layout>,
+    /// Recently generated parts, most recent last
+    cache: Mutex<VecDeque<(i32, Arc<Vec<u8>>)>>,
+}
+
+/// How the parts of a [`VirtualFile`] are generated
+enum Encoding {
+    /// TBL or CSV: the header followed by the text of each part
+    Text {
+        header: Vec<u8>,
+        part: Box<dyn Fn(i32) -> Vec<u8> + Send + Sync>,
+    },
+    /// Parquet: each part is encoded as one row group
+    Parquet {
+        parquet_schema: SchemaDescPtr,
+        writer_properties: Arc<WriterProperties>,
+        row_group: Box<dyn Fn(i32) -> Vec<ArrowColumnChunk> + Send + Sync>,
+    },
+}
+
+/// Where the parts of a [`VirtualFile`] are
+struct Layout {
+    /// Bytes before the first part
+    header: Vec<u8>,
+    /// Offset of the start of each part, followed by the offset of the tail
+    offsets: Vec<u64>,
+    /// Bytes after the last part
+    tail: Vec<u8>,
+}
+
+impl Layout {
+    fn size(&self) -> u64 {
+        self.tail_offset() + self.tail.len() as u64
+    }
+
+    fn tail_offset(&self) -> u64 {
+        *self.offsets.last().unwrap()
+    }
+}
+
+impl VirtualFile {
+    /// Creates a TBL or CSV file of `num_parts` parts, where `new_source`
+    /// creates the [`Source`] of each (1 based) part
+    pub fn text<S, F>(num_parts: i32, num_threads: usize, new_source: F) -> Self
+    where
+        S: Source,
+        F: Fn(i32) -> S + Send + Sync + 'static,
+    {
+        let header = new_source(1).header(Vec::new());
+        let part = Box::new(move |part| new_source(part).create(Vec::new()));
+        Self::new(num_parts, num_threads, Encoding::Text { header, part })
+    }
+
+    /// Creates a Parquet file of `num_parts` row groups written with
+    /// `options`, where `new_iter` creates the batches of each (1 based) part
+    pub fn parquet<I, F>(
+        num_parts: i32,
+        num_threads: usize,
+        options: &ParquetOptions,
+        new_iter: F,
+    ) -> io::Result<Self>
+    where
+        I: RecordBatchIterator,
+        F: Fn(i32) -> I + Send + Sync + 'static,
+    {
+        let schema: SchemaRef = Arc::clone(new_iter(1).schema());
+        let (parquet_schema, writer_properties) = options.parquet_schema(&schema)?;
+        let captured_parquet_schema = Arc::clone(&parquet_schema);
+        let captured_writer_properties = Arc::clone(&writer_properties);
+        let column_threads = options.column_threads();
+        let row_group = Box::new(move |part| {
+            encode_row_group(
+                Arc::clone(&captured_parquet_schema),
+                Arc::clone(&captured_writer_properties),
+                Arc::clone(&schema),
+                column_threads,
+                new_iter(part),
+            )
+        });
+        let encoding = Encoding::Parquet {
+            parquet_schema,
+            writer_properties,
+            row_group,
+        };
+        Ok(Self::new(num_parts, num_threads, encoding))
+    }
+
+    fn new(num_parts: i32, num_threads: usize, encoding: Encoding) -> Self {
+        Self {
+            num_parts,
+            num_threads,
+            encoding,
+            layout: OnceLock::new(),
+            cache: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Returns true if the size of the file is known without generating it
+    pub fn is_size_known(&self) -> bool {
+        self.layout.get().is_some()
+    }
+
+    /// Returns the size of the file in bytes, generating the whole file the
+    /// first time it is called
+    pub fn size(&self) -> u64 {
+        self.layout().size()
+    }
+
+    /// Returns up to `len` bytes starting at `offset`, fewer at the end of
+    /// the file
+    pub fn read(&self, offset: u64, len: usize) -> Vec<u8> {
+        let layout = self.layout();
+        let end = offset.saturating_add(len as u64).min(layout.size());
+        let header_end = layout.header.len() as u64;
+        let tail_offset = layout.tail_offset();
+        let mut buffer = Vec::with_capacity(end.saturating_sub(offset) as usize);
+        let mut pos = offset;
+        while pos < end {
+            let (bytes, start) = if pos < header_end {
+                (&layout.header[..], 0)
+            } else if pos >= tail_offset {
+                (&layout.tail[..], tail_offset)
+            } else {
+                // the last part starting at or before pos (skipping empty parts)
+                let index = layout.offsets.partition_point(|&o| o <= pos) - 1;
+                let part = self.part(index as i32 + 1);
+                let from = (pos - layout.offsets[index]) as usize;
+                let to = ((end - layout.offsets[index]) as usize).min(part.len());
+                buffer.extend_from_slice(&part[from..to]);
+                pos += (to - from) as u64;
+                continue;
+            };
+            let from = (pos - start) as usize;
+            let to = ((end - start) as usize).min(bytes.len());
+            buffer.extend_from_slice(&bytes[from..to]);
+            pos += (to - from) as u64;
+        }
+        buffer
+    }
+
+    fn layout(&self) -> &Layout {
+        self.layout.get_or_init(|| match &self.encoding {
+            Encoding::Text { header, part } => {
+                let sizes = generate_parallel(1..self.num_parts + 1, self.num_threads, |p| {
+                    part(p).len() as u64
+                });
+                let mut offsets = vec![header.len() as u64];
+                for size in sizes {
+                    offsets.push(offsets.last().unwrap() + size);
+                }
+                Layout {
+                    header: header.clone(),
+                    offsets,
+                    tail: Vec::new(),
+                }
+            }
+            Encoding::Parquet {
+                parquet_schema,
+                writer_properties,
+                row_group,
+            } => {
+                // write the whole file, only keeping what follows the last row group
+                let sink = TailSink::default();
+                let mut writer = SerializedFileWriter::new(
+                    sink.clone(),
+                    parquet_schema.root_schema_ptr(),
+                    Arc::clone(writer_properties),
+                )
+                .unwrap();
+                let mut offsets = vec![PARQUET_MAGIC.len() as u64];
+                let window = self.num_threads.max(1) as i32;
+                let mut first = 1;
+                while first <= self.num_parts {
+                    let parts = first..(first + window).min(self.num_parts + 1);
+                    first = parts.end;
+                    for chunks in generate_parallel(parts, self.num_threads, row_group) {
+                        let mut row_group_writer = writer.next_row_group().unwrap();
+                        for chunk in chunks {
+                            chunk.append_to_row_group(&mut row_group_writer).unwrap();
+                        }
+                        row_group_writer.close().unwrap();
+                        offsets.push(writer.bytes_written() as u64);
+                    }
+                }
+                sink.keep_from(writer.bytes_written());
+                writer.close().unwrap();
+                Layout {
+                    header: PARQUET_MAGIC.to_vec(),
+                    offsets,
+                    tail: sink.take(),
+                }
+            }
+        })
+    }
+
+    /// Returns the bytes of `part` (1 based), from the cache if possible
+    fn part(&self, part: i32) -> Arc<Vec<u8>> {
+        if let Some((_, bytes)) = self.cache.lock().unwrap().iter().find(|(p, _)| *p == part) {
+            return Arc::clone(bytes);
+        }
+        let bytes = Arc::new(match &self.encoding {
+            Encoding::Text { part: text, .. } => text(part),
+            Encoding::Parquet {
+                parquet_schema,
+                writer_properties,
+                row_group,
+            } => {
+                // the row group is what follows the magic bytes in a file of its own
+                let mut writer = SerializedFileWriter::new(
+                    Vec::new(),
+                    parquet_schema.root_schema_ptr(),
+                    Arc::clone(writer_properties),
+                )
+                .unwrap();
+                let mut row_group_writer = writer.next_row_group().unwrap();
+                for chunk in row_group(part) {
+                    chunk.append_to_row_group(&mut row_group_writer).unwrap();
+                }
+                row_group_writer.close().unwrap();
+                let end = writer.bytes_written();
+                writer.into_inner().unwrap()[PARQUET_MAGIC.len()..end].to_vec()
+            }
+        });
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() == CACHED_PARTS {
+            cache.pop_front();
+        }
+        cache.push_back((part, Arc::clone(&bytes)));
+        bytes
+    }
+}
+
+/// Calls `f` for each part in `parts` using `num_threads` threads, returning
+/// the results in order
+fn generate_parallel<T, F>(parts: Range<i32>, num_threads: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(i32) -> T + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let len = parts.len();
+    let results: Vec<Mutex<Option<T>>> = (0..len).map(|_| Mutex::new(None)).collect();
+    std::thread::scope(|scope| {
+        for _ in 0..num_threads.clamp(1, len.max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= len {
+                    break;
+                }
+                let result = f(parts.start + index as i32);
+                *results[index].lock().unwrap() = Some(result);
+            });
+        }
+    });
+    results
+        .into_iter()
+        .map(|result| result.into_inner().unwrap().unwrap())
+        .collect()
+}
+
+/// A [`Write`] that discards everything written before [`Self::keep_from`]
+#[derive(Debug, Clone, Default)]
+struct TailSink {
+    inner: Arc<Mutex<TailSinkInner>>,
+}
+
+#[derive(Debug, Default)]
+struct TailSinkInner {
+    written: usize,
+    keep_from: Option<usize>,
+    kept: Vec<u8>,
+}
+
+impl TailSink {
+    /// Keep the bytes written from offset `offset` on
+    fn keep_from(&self, offset: usize) {
+        self.inner.lock().unwrap().keep_from = Some(offset);
+    }
+
+    /// Returns the bytes that were kept
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut self.inner.lock().unwrap().kept)
+    }
+}
+
+impl Write for TailSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(keep_from) = inner.keep_from {
+            let skip = keep_from.saturating_sub(inner.written).min(buf.len());
+            inner.kept.extend_from_slice(&buf[skip..]);
+        }
+        inner.written += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv::OrderCsvSource;
+    use crate::parquet::{generate_parquet, IntoSize, RowGroupOrder};
+    use crate::tbl::LineItemTblSource;
+    use ::parquet::basic::Compression;
+    use tpchgen::generators::{LineItemGenerator, OrderGenerator};
+    use tpchgen_arrow::OrderArrow;
+
+    /// Reads `file` in pieces of `len` bytes
+    fn read_all(file: &VirtualFile, len: usize) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        loop {
+            let bytes = file.read(buffer.len() as u64, len);
+            if bytes.is_empty() {
+                return buffer;
+            }
+            buffer.extend_from_slice(&bytes);
+        }
+    }
+
+    #[test]
+    fn text_reads_match_generated() {
+        let num_parts = 3;
+        let file = VirtualFile::text(num_parts, 2, move |part| {
+            LineItemTblSource::new(LineItemGenerator::new(0.001, part, num_parts))
+        });
+        let expected = LineItemTblSource::new(LineItemGenerator::new(0.001, 1, 1)).create(vec![]);
+        assert!(!file.is_size_known());
+        assert_eq!(file.size(), expected.len() as u64);
+        assert_eq!(read_all(&file, 4096), expected);
+        assert_eq!(read_all(&file, 100_000), expected);
+        assert_eq!(file.read(10, 20), expected[10..30]);
+        assert!(file.read(expected.len() as u64 + 10, 20).is_empty());
+
+        let file = VirtualFile::text(2, 1, move |part| {
+            OrderCsvSource::new(OrderGenerator::new(0.001, part, 2))
+        });
+        let source = OrderCsvSource::new(OrderGenerator::new(0.001, 1, 1));
+        let expected = source.header(Vec::new());
+        let expected = source.create(expected);
+        assert_eq!(read_all(&file, 1000), expected);
+    }
+
+    #[tokio::test]
+    async fn parquet_reads_match_generated() {
+        let num_parts = 3;
+        let options = ParquetOptions::new(Compression::SNAPPY);
+        let file = VirtualFile::parquet(num_parts, 2, &options, move |part| {
+            OrderArrow::new(OrderGenerator::new(0.01, part, num_parts))
+        })
+        .unwrap();
+        let expected = SharedFile::default();
+        let sources =
+            (1..=num_parts).map(move |part| OrderArrow::new(OrderGenerator::new(0.01, part, 3)));
+        generate_parquet(
+            expected.clone(),
+            sources,
+            1,
+            None,
+            RowGroupOrder::Strict,
+            &options,
+            None,
+        )
+        .await
+        .unwrap();
+        let expected = expected.0.lock().unwrap().clone();
+        assert_eq!(file.size(), expected.len() as u64);
+        assert!(read_all(&file, 8192) == expected);
+        assert_eq!(file.read(1000, 10), expected[1000..1010]);
+    }
+
+    /// An in memory file whose contents can be read after it is written
+    #[derive(Debug, Clone, Default)]
+    struct SharedFile(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedFile {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl IntoSize for SharedFile {
+        fn into_size(self) -> Result<usize, io::Error> {
+            Ok(self.0.lock().unwrap().len())
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Experimental [Vortex] output (`--format=vortex`)
+//!
+//! Requires the `vortex` feature. The Arrow batches produced by
+//! `tpchgen-arrow` are converted to Vortex arrays and written as a single
+//! Vortex file to a [`Sink`], which lets users evaluate the format with
+//! realistic benchmark data.
+//!
+//! [Vortex]: https://github.com/vortex-data/vortex
+
+use crate::progress::{write_callbacks, ProgressReporter};
+use arrow::array::RecordBatch;
+use futures::StreamExt;
+use log::debug;
+use std::future::{ready, Future};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_cli::generate::Sink;
+use tpchgen_cli::statistics::WriteStatistics;
+use vortex::arrow::FromArrowArray;
+use vortex::dtype::arrow::FromArrowType;
+use vortex::dtype::DType;
+use vortex::file::VortexWriteOptions;
+use vortex::stream::ArrayStreamAdapter;
+use vortex::ArrayRef;
+use vortex_io::{IoBuf, VortexWrite};
+
+/// Converts a set of RecordBatchIterators into a Vortex file written to `sink`
+///
+/// Uses num_threads to generate the batches of each iterator in parallel.
+/// The batches are written in the order of the input.
+pub async fn generate_vortex<S, I>(
+    sink: S,
+    iter_iter: I,
+    num_threads: usize,
+    mut progress: Option<ProgressReporter>,
+) -> Result<(), io::Error>
+where
+    S: Sink,
+    I: Iterator<Item: RecordBatchIterator> + Send + 'static,
+{
+    debug!("Generating Vortex with {num_threads} threads");
+    let mut iter_iter = iter_iter.peekable();
+
+    // get schema from the first iterator
+    let Some(first_iter) = iter_iter.peek() else {
+        return Ok(()); // no data shrug
+    };
+    let dtype = DType::from_arrow(first_iter.schema().as_ref());
+
+    let mut statistics =
+        WriteStatistics::new("parts").with_callbacks(write_callbacks(progress.as_ref()));
+    // the stream is moved into the writer, so count the parts separately
+    let num_parts = Arc::new(AtomicUsize::new(0));
+    let captured_num_parts = Arc::clone(&num_parts);
+
+    // generate the batches for each part in parallel
+    let batch_stream = futures::stream::iter(iter_iter)
+        .map(async |iter| {
+            // run on a separate thread
+            tokio::task::spawn(async move { iter.collect::<Vec<RecordBatch>>() })
+                .await
+                .expect("Inner task panicked")
+        })
+        .buffered(num_threads)
+        .map(move |batches| {
+            captured_num_parts.fetch_add(1, Ordering::Relaxed);
+            if let Some(progress) = progress.as_mut() {
+                // the size of each part is not known until the file is written
+                let rows = batches.iter().map(|b| b.num_rows() as u64).sum();
+                progress.part_complete(rows, 0);
+            }
+            futures::stream::iter(batches)
+        })
+        .flatten()
+        .map(|batch| Ok(ArrayRef::from_arrow(batch, false)));
+
+    let writer = VortexWriteOptions::default()
+        .write(
+            SinkWriter::new(sink),
+            ArrayStreamAdapter::new(dtype, batch_stream),
+        )
+        .await
+        .map_err(io::Error::other)?;
+
+    statistics.increment_bytes(writer.bytes);
+    statistics.increment_chunks(num_parts.load(Ordering::Relaxed));
+    writer.sink.flush()
+}
+
+/// A [`VortexWrite`] that writes to a [`Sink`], counting the bytes written
+struct SinkWriter<S> {
+    sink: S,
+    bytes: usize,
+}
+
+impl<S> SinkWriter<S> {
+    fn new(sink: S) -> Self {
+        Self { sink, bytes: 0 }
+    }
+}
+
+impl<S: Sink> VortexWrite for SinkWriter<S> {
+    fn write_all<B: IoBuf>(&mut self, buffer: B) -> impl Future<Output = io::Result<B>> {
+        self.bytes += buffer.as_slice().len();
+        ready(self.sink.sink(buffer.as_slice()).map(|_| buffer))
+    }
+
+    fn flush(&mut self) -> impl Future<Output = io::Result<()>> {
+        ready(Ok(()))
+    }
+
+    /// The sink is flushed once the file is written, as [`Sink::flush`]
+    /// consumes it
+    fn shutdown(&mut self) -> impl Future<Output = io::Result<()>> {
+        ready(Ok(()))
+    }
+}
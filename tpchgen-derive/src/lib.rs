@@ -0,0 +1,644 @@
+//! Derive deterministic TPC-H style data generators for custom tables
+//!
+//! `#[derive(TableGenerator)]` on a row struct generates a generator for the
+//! table in the style of the generators in [`tpchgen::generators`], using the
+//! same random number generators, distributions and text pool as the TPC-H
+//! tables. The generated data is deterministic: the same scale factor always
+//! produces the same rows, and the rows of a part do not depend on how many
+//! parts are generated.
+//!
+//! For a struct `Widget` this generates:
+//!
+//! * `WidgetGenerator`: created with `new(scale_factor, part, part_count)`,
+//!   like `SupplierGenerator`, and iterated with `iter()`
+//! * `WidgetGeneratorIterator`: the iterator of `Widget` rows
+//! * A `Display` impl that formats rows in TBL format
+//! * `WidgetCsv`: the CSV formatter, and an impl of [`tpchgen::csv::CsvRow`]
+//! * `WidgetArrow`: a `tpchgen_arrow::RecordBatchIterator`, if the table is
+//!   annotated with `#[table(arrow)]` (requires the `arrow` and
+//!   `tpchgen-arrow` crates)
+//!
+//! # Attributes
+//!
+//! The struct is annotated with `#[table(scale_base = N)]`, the number of
+//! rows at scale factor 1, and optionally `arrow`.
+//!
+//! Each field is annotated with `#[column(..)]` and one of these kinds:
+//!
+//! | Kind                      | Field type   | Values                                                 |
+//! |---------------------------|--------------|--------------------------------------------------------|
+//! | `key`                     | `i64`        | the row number, starting at 1                          |
+//! | `int(lower, upper)`       | `i32`        | uniformly random between `lower` and `upper` inclusive |
+//! | `distribution = "name"`   | `&'a str`    | a random value of a [`Distributions`] distribution, e.g. `"part_colors"` |
+//! | `text(average_length)`    | `&'a str`    | random text from the [`TextPool`]                      |
+//! | `phone(nation = "field")` | `PhoneNumberInstance` | a phone number for the nation key in an earlier `field` |
+//!
+//! Random columns are seeded from the names of the struct and the field, so
+//! adding a column does not change the values of the others. A seed can be
+//! given explicitly with `seed = N`, e.g. `#[column(int(1, 50), seed = 42)]`.
+//!
+//! [`tpchgen::generators`]: https://docs.rs/tpchgen/latest/tpchgen/generators/index.html
+//! [`tpchgen::csv::CsvRow`]: https://docs.rs/tpchgen/latest/tpchgen/csv/trait.CsvRow.html
+//! [`Distributions`]: https://docs.rs/tpchgen/latest/tpchgen/distribution/struct.Distributions.html
+//! [`TextPool`]: https://docs.rs/tpchgen/latest/tpchgen/text/struct.TextPool.html
+//!
+//! # Example
+//! ```
+//! use tpchgen::random::PhoneNumberInstance;
+//! use tpchgen_derive::TableGenerator;
+//!
+//! #[derive(Debug, Clone, TableGenerator)]
+//! #[table(scale_base = 1000)]
+//! pub struct Widget<'a> {
+//!     #[column(key)]
+//!     w_widgetkey: i64,
+//!     #[column(int(0, 24))]
+//!     w_nationkey: i32,
+//!     #[column(distribution = "part_colors")]
+//!     w_color: &'a str,
+//!     #[column(phone(nation = "w_nationkey"))]
+//!     w_phone: PhoneNumberInstance,
+//!     #[column(text(40))]
+//!     w_comment: &'a str,
+//! }
+//!
+//! // 1000 rows at scale factor 1, split into 10 parts
+//! let generator = WidgetGenerator::new(1.0, 1, 10);
+//! let rows: Vec<_> = generator.iter().map(|w| w.to_string()).collect();
+//! assert_eq!(rows.len(), 100);
+//! assert!(rows[0].starts_with("1|"));
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Expr, Fields, GenericParam, Ident, Lifetime, LitInt,
+    LitStr,
+};
+
+/// Derives a generator for a table, see the [crate documentation](crate)
+#[proc_macro_derive(TableGenerator, attributes(table, column))]
+pub fn derive_table_generator(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match Table::try_from_input(&input) {
+        Ok(table) => table.expand().into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// The table described by a struct
+struct Table {
+    ident: Ident,
+    vis: syn::Visibility,
+    /// Lifetime of the struct, if it has one
+    lifetime: Option<Lifetime>,
+    scale_base: LitInt,
+    arrow: bool,
+    columns: Vec<Column>,
+}
+
+/// A field of the struct
+struct Column {
+    ident: Ident,
+    kind: Kind,
+    seed: i64,
+}
+
+/// How the values of a column are generated
+enum Kind {
+    Key,
+    Int { lower: Expr, upper: Expr },
+    Distribution(Ident),
+    Text(Expr),
+    Phone { nation: Ident },
+}
+
+impl Table {
+    fn try_from_input(input: &DeriveInput) -> syn::Result<Self> {
+        let Data::Struct(data) = &input.data else {
+            return Err(syn::Error::new_spanned(
+                input,
+                "TableGenerator can only be derived for structs",
+            ));
+        };
+        let Fields::Named(fields) = &data.fields else {
+            return Err(syn::Error::new_spanned(
+                &data.fields,
+                "TableGenerator requires named fields",
+            ));
+        };
+
+        let mut lifetime = None;
+        for param in &input.generics.params {
+            match param {
+                GenericParam::Lifetime(def) if lifetime.is_none() => {
+                    lifetime = Some(def.lifetime.clone())
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        param,
+                        "TableGenerator structs can only have a single lifetime parameter",
+                    ))
+                }
+            }
+        }
+
+        let mut scale_base = None;
+        let mut arrow = false;
+        for attr in input.attrs.iter().filter(|a| a.path().is_ident("table")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("scale_base") {
+                    scale_base = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("arrow") {
+                    arrow = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `scale_base` or `arrow`"))
+                }
+            })?;
+        }
+        let Some(scale_base) = scale_base else {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "missing `#[table(scale_base = N)]`",
+            ));
+        };
+
+        let table_name = input.ident.to_string();
+        let mut columns: Vec<Column> = Vec::new();
+        for field in &fields.named {
+            let ident = field.ident.clone().unwrap();
+            let mut kind = None;
+            let mut seed = None;
+            for attr in field.attrs.iter().filter(|a| a.path().is_ident("column")) {
+                attr.parse_nested_meta(|meta| {
+                    let path = &meta.path;
+                    if path.is_ident("key") {
+                        kind = Some(Kind::Key);
+                    } else if path.is_ident("int") {
+                        let content;
+                        syn::parenthesized!(content in meta.input);
+                        let lower = content.parse()?;
+                        content.parse::<syn::Token![,]>()?;
+                        let upper = content.parse()?;
+                        kind = Some(Kind::Int { lower, upper });
+                    } else if path.is_ident("distribution") {
+                        let name: LitStr = meta.value()?.parse()?;
+                        kind = Some(Kind::Distribution(Ident::new(&name.value(), name.span())));
+                    } else if path.is_ident("text") {
+                        let content;
+                        syn::parenthesized!(content in meta.input);
+                        kind = Some(Kind::Text(content.parse()?));
+                    } else if path.is_ident("phone") {
+                        let mut nation = None;
+                        meta.parse_nested_meta(|inner| {
+                            if inner.path.is_ident("nation") {
+                                let name: LitStr = inner.value()?.parse()?;
+                                nation = Some(Ident::new(&name.value(), name.span()));
+                                Ok(())
+                            } else {
+                                Err(inner.error("expected `nation = \"field\"`"))
+                            }
+                        })?;
+                        let nation =
+                            nation.ok_or_else(|| meta.error("expected `phone(nation = \"field\")`"))?;
+                        if !columns.iter().any(|c| c.ident == nation) {
+                            return Err(meta.error(format!(
+                                "the nation column `{nation}` must be a field before `{ident}`"
+                            )));
+                        }
+                        kind = Some(Kind::Phone { nation });
+                    } else if path.is_ident("seed") {
+                        let value: LitInt = meta.value()?.parse()?;
+                        seed = Some(value.base10_parse()?);
+                    } else {
+                        return Err(meta.error(
+                            "expected one of `key`, `int(..)`, `distribution = \"..\"`, `text(..)`, `phone(..)` or `seed = ..`",
+                        ));
+                    }
+                    Ok(())
+                })?;
+            }
+            let Some(kind) = kind else {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "missing `#[column(..)]` with the kind of the column",
+                ));
+            };
+            let seed = seed.unwrap_or_else(|| default_seed(&table_name, &ident.to_string()));
+            columns.push(Column { ident, kind, seed });
+        }
+
+        Ok(Self {
+            ident: input.ident.clone(),
+            vis: input.vis.clone(),
+            lifetime,
+            scale_base,
+            arrow,
+            columns,
+        })
+    }
+
+    fn expand(&self) -> TokenStream2 {
+        let Self {
+            ident,
+            vis,
+            scale_base,
+            ..
+        } = self;
+        let generator = format_ident!("{ident}Generator");
+        let iterator = format_ident!("{ident}GeneratorIterator");
+        let csv = format_ident!("{ident}Csv");
+
+        // the row type with the lifetime of the generator ('a), of the
+        // CSV formatter and an anonymous lifetime
+        let a = Lifetime::new("'a", Span::call_site());
+        let (row, row_anon, csv_generics) = match &self.lifetime {
+            Some(lifetime) => (quote!(#ident<#a>), quote!(#ident<'_>), quote!(<#lifetime>)),
+            None => (quote!(#ident), quote!(#ident), quote!()),
+        };
+        let (row_csv, csv_anon) = match &self.lifetime {
+            Some(lifetime) => (quote!(#ident<#lifetime>), quote!(#csv<'_>)),
+            None => (quote!(#ident), quote!(#csv)),
+        };
+
+        let randoms: Vec<_> = self
+            .columns
+            .iter()
+            .filter_map(|c| {
+                c.random_type()
+                    .map(|t| (format_ident!("{}_random", c.ident), t))
+            })
+            .collect();
+        let random_idents: Vec<_> = randoms.iter().map(|(i, _)| i).collect();
+        let random_types: Vec<_> = randoms.iter().map(|(_, t)| t).collect();
+        let random_inits: Vec<_> = self
+            .columns
+            .iter()
+            .filter_map(Column::random_init)
+            .collect();
+        let fields: Vec<_> = self.columns.iter().map(|c| &c.ident).collect();
+        let values: Vec<_> = self.columns.iter().map(Column::value).collect();
+
+        let tbl_format = "{}|".repeat(self.columns.len());
+        let csv_format = self
+            .columns
+            .iter()
+            .map(|c| match c.kind {
+                // text may contain commas
+                Kind::Text(_) => "\"{}\"",
+                _ => "{}",
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let csv_header = self
+            .columns
+            .iter()
+            .map(|c| c.ident.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let generator_doc = format!("Generator for [`{ident}`] rows");
+        let iterator_doc = format!("Iterator that generates [`{ident}`] rows");
+        let csv_doc = format!("Write [`{ident}`]s in CSV format.");
+
+        let arrow = self
+            .arrow
+            .then(|| self.expand_arrow(&generator, &iterator, &row_anon));
+
+        quote! {
+            impl ::std::fmt::Display for #row_anon {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    write!(f, #tbl_format, #(self.#fields),*)
+                }
+            }
+
+            #[doc = #csv_doc]
+            #vis struct #csv #csv_generics {
+                inner: #row_csv,
+            }
+
+            impl #csv_generics #csv #csv_generics {
+                pub fn new(inner: #row_csv) -> Self {
+                    Self { inner }
+                }
+
+                /// Returns the CSV header for the table
+                pub fn header() -> &'static str {
+                    #csv_header
+                }
+            }
+
+            impl ::std::fmt::Display for #csv_anon {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    write!(f, #csv_format, #(self.inner.#fields),*)
+                }
+            }
+
+            impl #csv_generics ::tpchgen::csv::CsvRow for #row_csv {
+                type Csv = #csv #csv_generics;
+
+                fn csv_header() -> &'static str {
+                    #csv::header()
+                }
+
+                fn into_csv(self) -> Self::Csv {
+                    #csv::new(self)
+                }
+            }
+
+            #[doc = #generator_doc]
+            #[derive(Debug, Clone)]
+            #vis struct #generator<#a> {
+                scale_factor: f64,
+                part: i32,
+                part_count: i32,
+                distributions: &#a ::tpchgen::distribution::Distributions,
+                text_pool: &#a ::tpchgen::text::TextPool,
+            }
+
+            impl<#a> #generator<#a> {
+                /// Number of rows at scale factor 1
+                pub const SCALE_BASE: i32 = #scale_base;
+
+                /// Creates a new generator with the given scale factor, using
+                /// the default distributions and text pool
+                pub fn new(scale_factor: f64, part: i32, part_count: i32) -> #generator<'static> {
+                    #generator::new_with_distributions_and_text_pool(
+                        scale_factor,
+                        part,
+                        part_count,
+                        ::tpchgen::distribution::Distributions::static_default(),
+                        ::tpchgen::text::TextPool::get_or_init_default(),
+                    )
+                }
+
+                /// Creates a generator with specified distributions and text pool
+                pub fn new_with_distributions_and_text_pool<'b>(
+                    scale_factor: f64,
+                    part: i32,
+                    part_count: i32,
+                    distributions: &'b ::tpchgen::distribution::Distributions,
+                    text_pool: &'b ::tpchgen::text::TextPool,
+                ) -> #generator<'b> {
+                    #generator {
+                        scale_factor,
+                        part,
+                        part_count,
+                        distributions,
+                        text_pool,
+                    }
+                }
+
+                /// Return the row count for the given scale factor and generator part count
+                pub fn calculate_row_count(scale_factor: f64, part: i32, part_count: i32) -> i64 {
+                    ::tpchgen::dates::GenerateUtils::calculate_row_count(
+                        Self::SCALE_BASE,
+                        scale_factor,
+                        part,
+                        part_count,
+                    )
+                }
+
+                /// Returns an iterator over the rows
+                pub fn iter(&self) -> #iterator<#a> {
+                    #iterator::new(
+                        self.distributions,
+                        self.text_pool,
+                        ::tpchgen::dates::GenerateUtils::calculate_start_index(
+                            Self::SCALE_BASE,
+                            self.scale_factor,
+                            self.part,
+                            self.part_count,
+                        ),
+                        Self::calculate_row_count(self.scale_factor, self.part, self.part_count),
+                    )
+                }
+            }
+
+            impl<#a> ::std::iter::IntoIterator for #generator<#a> {
+                type Item = #row;
+                type IntoIter = #iterator<#a>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    self.iter()
+                }
+            }
+
+            #[doc = #iterator_doc]
+            #[derive(Debug)]
+            #vis struct #iterator<#a> {
+                #(#random_idents: #random_types,)*
+                start_index: i64,
+                row_count: i64,
+                index: i64,
+                _text: ::std::marker::PhantomData<&#a ::tpchgen::text::TextPool>,
+            }
+
+            impl<#a> #iterator<#a> {
+                #[allow(unused_variables)]
+                fn new(
+                    distributions: &#a ::tpchgen::distribution::Distributions,
+                    text_pool: &#a ::tpchgen::text::TextPool,
+                    start_index: i64,
+                    row_count: i64,
+                ) -> Self {
+                    #(
+                        let mut #random_idents = #random_inits;
+                        #random_idents.advance_rows(start_index);
+                    )*
+                    Self {
+                        #(#random_idents,)*
+                        start_index,
+                        row_count,
+                        index: 0,
+                        _text: ::std::marker::PhantomData,
+                    }
+                }
+            }
+
+            impl<#a> ::std::iter::Iterator for #iterator<#a> {
+                type Item = #row;
+
+                fn next(&mut self) -> Option<Self::Item> {
+                    if self.index >= self.row_count {
+                        return None;
+                    }
+                    #(let #fields = #values;)*
+                    #(self.#random_idents.row_finished();)*
+                    self.index += 1;
+                    Some(#ident { #(#fields),* })
+                }
+            }
+
+            #arrow
+        }
+    }
+
+    /// Generates the `RecordBatchIterator` for `#[table(arrow)]`
+    fn expand_arrow(
+        &self,
+        generator: &Ident,
+        iterator: &Ident,
+        row_anon: &TokenStream2,
+    ) -> TokenStream2 {
+        let Self { ident, vis, .. } = self;
+        let arrow = format_ident!("{ident}Arrow");
+        let doc = format!("Generate [`{ident}`]s in `RecordBatch` format");
+        let fields: Vec<_> = self.columns.iter().map(|c| &c.ident).collect();
+        let names: Vec<_> = self.columns.iter().map(|c| c.ident.to_string()).collect();
+        let (data_types, arrays): (Vec<_>, Vec<_>) = self
+            .columns
+            .iter()
+            .map(|c| {
+                let field = &c.ident;
+                match c.kind {
+                    Kind::Key => (
+                        quote!(Int64),
+                        quote!(::arrow::array::Int64Array::from_iter_values(
+                            rows.iter().map(|r| r.#field)
+                        )),
+                    ),
+                    Kind::Int { .. } => (
+                        quote!(Int32),
+                        quote!(::arrow::array::Int32Array::from_iter_values(
+                            rows.iter().map(|r| r.#field)
+                        )),
+                    ),
+                    Kind::Distribution(_) | Kind::Text(_) => (
+                        quote!(Utf8View),
+                        quote!(::arrow::array::StringViewArray::from_iter_values(
+                            rows.iter().map(|r| r.#field)
+                        )),
+                    ),
+                    Kind::Phone { .. } => (
+                        quote!(Utf8View),
+                        quote!(::arrow::array::StringViewArray::from_iter_values(
+                            rows.iter().map(|r| r.#field.to_string())
+                        )),
+                    ),
+                }
+            })
+            .unzip();
+
+        quote! {
+            #[doc = #doc]
+            #vis struct #arrow {
+                inner: #iterator<'static>,
+                batch_size: usize,
+                schema: ::arrow::datatypes::SchemaRef,
+            }
+
+            impl #arrow {
+                pub fn new(generator: #generator<'static>) -> Self {
+                    Self {
+                        inner: generator.iter(),
+                        batch_size: ::tpchgen_arrow::DEFAULT_BATCH_SIZE,
+                        schema: Self::schema_ref(),
+                    }
+                }
+
+                /// Set the batch size
+                pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+                    self.batch_size = batch_size;
+                    self
+                }
+
+                /// Returns the schema of the table
+                pub fn schema_ref() -> ::arrow::datatypes::SchemaRef {
+                    ::std::sync::Arc::new(::arrow::datatypes::Schema::new(vec![
+                        #(::arrow::datatypes::Field::new(
+                            #names,
+                            ::arrow::datatypes::DataType::#data_types,
+                            false,
+                        ),)*
+                    ]))
+                }
+
+                /// Convert `rows` to a `RecordBatch` with this table's schema
+                pub fn batch_from_rows(rows: &[#row_anon]) -> ::arrow::array::RecordBatch {
+                    #(let #fields = #arrays;)*
+                    ::arrow::array::RecordBatch::try_new(
+                        Self::schema_ref(),
+                        vec![#(::std::sync::Arc::new(#fields)),*],
+                    )
+                    .unwrap()
+                }
+            }
+
+            impl ::tpchgen_arrow::RecordBatchIterator for #arrow {
+                fn schema(&self) -> &::arrow::datatypes::SchemaRef {
+                    &self.schema
+                }
+            }
+
+            impl ::std::iter::Iterator for #arrow {
+                type Item = ::arrow::array::RecordBatch;
+
+                fn next(&mut self) -> Option<Self::Item> {
+                    let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
+                    if rows.is_empty() {
+                        return None;
+                    }
+                    Some(Self::batch_from_rows(&rows))
+                }
+            }
+        }
+    }
+}
+
+impl Column {
+    /// Returns the type of the random number generator of the column
+    fn random_type(&self) -> Option<TokenStream2> {
+        match self.kind {
+            Kind::Key => None,
+            Kind::Int { .. } => Some(quote!(::tpchgen::random::RandomBoundedInt)),
+            Kind::Distribution(_) => Some(quote!(::tpchgen::random::RandomString<'a>)),
+            Kind::Text(_) => Some(quote!(::tpchgen::random::RandomText<'a>)),
+            Kind::Phone { .. } => Some(quote!(::tpchgen::random::RandomPhoneNumber)),
+        }
+    }
+
+    /// Returns the expression that creates the random number generator
+    fn random_init(&self) -> Option<TokenStream2> {
+        let seed = self.seed;
+        match &self.kind {
+            Kind::Key => None,
+            Kind::Int { lower, upper } => Some(quote!(
+                ::tpchgen::random::RandomBoundedInt::new(#seed, #lower, #upper)
+            )),
+            Kind::Distribution(name) => Some(quote!(
+                ::tpchgen::random::RandomString::new(#seed, distributions.#name())
+            )),
+            Kind::Text(average_length) => Some(quote!(
+                ::tpchgen::random::RandomText::new(#seed, text_pool, (#average_length) as f64)
+            )),
+            Kind::Phone { .. } => Some(quote!(::tpchgen::random::RandomPhoneNumber::new(#seed))),
+        }
+    }
+
+    /// Returns the expression for the value of the column in the current row
+    fn value(&self) -> TokenStream2 {
+        let random = format_ident!("{}_random", self.ident);
+        match &self.kind {
+            Kind::Key => quote!(self.start_index + self.index + 1),
+            Kind::Phone { nation } => quote!(self.#random.next_value(#nation as i64)),
+            Kind::Int { .. } | Kind::Distribution(_) | Kind::Text(_) => {
+                quote!(self.#random.next_value())
+            }
+        }
+    }
+}
+
+/// Returns a seed for the column `column` of `table`, between 1 and 2^31 - 2
+/// like the seeds of the TPC-H columns (FNV-1a hash)
+fn default_seed(table: &str, column: &str) -> i64 {
+    let mut hash: u32 = 0x811c9dc5;
+    for byte in table.bytes().chain([b'.']).chain(column.bytes()) {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    (hash % (i32::MAX as u32 - 1)) as i64 + 1
+}
@@ -0,0 +1,145 @@
+//! Tests for generators derived with `#[derive(TableGenerator)]`
+
+use arrow::array::{Array, Int32Array, Int64Array, StringViewArray};
+use tpchgen::csv::CsvRow;
+use tpchgen::random::PhoneNumberInstance;
+use tpchgen::reader::CsvReader;
+use tpchgen_arrow::RecordBatchIterator;
+use tpchgen_derive::TableGenerator;
+
+#[derive(Debug, Clone, PartialEq, TableGenerator)]
+#[table(scale_base = 1000, arrow)]
+pub struct Widget<'a> {
+    #[column(key)]
+    w_widgetkey: i64,
+    #[column(int(0, 24))]
+    w_nationkey: i32,
+    #[column(distribution = "part_colors")]
+    w_color: &'a str,
+    #[column(phone(nation = "w_nationkey"))]
+    w_phone: PhoneNumberInstance,
+    #[column(int(1, 50), seed = 42)]
+    w_size: i32,
+    #[column(text(40))]
+    w_comment: &'a str,
+}
+
+/// A table without borrowed columns
+#[derive(Debug, Clone, TableGenerator)]
+#[table(scale_base = 10)]
+struct Counter {
+    #[column(key)]
+    c_key: i64,
+    #[column(int(-5, 5))]
+    c_value: i32,
+}
+
+#[test]
+fn test_rows() {
+    let rows: Vec<_> = WidgetGenerator::new(1.0, 1, 1).iter().collect();
+    assert_eq!(rows.len(), 1000);
+    for (i, row) in rows.iter().enumerate() {
+        assert_eq!(row.w_widgetkey, i as i64 + 1);
+        assert!((0..=24).contains(&row.w_nationkey));
+        assert!((1..=50).contains(&row.w_size));
+        assert!(!row.w_color.is_empty());
+        assert!((16..=64).contains(&row.w_comment.len()));
+        // the country code of the phone number is the nation key + 10
+        let phone = row.w_phone.to_string();
+        assert_eq!(phone[..2].parse::<i32>().unwrap(), row.w_nationkey + 10);
+    }
+
+    // columns with different seeds have different values
+    let nations: Vec<_> = rows.iter().map(|r| r.w_nationkey).collect();
+    let sizes: Vec<_> = rows.iter().map(|r| r.w_size - 1).collect();
+    assert_ne!(nations, sizes);
+
+    assert_eq!(WidgetGenerator::calculate_row_count(0.5, 1, 1), 500);
+    assert_eq!(CounterGenerator::new(2.0, 1, 1).iter().count(), 20);
+    assert!(CounterGenerator::new(1.0, 1, 1)
+        .into_iter()
+        .all(|c| (-5..=5).contains(&c.c_value)));
+}
+
+#[test]
+fn test_deterministic_across_parts() {
+    let all: Vec<_> = WidgetGenerator::new(1.0, 1, 1).iter().collect();
+    assert_eq!(
+        all,
+        WidgetGenerator::new(1.0, 1, 1).iter().collect::<Vec<_>>()
+    );
+
+    let parts: Vec<_> = (1..=7)
+        .flat_map(|part| WidgetGenerator::new(1.0, part, 7).iter())
+        .collect();
+    assert_eq!(all, parts);
+}
+
+#[test]
+fn test_tbl_and_csv() {
+    let row = WidgetGenerator::new(1.0, 1, 1).iter().next().unwrap();
+    let expected_tbl = format!(
+        "1|{}|{}|{}|{}|{}|",
+        row.w_nationkey, row.w_color, row.w_phone, row.w_size, row.w_comment
+    );
+    assert_eq!(row.to_string(), expected_tbl);
+
+    assert_eq!(
+        WidgetCsv::header(),
+        "w_widgetkey,w_nationkey,w_color,w_phone,w_size,w_comment"
+    );
+    let expected_csv = format!(
+        "1,{},{},{},{},\"{}\"",
+        row.w_nationkey, row.w_color, row.w_phone, row.w_size, row.w_comment
+    );
+    assert_eq!(row.clone().into_csv().to_string(), expected_csv);
+
+    let mut csv = String::new();
+    std::io::Read::read_to_string(
+        &mut CsvReader::new(CounterGenerator::new(1.0, 1, 1)),
+        &mut csv,
+    )
+    .unwrap();
+    assert_eq!(csv.lines().count(), 11);
+    assert!(csv.starts_with("c_key,c_value\n1,"));
+}
+
+#[test]
+fn test_arrow() {
+    let rows: Vec<_> = WidgetGenerator::new(1.0, 1, 1).iter().collect();
+    let mut arrow = WidgetArrow::new(WidgetGenerator::new(1.0, 1, 1)).with_batch_size(300);
+    assert_eq!(arrow.schema().fields().len(), 6);
+
+    let batches: Vec<_> = arrow.by_ref().collect();
+    let sizes: Vec<_> = batches.iter().map(|b| b.num_rows()).collect();
+    assert_eq!(sizes, vec![300, 300, 300, 100]);
+
+    let batch = &batches[1];
+    let keys = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    let nations = batch
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int32Array>()
+        .unwrap();
+    let phones = batch
+        .column(3)
+        .as_any()
+        .downcast_ref::<StringViewArray>()
+        .unwrap();
+    let comments = batch
+        .column(5)
+        .as_any()
+        .downcast_ref::<StringViewArray>()
+        .unwrap();
+    for (i, row) in rows[300..600].iter().enumerate() {
+        assert_eq!(keys.value(i), row.w_widgetkey);
+        assert_eq!(nations.value(i), row.w_nationkey);
+        assert_eq!(phones.value(i), row.w_phone.to_string());
+        assert_eq!(comments.value(i), row.w_comment);
+    }
+    assert_eq!(keys.null_count(), 0);
+}
@@ -0,0 +1,41 @@
+import pyarrow as pa
+import pytest
+
+import tpchgen
+
+
+def test_tables():
+    assert tpchgen.tables() == [
+        "nation", "region", "part", "supplier", "partsupp", "customer", "orders", "lineitem",
+    ]
+
+
+@pytest.mark.parametrize(
+    "table, rows",
+    [("nation", 25), ("region", 5), ("orders", 15000), ("lineitem", 60175)],
+)
+def test_row_counts(table, rows):
+    assert pa.table(tpchgen.Generator(table, scale_factor=0.01)).num_rows == rows
+
+
+def test_parts():
+    parts = [
+        pa.table(tpchgen.Generator("orders", scale_factor=0.01, part=part, num_parts=3))
+        for part in (1, 2, 3)
+    ]
+    whole = pa.table(tpchgen.Generator("orders", scale_factor=0.01))
+    assert pa.concat_tables(parts).equals(whole)
+
+
+def test_batches_are_generated_while_read():
+    generator = tpchgen.Generator("lineitem", scale_factor=0.01, batch_size=1000)
+    reader = pa.RecordBatchReader.from_stream(generator)
+    assert reader.schema.names[:2] == ["l_orderkey", "l_partkey"]
+    assert reader.read_next_batch().num_rows == 1000
+
+
+def test_invalid_arguments():
+    with pytest.raises(ValueError, match="unknown table"):
+        tpchgen.Generator("foo")
+    with pytest.raises(ValueError, match="invalid part"):
+        tpchgen.Generator("orders", part=3, num_parts=2)
@@ -0,0 +1,229 @@
+//! Python bindings for `tpchgen`
+//!
+//! The `tpchgen` Python module generates TPC-H tables in process as Arrow
+//! data, without writing files or running `tpchgen-cli`:
+//!
+//! ```python
+//! import pyarrow as pa
+//! import tpchgen
+//!
+//! lineitem = pa.table(tpchgen.Generator("lineitem", scale_factor=0.1))
+//! ```
+//!
+//! A `Generator` implements the [Arrow PyCapsule Interface]: its
+//! `__arrow_c_stream__` method exports the record batches of
+//! [`tpchgen_arrow`] through the Arrow C Stream Interface, so pyarrow,
+//! polars, DuckDB and other Arrow based libraries read them without copying
+//! or converting the data. The batches are generated while they are read.
+//!
+//! [Arrow PyCapsule Interface]: https://arrow.apache.org/docs/format/CDataInterface/PyCapsuleInterface.html
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::ffi::FFI_ArrowSchema;
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyCapsule;
+use std::ffi::CString;
+use tpchgen::generators::{
+    CustomerGenerator, LineItemGenerator, NationGenerator, OrderGenerator, PartGenerator,
+    PartSuppGenerator, RegionGenerator, SupplierGenerator,
+};
+use tpchgen_arrow::{
+    CustomerArrow, LineItemArrow, NationArrow, OrderArrow, PartArrow, PartSuppArrow,
+    RecordBatchIterator, RegionArrow, SupplierArrow, DEFAULT_BATCH_SIZE,
+};
+
+/// The names of the tables, in the order of the specification
+const TABLES: [&str; 8] = [
+    "nation", "region", "part", "supplier", "partsupp", "customer", "orders", "lineitem",
+];
+
+/// Generates the rows of one part of a TPC-H table as Arrow record batches
+///
+/// The generator can be read any number of times, each time from the first
+/// row.
+#[pyclass(frozen, module = "tpchgen")]
+struct Generator {
+    table: &'static str,
+    scale_factor: f64,
+    part: i32,
+    num_parts: i32,
+    batch_size: usize,
+}
+
+impl Generator {
+    /// Returns a new iterator over the record batches of the table
+    fn batches(&self) -> Box<dyn RecordBatchIterator> {
+        let (sf, part, num_parts) = (self.scale_factor, self.part, self.num_parts);
+        let batch_size = self.batch_size;
+        match self.table {
+            "nation" => Box::new(
+                NationArrow::new(NationGenerator::new(sf, part, num_parts))
+                    .with_batch_size(batch_size),
+            ),
+            "region" => Box::new(
+                RegionArrow::new(RegionGenerator::new(sf, part, num_parts))
+                    .with_batch_size(batch_size),
+            ),
+            "part" => Box::new(
+                PartArrow::new(PartGenerator::new(sf, part, num_parts)).with_batch_size(batch_size),
+            ),
+            "supplier" => Box::new(
+                SupplierArrow::new(SupplierGenerator::new(sf, part, num_parts))
+                    .with_batch_size(batch_size),
+            ),
+            "partsupp" => Box::new(
+                PartSuppArrow::new(PartSuppGenerator::new(sf, part, num_parts))
+                    .with_batch_size(batch_size),
+            ),
+            "customer" => Box::new(
+                CustomerArrow::new(CustomerGenerator::new(sf, part, num_parts))
+                    .with_batch_size(batch_size),
+            ),
+            "orders" => Box::new(
+                OrderArrow::new(OrderGenerator::new(sf, part, num_parts))
+                    .with_batch_size(batch_size),
+            ),
+            "lineitem" => Box::new(
+                LineItemArrow::new(LineItemGenerator::new(sf, part, num_parts))
+                    .with_batch_size(batch_size),
+            ),
+            _ => unreachable!("unknown table {}", self.table),
+        }
+    }
+}
+
+#[pymethods]
+impl Generator {
+    /// Creates a generator of part `part` (1 based) of `num_parts` of
+    /// `table`, e.g. `"lineitem"`, at `scale_factor`, in batches of
+    /// `batch_size` rows
+    #[new]
+    #[pyo3(signature = (table, scale_factor = 1.0, part = 1, num_parts = 1, batch_size = DEFAULT_BATCH_SIZE))]
+    fn new(
+        table: &str,
+        scale_factor: f64,
+        part: i32,
+        num_parts: i32,
+        batch_size: usize,
+    ) -> PyResult<Self> {
+        let Some(table) = TABLES.into_iter().find(|t| *t == table) else {
+            return Err(PyValueError::new_err(format!(
+                "unknown table '{table}', expected one of {}",
+                TABLES.join(", ")
+            )));
+        };
+        if !(scale_factor.is_finite() && scale_factor > 0.0) {
+            return Err(PyValueError::new_err(format!(
+                "invalid scale factor {scale_factor}, must be positive"
+            )));
+        }
+        if num_parts < 1 || !(1..=num_parts).contains(&part) {
+            return Err(PyValueError::new_err(format!(
+                "invalid part {part} of {num_parts} parts"
+            )));
+        }
+        if batch_size == 0 {
+            return Err(PyValueError::new_err("batch_size must be at least 1"));
+        }
+        Ok(Self {
+            table,
+            scale_factor,
+            part,
+            num_parts,
+            batch_size,
+        })
+    }
+
+    /// The name of the table
+    #[getter]
+    fn table(&self) -> &'static str {
+        self.table
+    }
+
+    /// The scale factor
+    #[getter]
+    fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// The (1 based) part of the table that is generated
+    #[getter]
+    fn part(&self) -> i32 {
+        self.part
+    }
+
+    /// The number of parts the table is split into
+    #[getter]
+    fn num_parts(&self) -> i32 {
+        self.num_parts
+    }
+
+    /// Exports the schema of the table as an `arrow_schema` PyCapsule
+    fn __arrow_c_schema__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyCapsule>> {
+        let schema = FFI_ArrowSchema::try_from(self.batches().schema().as_ref())
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        PyCapsule::new(py, schema, Some(CString::new("arrow_schema")?))
+    }
+
+    /// Exports the record batches as an `arrow_array_stream` PyCapsule,
+    /// which generates them while they are read
+    ///
+    /// `requested_schema` is ignored: the batches always have the schema of
+    /// the table, which consumers may cast.
+    #[pyo3(signature = (requested_schema = None))]
+    fn __arrow_c_stream__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyCapsule>> {
+        let _ = requested_schema;
+        let reader = Box::new(BatchReader(self.batches()));
+        let stream = FFI_ArrowArrayStream::new(reader);
+        PyCapsule::new(py, stream, Some(CString::new("arrow_array_stream")?))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Generator('{}', scale_factor={}, part={}, num_parts={}, batch_size={})",
+            self.table, self.scale_factor, self.part, self.num_parts, self.batch_size
+        )
+    }
+}
+
+/// A [`RecordBatchReader`] of the batches of a [`RecordBatchIterator`], for
+/// the C Stream Interface
+struct BatchReader(Box<dyn RecordBatchIterator>);
+
+impl Iterator for BatchReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Ok)
+    }
+}
+
+impl RecordBatchReader for BatchReader {
+    fn schema(&self) -> SchemaRef {
+        self.0.schema().clone()
+    }
+}
+
+/// Returns the names of the tables, e.g. `["nation", "region", ...]`
+#[pyfunction]
+fn tables() -> Vec<&'static str> {
+    TABLES.to_vec()
+}
+
+/// The `tpchgen` Python module
+#[pymodule]
+#[pyo3(name = "tpchgen")]
+fn tpchgen_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add_class::<Generator>()?;
+    m.add_function(wrap_pyfunction!(tables, m)?)?;
+    Ok(())
+}
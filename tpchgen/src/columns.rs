@@ -0,0 +1,482 @@
+//! Standalone generators for individual TPC-H column kinds.
+//!
+//! The table generators in [`generators`] are built from a handful of reusable
+//! column generators. This module exposes the most commonly useful ones so you
+//! can build custom tables whose values blend seamlessly with TPC-H data.
+//!
+//! Each generator is constructed with the same seed and parameters as the
+//! corresponding TPC-H column, from the [`ColumnSeeds`] of the table
+//! generators (e.g. [`AddressGenerator::customer`] with [`ColumnSeeds::SPEC`]
+//! produces exactly the `c_address` values of the CUSTOMER table, and with
+//! `ColumnSeeds::SPEC.with_offset(n)` those of a `CustomerGenerator` with a
+//! seed offset of `n`), or with a custom seed via `new`.
+//!
+//! Like the table generators, column generators produce one value per row.
+//! When used as an [`Iterator`] each call to `next` returns the value for the
+//! next row. When driving them manually, call `next_value` followed by
+//! `row_finished` once per row, and use `advance_rows` to skip directly to a
+//! later row.
+//!
+//! # Example
+//! ```
+//! # use tpchgen::columns::{AccountBalanceGenerator, AddressGenerator};
+//! # use tpchgen::random::ColumnSeeds;
+//! // The same addresses and balances as the first 3 rows of the CUSTOMER table
+//! let addresses: Vec<_> = AddressGenerator::customer(&ColumnSeeds::SPEC)
+//!     .take(3)
+//!     .map(|address| address.to_string())
+//!     .collect();
+//! let balances: Vec<_> = AccountBalanceGenerator::customer(&ColumnSeeds::SPEC)
+//!     .take(3)
+//!     .map(|balance| balance.to_string())
+//!     .collect();
+//! assert_eq!(
+//!     addresses,
+//!     vec!["IVhzIApeRb ot,c,E", "XSTf4,NCwDVaWNe6tEgvwfmRchLXak", "MG9kdTD2WBHm"]
+//! );
+//! assert_eq!(balances, vec!["711.56", "121.65", "7498.12"]);
+//! ```
+//!
+//! [`generators`]: crate::generators
+
+use crate::decimal::TPCHDecimal;
+use crate::generators::ClerkName;
+use crate::random::{
+    ColumnSeeds, Extreme, PhoneNumberInstance, RandomAlphaNumeric, RandomAlphaNumericInstance,
+    RandomBoundedInt, RandomPhoneNumber, RandomText,
+};
+use crate::text::TextPool;
+
+/// Generates variable length comments from a [`TextPool`].
+///
+/// # Example
+/// ```
+/// # use tpchgen::columns::CommentGenerator;
+/// # use tpchgen::random::ColumnSeeds;
+/// # use tpchgen::text::TextPool;
+/// // The first 2 comments of the ORDERS table
+/// let comments: Vec<_> = CommentGenerator::order(TextPool::get_or_init_default(), &ColumnSeeds::SPEC)
+///     .take(2)
+///     .collect();
+/// assert_eq!(
+///     comments,
+///     vec![
+///         "nstructions sleep furiously among ",
+///         " foxes. pending accounts at the pending, silent asymptot"
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommentGenerator<'a> {
+    inner: RandomText<'a>,
+}
+
+impl<'a> CommentGenerator<'a> {
+    /// Creates a comment generator with the given seed and average length.
+    ///
+    /// Comment lengths are uniformly distributed between 40% and 160% of
+    /// `average_length`, as required by the TPC-H specification.
+    pub fn new(seed: i64, text_pool: &'a TextPool, average_length: i32) -> Self {
+        Self {
+            inner: RandomText::new(seed, text_pool, average_length as f64),
+        }
+    }
+
+    /// Generator for `n_comment` (NATION table) from `seeds`
+    pub fn nation(text_pool: &'a TextPool, seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.n_comment, text_pool, 72)
+    }
+
+    /// Generator for `r_comment` (REGION table) from `seeds`
+    pub fn region(text_pool: &'a TextPool, seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.r_comment, text_pool, 72)
+    }
+
+    /// Generator for `p_comment` (PART table) from `seeds`
+    pub fn part(text_pool: &'a TextPool, seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.p_comment, text_pool, 14)
+    }
+
+    /// Generator for `s_comment` (SUPPLIER table) from `seeds`.
+    ///
+    /// Note these are the comments before the "Customer Complaints" /
+    /// "Customer Recommends" text is injected into some of them, see
+    /// [`SupplierGenerator::BBB_BASE_TEXT`].
+    ///
+    /// [`SupplierGenerator::BBB_BASE_TEXT`]: crate::generators::SupplierGenerator::BBB_BASE_TEXT
+    pub fn supplier(text_pool: &'a TextPool, seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.s_comment, text_pool, 63)
+    }
+
+    /// Generator for `c_comment` (CUSTOMER table) from `seeds`
+    pub fn customer(text_pool: &'a TextPool, seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.c_comment, text_pool, 73)
+    }
+
+    /// Generator for `o_comment` (ORDERS table) from `seeds`
+    pub fn order(text_pool: &'a TextPool, seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.o_comment, text_pool, 49)
+    }
+
+    /// Returns the comment for the current row
+    pub fn next_value(&mut self) -> &'a str {
+        self.inner.next_value()
+    }
+
+    /// Returns the comment for the current row, with the minimum or maximum
+    /// length if `extreme` is set
+    pub(crate) fn next_value_or_extreme(&mut self, extreme: Option<Extreme>) -> &'a str {
+        self.inner.next_value_or_extreme(extreme)
+    }
+
+    /// Skips the specified number of rows
+    pub fn advance_rows(&mut self, row_count: i64) {
+        self.inner.advance_rows(row_count);
+    }
+
+    /// Moves to the next row
+    pub fn row_finished(&mut self) {
+        self.inner.row_finished();
+    }
+}
+
+impl<'a> Iterator for CommentGenerator<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.next_value();
+        self.row_finished();
+        Some(value)
+    }
+}
+
+/// Generates random alphanumeric addresses.
+///
+/// # Example
+/// ```
+/// # use tpchgen::columns::AddressGenerator;
+/// # use tpchgen::random::ColumnSeeds;
+/// let mut generator = AddressGenerator::supplier(&ColumnSeeds::SPEC);
+/// // skip directly to the 3rd row of the SUPPLIER table
+/// generator.advance_rows(2);
+/// assert_eq!(
+///     generator.next().unwrap().to_string(),
+///     "q1,G3Pj6OjIuUYfUoH18BFTKP5aU9bEV3"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct AddressGenerator {
+    inner: RandomAlphaNumeric,
+}
+
+impl AddressGenerator {
+    /// Average address length used by the TPC-H tables
+    pub const AVERAGE_LENGTH: i32 = 25;
+
+    /// Creates an address generator with the given seed and average length
+    pub fn new(seed: i64, average_length: i32) -> Self {
+        Self {
+            inner: RandomAlphaNumeric::new(seed, average_length),
+        }
+    }
+
+    /// Generator for `s_address` (SUPPLIER table) from `seeds`
+    pub fn supplier(seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.s_address, Self::AVERAGE_LENGTH)
+    }
+
+    /// Generator for `c_address` (CUSTOMER table) from `seeds`
+    pub fn customer(seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.c_address, Self::AVERAGE_LENGTH)
+    }
+
+    /// Uses the characters of the addresses of `text_pool`, if it is an
+    /// extended text pool, see [`TextPool::new_extended`]
+    pub fn with_text_pool(mut self, text_pool: &TextPool) -> Self {
+        self.inner = self.inner.with_alphabet(text_pool.alphabet());
+        self
+    }
+
+    /// Returns the address for the current row
+    pub fn next_value(&mut self) -> RandomAlphaNumericInstance {
+        self.inner.next_value()
+    }
+
+    /// Returns the address for the current row, with the minimum or maximum
+    /// length if `extreme` is set
+    pub(crate) fn next_value_or_extreme(
+        &mut self,
+        extreme: Option<Extreme>,
+    ) -> RandomAlphaNumericInstance {
+        self.inner.next_value_or_extreme(extreme)
+    }
+
+    /// Skips the specified number of rows
+    pub fn advance_rows(&mut self, row_count: i64) {
+        self.inner.advance_rows(row_count);
+    }
+
+    /// Moves to the next row
+    pub fn row_finished(&mut self) {
+        self.inner.row_finished();
+    }
+}
+
+impl Iterator for AddressGenerator {
+    type Item = RandomAlphaNumericInstance;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.next_value();
+        self.row_finished();
+        Some(value)
+    }
+}
+
+/// Generates phone numbers whose country code is derived from a nation key.
+///
+/// As each phone number depends on the row's nation key, this generator does
+/// not implement [`Iterator`].
+///
+/// # Example
+/// ```
+/// # use tpchgen::columns::PhoneNumberGenerator;
+/// # use tpchgen::random::ColumnSeeds;
+/// let mut generator = PhoneNumberGenerator::supplier(&ColumnSeeds::SPEC);
+/// // The first row of the SUPPLIER table has nation key 17
+/// assert_eq!(generator.next_value(17).to_string(), "27-918-335-1736");
+/// generator.row_finished();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PhoneNumberGenerator {
+    inner: RandomPhoneNumber,
+}
+
+impl PhoneNumberGenerator {
+    /// Creates a phone number generator with the given seed
+    pub fn new(seed: i64) -> Self {
+        Self {
+            inner: RandomPhoneNumber::new(seed),
+        }
+    }
+
+    /// Generator for `s_phone` (SUPPLIER table) from `seeds`
+    pub fn supplier(seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.s_phone)
+    }
+
+    /// Generator for `c_phone` (CUSTOMER table) from `seeds`
+    pub fn customer(seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.c_phone)
+    }
+
+    /// Returns the phone number for the current row in the given nation
+    pub fn next_value(&mut self, nation_key: i64) -> PhoneNumberInstance {
+        self.inner.next_value(nation_key)
+    }
+
+    /// Skips the specified number of rows
+    pub fn advance_rows(&mut self, row_count: i64) {
+        self.inner.advance_rows(row_count);
+    }
+
+    /// Moves to the next row
+    pub fn row_finished(&mut self) {
+        self.inner.row_finished();
+    }
+}
+
+/// Generates clerk names (`Clerk#000000951`) for a given scale factor.
+///
+/// The number of distinct clerks is `1000 * scale_factor` (at least 1000).
+///
+/// # Example
+/// ```
+/// # use tpchgen::columns::ClerkNameGenerator;
+/// # use tpchgen::random::ColumnSeeds;
+/// // The first 3 clerks of the ORDERS table at SF1
+/// let clerks: Vec<_> = ClerkNameGenerator::new(1.0, &ColumnSeeds::SPEC)
+///     .take(3)
+///     .map(|clerk| clerk.to_string())
+///     .collect();
+/// assert_eq!(clerks, vec!["Clerk#000000951", "Clerk#000000880", "Clerk#000000955"]);
+/// ```
+#[derive(Debug)]
+pub struct ClerkNameGenerator {
+    inner: RandomBoundedInt,
+}
+
+impl ClerkNameGenerator {
+    const SCALE_BASE: i32 = 1000;
+
+    /// Generator for `o_clerk` (ORDERS table) at the given scale factor from
+    /// `seeds`
+    pub fn new(scale_factor: f64, seeds: &ColumnSeeds) -> Self {
+        let max_clerk = (scale_factor * Self::SCALE_BASE as f64).max(Self::SCALE_BASE as f64);
+        Self {
+            inner: RandomBoundedInt::new(seeds.o_clerk, 1, max_clerk as i32),
+        }
+    }
+
+    /// Returns the clerk for the current row
+    pub fn next_value(&mut self) -> ClerkName {
+        ClerkName::new(self.inner.next_value())
+    }
+
+    /// Skips the specified number of rows
+    pub fn advance_rows(&mut self, row_count: i64) {
+        self.inner.advance_rows(row_count);
+    }
+
+    /// Moves to the next row
+    pub fn row_finished(&mut self) {
+        self.inner.row_finished();
+    }
+}
+
+impl Iterator for ClerkNameGenerator {
+    type Item = ClerkName;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.next_value();
+        self.row_finished();
+        Some(value)
+    }
+}
+
+/// Generates account balances between -999.99 and 9999.99.
+#[derive(Debug)]
+pub struct AccountBalanceGenerator {
+    inner: RandomBoundedInt,
+}
+
+impl AccountBalanceGenerator {
+    /// Minimum account balance, in cents
+    pub const MIN: i32 = -99999;
+    /// Maximum account balance, in cents
+    pub const MAX: i32 = 999999;
+
+    /// Creates an account balance generator with the given seed
+    pub fn new(seed: i64) -> Self {
+        Self {
+            inner: RandomBoundedInt::new(seed, Self::MIN, Self::MAX),
+        }
+    }
+
+    /// Generator for `s_acctbal` (SUPPLIER table) from `seeds`
+    pub fn supplier(seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.s_acctbal)
+    }
+
+    /// Generator for `c_acctbal` (CUSTOMER table) from `seeds`
+    pub fn customer(seeds: &ColumnSeeds) -> Self {
+        Self::new(seeds.c_acctbal)
+    }
+
+    /// Returns the account balance for the current row
+    pub fn next_value(&mut self) -> TPCHDecimal {
+        TPCHDecimal(self.inner.next_value() as i64)
+    }
+
+    /// Skips the specified number of rows
+    pub fn advance_rows(&mut self, row_count: i64) {
+        self.inner.advance_rows(row_count);
+    }
+
+    /// Moves to the next row
+    pub fn row_finished(&mut self) {
+        self.inner.row_finished();
+    }
+}
+
+impl Iterator for AccountBalanceGenerator {
+    type Item = TPCHDecimal;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.next_value();
+        self.row_finished();
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{CustomerGenerator, OrderGenerator, SupplierGenerator};
+
+    #[test]
+    fn test_customer_columns_match_table() {
+        // use a later part to also exercise advance_rows
+        let customers: Vec<_> = CustomerGenerator::new(0.01, 3, 4).iter().collect();
+        let start = CustomerGenerator::calculate_row_count(0.01, 1, 4) * 2;
+
+        let text_pool = TextPool::get_or_init_default();
+        let seeds = ColumnSeeds::SPEC;
+        let mut address = AddressGenerator::customer(&seeds);
+        let mut phone = PhoneNumberGenerator::customer(&seeds);
+        let mut balance = AccountBalanceGenerator::customer(&seeds);
+        let mut comment = CommentGenerator::customer(text_pool, &seeds);
+        address.advance_rows(start);
+        phone.advance_rows(start);
+        balance.advance_rows(start);
+        comment.advance_rows(start);
+
+        for customer in customers {
+            assert_eq!(address.next().unwrap(), customer.c_address);
+            assert_eq!(phone.next_value(customer.c_nationkey), customer.c_phone);
+            phone.row_finished();
+            assert_eq!(balance.next().unwrap(), customer.c_acctbal);
+            assert_eq!(comment.next().unwrap(), customer.c_comment);
+        }
+    }
+
+    #[test]
+    fn test_supplier_columns_match_table() {
+        let seeds = ColumnSeeds::SPEC;
+        let mut address = AddressGenerator::supplier(&seeds);
+        let mut phone = PhoneNumberGenerator::supplier(&seeds);
+        let mut balance = AccountBalanceGenerator::supplier(&seeds);
+
+        for supplier in SupplierGenerator::new(0.01, 1, 1).iter() {
+            assert_eq!(address.next().unwrap(), supplier.s_address);
+            assert_eq!(phone.next_value(supplier.s_nationkey), supplier.s_phone);
+            phone.row_finished();
+            assert_eq!(balance.next().unwrap(), supplier.s_acctbal);
+        }
+    }
+
+    #[test]
+    fn test_order_columns_match_table() {
+        let text_pool = TextPool::get_or_init_default();
+        let mut clerk = ClerkNameGenerator::new(0.01, &ColumnSeeds::SPEC);
+        let mut comment = CommentGenerator::order(text_pool, &ColumnSeeds::SPEC);
+
+        for order in OrderGenerator::new(0.01, 1, 1).iter() {
+            assert_eq!(clerk.next().unwrap(), order.o_clerk);
+            assert_eq!(comment.next().unwrap(), order.o_comment);
+        }
+    }
+
+    #[test]
+    fn test_columns_match_table_with_seed_offset() {
+        let text_pool = TextPool::get_or_init_default();
+        let seeds = ColumnSeeds::SPEC.with_offset(7);
+        let mut address = AddressGenerator::customer(&seeds);
+        let mut balance = AccountBalanceGenerator::customer(&seeds);
+        let mut comment = CommentGenerator::customer(text_pool, &seeds);
+        let mut spec_address = AddressGenerator::customer(&ColumnSeeds::SPEC);
+
+        let customers = CustomerGenerator::new(0.01, 1, 1).with_seed_offset(7);
+        for customer in customers.iter() {
+            assert_eq!(address.next().unwrap(), customer.c_address);
+            assert_eq!(balance.next().unwrap(), customer.c_acctbal);
+            assert_eq!(comment.next().unwrap(), customer.c_comment);
+            assert_ne!(spec_address.next().unwrap(), customer.c_address);
+        }
+
+        let mut clerk = ClerkNameGenerator::new(0.01, &seeds);
+        let orders = OrderGenerator::new(0.01, 1, 1).with_seed_offset(7);
+        for order in orders.iter().take(1000) {
+            assert_eq!(clerk.next().unwrap(), order.o_clerk);
+        }
+    }
+}
@@ -1,8 +1,222 @@
 //! CSV formatting support for the row struct objects generated by the library.
+//!
+//! By default the fields are separated by commas and the free text columns
+//! (comments and addresses), which may contain commas, are quoted, with any
+//! quotes in them doubled as in [RFC 4180]. The delimiter, quoting and
+//! escaping can be changed with a [`CsvDialect`].
+//!
+//! With a [`NullInjection`] the NULL values are written as its token, which
+//! is never quoted.
+//!
+//! [RFC 4180]: https://www.rfc-editor.org/rfc/rfc4180
 
 use crate::generators::{Customer, LineItem, Nation, Order, Part, PartSupp, Region, Supplier};
+use crate::nulls::NullInjection;
 use core::fmt;
-use std::fmt::Display;
+use std::fmt::{Display, Write};
+
+/// Which fields of CSV output are quoted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CsvQuoting {
+    /// Quote the free text columns (comments and addresses) and any other
+    /// field that needs quotes
+    #[default]
+    Text,
+    /// Quote only the fields that contain the delimiter, a quote, a line
+    /// break or (with [`CsvEscape::Backslash`]) a backslash
+    Necessary,
+    /// Quote all fields, including the header
+    All,
+}
+
+/// How quotes in quoted fields of CSV output are escaped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CsvEscape {
+    /// Double the quote (`""`), as in RFC 4180
+    #[default]
+    Double,
+    /// Precede quotes and backslashes with a backslash (`\"` and `\\`)
+    Backslash,
+}
+
+/// The delimiter, quoting and escaping of CSV output
+///
+/// The default is the format described in the [module documentation](self).
+///
+/// # Example
+/// ```
+/// # use tpchgen::generators::RegionGenerator;
+/// # use tpchgen::csv::{CsvDialect, CsvQuoting, RegionCsv};
+/// let dialect = CsvDialect::default()
+///     .with_delimiter(';')
+///     .with_quoting(CsvQuoting::Necessary);
+/// let region = RegionGenerator::default().iter().nth(1).unwrap();
+/// assert_eq!(dialect.header(RegionCsv::header()), "r_regionkey;r_name;r_comment");
+/// assert_eq!(
+///     RegionCsv::new(region).with_dialect(dialect).to_string(),
+///     "1;AMERICA;hs use ironic, even requests. s"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    delimiter: char,
+    quoting: CsvQuoting,
+    escape: CsvEscape,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quoting: CsvQuoting::default(),
+            escape: CsvEscape::default(),
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Set the delimiter between fields (default: `,`)
+    ///
+    /// # Panics
+    /// If the delimiter is a quote, a backslash or a line break
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        assert!(
+            !matches!(delimiter, '"' | '\\' | '\n' | '\r'),
+            "invalid CSV delimiter {delimiter:?}"
+        );
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set which fields are quoted
+    pub fn with_quoting(mut self, quoting: CsvQuoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Set how quotes in quoted fields are escaped
+    pub fn with_escape(mut self, escape: CsvEscape) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Returns the delimiter between fields
+    pub fn delimiter(&self) -> char {
+        self.delimiter
+    }
+
+    /// Returns which fields are quoted
+    pub fn quoting(&self) -> CsvQuoting {
+        self.quoting
+    }
+
+    /// Returns how quotes in quoted fields are escaped
+    pub fn escape(&self) -> CsvEscape {
+        self.escape
+    }
+
+    /// Returns `header`, a header with comma separated column names such as
+    /// [`LineItemCsv::header`], in this dialect
+    pub fn header(&self, header: &str) -> String {
+        let mut out = String::with_capacity(header.len());
+        for (i, name) in header.split(',').enumerate() {
+            if i > 0 {
+                out.push(self.delimiter);
+            }
+            self.write_field(&mut out, name, false)
+                .expect("writing to a String is infallible");
+        }
+        out
+    }
+
+    /// Writes the fields of a record, without a line break
+    fn write_record(&self, f: &mut fmt::Formatter, fields: &[Field]) -> fmt::Result {
+        let mut value = String::new();
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                f.write_char(self.delimiter)?;
+            }
+            let (display, text) = match field {
+                Field::Value(display) => (display, false),
+                Field::Text(display) => (display, true),
+                Field::Null(token) => {
+                    f.write_str(token)?;
+                    continue;
+                }
+            };
+            value.clear();
+            write!(value, "{display}")?;
+            self.write_field(f, &value, text)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a field, quoted and escaped if needed, where `text` is true
+    /// for the free text columns
+    fn write_field(&self, out: &mut impl Write, value: &str, text: bool) -> fmt::Result {
+        let quote = match self.quoting {
+            CsvQuoting::All => true,
+            CsvQuoting::Text if text => true,
+            CsvQuoting::Text | CsvQuoting::Necessary => value.chars().any(|c| {
+                c == self.delimiter
+                    || matches!(c, '"' | '\n' | '\r')
+                    || (c == '\\' && self.escape == CsvEscape::Backslash)
+            }),
+        };
+        if !quote {
+            return out.write_str(value);
+        }
+        out.write_char('"')?;
+        for c in value.chars() {
+            match (c, self.escape) {
+                ('"', CsvEscape::Double) => out.write_str("\"\"")?,
+                ('"' | '\\', CsvEscape::Backslash) => {
+                    out.write_char('\\')?;
+                    out.write_char(c)?
+                }
+                (c, _) => out.write_char(c)?,
+            }
+        }
+        out.write_char('"')
+    }
+}
+
+/// A field of a record written by [`CsvDialect::write_record`]
+enum Field<'a> {
+    Value(&'a dyn Display),
+    /// A free text column, which is quoted with [`CsvQuoting::Text`]
+    Text(&'a dyn Display),
+    /// A NULL, written as the token of the [`NullInjection`]
+    Null(&'a str),
+}
+
+/// The NULLs of the row with primary key `key`, if NULLs are injected
+struct RowNulls<'a> {
+    nulls: Option<&'a NullInjection>,
+    key: &'a [i64],
+}
+
+impl<'a> RowNulls<'a> {
+    /// Returns `field`, the value of `column`, or a NULL if it is NULL
+    fn field(&self, column: &str, field: Field<'a>) -> Field<'a> {
+        match self.nulls {
+            Some(nulls) if nulls.is_null(column, self.key) => Field::Null(nulls.token()),
+            _ => field,
+        }
+    }
+}
+
+/// Formats the contents of a quoted field with any quotes doubled
+struct Escaped<'a>(&'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.0.contains('"') {
+            return f.write_str(self.0);
+        }
+        f.write_str(&self.0.replace('"', "\"\""))
+    }
+}
 
 /// Write [`Nation`]s in CSV format.
 ///
@@ -29,11 +243,29 @@ use std::fmt::Display;
 /// ```
 pub struct NationCsv<'a> {
     inner: Nation<'a>,
+    dialect: CsvDialect,
+    nulls: Option<&'a NullInjection>,
 }
 
 impl<'a> NationCsv<'a> {
     pub fn new(inner: Nation<'a>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            dialect: CsvDialect::default(),
+            nulls: None,
+        }
+    }
+
+    /// Set the delimiter, quoting and escaping of the output, see [`CsvDialect`]
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Write the NULLs selected by `nulls`, see [`crate::nulls`]
+    pub fn with_nulls(mut self, nulls: &'a NullInjection) -> Self {
+        self.nulls = Some(nulls);
+        self
     }
 
     /// Returns the CSV header for the Nation table
@@ -44,11 +276,30 @@ impl<'a> NationCsv<'a> {
 
 impl Display for NationCsv<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dialect != CsvDialect::default() || self.nulls.is_some() {
+            let key = [self.inner.n_nationkey];
+            let nulls = RowNulls {
+                nulls: self.nulls,
+                key: &key,
+            };
+            return self.dialect.write_record(
+                f,
+                &[
+                    Field::Value(&self.inner.n_nationkey),
+                    Field::Value(&self.inner.n_name),
+                    Field::Value(&self.inner.n_regionkey),
+                    nulls.field("n_comment", Field::Text(&self.inner.n_comment)),
+                ],
+            );
+        }
         write!(
             f,
             // note must quote the comment field as it may contain commas
             "{},{},{},\"{}\"",
-            self.inner.n_nationkey, self.inner.n_name, self.inner.n_regionkey, self.inner.n_comment
+            self.inner.n_nationkey,
+            self.inner.n_name,
+            self.inner.n_regionkey,
+            Escaped(self.inner.n_comment),
         )
     }
 }
@@ -78,11 +329,29 @@ impl Display for NationCsv<'_> {
 /// ```
 pub struct RegionCsv<'a> {
     inner: Region<'a>,
+    dialect: CsvDialect,
+    nulls: Option<&'a NullInjection>,
 }
 
 impl<'a> RegionCsv<'a> {
     pub fn new(inner: Region<'a>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            dialect: CsvDialect::default(),
+            nulls: None,
+        }
+    }
+
+    /// Set the delimiter, quoting and escaping of the output, see [`CsvDialect`]
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Write the NULLs selected by `nulls`, see [`crate::nulls`]
+    pub fn with_nulls(mut self, nulls: &'a NullInjection) -> Self {
+        self.nulls = Some(nulls);
+        self
     }
 
     /// Returns the CSV header for the Region table
@@ -93,11 +362,28 @@ impl<'a> RegionCsv<'a> {
 
 impl Display for RegionCsv<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dialect != CsvDialect::default() || self.nulls.is_some() {
+            let key = [self.inner.r_regionkey];
+            let nulls = RowNulls {
+                nulls: self.nulls,
+                key: &key,
+            };
+            return self.dialect.write_record(
+                f,
+                &[
+                    Field::Value(&self.inner.r_regionkey),
+                    Field::Value(&self.inner.r_name),
+                    nulls.field("r_comment", Field::Text(&self.inner.r_comment)),
+                ],
+            );
+        }
         write!(
             f,
             // note must quote the comment field as it may contain commas
             "{},{},\"{}\"",
-            self.inner.r_regionkey, self.inner.r_name, self.inner.r_comment
+            self.inner.r_regionkey,
+            self.inner.r_name,
+            Escaped(self.inner.r_comment),
         )
     }
 }
@@ -127,11 +413,29 @@ impl Display for RegionCsv<'_> {
 /// ```
 pub struct PartCsv<'a> {
     inner: Part<'a>,
+    dialect: CsvDialect,
+    nulls: Option<&'a NullInjection>,
 }
 
 impl<'a> PartCsv<'a> {
     pub fn new(inner: Part<'a>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            dialect: CsvDialect::default(),
+            nulls: None,
+        }
+    }
+
+    /// Set the delimiter, quoting and escaping of the output, see [`CsvDialect`]
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Write the NULLs selected by `nulls`, see [`crate::nulls`]
+    pub fn with_nulls(mut self, nulls: &'a NullInjection) -> Self {
+        self.nulls = Some(nulls);
+        self
     }
 
     /// Returns the CSV header for the Part table
@@ -142,6 +446,27 @@ impl<'a> PartCsv<'a> {
 
 impl Display for PartCsv<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dialect != CsvDialect::default() || self.nulls.is_some() {
+            let key = [self.inner.p_partkey];
+            let nulls = RowNulls {
+                nulls: self.nulls,
+                key: &key,
+            };
+            return self.dialect.write_record(
+                f,
+                &[
+                    Field::Value(&self.inner.p_partkey),
+                    Field::Value(&self.inner.p_name),
+                    Field::Value(&self.inner.p_mfgr),
+                    Field::Value(&self.inner.p_brand),
+                    Field::Value(&self.inner.p_type),
+                    Field::Value(&self.inner.p_size),
+                    Field::Value(&self.inner.p_container),
+                    Field::Value(&self.inner.p_retailprice),
+                    nulls.field("p_comment", Field::Text(&self.inner.p_comment)),
+                ],
+            );
+        }
         write!(
             f,
             // note must quote the comment field as it may contain commas
@@ -154,7 +479,7 @@ impl Display for PartCsv<'_> {
             self.inner.p_size,
             self.inner.p_container,
             self.inner.p_retailprice,
-            self.inner.p_comment
+            Escaped(self.inner.p_comment),
         )
     }
 }
@@ -182,13 +507,31 @@ impl Display for PartCsv<'_> {
 ///    3,Supplier#000000003,\"q1,G3Pj6OjIuUYfUoH18BFTKP5aU9bEV3\",1,11-383-516-1199,4192.40,\"blithely silent requests after the express dependencies are sl\"\n"
 /// );
 /// ```
-pub struct SupplierCsv {
+pub struct SupplierCsv<'a> {
     inner: Supplier,
+    dialect: CsvDialect,
+    nulls: Option<&'a NullInjection>,
 }
 
-impl SupplierCsv {
+impl<'a> SupplierCsv<'a> {
     pub fn new(inner: Supplier) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            dialect: CsvDialect::default(),
+            nulls: None,
+        }
+    }
+
+    /// Set the delimiter, quoting and escaping of the output, see [`CsvDialect`]
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Write the NULLs selected by `nulls`, see [`crate::nulls`]
+    pub fn with_nulls(mut self, nulls: &'a NullInjection) -> Self {
+        self.nulls = Some(nulls);
+        self
     }
 
     /// Returns the CSV header for the Supplier table
@@ -197,8 +540,27 @@ impl SupplierCsv {
     }
 }
 
-impl Display for SupplierCsv {
+impl Display for SupplierCsv<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dialect != CsvDialect::default() || self.nulls.is_some() {
+            let key = [self.inner.s_suppkey];
+            let nulls = RowNulls {
+                nulls: self.nulls,
+                key: &key,
+            };
+            return self.dialect.write_record(
+                f,
+                &[
+                    Field::Value(&self.inner.s_suppkey),
+                    Field::Value(&self.inner.s_name),
+                    Field::Text(&self.inner.s_address),
+                    Field::Value(&self.inner.s_nationkey),
+                    Field::Value(&self.inner.s_phone),
+                    Field::Value(&self.inner.s_acctbal),
+                    nulls.field("s_comment", Field::Text(&self.inner.s_comment)),
+                ],
+            );
+        }
         write!(
             f,
             // note must quote the address and comment fields as they may contain commas
@@ -209,7 +571,7 @@ impl Display for SupplierCsv {
             self.inner.s_nationkey,
             self.inner.s_phone,
             self.inner.s_acctbal,
-            self.inner.s_comment
+            Escaped(&self.inner.s_comment),
         )
     }
 }
@@ -239,11 +601,29 @@ impl Display for SupplierCsv {
 /// ```
 pub struct CustomerCsv<'a> {
     inner: Customer<'a>,
+    dialect: CsvDialect,
+    nulls: Option<&'a NullInjection>,
 }
 
 impl<'a> CustomerCsv<'a> {
     pub fn new(inner: Customer<'a>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            dialect: CsvDialect::default(),
+            nulls: None,
+        }
+    }
+
+    /// Set the delimiter, quoting and escaping of the output, see [`CsvDialect`]
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Write the NULLs selected by `nulls`, see [`crate::nulls`]
+    pub fn with_nulls(mut self, nulls: &'a NullInjection) -> Self {
+        self.nulls = Some(nulls);
+        self
     }
 
     /// Returns the CSV header for the Customer table
@@ -254,6 +634,26 @@ impl<'a> CustomerCsv<'a> {
 
 impl Display for CustomerCsv<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dialect != CsvDialect::default() || self.nulls.is_some() {
+            let key = [self.inner.c_custkey];
+            let nulls = RowNulls {
+                nulls: self.nulls,
+                key: &key,
+            };
+            return self.dialect.write_record(
+                f,
+                &[
+                    Field::Value(&self.inner.c_custkey),
+                    Field::Value(&self.inner.c_name),
+                    Field::Text(&self.inner.c_address),
+                    Field::Value(&self.inner.c_nationkey),
+                    Field::Value(&self.inner.c_phone),
+                    Field::Value(&self.inner.c_acctbal),
+                    Field::Value(&self.inner.c_mktsegment),
+                    nulls.field("c_comment", Field::Text(&self.inner.c_comment)),
+                ],
+            );
+        }
         write!(
             f,
             // note must quote the address and comment fields as they may contain commas
@@ -265,7 +665,7 @@ impl Display for CustomerCsv<'_> {
             self.inner.c_phone,
             self.inner.c_acctbal,
             self.inner.c_mktsegment,
-            self.inner.c_comment
+            Escaped(self.inner.c_comment),
         )
     }
 }
@@ -295,11 +695,29 @@ impl Display for CustomerCsv<'_> {
 /// ```
 pub struct PartSuppCsv<'a> {
     inner: PartSupp<'a>,
+    dialect: CsvDialect,
+    nulls: Option<&'a NullInjection>,
 }
 
 impl<'a> PartSuppCsv<'a> {
     pub fn new(inner: PartSupp<'a>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            dialect: CsvDialect::default(),
+            nulls: None,
+        }
+    }
+
+    /// Set the delimiter, quoting and escaping of the output, see [`CsvDialect`]
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Write the NULLs selected by `nulls`, see [`crate::nulls`]
+    pub fn with_nulls(mut self, nulls: &'a NullInjection) -> Self {
+        self.nulls = Some(nulls);
+        self
     }
 
     /// Returns the CSV header for the PartSupp table
@@ -310,6 +728,23 @@ impl<'a> PartSuppCsv<'a> {
 
 impl Display for PartSuppCsv<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dialect != CsvDialect::default() || self.nulls.is_some() {
+            let key = [self.inner.ps_partkey, self.inner.ps_suppkey];
+            let nulls = RowNulls {
+                nulls: self.nulls,
+                key: &key,
+            };
+            return self.dialect.write_record(
+                f,
+                &[
+                    Field::Value(&self.inner.ps_partkey),
+                    Field::Value(&self.inner.ps_suppkey),
+                    Field::Value(&self.inner.ps_availqty),
+                    Field::Value(&self.inner.ps_supplycost),
+                    nulls.field("ps_comment", Field::Text(&self.inner.ps_comment)),
+                ],
+            );
+        }
         write!(
             f,
             // note must quote the comment field as it may contain commas
@@ -318,7 +753,7 @@ impl Display for PartSuppCsv<'_> {
             self.inner.ps_suppkey,
             self.inner.ps_availqty,
             self.inner.ps_supplycost,
-            self.inner.ps_comment
+            Escaped(self.inner.ps_comment),
         )
     }
 }
@@ -348,11 +783,29 @@ impl Display for PartSuppCsv<'_> {
 /// ```
 pub struct OrderCsv<'a> {
     inner: Order<'a>,
+    dialect: CsvDialect,
+    nulls: Option<&'a NullInjection>,
 }
 
 impl<'a> OrderCsv<'a> {
     pub fn new(inner: Order<'a>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            dialect: CsvDialect::default(),
+            nulls: None,
+        }
+    }
+
+    /// Set the delimiter, quoting and escaping of the output, see [`CsvDialect`]
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Write the NULLs selected by `nulls`, see [`crate::nulls`]
+    pub fn with_nulls(mut self, nulls: &'a NullInjection) -> Self {
+        self.nulls = Some(nulls);
+        self
     }
 
     /// Returns the CSV header for the Order table
@@ -363,6 +816,27 @@ impl<'a> OrderCsv<'a> {
 
 impl Display for OrderCsv<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dialect != CsvDialect::default() || self.nulls.is_some() {
+            let key = [self.inner.o_orderkey];
+            let nulls = RowNulls {
+                nulls: self.nulls,
+                key: &key,
+            };
+            return self.dialect.write_record(
+                f,
+                &[
+                    Field::Value(&self.inner.o_orderkey),
+                    Field::Value(&self.inner.o_custkey),
+                    Field::Value(&self.inner.o_orderstatus),
+                    Field::Value(&self.inner.o_totalprice),
+                    nulls.field("o_orderdate", Field::Value(&self.inner.o_orderdate)),
+                    Field::Value(&self.inner.o_orderpriority),
+                    Field::Value(&self.inner.o_clerk),
+                    Field::Value(&self.inner.o_shippriority),
+                    nulls.field("o_comment", Field::Text(&self.inner.o_comment)),
+                ],
+            );
+        }
         write!(
             f,
             // note must quote the comment field as it may contain commas
@@ -375,7 +849,7 @@ impl Display for OrderCsv<'_> {
             self.inner.o_orderpriority,
             self.inner.o_clerk,
             self.inner.o_shippriority,
-            self.inner.o_comment
+            Escaped(self.inner.o_comment),
         )
     }
 }
@@ -407,10 +881,28 @@ impl Display for OrderCsv<'_> {
 /// [crate documentation]: crate
 pub struct LineItemCsv<'a> {
     inner: LineItem<'a>,
+    dialect: CsvDialect,
+    nulls: Option<&'a NullInjection>,
 }
 impl<'a> LineItemCsv<'a> {
     pub fn new(inner: LineItem<'a>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            dialect: CsvDialect::default(),
+            nulls: None,
+        }
+    }
+
+    /// Set the delimiter, quoting and escaping of the output, see [`CsvDialect`]
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Write the NULLs selected by `nulls`, see [`crate::nulls`]
+    pub fn with_nulls(mut self, nulls: &'a NullInjection) -> Self {
+        self.nulls = Some(nulls);
+        self
     }
 
     /// Returns the CSV header for the LineItem table
@@ -421,6 +913,34 @@ impl<'a> LineItemCsv<'a> {
 
 impl Display for LineItemCsv<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dialect != CsvDialect::default() || self.nulls.is_some() {
+            let key = [self.inner.l_orderkey, self.inner.l_linenumber as i64];
+            let nulls = RowNulls {
+                nulls: self.nulls,
+                key: &key,
+            };
+            return self.dialect.write_record(
+                f,
+                &[
+                    Field::Value(&self.inner.l_orderkey),
+                    Field::Value(&self.inner.l_partkey),
+                    Field::Value(&self.inner.l_suppkey),
+                    Field::Value(&self.inner.l_linenumber),
+                    Field::Value(&self.inner.l_quantity),
+                    Field::Value(&self.inner.l_extendedprice),
+                    Field::Value(&self.inner.l_discount),
+                    Field::Value(&self.inner.l_tax),
+                    Field::Value(&self.inner.l_returnflag),
+                    Field::Value(&self.inner.l_linestatus),
+                    nulls.field("l_shipdate", Field::Value(&self.inner.l_shipdate)),
+                    nulls.field("l_commitdate", Field::Value(&self.inner.l_commitdate)),
+                    nulls.field("l_receiptdate", Field::Value(&self.inner.l_receiptdate)),
+                    Field::Value(&self.inner.l_shipinstruct),
+                    Field::Value(&self.inner.l_shipmode),
+                    nulls.field("l_comment", Field::Text(&self.inner.l_comment)),
+                ],
+            );
+        }
         write!(
             f,
             // note must quote the comment field as it may contain commas
@@ -440,7 +960,49 @@ impl Display for LineItemCsv<'_> {
             self.inner.l_receiptdate,
             self.inner.l_shipinstruct,
             self.inner.l_shipmode,
-            self.inner.l_comment
+            Escaped(self.inner.l_comment),
         )
     }
 }
+
+/// A row struct that can be formatted as CSV, e.g. [`LineItem`] with
+/// [`LineItemCsv`]
+///
+/// Allows writing code that is generic over the tables, such as
+/// [`CsvReader`](crate::reader::CsvReader).
+pub trait CsvRow: Sized {
+    /// The CSV formatter of the row
+    type Csv: Display;
+
+    /// Returns the CSV header for the table of the row
+    fn csv_header() -> &'static str;
+
+    /// Returns the row wrapped in its CSV formatter
+    fn into_csv(self) -> Self::Csv;
+}
+
+/// Implements [`CsvRow`] for a row struct and its CSV formatter
+macro_rules! impl_csv_row {
+    ($ROW:ty, $CSV:ty) => {
+        impl<'a> CsvRow for $ROW {
+            type Csv = $CSV;
+
+            fn csv_header() -> &'static str {
+                <$CSV>::header()
+            }
+
+            fn into_csv(self) -> Self::Csv {
+                <$CSV>::new(self)
+            }
+        }
+    };
+}
+
+impl_csv_row!(Nation<'a>, NationCsv<'a>);
+impl_csv_row!(Region<'a>, RegionCsv<'a>);
+impl_csv_row!(Part<'a>, PartCsv<'a>);
+impl_csv_row!(Supplier, SupplierCsv<'static>);
+impl_csv_row!(PartSupp<'a>, PartSuppCsv<'a>);
+impl_csv_row!(Customer<'a>, CustomerCsv<'a>);
+impl_csv_row!(Order<'a>, OrderCsv<'a>);
+impl_csv_row!(LineItem<'a>, LineItemCsv<'a>);
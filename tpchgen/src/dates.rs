@@ -1,6 +1,7 @@
 //! [`TPCHDate`] and date handling
 use std::{
     fmt::{Display, Formatter},
+    str::FromStr,
     sync::LazyLock,
 };
 
@@ -15,6 +16,9 @@ pub const MIN_GENERATE_DATE: i32 = 92001;
 const CURRENT_DATE: i32 = 95168;
 /// The total number of days that can be generated by the data generator
 pub const TOTAL_DATE_RANGE: i32 = 2557;
+/// The index of the last date that can be parsed or created from its
+/// parts, `9999-12-31`, the last date with a four digit year
+const MAX_DATE_INDEX: i32 = 2_924_861;
 
 /// Month boundaries for a standard (non-leap) year
 const MONTH_YEAR_DAY_START: [i32; 13] =
@@ -83,7 +87,7 @@ impl GenerateUtils {
 /// // format as a string using the Display impl
 /// assert_eq!("1992-02-11", date.to_string());
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TPCHDate {
     /// date index (0 based) from MIN_GENERATE_DATE
     date_index: i32,
@@ -91,8 +95,16 @@ pub struct TPCHDate {
 
 impl Display for TPCHDate {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        // uses a pre-computed table to avoid recalculating the date
-        write!(f, "{}", &DATE_TO_STRING[self.date_index as usize])
+        // uses a pre-computed table to avoid recalculating the dates of the
+        // specification, e.g. not the later order dates of
+        // `OrderGenerator::with_orders_per_day`
+        match DATE_TO_STRING.get(self.date_index as usize) {
+            Some(date) => write!(f, "{date}"),
+            None => {
+                let (y, m, dy) = self.to_ymd();
+                write!(f, "{}", format_ymd(y, m, dy))
+            }
+        }
     }
 }
 
@@ -130,13 +142,13 @@ impl TPCHDate {
     }
 
     /// Checks if a date is in the past
+    ///
+    /// The dates after those of the specification are after the current
+    /// date too.
     pub fn is_in_past(date: i32) -> bool {
-        Self::to_julian(date) <= CURRENT_DATE
-    }
-
-    /// Lookup if a date is in the past.
-    fn to_julian(date: i32) -> i32 {
-        JULIAN_DATE[(date - MIN_GENERATE_DATE) as usize]
+        JULIAN_DATE
+            .get((date - MIN_GENERATE_DATE) as usize)
+            .is_some_and(|&julian| julian <= CURRENT_DATE)
     }
 
     /// Returns the number of days since the Unix epoch this date
@@ -145,8 +157,183 @@ impl TPCHDate {
     pub fn to_unix_epoch(&self) -> i32 {
         self.date_index + Self::UNIX_EPOCH_OFFSET
     }
+
+    /// Returns the number of days since the Unix epoch (1970-01-01) this date
+    /// represents.
+    ///
+    /// This is the same as [`Self::to_unix_epoch`] and is the representation
+    /// used by Arrow's `Date32` type.
+    ///
+    /// # Example
+    /// ```
+    /// # use tpchgen::dates::{TPCHDate, MIN_GENERATE_DATE};
+    /// // 1992-01-01 is 8035 days after 1970-01-01
+    /// assert_eq!(TPCHDate::new(MIN_GENERATE_DATE).to_epoch_days(), 8035);
+    /// ```
+    #[inline(always)]
+    pub fn to_epoch_days(&self) -> i32 {
+        self.to_unix_epoch()
+    }
+
+    /// Creates a date from the number of days since the Unix epoch (1970-01-01).
+    ///
+    /// Returns `None` if the date is before `1992-01-01`, the first date the
+    /// data generator can produce, or after `9999-12-31`. The later dates
+    /// include those of the orders of
+    /// [`LiveOrderGenerator`](crate::generators::LiveOrderGenerator).
+    ///
+    /// # Example
+    /// ```
+    /// # use tpchgen::dates::TPCHDate;
+    /// let date = TPCHDate::from_epoch_days(9269).unwrap();
+    /// assert_eq!(date.to_string(), "1995-05-19");
+    /// let date = TPCHDate::from_epoch_days(17532).unwrap();
+    /// assert_eq!(date.to_string(), "2018-01-01");
+    /// // dates before the generated range are not representable
+    /// assert_eq!(TPCHDate::from_epoch_days(0), None);
+    /// ```
+    pub fn from_epoch_days(days: i32) -> Option<Self> {
+        let date_index = days.checked_sub(Self::UNIX_EPOCH_OFFSET)?;
+        (0..=MAX_DATE_INDEX)
+            .contains(&date_index)
+            .then_some(Self { date_index })
+    }
+
+    /// Creates a date from its year, month (1 to 12) and day (1 to 31).
+    ///
+    /// Returns `None` if there is no such date or if it is outside the range
+    /// of [`Self::from_epoch_days`] (`1992-01-01` to `9999-12-31`).
+    ///
+    /// # Example
+    /// ```
+    /// # use tpchgen::dates::TPCHDate;
+    /// let date = TPCHDate::from_ymd(1995, 5, 19).unwrap();
+    /// assert_eq!(date.to_string(), "1995-05-19");
+    /// assert_eq!(TPCHDate::from_ymd(1991, 12, 31), None);
+    /// assert_eq!(TPCHDate::from_ymd(1995, 2, 29), None);
+    /// ```
+    pub fn from_ymd(year: i32, month: i32, day: i32) -> Option<Self> {
+        Self::from_epoch_days(Self::ymd_to_epoch_days(year, month, day)?)
+    }
+
+    /// Returns the number of days since the Unix epoch (1970-01-01) of the
+    /// date with the year, month (1 to 12) and day (1 to 31) of the proleptic
+    /// Gregorian calendar, which need not be in the range of the data
+    /// generator.
+    ///
+    /// Returns `None` if there is no such date.
+    ///
+    /// # Example
+    /// ```
+    /// # use tpchgen::dates::TPCHDate;
+    /// assert_eq!(TPCHDate::ymd_to_epoch_days(1970, 1, 1), Some(0));
+    /// assert_eq!(TPCHDate::ymd_to_epoch_days(2000, 2, 29), Some(11016));
+    /// assert_eq!(TPCHDate::ymd_to_epoch_days(2023, 2, 29), None);
+    /// assert_eq!(TPCHDate::ymd_to_epoch_days(2023, 4, 31), None);
+    /// ```
+    pub fn ymd_to_epoch_days(year: i32, month: i32, day: i32) -> Option<i32> {
+        let days_in_month = match month {
+            2 if is_leap_year(year - 1900) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return None,
+        };
+        if !(1..=days_in_month).contains(&day) {
+            return None;
+        }
+        // see http://howardhinnant.github.io/date_algorithms.html
+        let (year, month) = if month <= 2 {
+            (year - 1, month + 9)
+        } else {
+            (year, month - 3)
+        };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * month + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        Some(era * 146097 + day_of_era - 719468)
+    }
+
+    /// Converts this date to a [`chrono::NaiveDate`].
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn to_naive_date(&self) -> chrono::NaiveDate {
+        chrono::NaiveDate::from_num_days_from_ce_opt(self.to_epoch_days() + EPOCH_DAYS_FROM_CE)
+            .expect("generated dates are always valid")
+    }
+
+    /// Creates a date from a [`chrono::NaiveDate`].
+    ///
+    /// Returns `None` if the date is outside the range of
+    /// [`Self::from_epoch_days`] (`1992-01-01` to `9999-12-31`).
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn from_naive_date(date: chrono::NaiveDate) -> Option<Self> {
+        use chrono::Datelike;
+        Self::from_epoch_days(date.num_days_from_ce() - EPOCH_DAYS_FROM_CE)
+    }
+}
+
+/// Error returned when parsing a [`TPCHDate`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDateError {
+    input: String,
+}
+
+impl Display for ParseDateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid date '{}': expected YYYY-MM-DD between 1992-01-01 and 9999-12-31",
+            self.input
+        )
+    }
 }
 
+impl std::error::Error for ParseDateError {}
+
+/// Parses a date in the format produced by [`Display`], e.g. `"1995-05-19"`.
+///
+/// Only the dates of [`TPCHDate::from_ymd`] (`1992-01-01` to `9999-12-31`)
+/// can be parsed.
+///
+/// # Example
+/// ```
+/// # use tpchgen::dates::TPCHDate;
+/// let date: TPCHDate = "1995-05-19".parse().unwrap();
+/// assert_eq!(date.to_epoch_days(), 9269);
+/// assert_eq!("2018-01-01".parse::<TPCHDate>().unwrap().to_epoch_days(), 17532);
+/// assert!("1995-5-19".parse::<TPCHDate>().is_err());
+/// assert!("1991-12-31".parse::<TPCHDate>().is_err());
+/// ```
+impl FromStr for TPCHDate {
+    type Err = ParseDateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseDateError {
+            input: s.to_string(),
+        };
+        let bytes = s.as_bytes();
+        let well_formed = bytes.len() == 10
+            && bytes.iter().enumerate().all(|(i, b)| match i {
+                4 | 7 => *b == b'-',
+                _ => b.is_ascii_digit(),
+            });
+        if !well_formed {
+            return Err(error());
+        }
+        let field = |range: std::ops::Range<usize>| s[range].parse().expect("ASCII digits");
+        Self::from_ymd(field(0..4), field(5..7), field(8..10)).ok_or_else(error)
+    }
+}
+
+/// Number of days between 0001-01-01 (day 1 of the common era) and 1970-01-01
+#[cfg(feature = "chrono")]
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
 /// Creates a index table of formatted strings
 ///
 /// index: dates generated by the data generator
@@ -203,8 +390,10 @@ const fn julian(date: i32) -> i32 {
     result + offset
 }
 
+/// Returns true if `year`, in years since 1900, is a leap year
 const fn is_leap_year(year: i32) -> bool {
-    year % 4 == 0 && year % 100 != 0
+    let year = 1900 + year;
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
 }
 
 const fn leap_year_adjustment(year: i32, month: i32) -> i32 {
@@ -230,6 +419,22 @@ mod test {
         assert_eq!(date.to_string(), "1998-12-31");
     }
 
+    #[test]
+    fn test_dates_after_the_specification() {
+        // e.g. the order dates of OrderGenerator::with_orders_per_day
+        let date = TPCHDate::new(MIN_GENERATE_DATE + TOTAL_DATE_RANGE);
+        assert_eq!(date.to_string(), "1999-01-01");
+        // 2000 is a leap year, 2100 is not
+        let date = TPCHDate::from_ymd(1998, 12, 31).unwrap();
+        let after = |days: i32| TPCHDate::new(MIN_GENERATE_DATE + date.into_inner() + days);
+        assert_eq!(after(425).to_string(), "2000-02-29");
+        assert_eq!(after(426).to_string(), "2000-03-01");
+        assert_eq!(after(36949).to_string(), "2100-02-28");
+        assert_eq!(after(36950).to_string(), "2100-03-01");
+        assert!(!TPCHDate::is_in_past(MIN_GENERATE_DATE + TOTAL_DATE_RANGE));
+        assert!(TPCHDate::is_in_past(MIN_GENERATE_DATE));
+    }
+
     #[test]
     fn test_display_dates() {
         for index in [1, 23, 321, 623, 1234, 2345, 2556] {
@@ -250,4 +455,59 @@ mod test {
         assert_eq!(date.to_string(), "1995-05-19");
         assert_eq!(date.to_unix_epoch(), 9269);
     }
+
+    #[test]
+    fn test_epoch_days_round_trip() {
+        for index in 0..TOTAL_DATE_RANGE {
+            let date = TPCHDate::new(MIN_GENERATE_DATE + index);
+            assert_eq!(TPCHDate::from_epoch_days(date.to_epoch_days()), Some(date));
+        }
+        assert_eq!(
+            TPCHDate::from_epoch_days(TPCHDate::UNIX_EPOCH_OFFSET - 1),
+            None
+        );
+        assert_eq!(TPCHDate::from_epoch_days(i32::MIN), None);
+        assert_eq!(TPCHDate::from_epoch_days(i32::MAX), None);
+    }
+
+    #[test]
+    fn test_dates_after_the_specification_round_trip() {
+        // e.g. the order dates of LiveOrderGenerator
+        let last = TPCHDate::new(MIN_GENERATE_DATE + MAX_DATE_INDEX);
+        assert_eq!(last.to_string(), "9999-12-31");
+        for index in [
+            TOTAL_DATE_RANGE,
+            TOTAL_DATE_RANGE + 425,
+            9496,
+            MAX_DATE_INDEX,
+        ] {
+            let date = TPCHDate::new(MIN_GENERATE_DATE + index);
+            assert_eq!(TPCHDate::from_epoch_days(date.to_epoch_days()), Some(date));
+            assert_eq!(date.to_string().parse(), Ok(date));
+            let (y, m, dy) = date.to_ymd();
+            assert_eq!(TPCHDate::from_ymd(1900 + y, m, dy), Some(date));
+        }
+        assert_eq!(TPCHDate::from_epoch_days(last.to_epoch_days() + 1), None);
+        assert!("10000-01-01".parse::<TPCHDate>().is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_naive_date_round_trip() {
+        for index in 0..TOTAL_DATE_RANGE {
+            let date = TPCHDate::new(MIN_GENERATE_DATE + index);
+            let naive_date = date.to_naive_date();
+            assert_eq!(naive_date.to_string(), date.to_string());
+            assert_eq!(TPCHDate::from_naive_date(naive_date), Some(date));
+        }
+        let before = chrono::NaiveDate::from_ymd_opt(1991, 12, 31).unwrap();
+        assert_eq!(TPCHDate::from_naive_date(before), None);
+        let after = chrono::NaiveDate::from_ymd_opt(2018, 1, 1).unwrap();
+        assert_eq!(
+            TPCHDate::from_naive_date(after).unwrap().to_string(),
+            "2018-01-01"
+        );
+        let after = chrono::NaiveDate::from_ymd_opt(10000, 1, 1).unwrap();
+        assert_eq!(TPCHDate::from_naive_date(after), None);
+    }
 }
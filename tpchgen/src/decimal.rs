@@ -1,6 +1,7 @@
 //! [`TPCHDecimal`] and decimal handling
 
 use std::fmt;
+use std::str::FromStr;
 
 /// Represents a decimal with a scale of 2.
 ///
@@ -66,6 +67,73 @@ impl fmt::Display for TPCHDecimal {
     }
 }
 
+/// Error returned when parsing a [`TPCHDecimal`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDecimalError {
+    input: String,
+}
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid decimal '{}': expected digits with exactly 2 decimal places (e.g. '-12.34')",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for ParseDecimalError {}
+
+/// Parses a decimal in the format produced by [`Display`], e.g. `"-12.34"`.
+///
+/// Exactly two digits are required after the decimal point so that parsing
+/// is an exact inverse of formatting.
+///
+/// # Example
+/// ```
+/// use tpchgen::decimal::TPCHDecimal;
+/// let decimal: TPCHDecimal = "-12.34".parse().unwrap();
+/// assert_eq!(decimal, TPCHDecimal(-1234));
+/// assert!("12.3".parse::<TPCHDecimal>().is_err());
+/// ```
+///
+/// [`Display`]: fmt::Display
+impl FromStr for TPCHDecimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseDecimalError {
+            input: s.to_string(),
+        };
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (int_part, decimal_part) = digits.split_once('.').ok_or_else(err)?;
+        let all_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        if !all_digits(int_part) || !all_digits(decimal_part) || decimal_part.len() != 2 {
+            return Err(err());
+        }
+
+        let value = int_part
+            .parse::<i64>()
+            .ok()
+            .and_then(|v| v.checked_mul(100))
+            .and_then(|v| v.checked_add(decimal_part.parse::<i64>().ok()?))
+            .ok_or_else(err)?;
+        Ok(TPCHDecimal(if negative { -value } else { value }))
+    }
+}
+
+impl TryFrom<&str> for TPCHDecimal {
+    type Error = ParseDecimalError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,6 +205,36 @@ mod tests {
                 "input decimal: {:?}",
                 test_case.decimal,
             );
+            // parsing the formatted value must round trip
+            assert_eq!(
+                Ok(test_case.decimal),
+                TPCHDecimal::try_from(formatted.as_str()),
+                "input string: {formatted}",
+            );
+        }
+    }
+
+    #[test]
+    fn decimal_parse_invalid() {
+        for input in [
+            "",
+            "-",
+            "12",
+            "12.",
+            ".12",
+            "12.3",
+            "12.345",
+            "+12.34",
+            "1a.34",
+            "12.3a",
+            "--12.34",
+            " 12.34",
+            "99999999999999999999.00",
+        ] {
+            assert!(
+                input.parse::<TPCHDecimal>().is_err(),
+                "expected error parsing '{input}'"
+            );
         }
     }
 }
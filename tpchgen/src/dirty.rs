@@ -0,0 +1,244 @@
+//! Injection of dirty data, for testing data quality and ETL tools.
+//!
+//! TPC-H data is clean: keys are unique, values are in range and every row
+//! has all its fields. A [`DirtyData`] selects a percentage of the rows of
+//! each table to be written with one [`Corruption`] instead:
+//!
+//! * [`Corruption::DuplicateKey`]: the row is written twice
+//! * [`Corruption::OutOfRangeDecimal`]: a decimal column, e.g.
+//!   `l_extendedprice`, is larger than a `DECIMAL(15,2)`
+//! * [`Corruption::MalformedDate`]: a date column, e.g. `o_orderdate`, is
+//!   not a valid date
+//! * [`Corruption::TruncatedRow`]: the row ends in the middle of a field
+//!
+//! Like [`NullInjection`](crate::nulls::NullInjection), whether and how a
+//! row is corrupted only depends on its table and primary key, so the same
+//! rows are corrupted for every number of parts. Each table has the
+//! corruptions its columns allow: NATION and REGION have no decimal or date
+//! columns, and only ORDERS and LINEITEM have dates.
+//!
+//! # Example
+//! ```
+//! # use tpchgen::dirty::{Corruption, DirtyData};
+//! # use tpchgen::generators::OrderGenerator;
+//! let dirty = DirtyData::new(100.0);
+//! let columns = [
+//!     "o_orderkey", "o_custkey", "o_orderstatus", "o_totalprice", "o_orderdate",
+//!     "o_orderpriority", "o_clerk", "o_shippriority", "o_comment",
+//! ];
+//! let order = OrderGenerator::new(0.01, 1, 1).iter().next().unwrap();
+//! let mut output = Vec::new();
+//! let row = dirty
+//!     .write_tbl_row(&mut output, "orders", &columns, &[1], &order.to_string())
+//!     .unwrap();
+//! assert_eq!(row.corruption, Corruption::OutOfRangeDecimal);
+//! assert_eq!(row.column, Some("o_totalprice"));
+//! assert_eq!(
+//!     String::from_utf8(output).unwrap(),
+//!     "1|370|O|1000000000000000.00|1996-01-02|5-LOW|Clerk#000000951|0|nstructions sleep furiously among |\n"
+//! );
+//! ```
+
+use crate::nulls::{fraction, key_hash, mix};
+use std::fmt::{self, Display};
+
+/// The decimal columns of all tables
+const DECIMAL_COLUMNS: &[&str] = &[
+    "p_retailprice",
+    "s_acctbal",
+    "ps_supplycost",
+    "c_acctbal",
+    "o_totalprice",
+    "l_quantity",
+    "l_extendedprice",
+    "l_discount",
+    "l_tax",
+];
+
+/// The date columns of all tables
+const DATE_COLUMNS: &[&str] = &["o_orderdate", "l_shipdate", "l_commitdate", "l_receiptdate"];
+
+/// The value of out of range decimals, with more than the 15 digits of a
+/// `DECIMAL(15,2)`
+const OUT_OF_RANGE_DECIMAL: &str = "1000000000000000.00";
+
+/// The values of malformed dates
+const MALFORMED_DATES: &[&str] = &["1996-02-30", "1995-13-01", "19950315", "15/03/1995"];
+
+/// How a row is corrupted, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corruption {
+    /// The row is written twice, so its primary key is not unique
+    DuplicateKey,
+    /// A decimal column has a value larger than a `DECIMAL(15,2)`
+    OutOfRangeDecimal,
+    /// A date column has a value that is not a valid `YYYY-MM-DD` date
+    MalformedDate,
+    /// The row ends in the middle of a field, without the remaining fields
+    TruncatedRow,
+}
+
+impl Corruption {
+    /// Returns the name of the corruption, e.g. `duplicate-key`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Corruption::DuplicateKey => "duplicate-key",
+            Corruption::OutOfRangeDecimal => "out-of-range-decimal",
+            Corruption::MalformedDate => "malformed-date",
+            Corruption::TruncatedRow => "truncated-row",
+        }
+    }
+}
+
+impl Display for Corruption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A row corrupted by [`DirtyData::write_tbl_row`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtyRow<'a> {
+    /// How the row is corrupted
+    pub corruption: Corruption,
+    /// The corrupted column, for out of range decimals and malformed dates
+    pub column: Option<&'a str>,
+}
+
+/// Selects the rows that are corrupted and how, see the
+/// [module documentation](self)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirtyData {
+    percent: f64,
+}
+
+impl DirtyData {
+    /// Creates an injection of `percent`% corrupted rows
+    ///
+    /// # Panics
+    /// If `percent` is not between 0 and 100
+    pub fn new(percent: f64) -> Self {
+        assert!(
+            (0.0..=100.0).contains(&percent),
+            "invalid dirty row percentage {percent}"
+        );
+        Self { percent }
+    }
+
+    /// Returns the percentage of corrupted rows in each table
+    pub fn percent(&self) -> f64 {
+        self.percent
+    }
+
+    /// Returns how the row of `table`, e.g. `lineitem`, with primary key
+    /// `key` and the given columns is corrupted, if it is, and the corrupted
+    /// column
+    pub fn corruption<'a, S: AsRef<str>>(
+        &self,
+        table: &str,
+        columns: &'a [S],
+        key: &[i64],
+    ) -> Option<DirtyRow<'a>> {
+        if self.percent == 0.0 {
+            return None;
+        }
+        let hash = key_hash(table, key);
+        if fraction(hash) * 100.0 >= self.percent {
+            return None;
+        }
+        let decimals = Self::columns_in(columns, DECIMAL_COLUMNS);
+        let dates = Self::columns_in(columns, DATE_COLUMNS);
+        let mut corruptions = vec![Corruption::DuplicateKey, Corruption::TruncatedRow];
+        if !decimals.is_empty() {
+            corruptions.push(Corruption::OutOfRangeDecimal);
+        }
+        if !dates.is_empty() {
+            corruptions.push(Corruption::MalformedDate);
+        }
+        let choice = mix(hash);
+        let corruption = corruptions[(choice % corruptions.len() as u64) as usize];
+        let candidates = match corruption {
+            Corruption::OutOfRangeDecimal => decimals,
+            Corruption::MalformedDate => dates,
+            _ => vec![],
+        };
+        let column = (!candidates.is_empty()).then(|| {
+            let index = mix(choice) % candidates.len() as u64;
+            candidates[index as usize]
+        });
+        Some(DirtyRow { corruption, column })
+    }
+
+    /// Appends `line`, a row of `table` in TBL format without a newline, to
+    /// `output`, corrupted if it is selected, followed by a newline
+    ///
+    /// `columns` are the names of the columns of the row, and `key` its
+    /// primary key. Returns how the row is corrupted, if it is.
+    pub fn write_tbl_row<'a, S: AsRef<str>>(
+        &self,
+        output: &mut Vec<u8>,
+        table: &str,
+        columns: &'a [S],
+        key: &[i64],
+        line: &str,
+    ) -> Option<DirtyRow<'a>> {
+        let Some(row) = self.corruption(table, columns, key) else {
+            output.extend_from_slice(line.as_bytes());
+            output.push(b'\n');
+            return None;
+        };
+        // generated values never contain '|'
+        let fields: Vec<_> = line.split_terminator('|').collect();
+        match row.corruption {
+            Corruption::DuplicateKey => {
+                for _ in 0..2 {
+                    output.extend_from_slice(line.as_bytes());
+                    output.push(b'\n');
+                }
+            }
+            Corruption::TruncatedRow => {
+                // the first half of the fields and half of the next one
+                let kept = fields.len() / 2;
+                for field in &fields[..kept] {
+                    output.extend_from_slice(field.as_bytes());
+                    output.push(b'|');
+                }
+                if let Some(field) = fields.get(kept) {
+                    let end = field.char_indices().nth(field.chars().count() / 2);
+                    output.extend_from_slice(&field.as_bytes()[..end.map_or(0, |(i, _)| i)]);
+                }
+                output.push(b'\n');
+            }
+            Corruption::OutOfRangeDecimal | Corruption::MalformedDate => {
+                let value = match row.corruption {
+                    Corruption::OutOfRangeDecimal => OUT_OF_RANGE_DECIMAL,
+                    _ => {
+                        let index = key_hash("malformed_date", key) % MALFORMED_DATES.len() as u64;
+                        MALFORMED_DATES[index as usize]
+                    }
+                };
+                for (i, field) in fields.iter().enumerate() {
+                    let column = columns.get(i).map(AsRef::as_ref);
+                    let field = if column.is_some() && column == row.column {
+                        value
+                    } else {
+                        field
+                    };
+                    output.extend_from_slice(field.as_bytes());
+                    output.push(b'|');
+                }
+                output.push(b'\n');
+            }
+        }
+        Some(row)
+    }
+
+    /// Returns the columns of `columns` that are in `kind`
+    fn columns_in<'a, S: AsRef<str>>(columns: &'a [S], kind: &[&str]) -> Vec<&'a str> {
+        columns
+            .iter()
+            .map(AsRef::as_ref)
+            .filter(|column| kind.contains(column))
+            .collect()
+    }
+}
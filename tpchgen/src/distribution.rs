@@ -1,6 +1,10 @@
+use crate::generators::{InvalidValueError, OrderPriority, ReturnFlag, ShipMode};
 use crate::random::RowRandomInt;
 use std::{
+    fs,
     io::{self},
+    path::Path,
+    str::FromStr,
     sync::LazyLock,
 };
 
@@ -105,6 +109,18 @@ impl Distribution {
         self.distribution[random_value as usize]
     }
 
+    /// Gets the index of a random value from this distribution using the
+    /// provided random number, the index of the value of [`Self::random_value`]
+    pub fn random_index(&self, random: &mut RowRandomInt) -> usize {
+        debug_assert!(
+            !self.distribution.is_empty(),
+            "Not a valid distribution, cannot get a random value"
+        );
+        let random_value = random.next_int(0, self.max_weight - 1);
+        self.weights
+            .partition_point(|&weight| weight <= random_value)
+    }
+
     /// Loads a single distribution until its END marker.
     fn load_distribution<I>(
         lines: &mut std::iter::Peekable<I>,
@@ -172,6 +188,35 @@ static DEFAULT_DISTRIBUTIONS: LazyLock<Distributions> =
     LazyLock::new(|| Distributions::try_load_default().unwrap());
 
 /// Distributions wraps all TPC-H distributions and provides methods to access them.
+///
+/// The generators take the distributions in their
+/// `new_with_distributions_and_text_pool` constructors, e.g.
+/// [`OrderGenerator::new_with_distributions_and_text_pool`], and use these
+/// distributions, by their names in `dists.dss`:
+///
+/// | Generator | Distributions |
+/// |-----------|---------------|
+/// | NATION    | `nations` |
+/// | REGION    | `regions` |
+/// | PART      | `colors`, `p_types`, `p_cntr` |
+/// | SUPPLIER  | `nations` |
+/// | PARTSUPP  | none |
+/// | CUSTOMER  | `nations`, `msegmnt` |
+/// | ORDERS    | `o_oprio` |
+/// | LINEITEM  | `rflag`, `instruct`, `smode` |
+///
+/// The comments of every table come from the [`TextPool`], which is created
+/// from the `grammar`, `np`, `vp`, `nouns`, `verbs`, `adjectives`,
+/// `adverbs`, `articles`, `prepositions`, `auxillaries` and `terminators`
+/// distributions.
+///
+/// Distributions can be loaded from a modified `dists.dss`, see
+/// [`Self::try_load`], or replaced one by one, see
+/// [`Self::with_distribution`], which can also add distributions that the
+/// generators do not use, e.g. for custom generators.
+///
+/// [`OrderGenerator::new_with_distributions_and_text_pool`]: crate::generators::OrderGenerator::new_with_distributions_and_text_pool
+/// [`TextPool`]: crate::text::TextPool
 #[derive(Debug, Clone, Default)]
 pub struct Distributions {
     articles: Distribution,
@@ -196,53 +241,209 @@ pub struct Distributions {
     terminators: Distribution,
     verb_phrase: Distribution,
     verbs: Distribution,
+    /// Distributions not used by the generators
+    extra: Vec<Distribution>,
 }
 
 impl Distributions {
+    /// Names of the distributions used by the generators
+    const REQUIRED: [&'static str; 22] = [
+        "articles",
+        "adjectives",
+        "adverbs",
+        "auxillaries",
+        "grammar",
+        "category",
+        "msegmnt",
+        "nations",
+        "np",
+        "nouns",
+        "o_oprio",
+        "colors",
+        "p_cntr",
+        "p_types",
+        "prepositions",
+        "regions",
+        "rflag",
+        "instruct",
+        "smode",
+        "terminators",
+        "vp",
+        "verbs",
+    ];
+
+    /// Loads the built-in distributions, the `dists.dss` file of dbgen
     pub fn try_load_default() -> io::Result<Self> {
-        let lines = DISTS_SEED.split('\n');
+        Self::try_load(DISTS_SEED)
+    }
+
+    /// Loads distributions from `text` in the format of dbgen's `dists.dss`,
+    /// e.g. to generate data with a modified copy of the file.
+    ///
+    /// The text must define every distribution used by the generators. Other
+    /// distributions are kept, see [`Self::get`].
+    pub fn try_load(text: &'static str) -> io::Result<Self> {
+        let lines = text.split('\n');
 
         let mut new_self = Self::default();
-        for (name, distribution) in Self::load_distributions(lines)? {
-            match name {
-                "articles" => new_self.articles = distribution,
-                "adjectives" => new_self.adjectives = distribution,
-                "adverbs" => new_self.adverbs = distribution,
-                // P.S: The correct spelling is `auxiliaries` which is what we use.
-                "auxillaries" => new_self.auxiliaries = distribution,
-                "grammar" => new_self.grammar = distribution,
-                "category" => new_self.category = distribution,
-                "msegmnt" => new_self.market_segments = distribution,
-                "nations" => new_self.nations = distribution,
-                "np" => new_self.noun_phrase = distribution,
-                "nouns" => new_self.nouns = distribution,
-                "o_oprio" => new_self.order_priority = distribution,
-                "colors" => new_self.part_colors = distribution,
-                "p_cntr" => new_self.part_containers = distribution,
-                "p_types" => new_self.part_types = distribution,
-                "prepositions" => new_self.prepositions = distribution,
-                "regions" => new_self.regions = distribution,
-                "rflag" => new_self.return_flags = distribution,
-                "instruct" => new_self.ship_instructions = distribution,
-                "smode" => new_self.ship_modes = distribution,
-                "terminators" => new_self.terminators = distribution,
-                "vp" => new_self.verb_phrase = distribution,
-                "verbs" => new_self.verbs = distribution,
-
-                // currently unused distributions
-                "nations2" | "Q13a" | "Q13b" | "p_names" => {}
-                _ => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        format!("Internal Error: Unknown distribution: {name}"),
-                    ));
-                }
-            }
+        let distributions = Self::load_distributions(lines)?;
+        if let Some(missing) = Self::REQUIRED
+            .iter()
+            .find(|name| !distributions.iter().any(|(n, _)| n == *name))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Missing distribution: {missing}"),
+            ));
+        }
+        for (_, distribution) in distributions {
+            new_self = new_self.with_distribution(distribution);
         }
+        new_self.validate_column_values()?;
 
         Ok(new_self)
     }
 
+    /// Returns an error if a value of the distribution of a column with a
+    /// fixed set of values, e.g. `smode` for [`ShipMode`], is not one of them
+    fn validate_column_values(&self) -> io::Result<()> {
+        fn validate<T: FromStr<Err = InvalidValueError>>(
+            distribution: &Distribution,
+        ) -> io::Result<()> {
+            for value in distribution.get_values() {
+                value.parse::<T>().map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{e} in distribution {}", distribution.name()),
+                    )
+                })?;
+            }
+            Ok(())
+        }
+        validate::<OrderPriority>(&self.order_priority)?;
+        validate::<ReturnFlag>(&self.return_flags)?;
+        validate::<ShipMode>(&self.ship_modes)
+    }
+
+    /// Replaces the distribution with the name of `distribution`, e.g.
+    /// `colors` for the colors of the part names, or adds it if the
+    /// generators do not use a distribution of that name.
+    ///
+    /// The values of the distributions of columns with a fixed set of
+    /// values, e.g. `o_oprio` for [`OrderPriority`], must be among those
+    /// values, or creating the generators panics. [`Self::try_load`] checks
+    /// them.
+    ///
+    /// ```
+    /// # use tpchgen::distribution::{Distribution, Distributions};
+    /// # use tpchgen::generators::{OrderGenerator, OrderPriority};
+    /// # use tpchgen::text::TextPool;
+    /// // all orders are urgent
+    /// let distributions = Distributions::try_load_default()
+    ///     .unwrap()
+    ///     .with_distribution(Distribution::new("o_oprio", vec![("1-URGENT", 1)]));
+    /// let orders = OrderGenerator::new_with_distributions_and_text_pool(
+    ///     0.01,
+    ///     1,
+    ///     1,
+    ///     &distributions,
+    ///     TextPool::get_or_init_default(),
+    /// );
+    /// assert!(orders.iter().all(|o| o.o_orderpriority == OrderPriority::Urgent));
+    /// ```
+    pub fn with_distribution(mut self, distribution: Distribution) -> Self {
+        let target = match distribution.name() {
+            "articles" => &mut self.articles,
+            "adjectives" => &mut self.adjectives,
+            "adverbs" => &mut self.adverbs,
+            // P.S: The correct spelling is `auxiliaries` which is what we use.
+            "auxillaries" => &mut self.auxiliaries,
+            "grammar" => &mut self.grammar,
+            "category" => &mut self.category,
+            "msegmnt" => &mut self.market_segments,
+            "nations" => &mut self.nations,
+            "np" => &mut self.noun_phrase,
+            "nouns" => &mut self.nouns,
+            "o_oprio" => &mut self.order_priority,
+            "colors" => &mut self.part_colors,
+            "p_cntr" => &mut self.part_containers,
+            "p_types" => &mut self.part_types,
+            "prepositions" => &mut self.prepositions,
+            "regions" => &mut self.regions,
+            "rflag" => &mut self.return_flags,
+            "instruct" => &mut self.ship_instructions,
+            "smode" => &mut self.ship_modes,
+            "terminators" => &mut self.terminators,
+            "vp" => &mut self.verb_phrase,
+            "verbs" => &mut self.verbs,
+            // e.g. the unused `nations2`, `Q13a`, `Q13b` and `p_names` of
+            // dists.dss
+            name => match self.extra.iter_mut().find(|d| d.name() == name) {
+                Some(extra) => extra,
+                None => {
+                    self.extra.push(distribution);
+                    return self;
+                }
+            },
+        };
+        *target = distribution;
+        self
+    }
+
+    /// Returns the distribution with the given name in `dists.dss`, e.g.
+    /// `o_oprio`, including those the generators do not use, or `None` if
+    /// there is none
+    pub fn get(&self, name: &str) -> Option<&Distribution> {
+        [
+            &self.articles,
+            &self.adjectives,
+            &self.adverbs,
+            &self.auxiliaries,
+            &self.grammar,
+            &self.category,
+            &self.market_segments,
+            &self.nations,
+            &self.noun_phrase,
+            &self.nouns,
+            &self.order_priority,
+            &self.part_colors,
+            &self.part_containers,
+            &self.part_types,
+            &self.prepositions,
+            &self.regions,
+            &self.return_flags,
+            &self.ship_instructions,
+            &self.ship_modes,
+            &self.terminators,
+            &self.verb_phrase,
+            &self.verbs,
+        ]
+        .into_iter()
+        .chain(&self.extra)
+        .find(|d| d.name() == name)
+    }
+
+    /// Loads distributions from the file at `path` in the format of dbgen's
+    /// `dists.dss`, like `dbgen -b`, see [`Self::try_load`].
+    ///
+    /// The distributions borrow the text of the file for the rest of the
+    /// process, so it is leaked: load a file once and share the result.
+    pub fn try_load_from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Can not read distributions {}: {e}", path.display()),
+            )
+        })?;
+        Self::try_load(text.leak()).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Invalid distributions {}: {e}", path.display()),
+            )
+        })
+    }
+
     /// Loads distributions from a stream of lines.
     ///
     /// The format is expected to follow the TPC-H specification format where:
@@ -501,6 +702,80 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_try_load() {
+        // a modified copy of the default file
+        let text = DISTS_SEED.replace("1-URGENT|1", "1-URGENT|9");
+        let distributions = Distributions::try_load(Box::leak(text.into_boxed_str())).unwrap();
+        assert_eq!(distributions.order_priority().get_weight(0), 9);
+        assert_eq!(distributions.nations().size(), 25);
+
+        let err = Distributions::try_load("BEGIN test\nvalue|10\nEND\n").unwrap_err();
+        assert_eq!(err.to_string(), "Missing distribution: articles");
+
+        // a renamed ship mode
+        let text = DISTS_SEED.replace("TRUCK|", "LORRY|");
+        let err = Distributions::try_load(Box::leak(text.into_boxed_str())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "invalid value 'LORRY' for l_shipmode in distribution smode"
+        );
+    }
+
+    #[test]
+    fn test_random_index() {
+        let distribution = Distribution::new("test", vec![("a", 1), ("b", 2), ("c", 3)]);
+        let mut random = RowRandomInt::new(1, 1);
+        let mut copy = random;
+        for _ in 0..100 {
+            let index = distribution.random_index(&mut random);
+            assert_eq!(
+                distribution.get_value(index),
+                distribution.random_value(&mut copy)
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_distribution() {
+        let distributions = Distributions::try_load_default().unwrap();
+        // the distributions of dists.dss the generators do not use are kept
+        assert_eq!(distributions.get("Q13a").unwrap().size(), 4);
+        assert!(distributions.get("custom").is_none());
+
+        let distributions = distributions
+            .with_distribution(Distribution::new("smode", vec![("TRUCK", 1)]))
+            .with_distribution(Distribution::new("custom", vec![("a", 1), ("b", 2)]))
+            .with_distribution(Distribution::new("custom", vec![("c", 3)]));
+        assert_eq!(distributions.ship_modes().get_values(), ["TRUCK"]);
+        assert_eq!(distributions.get("smode").unwrap().size(), 1);
+        assert_eq!(distributions.get("custom").unwrap().get_values(), ["c"]);
+        assert_eq!(distributions.get("nations").unwrap().size(), 25);
+    }
+
+    #[test]
+    fn test_try_load_from_path() {
+        let path = std::env::temp_dir().join(format!("tpchgen-dists-{}.dss", std::process::id()));
+        fs::write(&path, DISTS_SEED.replace("1-URGENT|1", "1-URGENT|9")).unwrap();
+        let distributions = Distributions::try_load_from_path(&path).unwrap();
+        assert_eq!(distributions.order_priority().get_weight(0), 9);
+
+        fs::write(&path, "BEGIN test\nvalue|10\nEND\n").unwrap();
+        let err = Distributions::try_load_from_path(&path).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Invalid distributions {}: Missing distribution: articles",
+                path.display()
+            )
+        );
+        fs::remove_file(&path).unwrap();
+
+        let err = Distributions::try_load_from_path(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
     #[test]
     fn test_with_default_seeds_file() {
         let expected_distributions = vec![
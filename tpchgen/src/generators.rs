@@ -1,24 +1,151 @@
 //! Generators for each TPC-H Tables
+use crate::columns::{
+    AccountBalanceGenerator, AddressGenerator, ClerkNameGenerator, CommentGenerator,
+    PhoneNumberGenerator,
+};
 use crate::dates;
 use crate::decimal::TPCHDecimal;
 use crate::distribution::Distribution;
 use crate::distribution::Distributions;
-use crate::random::RandomPhoneNumber;
-use crate::random::RowRandomInt;
+use crate::random::RandomAlphaNumericInstance;
+use crate::random::{ColumnSeeds, Extreme, RowRandomInt, Zipf};
 use crate::random::{PhoneNumberInstance, RandomBoundedLong, StringSequenceInstance};
-use crate::random::{RandomAlphaNumeric, RandomAlphaNumericInstance};
-use crate::text::TextPool;
+use crate::text::{floor_char_boundary, TextPool};
 use core::fmt;
 use std::fmt::Display;
 
 use crate::dates::{GenerateUtils, TPCHDate};
-use crate::random::{RandomBoundedInt, RandomString, RandomStringSequence, RandomText};
+use crate::random::{RandomBoundedInt, RandomEnum, RandomString, RandomStringSequence, RandomText};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// A row filter set with e.g. [`LineItemGenerator::with_filter`]
+///
+/// Wraps the closure so the generators can still be `Debug` and `Clone`
+struct RowFilter<F: ?Sized>(Arc<F>);
+
+impl<F: ?Sized> Clone for RowFilter<F> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for RowFilter<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RowFilter")
+    }
+}
+
+type NationFilter = RowFilter<dyn Fn(&Nation<'_>) -> bool + Send + Sync>;
+type RegionFilter = RowFilter<dyn Fn(&Region<'_>) -> bool + Send + Sync>;
+type PartFilter = RowFilter<dyn Fn(&Part<'_>) -> bool + Send + Sync>;
+type SupplierFilter = RowFilter<dyn Fn(&Supplier) -> bool + Send + Sync>;
+type CustomerFilter = RowFilter<dyn Fn(&Customer<'_>) -> bool + Send + Sync>;
+type PartSuppFilter = RowFilter<dyn Fn(&PartSupp<'_>) -> bool + Send + Sync>;
+type OrderFilter = RowFilter<dyn Fn(&Order<'_>) -> bool + Send + Sync>;
+type LineItemFilter = RowFilter<dyn Fn(&LineItem<'_>) -> bool + Send + Sync>;
+
+/// Returns `value`, or the `min` or `max` value if `extreme` is set
+fn edge_value<T>(extreme: Option<Extreme>, value: T, min: T, max: T) -> T {
+    extreme.map_or(value, |e| e.pick(min, max))
+}
+
+/// The largest supported scale factor
+///
+/// The keys and row counts are 64-bit, and the keys of parts and customers
+/// switch to 64-bit random values at scale factor 30000 like in dbgen, but
+/// the clerks of `o_clerk` are numbered with 32-bit values, 1000 per scale
+/// factor. The generators do not check the scale factor, so programs should
+/// reject larger ones rather than generate wrong data.
+pub const MAX_SCALE_FACTOR: f64 = 1_000_000.0;
+
+/// Multipliers of the number of rows of individual tables relative to the
+/// scale factor, for datasets with other ratios between the tables than those
+/// of the specification
+///
+/// A generator given the multipliers with `with_table_scales`, e.g.
+/// [`CustomerGenerator::with_table_scales`], generates its table at the scale
+/// factor times the table's multiplier, e.g. ten times the customers of the
+/// scale factor with a `customer` multiplier of 10. The generators of the
+/// tables that refer to it select their foreign keys from all of its rows,
+/// so the tables remain consistent with each other when every generator is
+/// given the same multipliers. Like the scale factor, the multipliers do not
+/// change how the rows depend on the parts a table is split into.
+///
+/// LINEITEM has the line items of the orders of ORDERS, so it is scaled by
+/// `orders`. PARTSUPP has the suppliers of each part of PART, 4 at the
+/// scale factor, so `partsupp` scales the number of suppliers per part and
+/// is rounded to a multiple of 1/4. All multipliers must be positive.
+///
+/// ```
+/// # use tpchgen::generators::{CustomerGenerator, OrderGenerator, TableScales};
+/// let scales = TableScales {
+///     customer: 10.0,
+///     ..TableScales::default()
+/// };
+/// let customers = CustomerGenerator::new(0.01, 1, 1).with_table_scales(scales);
+/// assert_eq!(customers.iter().count(), 15_000);
+/// // the orders refer to all of the customers, and there are as many as
+/// // at the scale factor
+/// let orders = OrderGenerator::new(0.01, 1, 1).with_table_scales(scales);
+/// assert_eq!(orders.iter().count(), 15_000);
+/// assert!(orders.iter().any(|o| o.o_custkey > 1_500));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableScales {
+    /// Multiplier of the rows of PART
+    pub part: f64,
+    /// Multiplier of the rows of SUPPLIER
+    pub supplier: f64,
+    /// Multiplier of the suppliers of each part in PARTSUPP
+    pub partsupp: f64,
+    /// Multiplier of the rows of CUSTOMER
+    pub customer: f64,
+    /// Multiplier of the rows of ORDERS and LINEITEM
+    pub orders: f64,
+}
+
+impl Default for TableScales {
+    fn default() -> Self {
+        Self {
+            part: 1.0,
+            supplier: 1.0,
+            partsupp: 1.0,
+            customer: 1.0,
+            orders: 1.0,
+        }
+    }
+}
+
+impl TableScales {
+    /// Returns the number of suppliers of each part in PARTSUPP, 4 times
+    /// `partsupp` rounded, at least 1
+    pub fn suppliers_per_part(&self) -> i32 {
+        let suppliers = PartSuppGenerator::SUPPLIERS_PER_PART as f64 * self.partsupp;
+        (suppliers.round() as i32).max(1)
+    }
+
+    /// Returns the number of suppliers at `scale_factor`
+    fn supplier_count(&self, scale_factor: f64) -> i64 {
+        (SupplierGenerator::SCALE_BASE as f64 * scale_factor * self.supplier) as i64
+    }
+}
+
+/// Returns `value`, drawn uniformly from `[min, max]`, skewed by `zipf` if
+/// set
+fn skewed_value(zipf: Option<Zipf>, value: i64, min: i64, max: i64) -> i64 {
+    zipf.map_or(value, |zipf| zipf.skew(value, min, max))
+}
 
 /// Generator for Nation table data
 #[derive(Debug, Clone)]
 pub struct NationGenerator<'a> {
     distributions: &'a Distributions,
     text_pool: &'a TextPool,
+    filter: Option<NationFilter>,
+    edge_cases: bool,
+    seed_offset: i64,
+    column_seeds: ColumnSeeds,
 }
 
 impl Default for NationGenerator<'_> {
@@ -47,6 +174,8 @@ impl<'a> NationGenerator<'a> {
     }
 
     /// Creates a NationGenerator with the specified distributions and text pool
+    ///
+    /// Uses the `nations` distribution, see [`Distributions`].
     pub fn new_with_distributions_and_text_pool<'b>(
         distributions: &'b Distributions,
         text_pool: &'b TextPool,
@@ -54,12 +183,52 @@ impl<'a> NationGenerator<'a> {
         NationGenerator {
             distributions,
             text_pool,
+            filter: None,
+            edge_cases: false,
+            seed_offset: 0,
+            column_seeds: ColumnSeeds::SPEC,
         }
     }
 
+    /// Only generate the nation rows for which `filter` returns true
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&Nation<'_>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = Some(RowFilter(Arc::new(filter)));
+        self
+    }
+
+    /// Generate edge case values instead of random ones, see
+    /// [`LineItemGenerator::with_edge_cases`]
+    pub fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generate the rows of a dataset other than that of the specification,
+    /// see [`LineItemGenerator::with_seed_offset`]
+    pub fn with_seed_offset(mut self, seed_offset: i64) -> Self {
+        self.seed_offset = seed_offset;
+        self
+    }
+
+    /// Generate the columns from other seeds, see
+    /// [`LineItemGenerator::with_column_seeds`]
+    pub fn with_column_seeds(mut self, column_seeds: ColumnSeeds) -> Self {
+        self.column_seeds = column_seeds;
+        self
+    }
+
     /// Returns an iterator over the nation rows
     pub fn iter(&self) -> NationGeneratorIterator<'a> {
-        NationGeneratorIterator::new(self.distributions.nations(), self.text_pool)
+        NationGeneratorIterator::new(
+            self.distributions.nations(),
+            self.text_pool,
+            self.column_seeds.with_offset(self.seed_offset),
+        )
+        .with_filter(self.filter.clone())
+        .with_edge_cases(self.edge_cases)
     }
 }
 
@@ -119,34 +288,44 @@ impl<'a> Nation<'a> {
 #[derive(Debug)]
 pub struct NationGeneratorIterator<'a> {
     nations: &'a Distribution,
-    comment_random: RandomText<'a>,
+    comment_random: CommentGenerator<'a>,
     index: usize,
+    filter: Option<NationFilter>,
+    edge_cases: bool,
 }
 
 impl<'a> NationGeneratorIterator<'a> {
-    const COMMENT_AVERAGE_LENGTH: i32 = 72;
-
-    fn new(nations: &'a Distribution, text_pool: &'a TextPool) -> Self {
+    fn new(nations: &'a Distribution, text_pool: &'a TextPool, seeds: ColumnSeeds) -> Self {
         NationGeneratorIterator {
             nations,
-            comment_random: RandomText::new(
-                606179079,
-                text_pool,
-                Self::COMMENT_AVERAGE_LENGTH as f64,
-            ),
+            comment_random: CommentGenerator::nation(text_pool, &seeds),
             index: 0,
+            filter: None,
+            edge_cases: false,
         }
     }
-}
 
-impl<'a> Iterator for NationGeneratorIterator<'a> {
-    type Item = Nation<'a>;
+    /// Only return rows matching `filter`
+    fn with_filter(mut self, filter: Option<NationFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Replace values with their edge cases if `edge_cases` is true
+    fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generates the next row, ignoring the filter
+    fn next_row(&mut self) -> Option<Nation<'a>> {
         if self.index >= self.nations.size() {
             return None;
         }
 
+        let extreme = self
+            .edge_cases
+            .then(|| Extreme::for_row(self.index as i64 + 1));
         let nation = Nation {
             // n_nationkey
             n_nationkey: self.index as i64,
@@ -155,7 +334,7 @@ impl<'a> Iterator for NationGeneratorIterator<'a> {
             // n_regionkey
             n_regionkey: self.nations.get_weight(self.index) as i64,
             // n_comment
-            n_comment: self.comment_random.next_value(),
+            n_comment: self.comment_random.next_value_or_extreme(extreme),
         };
 
         self.comment_random.row_finished();
@@ -165,6 +344,20 @@ impl<'a> Iterator for NationGeneratorIterator<'a> {
     }
 }
 
+impl<'a> Iterator for NationGeneratorIterator<'a> {
+    type Item = Nation<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = self.next_row()?;
+            match &self.filter {
+                Some(filter) if !(filter.0)(&row) => continue,
+                _ => return Some(row),
+            }
+        }
+    }
+}
+
 /// The REGION table
 ///
 /// The Display trait is implemented to format the line item data as a string
@@ -210,6 +403,10 @@ impl<'a> Region<'a> {
 pub struct RegionGenerator<'a> {
     distributions: &'a Distributions,
     text_pool: &'a TextPool,
+    filter: Option<RegionFilter>,
+    edge_cases: bool,
+    seed_offset: i64,
+    column_seeds: ColumnSeeds,
 }
 
 impl Default for RegionGenerator<'_> {
@@ -238,6 +435,8 @@ impl<'a> RegionGenerator<'a> {
     }
 
     /// Creates a RegionGenerator with the specified distributions and text pool
+    ///
+    /// Uses the `regions` distribution, see [`Distributions`].
     pub fn new_with_distributions_and_text_pool<'b>(
         distributions: &'b Distributions,
         text_pool: &'b TextPool,
@@ -245,12 +444,52 @@ impl<'a> RegionGenerator<'a> {
         RegionGenerator {
             distributions,
             text_pool,
+            filter: None,
+            edge_cases: false,
+            seed_offset: 0,
+            column_seeds: ColumnSeeds::SPEC,
         }
     }
 
+    /// Only generate the region rows for which `filter` returns true
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&Region<'_>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = Some(RowFilter(Arc::new(filter)));
+        self
+    }
+
+    /// Generate edge case values instead of random ones, see
+    /// [`LineItemGenerator::with_edge_cases`]
+    pub fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generate the rows of a dataset other than that of the specification,
+    /// see [`LineItemGenerator::with_seed_offset`]
+    pub fn with_seed_offset(mut self, seed_offset: i64) -> Self {
+        self.seed_offset = seed_offset;
+        self
+    }
+
+    /// Generate the columns from other seeds, see
+    /// [`LineItemGenerator::with_column_seeds`]
+    pub fn with_column_seeds(mut self, column_seeds: ColumnSeeds) -> Self {
+        self.column_seeds = column_seeds;
+        self
+    }
+
     /// Returns an iterator over the region rows
     pub fn iter(&self) -> RegionGeneratorIterator<'a> {
-        RegionGeneratorIterator::new(self.distributions.regions(), self.text_pool)
+        RegionGeneratorIterator::new(
+            self.distributions.regions(),
+            self.text_pool,
+            self.column_seeds.with_offset(self.seed_offset),
+        )
+        .with_filter(self.filter.clone())
+        .with_edge_cases(self.edge_cases)
     }
 }
 
@@ -267,38 +506,48 @@ impl<'a> IntoIterator for RegionGenerator<'a> {
 #[derive(Debug)]
 pub struct RegionGeneratorIterator<'a> {
     regions: &'a Distribution,
-    comment_random: RandomText<'a>,
+    comment_random: CommentGenerator<'a>,
     index: usize,
+    filter: Option<RegionFilter>,
+    edge_cases: bool,
 }
 
 impl<'a> RegionGeneratorIterator<'a> {
-    const COMMENT_AVERAGE_LENGTH: i32 = 72;
-
-    fn new(regions: &'a Distribution, text_pool: &'a TextPool) -> Self {
+    fn new(regions: &'a Distribution, text_pool: &'a TextPool, seeds: ColumnSeeds) -> Self {
         RegionGeneratorIterator {
             regions,
-            comment_random: RandomText::new(
-                1500869201,
-                text_pool,
-                Self::COMMENT_AVERAGE_LENGTH as f64,
-            ),
+            comment_random: CommentGenerator::region(text_pool, &seeds),
             index: 0,
+            filter: None,
+            edge_cases: false,
         }
     }
-}
 
-impl<'a> Iterator for RegionGeneratorIterator<'a> {
-    type Item = Region<'a>;
+    /// Only return rows matching `filter`
+    fn with_filter(mut self, filter: Option<RegionFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Replace values with their edge cases if `edge_cases` is true
+    fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generates the next row, ignoring the filter
+    fn next_row(&mut self) -> Option<Region<'a>> {
         if self.index >= self.regions.size() {
             return None;
         }
 
+        let extreme = self
+            .edge_cases
+            .then(|| Extreme::for_row(self.index as i64 + 1));
         let region = Region {
             r_regionkey: self.index as i64,
             r_name: self.regions.get_value(self.index),
-            r_comment: self.comment_random.next_value(),
+            r_comment: self.comment_random.next_value_or_extreme(extreme),
         };
 
         self.comment_random.row_finished();
@@ -308,6 +557,20 @@ impl<'a> Iterator for RegionGeneratorIterator<'a> {
     }
 }
 
+impl<'a> Iterator for RegionGeneratorIterator<'a> {
+    type Item = Region<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = self.next_row()?;
+            match &self.filter {
+                Some(filter) if !(filter.0)(&row) => continue,
+                _ => return Some(row),
+            }
+        }
+    }
+}
+
 /// A Part Manufacturer, formatted as `"Manufacturer#<n>"`
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PartManufacturerName(i32);
@@ -389,6 +652,22 @@ impl fmt::Display for Part<'_> {
     }
 }
 
+/// Returns the `(start_index, row_count)` of the rows from `start_index`
+/// whose keys, their index plus 1, are in `keys`, or of all `row_count` rows
+/// if `None`
+fn rows_with_keys(
+    start_index: i64,
+    row_count: i64,
+    keys: Option<&RangeInclusive<i64>>,
+) -> (i64, i64) {
+    let Some(keys) = keys else {
+        return (start_index, row_count);
+    };
+    let first = start_index.max(keys.start().saturating_sub(1));
+    let end = (start_index + row_count).min(*keys.end());
+    (first, (end - first).max(0))
+}
+
 /// Generator for Part table data
 #[derive(Debug, Clone)]
 pub struct PartGenerator<'a> {
@@ -397,6 +676,12 @@ pub struct PartGenerator<'a> {
     part_count: i32,
     distributions: &'a Distributions,
     text_pool: &'a TextPool,
+    filter: Option<PartFilter>,
+    edge_cases: bool,
+    scales: TableScales,
+    seed_offset: i64,
+    column_seeds: ColumnSeeds,
+    keys: Option<RangeInclusive<i64>>,
 }
 
 impl<'a> PartGenerator<'a> {
@@ -411,7 +696,6 @@ impl<'a> PartGenerator<'a> {
     const BRAND_MAX: i32 = 5;
     const SIZE_MIN: i32 = 1;
     const SIZE_MAX: i32 = 50;
-    const COMMENT_AVERAGE_LENGTH: i32 = 14;
 
     /// Creates a new PartGenerator with the given scale factor
     ///
@@ -429,6 +713,9 @@ impl<'a> PartGenerator<'a> {
     }
 
     /// Creates a PartGenerator with specified distributions and text pool
+    ///
+    /// Uses the `colors`, `p_types` and `p_cntr` distributions, see
+    /// [`Distributions`].
     pub fn new_with_distributions_and_text_pool<'b>(
         scale_factor: f64,
         part: i32,
@@ -442,6 +729,12 @@ impl<'a> PartGenerator<'a> {
             part_count,
             distributions,
             text_pool,
+            filter: None,
+            edge_cases: false,
+            scales: TableScales::default(),
+            seed_offset: 0,
+            column_seeds: ColumnSeeds::SPEC,
+            keys: None,
         }
     }
 
@@ -450,19 +743,89 @@ impl<'a> PartGenerator<'a> {
         GenerateUtils::calculate_row_count(Self::SCALE_BASE, scale_factor, part, part_count)
     }
 
+    /// Only generate the part rows for which `filter` returns true
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&Part<'_>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = Some(RowFilter(Arc::new(filter)));
+        self
+    }
+
+    /// Generate edge case values instead of random ones, see
+    /// [`LineItemGenerator::with_edge_cases`]
+    pub fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generate the parts at the scale factor times `scales.part`, see
+    /// [`TableScales`]
+    pub fn with_table_scales(mut self, scales: TableScales) -> Self {
+        self.scales = scales;
+        self
+    }
+
+    /// Generate the rows of a dataset other than that of the specification,
+    /// see [`LineItemGenerator::with_seed_offset`]
+    pub fn with_seed_offset(mut self, seed_offset: i64) -> Self {
+        self.seed_offset = seed_offset;
+        self
+    }
+
+    /// Generate the columns from other seeds, see
+    /// [`LineItemGenerator::with_column_seeds`]
+    pub fn with_column_seeds(mut self, column_seeds: ColumnSeeds) -> Self {
+        self.column_seeds = column_seeds;
+        self
+    }
+
+    /// Only generate the parts whose keys are in `part_keys`, or all parts if
+    /// `None` (the default)
+    ///
+    /// Unlike [`Self::with_filter`], the other parts are not generated at
+    /// all, as for [`OrderGenerator::with_order_keys`], and the parts in the
+    /// range are the same as without it.
+    ///
+    /// ```
+    /// # use tpchgen::generators::PartGenerator;
+    /// let keys: Vec<_> = PartGenerator::new(0.01, 1, 1)
+    ///     .with_part_keys(Some(30..=33))
+    ///     .iter()
+    ///     .map(|part| part.p_partkey)
+    ///     .collect();
+    /// assert_eq!(keys, [30, 31, 32, 33]);
+    /// ```
+    pub fn with_part_keys(mut self, part_keys: Option<RangeInclusive<i64>>) -> Self {
+        self.keys = part_keys;
+        self
+    }
+
     /// Returns an iterator over the part rows
     pub fn iter(&self) -> PartGeneratorIterator<'a> {
-        PartGeneratorIterator::new(
-            self.distributions,
-            self.text_pool,
+        let (start_index, row_count) = rows_with_keys(
             GenerateUtils::calculate_start_index(
                 Self::SCALE_BASE,
-                self.scale_factor,
+                self.scale_factor * self.scales.part,
                 self.part,
                 self.part_count,
             ),
-            Self::calculate_row_count(self.scale_factor, self.part, self.part_count),
+            Self::calculate_row_count(
+                self.scale_factor * self.scales.part,
+                self.part,
+                self.part_count,
+            ),
+            self.keys.as_ref(),
+        );
+        PartGeneratorIterator::new(
+            self.distributions,
+            self.text_pool,
+            self.column_seeds.with_offset(self.seed_offset),
+            start_index,
+            row_count,
         )
+        .with_filter(self.filter.clone())
+        .with_edge_cases(self.edge_cases)
     }
 }
 
@@ -484,41 +847,47 @@ pub struct PartGeneratorIterator<'a> {
     type_random: RandomString<'a>,
     size_random: RandomBoundedInt,
     container_random: RandomString<'a>,
-    comment_random: RandomText<'a>,
+    comment_random: CommentGenerator<'a>,
 
     start_index: i64,
     row_count: i64,
     index: i64,
+    filter: Option<PartFilter>,
+    edge_cases: bool,
 }
 
 impl<'a> PartGeneratorIterator<'a> {
     fn new(
         distributions: &'a Distributions,
         text_pool: &'a TextPool,
+        seeds: ColumnSeeds,
         start_index: i64,
         row_count: i64,
     ) -> Self {
         let mut name_random = RandomStringSequence::new(
-            709314158,
+            seeds.p_name,
             PartGenerator::NAME_WORDS,
             distributions.part_colors(),
         );
         let mut manufacturer_random = RandomBoundedInt::new(
-            1,
+            seeds.p_mfgr,
             PartGenerator::MANUFACTURER_MIN,
             PartGenerator::MANUFACTURER_MAX,
         );
-        let mut brand_random =
-            RandomBoundedInt::new(46831694, PartGenerator::BRAND_MIN, PartGenerator::BRAND_MAX);
-        let mut type_random = RandomString::new(1841581359, distributions.part_types());
-        let mut size_random =
-            RandomBoundedInt::new(1193163244, PartGenerator::SIZE_MIN, PartGenerator::SIZE_MAX);
-        let mut container_random = RandomString::new(727633698, distributions.part_containers());
-        let mut comment_random = RandomText::new(
-            804159733,
-            text_pool,
-            PartGenerator::COMMENT_AVERAGE_LENGTH as f64,
+        let mut brand_random = RandomBoundedInt::new(
+            seeds.p_brand,
+            PartGenerator::BRAND_MIN,
+            PartGenerator::BRAND_MAX,
         );
+        let mut type_random = RandomString::new(seeds.p_type, distributions.part_types());
+        let mut size_random = RandomBoundedInt::new(
+            seeds.p_size,
+            PartGenerator::SIZE_MIN,
+            PartGenerator::SIZE_MAX,
+        );
+        let mut container_random =
+            RandomString::new(seeds.p_container, distributions.part_containers());
+        let mut comment_random = CommentGenerator::part(text_pool, &seeds);
 
         // Advance all generators to the starting position
         name_random.advance_rows(start_index);
@@ -540,11 +909,14 @@ impl<'a> PartGeneratorIterator<'a> {
             start_index,
             row_count,
             index: 0,
+            filter: None,
+            edge_cases: false,
         }
     }
 
     /// Creates a part with the given key
     fn make_part(&mut self, part_key: i64) -> Part<'a> {
+        let extreme = self.edge_cases.then(|| Extreme::for_row(part_key));
         let name = self.name_random.next_value();
 
         let manufacturer = self.manufacturer_random.next_value();
@@ -556,10 +928,15 @@ impl<'a> PartGeneratorIterator<'a> {
             p_mfgr: PartManufacturerName::new(manufacturer),
             p_brand: PartBrandName::new(brand),
             p_type: self.type_random.next_value(),
-            p_size: self.size_random.next_value(),
+            p_size: edge_value(
+                extreme,
+                self.size_random.next_value(),
+                PartGenerator::SIZE_MIN,
+                PartGenerator::SIZE_MAX,
+            ),
             p_container: self.container_random.next_value(),
             p_retailprice: TPCHDecimal(Self::calculate_part_price(part_key)),
-            p_comment: self.comment_random.next_value(),
+            p_comment: self.comment_random.next_value_or_extreme(extreme),
         }
     }
 
@@ -573,12 +950,21 @@ impl<'a> PartGeneratorIterator<'a> {
 
         price
     }
-}
 
-impl<'a> Iterator for PartGeneratorIterator<'a> {
-    type Item = Part<'a>;
+    /// Only return rows matching `filter`
+    fn with_filter(mut self, filter: Option<PartFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Replace values with their edge cases if `edge_cases` is true
+    fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generates the next row, ignoring the filter
+    fn next_row(&mut self) -> Option<Part<'a>> {
         if self.index >= self.row_count {
             return None;
         }
@@ -599,6 +985,20 @@ impl<'a> Iterator for PartGeneratorIterator<'a> {
     }
 }
 
+impl<'a> Iterator for PartGeneratorIterator<'a> {
+    type Item = Part<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = self.next_row()?;
+            match &self.filter {
+                Some(filter) if !(filter.0)(&row) => continue,
+                _ => return Some(row),
+            }
+        }
+    }
+}
+
 /// A supplier name, formatted as `"Supplier#<n>"`
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct SupplierName(i64);
@@ -667,18 +1067,18 @@ pub struct SupplierGenerator<'a> {
     part_count: i32,
     distributions: &'a Distributions,
     text_pool: &'a TextPool,
+    filter: Option<SupplierFilter>,
+    edge_cases: bool,
+    scales: TableScales,
+    seed_offset: i64,
+    column_seeds: ColumnSeeds,
+    keys: Option<RangeInclusive<i64>>,
 }
 
 impl<'a> SupplierGenerator<'a> {
     /// Base scale for supplier generation
     const SCALE_BASE: i32 = 10_000;
 
-    // Constants for supplier generation
-    const ACCOUNT_BALANCE_MIN: i32 = -99999;
-    const ACCOUNT_BALANCE_MAX: i32 = 999999;
-    const ADDRESS_AVERAGE_LENGTH: i32 = 25;
-    const COMMENT_AVERAGE_LENGTH: i32 = 63;
-
     // Better Business Bureau comment constants
     pub const BBB_BASE_TEXT: &'static str = "Customer ";
     pub const BBB_COMPLAINT_TEXT: &'static str = "Complaints";
@@ -704,6 +1104,8 @@ impl<'a> SupplierGenerator<'a> {
     }
 
     /// Creates a SupplierGenerator with specified distributions and text pool
+    ///
+    /// Uses the `nations` distribution, see [`Distributions`].
     pub fn new_with_distributions_and_text_pool<'b>(
         scale_factor: f64,
         part: i32,
@@ -717,6 +1119,12 @@ impl<'a> SupplierGenerator<'a> {
             part_count,
             distributions,
             text_pool,
+            filter: None,
+            edge_cases: false,
+            scales: TableScales::default(),
+            seed_offset: 0,
+            column_seeds: ColumnSeeds::SPEC,
+            keys: None,
         }
     }
 
@@ -725,19 +1133,75 @@ impl<'a> SupplierGenerator<'a> {
         GenerateUtils::calculate_row_count(Self::SCALE_BASE, scale_factor, part, part_count)
     }
 
+    /// Only generate the supplier rows for which `filter` returns true
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&Supplier) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = Some(RowFilter(Arc::new(filter)));
+        self
+    }
+
+    /// Generate edge case values instead of random ones, see
+    /// [`LineItemGenerator::with_edge_cases`]
+    pub fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generate the suppliers at the scale factor times `scales.supplier`, see
+    /// [`TableScales`]
+    pub fn with_table_scales(mut self, scales: TableScales) -> Self {
+        self.scales = scales;
+        self
+    }
+
+    /// Generate the rows of a dataset other than that of the specification,
+    /// see [`LineItemGenerator::with_seed_offset`]
+    pub fn with_seed_offset(mut self, seed_offset: i64) -> Self {
+        self.seed_offset = seed_offset;
+        self
+    }
+
+    /// Generate the columns from other seeds, see
+    /// [`LineItemGenerator::with_column_seeds`]
+    pub fn with_column_seeds(mut self, column_seeds: ColumnSeeds) -> Self {
+        self.column_seeds = column_seeds;
+        self
+    }
+
+    /// Only generate the suppliers whose keys are in `supplier_keys`, or all
+    /// suppliers if `None` (the default), see [`PartGenerator::with_part_keys`]
+    pub fn with_supplier_keys(mut self, supplier_keys: Option<RangeInclusive<i64>>) -> Self {
+        self.keys = supplier_keys;
+        self
+    }
+
     /// Returns an iterator over the supplier rows
     pub fn iter(&self) -> SupplierGeneratorIterator<'a> {
-        SupplierGeneratorIterator::new(
-            self.distributions,
-            self.text_pool,
+        let (start_index, row_count) = rows_with_keys(
             GenerateUtils::calculate_start_index(
                 Self::SCALE_BASE,
-                self.scale_factor,
+                self.scale_factor * self.scales.supplier,
                 self.part,
                 self.part_count,
             ),
-            Self::calculate_row_count(self.scale_factor, self.part, self.part_count),
+            Self::calculate_row_count(
+                self.scale_factor * self.scales.supplier,
+                self.part,
+                self.part_count,
+            ),
+            self.keys.as_ref(),
+        );
+        SupplierGeneratorIterator::new(
+            self.distributions,
+            self.text_pool,
+            self.column_seeds.with_offset(self.seed_offset),
+            start_index,
+            row_count,
         )
+        .with_filter(self.filter.clone())
+        .with_edge_cases(self.edge_cases)
     }
 }
 
@@ -753,11 +1217,11 @@ impl<'a> IntoIterator for SupplierGenerator<'a> {
 /// Iterator that generates Supplier rows
 #[derive(Debug)]
 pub struct SupplierGeneratorIterator<'a> {
-    address_random: RandomAlphaNumeric,
+    address_random: AddressGenerator,
     nation_key_random: RandomBoundedInt,
-    phone_random: RandomPhoneNumber,
-    account_balance_random: RandomBoundedInt,
-    comment_random: RandomText<'a>,
+    phone_random: PhoneNumberGenerator,
+    account_balance_random: AccountBalanceGenerator,
+    comment_random: CommentGenerator<'a>,
     bbb_comment_random: RandomBoundedInt,
     bbb_junk_random: RowRandomInt,
     bbb_offset_random: RowRandomInt,
@@ -766,35 +1230,32 @@ pub struct SupplierGeneratorIterator<'a> {
     start_index: i64,
     row_count: i64,
     index: i64,
+    filter: Option<SupplierFilter>,
+    edge_cases: bool,
 }
 
 impl<'a> SupplierGeneratorIterator<'a> {
     fn new(
         distributions: &Distributions,
         text_pool: &'a TextPool,
+        seeds: ColumnSeeds,
         start_index: i64,
         row_count: i64,
     ) -> Self {
-        let mut address_random =
-            RandomAlphaNumeric::new(706178559, SupplierGenerator::ADDRESS_AVERAGE_LENGTH);
-        let mut nation_key_random =
-            RandomBoundedInt::new(110356601, 0, (distributions.nations().size() - 1) as i32);
-        let mut phone_random = RandomPhoneNumber::new(884434366);
-        let mut account_balance_random = RandomBoundedInt::new(
-            962338209,
-            SupplierGenerator::ACCOUNT_BALANCE_MIN,
-            SupplierGenerator::ACCOUNT_BALANCE_MAX,
-        );
-        let mut comment_random = RandomText::new(
-            1341315363,
-            text_pool,
-            SupplierGenerator::COMMENT_AVERAGE_LENGTH as f64,
+        let mut address_random = AddressGenerator::supplier(&seeds).with_text_pool(text_pool);
+        let mut nation_key_random = RandomBoundedInt::new(
+            seeds.s_nationkey,
+            0,
+            (distributions.nations().size() - 1) as i32,
         );
+        let mut phone_random = PhoneNumberGenerator::supplier(&seeds);
+        let mut account_balance_random = AccountBalanceGenerator::supplier(&seeds);
+        let mut comment_random = CommentGenerator::supplier(text_pool, &seeds);
         let mut bbb_comment_random =
-            RandomBoundedInt::new(202794285, 1, SupplierGenerator::SCALE_BASE);
-        let mut bbb_junk_random = RowRandomInt::new(263032577, 1);
-        let mut bbb_offset_random = RowRandomInt::new(715851524, 1);
-        let mut bbb_type_random = RandomBoundedInt::new(753643799, 0, 100);
+            RandomBoundedInt::new(seeds.s_bbb_row, 1, SupplierGenerator::SCALE_BASE);
+        let mut bbb_junk_random = RowRandomInt::new(seeds.s_bbb_junk, 1);
+        let mut bbb_offset_random = RowRandomInt::new(seeds.s_bbb_offset, 1);
+        let mut bbb_type_random = RandomBoundedInt::new(seeds.s_bbb_type, 0, 100);
 
         // Advance all generators to the starting position
         address_random.advance_rows(start_index);
@@ -820,12 +1281,18 @@ impl<'a> SupplierGeneratorIterator<'a> {
             start_index,
             row_count,
             index: 0,
+            filter: None,
+            edge_cases: false,
         }
     }
 
     /// Creates a supplier with the given key
     fn make_supplier(&mut self, supplier_key: i64) -> Supplier {
-        let mut comment = self.comment_random.next_value().to_string();
+        let extreme = self.edge_cases.then(|| Extreme::for_row(supplier_key));
+        let mut comment = self
+            .comment_random
+            .next_value_or_extreme(extreme)
+            .to_string();
 
         // Add supplier complaints or commendation to the comment
         let bbb_comment_random_value = self.bbb_comment_random.next_value();
@@ -850,19 +1317,20 @@ impl<'a> SupplierGeneratorIterator<'a> {
                     SupplierGenerator::BBB_RECOMMEND_TEXT
                 };
 
-            // Create a mutable string that we can modify in chunks
+            // Create a mutable string that we can modify in chunks, cut at
+            // whole characters in case the text pool is not ASCII
+            let cut = |index| floor_char_boundary(comment.as_bytes(), index);
+            let base_start = cut(offset);
+            let noise_start = cut(offset + SupplierGenerator::BBB_BASE_TEXT.len());
+            let noise_end = cut(offset + SupplierGenerator::BBB_BASE_TEXT.len() + noise);
+            let rest_start =
+                cut(offset + SupplierGenerator::BBB_BASE_TEXT.len() + noise + type_text.len());
             let mut modified_comment = String::with_capacity(comment.len());
-            modified_comment.push_str(&comment[..offset]);
+            modified_comment.push_str(&comment[..base_start]);
             modified_comment.push_str(SupplierGenerator::BBB_BASE_TEXT);
-            modified_comment.push_str(
-                &comment[offset + SupplierGenerator::BBB_BASE_TEXT.len()
-                    ..offset + SupplierGenerator::BBB_BASE_TEXT.len() + noise],
-            );
+            modified_comment.push_str(&comment[noise_start..noise_end]);
             modified_comment.push_str(type_text);
-            modified_comment.push_str(
-                &comment
-                    [offset + SupplierGenerator::BBB_BASE_TEXT.len() + noise + type_text.len()..],
-            );
+            modified_comment.push_str(&comment[rest_start..]);
 
             comment = modified_comment;
         }
@@ -872,19 +1340,33 @@ impl<'a> SupplierGeneratorIterator<'a> {
         Supplier {
             s_suppkey: supplier_key,
             s_name: SupplierName::new(supplier_key),
-            s_address: self.address_random.next_value(),
+            s_address: self.address_random.next_value_or_extreme(extreme),
             s_nationkey: nation_key,
             s_phone: self.phone_random.next_value(nation_key),
-            s_acctbal: TPCHDecimal(self.account_balance_random.next_value() as i64),
+            s_acctbal: edge_value(
+                extreme,
+                self.account_balance_random.next_value(),
+                TPCHDecimal(AccountBalanceGenerator::MIN as i64),
+                TPCHDecimal(AccountBalanceGenerator::MAX as i64),
+            ),
             s_comment: comment,
         }
     }
-}
 
-impl Iterator for SupplierGeneratorIterator<'_> {
-    type Item = Supplier;
+    /// Only return rows matching `filter`
+    fn with_filter(mut self, filter: Option<SupplierFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Replace values with their edge cases if `edge_cases` is true
+    fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generates the next row, ignoring the filter
+    fn next_row(&mut self) -> Option<Supplier> {
         if self.index >= self.row_count {
             return None;
         }
@@ -907,6 +1389,20 @@ impl Iterator for SupplierGeneratorIterator<'_> {
     }
 }
 
+impl Iterator for SupplierGeneratorIterator<'_> {
+    type Item = Supplier;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = self.next_row()?;
+            match &self.filter {
+                Some(filter) if !(filter.0)(&row) => continue,
+                _ => return Some(row),
+            }
+        }
+    }
+}
+
 /// A Customer Name, formatted as `"Customer#<n>"`
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct CustomerName(i64);
@@ -978,18 +1474,18 @@ pub struct CustomerGenerator<'a> {
     part_count: i32,
     distributions: &'a Distributions,
     text_pool: &'a TextPool,
+    filter: Option<CustomerFilter>,
+    edge_cases: bool,
+    scales: TableScales,
+    seed_offset: i64,
+    column_seeds: ColumnSeeds,
+    keys: Option<RangeInclusive<i64>>,
 }
 
 impl<'a> CustomerGenerator<'a> {
     /// Base scale for customer generation
     const SCALE_BASE: i32 = 150_000;
 
-    // Constants for customer generation
-    const ACCOUNT_BALANCE_MIN: i32 = -99999;
-    const ACCOUNT_BALANCE_MAX: i32 = 999999;
-    const ADDRESS_AVERAGE_LENGTH: i32 = 25;
-    const COMMENT_AVERAGE_LENGTH: i32 = 73;
-
     /// Creates a new CustomerGenerator with the given scale factor
     ///
     /// Note the generator's lifetime is `&'static`. See [`NationGenerator`] for
@@ -1006,6 +1502,8 @@ impl<'a> CustomerGenerator<'a> {
     }
 
     /// Creates a CustomerGenerator with specified distributions and text pool
+    ///
+    /// Uses the `nations` and `msegmnt` distributions, see [`Distributions`].
     pub fn new_with_distributions_and_text_pool<'b>(
         scale_factor: f64,
         part: i32,
@@ -1019,6 +1517,12 @@ impl<'a> CustomerGenerator<'a> {
             part_count,
             distributions,
             text_pool,
+            filter: None,
+            edge_cases: false,
+            scales: TableScales::default(),
+            seed_offset: 0,
+            column_seeds: ColumnSeeds::SPEC,
+            keys: None,
         }
     }
 
@@ -1027,19 +1531,75 @@ impl<'a> CustomerGenerator<'a> {
         GenerateUtils::calculate_row_count(Self::SCALE_BASE, scale_factor, part, part_count)
     }
 
+    /// Only generate the customer rows for which `filter` returns true
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&Customer<'_>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = Some(RowFilter(Arc::new(filter)));
+        self
+    }
+
+    /// Generate edge case values instead of random ones, see
+    /// [`LineItemGenerator::with_edge_cases`]
+    pub fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generate the customers at the scale factor times `scales.customer`, see
+    /// [`TableScales`]
+    pub fn with_table_scales(mut self, scales: TableScales) -> Self {
+        self.scales = scales;
+        self
+    }
+
+    /// Generate the rows of a dataset other than that of the specification,
+    /// see [`LineItemGenerator::with_seed_offset`]
+    pub fn with_seed_offset(mut self, seed_offset: i64) -> Self {
+        self.seed_offset = seed_offset;
+        self
+    }
+
+    /// Generate the columns from other seeds, see
+    /// [`LineItemGenerator::with_column_seeds`]
+    pub fn with_column_seeds(mut self, column_seeds: ColumnSeeds) -> Self {
+        self.column_seeds = column_seeds;
+        self
+    }
+
+    /// Only generate the customers whose keys are in `customer_keys`, or all
+    /// customers if `None` (the default), see [`PartGenerator::with_part_keys`]
+    pub fn with_customer_keys(mut self, customer_keys: Option<RangeInclusive<i64>>) -> Self {
+        self.keys = customer_keys;
+        self
+    }
+
     /// Returns an iterator over the customer rows
     pub fn iter(&self) -> CustomerGeneratorIterator<'a> {
-        CustomerGeneratorIterator::new(
-            self.distributions,
-            self.text_pool,
+        let (start_index, row_count) = rows_with_keys(
             GenerateUtils::calculate_start_index(
                 Self::SCALE_BASE,
-                self.scale_factor,
+                self.scale_factor * self.scales.customer,
+                self.part,
+                self.part_count,
+            ),
+            Self::calculate_row_count(
+                self.scale_factor * self.scales.customer,
                 self.part,
                 self.part_count,
             ),
-            Self::calculate_row_count(self.scale_factor, self.part, self.part_count),
+            self.keys.as_ref(),
+        );
+        CustomerGeneratorIterator::new(
+            self.distributions,
+            self.text_pool,
+            self.column_seeds.with_offset(self.seed_offset),
+            start_index,
+            row_count,
         )
+        .with_filter(self.filter.clone())
+        .with_edge_cases(self.edge_cases)
     }
 }
 
@@ -1055,42 +1615,39 @@ impl<'a> IntoIterator for CustomerGenerator<'a> {
 /// Iterator that generates Customer rows
 #[derive(Debug)]
 pub struct CustomerGeneratorIterator<'a> {
-    address_random: RandomAlphaNumeric,
+    address_random: AddressGenerator,
     nation_key_random: RandomBoundedInt,
-    phone_random: RandomPhoneNumber,
-    account_balance_random: RandomBoundedInt,
+    phone_random: PhoneNumberGenerator,
+    account_balance_random: AccountBalanceGenerator,
     market_segment_random: RandomString<'a>,
-    comment_random: RandomText<'a>,
+    comment_random: CommentGenerator<'a>,
 
     start_index: i64,
     row_count: i64,
     index: i64,
+    filter: Option<CustomerFilter>,
+    edge_cases: bool,
 }
 
 impl<'a> CustomerGeneratorIterator<'a> {
     fn new(
         distributions: &'a Distributions,
         text_pool: &'a TextPool,
+        seeds: ColumnSeeds,
         start_index: i64,
         row_count: i64,
     ) -> Self {
-        let mut address_random =
-            RandomAlphaNumeric::new(881155353, CustomerGenerator::ADDRESS_AVERAGE_LENGTH);
-        let mut nation_key_random =
-            RandomBoundedInt::new(1489529863, 0, (distributions.nations().size() - 1) as i32);
-        let mut phone_random = RandomPhoneNumber::new(1521138112);
-        let mut account_balance_random = RandomBoundedInt::new(
-            298370230,
-            CustomerGenerator::ACCOUNT_BALANCE_MIN,
-            CustomerGenerator::ACCOUNT_BALANCE_MAX,
+        let mut address_random = AddressGenerator::customer(&seeds).with_text_pool(text_pool);
+        let mut nation_key_random = RandomBoundedInt::new(
+            seeds.c_nationkey,
+            0,
+            (distributions.nations().size() - 1) as i32,
         );
+        let mut phone_random = PhoneNumberGenerator::customer(&seeds);
+        let mut account_balance_random = AccountBalanceGenerator::customer(&seeds);
         let mut market_segment_random =
-            RandomString::new(1140279430, distributions.market_segments());
-        let mut comment_random = RandomText::new(
-            1335826707,
-            text_pool,
-            CustomerGenerator::COMMENT_AVERAGE_LENGTH as f64,
-        );
+            RandomString::new(seeds.c_mktsegment, distributions.market_segments());
+        let mut comment_random = CommentGenerator::customer(text_pool, &seeds);
 
         // Advance all generators to the starting position
         address_random.advance_rows(start_index);
@@ -1110,30 +1667,47 @@ impl<'a> CustomerGeneratorIterator<'a> {
             start_index,
             row_count,
             index: 0,
+            filter: None,
+            edge_cases: false,
         }
     }
 
     /// Creates a customer with the given key
     fn make_customer(&mut self, customer_key: i64) -> Customer<'a> {
+        let extreme = self.edge_cases.then(|| Extreme::for_row(customer_key));
         let nation_key = self.nation_key_random.next_value() as i64;
 
         Customer {
             c_custkey: customer_key,
             c_name: CustomerName::new(customer_key),
-            c_address: self.address_random.next_value(),
+            c_address: self.address_random.next_value_or_extreme(extreme),
             c_nationkey: nation_key,
             c_phone: self.phone_random.next_value(nation_key),
-            c_acctbal: TPCHDecimal(self.account_balance_random.next_value() as i64),
+            c_acctbal: edge_value(
+                extreme,
+                self.account_balance_random.next_value(),
+                TPCHDecimal(AccountBalanceGenerator::MIN as i64),
+                TPCHDecimal(AccountBalanceGenerator::MAX as i64),
+            ),
             c_mktsegment: self.market_segment_random.next_value(),
-            c_comment: self.comment_random.next_value(),
+            c_comment: self.comment_random.next_value_or_extreme(extreme),
         }
     }
-}
 
-impl<'a> Iterator for CustomerGeneratorIterator<'a> {
-    type Item = Customer<'a>;
+    /// Only return rows matching `filter`
+    fn with_filter(mut self, filter: Option<CustomerFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Replace values with their edge cases if `edge_cases` is true
+    fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generates the next row, ignoring the filter
+    fn next_row(&mut self) -> Option<Customer<'a>> {
         if self.index >= self.row_count {
             return None;
         }
@@ -1153,6 +1727,20 @@ impl<'a> Iterator for CustomerGeneratorIterator<'a> {
     }
 }
 
+impl<'a> Iterator for CustomerGeneratorIterator<'a> {
+    type Item = Customer<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = self.next_row()?;
+            match &self.filter {
+                Some(filter) if !(filter.0)(&row) => continue,
+                _ => return Some(row),
+            }
+        }
+    }
+}
+
 /// The PARTSUPP (part supplier) table
 ///
 /// The Display trait is implemented to format the line item data as a string
@@ -1193,6 +1781,12 @@ pub struct PartSuppGenerator<'a> {
     part: i32,
     part_count: i32,
     text_pool: &'a TextPool,
+    filter: Option<PartSuppFilter>,
+    edge_cases: bool,
+    scales: TableScales,
+    seed_offset: i64,
+    column_seeds: ColumnSeeds,
+    keys: Option<RangeInclusive<i64>>,
 }
 
 impl<'a> PartSuppGenerator<'a> {
@@ -1220,6 +1814,20 @@ impl<'a> PartSuppGenerator<'a> {
         )
     }
 
+    /// Creates a PartSuppGenerator with specified distributions and text pool
+    ///
+    /// Uses none of the distributions, only the text pool, see
+    /// [`Distributions`]; they are taken like by the other generators.
+    pub fn new_with_distributions_and_text_pool<'b>(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+        _distributions: &'b Distributions,
+        text_pool: &'b TextPool,
+    ) -> PartSuppGenerator<'b> {
+        Self::new_with_text_pool(scale_factor, part, part_count, text_pool)
+    }
+
     /// Creates a PartSuppGenerator with specified text pool
     pub fn new_with_text_pool(
         scale_factor: f64,
@@ -1232,6 +1840,12 @@ impl<'a> PartSuppGenerator<'a> {
             part,
             part_count,
             text_pool,
+            filter: None,
+            edge_cases: false,
+            scales: TableScales::default(),
+            seed_offset: 0,
+            column_seeds: ColumnSeeds::SPEC,
+            keys: None,
         }
     }
 
@@ -1246,21 +1860,78 @@ impl<'a> PartSuppGenerator<'a> {
         )
     }
 
-    /// Returns an iterator over the part supplier rows
-    pub fn iter(&self) -> PartSuppGeneratorIterator<'a> {
-        let scale_base = PartGenerator::SCALE_BASE;
-
-        PartSuppGeneratorIterator::new(
-            self.text_pool,
-            self.scale_factor,
+    /// Only generate the part supplier rows for which `filter` returns true
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&PartSupp<'_>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = Some(RowFilter(Arc::new(filter)));
+        self
+    }
+
+    /// Generate edge case values instead of random ones, see
+    /// [`LineItemGenerator::with_edge_cases`]
+    pub fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generate the suppliers of the parts at the scale factor times
+    /// `scales.part`, [`TableScales::suppliers_per_part`] of the suppliers at
+    /// the scale factor times `scales.supplier` for each part, see
+    /// [`TableScales`]
+    pub fn with_table_scales(mut self, scales: TableScales) -> Self {
+        self.scales = scales;
+        self
+    }
+
+    /// Generate the rows of a dataset other than that of the specification,
+    /// see [`LineItemGenerator::with_seed_offset`]
+    pub fn with_seed_offset(mut self, seed_offset: i64) -> Self {
+        self.seed_offset = seed_offset;
+        self
+    }
+
+    /// Generate the columns from other seeds, see
+    /// [`LineItemGenerator::with_column_seeds`]
+    pub fn with_column_seeds(mut self, column_seeds: ColumnSeeds) -> Self {
+        self.column_seeds = column_seeds;
+        self
+    }
+
+    /// Only generate the suppliers of the parts whose keys are in
+    /// `part_keys`, or of all parts if `None` (the default), see
+    /// [`PartGenerator::with_part_keys`]
+    pub fn with_part_keys(mut self, part_keys: Option<RangeInclusive<i64>>) -> Self {
+        self.keys = part_keys;
+        self
+    }
+
+    /// Returns an iterator over the part supplier rows
+    pub fn iter(&self) -> PartSuppGeneratorIterator<'a> {
+        let scale_base = PartGenerator::SCALE_BASE;
+        let scale_factor = self.scale_factor * self.scales.part;
+
+        let (start_index, row_count) = rows_with_keys(
             GenerateUtils::calculate_start_index(
                 scale_base,
-                self.scale_factor,
+                scale_factor,
                 self.part,
                 self.part_count,
             ),
-            Self::calculate_row_count(self.scale_factor, self.part, self.part_count),
+            Self::calculate_row_count(scale_factor, self.part, self.part_count),
+            self.keys.as_ref(),
+        );
+        PartSuppGeneratorIterator::new(
+            self.text_pool,
+            self.column_seeds.with_offset(self.seed_offset),
+            self.scales.supplier_count(self.scale_factor),
+            self.scales.suppliers_per_part(),
+            start_index,
+            row_count,
         )
+        .with_filter(self.filter.clone())
+        .with_edge_cases(self.edge_cases)
     }
 }
 
@@ -1276,7 +1947,8 @@ impl<'a> IntoIterator for PartSuppGenerator<'a> {
 /// Iterator that generates PartSupp rows
 #[derive(Debug)]
 pub struct PartSuppGeneratorIterator<'a> {
-    scale_factor: f64,
+    supplier_count: i64,
+    suppliers_per_part: i32,
     start_index: i64,
     row_count: i64,
 
@@ -1286,27 +1958,36 @@ pub struct PartSuppGeneratorIterator<'a> {
 
     index: i64,
     part_supplier_number: i32,
+    filter: Option<PartSuppFilter>,
+    edge_cases: bool,
 }
 
 impl<'a> PartSuppGeneratorIterator<'a> {
-    fn new(text_pool: &'a TextPool, scale_factor: f64, start_index: i64, row_count: i64) -> Self {
+    fn new(
+        text_pool: &'a TextPool,
+        seeds: ColumnSeeds,
+        supplier_count: i64,
+        suppliers_per_part: i32,
+        start_index: i64,
+        row_count: i64,
+    ) -> Self {
         let mut available_quantity_random = RandomBoundedInt::new_with_seeds_per_row(
-            1671059989,
+            seeds.ps_availqty,
             PartSuppGenerator::AVAILABLE_QUANTITY_MIN,
             PartSuppGenerator::AVAILABLE_QUANTITY_MAX,
-            PartSuppGenerator::SUPPLIERS_PER_PART,
+            suppliers_per_part,
         );
         let mut supply_cost_random = RandomBoundedInt::new_with_seeds_per_row(
-            1051288424,
+            seeds.ps_supplycost,
             PartSuppGenerator::SUPPLY_COST_MIN,
             PartSuppGenerator::SUPPLY_COST_MAX,
-            PartSuppGenerator::SUPPLIERS_PER_PART,
+            suppliers_per_part,
         );
         let mut comment_random = RandomText::new_with_expected_row_count(
-            1961692154,
+            seeds.ps_comment,
             text_pool,
             PartSuppGenerator::COMMENT_AVERAGE_LENGTH as f64,
-            PartSuppGenerator::SUPPLIERS_PER_PART,
+            suppliers_per_part,
         );
 
         // Advance all generators to the starting position
@@ -1315,7 +1996,8 @@ impl<'a> PartSuppGeneratorIterator<'a> {
         comment_random.advance_rows(start_index);
 
         PartSuppGeneratorIterator {
-            scale_factor,
+            supplier_count,
+            suppliers_per_part,
             start_index,
             row_count,
             available_quantity_random,
@@ -1323,20 +2005,35 @@ impl<'a> PartSuppGeneratorIterator<'a> {
             comment_random,
             index: 0,
             part_supplier_number: 0,
+            filter: None,
+            edge_cases: false,
         }
     }
 
     /// Creates a part-supplier entry with the given part key
     fn make_part_supplier(&mut self, part_key: i64) -> PartSupp<'a> {
-        let supplier_key = Self::select_part_supplier(
+        let supplier_key = Self::select_supplier(
             part_key,
             self.part_supplier_number as i64,
-            self.scale_factor,
+            self.supplier_count,
+            self.suppliers_per_part,
         );
 
-        let ps_availqty = self.available_quantity_random.next_value();
-        let ps_supplycost = TPCHDecimal(self.supply_cost_random.next_value() as i64);
-        let ps_comment = self.comment_random.next_value();
+        let extreme = self.edge_cases.then(|| Extreme::for_row(part_key));
+        let ps_availqty = edge_value(
+            extreme,
+            self.available_quantity_random.next_value(),
+            PartSuppGenerator::AVAILABLE_QUANTITY_MIN,
+            PartSuppGenerator::AVAILABLE_QUANTITY_MAX,
+        );
+        let ps_supplycost = edge_value(
+            extreme,
+            self.supply_cost_random.next_value(),
+            PartSuppGenerator::SUPPLY_COST_MIN,
+            PartSuppGenerator::SUPPLY_COST_MAX,
+        );
+        let ps_supplycost = TPCHDecimal(ps_supplycost as i64);
+        let ps_comment = self.comment_random.next_value_or_extreme(extreme);
 
         PartSupp {
             ps_partkey: part_key,
@@ -1351,20 +2048,45 @@ impl<'a> PartSuppGeneratorIterator<'a> {
     pub fn select_part_supplier(part_key: i64, supplier_number: i64, scale_factor: f64) -> i64 {
         // Use supplier generator's scale base
         let supplier_count = (SupplierGenerator::SCALE_BASE as f64 * scale_factor) as i64;
+        Self::select_supplier(
+            part_key,
+            supplier_number,
+            supplier_count,
+            PartSuppGenerator::SUPPLIERS_PER_PART,
+        )
+    }
 
+    /// Selects a supplier for a given part and supplier number out of
+    /// `supplier_count` suppliers, with `suppliers_per_part` suppliers for
+    /// each part
+    fn select_supplier(
+        part_key: i64,
+        supplier_number: i64,
+        supplier_count: i64,
+        suppliers_per_part: i32,
+    ) -> i64 {
         ((part_key
             + (supplier_number
-                * ((supplier_count / PartSuppGenerator::SUPPLIERS_PER_PART as i64)
+                * ((supplier_count / suppliers_per_part as i64)
                     + ((part_key - 1) / supplier_count))))
             % supplier_count)
             + 1
     }
-}
 
-impl<'a> Iterator for PartSuppGeneratorIterator<'a> {
-    type Item = PartSupp<'a>;
+    /// Only return rows matching `filter`
+    fn with_filter(mut self, filter: Option<PartSuppFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Replace values with their edge cases if `edge_cases` is true
+    fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Generates the next row, ignoring the filter
+    fn next_row(&mut self) -> Option<PartSupp<'a>> {
         if self.index >= self.row_count {
             return None;
         }
@@ -1374,7 +2096,7 @@ impl<'a> Iterator for PartSuppGeneratorIterator<'a> {
         self.part_supplier_number += 1;
 
         // advance next row only when all suppliers for the part have been produced
-        if self.part_supplier_number >= PartSuppGenerator::SUPPLIERS_PER_PART {
+        if self.part_supplier_number >= self.suppliers_per_part {
             self.available_quantity_random.row_finished();
             self.supply_cost_random.row_finished();
             self.comment_random.row_finished();
@@ -1387,6 +2109,20 @@ impl<'a> Iterator for PartSuppGeneratorIterator<'a> {
     }
 }
 
+impl<'a> Iterator for PartSuppGeneratorIterator<'a> {
+    type Item = PartSupp<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = self.next_row()?;
+            match &self.filter {
+                Some(filter) if !(filter.0)(&row) => continue,
+                _ => return Some(row),
+            }
+        }
+    }
+}
+
 /// A clerk name, formatted as `"Clerk#<n>"`
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ClerkName(i32);
@@ -1404,32 +2140,139 @@ impl fmt::Display for ClerkName {
     }
 }
 
-/// Order status (F=final, O=open, P=pending)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd)]
-pub enum OrderStatus {
-    /// Fulfilled - all line items shipped
-    Fulfilled,
-    /// Open - no line items shipped
-    Open,
-    /// Partially fulfilled - some line items shipped
-    Pending,
+/// Error returned when parsing an enum column value, such as a [`ShipMode`],
+/// from text that is not one of its values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidValueError {
+    column: &'static str,
+    value: String,
+}
+
+impl Display for InvalidValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid value '{}' for {}", self.value, self.column)
+    }
 }
 
-impl OrderStatus {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            OrderStatus::Fulfilled => "F",
-            OrderStatus::Open => "O",
-            OrderStatus::Pending => "P",
+impl std::error::Error for InvalidValueError {}
+
+/// Defines an enum for a column with a fixed set of values, whose `Display`
+/// and `FromStr` impls use the exact text of the TPC-H specification
+macro_rules! define_column_enum {
+    (
+        $(#[$meta:meta])*
+        $NAME:ident, $COLUMN:literal,
+        { $($(#[$variant_meta:meta])* $VARIANT:ident => $TEXT:literal),+ $(,)? }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum $NAME {
+            $($(#[$variant_meta])* $VARIANT),+
         }
-    }
+
+        impl $NAME {
+            /// All values, in the order of the TPC-H specification
+            pub const ALL: &'static [$NAME] = &[$($NAME::$VARIANT),+];
+
+            /// Returns the text of the value, e.g. as written in TBL files
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($NAME::$VARIANT => $TEXT),+
+                }
+            }
+        }
+
+        impl Display for $NAME {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.as_str())
+            }
+        }
+
+        impl std::str::FromStr for $NAME {
+            type Err = InvalidValueError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($TEXT => Ok($NAME::$VARIANT),)+
+                    _ => Err(InvalidValueError {
+                        column: $COLUMN,
+                        value: s.to_string(),
+                    }),
+                }
+            }
+        }
+    };
 }
 
-impl Display for OrderStatus {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.as_str())
+define_column_enum!(
+    /// Order status (F=final, O=open, P=pending)
+    OrderStatus, "o_orderstatus", {
+        /// Fulfilled - all line items shipped
+        Fulfilled => "F",
+        /// Open - no line items shipped
+        Open => "O",
+        /// Partially fulfilled - some line items shipped
+        Pending => "P",
     }
-}
+);
+
+define_column_enum!(
+    /// Order priority
+    OrderPriority, "o_orderpriority", {
+        /// Urgent, the highest priority
+        Urgent => "1-URGENT",
+        /// High
+        High => "2-HIGH",
+        /// Medium
+        Medium => "3-MEDIUM",
+        /// Not specified
+        NotSpecified => "4-NOT SPECIFIED",
+        /// Low, the lowest priority
+        Low => "5-LOW",
+    }
+);
+
+define_column_enum!(
+    /// Line item return flag (R=returned, A=accepted, N=not returned yet)
+    ReturnFlag, "l_returnflag", {
+        /// Returned
+        Returned => "R",
+        /// Accepted
+        Accepted => "A",
+        /// Not returned (yet), the item has not been received
+        NotReturned => "N",
+    }
+);
+
+define_column_enum!(
+    /// Line item status (F=fulfilled, O=open)
+    LineStatus, "l_linestatus", {
+        /// Fulfilled - the item has shipped
+        Fulfilled => "F",
+        /// Open - the item has not shipped yet
+        Open => "O",
+    }
+);
+
+define_column_enum!(
+    /// Line item shipping mode
+    ShipMode, "l_shipmode", {
+        /// Regular air
+        RegularAir => "REG AIR",
+        /// Air
+        Air => "AIR",
+        /// Rail
+        Rail => "RAIL",
+        /// Truck
+        Truck => "TRUCK",
+        /// Mail
+        Mail => "MAIL",
+        /// Free on board
+        Fob => "FOB",
+        /// Ship
+        Ship => "SHIP",
+    }
+);
 
 /// The ORDERS table
 ///
@@ -1453,7 +2296,7 @@ pub struct Order<'a> {
     /// Order date
     pub o_orderdate: TPCHDate,
     /// Order priority
-    pub o_orderpriority: &'a str,
+    pub o_orderpriority: OrderPriority,
     /// Clerk who processed the order.
     pub o_clerk: ClerkName,
     /// Order shipping priority
@@ -1488,24 +2331,36 @@ pub struct OrderGenerator<'a> {
     part_count: i32,
     distributions: &'a Distributions,
     text_pool: &'a TextPool,
+    filter: Option<OrderFilter>,
+    edge_cases: bool,
+    zipf: Option<Zipf>,
+    scales: TableScales,
+    update_set: i32,
+    delete_set: i32,
+    orders_per_day: Option<i64>,
+    order_keys: Option<RangeInclusive<i64>>,
+    order_dates: Option<RangeInclusive<i32>>,
+    seed_offset: i64,
+    column_seeds: ColumnSeeds,
 }
 
 impl<'a> OrderGenerator<'a> {
     /// Base scale for order generation
     pub const SCALE_BASE: i32 = 1_500_000;
 
+    /// Base scale of the orders inserted and deleted by each refresh set,
+    /// 0.1% of the orders
+    pub const UPDATE_SCALE_BASE: i32 = Self::SCALE_BASE / 1000;
+
     // Constants for order generation
     const CUSTOMER_MORTALITY: i32 = 3; // portion with no orders
     const ORDER_DATE_MIN: i32 = dates::MIN_GENERATE_DATE;
     const ORDER_DATE_MAX: i32 =
         Self::ORDER_DATE_MIN + (dates::TOTAL_DATE_RANGE - LineItemGenerator::ITEM_SHIP_DAYS - 1);
-    const CLERK_SCALE_BASE: i32 = 1000;
 
     const LINE_COUNT_MIN: i32 = 1;
     pub const LINE_COUNT_MAX: i32 = 7;
 
-    const COMMENT_AVERAGE_LENGTH: i32 = 49;
-
     const ORDER_KEY_SPARSE_BITS: i32 = 2;
     const ORDER_KEY_SPARSE_KEEP: i32 = 3;
     /// Creates a new OrderGenerator with the given scale factor
@@ -1524,6 +2379,8 @@ impl<'a> OrderGenerator<'a> {
     }
 
     /// Creates a OrderGenerator with specified distributions and text pool
+    ///
+    /// Uses the `o_oprio` distribution, see [`Distributions`].
     pub fn new_with_distributions_and_text_pool<'b>(
         scale_factor: f64,
         part: i32,
@@ -1537,6 +2394,17 @@ impl<'a> OrderGenerator<'a> {
             part_count,
             distributions,
             text_pool,
+            filter: None,
+            edge_cases: false,
+            zipf: None,
+            scales: TableScales::default(),
+            update_set: 0,
+            delete_set: 0,
+            orders_per_day: None,
+            order_keys: None,
+            order_dates: None,
+            seed_offset: 0,
+            column_seeds: ColumnSeeds::SPEC,
         }
     }
 
@@ -1545,39 +2413,248 @@ impl<'a> OrderGenerator<'a> {
         GenerateUtils::calculate_row_count(Self::SCALE_BASE, scale_factor, part, part_count)
     }
 
+    /// Only generate the order rows for which `filter` returns true
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&Order<'_>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = Some(RowFilter(Arc::new(filter)));
+        self
+    }
+
+    /// Generate edge case values instead of random ones, see
+    /// [`LineItemGenerator::with_edge_cases`]
+    pub fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Select the customers of the orders, and the parts of the line items
+    /// that `o_totalprice` is computed from, from a Zipf distribution
+    /// instead of uniformly, see [`LineItemGenerator::with_zipf`]
+    pub fn with_zipf(mut self, zipf: Option<Zipf>) -> Self {
+        self.zipf = zipf;
+        self
+    }
+
+    /// Generate the orders at the scale factor times `scales.orders`, for
+    /// the customers and parts scaled by `scales`, see [`TableScales`]
+    pub fn with_table_scales(mut self, scales: TableScales) -> Self {
+        self.scales = scales;
+        self
+    }
+
+    /// Generate the orders inserted by refresh function RF1 of the (1 based)
+    /// refresh set `update_set` instead of the base orders, or the base
+    /// orders if `update_set` is 0
+    ///
+    /// Each refresh set has [`Self::UPDATE_SCALE_BASE`] orders per unit of
+    /// scale factor, split into parts like the base orders. Like dbgen, the
+    /// orders of refresh set `n` have the sparse keys that the base orders
+    /// leave unused, and their values continue the random streams of the
+    /// base orders, after those of the previous refresh sets.
+    /// [`LineItemGenerator::with_update_set`] generates their line items.
+    ///
+    /// ```
+    /// # use tpchgen::generators::OrderGenerator;
+    /// let keys: Vec<_> = OrderGenerator::new(1.0, 1, 1)
+    ///     .with_update_set(1)
+    ///     .iter()
+    ///     .map(|order| order.o_orderkey)
+    ///     .take(10)
+    ///     .collect();
+    /// assert_eq!(keys, vec![9, 10, 11, 12, 13, 14, 15, 40, 41, 42]);
+    /// ```
+    pub fn with_update_set(mut self, update_set: i32) -> Self {
+        self.update_set = update_set;
+        self
+    }
+
+    /// Generate the base orders deleted by refresh function RF2 of the (1
+    /// based) refresh set `delete_set` instead of all base orders, or all
+    /// base orders if `delete_set` is 0
+    ///
+    /// The orders are those whose keys [`DeleteKeyGenerator`] generates,
+    /// e.g. for the images of the deleted rows. They are the same rows as in
+    /// the base orders, split into parts like the keys.
+    /// [`LineItemGenerator::with_delete_set`] generates their line items.
+    ///
+    /// ```
+    /// # use tpchgen::generators::{DeleteKeyGenerator, OrderGenerator};
+    /// let orders = OrderGenerator::new(0.01, 1, 1).with_delete_set(2);
+    /// let keys = DeleteKeyGenerator::new(0.01, 1, 1).with_update_set(2);
+    /// assert!(orders.iter().map(|o| o.o_orderkey).eq(keys.iter()));
+    /// ```
+    pub fn with_delete_set(mut self, delete_set: i32) -> Self {
+        self.delete_set = delete_set;
+        self
+    }
+
+    /// Give the orders increasing order dates instead of random ones, one
+    /// day for every `orders_per_day` orders from the day after the last
+    /// order date of the specification, or random order dates if
+    /// `orders_per_day` is `None` (the default)
+    ///
+    /// The dates start at `1998-08-03`, so the orders follow those of the
+    /// base tables, and only depend on the index of the order, so they
+    /// increase across parts and refresh sets too. The ship, commit and
+    /// receipt dates of the line items follow the order dates, so they are
+    /// all after the current date of the specification: the orders are open
+    /// and their line items neither shipped nor returned.
+    /// [`LineItemGenerator::with_orders_per_day`] must be given the same
+    /// value for the tables to match.
+    ///
+    /// ```
+    /// # use tpchgen::generators::OrderGenerator;
+    /// let dates: Vec<_> = OrderGenerator::new(0.01, 1, 1)
+    ///     .with_orders_per_day(Some(2))
+    ///     .iter()
+    ///     .map(|order| order.o_orderdate.to_string())
+    ///     .take(5)
+    ///     .collect();
+    /// assert_eq!(dates, ["1998-08-03", "1998-08-03", "1998-08-04", "1998-08-04", "1998-08-05"]);
+    /// ```
+    pub fn with_orders_per_day(mut self, orders_per_day: Option<i64>) -> Self {
+        self.orders_per_day = orders_per_day;
+        self
+    }
+
+    /// Only generate the orders whose keys are in `order_keys`, or all
+    /// orders if `None` (the default)
+    ///
+    /// Unlike [`Self::with_filter`], the other orders are not generated at
+    /// all: the random streams skip to the first order in the range, as for
+    /// the start of a part, so the orders of a small range of keys are
+    /// generated in constant time, and parts without such orders are empty.
+    /// The orders in the range are the same as without it.
+    ///
+    /// ```
+    /// # use tpchgen::generators::OrderGenerator;
+    /// let keys: Vec<_> = OrderGenerator::new(0.01, 1, 1)
+    ///     .with_order_keys(Some(30..=40))
+    ///     .iter()
+    ///     .map(|order| order.o_orderkey)
+    ///     .collect();
+    /// assert_eq!(keys, [32, 33, 34, 35, 36, 37, 38, 39]);
+    /// ```
+    pub fn with_order_keys(mut self, order_keys: Option<RangeInclusive<i64>>) -> Self {
+        self.order_keys = order_keys;
+        self
+    }
+
+    /// Only generate the orders whose order dates, as days since the Unix
+    /// epoch (1970-01-01), are in `order_dates`, or all orders if `None`
+    /// (the default)
+    ///
+    /// The order date of each order is generated first, so the other columns
+    /// of the orders outside the range are not generated at all: their
+    /// random streams skip to the next order. The orders in the range are
+    /// the same as without it.
+    ///
+    /// ```
+    /// # use tpchgen::dates::TPCHDate;
+    /// # use tpchgen::generators::OrderGenerator;
+    /// let start = TPCHDate::from_ymd(1995, 1, 1).unwrap().to_epoch_days();
+    /// let end = TPCHDate::from_ymd(1995, 1, 31).unwrap().to_epoch_days();
+    /// let generator = OrderGenerator::new(0.01, 1, 1).with_order_dates(Some(start..=end));
+    /// assert!(generator
+    ///     .iter()
+    ///     .all(|o| o.o_orderdate.to_string().starts_with("1995-01")));
+    /// ```
+    pub fn with_order_dates(mut self, order_dates: Option<RangeInclusive<i32>>) -> Self {
+        self.order_dates = order_dates;
+        self
+    }
+
+    /// Generate the rows of a dataset other than that of the specification,
+    /// see [`LineItemGenerator::with_seed_offset`]
+    pub fn with_seed_offset(mut self, seed_offset: i64) -> Self {
+        self.seed_offset = seed_offset;
+        self
+    }
+
+    /// Generate the columns from other seeds, see
+    /// [`LineItemGenerator::with_column_seeds`]
+    pub fn with_column_seeds(mut self, column_seeds: ColumnSeeds) -> Self {
+        self.column_seeds = column_seeds;
+        self
+    }
+
     /// Returns an iterator over the order rows
     pub fn iter(&self) -> OrderGeneratorIterator<'a> {
         OrderGeneratorIterator::new(
             self.distributions,
             self.text_pool,
+            self.column_seeds.with_offset(self.seed_offset),
             self.scale_factor,
-            GenerateUtils::calculate_start_index(
-                Self::SCALE_BASE,
-                self.scale_factor,
+            self.scales,
+            OrderRange::for_sets(
+                self.scale_factor * self.scales.orders,
                 self.part,
                 self.part_count,
-            ),
-            Self::calculate_row_count(self.scale_factor, self.part, self.part_count),
+                self.update_set,
+                self.delete_set,
+            )
+            .with_order_keys(self.order_keys.as_ref()),
         )
+        .with_filter(self.filter.clone())
+        .with_edge_cases(self.edge_cases)
+        .with_zipf(self.zipf)
+        .with_orders_per_day(self.orders_per_day)
+        .with_order_dates(self.order_dates.clone())
     }
 
     /// Creates the order date random generator
     pub fn create_order_date_random() -> RandomBoundedInt {
-        RandomBoundedInt::new(1066728069, Self::ORDER_DATE_MIN, Self::ORDER_DATE_MAX)
+        RandomBoundedInt::new(
+            ColumnSeeds::SPEC.o_orderdate,
+            Self::ORDER_DATE_MIN,
+            Self::ORDER_DATE_MAX,
+        )
     }
 
     /// Creates the line count random generator
     pub fn create_line_count_random() -> RandomBoundedInt {
-        RandomBoundedInt::new(1434868289, Self::LINE_COUNT_MIN, Self::LINE_COUNT_MAX)
+        RandomBoundedInt::new(
+            ColumnSeeds::SPEC.o_line_count,
+            Self::LINE_COUNT_MIN,
+            Self::LINE_COUNT_MAX,
+        )
+    }
+
+    /// Returns the order date of the order with the (1 based) `order_index`
+    /// for increasing order dates, see [`Self::with_orders_per_day`]
+    fn increasing_order_date(order_index: i64, orders_per_day: i64) -> i32 {
+        let days = (order_index - 1) / orders_per_day.max(1);
+        Self::ORDER_DATE_MAX + 1 + days as i32
+    }
+
+    /// Returns the smallest or largest customer key that can have orders
+    fn edge_customer_key(extreme: Extreme, max_customer_key: i64) -> i64 {
+        let mut customer_key = extreme.pick(1, max_customer_key);
+        while customer_key % Self::CUSTOMER_MORTALITY as i64 == 0 {
+            customer_key -= 1;
+        }
+        customer_key
     }
 
     /// Creates an order key from an index
     pub fn make_order_key(order_index: i64) -> i64 {
+        Self::make_sparse_order_key(order_index, 0)
+    }
+
+    /// Creates an order key from an index and the sequence number of the
+    /// keys, 0 for the base orders and 1 for the first refresh sets
+    ///
+    /// Only the first 8 of every 32 keys are used by the base orders, the
+    /// next 8 by the orders of the refresh sets.
+    fn make_sparse_order_key(order_index: i64, sequence: i64) -> i64 {
         let low_bits = order_index & ((1 << Self::ORDER_KEY_SPARSE_KEEP) - 1);
 
         let mut ok = order_index;
         ok >>= Self::ORDER_KEY_SPARSE_KEEP;
         ok <<= Self::ORDER_KEY_SPARSE_BITS;
+        ok += sequence;
         ok <<= Self::ORDER_KEY_SPARSE_KEEP;
         ok += low_bits;
 
@@ -1585,6 +2662,117 @@ impl<'a> OrderGenerator<'a> {
     }
 }
 
+/// The orders generated by one part of an [`OrderGenerator`] or
+/// [`LineItemGenerator`]
+#[derive(Debug, Clone, Copy)]
+struct OrderRange {
+    /// The index of the order before the first one, which determines the
+    /// order keys
+    start_index: i64,
+    row_count: i64,
+    /// The number of rows the random streams are ahead of `start_index`
+    random_offset: i64,
+    /// The sequence number of the order keys, see
+    /// [`OrderGenerator::make_sparse_order_key`]
+    key_sequence: i64,
+}
+
+impl OrderRange {
+    /// Returns the orders of `part` of the base orders, or of the (1 based)
+    /// refresh set `update_set` if not 0
+    fn new(scale_factor: f64, part: i32, part_count: i32, update_set: i32) -> Self {
+        if update_set == 0 {
+            return Self {
+                start_index: GenerateUtils::calculate_start_index(
+                    OrderGenerator::SCALE_BASE,
+                    scale_factor,
+                    part,
+                    part_count,
+                ),
+                row_count: OrderGenerator::calculate_row_count(scale_factor, part, part_count),
+                random_offset: 0,
+                key_sequence: 0,
+            };
+        }
+        let base = OrderGenerator::UPDATE_SCALE_BASE;
+        let set_row_count = GenerateUtils::calculate_row_count(base, scale_factor, 1, 1);
+        Self {
+            start_index: (update_set as i64 - 1) * set_row_count
+                + GenerateUtils::calculate_start_index(base, scale_factor, part, part_count),
+            row_count: GenerateUtils::calculate_row_count(base, scale_factor, part, part_count),
+            // the random streams continue after the base orders
+            random_offset: OrderGenerator::calculate_row_count(scale_factor, 1, 1),
+            // as in dbgen, the key sequence changes every 1000 refresh sets
+            key_sequence: 1 + update_set as i64 / 1000,
+        }
+    }
+
+    /// Returns the orders of `part` of the base orders, of those inserted by
+    /// the (1 based) refresh set `update_set` if not 0, or of those deleted
+    /// by refresh set `delete_set` if not 0
+    fn for_sets(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+        update_set: i32,
+        delete_set: i32,
+    ) -> Self {
+        if delete_set == 0 {
+            return Self::new(scale_factor, part, part_count, update_set);
+        }
+        // the base orders with the indexes of the orders of the refresh set
+        Self {
+            random_offset: 0,
+            key_sequence: 0,
+            ..Self::new(scale_factor, part, part_count, delete_set)
+        }
+    }
+
+    /// Returns the orders of this range whose keys are in `order_keys`, or
+    /// all if `None`
+    fn with_order_keys(self, order_keys: Option<&RangeInclusive<i64>>) -> Self {
+        let Some(order_keys) = order_keys else {
+            return self;
+        };
+        // the keys increase with the index, so the orders in the range are
+        // those from the first index with a key of at least the start to
+        // the one before the first index with a key after the end
+        let first = self.first_index_from_key(*order_keys.start());
+        let last = self.first_index_from_key(order_keys.end().saturating_add(1)) - 1;
+        let start_index = self.start_index.max(first - 1);
+        let end_index = (self.start_index + self.row_count).min(last);
+        Self {
+            start_index,
+            row_count: (end_index - start_index).max(0),
+            ..self
+        }
+    }
+
+    /// Returns the first index whose order key is at least `order_key`, see
+    /// [`OrderGenerator::make_sparse_order_key`]
+    fn first_index_from_key(&self, order_key: i64) -> i64 {
+        let keep = OrderGenerator::ORDER_KEY_SPARSE_KEEP;
+        let bits = OrderGenerator::ORDER_KEY_SPARSE_BITS + keep;
+        let order_key = order_key.max(0);
+        let block = (order_key >> bits) << keep;
+        // the position of the key among the keys of its sequence in the block
+        let offset = (order_key & ((1 << bits) - 1)) - (self.key_sequence << keep);
+        if offset < 0 {
+            block
+        } else if offset >= 1 << keep {
+            block + (1 << keep)
+        } else {
+            block + offset
+        }
+    }
+}
+
+/// Returns true if the generated `order_date` is in `order_dates`, days since
+/// the Unix epoch, or if there are none
+fn in_order_dates(order_dates: Option<&RangeInclusive<i32>>, order_date: i32) -> bool {
+    order_dates.is_none_or(|dates| dates.contains(&TPCHDate::new(order_date).to_epoch_days()))
+}
+
 impl<'a> IntoIterator for OrderGenerator<'a> {
     type Item = Order<'a>;
     type IntoIter = OrderGeneratorIterator<'a>;
@@ -1600,9 +2788,9 @@ pub struct OrderGeneratorIterator<'a> {
     order_date_random: RandomBoundedInt,
     line_count_random: RandomBoundedInt,
     customer_key_random: RandomBoundedLong,
-    order_priority_random: RandomString<'a>,
-    clerk_random: RandomBoundedInt,
-    comment_random: RandomText<'a>,
+    order_priority_random: RandomEnum<'a, OrderPriority>,
+    clerk_random: ClerkNameGenerator,
+    comment_random: CommentGenerator<'a>,
 
     // For line item simulation to determine order status
     line_quantity_random: RandomBoundedInt,
@@ -1613,47 +2801,63 @@ pub struct OrderGeneratorIterator<'a> {
 
     start_index: i64,
     row_count: i64,
+    key_sequence: i64,
     max_customer_key: i64,
+    max_part_key: i64,
 
     index: i64,
+    filter: Option<OrderFilter>,
+    edge_cases: bool,
+    zipf: Option<Zipf>,
+    orders_per_day: Option<i64>,
+    order_dates: Option<RangeInclusive<i32>>,
 }
 impl<'a> OrderGeneratorIterator<'a> {
     fn new(
         distributions: &'a Distributions,
         text_pool: &'a TextPool,
+        seeds: ColumnSeeds,
         scale_factor: f64,
-        start_index: i64,
-        row_count: i64,
+        scales: TableScales,
+        orders: OrderRange,
     ) -> Self {
-        let mut order_date_random = OrderGenerator::create_order_date_random();
-        let mut line_count_random = OrderGenerator::create_line_count_random();
-
-        let max_customer_key = (CustomerGenerator::SCALE_BASE as f64 * scale_factor) as i64;
-
-        let mut customer_key_random =
-            RandomBoundedLong::new(851767375, scale_factor >= 30000.0, 1, max_customer_key);
+        let mut order_date_random =
+            OrderGenerator::create_order_date_random().with_seed(seeds.o_orderdate);
+        let mut line_count_random =
+            OrderGenerator::create_line_count_random().with_seed(seeds.o_line_count);
+
+        let customer_scale_factor = scale_factor * scales.customer;
+        let max_customer_key =
+            (CustomerGenerator::SCALE_BASE as f64 * customer_scale_factor) as i64;
+
+        let mut customer_key_random = RandomBoundedLong::new(
+            seeds.o_custkey,
+            customer_scale_factor >= 30000.0,
+            1,
+            max_customer_key,
+        );
 
         let mut order_priority_random =
-            RandomString::new(591449447, distributions.order_priority());
+            RandomEnum::new(seeds.o_orderpriority, distributions.order_priority());
 
-        let max_clerk = (scale_factor * OrderGenerator::CLERK_SCALE_BASE as f64)
-            .max(OrderGenerator::CLERK_SCALE_BASE as f64) as i32;
-        let mut clerk_random = RandomBoundedInt::new(1171034773, 1, max_clerk);
+        let mut clerk_random = ClerkNameGenerator::new(scale_factor, &seeds);
 
-        let mut comment_random = RandomText::new(
-            276090261,
-            text_pool,
-            OrderGenerator::COMMENT_AVERAGE_LENGTH as f64,
-        );
+        let mut comment_random = CommentGenerator::order(text_pool, &seeds);
 
         // For line item simulation
-        let mut line_quantity_random = LineItemGenerator::create_quantity_random();
-        let mut line_discount_random = LineItemGenerator::create_discount_random();
-        let mut line_tax_random = LineItemGenerator::create_tax_random();
-        let mut line_part_key_random = LineItemGenerator::create_part_key_random(scale_factor);
-        let mut line_ship_date_random = LineItemGenerator::create_ship_date_random();
+        let mut line_quantity_random =
+            LineItemGenerator::create_quantity_random().with_seed(seeds.l_quantity);
+        let mut line_discount_random =
+            LineItemGenerator::create_discount_random().with_seed(seeds.l_discount);
+        let mut line_tax_random = LineItemGenerator::create_tax_random().with_seed(seeds.l_tax);
+        let part_scale_factor = scale_factor * scales.part;
+        let mut line_part_key_random =
+            LineItemGenerator::create_part_key_random(part_scale_factor).with_seed(seeds.l_partkey);
+        let mut line_ship_date_random =
+            LineItemGenerator::create_ship_date_random().with_seed(seeds.l_shipdate);
 
         // Advance all generators to the starting position
+        let start_index = orders.start_index + orders.random_offset;
         order_date_random.advance_rows(start_index);
         line_count_random.advance_rows(start_index);
         customer_key_random.advance_rows(start_index);
@@ -1679,27 +2883,59 @@ impl<'a> OrderGeneratorIterator<'a> {
             line_tax_random,
             line_part_key_random,
             line_ship_date_random,
-            start_index,
-            row_count,
+            start_index: orders.start_index,
+            row_count: orders.row_count,
+            key_sequence: orders.key_sequence,
             max_customer_key,
+            max_part_key: (PartGenerator::SCALE_BASE as f64 * part_scale_factor) as i64,
             index: 0,
+            filter: None,
+            edge_cases: false,
+            zipf: None,
+            orders_per_day: None,
+            order_dates: None,
         }
     }
 
-    /// Creates an order with the given index
-    fn make_order(&mut self, index: i64) -> Order<'a> {
-        let order_key = OrderGenerator::make_order_key(index);
+    /// Returns the order date of the order with the given index
+    fn next_order_date(&mut self, index: i64) -> i32 {
+        let extreme = self.edge_cases.then(|| Extreme::for_row(index));
+
+        // the random order date is consumed either way, so the other columns
+        // do not depend on the dates
+        let mut order_date = self.order_date_random.next_value();
+        if let Some(orders_per_day) = self.orders_per_day {
+            order_date = OrderGenerator::increasing_order_date(index, orders_per_day);
+        }
+        edge_value(
+            extreme,
+            order_date,
+            OrderGenerator::ORDER_DATE_MIN,
+            OrderGenerator::ORDER_DATE_MAX,
+        )
+    }
 
-        let order_date = self.order_date_random.next_value();
+    /// Creates an order with the given index and order date
+    fn make_order(&mut self, index: i64, order_date: i32) -> Order<'a> {
+        let order_key = OrderGenerator::make_sparse_order_key(index, self.key_sequence);
+        let extreme = self.edge_cases.then(|| Extreme::for_row(index));
 
         // generate customer key, taking into account customer mortality rate
-        let mut customer_key = self.customer_key_random.next_value();
+        let mut customer_key = skewed_value(
+            self.zipf,
+            self.customer_key_random.next_value(),
+            1,
+            self.max_customer_key,
+        );
         let mut delta = 1;
         while customer_key % OrderGenerator::CUSTOMER_MORTALITY as i64 == 0 {
             customer_key += delta;
             customer_key = customer_key.min(self.max_customer_key);
             delta *= -1;
         }
+        if let Some(extreme) = extreme {
+            customer_key = OrderGenerator::edge_customer_key(extreme, self.max_customer_key);
+        }
 
         let mut total_price = 0;
         let mut shipped_count = 0;
@@ -1710,14 +2946,30 @@ impl<'a> OrderGeneratorIterator<'a> {
             let discount = self.line_discount_random.next_value();
             let tax = self.line_tax_random.next_value();
 
-            let part_key = self.line_part_key_random.next_value();
+            let part_key = skewed_value(
+                self.zipf,
+                self.line_part_key_random.next_value(),
+                LineItemGenerator::PART_KEY_MIN as i64,
+                self.max_part_key,
+            );
+            let part_key = edge_value(
+                extreme,
+                part_key,
+                LineItemGenerator::PART_KEY_MIN as i64,
+                self.max_part_key,
+            );
 
             let part_price = PartGeneratorIterator::calculate_part_price(part_key);
             let extended_price = part_price * quantity as i64;
             let discounted_price = extended_price * (100 - discount as i64);
             total_price += ((discounted_price / 100) * (100 + tax as i64)) / 100;
 
-            let ship_date = self.line_ship_date_random.next_value() + order_date;
+            let ship_date = edge_value(
+                extreme,
+                self.line_ship_date_random.next_value(),
+                LineItemGenerator::SHIP_DATE_MIN,
+                LineItemGenerator::SHIP_DATE_MAX,
+            ) + order_date;
             if TPCHDate::is_in_past(ship_date) {
                 shipped_count += 1;
             }
@@ -1731,9 +2983,6 @@ impl<'a> OrderGeneratorIterator<'a> {
             OrderStatus::Open
         };
 
-        let clerk_id = self.clerk_random.next_value();
-        let clerk_name = ClerkName::new(clerk_id);
-
         Order {
             o_orderkey: order_key,
             o_custkey: customer_key,
@@ -1741,23 +2990,62 @@ impl<'a> OrderGeneratorIterator<'a> {
             o_totalprice: TPCHDecimal(total_price),
             o_orderdate: TPCHDate::new(order_date),
             o_orderpriority: self.order_priority_random.next_value(),
-            o_clerk: clerk_name,
+            o_clerk: self.clerk_random.next_value(),
             o_shippriority: 0, // Fixed value per TPC-H spec
-            o_comment: self.comment_random.next_value(),
+            o_comment: self.comment_random.next_value_or_extreme(extreme),
         }
     }
-}
 
-impl<'a> Iterator for OrderGeneratorIterator<'a> {
-    type Item = Order<'a>;
+    /// Only return rows matching `filter`
+    fn with_filter(mut self, filter: Option<OrderFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.row_count {
-            return None;
-        }
+    /// Replace values with their edge cases if `edge_cases` is true
+    fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Skew the foreign keys with `zipf` if set
+    fn with_zipf(mut self, zipf: Option<Zipf>) -> Self {
+        self.zipf = zipf;
+        self
+    }
+
+    /// Increase the order dates with the order index if `orders_per_day` is
+    /// set
+    fn with_orders_per_day(mut self, orders_per_day: Option<i64>) -> Self {
+        self.orders_per_day = orders_per_day;
+        self
+    }
+
+    /// Skip the orders whose order dates are not in `order_dates` if set
+    fn with_order_dates(mut self, order_dates: Option<RangeInclusive<i32>>) -> Self {
+        self.order_dates = order_dates;
+        self
+    }
 
-        let order = self.make_order(self.start_index + self.index + 1);
+    /// Generates the next row in the order dates, ignoring the filter
+    fn next_row(&mut self) -> Option<Order<'a>> {
+        while self.index < self.row_count {
+            let index = self.start_index + self.index + 1;
+            let order_date = self.next_order_date(index);
+            // the other columns of the orders outside the order dates are
+            // not generated
+            let order = in_order_dates(self.order_dates.as_ref(), order_date)
+                .then(|| self.make_order(index, order_date));
+            self.row_finished();
+            if order.is_some() {
+                return order;
+            }
+        }
+        None
+    }
 
+    /// Moves the random streams to the next order
+    fn row_finished(&mut self) {
         self.order_date_random.row_finished();
         self.line_count_random.row_finished();
         self.customer_key_random.row_finished();
@@ -1772,8 +3060,20 @@ impl<'a> Iterator for OrderGeneratorIterator<'a> {
         self.line_ship_date_random.row_finished();
 
         self.index += 1;
+    }
+}
 
-        Some(order)
+impl<'a> Iterator for OrderGeneratorIterator<'a> {
+    type Item = Order<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = self.next_row()?;
+            match &self.filter {
+                Some(filter) if !(filter.0)(&row) => continue,
+                _ => return Some(row),
+            }
+        }
     }
 }
 
@@ -1806,10 +3106,10 @@ pub struct LineItem<'a> {
     pub l_discount: TPCHDecimal,
     /// Tax percentage
     pub l_tax: TPCHDecimal,
-    /// Return flag (R=returned, A=accepted, null=pending)
-    pub l_returnflag: &'a str,
+    /// Return flag (R=returned, A=accepted, N=not returned yet)
+    pub l_returnflag: ReturnFlag,
     /// Line status (O=ordered, F=fulfilled)
-    pub l_linestatus: &'static str,
+    pub l_linestatus: LineStatus,
     /// Date shipped
     pub l_shipdate: TPCHDate,
     /// Date committed to ship
@@ -1819,7 +3119,7 @@ pub struct LineItem<'a> {
     /// Shipping instructions
     pub l_shipinstruct: &'a str,
     /// Shipping mode
-    pub l_shipmode: &'a str,
+    pub l_shipmode: ShipMode,
     /// Variable length comment
     pub l_comment: &'a str,
 }
@@ -1857,6 +3157,17 @@ pub struct LineItemGenerator<'a> {
     part_count: i32,
     distributions: &'a Distributions,
     text_pool: &'a TextPool,
+    filter: Option<LineItemFilter>,
+    edge_cases: bool,
+    zipf: Option<Zipf>,
+    scales: TableScales,
+    update_set: i32,
+    delete_set: i32,
+    orders_per_day: Option<i64>,
+    order_keys: Option<RangeInclusive<i64>>,
+    order_dates: Option<RangeInclusive<i32>>,
+    seed_offset: i64,
+    column_seeds: ColumnSeeds,
 }
 
 impl<'a> LineItemGenerator<'a> {
@@ -1869,12 +3180,18 @@ impl<'a> LineItemGenerator<'a> {
     const DISCOUNT_MAX: TPCHDecimal = TPCHDecimal(10); // 0.10
     const PART_KEY_MIN: i32 = 1;
 
-    const SHIP_DATE_MIN: i32 = 1;
-    const SHIP_DATE_MAX: i32 = 121;
-    const COMMIT_DATE_MIN: i32 = 30;
-    const COMMIT_DATE_MAX: i32 = 90;
-    const RECEIPT_DATE_MIN: i32 = 1;
-    const RECEIPT_DATE_MAX: i32 = 30;
+    /// Smallest number of days from the order date to the ship date
+    pub const SHIP_DATE_MIN: i32 = 1;
+    /// Largest number of days from the order date to the ship date
+    pub const SHIP_DATE_MAX: i32 = 121;
+    /// Smallest number of days from the order date to the commit date
+    pub const COMMIT_DATE_MIN: i32 = 30;
+    /// Largest number of days from the order date to the commit date
+    pub const COMMIT_DATE_MAX: i32 = 90;
+    /// Smallest number of days from the ship date to the receipt date
+    pub const RECEIPT_DATE_MIN: i32 = 1;
+    /// Largest number of days from the ship date to the receipt date
+    pub const RECEIPT_DATE_MAX: i32 = 30;
 
     pub const ITEM_SHIP_DAYS: i32 = Self::SHIP_DATE_MAX + Self::RECEIPT_DATE_MAX;
 
@@ -1895,6 +3212,9 @@ impl<'a> LineItemGenerator<'a> {
     }
 
     /// Creates a LineItemGenerator with specified distributions and text pool
+    ///
+    /// Uses the `rflag`, `instruct` and `smode` distributions, see
+    /// [`Distributions`].
     pub fn new_with_distributions_and_text_pool<'b>(
         scale_factor: f64,
         part: i32,
@@ -1908,57 +3228,326 @@ impl<'a> LineItemGenerator<'a> {
             part_count,
             distributions,
             text_pool,
+            filter: None,
+            edge_cases: false,
+            zipf: None,
+            scales: TableScales::default(),
+            update_set: 0,
+            delete_set: 0,
+            orders_per_day: None,
+            order_keys: None,
+            order_dates: None,
+            seed_offset: 0,
+            column_seeds: ColumnSeeds::SPEC,
         }
     }
 
-    /// Returns an iterator over the line item rows
-    pub fn iter(&self) -> LineItemGeneratorIterator<'a> {
-        LineItemGeneratorIterator::new(
-            self.distributions,
-            self.text_pool,
-            self.scale_factor,
-            GenerateUtils::calculate_start_index(
-                OrderGenerator::SCALE_BASE,
-                self.scale_factor,
-                self.part,
-                self.part_count,
-            ),
-            GenerateUtils::calculate_row_count(
-                OrderGenerator::SCALE_BASE,
-                self.scale_factor,
-                self.part,
-                self.part_count,
-            ),
-        )
+    /// Returns the exact number of line item rows for the given scale factor
+    /// and generator part count
+    ///
+    /// This is the [`Self::row_count`] of the line items of the
+    /// specification, i.e. a [`Self::with_seed_offset`] of 0 and the
+    /// [`ColumnSeeds::SPEC`] seeds, and takes time proportional to the
+    /// number of orders in the part.
+    ///
+    /// ```
+    /// # use tpchgen::generators::LineItemGenerator;
+    /// assert_eq!(LineItemGenerator::exact_row_count(1.0, 1, 1), 6_001_215);
+    /// ```
+    pub fn exact_row_count(scale_factor: f64, part: i32, part_count: i32) -> i64 {
+        Self::new(scale_factor, part, part_count).row_count()
     }
 
-    /// Creates a quantity random generator
-    pub fn create_quantity_random() -> RandomBoundedInt {
-        RandomBoundedInt::new_with_seeds_per_row(
-            209208115,
-            Self::QUANTITY_MIN,
-            Self::QUANTITY_MAX,
-            OrderGenerator::LINE_COUNT_MAX,
+    /// Returns the exact number of line item rows this generator generates,
+    /// ignoring any [`Self::with_filter`] filter and [`Self::with_order_dates`]
+    ///
+    /// Orders have between 1 and 7 line items, so unlike the other tables the
+    /// count is not a simple function of the scale factor. This replays only
+    /// the line count random stream of the orders in the part, from the seeds
+    /// of the generator, skipping directly to the first one. It is much
+    /// faster than generating the rows, but still takes time proportional to
+    /// the number of orders: about a second for every 10 million orders, i.e.
+    /// scale factor 7, on one core.
+    ///
+    /// ```
+    /// # use tpchgen::generators::LineItemGenerator;
+    /// let generator = LineItemGenerator::new(0.01, 2, 3).with_seed_offset(5);
+    /// assert_eq!(generator.row_count(), generator.iter().count() as i64);
+    /// ```
+    pub fn row_count(&self) -> i64 {
+        let orders = OrderRange::for_sets(
+            self.scale_factor * self.scales.orders,
+            self.part,
+            self.part_count,
+            self.update_set,
+            self.delete_set,
         )
+        .with_order_keys(self.order_keys.as_ref());
+        let seeds = self.column_seeds.with_offset(self.seed_offset);
+        let mut line_count_random =
+            OrderGenerator::create_line_count_random().with_seed(seeds.o_line_count);
+        line_count_random.advance_rows(orders.start_index + orders.random_offset);
+        (0..orders.row_count)
+            .map(|_| {
+                let line_count = line_count_random.next_value();
+                line_count_random.row_finished();
+                line_count as i64
+            })
+            .sum()
     }
 
-    /// Creates a discount random generator
-    pub fn create_discount_random() -> RandomBoundedInt {
-        RandomBoundedInt::new_with_seeds_per_row(
-            554590007,
-            Self::DISCOUNT_MIN.0 as i32,
-            Self::DISCOUNT_MAX.0 as i32,
-            OrderGenerator::LINE_COUNT_MAX,
-        )
+    /// Only generate the line item rows for which `filter` returns true
+    ///
+    /// Rows that do not match are skipped during generation, so no
+    /// post-processing pass is needed to produce a filtered subset. The
+    /// filter does not change the values of the rows that are generated.
+    ///
+    /// The other generators have the same method, e.g.
+    /// [`OrderGenerator::with_filter`].
+    ///
+    /// ```
+    /// # use tpchgen::dates::TPCHDate;
+    /// # use tpchgen::generators::LineItemGenerator;
+    /// // only line items shipped in 1995
+    /// let start = TPCHDate::from_epoch_days(9131).unwrap(); // 1995-01-01
+    /// let end = TPCHDate::from_epoch_days(9496).unwrap(); // 1996-01-01
+    /// let generator = LineItemGenerator::new(0.01, 1, 1)
+    ///     .with_filter(move |l| l.l_shipdate >= start && l.l_shipdate < end);
+    /// assert!(generator
+    ///     .iter()
+    ///     .all(|l| l.l_shipdate.to_string().starts_with("1995")));
+    /// ```
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&LineItem<'_>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = Some(RowFilter(Arc::new(filter)));
+        self
     }
 
-    /// Creates a tax random generator
-    pub fn create_tax_random() -> RandomBoundedInt {
-        RandomBoundedInt::new_with_seeds_per_row(
-            721958466,
-            Self::TAX_MIN.0 as i32,
-            Self::TAX_MAX.0 as i32,
-            OrderGenerator::LINE_COUNT_MAX,
+    /// Generate edge case values instead of random ones
+    ///
+    /// Edge case data is intended for testing the limits of the programs that
+    /// read the generated files, while still matching the TPC-H schema. Rows
+    /// alternate between the largest (odd keys) and smallest (even keys)
+    /// values allowed by the specification for:
+    ///
+    /// * the length of comments and addresses
+    /// * account balances, part sizes, available quantities and supply costs
+    /// * order, ship, commit and receipt dates
+    /// * the customer and part keys referenced by orders and line items
+    ///
+    /// Dependent values, such as `l_extendedprice`, `l_linestatus` and
+    /// `o_orderstatus`, are computed from the edge case values so the tables
+    /// remain consistent with each other when all generators use edge cases.
+    /// Like the random values, the edge cases do not depend on how the table
+    /// is split into parts.
+    ///
+    /// The other generators have the same method, e.g.
+    /// [`OrderGenerator::with_edge_cases`].
+    pub fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Select the parts and suppliers of the line items from a Zipf
+    /// distribution instead of uniformly, or uniformly if `zipf` is `None`
+    ///
+    /// Skewed foreign keys stress the handling of join skew: with a Zipf
+    /// exponent of 1, the part with key 1 is in about 6% of the line items at
+    /// scale factor 1. The supplier is still one of the four suppliers of the
+    /// part in PARTSUPP, the first of them the most frequent. The skew only
+    /// changes which keys are selected, so the values of the other columns
+    /// are the same as without skew, except for the prices that depend on
+    /// the part.
+    ///
+    /// [`OrderGenerator::with_zipf`] skews the customers of the orders, and
+    /// must be given the same distribution for `o_totalprice` to match the
+    /// line items.
+    ///
+    /// ```
+    /// # use tpchgen::generators::LineItemGenerator;
+    /// # use tpchgen::random::Zipf;
+    /// let generator = LineItemGenerator::new(0.01, 1, 1).with_zipf(Some(Zipf::new(1.0)));
+    /// let hot = generator.iter().filter(|l| l.l_partkey == 1).count();
+    /// assert!(hot > 4000, "{hot} line items of part 1");
+    /// ```
+    pub fn with_zipf(mut self, zipf: Option<Zipf>) -> Self {
+        self.zipf = zipf;
+        self
+    }
+
+    /// Generate the line items of the orders at the scale factor times
+    /// `scales.orders`, for the parts and suppliers scaled by `scales`, see
+    /// [`TableScales`]
+    ///
+    /// [`OrderGenerator::with_table_scales`] must be given the same
+    /// multipliers for the tables to match.
+    pub fn with_table_scales(mut self, scales: TableScales) -> Self {
+        self.scales = scales;
+        self
+    }
+
+    /// Generate the line items of the orders inserted by refresh function
+    /// RF1 of the (1 based) refresh set `update_set` instead of the base line
+    /// items, or the base line items if `update_set` is 0, see
+    /// [`OrderGenerator::with_update_set`]
+    pub fn with_update_set(mut self, update_set: i32) -> Self {
+        self.update_set = update_set;
+        self
+    }
+
+    /// Generate the line items of the base orders deleted by refresh
+    /// function RF2 of the (1 based) refresh set `delete_set` instead of all
+    /// base line items, or all if `delete_set` is 0, see
+    /// [`OrderGenerator::with_delete_set`]
+    pub fn with_delete_set(mut self, delete_set: i32) -> Self {
+        self.delete_set = delete_set;
+        self
+    }
+
+    /// Give the line items the dates of orders with increasing order dates,
+    /// see [`OrderGenerator::with_orders_per_day`]
+    pub fn with_orders_per_day(mut self, orders_per_day: Option<i64>) -> Self {
+        self.orders_per_day = orders_per_day;
+        self
+    }
+
+    /// Only generate the line items of the orders whose keys are in
+    /// `order_keys`, or of all orders if `None` (the default), see
+    /// [`OrderGenerator::with_order_keys`]
+    pub fn with_order_keys(mut self, order_keys: Option<RangeInclusive<i64>>) -> Self {
+        self.order_keys = order_keys;
+        self
+    }
+
+    /// Only generate the line items of the orders whose order dates, as days
+    /// since the Unix epoch (1970-01-01), are in `order_dates`, or of all
+    /// orders if `None` (the default), see [`OrderGenerator::with_order_dates`]
+    ///
+    /// The ship, commit and receipt dates of the line items are between
+    /// [`Self::SHIP_DATE_MIN`], [`Self::COMMIT_DATE_MIN`] and
+    /// [`Self::RECEIPT_DATE_MIN`] days and the corresponding maximums after
+    /// the order date (the receipt date after the ship date), so a range of
+    /// these dates selects a range of order dates.
+    pub fn with_order_dates(mut self, order_dates: Option<RangeInclusive<i32>>) -> Self {
+        self.order_dates = order_dates;
+        self
+    }
+
+    /// Generate the line items of a dataset other than that of the
+    /// specification, whose values differ but have the same distributions,
+    /// or those of the specification if `seed_offset` is 0 (the default)
+    ///
+    /// The seed of the random values of each column is moved along its
+    /// random stream by the same amount for each step of `seed_offset`, so
+    /// every offset generates a distinct dataset, e.g. one per tenant. The
+    /// number of line items of the orders changes too, unlike the keys of
+    /// the other tables. Like [`Self::with_zipf`], the generators of all
+    /// tables must be given the same offset for the orders and line items to
+    /// match.
+    ///
+    /// ```
+    /// # use tpchgen::generators::LineItemGenerator;
+    /// let part_keys = |seed_offset| -> Vec<i64> {
+    ///     LineItemGenerator::new(0.01, 1, 1)
+    ///         .with_seed_offset(seed_offset)
+    ///         .iter()
+    ///         .map(|l| l.l_partkey)
+    ///         .take(5)
+    ///         .collect()
+    /// };
+    /// assert_eq!(part_keys(0), vec![1552, 674, 637, 22, 241]);
+    /// assert_ne!(part_keys(1), part_keys(0));
+    /// assert_eq!(part_keys(1), part_keys(1));
+    /// ```
+    pub fn with_seed_offset(mut self, seed_offset: i64) -> Self {
+        self.seed_offset = seed_offset;
+        self
+    }
+
+    /// Generate the columns of the line items from `column_seeds` instead of
+    /// the seeds of the specification, [`ColumnSeeds::SPEC`]
+    ///
+    /// Changing the seed of one column regenerates its values with other
+    /// randomness and leaves the other columns unchanged, except for the
+    /// columns computed from it, e.g. `l_extendedprice` from `l_quantity` or
+    /// the number of line items of the orders from
+    /// [`ColumnSeeds::o_line_count`]. A [`Self::with_seed_offset`] applies
+    /// to these seeds. Like [`Self::with_zipf`], the generators of all
+    /// tables must be given the same seeds for the orders and line items to
+    /// match.
+    ///
+    /// ```
+    /// # use tpchgen::generators::LineItemGenerator;
+    /// # use tpchgen::random::ColumnSeeds;
+    /// let seeds = ColumnSeeds {
+    ///     l_shipmode: 42,
+    ///     ..ColumnSeeds::SPEC
+    /// };
+    /// let spec = LineItemGenerator::new(0.01, 1, 1);
+    /// let other = LineItemGenerator::new(0.01, 1, 1).with_column_seeds(seeds);
+    /// let (spec, other) = (spec.iter().next().unwrap(), other.iter().next().unwrap());
+    /// assert_eq!(other.l_partkey, spec.l_partkey);
+    /// assert_eq!(other.l_comment, spec.l_comment);
+    /// assert_ne!(other.l_shipmode, spec.l_shipmode);
+    /// ```
+    pub fn with_column_seeds(mut self, column_seeds: ColumnSeeds) -> Self {
+        self.column_seeds = column_seeds;
+        self
+    }
+
+    /// Returns an iterator over the line item rows
+    pub fn iter(&self) -> LineItemGeneratorIterator<'a> {
+        LineItemGeneratorIterator::new(
+            self.distributions,
+            self.text_pool,
+            self.column_seeds.with_offset(self.seed_offset),
+            self.scale_factor,
+            self.scales,
+            OrderRange::for_sets(
+                self.scale_factor * self.scales.orders,
+                self.part,
+                self.part_count,
+                self.update_set,
+                self.delete_set,
+            )
+            .with_order_keys(self.order_keys.as_ref()),
+        )
+        .with_filter(self.filter.clone())
+        .with_edge_cases(self.edge_cases)
+        .with_zipf(self.zipf)
+        .with_orders_per_day(self.orders_per_day)
+        .with_order_dates(self.order_dates.clone())
+    }
+
+    /// Creates a quantity random generator
+    pub fn create_quantity_random() -> RandomBoundedInt {
+        RandomBoundedInt::new_with_seeds_per_row(
+            ColumnSeeds::SPEC.l_quantity,
+            Self::QUANTITY_MIN,
+            Self::QUANTITY_MAX,
+            OrderGenerator::LINE_COUNT_MAX,
+        )
+    }
+
+    /// Creates a discount random generator
+    pub fn create_discount_random() -> RandomBoundedInt {
+        RandomBoundedInt::new_with_seeds_per_row(
+            ColumnSeeds::SPEC.l_discount,
+            Self::DISCOUNT_MIN.0 as i32,
+            Self::DISCOUNT_MAX.0 as i32,
+            OrderGenerator::LINE_COUNT_MAX,
+        )
+    }
+
+    /// Creates a tax random generator
+    pub fn create_tax_random() -> RandomBoundedInt {
+        RandomBoundedInt::new_with_seeds_per_row(
+            ColumnSeeds::SPEC.l_tax,
+            Self::TAX_MIN.0 as i32,
+            Self::TAX_MAX.0 as i32,
+            OrderGenerator::LINE_COUNT_MAX,
         )
     }
 
@@ -1967,7 +3556,7 @@ impl<'a> LineItemGenerator<'a> {
         // If scale_factor >= 30000, use long `RandomBoundedLong` otherwise
         // use `RandomBoundedInt` to avoid overflow.
         RandomBoundedLong::new_with_seeds_per_row(
-            1808217256,
+            ColumnSeeds::SPEC.l_partkey,
             scale_factor >= 30000.0,
             Self::PART_KEY_MIN as i64,
             (PartGenerator::SCALE_BASE as f64 * scale_factor) as i64,
@@ -1978,7 +3567,7 @@ impl<'a> LineItemGenerator<'a> {
     /// Creates a ship date random generator
     pub fn create_ship_date_random() -> RandomBoundedInt {
         RandomBoundedInt::new_with_seeds_per_row(
-            1769349045,
+            ColumnSeeds::SPEC.l_shipdate,
             Self::SHIP_DATE_MIN,
             Self::SHIP_DATE_MAX,
             OrderGenerator::LINE_COUNT_MAX,
@@ -2013,83 +3602,101 @@ pub struct LineItemGeneratorIterator<'a> {
     commit_date_random: RandomBoundedInt,
     receipt_date_random: RandomBoundedInt,
 
-    returned_flag_random: RandomString<'a>,
+    returned_flag_random: RandomEnum<'a, ReturnFlag>,
     ship_instructions_random: RandomString<'a>,
-    ship_mode_random: RandomString<'a>,
+    ship_mode_random: RandomEnum<'a, ShipMode>,
 
     comment_random: RandomText<'a>,
 
-    scale_factor: f64,
+    max_part_key: i64,
+    supplier_count: i64,
+    suppliers_per_part: i32,
     start_index: i64,
     row_count: i64,
+    key_sequence: i64,
 
     index: i64,
     order_date: i32,
     line_count: i32,
     line_number: i32,
+    filter: Option<LineItemFilter>,
+    edge_cases: bool,
+    zipf: Option<Zipf>,
+    orders_per_day: Option<i64>,
+    order_dates: Option<RangeInclusive<i32>>,
 }
 
 impl<'a> LineItemGeneratorIterator<'a> {
     fn new(
         distributions: &'a Distributions,
         text_pool: &'a TextPool,
+        seeds: ColumnSeeds,
         scale_factor: f64,
-        start_index: i64,
-        row_count: i64,
+        scales: TableScales,
+        orders: OrderRange,
     ) -> Self {
-        let mut order_date_random = OrderGenerator::create_order_date_random();
-        let mut line_count_random = OrderGenerator::create_line_count_random();
-
-        let mut quantity_random = LineItemGenerator::create_quantity_random();
-        let mut discount_random = LineItemGenerator::create_discount_random();
-        let mut tax_random = LineItemGenerator::create_tax_random();
-
-        let mut line_part_key_random = LineItemGenerator::create_part_key_random(scale_factor);
-
+        let mut order_date_random =
+            OrderGenerator::create_order_date_random().with_seed(seeds.o_orderdate);
+        let mut line_count_random =
+            OrderGenerator::create_line_count_random().with_seed(seeds.o_line_count);
+
+        let mut quantity_random =
+            LineItemGenerator::create_quantity_random().with_seed(seeds.l_quantity);
+        let mut discount_random =
+            LineItemGenerator::create_discount_random().with_seed(seeds.l_discount);
+        let mut tax_random = LineItemGenerator::create_tax_random().with_seed(seeds.l_tax);
+
+        let part_scale_factor = scale_factor * scales.part;
+        let mut line_part_key_random =
+            LineItemGenerator::create_part_key_random(part_scale_factor).with_seed(seeds.l_partkey);
+
+        let suppliers_per_part = scales.suppliers_per_part();
         let mut supplier_number_random = RandomBoundedInt::new_with_seeds_per_row(
-            2095021727,
+            seeds.l_suppkey,
             0,
-            3,
+            suppliers_per_part - 1,
             OrderGenerator::LINE_COUNT_MAX,
         );
 
-        let mut ship_date_random = LineItemGenerator::create_ship_date_random();
+        let mut ship_date_random =
+            LineItemGenerator::create_ship_date_random().with_seed(seeds.l_shipdate);
         let mut commit_date_random = RandomBoundedInt::new_with_seeds_per_row(
-            904914315,
+            seeds.l_commitdate,
             LineItemGenerator::COMMIT_DATE_MIN,
             LineItemGenerator::COMMIT_DATE_MAX,
             OrderGenerator::LINE_COUNT_MAX,
         );
         let mut receipt_date_random = RandomBoundedInt::new_with_seeds_per_row(
-            373135028,
+            seeds.l_receiptdate,
             LineItemGenerator::RECEIPT_DATE_MIN,
             LineItemGenerator::RECEIPT_DATE_MAX,
             OrderGenerator::LINE_COUNT_MAX,
         );
 
-        let mut returned_flag_random = RandomString::new_with_expected_row_count(
-            717419739,
+        let mut returned_flag_random = RandomEnum::new_with_expected_row_count(
+            seeds.l_returnflag,
             distributions.return_flags(),
             OrderGenerator::LINE_COUNT_MAX,
         );
         let mut ship_instructions_random = RandomString::new_with_expected_row_count(
-            1371272478,
+            seeds.l_shipinstruct,
             distributions.ship_instructions(),
             OrderGenerator::LINE_COUNT_MAX,
         );
-        let mut ship_mode_random = RandomString::new_with_expected_row_count(
-            675466456,
+        let mut ship_mode_random = RandomEnum::new_with_expected_row_count(
+            seeds.l_shipmode,
             distributions.ship_modes(),
             OrderGenerator::LINE_COUNT_MAX,
         );
         let mut comment_random = RandomText::new_with_expected_row_count(
-            1095462486,
+            seeds.l_comment,
             text_pool,
             LineItemGenerator::COMMENT_AVERAGE_LENGTH as f64,
             OrderGenerator::LINE_COUNT_MAX,
         );
 
         // Advance all generators to the starting position
+        let start_index = orders.start_index + orders.random_offset;
         order_date_random.advance_rows(start_index);
         line_count_random.advance_rows(start_index);
 
@@ -2130,58 +3737,115 @@ impl<'a> LineItemGeneratorIterator<'a> {
             ship_instructions_random,
             ship_mode_random,
             comment_random,
-            scale_factor,
-            start_index,
-            row_count,
+            max_part_key: (PartGenerator::SCALE_BASE as f64 * part_scale_factor) as i64,
+            supplier_count: scales.supplier_count(scale_factor),
+            suppliers_per_part,
+            start_index: orders.start_index,
+            row_count: orders.row_count,
+            key_sequence: orders.key_sequence,
             index: 0,
             order_date,
             line_count,
             line_number: 0,
+            filter: None,
+            edge_cases: false,
+            zipf: None,
+            orders_per_day: None,
+            order_dates: None,
         }
     }
 
+    /// Returns the order date of the current order, with the given index
+    fn order_date(&self, order_index: i64) -> i32 {
+        // use the same extreme as the order so the tables stay consistent
+        let extreme = self.edge_cases.then(|| Extreme::for_row(order_index));
+        let order_date = match self.orders_per_day {
+            Some(orders_per_day) => {
+                OrderGenerator::increasing_order_date(order_index, orders_per_day)
+            }
+            None => self.order_date,
+        };
+        edge_value(
+            extreme,
+            order_date,
+            OrderGenerator::ORDER_DATE_MIN,
+            OrderGenerator::ORDER_DATE_MAX,
+        )
+    }
+
     /// Creates a line item with the given order index
     fn make_line_item(&mut self, order_index: i64) -> LineItem<'a> {
-        let order_key = OrderGenerator::make_order_key(order_index);
+        let order_key = OrderGenerator::make_sparse_order_key(order_index, self.key_sequence);
+        let extreme = self.edge_cases.then(|| Extreme::for_row(order_index));
+        let order_date = self.order_date(order_index);
 
         let quantity = self.quantity_random.next_value();
         let discount = self.discount_random.next_value();
         let tax = self.tax_random.next_value();
 
-        let part_key = self.line_part_key_random.next_value();
+        let part_key = skewed_value(
+            self.zipf,
+            self.line_part_key_random.next_value(),
+            LineItemGenerator::PART_KEY_MIN as i64,
+            self.max_part_key,
+        );
+        let part_key = edge_value(
+            extreme,
+            part_key,
+            LineItemGenerator::PART_KEY_MIN as i64,
+            self.max_part_key,
+        );
 
-        let supplier_number = self.supplier_number_random.next_value() as i64;
-        let supplier_key = PartSuppGeneratorIterator::select_part_supplier(
+        let supplier_number = skewed_value(
+            self.zipf,
+            self.supplier_number_random.next_value() as i64,
+            0,
+            self.suppliers_per_part as i64 - 1,
+        );
+        let supplier_key = PartSuppGeneratorIterator::select_supplier(
             part_key,
             supplier_number,
-            self.scale_factor,
+            self.supplier_count,
+            self.suppliers_per_part,
         );
 
         let part_price = PartGeneratorIterator::calculate_part_price(part_key);
         let extended_price = part_price * quantity as i64;
 
-        let mut ship_date = self.ship_date_random.next_value();
-        ship_date += self.order_date;
-        let mut commit_date = self.commit_date_random.next_value();
-        commit_date += self.order_date;
-        let mut receipt_date = self.receipt_date_random.next_value();
-        receipt_date += ship_date;
+        let ship_date = edge_value(
+            extreme,
+            self.ship_date_random.next_value(),
+            LineItemGenerator::SHIP_DATE_MIN,
+            LineItemGenerator::SHIP_DATE_MAX,
+        ) + order_date;
+        let commit_date = edge_value(
+            extreme,
+            self.commit_date_random.next_value(),
+            LineItemGenerator::COMMIT_DATE_MIN,
+            LineItemGenerator::COMMIT_DATE_MAX,
+        ) + order_date;
+        let receipt_date = edge_value(
+            extreme,
+            self.receipt_date_random.next_value(),
+            LineItemGenerator::RECEIPT_DATE_MIN,
+            LineItemGenerator::RECEIPT_DATE_MAX,
+        ) + ship_date;
 
         let returned_flag = if TPCHDate::is_in_past(receipt_date) {
             self.returned_flag_random.next_value()
         } else {
-            "N"
+            ReturnFlag::NotReturned
         };
 
         let status = if TPCHDate::is_in_past(ship_date) {
-            "F" // Fulfilled
+            LineStatus::Fulfilled
         } else {
-            "O" // Open
+            LineStatus::Open
         };
 
         let ship_instructions = self.ship_instructions_random.next_value();
         let ship_mode = self.ship_mode_random.next_value();
-        let comment = self.comment_random.next_value();
+        let comment = self.comment_random.next_value_or_extreme(extreme);
 
         LineItem {
             l_orderkey: order_key,
@@ -2202,12 +3866,51 @@ impl<'a> LineItemGeneratorIterator<'a> {
             l_comment: comment,
         }
     }
-}
 
-impl<'a> Iterator for LineItemGeneratorIterator<'a> {
-    type Item = LineItem<'a>;
+    /// Only return rows matching `filter`
+    fn with_filter(mut self, filter: Option<LineItemFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Replace values with their edge cases if `edge_cases` is true
+    fn with_edge_cases(mut self, edge_cases: bool) -> Self {
+        self.edge_cases = edge_cases;
+        self
+    }
+
+    /// Skew the foreign keys with `zipf` if set
+    fn with_zipf(mut self, zipf: Option<Zipf>) -> Self {
+        self.zipf = zipf;
+        self
+    }
+
+    /// Increase the order dates with the order index if `orders_per_day` is
+    /// set
+    fn with_orders_per_day(mut self, orders_per_day: Option<i64>) -> Self {
+        self.orders_per_day = orders_per_day;
+        self
+    }
+
+    /// Skip the orders whose order dates are not in `order_dates` if set
+    fn with_order_dates(mut self, order_dates: Option<RangeInclusive<i32>>) -> Self {
+        self.order_dates = order_dates;
+        self
+    }
+
+    /// Generates the next row in the order dates, ignoring the filter
+    fn next_row(&mut self) -> Option<LineItem<'a>> {
+        // the line items of the orders outside the order dates are not
+        // generated
+        while self.index < self.row_count
+            && self.line_number == 0
+            && !in_order_dates(
+                self.order_dates.as_ref(),
+                self.order_date(self.start_index + self.index + 1),
+            )
+        {
+            self.order_finished();
+        }
         if self.index >= self.row_count {
             return None;
         }
@@ -2217,41 +3920,397 @@ impl<'a> Iterator for LineItemGeneratorIterator<'a> {
 
         // advance next row only when all lines for the order have been produced
         if self.line_number > self.line_count {
-            self.order_date_random.row_finished();
-            self.line_count_random.row_finished();
+            self.order_finished();
+        }
 
-            self.quantity_random.row_finished();
-            self.discount_random.row_finished();
-            self.tax_random.row_finished();
+        Some(line_item)
+    }
 
-            self.line_part_key_random.row_finished();
-            self.supplier_number_random.row_finished();
+    /// Moves the random streams to the next order and generates its order
+    /// date and number of line items
+    fn order_finished(&mut self) {
+        self.order_date_random.row_finished();
+        self.line_count_random.row_finished();
 
-            self.ship_date_random.row_finished();
-            self.commit_date_random.row_finished();
-            self.receipt_date_random.row_finished();
+        self.quantity_random.row_finished();
+        self.discount_random.row_finished();
+        self.tax_random.row_finished();
 
-            self.returned_flag_random.row_finished();
-            self.ship_instructions_random.row_finished();
-            self.ship_mode_random.row_finished();
+        self.line_part_key_random.row_finished();
+        self.supplier_number_random.row_finished();
 
-            self.comment_random.row_finished();
+        self.ship_date_random.row_finished();
+        self.commit_date_random.row_finished();
+        self.receipt_date_random.row_finished();
 
-            self.index += 1;
+        self.returned_flag_random.row_finished();
+        self.ship_instructions_random.row_finished();
+        self.ship_mode_random.row_finished();
+
+        self.comment_random.row_finished();
+
+        self.index += 1;
+
+        // generate information for next order
+        self.line_count = self.line_count_random.next_value() - 1;
+        self.order_date = self.order_date_random.next_value();
+        self.line_number = 0;
+    }
+}
 
-            // generate information for next order
-            self.line_count = self.line_count_random.next_value() - 1;
-            self.order_date = self.order_date_random.next_value();
-            self.line_number = 0;
+impl<'a> Iterator for LineItemGeneratorIterator<'a> {
+    type Item = LineItem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let row = self.next_row()?;
+            match &self.filter {
+                Some(filter) if !(filter.0)(&row) => continue,
+                _ => return Some(row),
+            }
         }
+    }
+}
 
-        Some(line_item)
+/// Generator for the keys of the orders deleted by refresh function RF2
+///
+/// Refresh set `n` deletes the `n`th [`OrderGenerator::UPDATE_SCALE_BASE`]
+/// base orders per unit of scale factor, with their line items, as `dbgen -U`
+/// does. The rows inserted by refresh function RF1 are generated by
+/// [`OrderGenerator::with_update_set`] and
+/// [`LineItemGenerator::with_update_set`]. Like the other generators, the
+/// keys are split into parts, so large refresh sets can be generated in
+/// parallel.
+///
+/// ```
+/// # use tpchgen::generators::DeleteKeyGenerator;
+/// let keys: Vec<_> = DeleteKeyGenerator::new(1.0, 1, 1)
+///     .with_update_set(1)
+///     .iter()
+///     .take(9)
+///     .collect();
+/// assert_eq!(keys, vec![1, 2, 3, 4, 5, 6, 7, 32, 33]);
+/// assert_eq!(DeleteKeyGenerator::calculate_row_count(1.0, 1, 1), 1500);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeleteKeyGenerator {
+    scale_factor: f64,
+    part: i32,
+    part_count: i32,
+    update_set: i32,
+}
+
+impl DeleteKeyGenerator {
+    /// Creates a new DeleteKeyGenerator for the first refresh set with the
+    /// given scale factor
+    pub fn new(scale_factor: f64, part: i32, part_count: i32) -> Self {
+        DeleteKeyGenerator {
+            scale_factor,
+            part,
+            part_count,
+            update_set: 1,
+        }
+    }
+
+    /// Return the number of orders deleted by each refresh set for the given
+    /// scale factor and generator part count
+    pub fn calculate_row_count(scale_factor: f64, part: i32, part_count: i32) -> i64 {
+        GenerateUtils::calculate_row_count(
+            OrderGenerator::UPDATE_SCALE_BASE,
+            scale_factor,
+            part,
+            part_count,
+        )
+    }
+
+    /// Generate the keys deleted by the (1 based) refresh set `update_set`
+    /// (default: 1)
+    pub fn with_update_set(mut self, update_set: i32) -> Self {
+        self.update_set = update_set;
+        self
+    }
+
+    /// Returns an iterator over the deleted order keys
+    pub fn iter(&self) -> DeleteKeyGeneratorIterator {
+        let orders = OrderRange::new(
+            self.scale_factor,
+            self.part,
+            self.part_count,
+            self.update_set.max(1),
+        );
+        DeleteKeyGeneratorIterator {
+            index: orders.start_index,
+            end_index: orders.start_index + orders.row_count,
+        }
+    }
+}
+
+impl IntoIterator for DeleteKeyGenerator {
+    type Item = i64;
+    type IntoIter = DeleteKeyGeneratorIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator that generates the keys of deleted orders
+#[derive(Debug)]
+pub struct DeleteKeyGeneratorIterator {
+    index: i64,
+    end_index: i64,
+}
+
+impl Iterator for DeleteKeyGeneratorIterator {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.end_index {
+            return None;
+        }
+        self.index += 1;
+        // the deleted orders are base orders
+        Some(OrderGenerator::make_order_key(self.index))
+    }
+}
+
+/// An order with its line items, generated by a [`LiveOrderGenerator`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveOrder<'a> {
+    /// The order
+    pub order: Order<'a>,
+    /// The line items of the order, by line number
+    pub line_items: Vec<LineItem<'a>>,
+}
+
+impl LiveOrder<'_> {
+    /// Returns the number of rows of the order and its line items
+    pub fn row_count(&self) -> usize {
+        1 + self.line_items.len()
+    }
+}
+
+/// Generator of the orders, with their line items, that continue the ORDERS
+/// and LINEITEM tables beyond the scale factor, e.g. for streaming ingestion
+/// benchmarks
+///
+/// The orders are those inserted by refresh function RF1 of refresh sets 1,
+/// 2, 3 and so on, see [`OrderGenerator::with_update_set`], so their keys do
+/// not collide with the keys of the base orders. Their order dates increase
+/// from the day after the last order date of the base orders, see
+/// [`OrderGenerator::with_orders_per_day`], by default by one day for as
+/// many orders as the base orders have per day. The generator ends after
+/// [`Self::MAX_UPDATE_SETS`] refresh sets, about 3 times as many orders as
+/// the base orders, after which the keys would collide.
+///
+/// ```
+/// # use tpchgen::generators::LiveOrderGenerator;
+/// let mut orders = LiveOrderGenerator::new(1.0).iter();
+/// let first = orders.next().unwrap();
+/// assert_eq!(first.order.o_orderkey, 9);
+/// assert_eq!(first.order.o_orderdate.to_string(), "1998-08-03");
+/// assert!(first.line_items.iter().all(|l| l.l_orderkey == 9));
+/// let later = orders.nth(10_000).unwrap();
+/// assert!(later.order.o_orderdate > first.order.o_orderdate);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LiveOrderGenerator<'a> {
+    scale_factor: f64,
+    distributions: &'a Distributions,
+    text_pool: &'a TextPool,
+    orders_per_day: i64,
+}
+
+impl<'a> LiveOrderGenerator<'a> {
+    /// The number of refresh sets whose orders are generated, the last one
+    /// whose keys do not collide with those of the base orders
+    pub const MAX_UPDATE_SETS: i32 = 2999;
+
+    /// Creates a new LiveOrderGenerator that continues the orders of the
+    /// given scale factor
+    ///
+    /// Note the generator's lifetime is `&'static`. See [`NationGenerator`] for
+    /// more details.
+    pub fn new(scale_factor: f64) -> LiveOrderGenerator<'static> {
+        Self::new_with_distributions_and_text_pool(
+            scale_factor,
+            Distributions::static_default(),
+            TextPool::get_or_init_default(),
+        )
+    }
+
+    /// Creates a LiveOrderGenerator with specified distributions and text
+    /// pool
+    pub fn new_with_distributions_and_text_pool<'b>(
+        scale_factor: f64,
+        distributions: &'b Distributions,
+        text_pool: &'b TextPool,
+    ) -> LiveOrderGenerator<'b> {
+        let days = (OrderGenerator::ORDER_DATE_MAX - OrderGenerator::ORDER_DATE_MIN + 1) as i64;
+        let base_orders = OrderGenerator::calculate_row_count(scale_factor, 1, 1);
+        LiveOrderGenerator {
+            scale_factor,
+            distributions,
+            text_pool,
+            orders_per_day: (base_orders + days - 1) / days,
+        }
+    }
+
+    /// Increase the order dates by one day for every `orders_per_day`
+    /// orders instead of as many as the base orders have per day
+    ///
+    /// By default the dates end before 2020. With fewer orders per day they
+    /// can go past `9999-12-31`, e.g. with one order per day at scale factor
+    /// 1, and those dates can not be parsed back into a [`TPCHDate`].
+    pub fn with_orders_per_day(mut self, orders_per_day: i64) -> Self {
+        self.orders_per_day = orders_per_day.max(1);
+        self
+    }
+
+    /// Returns an iterator over the orders with their line items
+    pub fn iter(&self) -> LiveOrderGeneratorIterator<'a> {
+        LiveOrderGeneratorIterator {
+            generator: self.clone(),
+            update_set: 1,
+            orders: self.orders(1),
+            line_items: self.line_items(1).peekable(),
+        }
+    }
+
+    /// Returns the orders of the (1 based) refresh set `update_set`
+    fn orders(&self, update_set: i32) -> OrderGeneratorIterator<'a> {
+        OrderGenerator::new_with_distributions_and_text_pool(
+            self.scale_factor,
+            1,
+            1,
+            self.distributions,
+            self.text_pool,
+        )
+        .with_update_set(update_set)
+        .with_orders_per_day(Some(self.orders_per_day))
+        .iter()
+    }
+
+    /// Returns the line items of the (1 based) refresh set `update_set`
+    fn line_items(&self, update_set: i32) -> LineItemGeneratorIterator<'a> {
+        LineItemGenerator::new_with_distributions_and_text_pool(
+            self.scale_factor,
+            1,
+            1,
+            self.distributions,
+            self.text_pool,
+        )
+        .with_update_set(update_set)
+        .with_orders_per_day(Some(self.orders_per_day))
+        .iter()
+    }
+}
+
+impl<'a> IntoIterator for LiveOrderGenerator<'a> {
+    type Item = LiveOrder<'a>;
+    type IntoIter = LiveOrderGeneratorIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator that generates the orders of a [`LiveOrderGenerator`]
+#[derive(Debug)]
+pub struct LiveOrderGeneratorIterator<'a> {
+    generator: LiveOrderGenerator<'a>,
+    update_set: i32,
+    orders: OrderGeneratorIterator<'a>,
+    line_items: std::iter::Peekable<LineItemGeneratorIterator<'a>>,
+}
+
+impl<'a> Iterator for LiveOrderGeneratorIterator<'a> {
+    type Item = LiveOrder<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(order) = self.orders.next() {
+                let mut line_items = Vec::new();
+                while let Some(line_item) = self
+                    .line_items
+                    .next_if(|l| l.l_orderkey == order.o_orderkey)
+                {
+                    line_items.push(line_item);
+                }
+                return Some(LiveOrder { order, line_items });
+            }
+            if self.update_set >= LiveOrderGenerator::MAX_UPDATE_SETS {
+                return None;
+            }
+            self.update_set += 1;
+            self.orders = self.generator.orders(self.update_set);
+            self.line_items = self.generator.line_items(self.update_set).peekable();
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_column_enums() {
+        for mode in ShipMode::ALL {
+            assert_eq!(mode.as_str().parse::<ShipMode>(), Ok(*mode));
+        }
+        assert_eq!(ShipMode::RegularAir.to_string(), "REG AIR");
+        assert_eq!(OrderPriority::NotSpecified.to_string(), "4-NOT SPECIFIED");
+        assert_eq!("5-LOW".parse(), Ok(OrderPriority::Low));
+        assert_eq!(
+            "X".parse::<ReturnFlag>().unwrap_err().to_string(),
+            "invalid value 'X' for l_returnflag"
+        );
+
+        // the generators produce every value
+        let distributions = Distributions::static_default();
+        assert_eq!(distributions.ship_modes().size(), ShipMode::ALL.len());
+        assert_eq!(
+            distributions.order_priority().size(),
+            OrderPriority::ALL.len()
+        );
+        let items: Vec<_> = LineItemGenerator::new(0.01, 1, 1).iter().collect();
+        for flag in ReturnFlag::ALL {
+            assert!(items.iter().any(|l| l.l_returnflag == *flag));
+        }
+    }
+
+    #[test]
+    fn test_lineitem_exact_row_count() {
+        for part_count in [1, 3, 7] {
+            let mut total = 0;
+            for part in 1..=part_count {
+                let expected = LineItemGenerator::new(0.01, part, part_count)
+                    .iter()
+                    .count();
+                let count = LineItemGenerator::exact_row_count(0.01, part, part_count);
+                assert_eq!(count, expected as i64);
+                total += count;
+            }
+            assert_eq!(total, 60_175);
+        }
+
+        // other datasets have other numbers of line items per order
+        for (part, seed_offset) in [(1, 1), (2, 7), (3, -3)] {
+            let line_items = LineItemGenerator::new(0.01, part, 3).with_seed_offset(seed_offset);
+            let count = line_items.row_count();
+            assert_eq!(count, line_items.iter().count() as i64);
+            assert_ne!(count, LineItemGenerator::exact_row_count(0.01, part, 3));
+        }
+        let line_items = LineItemGenerator::new(0.01, 2, 4)
+            .with_update_set(3)
+            .with_order_keys(Some(70_000..=600_200))
+            .with_table_scales(TableScales {
+                orders: 2.0,
+                ..TableScales::default()
+            });
+        assert_eq!(line_items.row_count(), line_items.iter().count() as i64);
+    }
 
     #[test]
     fn test_nation_generator() {
@@ -2454,6 +4513,49 @@ mod tests {
         assert_eq!(OrderGenerator::make_order_key(10), 32 + 2); // 10 becomes 1000010
     }
 
+    #[test]
+    fn test_update_set_generation() {
+        let base: HashSet<_> = OrderGenerator::new(0.01, 1, 1)
+            .iter()
+            .map(|o| o.o_orderkey)
+            .collect();
+        let orders: Vec<_> = (1..=3)
+            .flat_map(|set| OrderGenerator::new(0.01, 1, 1).with_update_set(set).iter())
+            .collect();
+        // 0.1% of the orders per set, with keys unused by the base orders
+        assert_eq!(orders.len(), 3 * 15);
+        let keys: HashSet<_> = orders.iter().map(|o| o.o_orderkey).collect();
+        assert_eq!(keys.len(), orders.len());
+        assert!(keys.is_disjoint(&base));
+
+        // the parts of a set are its rows, and its line items match its orders
+        let parts: Vec<_> = (1..=4)
+            .flat_map(|part| OrderGenerator::new(0.01, part, 4).with_update_set(2).iter())
+            .map(|o| o.to_string())
+            .collect();
+        let set: Vec<_> = orders[15..30].iter().map(|o| o.to_string()).collect();
+        assert_eq!(parts, set);
+        let line_items: Vec<_> = LineItemGenerator::new(0.01, 1, 1)
+            .with_update_set(2)
+            .iter()
+            .collect();
+        assert_eq!(line_items[0].l_orderkey, orders[15].o_orderkey);
+        assert_eq!(line_items.last().unwrap().l_orderkey, orders[29].o_orderkey);
+    }
+
+    #[test]
+    fn test_delete_key_generation() {
+        let base: Vec<_> = OrderGenerator::new(0.01, 1, 1)
+            .iter()
+            .map(|o| o.o_orderkey)
+            .collect();
+        // refresh set 2 deletes the second 15 orders, in parts
+        let keys: Vec<_> = (1..=4)
+            .flat_map(|part| DeleteKeyGenerator::new(0.01, part, 4).with_update_set(2))
+            .collect();
+        assert_eq!(keys, base[15..30]);
+    }
+
     #[test]
     fn test_line_item_generation() {
         // Create a generator with a small scale factor
@@ -2508,6 +4610,552 @@ mod tests {
         assert!(!line_statuses.is_empty());
     }
 
+    #[test]
+    fn test_line_item_filter() {
+        let start = TPCHDate::from_epoch_days(9131).unwrap(); // 1995-01-01
+        let end = TPCHDate::from_epoch_days(9496).unwrap(); // 1996-01-01
+        let in_1995 = move |l: &LineItem<'_>| l.l_shipdate >= start && l.l_shipdate < end;
+
+        let expected: Vec<_> = LineItemGenerator::new(0.01, 1, 1)
+            .iter()
+            .filter(in_1995)
+            .map(|l| l.to_string())
+            .collect();
+        let actual: Vec<_> = LineItemGenerator::new(0.01, 1, 1)
+            .with_filter(in_1995)
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        assert!(!actual.is_empty());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_filter_keeps_part_boundaries() {
+        // filtering each part matches filtering the whole table
+        let even = |o: &Order<'_>| o.o_custkey % 2 == 0;
+        let expected: Vec<_> = OrderGenerator::new(0.01, 1, 1)
+            .iter()
+            .filter(even)
+            .map(|o| o.o_orderkey)
+            .collect();
+        let actual: Vec<_> = (1..=3)
+            .flat_map(|part| OrderGenerator::new(0.01, part, 3).with_filter(even).iter())
+            .map(|o| o.o_orderkey)
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_customer_edge_cases() {
+        let customers: Vec<_> = CustomerGenerator::new(0.01, 1, 1)
+            .with_edge_cases(true)
+            .iter()
+            .take(2)
+            .collect();
+        // odd keys use the largest values
+        assert_eq!(customers[0].c_custkey, 1);
+        assert_eq!(customers[0].c_address.to_string().len(), 40);
+        assert_eq!(customers[0].c_acctbal.to_string(), "9999.99");
+        assert_eq!(customers[0].c_comment.len(), 116);
+        // even keys use the smallest values
+        assert_eq!(customers[1].c_address.to_string().len(), 10);
+        assert_eq!(customers[1].c_acctbal.to_string(), "-999.99");
+        assert_eq!(customers[1].c_comment.len(), 29);
+    }
+
+    #[test]
+    fn test_order_line_item_edge_cases_consistent() {
+        let orders: Vec<_> = OrderGenerator::new(0.01, 1, 1)
+            .with_edge_cases(true)
+            .iter()
+            .take(100)
+            .collect();
+        let line_items: Vec<_> = LineItemGenerator::new(0.01, 1, 1)
+            .with_edge_cases(true)
+            .iter()
+            .take_while(|l| l.l_orderkey <= orders.last().unwrap().o_orderkey)
+            .collect();
+
+        for order in &orders {
+            let lines: Vec<_> = line_items
+                .iter()
+                .filter(|l| l.l_orderkey == order.o_orderkey)
+                .collect();
+            // the order total and status are computed from its line items
+            let total_price: i64 = lines
+                .iter()
+                .map(|l| {
+                    let discounted = l.l_extendedprice.0 * (100 - l.l_discount.0);
+                    ((discounted / 100) * (100 + l.l_tax.0)) / 100
+                })
+                .sum();
+            assert_eq!(order.o_totalprice.0, total_price);
+            let status = if lines
+                .iter()
+                .all(|l| l.l_linestatus == LineStatus::Fulfilled)
+            {
+                OrderStatus::Fulfilled
+            } else {
+                assert!(lines.iter().all(|l| l.l_linestatus == LineStatus::Open));
+                OrderStatus::Open
+            };
+            assert_eq!(order.o_orderstatus, status);
+            for line in lines {
+                assert!(line.l_shipdate > order.o_orderdate);
+                assert!(line.l_receiptdate > line.l_shipdate);
+                assert!(line.l_receiptdate.to_string().as_str() <= "1998-12-31");
+            }
+        }
+        assert_eq!(orders[0].o_orderdate.to_string(), "1998-08-02");
+        assert_eq!(orders[1].o_orderdate.to_string(), "1992-01-01");
+    }
+
+    #[test]
+    fn test_edge_cases_independent_of_parts() {
+        let expected: Vec<_> = PartSuppGenerator::new(0.01, 1, 1)
+            .with_edge_cases(true)
+            .iter()
+            .map(|ps| ps.to_string())
+            .collect();
+        let actual: Vec<_> = (1..=3)
+            .flat_map(|part| {
+                PartSuppGenerator::new(0.01, part, 3)
+                    .with_edge_cases(true)
+                    .iter()
+            })
+            .map(|ps| ps.to_string())
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_zipf_skews_foreign_keys() {
+        let zipf = Some(Zipf::new(1.0));
+        let orders: Vec<_> = OrderGenerator::new(0.01, 1, 1)
+            .with_zipf(zipf)
+            .iter()
+            .take(1000)
+            .collect();
+        let line_items: Vec<_> = LineItemGenerator::new(0.01, 1, 1)
+            .with_zipf(zipf)
+            .iter()
+            .take_while(|l| l.l_orderkey <= orders.last().unwrap().o_orderkey)
+            .collect();
+
+        // the order totals are computed from the skewed parts
+        for order in &orders {
+            let total_price: i64 = line_items
+                .iter()
+                .filter(|l| l.l_orderkey == order.o_orderkey)
+                .map(|l| {
+                    let discounted = l.l_extendedprice.0 * (100 - l.l_discount.0);
+                    ((discounted / 100) * (100 + l.l_tax.0)) / 100
+                })
+                .sum();
+            assert_eq!(order.o_totalprice.0, total_price);
+            assert_ne!(order.o_custkey % 3, 0);
+        }
+        let hot_customers = orders.iter().filter(|o| o.o_custkey <= 2).count();
+        assert!(
+            hot_customers > 100,
+            "{hot_customers} orders of customer 1 or 2"
+        );
+
+        // the suppliers are still suppliers of the part, the first the most
+        // frequent
+        let first_suppliers = line_items
+            .iter()
+            .filter(|l| {
+                let first = PartSuppGeneratorIterator::select_part_supplier(l.l_partkey, 0, 0.01);
+                l.l_suppkey == first
+            })
+            .count();
+        assert!(first_suppliers > line_items.len() / 3);
+
+        // other columns are not skewed
+        let uniform: Vec<_> = LineItemGenerator::new(0.01, 1, 1)
+            .iter()
+            .take(line_items.len())
+            .collect();
+        for (skewed, uniform) in line_items.iter().zip(&uniform) {
+            assert_eq!(skewed.l_shipdate, uniform.l_shipdate);
+            assert_eq!(skewed.l_comment, uniform.l_comment);
+        }
+        let expected = LineItemGenerator::new(0.01, 1, 1).with_zipf(zipf);
+        let actual =
+            (1..=3).flat_map(|part| LineItemGenerator::new(0.01, part, 3).with_zipf(zipf).iter());
+        assert!(actual.eq(expected.iter()));
+        assert!(LineItemGenerator::new(0.01, 1, 1)
+            .with_zipf(Some(Zipf::new(0.0)))
+            .iter()
+            .eq(LineItemGenerator::new(0.01, 1, 1).iter()));
+    }
+
+    #[test]
+    fn test_table_scales_keep_tables_consistent() {
+        use std::collections::HashSet;
+
+        let scales = TableScales {
+            part: 0.5,
+            supplier: 2.0,
+            partsupp: 0.5,
+            customer: 3.0,
+            orders: 0.2,
+        };
+        assert_eq!(scales.suppliers_per_part(), 2);
+        let parts = PartGenerator::new(0.01, 1, 1).with_table_scales(scales);
+        assert_eq!(parts.iter().count(), 1000);
+        let suppliers = SupplierGenerator::new(0.01, 1, 1).with_table_scales(scales);
+        assert_eq!(suppliers.iter().count(), 200);
+        let customers = CustomerGenerator::new(0.01, 1, 1).with_table_scales(scales);
+        assert_eq!(customers.iter().count(), 4500);
+
+        let part_suppliers: HashSet<_> = PartSuppGenerator::new(0.01, 1, 1)
+            .with_table_scales(scales)
+            .iter()
+            .map(|ps| (ps.ps_partkey, ps.ps_suppkey))
+            .collect();
+        assert_eq!(part_suppliers.len(), 2000);
+        assert!(part_suppliers.iter().all(|&(p, s)| p <= 1000 && s <= 200));
+
+        let orders: Vec<_> = OrderGenerator::new(0.01, 1, 1)
+            .with_table_scales(scales)
+            .iter()
+            .collect();
+        assert_eq!(orders.len(), 3000);
+        assert!(orders.iter().all(|o| o.o_custkey <= 4500));
+        assert!(orders.iter().any(|o| o.o_custkey > 1500));
+        let line_items: Vec<_> = LineItemGenerator::new(0.01, 1, 1)
+            .with_table_scales(scales)
+            .iter()
+            .collect();
+        assert_eq!(
+            line_items.last().unwrap().l_orderkey,
+            orders.last().unwrap().o_orderkey
+        );
+        assert!(line_items
+            .iter()
+            .all(|l| part_suppliers.contains(&(l.l_partkey, l.l_suppkey))));
+        // the order totals are computed from the scaled parts
+        let total_price: i64 = line_items
+            .iter()
+            .filter(|l| l.l_orderkey == orders[0].o_orderkey)
+            .map(|l| {
+                let discounted = l.l_extendedprice.0 * (100 - l.l_discount.0);
+                ((discounted / 100) * (100 + l.l_tax.0)) / 100
+            })
+            .sum();
+        assert_eq!(orders[0].o_totalprice.0, total_price);
+
+        let actual = (1..=3).flat_map(|part| {
+            PartSuppGenerator::new(0.01, part, 3)
+                .with_table_scales(scales)
+                .iter()
+        });
+        assert!(actual.eq(PartSuppGenerator::new(0.01, 1, 1)
+            .with_table_scales(scales)
+            .iter()));
+    }
+
+    #[test]
+    fn test_seed_offset_generates_distinct_consistent_datasets() {
+        use std::collections::HashSet;
+
+        let customers = |seed_offset| -> Vec<_> {
+            CustomerGenerator::new(0.01, 1, 1)
+                .with_seed_offset(seed_offset)
+                .iter()
+                .collect()
+        };
+        let spec = customers(0);
+        assert_eq!(
+            spec,
+            CustomerGenerator::new(0.01, 1, 1)
+                .iter()
+                .collect::<Vec<_>>()
+        );
+        let other = customers(1);
+        assert_eq!(other.len(), 1500);
+        assert!(other.iter().zip(&spec).all(|(c, s)| {
+            c.c_custkey == s.c_custkey && c.c_name == s.c_name && c.c_phone != s.c_phone
+        }));
+        assert_eq!(other, customers(1));
+        assert_ne!(other, customers(2));
+
+        let regions = RegionGenerator::new(0.01, 1, 1);
+        assert!(regions
+            .iter()
+            .zip(regions.clone().with_seed_offset(-5).iter())
+            .all(|(r, o)| r.r_name == o.r_name && r.r_comment != o.r_comment));
+
+        let part_suppliers: HashSet<_> = PartSuppGenerator::new(0.01, 1, 1)
+            .iter()
+            .map(|ps| (ps.ps_partkey, ps.ps_suppkey))
+            .collect();
+        let orders: Vec<_> = OrderGenerator::new(0.01, 1, 1)
+            .with_seed_offset(7)
+            .iter()
+            .collect();
+        let line_items: Vec<_> = LineItemGenerator::new(0.01, 1, 1)
+            .with_seed_offset(7)
+            .iter()
+            .collect();
+        assert_ne!(
+            line_items.len() as i64,
+            LineItemGenerator::exact_row_count(0.01, 1, 1)
+        );
+        assert!(line_items
+            .iter()
+            .all(|l| part_suppliers.contains(&(l.l_partkey, l.l_suppkey))));
+        for order in &orders[..100] {
+            let items: Vec<_> = line_items
+                .iter()
+                .filter(|l| l.l_orderkey == order.o_orderkey)
+                .collect();
+            assert!(!items.is_empty());
+            assert!(items.iter().all(|l| l.l_shipdate > order.o_orderdate));
+            let total_price: i64 = items
+                .iter()
+                .map(|l| {
+                    let discounted = l.l_extendedprice.0 * (100 - l.l_discount.0);
+                    ((discounted / 100) * (100 + l.l_tax.0)) / 100
+                })
+                .sum();
+            assert_eq!(order.o_totalprice.0, total_price);
+        }
+
+        let actual = (1..=3).flat_map(|part| {
+            LineItemGenerator::new(0.01, part, 3)
+                .with_seed_offset(7)
+                .iter()
+        });
+        assert!(actual.eq(line_items.into_iter()));
+    }
+
+    #[test]
+    fn test_extended_text() {
+        let distributions = Distributions::static_default();
+        let text_pool =
+            TextPool::new_extended(1024 * 1024, distributions, TextPool::EXTENDED_WORDS);
+        let spec: Vec<_> = SupplierGenerator::new(0.1, 1, 1).iter().collect();
+        let suppliers: Vec<_> = SupplierGenerator::new_with_distributions_and_text_pool(
+            0.1,
+            1,
+            1,
+            distributions,
+            &text_pool,
+        )
+        .iter()
+        .collect();
+        assert_eq!(suppliers.len(), spec.len());
+        for (supplier, spec) in suppliers.iter().zip(&spec) {
+            assert_eq!(supplier.s_phone, spec.s_phone);
+            assert!(supplier.s_comment.len() <= 101);
+            let address = supplier.s_address.to_string();
+            assert!(!address.is_ascii());
+            assert_eq!(
+                address.chars().count(),
+                spec.s_address.to_string().chars().count()
+            );
+            assert_eq!(
+                supplier.s_comment.contains("Customer "),
+                spec.s_comment.contains("Customer ")
+            );
+        }
+        assert!(suppliers.iter().any(|s| !s.s_comment.is_ascii()));
+
+        // deterministic
+        let again = TextPool::new_extended(1024 * 1024, distributions, TextPool::EXTENDED_WORDS);
+        assert_eq!(again.as_bytes(), text_pool.as_bytes());
+    }
+
+    #[test]
+    fn test_column_seeds_regenerate_only_their_columns() {
+        let seeds = ColumnSeeds {
+            l_comment: 42,
+            p_size: 43,
+            ..ColumnSeeds::SPEC
+        };
+        let spec = LineItemGenerator::new(0.01, 1, 1);
+        let other = LineItemGenerator::new(0.01, 1, 1).with_column_seeds(seeds);
+        let mut changed = 0;
+        for (s, o) in spec.iter().zip(other.iter()) {
+            assert_eq!(
+                (s.l_orderkey, s.l_partkey, s.l_quantity, s.l_shipmode),
+                (o.l_orderkey, o.l_partkey, o.l_quantity, o.l_shipmode)
+            );
+            changed += (s.l_comment != o.l_comment) as usize;
+        }
+        assert!(changed > 50000, "{changed} comments changed");
+
+        let spec = PartGenerator::new(0.01, 1, 1);
+        let other = PartGenerator::new(0.01, 1, 1).with_column_seeds(seeds);
+        assert!(spec
+            .iter()
+            .zip(other.iter())
+            .all(|(s, o)| s.p_name == o.p_name && s.p_comment == o.p_comment));
+        assert!(spec
+            .iter()
+            .zip(other.iter())
+            .any(|(s, o)| s.p_size != o.p_size));
+
+        // the same seeds with an offset are another dataset
+        let offset = LineItemGenerator::new(0.01, 1, 1)
+            .with_column_seeds(seeds)
+            .with_seed_offset(1);
+        let other = LineItemGenerator::new(0.01, 1, 1).with_column_seeds(seeds);
+        assert!(!offset.iter().take(10).eq(other.iter().take(10)));
+    }
+
+    #[test]
+    fn test_large_scale_factor_keys() {
+        // at SF 20000 the part and customer keys exceed i32::MAX but are
+        // still drawn from 32-bit random values, at SF 300000 from 64-bit ones
+        for scale_factor in [20_000.0, 300_000.0] {
+            let max_part_key = (PartGenerator::SCALE_BASE as f64 * scale_factor) as i64;
+            let max_supplier_key = (SupplierGenerator::SCALE_BASE as f64 * scale_factor) as i64;
+            let max_customer_key = (CustomerGenerator::SCALE_BASE as f64 * scale_factor) as i64;
+            let part_count = 1_000_000;
+            for part in [1, part_count] {
+                let orders: Vec<_> = OrderGenerator::new(scale_factor, part, part_count)
+                    .iter()
+                    .take(100)
+                    .collect();
+                assert!(orders
+                    .iter()
+                    .all(|o| (1..=max_customer_key).contains(&o.o_custkey)));
+                assert!(orders.iter().any(|o| o.o_custkey > i32::MAX as i64));
+
+                let line_items: Vec<_> = LineItemGenerator::new(scale_factor, part, part_count)
+                    .iter()
+                    .take(100)
+                    .collect();
+                assert!(line_items.iter().all(|l| {
+                    (1..=max_part_key).contains(&l.l_partkey)
+                        && (1..=max_supplier_key).contains(&l.l_suppkey)
+                }));
+                assert!(line_items.iter().any(|l| l.l_partkey > i32::MAX as i64));
+            }
+        }
+
+        let order_count = OrderGenerator::calculate_row_count(300_000.0, 1, 1);
+        assert_eq!(order_count, 450_000_000_000);
+        let last = OrderGenerator::new(300_000.0, 100_000_000, 100_000_000)
+            .iter()
+            .last()
+            .unwrap();
+        assert_eq!(last.o_orderkey, OrderGenerator::make_order_key(order_count));
+    }
+
+    #[test]
+    fn test_order_keys() {
+        // ranges inside a part, across parts and outside all parts, of the
+        // base orders and of a refresh set with other key sequences
+        for (update_set, keys) in [
+            (0, 100..=4000),
+            (0, 29_000..=31_000),
+            (0, -5..=7),
+            (0, 70_000..=80_000),
+            (3, 9..=600_200),
+            (1001, 0..=i64::MAX),
+        ] {
+            for part in 1..=4 {
+                let orders = OrderGenerator::new(0.01, part, 4).with_update_set(update_set);
+                let expected: Vec<_> = orders
+                    .iter()
+                    .filter(|o| keys.contains(&o.o_orderkey))
+                    .collect();
+                let pruned = orders.with_order_keys(Some(keys.clone()));
+                assert_eq!(pruned.iter().collect::<Vec<_>>(), expected);
+
+                let line_items = LineItemGenerator::new(0.01, part, 4).with_update_set(update_set);
+                let expected: Vec<_> = line_items
+                    .iter()
+                    .filter(|l| keys.contains(&l.l_orderkey))
+                    .collect();
+                let pruned = line_items.with_order_keys(Some(keys.clone()));
+                assert_eq!(pruned.iter().collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_order_dates() {
+        let epoch_days = |year, month, day| TPCHDate::ymd_to_epoch_days(year, month, day).unwrap();
+        // ranges inside, across the end of and outside the order dates, with
+        // random, edge case and increasing order dates
+        for (edge_cases, orders_per_day, dates) in [
+            (
+                false,
+                None,
+                epoch_days(1995, 3, 1)..=epoch_days(1995, 3, 31),
+            ),
+            (false, None, epoch_days(1998, 7, 1)..=epoch_days(2000, 1, 1)),
+            (false, None, 0..=epoch_days(1991, 12, 31)),
+            (true, None, epoch_days(1992, 1, 1)..=epoch_days(1992, 1, 1)),
+            (
+                false,
+                Some(10),
+                epoch_days(1998, 8, 10)..=epoch_days(1998, 8, 20),
+            ),
+        ] {
+            for part in 1..=4 {
+                let orders = OrderGenerator::new(0.01, part, 4)
+                    .with_edge_cases(edge_cases)
+                    .with_orders_per_day(orders_per_day);
+                let expected: Vec<_> = orders
+                    .iter()
+                    .filter(|o| dates.contains(&o.o_orderdate.to_epoch_days()))
+                    .collect();
+                let pruned = orders.with_order_dates(Some(dates.clone()));
+                assert_eq!(pruned.iter().collect::<Vec<_>>(), expected);
+
+                let order_keys: HashSet<_> = expected.iter().map(|o| o.o_orderkey).collect();
+                let line_items = LineItemGenerator::new(0.01, part, 4)
+                    .with_edge_cases(edge_cases)
+                    .with_orders_per_day(orders_per_day);
+                let expected: Vec<_> = line_items
+                    .iter()
+                    .filter(|l| order_keys.contains(&l.l_orderkey))
+                    .collect();
+                let pruned = line_items.with_order_dates(Some(dates.clone()));
+                assert_eq!(pruned.iter().collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_primary_keys() {
+        // compares the rows as text, as they would be written
+        fn rows<T: Display>(rows: impl Iterator<Item = T>) -> Vec<String> {
+            rows.map(|row| row.to_string()).collect()
+        }
+        for keys in [100..=400, 490..=510, -5..=7, 7_000..=8_000] {
+            for part in 1..=4 {
+                let parts = PartGenerator::new(0.01, part, 4);
+                let expected = rows(parts.iter().filter(|p| keys.contains(&p.p_partkey)));
+                let pruned = parts.with_part_keys(Some(keys.clone()));
+                assert_eq!(rows(pruned.iter()), expected);
+
+                let part_supps = PartSuppGenerator::new(0.01, part, 4);
+                let expected = rows(part_supps.iter().filter(|p| keys.contains(&p.ps_partkey)));
+                let pruned = part_supps.with_part_keys(Some(keys.clone()));
+                assert_eq!(rows(pruned.iter()), expected);
+
+                let suppliers = SupplierGenerator::new(0.01, part, 4);
+                let expected = rows(suppliers.iter().filter(|s| keys.contains(&s.s_suppkey)));
+                let pruned = suppliers.with_supplier_keys(Some(keys.clone()));
+                assert_eq!(rows(pruned.iter()), expected);
+
+                let customers = CustomerGenerator::new(0.01, part, 4);
+                let expected = rows(customers.iter().filter(|c| keys.contains(&c.c_custkey)));
+                let pruned = customers.with_customer_keys(Some(keys.clone()));
+                assert_eq!(rows(pruned.iter()), expected);
+            }
+        }
+    }
+
     #[test]
     fn check_iter_static_lifetimes() {
         // Lifetimes of iterators should be independent of the generator that
@@ -2521,5 +5169,94 @@ mod tests {
         let _iter: PartSuppGeneratorIterator<'static> = PartSuppGenerator::new(0.1, 1, 1).iter();
         let _iter: OrderGeneratorIterator<'static> = OrderGenerator::new(0.1, 1, 1).iter();
         let _iter: LineItemGeneratorIterator<'static> = LineItemGenerator::new(0.1, 1, 1).iter();
+        let _iter: LiveOrderGeneratorIterator<'static> = LiveOrderGenerator::new(0.1).iter();
+    }
+
+    #[test]
+    fn test_live_orders() {
+        let base_keys: HashSet<_> = OrderGenerator::new(0.01, 1, 1)
+            .iter()
+            .map(|o| o.o_orderkey)
+            .collect();
+        // the first 100 refresh sets, 15 orders each
+        let live: Vec<_> = LiveOrderGenerator::new(0.01)
+            .with_orders_per_day(3)
+            .iter()
+            .take(1500)
+            .collect();
+        let keys: HashSet<_> = live.iter().map(|l| l.order.o_orderkey).collect();
+        assert_eq!(keys.len(), live.len());
+        assert!(keys.is_disjoint(&base_keys));
+        assert!(live
+            .windows(2)
+            .all(|w| w[0].order.o_orderdate <= w[1].order.o_orderdate));
+        assert_eq!(live[1499].order.o_orderdate.to_string(), "1999-12-15");
+
+        // the same rows as the refresh sets with increasing dates
+        let orders = (1..=100).flat_map(|set| {
+            OrderGenerator::new(0.01, 1, 1)
+                .with_update_set(set)
+                .with_orders_per_day(Some(3))
+                .iter()
+        });
+        let line_items = (1..=100).flat_map(|set| {
+            LineItemGenerator::new(0.01, 1, 1)
+                .with_update_set(set)
+                .with_orders_per_day(Some(3))
+                .iter()
+        });
+        assert!(live
+            .iter()
+            .map(|l| &l.order)
+            .eq(orders.collect::<Vec<_>>().iter()));
+        assert!(live
+            .iter()
+            .flat_map(|l| &l.line_items)
+            .eq(line_items.collect::<Vec<_>>().iter()));
+
+        // the new orders are after the current date, so none has shipped
+        for l in &live {
+            assert_eq!(l.order.o_orderstatus, OrderStatus::Open);
+            assert!(l
+                .line_items
+                .iter()
+                .all(|l| l.l_linestatus == LineStatus::Open
+                    && l.l_returnflag == ReturnFlag::NotReturned));
+        }
+    }
+
+    #[test]
+    fn test_live_order_dates() {
+        // more orders than the base orders, whose dates they follow
+        let base = OrderGenerator::new(0.01, 1, 1);
+        let last_base_date = base.iter().map(|o| o.o_orderdate).max().unwrap();
+        let live: Vec<_> = LiveOrderGenerator::new(0.01)
+            .iter()
+            .map(|l| l.order.o_orderdate)
+            .take(2 * 15_000)
+            .collect();
+        assert_eq!(live[0].to_string(), "1998-08-03");
+        assert!(live[0] > last_base_date);
+        assert!(live.windows(2).all(|w| w[0] <= w[1]));
+        // 7 orders per day, as the base orders have
+        assert_eq!(live[7 * 1000].to_string(), "2001-04-29");
+        assert_eq!(live.last().unwrap().to_string(), "2010-04-27");
+    }
+
+    #[test]
+    fn test_live_order_dates_round_trip() {
+        // the dates of the last refresh set are after those of the
+        // specification, and can be parsed back
+        let last = LiveOrderGenerator::new(0.01).iter().last().unwrap();
+        assert_eq!(last.order.o_orderdate.to_string(), "2016-03-07");
+        let dates = std::iter::once(last.order.o_orderdate).chain(
+            last.line_items
+                .iter()
+                .flat_map(|l| [l.l_shipdate, l.l_commitdate, l.l_receiptdate]),
+        );
+        for date in dates {
+            assert_eq!(date.to_string().parse(), Ok(date));
+            assert_eq!(TPCHDate::from_epoch_days(date.to_epoch_days()), Some(date));
+        }
     }
 }
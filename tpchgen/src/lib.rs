@@ -40,6 +40,26 @@
 //! - TBL: The `Display` impl of the row structs produces the TPCH TBL format.
 //! - CSV: the [`csv`] module has formatters for CSV output (e.g. [`LineItemCsv`]).
 //!
+//! Lines in either format can be parsed back into the row structs with the
+//! functions in the [`parse`] module.
+//!
+//! [`reader::TblReader`] and [`reader::CsvReader`] implement [`std::io::Read`]
+//! over a generator, for APIs that take a reader.
+//!
+//! [`nulls::NullInjection`] writes a percentage of the comments and dates as
+//! NULLs, for testing how they are handled.
+//!
+//! The data of the refresh functions, the orders and line items inserted by
+//! RF1 and the keys of the orders deleted by RF2, is generated by
+//! [`OrderGenerator::with_update_set`], [`LineItemGenerator::with_update_set`]
+//! and [`DeleteKeyGenerator`], split into parts like the tables.
+//!
+//! The [`queries`] module has the texts of the 22 queries, and [`params`] the
+//! substitution parameters of each query stream, chosen like qgen does.
+//!
+//! [`OrderGenerator::with_update_set`]: generators::OrderGenerator::with_update_set
+//! [`LineItemGenerator::with_update_set`]: generators::LineItemGenerator::with_update_set
+//! [`DeleteKeyGenerator`]: generators::DeleteKeyGenerator
 //! [`LineItem`]: generators::LineItem
 //! [`LineItemCsv`]: csv::LineItemCsv
 //!
@@ -51,11 +71,17 @@
 //!
 //! If you want an easy way to generate the TPC-H dataset for usage with external
 //! systems you can use CLI tool instead.
+pub mod columns;
 pub mod csv;
 pub mod dates;
 pub mod decimal;
+pub mod dirty;
 pub mod distribution;
 pub mod generators;
+pub mod nulls;
+pub mod params;
+pub mod parse;
 pub mod queries;
 pub mod random;
+pub mod reader;
 pub mod text;
@@ -0,0 +1,138 @@
+//! Injection of NULL values, for testing how programs handle them.
+//!
+//! TPC-H data does not contain NULLs. A [`NullInjection`] selects a
+//! percentage of the values of the nullable columns, the comments and the
+//! dates (see [`is_nullable`]), to be written as NULL instead: as a token,
+//! e.g. an empty field, in text formats, or as real nulls in formats that
+//! have them.
+//!
+//! Whether a value is NULL only depends on its column and the primary key of
+//! its row, so the same values are NULL in every output format and number of
+//! parts, and about `percent`% of the values of each nullable column are
+//! NULL.
+//!
+//! # Example
+//! ```
+//! # use tpchgen::csv::OrderCsv;
+//! # use tpchgen::generators::OrderGenerator;
+//! # use tpchgen::nulls::NullInjection;
+//! let nulls = NullInjection::new(50.0).with_token("\\N");
+//! let lines: Vec<_> = OrderGenerator::new(0.01, 1, 1)
+//!     .iter()
+//!     .take(3)
+//!     .map(|order| OrderCsv::new(order).with_nulls(&nulls).to_string())
+//!     .collect();
+//! assert_eq!(
+//!     lines,
+//!     vec![
+//!         "1,370,O,172799.49,\\N,5-LOW,Clerk#000000951,0,\\N",
+//!         "2,781,O,38426.09,1996-12-01,1-URGENT,Clerk#000000880,0,\" foxes. pending accounts at the pending, silent asymptot\"",
+//!         "3,1234,F,205654.30,1993-10-14,5-LOW,Clerk#000000955,0,\\N",
+//!     ]
+//! );
+//! assert!(nulls.is_null("o_comment", &[1]));
+//! assert!(!nulls.is_null("o_comment", &[2]));
+//! ```
+
+/// The nullable columns: the comments and dates of all tables
+const NULLABLE_COLUMNS: &[&str] = &[
+    "n_comment",
+    "r_comment",
+    "p_comment",
+    "s_comment",
+    "ps_comment",
+    "c_comment",
+    "o_orderdate",
+    "o_comment",
+    "l_shipdate",
+    "l_commitdate",
+    "l_receiptdate",
+    "l_comment",
+];
+
+/// Returns true if NULLs are injected into `column`, e.g. `l_comment`
+pub fn is_nullable(column: &str) -> bool {
+    NULLABLE_COLUMNS.contains(&column)
+}
+
+/// Selects the values of the nullable columns that are NULL, see the
+/// [module documentation](self)
+#[derive(Debug, Clone, PartialEq)]
+pub struct NullInjection {
+    percent: f64,
+    token: String,
+}
+
+impl NullInjection {
+    /// Creates an injection of `percent`% NULLs, written as empty fields in
+    /// text formats
+    ///
+    /// # Panics
+    /// If `percent` is not between 0 and 100
+    pub fn new(percent: f64) -> Self {
+        assert!(
+            (0.0..=100.0).contains(&percent),
+            "invalid NULL percentage {percent}"
+        );
+        Self {
+            percent,
+            token: String::new(),
+        }
+    }
+
+    /// Set the token written for NULLs in text formats (default: empty),
+    /// e.g. `\N` or `NULL`
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = token.into();
+        self
+    }
+
+    /// Returns the percentage of NULLs in each nullable column
+    pub fn percent(&self) -> f64 {
+        self.percent
+    }
+
+    /// Returns the token written for NULLs in text formats
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Returns true if the value of `column` in the row with primary key
+    /// `key` is NULL
+    ///
+    /// Always false for columns that are not nullable.
+    pub fn is_null(&self, column: &str, key: &[i64]) -> bool {
+        if self.percent == 0.0 || !is_nullable(column) {
+            return false;
+        }
+        fraction(key_hash(column, key)) * 100.0 < self.percent
+    }
+}
+
+/// Returns a hash of `name` and the primary key `key` of a row, whose bits
+/// are unrelated for similar names and keys
+pub(crate) fn key_hash(name: &str, key: &[i64]) -> u64 {
+    // FNV-1a of the name, mixed with the key
+    let mut hash = name.bytes().fold(0xcbf29ce484222325_u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    for &k in key {
+        hash = mix(hash ^ k as u64);
+    }
+    hash
+}
+
+/// Returns `hash` as a fraction uniform in [0, 1)
+pub(crate) fn fraction(hash: u64) -> f64 {
+    // the top 53 bits are uniform in [0, 1)
+    (hash >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+/// The finalizer of SplitMix64, which maps similar inputs to unrelated
+/// outputs
+pub(crate) fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
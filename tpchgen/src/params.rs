@@ -0,0 +1,713 @@
+//! Substitution parameters of the TPC-H queries.
+//!
+//! Each query template in [`crate::queries`] has placeholders (`:1`, `:2`,
+//! ...) that qgen replaces with substitution parameters chosen at random
+//! from the ranges of the specification (clause 2.4), so that each query
+//! stream runs a different variant of each query.
+//!
+//! Without a seed, the parameters are the validation parameters of the
+//! specification, that `qgen -d` uses. With a seed, they are chosen at
+//! random: each query has its own random number generator, seeded from the
+//! seed, so the same seed always gives the same parameters. They are not
+//! checked against those of `qgen -r <seed>`, and may differ from them.
+//!
+//! The parameters do not depend on the query stream, which only orders the
+//! queries: the specification has each stream use its own seed.
+//!
+//! The parameters are typed, e.g. dates are [`TPCHDate`]s, for harnesses
+//! that run the queries with their own query texts:
+//!
+//! ```
+//! # use tpchgen::params::{stream_params, QueryParams};
+//! for params in stream_params(1.0, None) {
+//!     if let QueryParams::Q6 { date, discount, quantity } = params {
+//!         assert_eq!(date.to_string(), "1994-01-01");
+//!         assert_eq!(discount.to_string(), "0.06");
+//!         assert_eq!(quantity, 24);
+//!     }
+//! }
+//! ```
+
+use crate::dates::TPCHDate;
+use crate::decimal::TPCHDecimal;
+use crate::distribution::{Distribution, Distributions};
+use crate::random::RowRandomInt;
+
+/// Returns the parameters of query `query` (1 to 22) at `scale_factor`
+///
+/// With a `seed`, the parameters are chosen at random, see
+/// [`QueryParams::new`], otherwise they are the validation parameters, see
+/// [`QueryParams::validation`]. Returns `None` if there is no such query.
+pub fn query_params(query: i32, scale_factor: f64, seed: Option<i64>) -> Option<QueryParams> {
+    match seed {
+        Some(seed) => QueryParams::new(query, scale_factor, seed),
+        None => QueryParams::validation(query, scale_factor),
+    }
+}
+
+/// Returns the parameters of the 22 queries at `scale_factor`, in the order
+/// of the query numbers
+///
+/// See [`query_params`] for the `seed`, and
+/// [`stream_order`](crate::queries::stream_order) for the order in which the
+/// stream runs the queries.
+///
+/// # Example
+/// ```
+/// # use tpchgen::params::{stream_params, QueryParams};
+/// let params = stream_params(10.0, Some(1234));
+/// assert_eq!(params.len(), 22);
+/// assert_eq!(params[17].query(), 18);
+/// if let QueryParams::Q18 { quantity } = params[17] {
+///     assert!((312..=315).contains(&quantity));
+/// }
+/// // the same seed always gives the same parameters
+/// assert_eq!(params, stream_params(10.0, Some(1234)));
+/// ```
+pub fn stream_params(scale_factor: f64, seed: Option<i64>) -> Vec<QueryParams> {
+    (1..=22)
+        .map(|query| query_params(query, scale_factor, seed).expect("valid query"))
+        .collect()
+}
+
+/// Random values used for the parameters of one query
+const SEEDS_PER_QUERY: i32 = 100;
+
+/// Modulus of the random number generator; seeds are in `0..MODULUS`
+const MODULUS: i64 = 2147483647;
+
+/// Words of the `o_comment` pattern of Q13 with their weights (`Q13a` and
+/// `Q13b` of `dists.dss`)
+const Q13_WORDS_1: [(&str, i32); 4] = [
+    ("special", 20),
+    ("pending", 20),
+    ("unusual", 20),
+    ("express", 20),
+];
+const Q13_WORDS_2: [(&str, i32); 4] = [
+    ("packages", 40),
+    ("requests", 40),
+    ("accounts", 40),
+    ("deposits", 40),
+];
+
+/// The substitution parameters of one query
+///
+/// Dates are the first day of the interval of the query, and brands the
+/// `MN` of `Brand#MN`. [`Self::substitutions`] formats the parameters as
+/// qgen writes them.
+///
+/// # Example
+/// ```
+/// # use tpchgen::params::QueryParams;
+/// let Some(QueryParams::Q3 { segment, date }) = QueryParams::validation(3, 1.0) else {
+///     unreachable!()
+/// };
+/// assert_eq!(segment, "BUILDING");
+/// assert_eq!(date.to_string(), "1995-03-15");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryParams {
+    /// Pricing Summary Report Query (Q1)
+    Q1 {
+        /// Days before 1998-12-01 of the last ship date
+        delta: i32,
+    },
+    /// Minimum Cost Supplier Query (Q2)
+    Q2 {
+        size: i32,
+        /// Last syllable of the part type, e.g. `BRASS`
+        type_syllable: &'static str,
+        region: &'static str,
+    },
+    /// Shipping Priority Query (Q3)
+    Q3 {
+        segment: &'static str,
+        date: TPCHDate,
+    },
+    /// Order Priority Checking Query (Q4)
+    Q4 { date: TPCHDate },
+    /// Local Supplier Volume Query (Q5)
+    Q5 {
+        region: &'static str,
+        date: TPCHDate,
+    },
+    /// Forecasting Revenue Change Query (Q6)
+    Q6 {
+        date: TPCHDate,
+        discount: TPCHDecimal,
+        quantity: i32,
+    },
+    /// Volume Shipping Query (Q7)
+    Q7 {
+        nation1: &'static str,
+        nation2: &'static str,
+    },
+    /// National Market Share Query (Q8)
+    Q8 {
+        nation: &'static str,
+        region: &'static str,
+        part_type: &'static str,
+    },
+    /// Product Type Profit Measure Query (Q9)
+    Q9 { color: &'static str },
+    /// Returned Item Reporting Query (Q10)
+    Q10 { date: TPCHDate },
+    /// Important Stock Identification Query (Q11)
+    Q11 {
+        nation: &'static str,
+        /// Fraction of the total value of the stock, `0.0001 / SF`
+        fraction: f64,
+    },
+    /// Shipping Modes and Order Priority Query (Q12)
+    Q12 {
+        ship_mode1: &'static str,
+        ship_mode2: &'static str,
+        date: TPCHDate,
+    },
+    /// Customer Distribution Query (Q13)
+    Q13 {
+        /// Words of the pattern `%<word1>%<word2>%` of the order comments
+        word1: &'static str,
+        word2: &'static str,
+    },
+    /// Promotion Effect Query (Q14)
+    Q14 { date: TPCHDate },
+    /// Top Supplier Query (Q15)
+    Q15 { date: TPCHDate },
+    /// Parts/Supplier Relationship Query (Q16)
+    Q16 {
+        brand: i32,
+        /// First two syllables of the part type, e.g. `MEDIUM POLISHED`
+        part_type: &'static str,
+        sizes: [i32; 8],
+    },
+    /// Small-Quantity-Order Revenue Query (Q17)
+    Q17 { brand: i32, container: &'static str },
+    /// Large Volume Customer Query (Q18)
+    Q18 { quantity: i32 },
+    /// Discounted Revenue Query (Q19)
+    Q19 {
+        brands: [i32; 3],
+        quantities: [i32; 3],
+    },
+    /// Potential Part Promotion Query (Q20)
+    Q20 {
+        color: &'static str,
+        date: TPCHDate,
+        nation: &'static str,
+    },
+    /// Suppliers Who Kept Orders Waiting Query (Q21)
+    Q21 { nation: &'static str },
+    /// Global Sales Opportunity Query (Q22)
+    Q22 {
+        /// First two digits of the phone numbers of the customers
+        country_codes: [i32; 7],
+    },
+}
+
+impl QueryParams {
+    /// Returns the parameters of query `query` (1 to 22) at `scale_factor`,
+    /// chosen at random with `seed`
+    ///
+    /// Returns `None` if there is no such query, or if `seed` is a multiple
+    /// of 2147483647, with which the generator only returns 0.
+    pub fn new(query: i32, scale_factor: f64, seed: i64) -> Option<Self> {
+        // negative seeds are taken modulo the modulus
+        let seed = seed.rem_euclid(MODULUS);
+        if !(1..=22).contains(&query) || seed == 0 {
+            return None;
+        }
+        // the generator of query `q` is seeded with the `q`th value of the
+        // generator seeded with the seed
+        let mut seeds = RowRandomInt::new(seed, 1);
+        let mut seed = 0;
+        for _ in 0..query {
+            seed = seeds.next_rand();
+        }
+        let mut random = RowRandomInt::new(seed, SEEDS_PER_QUERY);
+        let random = &mut random;
+        let distributions = Distributions::static_default();
+        // the nations with the same weight, `nations2` of `dists.dss`
+        let nations = distributions.nations().get_values();
+        let nations2 = Distribution::new("nations2", nations.iter().map(|&n| (n, 1)).collect());
+        let nations2 = &nations2;
+
+        let params = match query {
+            1 => Self::Q1 {
+                delta: random.next_int(60, 120),
+            },
+            2 => Self::Q2 {
+                size: random.next_int(1, 50),
+                type_syllable: last_syllable(pick(distributions.part_types(), random)),
+                region: pick(distributions.regions(), random),
+            },
+            3 => Self::Q3 {
+                segment: pick(distributions.market_segments(), random),
+                date: date(1995, 3, random.next_int(1, 31)),
+            },
+            4 => Self::Q4 {
+                date: month(1993, random.next_int(1, 58)),
+            },
+            5 => Self::Q5 {
+                region: pick(distributions.regions(), random),
+                date: date(random.next_int(1993, 1997), 1, 1),
+            },
+            6 => Self::Q6 {
+                date: date(random.next_int(1993, 1997), 1, 1),
+                discount: TPCHDecimal(random.next_int(2, 9) as i64),
+                quantity: random.next_int(24, 25),
+            },
+            7 => {
+                let [nation1, nation2] = pick_distinct(nations2, random);
+                Self::Q7 { nation1, nation2 }
+            }
+            8 => {
+                // the weights of `nations` are the region keys
+                let index = pick_index(nations2, random);
+                let region = distributions.nations().get_weight(index) as usize;
+                Self::Q8 {
+                    nation: nations2.get_values()[index],
+                    region: distributions.regions().get_values()[region],
+                    part_type: pick(distributions.part_types(), random),
+                }
+            }
+            9 => Self::Q9 {
+                color: pick(distributions.part_colors(), random),
+            },
+            10 => Self::Q10 {
+                date: month(1993, random.next_int(1, 24)),
+            },
+            11 => Self::Q11 {
+                nation: pick(nations2, random),
+                fraction: 0.0001 / scale_factor,
+            },
+            12 => {
+                let [ship_mode1, ship_mode2] = pick_distinct(distributions.ship_modes(), random);
+                Self::Q12 {
+                    ship_mode1,
+                    ship_mode2,
+                    date: date(random.next_int(1993, 1997), 1, 1),
+                }
+            }
+            13 => Self::Q13 {
+                word1: pick(&Distribution::new("Q13a", Q13_WORDS_1.to_vec()), random),
+                word2: pick(&Distribution::new("Q13b", Q13_WORDS_2.to_vec()), random),
+            },
+            14 => Self::Q14 {
+                date: month(1993, random.next_int(1, 60)),
+            },
+            15 => Self::Q15 {
+                date: month(1993, random.next_int(1, 58)),
+            },
+            16 => {
+                let brand = random_brand(random);
+                let part_type = first_syllables(pick(distributions.part_types(), random));
+                let sizes = permutation::<50>(random);
+                Self::Q16 {
+                    brand,
+                    part_type,
+                    sizes: std::array::from_fn(|i| sizes[i] + 1),
+                }
+            }
+            17 => Self::Q17 {
+                brand: random_brand(random),
+                container: pick(distributions.part_containers(), random),
+            },
+            18 => Self::Q18 {
+                quantity: random.next_int(312, 315),
+            },
+            19 => Self::Q19 {
+                brands: [(); 3].map(|_| random_brand(random)),
+                quantities: [
+                    random.next_int(1, 10),
+                    random.next_int(10, 20),
+                    random.next_int(20, 30),
+                ],
+            },
+            20 => Self::Q20 {
+                color: pick(distributions.part_colors(), random),
+                date: date(random.next_int(1993, 1997), 1, 1),
+                nation: pick(nations2, random),
+            },
+            21 => Self::Q21 {
+                nation: pick(nations2, random),
+            },
+            22 => {
+                let codes = permutation::<25>(random);
+                Self::Q22 {
+                    country_codes: std::array::from_fn(|i| codes[i] + 10),
+                }
+            }
+            _ => unreachable!(),
+        };
+        Some(params)
+    }
+
+    /// Returns the validation parameters of query `query` (1 to 22) at
+    /// `scale_factor`, of clause 2.4 of the specification
+    ///
+    /// Returns `None` if there is no such query.
+    pub fn validation(query: i32, scale_factor: f64) -> Option<Self> {
+        let params = match query {
+            1 => Self::Q1 { delta: 90 },
+            2 => Self::Q2 {
+                size: 15,
+                type_syllable: "BRASS",
+                region: "EUROPE",
+            },
+            3 => Self::Q3 {
+                segment: "BUILDING",
+                date: date(1995, 3, 15),
+            },
+            4 => Self::Q4 {
+                date: date(1993, 7, 1),
+            },
+            5 => Self::Q5 {
+                region: "ASIA",
+                date: date(1994, 1, 1),
+            },
+            6 => Self::Q6 {
+                date: date(1994, 1, 1),
+                discount: TPCHDecimal(6),
+                quantity: 24,
+            },
+            7 => Self::Q7 {
+                nation1: "FRANCE",
+                nation2: "GERMANY",
+            },
+            8 => Self::Q8 {
+                nation: "BRAZIL",
+                region: "AMERICA",
+                part_type: "ECONOMY ANODIZED STEEL",
+            },
+            9 => Self::Q9 { color: "green" },
+            10 => Self::Q10 {
+                date: date(1993, 10, 1),
+            },
+            11 => Self::Q11 {
+                nation: "GERMANY",
+                fraction: 0.0001 / scale_factor,
+            },
+            12 => Self::Q12 {
+                ship_mode1: "MAIL",
+                ship_mode2: "SHIP",
+                date: date(1994, 1, 1),
+            },
+            13 => Self::Q13 {
+                word1: "special",
+                word2: "requests",
+            },
+            14 => Self::Q14 {
+                date: date(1995, 9, 1),
+            },
+            15 => Self::Q15 {
+                date: date(1996, 1, 1),
+            },
+            16 => Self::Q16 {
+                brand: 45,
+                part_type: "MEDIUM POLISHED",
+                sizes: [49, 14, 23, 45, 19, 3, 36, 9],
+            },
+            17 => Self::Q17 {
+                brand: 23,
+                container: "MED BOX",
+            },
+            18 => Self::Q18 { quantity: 300 },
+            19 => Self::Q19 {
+                brands: [12, 23, 34],
+                quantities: [1, 10, 20],
+            },
+            20 => Self::Q20 {
+                color: "forest",
+                date: date(1994, 1, 1),
+                nation: "CANADA",
+            },
+            21 => Self::Q21 {
+                nation: "SAUDI ARABIA",
+            },
+            22 => Self::Q22 {
+                country_codes: [13, 31, 23, 29, 30, 18, 17],
+            },
+            _ => return None,
+        };
+        Some(params)
+    }
+
+    /// Returns the number of the query of the parameters
+    pub fn query(&self) -> i32 {
+        match self {
+            Self::Q1 { .. } => 1,
+            Self::Q2 { .. } => 2,
+            Self::Q3 { .. } => 3,
+            Self::Q4 { .. } => 4,
+            Self::Q5 { .. } => 5,
+            Self::Q6 { .. } => 6,
+            Self::Q7 { .. } => 7,
+            Self::Q8 { .. } => 8,
+            Self::Q9 { .. } => 9,
+            Self::Q10 { .. } => 10,
+            Self::Q11 { .. } => 11,
+            Self::Q12 { .. } => 12,
+            Self::Q13 { .. } => 13,
+            Self::Q14 { .. } => 14,
+            Self::Q15 { .. } => 15,
+            Self::Q16 { .. } => 16,
+            Self::Q17 { .. } => 17,
+            Self::Q18 { .. } => 18,
+            Self::Q19 { .. } => 19,
+            Self::Q20 { .. } => 20,
+            Self::Q21 { .. } => 21,
+            Self::Q22 { .. } => 22,
+        }
+    }
+
+    /// Returns the values of the placeholders `:1`, `:2`, ... of the query
+    /// template, as qgen writes them
+    ///
+    /// # Example
+    /// ```
+    /// # use tpchgen::params::QueryParams;
+    /// let q19 = QueryParams::validation(19, 1.0).unwrap();
+    /// assert_eq!(
+    ///     q19.substitutions(),
+    ///     ["Brand#12", "Brand#23", "Brand#34", "1", "10", "20"]
+    /// );
+    /// ```
+    pub fn substitutions(&self) -> Vec<String> {
+        fn strings<T: ToString>(values: &[T]) -> Vec<String> {
+            values.iter().map(|v| v.to_string()).collect()
+        }
+        let brand = |brand: &i32| format!("Brand#{brand}");
+        match self {
+            Self::Q1 { delta } => strings(&[delta]),
+            Self::Q2 {
+                size,
+                type_syllable,
+                region,
+            } => vec![
+                size.to_string(),
+                type_syllable.to_string(),
+                region.to_string(),
+            ],
+            Self::Q3 { segment, date } => vec![segment.to_string(), date.to_string()],
+            Self::Q4 { date } | Self::Q10 { date } | Self::Q14 { date } | Self::Q15 { date } => {
+                strings(&[date])
+            }
+            Self::Q5 { region, date } => vec![region.to_string(), date.to_string()],
+            Self::Q6 {
+                date,
+                discount,
+                quantity,
+            } => vec![date.to_string(), discount.to_string(), quantity.to_string()],
+            Self::Q7 { nation1, nation2 } => strings(&[nation1, nation2]),
+            Self::Q8 {
+                nation,
+                region,
+                part_type,
+            } => strings(&[nation, region, part_type]),
+            Self::Q9 { color } => strings(&[color]),
+            Self::Q11 { nation, fraction } => vec![nation.to_string(), format!("{fraction:.10}")],
+            Self::Q12 {
+                ship_mode1,
+                ship_mode2,
+                date,
+            } => vec![
+                ship_mode1.to_string(),
+                ship_mode2.to_string(),
+                date.to_string(),
+            ],
+            Self::Q13 { word1, word2 } => strings(&[word1, word2]),
+            Self::Q16 {
+                brand: b,
+                part_type,
+                sizes,
+            } => [brand(b), part_type.to_string()]
+                .into_iter()
+                .chain(strings(sizes))
+                .collect(),
+            Self::Q17 {
+                brand: b,
+                container,
+            } => vec![brand(b), container.to_string()],
+            Self::Q18 { quantity } => strings(&[quantity]),
+            Self::Q19 { brands, quantities } => brands
+                .iter()
+                .map(brand)
+                .chain(strings(quantities))
+                .collect(),
+            Self::Q20 {
+                color,
+                date,
+                nation,
+            } => vec![color.to_string(), date.to_string(), nation.to_string()],
+            Self::Q21 { nation } => strings(&[nation]),
+            Self::Q22 { country_codes } => strings(country_codes),
+        }
+    }
+}
+
+/// Returns the index of a random value of `distribution`, picked in
+/// proportion to its weight
+fn pick_index(distribution: &Distribution, random: &mut RowRandomInt) -> usize {
+    let total = distribution.get_weight(distribution.size() - 1);
+    let value = random.next_int(1, total);
+    (0..distribution.size())
+        .find(|&index| distribution.get_weight(index) >= value)
+        .expect("value is at most the total weight")
+}
+
+/// Returns a random value of `distribution`, see [`pick_index`]
+fn pick(distribution: &Distribution, random: &mut RowRandomInt) -> &'static str {
+    distribution.get_values()[pick_index(distribution, random)]
+}
+
+/// Returns two different random values of `distribution`
+fn pick_distinct(distribution: &Distribution, random: &mut RowRandomInt) -> [&'static str; 2] {
+    let first = pick(distribution, random);
+    loop {
+        let second = pick(distribution, random);
+        if second != first {
+            return [first, second];
+        }
+    }
+}
+
+/// Returns a random permutation of `0..N`, swapping each position with a
+/// random one
+fn permutation<const N: usize>(random: &mut RowRandomInt) -> [i32; N] {
+    let mut values = std::array::from_fn(|i| i as i32);
+    for i in 0..N {
+        let j = random.next_int(0, N as i32 - 1) as usize;
+        values.swap(i, j);
+    }
+    values
+}
+
+/// Returns a random brand `MN`, with `M` and `N` from 1 to 5
+fn random_brand(random: &mut RowRandomInt) -> i32 {
+    random.next_int(1, 5) * 10 + random.next_int(1, 5)
+}
+
+/// Returns the last syllable of a part type, e.g. `TIN` of `STANDARD ANODIZED TIN`
+fn last_syllable(part_type: &'static str) -> &'static str {
+    part_type.rsplit(' ').next().unwrap_or(part_type)
+}
+
+/// Returns the first two syllables of a part type, e.g. `STANDARD ANODIZED`
+/// of `STANDARD ANODIZED TIN`
+fn first_syllables(part_type: &'static str) -> &'static str {
+    part_type
+        .rsplit_once(' ')
+        .map_or(part_type, |(first, _)| first)
+}
+
+/// Returns the first day of the month `months` after January of `year`
+fn month(year: i32, months: i32) -> TPCHDate {
+    date(year + months / 12, months % 12 + 1, 1)
+}
+
+/// Returns the date of `year`, `month` and `day`
+fn date(year: i32, month: i32, day: i32) -> TPCHDate {
+    TPCHDate::from_ymd(year, month, day).expect("query dates are in the generated range")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation_parameters() {
+        let q3 = QueryParams::validation(3, 1.0).unwrap();
+        assert_eq!(q3.substitutions(), ["BUILDING", "1995-03-15"]);
+        let q11 = QueryParams::validation(11, 10.0).unwrap();
+        assert_eq!(q11.substitutions(), ["GERMANY", "0.0000100000"]);
+        let q19 = QueryParams::validation(19, 1.0).unwrap();
+        assert_eq!(
+            q19.substitutions(),
+            ["Brand#12", "Brand#23", "Brand#34", "1", "10", "20"]
+        );
+        assert_eq!(QueryParams::validation(23, 1.0), None);
+    }
+
+    #[test]
+    fn random_parameters() {
+        for query in 1..=22 {
+            let params = QueryParams::new(query, 1.0, 42).unwrap();
+            assert_eq!(params.query(), query);
+            // the same seed gives the same parameters
+            assert_eq!(QueryParams::new(query, 1.0, 42), Some(params));
+        }
+        assert_eq!(QueryParams::new(23, 1.0, 42), None);
+        assert_eq!(QueryParams::new(1, 1.0, 0), None);
+        assert_eq!(QueryParams::new(1, 1.0, -2147483647), None);
+
+        for seed in 1..100 {
+            let Some(QueryParams::Q8 { nation, region, .. }) = QueryParams::new(8, 1.0, seed)
+            else {
+                panic!("Q8 parameters expected");
+            };
+            let nations = Distributions::static_default().nations();
+            let index = nations.get_values().iter().position(|n| *n == nation);
+            let regions = Distributions::static_default().regions();
+            assert_eq!(
+                regions.get_values()[nations.get_weight(index.unwrap()) as usize],
+                region
+            );
+            let Some(QueryParams::Q22 { country_codes }) = QueryParams::new(22, 1.0, seed) else {
+                panic!("Q22 parameters expected");
+            };
+            assert!(country_codes.iter().all(|c| (10..=34).contains(c)));
+            let Some(QueryParams::Q10 { date }) = QueryParams::new(10, 1.0, seed) else {
+                panic!("Q10 parameters expected");
+            };
+            let date = date.to_string();
+            assert!(
+                ("1993-02-01".."1995-02-01").contains(&date.as_str()),
+                "{date}"
+            );
+        }
+    }
+
+    #[test]
+    fn seeded_parameters() {
+        // the substitutions for seed 1, which must not change between
+        // releases so that a seed keeps giving the same queries
+        let expected = [
+            "68",
+            "38|STEEL|ASIA",
+            "FURNITURE|1995-03-17",
+            "1995-08-01",
+            "AMERICA|1993-01-01",
+            "1993-01-01|0.07|25",
+            "MOZAMBIQUE|UNITED KINGDOM",
+            "MOZAMBIQUE|AFRICA|PROMO POLISHED TIN",
+            "thistle",
+            "1993-11-01",
+            "JAPAN|0.0001000000",
+            "FOB|REG AIR|1993-01-01",
+            "special|packages",
+            "1993-05-01",
+            "1995-08-01",
+            "Brand#41|MEDIUM BURNISHED|4|21|15|41|49|43|27|47",
+            "Brand#12|SM BAG",
+            "313",
+            "Brand#13|Brand#43|Brand#55|6|11|27",
+            "ivory|1996-01-01|KENYA",
+            "PERU",
+            "24|11|14|23|31|26|10",
+        ];
+        let actual: Vec<_> = stream_params(1.0, Some(1))
+            .iter()
+            .map(|params| params.substitutions().join("|"))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(date(1992, 1, 1).to_string(), "1992-01-01");
+        assert_eq!(date(1996, 2, 29).to_string(), "1996-02-29");
+        assert_eq!(month(1993, 57).to_string(), "1997-10-01");
+    }
+}
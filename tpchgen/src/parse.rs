@@ -0,0 +1,463 @@
+//! Parsing TBL and CSV lines back into the row structs of [`generators`].
+//!
+//! Each function parses a single line in the format produced by the
+//! `Display` impl of the row struct (TBL) or by the formatters in the
+//! [`csv`] module (CSV). Parsing is the inverse of formatting, so a parsed
+//! row formats to the same line it was parsed from.
+//!
+//! String columns borrow from the input line, so the line must outlive the
+//! parsed row.
+//!
+//! # Example
+//! ```
+//! # use tpchgen::generators::LineItemGenerator;
+//! # use tpchgen::parse::{parse_lineitem, Format};
+//! let line = "1|155190|7706|1|17|21168.23|0.04|0.02|N|O|1996-03-13|1996-02-12|1996-03-22|DELIVER IN PERSON|TRUCK|egular courts above the|";
+//! let item = parse_lineitem(line, Format::Tbl).unwrap();
+//! assert_eq!(item.l_partkey, 155190);
+//! assert_eq!(item.l_shipdate.to_string(), "1996-03-13");
+//! // round trip
+//! assert_eq!(item.to_string(), line);
+//!
+//! // parsed rows compare equal to the generated rows
+//! let generated = LineItemGenerator::new(1.0, 1, 1).iter().next().unwrap();
+//! assert_eq!(item, generated);
+//! ```
+//!
+//! [`generators`]: crate::generators
+//! [`csv`]: crate::csv
+
+use crate::dates::TPCHDate;
+use crate::decimal::TPCHDecimal;
+use crate::generators::{
+    ClerkName, Customer, CustomerName, LineItem, LineStatus, Nation, Order, OrderPriority,
+    OrderStatus, Part, PartBrandName, PartManufacturerName, PartSupp, Region, ReturnFlag, ShipMode,
+    Supplier, SupplierName,
+};
+use crate::random::{PhoneNumberInstance, RandomAlphaNumericInstance, StringSequenceInstance};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// Format of the lines to parse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `|` separated columns with a trailing `|`, as written by dbgen
+    Tbl,
+    /// `,` separated columns, with string columns that may contain commas
+    /// quoted in `"`, as written by the [`csv`](crate::csv) formatters
+    Csv,
+}
+
+/// Error returned when a line can not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    message: String,
+}
+
+impl ParseError {
+    fn invalid(column: &str, value: &str, expected: &str) -> Self {
+        Self {
+            message: format!("invalid value '{value}' for {column}: expected {expected}"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a [`Nation`] from a line
+pub fn parse_nation(line: &str, format: Format) -> Result<Nation<'_>, ParseError> {
+    let mut fields = Fields::new(line, format, "nation", 4);
+    let nation = Nation {
+        n_nationkey: fields.parse("n_nationkey")?,
+        n_name: fields.next("n_name")?,
+        n_regionkey: fields.parse("n_regionkey")?,
+        n_comment: fields.next("n_comment")?,
+    };
+    fields.finish()?;
+    Ok(nation)
+}
+
+/// Parses a [`Region`] from a line
+pub fn parse_region(line: &str, format: Format) -> Result<Region<'_>, ParseError> {
+    let mut fields = Fields::new(line, format, "region", 3);
+    let region = Region {
+        r_regionkey: fields.parse("r_regionkey")?,
+        r_name: fields.next("r_name")?,
+        r_comment: fields.next("r_comment")?,
+    };
+    fields.finish()?;
+    Ok(region)
+}
+
+/// Parses a [`Part`] from a line
+pub fn parse_part(line: &str, format: Format) -> Result<Part<'_>, ParseError> {
+    let mut fields = Fields::new(line, format, "part", 9);
+    let part = Part {
+        p_partkey: fields.parse("p_partkey")?,
+        p_name: StringSequenceInstance::from_values(fields.next("p_name")?.split(' ').collect()),
+        p_mfgr: PartManufacturerName::new(fields.numbered("p_mfgr", "Manufacturer#")?),
+        p_brand: PartBrandName::new(fields.numbered("p_brand", "Brand#")?),
+        p_type: fields.next("p_type")?,
+        p_size: fields.parse("p_size")?,
+        p_container: fields.next("p_container")?,
+        p_retailprice: fields.decimal("p_retailprice")?,
+        p_comment: fields.next("p_comment")?,
+    };
+    fields.finish()?;
+    Ok(part)
+}
+
+/// Parses a [`Supplier`] from a line
+pub fn parse_supplier(line: &str, format: Format) -> Result<Supplier, ParseError> {
+    let mut fields = Fields::new(line, format, "supplier", 7);
+    let supplier = Supplier {
+        s_suppkey: fields.parse("s_suppkey")?,
+        s_name: SupplierName::new(fields.numbered("s_name", "Supplier#")?),
+        s_address: RandomAlphaNumericInstance::from_text(fields.next("s_address")?),
+        s_nationkey: fields.parse("s_nationkey")?,
+        s_phone: fields.phone("s_phone")?,
+        s_acctbal: fields.decimal("s_acctbal")?,
+        s_comment: fields.next("s_comment")?.to_string(),
+    };
+    fields.finish()?;
+    Ok(supplier)
+}
+
+/// Parses a [`PartSupp`] from a line
+pub fn parse_partsupp(line: &str, format: Format) -> Result<PartSupp<'_>, ParseError> {
+    let mut fields = Fields::new(line, format, "partsupp", 5);
+    let partsupp = PartSupp {
+        ps_partkey: fields.parse("ps_partkey")?,
+        ps_suppkey: fields.parse("ps_suppkey")?,
+        ps_availqty: fields.parse("ps_availqty")?,
+        ps_supplycost: fields.decimal("ps_supplycost")?,
+        ps_comment: fields.next("ps_comment")?,
+    };
+    fields.finish()?;
+    Ok(partsupp)
+}
+
+/// Parses a [`Customer`] from a line
+pub fn parse_customer(line: &str, format: Format) -> Result<Customer<'_>, ParseError> {
+    let mut fields = Fields::new(line, format, "customer", 8);
+    let customer = Customer {
+        c_custkey: fields.parse("c_custkey")?,
+        c_name: CustomerName::new(fields.numbered("c_name", "Customer#")?),
+        c_address: RandomAlphaNumericInstance::from_text(fields.next("c_address")?),
+        c_nationkey: fields.parse("c_nationkey")?,
+        c_phone: fields.phone("c_phone")?,
+        c_acctbal: fields.decimal("c_acctbal")?,
+        c_mktsegment: fields.next("c_mktsegment")?,
+        c_comment: fields.next("c_comment")?,
+    };
+    fields.finish()?;
+    Ok(customer)
+}
+
+/// Parses an [`Order`] from a line
+pub fn parse_order(line: &str, format: Format) -> Result<Order<'_>, ParseError> {
+    let mut fields = Fields::new(line, format, "orders", 9);
+    let order = Order {
+        o_orderkey: fields.parse("o_orderkey")?,
+        o_custkey: fields.parse("o_custkey")?,
+        o_orderstatus: fields.one_of("o_orderstatus", OrderStatus::ALL)?,
+        o_totalprice: fields.decimal("o_totalprice")?,
+        o_orderdate: fields.date("o_orderdate")?,
+        o_orderpriority: fields.one_of("o_orderpriority", OrderPriority::ALL)?,
+        o_clerk: ClerkName::new(fields.numbered("o_clerk", "Clerk#")?),
+        o_shippriority: fields.parse("o_shippriority")?,
+        o_comment: fields.next("o_comment")?,
+    };
+    fields.finish()?;
+    Ok(order)
+}
+
+/// Parses a [`LineItem`] from a line
+pub fn parse_lineitem(line: &str, format: Format) -> Result<LineItem<'_>, ParseError> {
+    let mut fields = Fields::new(line, format, "lineitem", 16);
+    let item = LineItem {
+        l_orderkey: fields.parse("l_orderkey")?,
+        l_partkey: fields.parse("l_partkey")?,
+        l_suppkey: fields.parse("l_suppkey")?,
+        l_linenumber: fields.parse("l_linenumber")?,
+        l_quantity: fields.parse("l_quantity")?,
+        l_extendedprice: fields.decimal("l_extendedprice")?,
+        l_discount: fields.decimal("l_discount")?,
+        l_tax: fields.decimal("l_tax")?,
+        l_returnflag: fields.one_of("l_returnflag", ReturnFlag::ALL)?,
+        l_linestatus: fields.one_of("l_linestatus", LineStatus::ALL)?,
+        l_shipdate: fields.date("l_shipdate")?,
+        l_commitdate: fields.date("l_commitdate")?,
+        l_receiptdate: fields.date("l_receiptdate")?,
+        l_shipinstruct: fields.next("l_shipinstruct")?,
+        l_shipmode: fields.one_of("l_shipmode", ShipMode::ALL)?,
+        l_comment: fields.next("l_comment")?,
+    };
+    fields.finish()?;
+    Ok(item)
+}
+
+/// Splits a line into its columns
+struct Fields<'a> {
+    /// The unparsed remainder of the line, `None` after the last column
+    rest: Option<&'a str>,
+    format: Format,
+    table: &'static str,
+    /// Number of columns of the table
+    expected: usize,
+}
+
+impl<'a> Fields<'a> {
+    fn new(line: &'a str, format: Format, table: &'static str, expected: usize) -> Self {
+        let line = line.trim_end_matches(['\n', '\r']);
+        // TBL lines end with a separator
+        let line = match format {
+            Format::Tbl => line.strip_suffix('|').unwrap_or(line),
+            Format::Csv => line,
+        };
+        Self {
+            rest: Some(line),
+            format,
+            table,
+            expected,
+        }
+    }
+
+    fn wrong_column_count(&self) -> ParseError {
+        ParseError {
+            message: format!(
+                "wrong number of columns for {}: expected {}",
+                self.table, self.expected
+            ),
+        }
+    }
+
+    /// Returns the next column
+    fn next(&mut self, column: &str) -> Result<&'a str, ParseError> {
+        let Some(rest) = self.rest else {
+            return Err(self.wrong_column_count());
+        };
+        let (value, rest) = match self.format {
+            Format::Tbl => split(rest, '|'),
+            Format::Csv => match rest.strip_prefix('"') {
+                Some(quoted) => {
+                    let (value, rest) = quoted
+                        .split_once('"')
+                        .ok_or_else(|| ParseError::invalid(column, rest, "closing quote"))?;
+                    match rest.strip_prefix(',') {
+                        Some(rest) => (value, Some(rest)),
+                        None if rest.is_empty() => (value, None),
+                        None => return Err(ParseError::invalid(column, rest, "',' after quote")),
+                    }
+                }
+                None => split(rest, ','),
+            },
+        };
+        self.rest = rest;
+        Ok(value)
+    }
+
+    /// Checks that all columns have been read
+    fn finish(self) -> Result<(), ParseError> {
+        match self.rest {
+            None => Ok(()),
+            Some(_) => Err(self.wrong_column_count()),
+        }
+    }
+
+    fn parse<T: FromStr>(&mut self, column: &str) -> Result<T, ParseError> {
+        let value = self.next(column)?;
+        value
+            .parse()
+            .map_err(|_| ParseError::invalid(column, value, "an integer"))
+    }
+
+    /// Parses one of the values in `all`, e.g. [`ShipMode::ALL`]
+    fn one_of<T: FromStr + Display>(&mut self, column: &str, all: &[T]) -> Result<T, ParseError> {
+        let value = self.next(column)?;
+        value.parse().map_err(|_| {
+            let names: Vec<_> = all.iter().map(|v| v.to_string()).collect();
+            let (last, rest) = names.split_last().unwrap();
+            let expected = format!("{} or {last}", rest.join(", "));
+            ParseError::invalid(column, value, &expected)
+        })
+    }
+
+    fn decimal(&mut self, column: &str) -> Result<TPCHDecimal, ParseError> {
+        let value = self.next(column)?;
+        value
+            .parse()
+            .map_err(|_| ParseError::invalid(column, value, "a decimal with 2 decimal places"))
+    }
+
+    fn date(&mut self, column: &str) -> Result<TPCHDate, ParseError> {
+        let value = self.next(column)?;
+        value.parse().map_err(|_| {
+            ParseError::invalid(column, value, "a date between 1992-01-01 and 1998-12-31")
+        })
+    }
+
+    /// Parses a name like `Supplier#000000001` into its number
+    fn numbered<T: FromStr>(&mut self, column: &str, prefix: &str) -> Result<T, ParseError> {
+        let value = self.next(column)?;
+        value
+            .strip_prefix(prefix)
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| ParseError::invalid(column, value, &format!("{prefix}<number>")))
+    }
+
+    /// Parses a phone number like `27-918-335-1736`
+    fn phone(&mut self, column: &str) -> Result<PhoneNumberInstance, ParseError> {
+        let value = self.next(column)?;
+        let parts: Vec<i32> = value
+            .split('-')
+            .map(|part| part.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| ParseError::invalid(column, value, "a phone number"))?;
+        let [country_code, local1, local2, local3] = parts[..] else {
+            return Err(ParseError::invalid(column, value, "a phone number"));
+        };
+        Ok(PhoneNumberInstance::new(
+            country_code,
+            local1,
+            local2,
+            local3,
+        ))
+    }
+}
+
+/// Splits `s` at the first `separator`, returning `None` as the remainder if
+/// there is no separator
+fn split(s: &str, separator: char) -> (&str, Option<&str>) {
+    match s.split_once(separator) {
+        Some((value, rest)) => (value, Some(rest)),
+        None => (s, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv::{
+        CustomerCsv, LineItemCsv, NationCsv, OrderCsv, PartCsv, PartSuppCsv, RegionCsv, SupplierCsv,
+    };
+    use crate::generators::{
+        CustomerGenerator, LineItemGenerator, NationGenerator, OrderGenerator, PartGenerator,
+        PartSuppGenerator, RegionGenerator, SupplierGenerator,
+    };
+
+    #[test]
+    fn test_round_trip() {
+        for row in NationGenerator::default().iter() {
+            let tbl = row.to_string();
+            assert_eq!(parse_nation(&tbl, Format::Tbl).unwrap(), row);
+            let csv = NationCsv::new(row.clone()).to_string();
+            assert_eq!(parse_nation(&csv, Format::Csv).unwrap(), row);
+        }
+        for row in RegionGenerator::default().iter() {
+            let tbl = row.to_string();
+            assert_eq!(parse_region(&tbl, Format::Tbl).unwrap(), row);
+            let csv = RegionCsv::new(row.clone()).to_string();
+            assert_eq!(parse_region(&csv, Format::Csv).unwrap(), row);
+        }
+
+        // edge cases include the shortest and longest strings
+        let sf = 0.01;
+        for edge_cases in [false, true] {
+            let generator = PartGenerator::new(sf, 1, 1).with_edge_cases(edge_cases);
+            for row in generator.iter().take(200) {
+                let tbl = row.to_string();
+                assert_eq!(parse_part(&tbl, Format::Tbl).unwrap(), row);
+                let csv = PartCsv::new(row.clone()).to_string();
+                assert_eq!(parse_part(&csv, Format::Csv).unwrap(), row);
+            }
+            let generator = SupplierGenerator::new(sf, 1, 1).with_edge_cases(edge_cases);
+            for row in generator.iter().take(200) {
+                let tbl = row.to_string();
+                let parsed = parse_supplier(&tbl, Format::Tbl).unwrap();
+                assert_eq!(parsed, row);
+                assert_eq!(parsed.to_string(), tbl);
+                let csv = SupplierCsv::new(row.clone()).to_string();
+                assert_eq!(parse_supplier(&csv, Format::Csv).unwrap(), row);
+            }
+            let generator = PartSuppGenerator::new(sf, 1, 1).with_edge_cases(edge_cases);
+            for row in generator.iter().take(200) {
+                let tbl = row.to_string();
+                assert_eq!(parse_partsupp(&tbl, Format::Tbl).unwrap(), row);
+                let csv = PartSuppCsv::new(row.clone()).to_string();
+                assert_eq!(parse_partsupp(&csv, Format::Csv).unwrap(), row);
+            }
+            let generator = CustomerGenerator::new(sf, 1, 1).with_edge_cases(edge_cases);
+            for row in generator.iter().take(200) {
+                let tbl = row.to_string();
+                let parsed = parse_customer(&tbl, Format::Tbl).unwrap();
+                assert_eq!(parsed, row);
+                assert_eq!(parsed.to_string(), tbl);
+                let csv = CustomerCsv::new(row.clone()).to_string();
+                assert_eq!(parse_customer(&csv, Format::Csv).unwrap(), row);
+            }
+            let generator = OrderGenerator::new(sf, 1, 1).with_edge_cases(edge_cases);
+            for row in generator.iter().take(200) {
+                let tbl = row.to_string();
+                assert_eq!(parse_order(&tbl, Format::Tbl).unwrap(), row);
+                let csv = OrderCsv::new(row.clone()).to_string();
+                assert_eq!(parse_order(&csv, Format::Csv).unwrap(), row);
+            }
+            let generator = LineItemGenerator::new(sf, 1, 1).with_edge_cases(edge_cases);
+            for row in generator.iter().take(200) {
+                let tbl = row.to_string();
+                assert_eq!(parse_lineitem(&tbl, Format::Tbl).unwrap(), row);
+                let csv = LineItemCsv::new(row.clone()).to_string();
+                assert_eq!(parse_lineitem(&csv, Format::Csv).unwrap(), row);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let line = "1|155190|7706|1|17|21168.23|0.04|0.02|N|O|1996-03-13|1996-02-12|1996-03-22|DELIVER IN PERSON|TRUCK|egular courts above the|";
+        let lineitem_error =
+            |line: &str| parse_lineitem(line, Format::Tbl).unwrap_err().to_string();
+        // trailing separator and line ending are optional
+        assert!(parse_lineitem(line.trim_end_matches('|'), Format::Tbl).is_ok());
+        assert!(parse_lineitem(&format!("{line}\r\n"), Format::Tbl).is_ok());
+
+        assert_eq!(
+            lineitem_error(&line.replace("1996-03-13", "1996-13-03")),
+            "invalid value '1996-13-03' for l_shipdate: expected a date between 1992-01-01 and 1998-12-31"
+        );
+        assert!(lineitem_error(&line.replace("21168.23", "21168.2")).contains("l_extendedprice"));
+        assert_eq!(
+            lineitem_error(&line.replace("TRUCK", "BIKE")),
+            "invalid value 'BIKE' for l_shipmode: expected REG AIR, AIR, RAIL, TRUCK, MAIL, FOB or SHIP"
+        );
+        assert_eq!(
+            lineitem_error(&line.replace("|N|O|", "|N|X|")),
+            "invalid value 'X' for l_linestatus: expected F or O"
+        );
+        assert_eq!(
+            lineitem_error("1|2|3|"),
+            "wrong number of columns for lineitem: expected 16"
+        );
+        assert_eq!(
+            lineitem_error(&format!("{line}extra|")),
+            "wrong number of columns for lineitem: expected 16"
+        );
+
+        let err = parse_region("0,AFRICA,\"unterminated", Format::Csv).unwrap_err();
+        assert!(err.to_string().contains("closing quote"));
+        assert!(parse_region("0,AFRICA,comment,", Format::Csv).is_err());
+        let err = parse_supplier(
+            "1|Supplier#000000001|addr|17|27-918-3351736|5755.94|comment|",
+            Format::Tbl,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("s_phone"));
+    }
+}
@@ -1,4 +1,12 @@
 //! TPC-H benchmark queries.
+//!
+//! The constants are the query templates of the specification, with the
+//! placeholders `:1`, `:2`, ... of their substitution parameters, and `:s`
+//! of the query stream. [`query_text`] fills them in like qgen, with the
+//! parameters of [`crate::params`], and [`stream_order`] returns the order
+//! of the queries of a query stream.
+
+use crate::params::query_params;
 
 /// TPC-H Pricing Summary Report Query (Q1).
 pub const Q1: &str = r#"
@@ -720,3 +728,220 @@ pub fn query(num: i32) -> Option<&'static str> {
         _ => None,
     }
 }
+
+/// Returns the text of query `num` (1 to 22) of query stream `stream` at
+/// `scale_factor`, with its placeholders replaced by the substitution
+/// parameters
+///
+/// With a `seed`, the parameters are chosen at random, see
+/// [`crate::params`], otherwise they are the validation parameters of the
+/// specification, like those of `qgen -d`. The stream only fills in the
+/// `:s` placeholder. Returns `None` if there is no such query.
+///
+/// # Example
+/// ```
+/// # use tpchgen::queries::query_text;
+/// let q6 = query_text(6, 1.0, 0, None).unwrap();
+/// assert!(q6.contains("l_discount between 0.06 - 0.01 and 0.06 + 0.01"));
+/// ```
+pub fn query_text(num: i32, scale_factor: f64, stream: i32, seed: Option<i64>) -> Option<String> {
+    let template = query(num)?;
+    let params = query_params(num, scale_factor, seed)?;
+    Some(substitute(template, &params.substitutions(), stream))
+}
+
+/// Replaces the placeholders `:1`, `:2`, ... of `template` with
+/// `substitutions` and `:s` with `stream`
+fn substitute(template: &str, substitutions: &[String], stream: i32) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(':') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits > 0 {
+            let index: usize = after[..digits].parse().expect("placeholder number");
+            text.push_str(&substitutions[index - 1]);
+            rest = &after[digits..];
+        } else if let Some(after) = after.strip_prefix('s') {
+            text.push_str(&stream.to_string());
+            rest = after;
+        } else {
+            text.push(':');
+            rest = after;
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+/// Returns the order in which query stream `stream` runs the 22 queries
+///
+/// Stream 0 is the power test and streams 1 and up the streams of the
+/// throughput test. The orders are those of Appendix A of the
+/// specification, and repeat after stream 40, like in qgen.
+///
+/// # Example
+/// ```
+/// # use tpchgen::queries::stream_order;
+/// assert_eq!(stream_order(0)[..4], [14, 2, 9, 20]);
+/// ```
+pub fn stream_order(stream: i32) -> [i32; 22] {
+    STREAM_ORDERS[stream.rem_euclid(STREAM_ORDERS.len() as i32) as usize]
+}
+
+/// Orders of the queries of each query stream (Appendix A)
+const STREAM_ORDERS: [[i32; 22]; 41] = [
+    [
+        14, 2, 9, 20, 6, 17, 18, 8, 21, 13, 3, 22, 16, 4, 11, 15, 1, 10, 19, 5, 7, 12,
+    ],
+    [
+        21, 3, 18, 5, 11, 7, 6, 20, 17, 12, 16, 15, 13, 10, 2, 8, 14, 19, 9, 22, 1, 4,
+    ],
+    [
+        6, 17, 14, 16, 19, 10, 9, 2, 15, 8, 5, 22, 12, 7, 13, 18, 1, 4, 20, 3, 11, 21,
+    ],
+    [
+        8, 5, 4, 6, 17, 7, 1, 18, 22, 14, 9, 10, 15, 11, 20, 2, 21, 19, 13, 16, 12, 3,
+    ],
+    [
+        5, 21, 14, 19, 15, 17, 12, 6, 4, 9, 8, 16, 11, 2, 10, 18, 1, 13, 7, 22, 3, 20,
+    ],
+    [
+        21, 15, 4, 6, 7, 16, 19, 18, 14, 22, 11, 13, 3, 1, 2, 5, 8, 20, 12, 17, 10, 9,
+    ],
+    [
+        10, 3, 15, 13, 6, 8, 9, 7, 4, 11, 22, 18, 12, 1, 5, 16, 2, 14, 19, 20, 17, 21,
+    ],
+    [
+        18, 8, 20, 21, 2, 4, 22, 17, 1, 11, 9, 19, 3, 13, 5, 7, 10, 16, 6, 14, 15, 12,
+    ],
+    [
+        19, 1, 15, 17, 5, 8, 9, 12, 14, 7, 4, 3, 20, 16, 6, 22, 10, 13, 2, 21, 18, 11,
+    ],
+    [
+        8, 13, 2, 20, 17, 3, 6, 21, 18, 11, 19, 10, 15, 4, 22, 1, 7, 12, 9, 14, 5, 16,
+    ],
+    [
+        6, 15, 18, 17, 12, 1, 7, 2, 22, 13, 21, 10, 14, 9, 3, 16, 20, 19, 11, 4, 8, 5,
+    ],
+    [
+        15, 14, 18, 17, 10, 20, 16, 11, 1, 8, 4, 22, 5, 12, 3, 9, 21, 2, 13, 6, 19, 7,
+    ],
+    [
+        1, 7, 16, 17, 18, 22, 12, 6, 8, 9, 11, 4, 2, 5, 20, 21, 13, 10, 19, 3, 14, 15,
+    ],
+    [
+        21, 17, 7, 3, 1, 10, 12, 22, 9, 16, 6, 11, 2, 4, 5, 14, 8, 20, 13, 18, 15, 19,
+    ],
+    [
+        2, 9, 5, 4, 18, 1, 20, 15, 16, 17, 7, 21, 13, 14, 19, 8, 22, 11, 10, 3, 12, 6,
+    ],
+    [
+        16, 9, 17, 8, 14, 11, 10, 12, 6, 21, 7, 3, 15, 5, 22, 20, 1, 13, 19, 2, 4, 18,
+    ],
+    [
+        1, 3, 6, 5, 2, 16, 14, 22, 17, 20, 4, 9, 10, 11, 15, 8, 12, 19, 18, 13, 7, 21,
+    ],
+    [
+        3, 16, 5, 11, 21, 9, 2, 15, 10, 18, 17, 7, 8, 19, 14, 13, 1, 4, 22, 20, 6, 12,
+    ],
+    [
+        14, 4, 13, 5, 21, 11, 8, 6, 3, 17, 2, 20, 1, 19, 10, 9, 12, 18, 15, 7, 22, 16,
+    ],
+    [
+        4, 12, 22, 14, 5, 15, 16, 2, 8, 10, 17, 9, 21, 7, 3, 6, 13, 18, 11, 20, 19, 1,
+    ],
+    [
+        16, 15, 14, 13, 4, 22, 18, 19, 7, 1, 12, 17, 5, 10, 20, 3, 9, 21, 11, 2, 6, 8,
+    ],
+    [
+        20, 14, 21, 12, 15, 17, 4, 19, 13, 10, 11, 1, 16, 5, 18, 7, 8, 22, 9, 6, 3, 2,
+    ],
+    [
+        16, 14, 13, 2, 21, 10, 11, 4, 1, 22, 18, 12, 19, 5, 7, 8, 6, 3, 15, 20, 9, 17,
+    ],
+    [
+        18, 15, 9, 14, 12, 2, 8, 11, 22, 21, 16, 1, 6, 17, 5, 10, 19, 4, 20, 13, 3, 7,
+    ],
+    [
+        7, 3, 10, 14, 13, 21, 18, 6, 20, 4, 9, 8, 22, 15, 2, 1, 5, 12, 19, 17, 11, 16,
+    ],
+    [
+        18, 1, 13, 7, 16, 10, 14, 2, 19, 5, 21, 11, 22, 15, 8, 17, 20, 3, 4, 12, 6, 9,
+    ],
+    [
+        13, 2, 22, 5, 11, 21, 20, 14, 7, 10, 4, 9, 19, 18, 6, 3, 1, 8, 15, 12, 17, 16,
+    ],
+    [
+        14, 17, 21, 8, 2, 9, 6, 4, 5, 13, 22, 7, 15, 3, 1, 18, 16, 11, 10, 12, 20, 19,
+    ],
+    [
+        10, 22, 1, 12, 13, 18, 21, 20, 2, 14, 16, 7, 15, 3, 4, 17, 5, 19, 6, 8, 9, 11,
+    ],
+    [
+        10, 8, 9, 18, 12, 6, 1, 5, 20, 11, 17, 4, 13, 14, 3, 2, 7, 21, 22, 19, 16, 15,
+    ],
+    [
+        5, 15, 17, 7, 20, 14, 9, 4, 18, 3, 12, 6, 11, 2, 13, 8, 21, 22, 1, 16, 19, 10,
+    ],
+    [
+        3, 7, 14, 15, 6, 5, 21, 20, 18, 10, 4, 16, 19, 1, 13, 9, 8, 17, 11, 12, 22, 2,
+    ],
+    [
+        13, 15, 17, 1, 22, 11, 3, 4, 7, 20, 14, 21, 9, 8, 2, 18, 16, 6, 10, 12, 5, 19,
+    ],
+    [
+        15, 22, 5, 12, 16, 17, 10, 2, 4, 6, 3, 20, 21, 13, 9, 1, 18, 11, 8, 7, 19, 14,
+    ],
+    [
+        9, 21, 7, 16, 20, 10, 1, 4, 13, 11, 8, 12, 18, 2, 14, 3, 19, 17, 15, 6, 22, 5,
+    ],
+    [
+        7, 14, 1, 13, 19, 2, 22, 20, 6, 18, 12, 4, 8, 10, 9, 16, 21, 15, 5, 17, 11, 3,
+    ],
+    [
+        2, 19, 18, 22, 7, 16, 12, 14, 4, 20, 8, 13, 21, 10, 5, 15, 1, 11, 3, 6, 17, 9,
+    ],
+    [
+        1, 20, 5, 22, 14, 9, 6, 12, 10, 21, 7, 11, 16, 4, 17, 15, 18, 19, 8, 2, 13, 3,
+    ],
+    [
+        3, 17, 12, 15, 11, 13, 10, 7, 6, 5, 20, 16, 22, 18, 8, 2, 4, 14, 21, 1, 19, 9,
+    ],
+    [
+        2, 15, 21, 13, 14, 9, 10, 16, 17, 19, 7, 8, 1, 5, 4, 12, 22, 20, 6, 11, 18, 3,
+    ],
+    [
+        4, 8, 1, 21, 20, 16, 3, 13, 14, 9, 5, 12, 11, 15, 18, 19, 7, 22, 17, 10, 6, 2,
+    ],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_orders_are_permutations() {
+        for order in STREAM_ORDERS {
+            let mut sorted = order;
+            sorted.sort();
+            assert_eq!(sorted, std::array::from_fn(|i| i as i32 + 1));
+        }
+        assert_eq!(stream_order(41), stream_order(0));
+    }
+
+    #[test]
+    fn fills_in_placeholders() {
+        for num in 1..=22 {
+            let text = query_text(num, 1.0, 3, Some(1)).unwrap();
+            assert!(!text.contains(':'), "Q{num}: {text}");
+        }
+        let q15 = query_text(15, 1.0, 3, None).unwrap();
+        assert!(q15.contains("create view revenue3 (supplier_no, total_revenue)"));
+        assert!(q15.contains("l_shipdate >= date '1996-01-01'"));
+        let q16 = query_text(16, 1.0, 0, None).unwrap();
+        assert!(q16.contains("p_size in (49, 14, 23, 45, 19, 3, 36, 9)"));
+        assert_eq!(query_text(0, 1.0, 0, None), None);
+    }
+}
@@ -2,6 +2,36 @@
 
 use crate::{distribution::Distribution, text::TextPool};
 use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Which boundary of its range a value takes in edge case mode, see
+/// [`LineItemGenerator::with_edge_cases`](crate::generators::LineItemGenerator::with_edge_cases)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Extreme {
+    Min,
+    Max,
+}
+
+impl Extreme {
+    /// Returns the extreme for the row with the given (1 based) index: odd
+    /// rows use the maximum and even rows the minimum values
+    pub(crate) fn for_row(index: i64) -> Self {
+        if index % 2 == 1 {
+            Self::Max
+        } else {
+            Self::Min
+        }
+    }
+
+    /// Returns `min` or `max` depending on this extreme
+    pub(crate) fn pick<T>(self, min: T, max: T) -> T {
+        match self {
+            Self::Min => min,
+            Self::Max => max,
+        }
+    }
+}
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RowRandomInt {
@@ -57,6 +87,18 @@ impl RowRandomInt {
         lower_bound + value
     }
 
+    /// Returns a random value between lower and upper bounds (both
+    /// inclusive), like [`Self::next_int`] but with 64-bit bounds as in
+    /// dbgen, for the keys of tables with more than `i32::MAX` rows.
+    pub fn next_long(&mut self, lower_bound: i64, upper_bound: i64) -> i64 {
+        let _ = self.next_rand();
+
+        let range = (upper_bound - lower_bound + 1) as f64;
+        let value = ((self.seed as f64 / Self::MODULUS as f64) * range) as i64;
+
+        lower_bound + value
+    }
+
     /// Instantiates a new seed for the next random value.
     pub fn next_rand(&mut self) -> i64 {
         self.seed = (self.seed * Self::MULTIPLIER) % Self::MODULUS;
@@ -129,14 +171,19 @@ impl RowRandomLong {
     pub fn next_long(&mut self, lower_bound: i64, upper_bound: i64) -> i64 {
         self.next_rand();
 
-        let value_in_range = (self.seed.abs()) % (upper_bound - lower_bound + 1);
+        let range = (upper_bound - lower_bound + 1) as u64;
+        let value_in_range = (self.seed.unsigned_abs() % range) as i64;
 
         lower_bound + value_in_range
     }
 
     /// Instantiates a new seed for the next random value.
     fn next_rand(&mut self) -> i64 {
-        self.seed = (self.seed.wrapping_mul(Self::MULTIPLIER)) + Self::INCREMENT;
+        // the seed wraps around like the 64-bit seeds of dbgen
+        self.seed = self
+            .seed
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(Self::INCREMENT);
         self.usage += 1;
         self.seed
     }
@@ -166,7 +213,8 @@ impl RowRandomLong {
 
         while count > 0 {
             if count % 2 != 0 {
-                self.seed = (multiplier * self.seed) % Self::MODULUS_32;
+                // the seed has 64 bits, so this wraps around as in dbgen
+                self.seed = multiplier.wrapping_mul(self.seed) % Self::MODULUS_32;
             }
 
             // Integer division, truncates
@@ -176,6 +224,183 @@ impl RowRandomLong {
     }
 }
 
+/// Defines [`ColumnSeeds`] with the seed of each column
+macro_rules! define_column_seeds {
+    ($($(#[$doc:meta])* $column:ident => $seed:expr,)+) => {
+        /// The base seeds of the random values of each column
+        ///
+        /// Every column, or part of a column such as the junk of the "Customer
+        /// Complaints" comments of SUPPLIER, has its own random stream, which
+        /// starts at its seed. [`Self::SPEC`], the default, has the seeds of
+        /// the specification, as used by dbgen.
+        ///
+        /// Other seeds regenerate individual columns with other randomness,
+        /// see [`LineItemGenerator::with_column_seeds`]. A seed must be
+        /// between 1 and 2^31 - 2: the streams of other seeds repeat a single
+        /// value or are not those of the random number generator.
+        ///
+        /// ```
+        /// # use tpchgen::generators::CustomerGenerator;
+        /// # use tpchgen::random::ColumnSeeds;
+        /// let seeds = ColumnSeeds {
+        ///     c_acctbal: 12345,
+        ///     ..ColumnSeeds::default()
+        /// };
+        /// let customer = CustomerGenerator::new(0.01, 1, 1)
+        ///     .with_column_seeds(seeds)
+        ///     .iter()
+        ///     .next()
+        ///     .unwrap();
+        /// assert_eq!(customer.c_custkey, 1);
+        /// assert_ne!(customer.c_acctbal.to_string(), "711.56");
+        /// ```
+        ///
+        /// [`LineItemGenerator::with_column_seeds`]: crate::generators::LineItemGenerator::with_column_seeds
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct ColumnSeeds {
+            $($(#[$doc])* pub $column: i64,)+
+        }
+
+        impl ColumnSeeds {
+            /// The seeds of the specification
+            pub const SPEC: Self = Self {
+                $($column: $seed,)+
+            };
+
+            /// Returns the seeds of another dataset, each seed moved along its
+            /// random stream by the same amount for each step of `offset`
+            ///
+            /// The values of each column are still uniformly distributed over
+            /// the same ranges, but differ from those of the other offsets. An
+            /// offset of 0 returns the seeds unchanged.
+            pub fn with_offset(self, offset: i64) -> Self {
+                if offset == 0 {
+                    return self;
+                }
+                // the streams have a period of MODULUS - 1, and the stride is
+                // coprime with it, so each offset moves the seeds differently
+                let period = RowRandomInt::MODULUS - 1;
+                let steps = (offset.rem_euclid(period) as i128 * Self::OFFSET_STRIDE as i128
+                    % period as i128) as i64;
+                let offset_seed = |seed: i64| {
+                    let mut random = RowRandomInt::new(seed, 1);
+                    random.advance_seed(steps);
+                    random.seed
+                };
+                Self {
+                    $($column: offset_seed(self.$column),)+
+                }
+            }
+        }
+    };
+}
+
+define_column_seeds! {
+    /// `n_comment`
+    n_comment => 606179079,
+    /// `r_comment`
+    r_comment => 1500869201,
+    /// `p_name`
+    p_name => 709314158,
+    /// `p_mfgr`, and `p_brand` by the manufacturer
+    p_mfgr => 1,
+    /// `p_brand` within the manufacturer
+    p_brand => 46831694,
+    /// `p_type`
+    p_type => 1841581359,
+    /// `p_size`
+    p_size => 1193163244,
+    /// `p_container`
+    p_container => 727633698,
+    /// `p_comment`
+    p_comment => 804159733,
+    /// `s_address`
+    s_address => 706178559,
+    /// `s_nationkey`
+    s_nationkey => 110356601,
+    /// `s_phone`
+    s_phone => 884434366,
+    /// `s_acctbal`
+    s_acctbal => 962338209,
+    /// `s_comment`
+    s_comment => 1341315363,
+    /// The suppliers with "Customer Complaints" or "Customer Recommends" in
+    /// `s_comment`
+    s_bbb_row => 202794285,
+    /// The junk text between "Customer" and "Complaints" or "Recommends"
+    s_bbb_junk => 263032577,
+    /// The position of the "Customer ..." text in `s_comment`
+    s_bbb_offset => 715851524,
+    /// Whether the text is "Complaints" or "Recommends"
+    s_bbb_type => 753643799,
+    /// `ps_availqty`
+    ps_availqty => 1671059989,
+    /// `ps_supplycost`
+    ps_supplycost => 1051288424,
+    /// `ps_comment`
+    ps_comment => 1961692154,
+    /// `c_address`
+    c_address => 881155353,
+    /// `c_nationkey`
+    c_nationkey => 1489529863,
+    /// `c_phone`
+    c_phone => 1521138112,
+    /// `c_acctbal`
+    c_acctbal => 298370230,
+    /// `c_mktsegment`
+    c_mktsegment => 1140279430,
+    /// `c_comment`
+    c_comment => 1335826707,
+    /// `o_orderdate`
+    o_orderdate => 1066728069,
+    /// The number of line items of each order
+    o_line_count => 1434868289,
+    /// `o_custkey`
+    o_custkey => 851767375,
+    /// `o_orderpriority`
+    o_orderpriority => 591449447,
+    /// `o_clerk`
+    o_clerk => 1171034773,
+    /// `o_comment`
+    o_comment => 276090261,
+    /// `l_quantity`
+    l_quantity => 209208115,
+    /// `l_discount`
+    l_discount => 554590007,
+    /// `l_tax`
+    l_tax => 721958466,
+    /// `l_partkey`
+    l_partkey => 1808217256,
+    /// `l_suppkey`, the number of the supplier among those of the part
+    l_suppkey => 2095021727,
+    /// `l_shipdate`
+    l_shipdate => 1769349045,
+    /// `l_commitdate`
+    l_commitdate => 904914315,
+    /// `l_receiptdate`
+    l_receiptdate => 373135028,
+    /// `l_returnflag`
+    l_returnflag => 717419739,
+    /// `l_shipinstruct`
+    l_shipinstruct => 1371272478,
+    /// `l_shipmode`
+    l_shipmode => 675466456,
+    /// `l_comment`
+    l_comment => 1095462486,
+}
+
+impl Default for ColumnSeeds {
+    fn default() -> Self {
+        Self::SPEC
+    }
+}
+
+impl ColumnSeeds {
+    /// The steps each seed is moved along its stream per unit of offset, a
+    /// prime
+    const OFFSET_STRIDE: i64 = 1_000_000_007;
+}
+
 /// Random number generator for bounded values.
 #[derive(Default, Debug, Clone, Copy)]
 pub struct RandomBoundedInt {
@@ -209,6 +434,12 @@ impl RandomBoundedInt {
         }
     }
 
+    /// Replaces the seed, before any value is generated
+    pub(crate) fn with_seed(mut self, seed: i64) -> Self {
+        self.random_int.seed = seed;
+        self
+    }
+
     /// Returns a random value between the lower and upper bounds (both inclusive).
     pub fn next_value(&mut self) -> i32 {
         self.random_int.next_int(self.lower_bound, self.upper_bound)
@@ -266,6 +497,13 @@ impl RandomBoundedLong {
         }
     }
 
+    /// Replaces the seed, before any value is generated
+    pub(crate) fn with_seed(mut self, seed: i64) -> Self {
+        self.random_long.seed = seed;
+        self.random_int.seed = seed;
+        self
+    }
+
     /// Returns a random value between the lower and upper bounds (both inclusive).
     pub fn next_value(&mut self) -> i64 {
         if self.use_64bits {
@@ -273,7 +511,7 @@ impl RandomBoundedLong {
                 .next_long(self.lower_bound, self.upper_bound)
         } else {
             self.random_int
-                .next_int(self.lower_bound as i32, self.upper_bound as i32) as i64
+                .next_long(self.lower_bound, self.upper_bound)
         }
     }
 
@@ -295,12 +533,79 @@ impl RandomBoundedLong {
     }
 }
 
+/// Skews uniformly distributed values to follow a Zipf distribution
+///
+/// A value drawn uniformly from `[lower, upper]` is mapped to the value with
+/// rank `k`, `lower` having rank 1, where `k` follows a bounded power law
+/// with density proportional to `k^-theta`, a continuous approximation of
+/// the Zipf distribution with exponent `theta`. Larger exponents give more
+/// skew: `0` keeps the values uniform and `1` is the classic Zipf law.
+///
+/// Since each skewed value is computed from exactly one uniform value, the
+/// random streams are consumed as without skew, so the skewed values do not
+/// depend on how a table is split into parts either.
+///
+/// ```
+/// # use tpchgen::random::Zipf;
+/// let zipf = Zipf::new(1.0);
+/// let skewed: Vec<_> = (1..=1000).map(|v| zipf.skew(v, 1, 1000)).collect();
+/// // 10% of the values are 1, the most frequent value
+/// assert_eq!(skewed.iter().filter(|&&v| v == 1).count(), 100);
+/// // and half of them are in the first 3% of the range
+/// assert_eq!(skewed[499], 31);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zipf {
+    theta: f64,
+}
+
+impl Zipf {
+    /// Creates a Zipf distribution with exponent `theta`
+    ///
+    /// # Panics
+    ///
+    /// If `theta` is negative or not finite.
+    pub fn new(theta: f64) -> Self {
+        assert!(
+            theta.is_finite() && theta >= 0.0,
+            "invalid Zipf exponent {theta}"
+        );
+        Self { theta }
+    }
+
+    /// Returns the exponent of the distribution
+    pub fn theta(&self) -> f64 {
+        self.theta
+    }
+
+    /// Returns the skewed value for `value`, drawn uniformly from `[lower,
+    /// upper]`
+    pub fn skew(&self, value: i64, lower: i64, upper: i64) -> i64 {
+        if self.theta == 0.0 || upper <= lower {
+            return value;
+        }
+        let count = (upper - lower + 1) as f64;
+        // the middle of the value's share of [0, 1)
+        let uniform = ((value - lower) as f64 + 0.5) / count;
+        // inverse of the CDF of the density k^-theta on [1, count + 1)
+        let rank = if (self.theta - 1.0).abs() < 1e-9 {
+            (count + 1.0).powf(uniform)
+        } else {
+            let exponent = 1.0 - self.theta;
+            (((count + 1.0).powf(exponent) - 1.0) * uniform + 1.0).powf(1.0 / exponent)
+        };
+        lower + (rank as i64).clamp(1, count as i64) - 1
+    }
+}
+
 /// Generates random alphanumeric strings
 #[derive(Debug)]
 pub struct RandomAlphaNumeric {
     inner: RowRandomInt,
     min_length: i32,
     max_length: i32,
+    /// The 64 characters used instead of [`Self::ALPHA_NUMERIC`], if any
+    alphabet: Option<Arc<[char]>>,
 }
 
 impl RandomAlphaNumeric {
@@ -327,18 +632,44 @@ impl RandomAlphaNumeric {
             inner: RowRandomInt::new(seed, Self::USAGE_PER_ROW * seeds_per_row),
             min_length,
             max_length,
+            alphabet: None,
         }
     }
 
+    /// Uses the 64 characters of `alphabet` instead of ASCII letters and
+    /// digits, see [`TextPool::new_extended`]
+    pub(crate) fn with_alphabet(mut self, alphabet: Option<Arc<[char]>>) -> Self {
+        debug_assert!(alphabet.as_ref().is_none_or(|a| a.len() == 64));
+        self.alphabet = alphabet;
+        self
+    }
+
     /// Returns the next string as a [`RandomAlphaNumericInstance`], which can
     /// generate the string on demand.
     pub fn next_value(&mut self) -> RandomAlphaNumericInstance {
-        let length = self.inner.next_int(self.min_length, self.max_length) as usize;
+        self.next_value_or_extreme(None)
+    }
 
-        RandomAlphaNumericInstance {
-            length,
-            snapshot: self.inner,
-        }
+    /// Like [`Self::next_value`], but with the minimum or maximum length
+    /// if `extreme` is set
+    pub(crate) fn next_value_or_extreme(
+        &mut self,
+        extreme: Option<Extreme>,
+    ) -> RandomAlphaNumericInstance {
+        let length = self.inner.next_int(self.min_length, self.max_length);
+        let length = extreme.map_or(length, |e| e.pick(self.min_length, self.max_length));
+
+        let length = length as usize;
+        let snapshot = self.inner;
+        let value = match &self.alphabet {
+            None => AlphaNumericValue::Generated { length, snapshot },
+            Some(alphabet) => AlphaNumericValue::Extended {
+                length,
+                snapshot,
+                alphabet: Arc::clone(alphabet),
+            },
+        };
+        RandomAlphaNumericInstance { value }
     }
 
     /// Advance the inner random number generator by the specified number of rows.
@@ -353,40 +684,109 @@ impl RandomAlphaNumeric {
 
 /// A random alphanumeric string. To avoid allocations
 /// the string is created on demand with the Display implementation.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Strings parsed from existing data (see [`crate::parse`]) can not be
+/// recreated from a random number generator, so they hold their text instead.
+#[derive(Debug, Clone)]
 pub struct RandomAlphaNumericInstance {
-    length: usize,
-    /// snapshot of the random number generator
-    snapshot: RowRandomInt,
+    value: AlphaNumericValue,
+}
+
+#[derive(Debug, Clone)]
+enum AlphaNumericValue {
+    Generated {
+        length: usize,
+        /// snapshot of the random number generator
+        snapshot: RowRandomInt,
+    },
+    /// Like `Generated`, with the characters of an extended text pool
+    Extended {
+        length: usize,
+        snapshot: RowRandomInt,
+        alphabet: Arc<[char]>,
+    },
+    Parsed(Box<str>),
+}
+
+/// Returns the indexes in the alphabet of the `length` characters of the
+/// string generated from `snapshot`
+fn alpha_numeric_indexes(length: usize, snapshot: RowRandomInt) -> impl Iterator<Item = usize> {
+    let mut generator = snapshot;
+    let mut char_index = 0;
+    (0..length).map(move |i| {
+        if i % 5 == 0 {
+            char_index = generator.next_int(0, i32::MAX) as i64;
+        }
+        let char_pos = (char_index & 0x3f) as usize;
+        char_index >>= 6;
+        char_pos
+    })
+}
+
+impl RandomAlphaNumericInstance {
+    /// Create an instance that displays `text`
+    pub(crate) fn from_text(text: &str) -> Self {
+        Self {
+            value: AlphaNumericValue::Parsed(text.into()),
+        }
+    }
+}
+
+/// Instances are equal if they display the same string
+impl PartialEq for RandomAlphaNumericInstance {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.value, &other.value) {
+            (
+                AlphaNumericValue::Generated { length, snapshot },
+                AlphaNumericValue::Generated {
+                    length: other_length,
+                    snapshot: other_snapshot,
+                },
+            ) if length == other_length && snapshot == other_snapshot => true,
+            (AlphaNumericValue::Parsed(text), AlphaNumericValue::Parsed(other_text)) => {
+                text == other_text
+            }
+            _ => self.to_string() == other.to_string(),
+        }
+    }
 }
 
+impl Eq for RandomAlphaNumericInstance {}
+
 impl Display for RandomAlphaNumericInstance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (length, snapshot) = match &self.value {
+            AlphaNumericValue::Generated { length, snapshot } => (*length, *snapshot),
+            AlphaNumericValue::Extended {
+                length,
+                snapshot,
+                alphabet,
+            } => {
+                let text: String = alpha_numeric_indexes(*length, *snapshot)
+                    .map(|i| alphabet[i])
+                    .collect();
+                return f.write_str(&text);
+            }
+            AlphaNumericValue::Parsed(text) => return f.write_str(text),
+        };
+
         // Use up to  64 bytes of a stack buffer for small strings to avoid
         // allocation, and heap allocation for larger ones.
         let mut stack_buffer = [0u8; 64];
         let mut heap_buffer = Vec::new();
 
-        let buffer = if self.length <= stack_buffer.len() {
-            &mut stack_buffer[0..self.length]
+        let buffer = if length <= stack_buffer.len() {
+            &mut stack_buffer[0..length]
         } else {
-            heap_buffer.resize(self.length, 0);
+            heap_buffer.resize(length, 0);
             &mut heap_buffer
         };
 
-        let mut generator = self.snapshot; // copy to for mutation
-
-        let mut char_index = 0;
-        // todo remove
-        #[allow(clippy::needless_range_loop)]
-        for i in 0..self.length {
-            if i % 5 == 0 {
-                char_index = generator.next_int(0, i32::MAX) as i64;
-            }
-
-            let char_pos = (char_index & 0x3f) as usize;
-            buffer[i] = RandomAlphaNumeric::ALPHA_NUMERIC[char_pos];
-            char_index >>= 6;
+        for (byte, char_pos) in buffer
+            .iter_mut()
+            .zip(alpha_numeric_indexes(length, snapshot))
+        {
+            *byte = RandomAlphaNumeric::ALPHA_NUMERIC[char_pos];
         }
         // Safety: only pushed ascii characters into the buffer
         let s = unsafe { std::str::from_utf8_unchecked(buffer) };
@@ -448,6 +848,18 @@ pub struct PhoneNumberInstance {
     local3: i32,
 }
 
+impl PhoneNumberInstance {
+    /// Create a phone number from its four parts
+    pub(crate) fn new(country_code: i32, local1: i32, local2: i32, local3: i32) -> Self {
+        Self {
+            country_code,
+            local1,
+            local2,
+            local3,
+        }
+    }
+}
+
 impl Display for PhoneNumberInstance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -495,6 +907,64 @@ impl<'a> RandomString<'a> {
     }
 }
 
+/// Fetches random values of a column with a fixed set of values, e.g. a
+/// [`ShipMode`](crate::generators::ShipMode), by the index of their text in a
+/// distribution
+#[derive(Debug, Clone)]
+pub struct RandomEnum<'a, T> {
+    inner: RowRandomInt,
+    distribution: &'a Distribution,
+    /// The value of each value of the distribution
+    values: Vec<T>,
+}
+
+impl<'a, T> RandomEnum<'a, T>
+where
+    T: Copy + FromStr,
+    T::Err: Display,
+{
+    /// Panics if a value of `distribution` is not a `T`, which
+    /// [`Distributions::try_load`](crate::distribution::Distributions::try_load)
+    /// checks
+    pub fn new(seed: i64, distribution: &'a Distribution) -> Self {
+        Self::new_with_expected_row_count(seed, distribution, 1)
+    }
+
+    pub fn new_with_expected_row_count(
+        seed: i64,
+        distribution: &'a Distribution,
+        seeds_per_row: i32,
+    ) -> Self {
+        let values = distribution
+            .get_values()
+            .iter()
+            .map(|value| {
+                value
+                    .parse()
+                    .unwrap_or_else(|e| panic!("{e} in distribution {}", distribution.name()))
+            })
+            .collect();
+        Self {
+            inner: RowRandomInt::new(seed, seeds_per_row),
+            distribution,
+            values,
+        }
+    }
+
+    pub fn next_value(&mut self) -> T {
+        self.values[self.distribution.random_index(&mut self.inner)]
+    }
+
+    /// Advance the inner random number generator by the given number of rows.
+    pub fn advance_rows(&mut self, row_count: i64) {
+        self.inner.advance_rows(row_count);
+    }
+
+    pub fn row_finished(&mut self) {
+        self.inner.row_finished();
+    }
+}
+
 /// Generates sequences of random sequence of strings from a distribution
 #[derive(Debug)]
 pub struct RandomStringSequence<'a> {
@@ -538,7 +1008,7 @@ impl<'a> RandomStringSequence<'a> {
 
         // Keep only the first 'count' values, and join them with spaces
         values.truncate(self.count as usize);
-        StringSequenceInstance { values }
+        StringSequenceInstance::from_values(values)
     }
 
     /// Advance the inner random number generator by the given number of rows.
@@ -564,6 +1034,13 @@ pub struct StringSequenceInstance<'a> {
     values: Vec<&'a str>,
 }
 
+impl<'a> StringSequenceInstance<'a> {
+    /// Create an instance that displays `values` separated by spaces
+    pub(crate) fn from_values(values: Vec<&'a str>) -> Self {
+        Self { values }
+    }
+}
+
 impl Display for StringSequenceInstance<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut iter = self.values.iter();
@@ -609,10 +1086,17 @@ impl<'a> RandomText<'a> {
     }
 
     pub fn next_value(&mut self) -> &'a str {
+        self.next_value_or_extreme(None)
+    }
+
+    /// Like [`Self::next_value`], but with the minimum or maximum length
+    /// if `extreme` is set
+    pub(crate) fn next_value_or_extreme(&mut self, extreme: Option<Extreme>) -> &'a str {
         let offset = self
             .inner
             .next_int(0, self.text_pool.size() - self.max_length);
         let length = self.inner.next_int(self.min_length, self.max_length);
+        let length = extreme.map_or(length, |e| e.pick(self.min_length, self.max_length));
 
         self.text_pool.text(offset, offset + length)
     }
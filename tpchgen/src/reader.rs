@@ -0,0 +1,176 @@
+//! [`Read`] implementations that encode generated rows as TBL or CSV.
+//!
+//! [`TblReader`] and [`CsvReader`] wrap a generator (or any iterator of rows)
+//! and fill the caller's buffer with the encoded bytes, so generated data can
+//! be handed to any API that takes a reader, such as uploads, hashing or
+//! parsers. Rows are only generated as the reader is read.
+//!
+//! # Example
+//! ```
+//! # use tpchgen::generators::NationGenerator;
+//! # use tpchgen::reader::{CsvReader, TblReader};
+//! # use std::io::Read;
+//! let mut tbl = String::new();
+//! TblReader::new(NationGenerator::default())
+//!     .read_to_string(&mut tbl)
+//!     .unwrap();
+//! assert!(tbl.starts_with("0|ALGERIA|0| haggle. carefully final deposits detect slyly agai|\n"));
+//!
+//! let mut csv = String::new();
+//! CsvReader::new(NationGenerator::default())
+//!     .read_to_string(&mut csv)
+//!     .unwrap();
+//! assert!(csv.starts_with("n_nationkey,n_name,n_regionkey,n_comment\n0,ALGERIA,"));
+//! ```
+
+use crate::csv::CsvRow;
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+
+/// Minimum number of bytes encoded at a time, so that small reads do not
+/// encode one row at a time
+const MIN_ENCODED_BYTES: usize = 8 * 1024;
+
+/// A [`Read`] of rows in TBL format, the `Display` format of the row structs
+/// followed by a newline.
+pub struct TblReader<I> {
+    lines: Lines<I>,
+}
+
+impl<I> TblReader<I>
+where
+    I: Iterator<Item: Display>,
+{
+    /// Create a reader of `rows`, e.g. a generator
+    pub fn new(rows: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            lines: Lines::new(rows.into_iter(), Vec::new()),
+        }
+    }
+}
+
+impl<I> Read for TblReader<I>
+where
+    I: Iterator<Item: Display>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.lines
+            .read(buf, |buffer, row| writeln!(buffer, "{row}"))
+    }
+}
+
+/// A [`Read`] of rows in CSV format, starting with the CSV header, see the
+/// [`csv`](crate::csv) module.
+pub struct CsvReader<I> {
+    lines: Lines<I>,
+}
+
+impl<I> CsvReader<I>
+where
+    I: Iterator<Item: CsvRow>,
+{
+    /// Create a reader of `rows`, e.g. a generator
+    pub fn new(rows: impl IntoIterator<IntoIter = I>) -> Self {
+        let header = format!("{}\n", I::Item::csv_header());
+        Self {
+            lines: Lines::new(rows.into_iter(), header.into_bytes()),
+        }
+    }
+}
+
+impl<I> Read for CsvReader<I>
+where
+    I: Iterator<Item: CsvRow>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.lines
+            .read(buf, |buffer, row| writeln!(buffer, "{}", row.into_csv()))
+    }
+}
+
+/// Rows encoded into a buffer ahead of the reads
+struct Lines<I> {
+    rows: I,
+    /// Encoded bytes, of which those from `pos` on have not been read yet
+    buffer: Vec<u8>,
+    pos: usize,
+}
+
+impl<I: Iterator> Lines<I> {
+    fn new(rows: I, buffer: Vec<u8>) -> Self {
+        Self {
+            rows,
+            buffer,
+            pos: 0,
+        }
+    }
+
+    /// Copies encoded bytes to `buf`, encoding rows with `encode` when no
+    /// bytes are left
+    fn read<F>(&mut self, buf: &mut [u8], mut encode: F) -> io::Result<usize>
+    where
+        F: FnMut(&mut Vec<u8>, I::Item) -> io::Result<()>,
+    {
+        if self.pos == self.buffer.len() {
+            self.buffer.clear();
+            self.pos = 0;
+            let target = buf.len().max(MIN_ENCODED_BYTES);
+            while self.buffer.len() < target {
+                let Some(row) = self.rows.next() else {
+                    break;
+                };
+                encode(&mut self.buffer, row)?;
+            }
+        }
+        let len = buf.len().min(self.buffer.len() - self.pos);
+        buf[..len].copy_from_slice(&self.buffer[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv::LineItemCsv;
+    use crate::generators::{LineItemGenerator, SupplierGenerator};
+
+    /// Reads all of `reader` with reads of at most `len` bytes
+    fn read_all(mut reader: impl Read, len: usize) -> String {
+        let mut output = Vec::new();
+        let mut buf = vec![0; len];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                return String::from_utf8(output).unwrap();
+            }
+            output.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    #[test]
+    fn test_tbl_reader() {
+        let generator = || LineItemGenerator::new(0.001, 1, 1);
+        let expected: String = generator().iter().map(|row| format!("{row}\n")).collect();
+        for len in [7, 4096, 100_000] {
+            assert_eq!(read_all(TblReader::new(generator()), len), expected);
+        }
+    }
+
+    #[test]
+    fn test_csv_reader() {
+        let generator = || LineItemGenerator::new(0.001, 1, 1);
+        let mut expected = format!("{}\n", LineItemCsv::header());
+        for row in generator() {
+            expected.push_str(&format!("{}\n", LineItemCsv::new(row)));
+        }
+        for len in [7, 4096, 100_000] {
+            assert_eq!(read_all(CsvReader::new(generator()), len), expected);
+        }
+
+        // any iterator of rows can be read
+        let rows = SupplierGenerator::new(0.001, 1, 1).iter().take(2);
+        let csv = read_all(CsvReader::new(rows), 16);
+        assert_eq!(csv.lines().count(), 3);
+    }
+}
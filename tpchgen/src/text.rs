@@ -6,13 +6,18 @@
 //! <https://github.com/trinodb/tpch/blob/master/src/main/java/io/trino/tpch/TextPool.java>
 
 use crate::{distribution::Distributions, random::RowRandomInt};
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 
 /// Pool of random text that follows TPC-H grammar.
 #[derive(Debug, Clone)]
 pub struct TextPool {
     /// Bytes making up the text pool, exact size.
     text: Vec<u8>,
+    /// Whether the text is ASCII, so chunks never split a character
+    ascii: bool,
+    /// The 64 characters of addresses of an extended text pool, see
+    /// [`Self::new_extended`]
+    alphabet: Option<Arc<[char]>>,
 }
 
 /// The default global text pool is lazily initialized once and shared across
@@ -21,9 +26,62 @@ static DEFAULT_TEXT_POOL: OnceLock<TextPool> = OnceLock::new();
 
 impl TextPool {
     /// Default text pool size.
-    const DEFAULT_TEXT_POOL_SIZE: i32 = 300 * 1024 * 1024;
+    pub const DEFAULT_TEXT_POOL_SIZE: i32 = 300 * 1024 * 1024;
     /// Maximum length of a sentence in the text.
     const MAX_SENTENCE_LENGTH: i32 = 256;
+    /// Number of characters of the alphabet of addresses
+    const ALPHABET_SIZE: usize = 64;
+
+    /// Default words of [`Self::new_extended`], in a range of scripts with
+    /// characters of 2, 3 and 4 bytes in UTF-8
+    pub const EXTENDED_WORDS: &'static [&'static str] = &[
+        "café",
+        "naïve",
+        "über",
+        "straße",
+        "smörgåsbord",
+        "jalapeño",
+        "façade",
+        "crème",
+        "Ångström",
+        "piñata",
+        "øl",
+        "żółw",
+        "čaj",
+        "ğüneş",
+        "Ελλάδα",
+        "καλημέρα",
+        "λόγος",
+        "привет",
+        "мир",
+        "данные",
+        "запрос",
+        "шёпот",
+        "שלום",
+        "נתונים",
+        "مرحبا",
+        "بيانات",
+        "नमस्ते",
+        "डेटा",
+        "ਪੰਜਾਬ",
+        "வணக்கம்",
+        "ขอบคุณ",
+        "ქართული",
+        "Հայաստան",
+        "こんにちは",
+        "データ",
+        "東京",
+        "数据",
+        "查询",
+        "北京",
+        "데이터",
+        "서울",
+        "🚚",
+        "📦",
+        "✓",
+        "€",
+        "𝔘𝔫𝔦𝔠𝔬𝔡𝔢",
+    ];
 
     /// Returns the default text pool or initializes for the first time if
     /// that's not already the case.
@@ -46,7 +104,115 @@ impl TextPool {
         }
         text_bytes.truncate(size as usize);
 
-        Self { text: text_bytes }
+        Self {
+            ascii: text_bytes.is_ascii(),
+            text: text_bytes,
+            alphabet: None,
+        }
+    }
+
+    /// Returns a new text pool of about `size` bytes whose words are from
+    /// `words` instead of the distributions, e.g. [`Self::EXTENDED_WORDS`],
+    /// to test the handling of non-ASCII text
+    ///
+    /// The text follows the grammar of the distributions, with each of their
+    /// words replaced by one of `words` chosen by a hash of the word, so the
+    /// same word is always replaced the same way and the frequencies of the
+    /// words follow those of the distributions. Comments have the same
+    /// length in bytes as with the default pool, less any partial character
+    /// at either end, so they have at most as many characters as allowed by
+    /// the specification.
+    ///
+    /// Addresses generated with this pool use the characters of `words`
+    /// instead of ASCII letters and digits.
+    ///
+    /// # Panics
+    ///
+    /// If `words` is empty or has no characters other than whitespace.
+    ///
+    /// # Example
+    /// ```
+    /// # use tpchgen::distribution::Distributions;
+    /// # use tpchgen::generators::CustomerGenerator;
+    /// # use tpchgen::text::TextPool;
+    /// let distributions = Distributions::static_default();
+    /// let text_pool = TextPool::new_extended(1024 * 1024, distributions, TextPool::EXTENDED_WORDS);
+    /// let generator = CustomerGenerator::new_with_distributions_and_text_pool(
+    ///     0.01,
+    ///     1,
+    ///     1,
+    ///     distributions,
+    ///     &text_pool,
+    /// );
+    /// let customer = generator.iter().next().unwrap();
+    /// assert!(!customer.c_comment.is_ascii());
+    /// assert!(!customer.c_address.to_string().is_ascii());
+    /// ```
+    pub fn new_extended<S: AsRef<str>>(
+        size: i32,
+        distributions: &Distributions,
+        words: &[S],
+    ) -> Self {
+        let mut alphabet = Vec::new();
+        for c in words.iter().flat_map(|word| word.as_ref().chars()) {
+            if !c.is_whitespace() && !alphabet.contains(&c) {
+                alphabet.push(c);
+            }
+        }
+        assert!(!alphabet.is_empty(), "no characters in the extended words");
+        let alphabet: Arc<[char]> = alphabet
+            .iter()
+            .copied()
+            .cycle()
+            .take(Self::ALPHABET_SIZE)
+            .collect();
+
+        let mut rng = RowRandomInt::new(933588178, i32::MAX);
+        let mut text_bytes = Vec::with_capacity(size as usize + Self::MAX_SENTENCE_LENGTH as usize);
+        let mut sentence = Vec::with_capacity(Self::MAX_SENTENCE_LENGTH as usize);
+        while text_bytes.len() < size as usize {
+            sentence.clear();
+            Self::generate_sentence(distributions, &mut sentence, &mut rng);
+            Self::replace_words(&sentence, words, &mut text_bytes);
+        }
+        let end = floor_char_boundary(&text_bytes, size as usize);
+        text_bytes.truncate(end);
+
+        Self {
+            ascii: text_bytes.is_ascii(),
+            text: text_bytes,
+            alphabet: Some(alphabet),
+        }
+    }
+
+    /// Appends `sentence` to `output` with each word, a run of ASCII letters,
+    /// replaced by one of `words`
+    fn replace_words<S: AsRef<str>>(sentence: &[u8], words: &[S], output: &mut Vec<u8>) {
+        let mut rest = sentence;
+        while let Some(&first) = rest.first() {
+            let len = rest
+                .iter()
+                .position(|b| b.is_ascii_alphabetic() != first.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            let (token, tail) = rest.split_at(len);
+            if first.is_ascii_alphabetic() {
+                // FNV-1a, stable across platforms and releases
+                let hash = token.iter().fold(0xcbf29ce484222325u64, |hash, &b| {
+                    (hash ^ b as u64).wrapping_mul(0x100000001b3)
+                });
+                let word = words[(hash % words.len() as u64) as usize].as_ref();
+                output.extend_from_slice(word.as_bytes());
+            } else {
+                output.extend_from_slice(token);
+            }
+            rest = tail;
+        }
+    }
+
+    /// Returns the characters of the addresses of an extended text pool,
+    /// see [`Self::new_extended`]
+    pub(crate) fn alphabet(&self) -> Option<Arc<[char]>> {
+        self.alphabet.clone()
     }
 
     /// Returns the text pool size.
@@ -56,16 +222,48 @@ impl TextPool {
         self.text.len() as i32
     }
 
+    /// Returns all the bytes of the text pool.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.text
+    }
+
     /// Returns a chunk of text from the pool
     ///
-    /// Returns the text from the pool between the given begin and end indices.
+    /// Returns the text from the pool between the given begin and end
+    /// indices, without any partial characters at either end if the pool is
+    /// not ASCII, e.g. an extended text pool.
     pub fn text(&self, begin: i32, end: i32) -> &str {
-        // get slice of bytes (note this also does bounds checks)
-        let result: &[u8] = &self.text[begin as usize..end as usize];
-        // Safety: text pool contains only ASCII
+        let result = self.bytes(begin, end);
+        // Safety: the text pool is valid UTF-8 and `bytes` only returns
+        // whole characters
         unsafe { std::str::from_utf8_unchecked(result) }
     }
 
+    /// Returns a chunk of the pool as raw bytes
+    ///
+    /// This is the same as [`Self::text`] but avoids the `&str` conversion, so
+    /// serializers can copy the bytes directly into their output buffers.
+    ///
+    /// # Example
+    /// ```
+    /// # use tpchgen::text::TextPool;
+    /// let text_pool = TextPool::get_or_init_default();
+    /// assert_eq!(text_pool.bytes(0, 10), text_pool.text(0, 10).as_bytes());
+    /// assert_eq!(text_pool.as_bytes().len(), text_pool.size() as usize);
+    /// ```
+    pub fn bytes(&self, begin: i32, end: i32) -> &[u8] {
+        let (mut begin, mut end) = (begin as usize, end as usize);
+        if !self.ascii {
+            end = floor_char_boundary(&self.text, end);
+            begin = begin.min(end);
+            while begin < end && !is_char_boundary(&self.text, begin) {
+                begin += 1;
+            }
+        }
+        // note this also does bounds checks
+        &self.text[begin..end]
+    }
+
     fn generate_sentence(
         distributions: &Distributions,
         output: &mut Vec<u8>,
@@ -154,3 +352,19 @@ impl TextPool {
         }
     }
 }
+
+/// Returns whether `index` is the start of a character of the UTF-8 `text`
+fn is_char_boundary(text: &[u8], index: usize) -> bool {
+    // UTF-8 continuation bytes are 0b10xxxxxx
+    text.get(index).is_none_or(|&b| (b as i8) >= -0x40)
+}
+
+/// Returns the largest index of the start of a character of the UTF-8 `text`
+/// that is at most `index`, like the unstable `str::floor_char_boundary`
+pub(crate) fn floor_char_boundary(text: &[u8], index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !is_char_boundary(text, index) {
+        index -= 1;
+    }
+    index
+}